@@ -1,13 +1,16 @@
-use super::{response::Response, types::*, APIEndpoint};
+#[cfg(feature = "stickers")]
+use super::request::check_sticker_lists;
+use super::{response::Response, types::*, APIEndpoint, BotCapability};
 use crate::{
     model::*,
     utils::{
-        result::{Result, TelegramError},
+        result::{Error, Result, TelegramError},
         FormDataFile,
     },
 };
 use async_trait::async_trait;
-use std::vec::Vec;
+use std::{sync::Arc, vec::Vec};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 /// This trait provides methods for interacting with the telegram API.
 #[async_trait]
@@ -32,6 +35,49 @@ pub trait API: Sync {
         files: Option<Vec<FormDataFile>>,
     ) -> Result<Response>;
 
+    /// The api client this one wraps, if it's a wrapper like an
+    /// [`ApiLayer`]. [`API::download`], [`API::download_to`],
+    /// [`API::get_me_cached`] and [`API::reset_connection`] are forwarded to
+    /// it by default, so wrappers only have to implement the requests they
+    /// alter. Returns `None` by default.
+    ///
+    /// [`ApiLayer`]: super::ApiLayer
+    fn inner(&self) -> Option<&dyn API> {
+        None
+    }
+
+    /// Downloads the file at the given path, as returned by [`API::get_file`],
+    /// from the file storage of telegram. The default implementation asks the
+    /// [`API::inner`] client, or returns an error without one, as only api
+    /// clients talking to telegram can download files.
+    async fn download(&self, file_path: &str) -> Result<Vec<u8>> {
+        if let Some(inner) = self.inner() {
+            return inner.download(file_path).await;
+        }
+        Err(TelegramError::Unknown(format!(
+            "this api client can't download files, like {file_path}"
+        ))
+        .into())
+    }
+
+    /// Downloads the file at the given path like [`API::download`], but
+    /// writes it into the writer as it's received instead of keeping it in
+    /// memory. Returns the amount of bytes written. The default
+    /// implementation asks the [`API::inner`] client, or downloads the whole
+    /// file with [`API::download`] first without one.
+    async fn download_to(
+        &self,
+        file_path: &str,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<u64> {
+        if let Some(inner) = self.inner() {
+            return inner.download_to(file_path, writer).await;
+        }
+        let bytes = self.download(file_path).await?;
+        writer.write_all(&bytes).await?;
+        Ok(bytes.len() as u64)
+    }
+
     /// A simple method for testing your bot's auth token. Requires no
     /// parameters. Returns basic information about the bot in form of a
     /// [`User`] object.
@@ -39,6 +85,42 @@ pub trait API: Sync {
         self.get(APIEndpoint::GetMe, None).await?.into()
     }
 
+    /// Returns the [`User`] of the bot like [`API::get_me`], but allows
+    /// implementations to cache it, as it rarely changes. The default
+    /// implementation asks the [`API::inner`] client, or calls
+    /// [`API::get_me`] without one.
+    async fn get_me_cached(&self) -> Result<User> {
+        if let Some(inner) = self.inner() {
+            return inner.get_me_cached().await;
+        }
+        self.get_me().await
+    }
+
+    /// Drops the connections to telegram, so following requests open new
+    /// ones. Called when a long poll hangs, see [`UpdatesStream::set_watchdog`].
+    /// The default implementation resets the [`API::inner`] client, and does
+    /// nothing without one.
+    ///
+    /// [`UpdatesStream::set_watchdog`]:
+    /// ../client/struct.UpdatesStream.html#method.set_watchdog
+    fn reset_connection(&self) {
+        if let Some(inner) = self.inner() {
+            inner.reset_connection();
+        }
+    }
+
+    /// Checks whether the bot has the given [`BotCapability`], using the
+    /// flags returned by [`API::get_me_cached`]. If it doesn't, an error is
+    /// returned describing which setting in
+    /// [BotFather](https://t.me/botfather) enables it.
+    async fn require_capability(&self, capability: BotCapability) -> Result<()> {
+        if capability.is_supported_by(&self.get_me_cached().await?) {
+            Ok(())
+        } else {
+            Err(TelegramError::MissingCapability(capability).into())
+        }
+    }
+
     /// Use this method to log out from the cloud Bot API server before
     /// launching the bot locally. You **must** log out the bot before
     /// running it locally, otherwise there is no guarantee that
@@ -79,16 +161,28 @@ pub trait API: Sync {
     /// JSON-serialized [Update]. In case of an unsuccessful request,
     /// we will give up after a reasonable amount of attempts. Returns True on
     /// success.
-    async fn set_webhook(&self, data: SetWebhook) -> Result<bool> {
-        self.post(APIEndpoint::SetWebhook, Some(serde_json::to_value(data)?))
-            .await?
-            .into()
+    async fn set_webhook(&self, mut data: SetWebhook) -> Result<bool> {
+        let files = attach_files(data.certificate.iter_mut());
+        if files.is_empty() {
+            return self
+                .post(APIEndpoint::SetWebhook, Some(serde_json::to_value(data)?))
+                .await?
+                .into();
+        }
+
+        self.post_file(
+            APIEndpoint::SetWebhook,
+            Some(serde_json::to_value(&data)?),
+            Some(files),
+        )
+        .await?
+        .into()
     }
 
     /// Use this method to remove webhook integration if you decide to switch
     /// back to using [API::get_updates]. Returns True on success.
     async fn delete_webhook(&self, data: DeleteWebhook) -> Result<bool> {
-        self.get(
+        self.post(
             APIEndpoint::DeleteWebhook,
             Some(serde_json::to_value(data)?),
         )
@@ -111,8 +205,8 @@ pub trait API: Sync {
             .into()
     }
 
-    /// Use this method to change the list of the bot's commands. Returns True
-    /// on success.
+    /// Use this method to change the list of the bot's commands for the given
+    /// scope and user language. Returns True on success.
     async fn set_my_commands(&self, data: SetMyCommands) -> Result<bool> {
         self.post(
             APIEndpoint::SetMyCommands,
@@ -122,10 +216,106 @@ pub trait API: Sync {
         .into()
     }
 
-    /// Use this method to get the current list of the bot's commands. Requires
-    /// no parameters. Returns a `Vec<`[`BotCommand`]`>` on success.
-    async fn get_my_commands(&self) -> Result<Vec<BotCommand>> {
-        self.get(APIEndpoint::GetMyCommands, None).await?.into()
+    /// Use this method to get the current list of the bot's commands for the
+    /// given scope and user language. Returns a `Vec<`[`BotCommand`]`>` on
+    /// success. If commands aren't set, an empty list is returned.
+    async fn get_my_commands(&self, data: GetMyCommands) -> Result<Vec<BotCommand>> {
+        self.get(
+            APIEndpoint::GetMyCommands,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to delete the list of the bot's commands for the given
+    /// scope and user language. After deletion, higher level commands will be
+    /// shown to affected users. Returns True on success.
+    async fn delete_my_commands(&self, data: DeleteMyCommands) -> Result<bool> {
+        self.post(
+            APIEndpoint::DeleteMyCommands,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to change the bot's name. Returns True on success.
+    async fn set_my_name(&self, data: SetMyName) -> Result<bool> {
+        self.post(APIEndpoint::SetMyName, Some(serde_json::to_value(data)?))
+            .await?
+            .into()
+    }
+
+    /// Use this method to get the current bot name for the given user
+    /// language. Returns [`BotName`] on success.
+    async fn get_my_name(&self, data: GetMyName) -> Result<BotName> {
+        self.get(APIEndpoint::GetMyName, Some(serde_json::to_value(data)?))
+            .await?
+            .into()
+    }
+
+    /// Use this method to change the bot's description, which is shown in the
+    /// chat with the bot if the chat is empty. Returns True on success.
+    async fn set_my_description(&self, data: SetMyDescription) -> Result<bool> {
+        self.post(
+            APIEndpoint::SetMyDescription,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to get the current bot description for the given user
+    /// language. Returns [`BotDescription`] on success.
+    async fn get_my_description(&self, data: GetMyDescription) -> Result<BotDescription> {
+        self.get(
+            APIEndpoint::GetMyDescription,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to change the bot's short description, which is shown
+    /// on the bot's profile page and is sent together with the link when
+    /// users share the bot. Returns True on success.
+    async fn set_my_short_description(&self, data: SetMyShortDescription) -> Result<bool> {
+        self.post(
+            APIEndpoint::SetMyShortDescription,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to get the current bot short description for the given
+    /// user language. Returns [`BotShortDescription`] on success.
+    async fn get_my_short_description(
+        &self,
+        data: GetMyShortDescription,
+    ) -> Result<BotShortDescription> {
+        self.get(
+            APIEndpoint::GetMyShortDescription,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to get information about the connection of the bot
+    /// with a business account. Returns a [`BusinessConnection`] object on
+    /// success.
+    async fn get_business_connection(
+        &self,
+        data: GetBusinessConnection,
+    ) -> Result<BusinessConnection> {
+        self.get(
+            APIEndpoint::GetBusinessConnection,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
     }
 
     /// Use this method to forward messages of any kind. On success, the sent
@@ -139,6 +329,21 @@ pub trait API: Sync {
         .into()
     }
 
+    /// Use this method to forward multiple messages of any kind. If some of
+    /// the specified messages can't be found or forwarded, they are skipped.
+    /// Album grouping is kept for forwarded messages. On success, a Vec of
+    /// the [`MessageId`] of the sent messages is returned.
+    async fn forward_messages(&self, data: ForwardMessages) -> Result<Vec<MessageId>> {
+        super::Request::validate(&data)?;
+
+        self.post(
+            APIEndpoint::ForwardMessages,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
     /// Use this method to copy messages of any kind. The method is analogous to
     /// the method [`forward_message`], but the copied message doesn't have
     /// a link to the original message. Returns the [`MessageId`] of the
@@ -151,11 +356,23 @@ pub trait API: Sync {
             .into()
     }
 
+    /// Use this method to copy multiple messages of any kind. If some of the
+    /// specified messages can't be found or copied, they are skipped. Album
+    /// grouping is kept for copied messages. On success, a Vec of the
+    /// [`MessageId`] of the sent messages is returned.
+    async fn copy_messages(&self, data: CopyMessages) -> Result<Vec<MessageId>> {
+        super::Request::validate(&data)?;
+
+        self.post(APIEndpoint::CopyMessages, Some(serde_json::to_value(data)?))
+            .await?
+            .into()
+    }
+
     /// Use this method to send photos. On success, the sent [`Message`] is
     /// returned.
     async fn send_photo(&self, data: SendPhoto) -> Result<Message> {
         match &data.photo {
-            InputFile::String(_) => self
+            InputFile::FileId(_) | InputFile::Url(_) => self
                 .post(APIEndpoint::SendPhoto, Some(serde_json::to_value(&data)?))
                 .await?
                 .into(),
@@ -175,19 +392,11 @@ pub trait API: Sync {
     /// .M4A format. On success, the sent [`Message`] is returned.
     /// Bots can currently send audio files of up to 50 MB in size, this limit
     /// may be changed in the future.
-    async fn send_audio(&self, data: SendAudio) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.audio {
-            files.push(f.clone());
-        }
-        if data.thumb.is_some() {
-            if let InputFile::File(f) = data.thumb.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_audio(&self, mut data: SendAudio) -> Result<Message> {
+        let files = attach_files(std::iter::once(&mut data.audio).chain(data.thumb.as_mut()));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendAudio,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
@@ -198,17 +407,8 @@ pub trait API: Sync {
     /// Use this method to send general files. On success, the sent [`Message`]
     /// is returned. Bots can currently send files of any type of up to 50
     /// MB in size, this limit may be changed in the future.
-    async fn send_document(&self, data: SendDocument) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.document {
-            files.push(f.clone());
-        }
-
-        if data.thumb.is_some() {
-            if let InputFile::File(f) = data.thumb.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_document(&self, mut data: SendDocument) -> Result<Message> {
+        let files = attach_files(std::iter::once(&mut data.document).chain(data.thumb.as_mut()));
 
         self.post_file(
             APIEndpoint::SendDocument,
@@ -223,20 +423,11 @@ pub trait API: Sync {
     /// (other formats may be sent as [`Document`]). On success, the sent
     /// [`Message`] is returned. Bots can currently send video files of up to 50
     /// MB in size, this limit may be changed in the future.
-    async fn send_video(&self, data: SendVideo) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.video {
-            files.push(f.clone());
-        }
-
-        if data.thumb.is_some() {
-            if let InputFile::File(f) = data.thumb.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_video(&self, mut data: SendVideo) -> Result<Message> {
+        let files = attach_files(std::iter::once(&mut data.video).chain(data.thumb.as_mut()));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendVideo,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
@@ -248,20 +439,11 @@ pub trait API: Sync {
     /// without sound). On success, the sent [`Message`] is returned. Bots
     /// can currently send animation files of up to 50 MB in size, this limit
     /// may be changed in the future.
-    async fn send_animation(&self, data: SendAnimation) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.animation {
-            files.push(f.clone());
-        }
-
-        if data.thumb.is_some() {
-            if let InputFile::File(f) = data.thumb.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_animation(&self, mut data: SendAnimation) -> Result<Message> {
+        let files = attach_files(std::iter::once(&mut data.animation).chain(data.thumb.as_mut()));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendAnimation,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
@@ -275,14 +457,11 @@ pub trait API: Sync {
     /// sent as [`Audio`] or [`Document`]). On success, the sent [`Message`]
     /// is returned. Bots can currently send voice messages of up to 50 MB in
     /// size, this limit may be changed in the future.
-    async fn send_voice(&self, data: SendVoice) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.voice {
-            files.push(f.clone());
-        }
+    async fn send_voice(&self, mut data: SendVoice) -> Result<Message> {
+        let files = attach_files(std::iter::once(&mut data.voice));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendVoice,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
@@ -293,20 +472,11 @@ pub trait API: Sync {
     /// As of v.4.0, Telegram clients support rounded square mp4 videos of up to
     /// 1 minute long. Use this method to send video messages. On success,
     /// the sent [`Message`] is returned.
-    async fn send_video_note(&self, data: SendVideoNote) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.video_note {
-            files.push(f.clone());
-        }
-
-        if data.thumb.is_some() {
-            if let InputFile::File(f) = data.thumb.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_video_note(&self, mut data: SendVideoNote) -> Result<Message> {
+        let files = attach_files(std::iter::once(&mut data.video_note).chain(data.thumb.as_mut()));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendVideoNote,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
@@ -316,15 +486,12 @@ pub trait API: Sync {
 
     /// Use this method to send a group of photos or videos as an album.
     /// On success, a [`Vec<Message>`] is returned.
-    async fn send_media_group(&self, data: SendMediaGroup) -> Result<Vec<Message>> {
-        let mut files = Vec::new();
-        for media in &data.media {
-            if let InputFile::File(f) = media.get_media() {
-                files.push(f.clone());
-            }
-        }
-
-        files.dedup_by(|f1, f2| f1 == f2);
+    ///
+    /// Local files and bytes in the media are uploaded along with the payload.
+    /// Files sharing a file name are attached under distinct names, so an
+    /// album can contain e.g. several "image.jpg" from different folders.
+    async fn send_media_group(&self, mut data: SendMediaGroup) -> Result<Vec<Message>> {
+        let files = attach_files(data.media.iter_mut().map(InputMedia::get_media_mut));
 
         self.post_file(
             APIEndpoint::SendMediaGroup,
@@ -335,9 +502,29 @@ pub trait API: Sync {
         .into()
     }
 
+    /// Use this method to send paid media, which users have to pay Telegram
+    /// Stars for to see. On success, the sent [`Message`] is returned.
+    async fn send_paid_media(&self, mut data: SendPaidMedia) -> Result<Message> {
+        let files = attach_files(data.media.iter_mut().map(InputPaidMedia::get_media_mut));
+
+        self.post_file(
+            APIEndpoint::SendPaidMedia,
+            Some(serde_json::to_value(&data)?),
+            Some(files),
+        )
+        .await?
+        .into()
+    }
+
     /// Use this method to send a point on the map. On success, the sent
     /// [`Message`] is returned.
+    ///
+    /// Locations with a horizontal accuracy, heading, proximity alert radius
+    /// or live period outside of the range telegram accepts are rejected
+    /// without calling the API.
     async fn send_location(&self, data: SendLocation) -> Result<Message> {
+        super::Request::validate(&data)?;
+
         self.post(APIEndpoint::SendLocation, Some(serde_json::to_value(data)?))
             .await?
             .into()
@@ -361,7 +548,13 @@ pub trait API: Sync {
 
     /// Use this method to send a native poll. On success, the sent [`Message`]
     /// is returned.
+    ///
+    /// Polls without 2-10 options, quizzes without a valid correct option and
+    /// polls with both an open period and a close date are rejected without
+    /// calling the API.
     async fn send_poll(&self, data: SendPoll) -> Result<Message> {
+        super::Request::validate(&data)?;
+
         self.post(APIEndpoint::SendPoll, Some(serde_json::to_value(data)?))
             .await?
             .into()
@@ -422,7 +615,29 @@ pub trait API: Sync {
     /// uploaded. Use previously uploaded file via its file_id or specify a
     /// URL. On success, if the edited message was sent by the bot, the
     /// edited [`Message`] is returned, otherwise True is returned.
+    ///
+    /// New files in the [`InputMedia`] are uploaded along with the payload.
+    /// Payloads uploading a file for an inline message are rejected without
+    /// calling the API.
     async fn edit_message_media(&self, data: EditMessageMedia) -> Result<TrueOrObject<Message>> {
+        if let InputFile::File(f) = data.media.get_media() {
+            if data.inline_message_id.is_some() {
+                return Err(TelegramError::InvalidArgument(
+                    "new files can't be uploaded when editing inline messages".to_owned(),
+                )
+                .into());
+            }
+            let files = vec![f.clone()];
+            return self
+                .post_file(
+                    APIEndpoint::EditMessageMedia,
+                    Some(serde_json::to_value(&data)?),
+                    Some(files),
+                )
+                .await?
+                .into();
+        }
+
         self.post(
             APIEndpoint::EditMessageMedia,
             Some(serde_json::to_value(data)?),
@@ -478,15 +693,36 @@ pub trait API: Sync {
         .into()
     }
 
+    /// Use this method to delete multiple messages simultaneously. If some of
+    /// the specified messages can't be found, they are skipped. The same
+    /// limitations as for [`delete_message`] apply. Returns True on success.
+    ///
+    /// [`delete_message`]: API::delete_message
+    async fn delete_messages(&self, data: DeleteMessages) -> Result<bool> {
+        super::Request::validate(&data)?;
+
+        self.post(
+            APIEndpoint::DeleteMessages,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
     /// Use this method to edit live location messages.
     /// A location can be edited until its live_period expires or editing is
     /// explicitly disabled by a call to stopMessageLiveLocation.
     /// On success, if the edited message was sent by the bot, the edited
     /// [`Message`] is returned, otherwise True is returned.
+    ///
+    /// Payloads not targeting either a message in a chat or an inline message,
+    /// or with parameters out of range, are rejected without calling the API.
     async fn edit_message_live_location(
         &self,
         data: EditMessageLiveLocation,
     ) -> Result<TrueOrObject<Message>> {
+        super::Request::validate(&data)?;
+
         self.post(
             APIEndpoint::EditMessageLiveLocation,
             Some(serde_json::to_value(data)?),
@@ -502,6 +738,8 @@ pub trait API: Sync {
         &self,
         data: StopMessageLiveLocation,
     ) -> Result<TrueOrObject<Message>> {
+        super::Request::validate(&data)?;
+
         self.post(
             APIEndpoint::StopMessageLiveLocation,
             Some(serde_json::to_value(data)?),
@@ -536,6 +774,45 @@ pub trait API: Sync {
             .into()
     }
 
+    /// Downloads the [`File`], as returned by [`API::get_file`]. When the
+    /// file has no path yet, or the link to it expired, a new one is requested
+    /// with [`API::get_file`] first.
+    async fn download_file(&self, file: &File) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.download_file_to(file, &mut bytes).await?;
+        Ok(bytes)
+    }
+
+    /// Downloads the [`File`] like [`API::download_file`], but writes it into
+    /// the writer as it's received, see [`API::download_to`]. Returns the
+    /// amount of bytes written.
+    async fn download_file_to(
+        &self,
+        file: &File,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<u64> {
+        if let Some(path) = &file.file_path {
+            match self.download_to(path, writer).await {
+                // the link expired, so a new one is requested
+                Err(Error::Telegram(TelegramError::NotFound)) => {},
+                res => return res,
+            }
+        }
+
+        let file = self
+            .get_file(GetFile {
+                file_id: file.file_id.clone(),
+            })
+            .await?;
+        let path = file.file_path.as_deref().ok_or_else(|| {
+            TelegramError::Unknown(format!(
+                "telegram didn't return a path for the file {}",
+                file.file_unique_id
+            ))
+        })?;
+        self.download_to(path, writer).await
+    }
+
     /// Use this method to unban a previously kicked user in a supergroup or
     /// channel. The user will not return to the group or channel
     /// automatically, but will be able to join via link, etc. The bot must
@@ -592,10 +869,15 @@ pub trait API: Sync {
 
     /// Use this method to set a custom title for an administrator in a
     /// supergroup promoted by the bot. Returns True on success.
+    ///
+    /// Titles longer than 16 characters are rejected without calling
+    /// telegram.
     async fn set_chat_administrator_custom_title(
         &self,
         data: SetChatAdministratorCustomTitle,
     ) -> Result<bool> {
+        super::Request::validate(&data)?;
+
         self.post(
             APIEndpoint::SetChatAdministratorCustomTitle,
             Some(serde_json::to_value(data)?),
@@ -819,6 +1101,10 @@ pub trait API: Sync {
 
     /// Use this method to get the number of members in a chat. Returns i64 on
     /// success.
+    ///
+    /// Telegram renamed this method, prefer using [`get_chat_member_count`].
+    ///
+    /// [`get_chat_member_count`]: API::get_chat_member_count
     async fn get_members_count(&self, data: GetChatMembersCount) -> Result<i64> {
         self.get(
             APIEndpoint::GetChatMembersCount,
@@ -828,6 +1114,17 @@ pub trait API: Sync {
         .into()
     }
 
+    /// Use this method to get the number of members in a chat. Returns i64 on
+    /// success.
+    async fn get_chat_member_count(&self, data: GetChatMemberCount) -> Result<i64> {
+        self.get(
+            APIEndpoint::GetChatMemberCount,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
     /// Use this method to get information about a member of a chat. Returns a
     /// [`ChatMember`] object on success.
     async fn get_chat_member(&self, data: GetChatMember) -> Result<ChatMember> {
@@ -881,9 +1178,10 @@ pub trait API: Sync {
 
     /// Use this method to send static .WEBP or animated .TGS stickers. On
     /// success, the sent [Message] is returned.
+    #[cfg(feature = "stickers")]
     async fn send_sticker(&self, data: SendSticker) -> Result<Message> {
         match &data.sticker {
-            InputFile::String(_) => self
+            InputFile::FileId(_) | InputFile::Url(_) => self
                 .post(APIEndpoint::SendSticker, Some(serde_json::to_value(&data)?))
                 .await?
                 .into(),
@@ -900,6 +1198,7 @@ pub trait API: Sync {
 
     /// Use this method to get a sticker set. On success, a [StickerSet] object
     /// is returned.
+    #[cfg(feature = "stickers")]
     async fn get_sticker_set(&self, data: GetStickerSet) -> Result<StickerSet> {
         self.post(
             APIEndpoint::GetStickerSet,
@@ -909,11 +1208,40 @@ pub trait API: Sync {
         .into()
     }
 
-    /// Use this method to upload a .PNG file with a sticker for later use in
-    /// createNewStickerSet and addStickerToSet methods (can be used
+    /// Use this method to get information about custom emoji stickers by their
+    /// identifiers. On success, returns a Vec of [`Sticker`] objects.
+    ///
+    /// Payloads with more than 200 identifiers are rejected without calling
+    /// the API.
+    #[cfg(feature = "stickers")]
+    async fn get_custom_emoji_stickers(
+        &self,
+        data: GetCustomEmojiStickers,
+    ) -> Result<Vec<Sticker>> {
+        if data.custom_emoji_ids.len() > 200 {
+            return Err(TelegramError::InvalidArgument(
+                "no more than 200 custom emoji ids can be requested at once".to_owned(),
+            )
+            .into());
+        }
+
+        self.post(
+            APIEndpoint::GetCustomEmojiStickers,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to upload a file with a sticker for later use in the
+    /// createNewStickerSet and addStickerToSet methods (the file can be used
     /// multiple times). Returns the uploaded [File] on success.
+    ///
+    /// Payloads with a `file_id` or url instead of a file to upload are
+    /// rejected without calling the API.
+    #[cfg(feature = "stickers")]
     async fn upload_sticker_file(&self, data: UploadStickerFile) -> Result<File> {
-        match &data.png_sticker {
+        match &data.sticker {
             InputFile::File(f) => self
                 .post_file(
                     APIEndpoint::UploadStickerFile,
@@ -931,37 +1259,21 @@ pub trait API: Sync {
 
     /// Use this method to create a new sticker set owned by a user.
     /// The bot will be able to edit the sticker set thus created.
-    /// You must use exactly one of the fields png_sticker or tgs_sticker.
     /// Returns True on success.
+    ///
+    /// Payloads without 1-50 stickers, with stickers having an invalid emoji
+    /// or keyword list, or uploading several files with the same name are
+    /// rejected without calling the API.
+    #[cfg(feature = "stickers")]
     async fn create_new_sticker_set(&self, data: CreateNewStickerSet) -> Result<bool> {
-        if (data.png_sticker.is_some() && data.tgs_sticker.is_some())
-            || (data.png_sticker.is_none() && data.tgs_sticker.is_none())
-        {
-            return Err(TelegramError::InvalidArgument(
-                "You must use exactly one of the fields png_sticker or tgs_sticker".to_owned(),
-            )
+        if data.stickers.is_empty() || data.stickers.len() > 50 {
+            return Err(TelegramError::InvalidArgument(format!(
+                "a sticker set needs 1-50 initial stickers, got {}",
+                data.stickers.len()
+            ))
             .into());
         }
-
-        let mut files = Vec::new();
-
-        if data.png_sticker.is_some() {
-            if let InputFile::File(f) = data.png_sticker.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
-
-        if data.tgs_sticker.is_some() {
-            match data.tgs_sticker.as_ref().unwrap() {
-                InputFile::File(f) => files.push(f.clone()),
-                _ => {
-                    return Err(TelegramError::InvalidArgument(
-                        "tgs_sticker only accepts files, not urls/ids".to_owned(),
-                    )
-                    .into())
-                },
-            }
-        }
+        let files = sticker_files(&data.stickers)?;
 
         self.post_file(
             APIEndpoint::CreateNewStickerSet,
@@ -973,39 +1285,14 @@ pub trait API: Sync {
     }
 
     /// Use this method to add a new sticker to a set created by the bot.
-    /// You must use exactly one of the fields png_sticker or tgs_sticker.
-    /// Animated stickers can be added to animated sticker sets and only to
-    /// them. Animated sticker sets can have up to 50 stickers. Static
-    /// sticker sets can have up to 120 stickers. Returns True on success.
+    /// Emoji sticker sets can have up to 200 stickers. Other sticker sets can
+    /// have up to 120 stickers. Returns True on success.
+    ///
+    /// Stickers with an invalid emoji or keyword list are rejected without
+    /// calling the API.
+    #[cfg(feature = "stickers")]
     async fn add_sticker_to_set(&self, data: AddStickerToSet) -> Result<bool> {
-        if (data.png_sticker.is_some() && data.tgs_sticker.is_some())
-            || (data.png_sticker.is_none() && data.tgs_sticker.is_none())
-        {
-            return Err(TelegramError::InvalidArgument(
-                "You must use exactly one of the fields png_sticker or tgs_sticker.".to_owned(),
-            )
-            .into());
-        }
-
-        let mut files = Vec::new();
-
-        if data.png_sticker.is_some() {
-            if let InputFile::File(f) = data.png_sticker.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
-
-        if data.tgs_sticker.is_some() {
-            match data.tgs_sticker.as_ref().unwrap() {
-                InputFile::File(f) => files.push(f.clone()),
-                _ => {
-                    return Err(TelegramError::InvalidArgument(
-                        "tgs_sticker only accepts files, not urls/ids.".to_owned(),
-                    )
-                    .into())
-                },
-            }
-        }
+        let files = sticker_files(std::slice::from_ref(&data.sticker))?;
 
         self.post_file(
             APIEndpoint::AddStickerToSet,
@@ -1018,6 +1305,7 @@ pub trait API: Sync {
 
     /// Use this method to move a sticker in a set created by the bot to a
     /// specific position. Returns True on success.
+    #[cfg(feature = "stickers")]
     async fn set_sticker_position_in_set(&self, data: SetStickerPositionInSet) -> Result<bool> {
         self.post(
             APIEndpoint::SetStickerPositionInSet,
@@ -1029,6 +1317,7 @@ pub trait API: Sync {
 
     /// Use this method to delete a sticker from a set created by the bot.
     /// Returns True on success.
+    #[cfg(feature = "stickers")]
     async fn delete_sticker_from_set(&self, data: DeleteStickerFromSet) -> Result<bool> {
         self.post(
             APIEndpoint::DeleteStickerFromSet,
@@ -1038,21 +1327,82 @@ pub trait API: Sync {
         .into()
     }
 
-    /// Use this method to set the thumbnail of a sticker set.
-    /// Animated thumbnails can be set for animated sticker sets only. Returns
-    /// True on success.
-    async fn set_sticker_set_thumb(&self, data: SetStickerSetThumb) -> Result<bool> {
-        match &data.thumb {
-            Some(InputFile::String(_)) | None => self
+    /// Use this method to change the list of emoji assigned to a regular or
+    /// custom emoji sticker. The sticker must belong to a sticker set created
+    /// by the bot. Returns True on success.
+    ///
+    /// Payloads without 1-20 emoji are rejected without calling the API.
+    #[cfg(feature = "stickers")]
+    async fn set_sticker_emoji_list(&self, data: SetStickerEmojiList) -> Result<bool> {
+        super::Request::validate(&data)?;
+
+        self.post(
+            APIEndpoint::SetStickerEmojiList,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to change search keywords assigned to a regular or
+    /// custom emoji sticker. The sticker must belong to a sticker set created
+    /// by the bot. Returns True on success.
+    ///
+    /// Payloads with more than 20 keywords, or keywords longer than 64
+    /// characters in total, are rejected without calling the API.
+    #[cfg(feature = "stickers")]
+    async fn set_sticker_keywords(&self, data: SetStickerKeywords) -> Result<bool> {
+        super::Request::validate(&data)?;
+
+        self.post(
+            APIEndpoint::SetStickerKeywords,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to change the mask position of a mask sticker. The
+    /// sticker must belong to a sticker set that was created by the bot.
+    /// Returns True on success.
+    #[cfg(feature = "stickers")]
+    async fn set_sticker_mask_position(&self, data: SetStickerMaskPosition) -> Result<bool> {
+        self.post(
+            APIEndpoint::SetStickerMaskPosition,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to set the title of a created sticker set. Returns True
+    /// on success.
+    #[cfg(feature = "stickers")]
+    async fn set_sticker_set_title(&self, data: SetStickerSetTitle) -> Result<bool> {
+        self.post(
+            APIEndpoint::SetStickerSetTitle,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to set the thumbnail of a regular or mask sticker set.
+    /// The format of the thumbnail file must match the format of the stickers
+    /// in the set. Returns True on success.
+    #[cfg(feature = "stickers")]
+    async fn set_sticker_set_thumbnail(&self, data: SetStickerSetThumbnail) -> Result<bool> {
+        match &data.thumbnail {
+            Some(InputFile::FileId(_) | InputFile::Url(_)) | None => self
                 .post(
-                    APIEndpoint::SetStickerSetThumb,
+                    APIEndpoint::SetStickerSetThumbnail,
                     Some(serde_json::to_value(&data)?),
                 )
                 .await?
                 .into(),
             Some(InputFile::File(f)) => self
                 .post_file(
-                    APIEndpoint::SetStickerSetThumb,
+                    APIEndpoint::SetStickerSetThumbnail,
                     Some(serde_json::to_value(&data)?),
                     Some(vec![f.clone()]),
                 )
@@ -1061,6 +1411,33 @@ pub trait API: Sync {
         }
     }
 
+    /// Use this method to set the thumbnail of a custom emoji sticker set.
+    /// Returns True on success.
+    #[cfg(feature = "stickers")]
+    async fn set_custom_emoji_sticker_set_thumbnail(
+        &self,
+        data: SetCustomEmojiStickerSetThumbnail,
+    ) -> Result<bool> {
+        self.post(
+            APIEndpoint::SetCustomEmojiStickerSetThumbnail,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Use this method to delete a sticker set that was created by the bot.
+    /// Returns True on success.
+    #[cfg(feature = "stickers")]
+    async fn delete_sticker_set(&self, data: DeleteStickerSet) -> Result<bool> {
+        self.post(
+            APIEndpoint::DeleteStickerSet,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
     /// Use this method to send answers to an inline query. On success, True is
     /// returned. No more than 50 results per query are allowed.
     async fn answer_inline_query(&self, data: AnswerInlineQuery) -> Result<bool> {
@@ -1081,7 +1458,14 @@ pub trait API: Sync {
 
     /// Use this method to send invoices. On success, the sent [Message] is
     /// returned.
+    ///
+    /// Invoices suggesting more than 4 tips, tips which aren't strictly
+    /// increasing or tips exceeding the `max_tip_amount` are rejected without
+    /// calling the API.
+    #[cfg(feature = "payments")]
     async fn send_invoice(&self, data: SendInvoice) -> Result<Message> {
+        super::Request::validate(&data)?;
+
         self.post(APIEndpoint::SendInvoice, Some(serde_json::to_value(data)?))
             .await?
             .into()
@@ -1091,7 +1475,14 @@ pub trait API: Sync {
     /// is_flexible was specified, the Bot API will send an [Update] with a
     /// shipping_query field to the bot. Use this method to reply to
     /// shipping queries. On success, True is returned.
+    ///
+    /// Answers accepting the address without any shipping options, or
+    /// rejecting it without an error message, are rejected without calling
+    /// telegram.
+    #[cfg(feature = "payments")]
     async fn answer_shipping_query(&self, data: AnswerShippingQuery) -> Result<bool> {
+        super::Request::validate(&data)?;
+
         self.post(
             APIEndpoint::AnswerShippingQuery,
             Some(serde_json::to_value(data)?),
@@ -1106,7 +1497,13 @@ pub trait API: Sync {
     /// pre-checkout queries. On success, True is returned.
     /// **Note:** The Bot API must receive an answer within 10 seconds after the
     /// pre-checkout query was sent.
+    ///
+    /// Answers cancelling the order without an error message are rejected
+    /// without calling telegram.
+    #[cfg(feature = "payments")]
     async fn answer_pre_checkout_query(&self, data: AnswerPreCheckoutQuery) -> Result<bool> {
+        super::Request::validate(&data)?;
+
         self.post(
             APIEndpoint::AnswerPreCheckoutQuery,
             Some(serde_json::to_value(data)?),
@@ -1115,8 +1512,33 @@ pub trait API: Sync {
         .into()
     }
 
+    /// Refunds a successful payment in Telegram Stars. Returns True on
+    /// success.
+    #[cfg(feature = "payments")]
+    async fn refund_star_payment(&self, data: RefundStarPayment) -> Result<bool> {
+        self.post(
+            APIEndpoint::RefundStarPayment,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
+    /// Returns the bot's Telegram Star transactions in chronological order. On
+    /// success, returns a [`StarTransactions`] object.
+    #[cfg(feature = "payments")]
+    async fn get_star_transactions(&self, data: GetStarTransactions) -> Result<StarTransactions> {
+        self.get(
+            APIEndpoint::GetStarTransactions,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into()
+    }
+
     /// Use this method to send a game. On success, the sent [Message] is
     /// returned.
+    #[cfg(feature = "games")]
     async fn send_game(&self, data: SendGame) -> Result<Message> {
         self.post(APIEndpoint::SendGame, Some(serde_json::to_value(data)?))
             .await?
@@ -1128,7 +1550,13 @@ pub trait API: Sync {
     /// Message, otherwise returns True. Returns an error, if the new score
     /// is not greater than the user's current score in the chat and force is
     /// False.
+    ///
+    /// Payloads identifying neither or both a chat message and an inline
+    /// message are rejected without calling the API.
+    #[cfg(feature = "games")]
     async fn set_game_score(&self, data: SetGameScore) -> Result<TrueOrObject<Message>> {
+        super::Request::validate(&data)?;
+
         self.post(APIEndpoint::SetGameScore, Some(serde_json::to_value(data)?))
             .await?
             .into()
@@ -1137,7 +1565,13 @@ pub trait API: Sync {
     /// Use this method to get data for high score tables. Will return the score
     /// of the specified user and several of his neighbors in a game.
     /// On success, returns a Vec of [GameHighScore] objects.
+    ///
+    /// Payloads identifying neither or both a chat message and an inline
+    /// message are rejected without calling the API.
+    #[cfg(feature = "games")]
     async fn get_game_high_scores(&self, data: GetGameHighScores) -> Result<Vec<GameHighScore>> {
+        super::Request::validate(&data)?;
+
         self.post(
             APIEndpoint::GetGameHighScores,
             Some(serde_json::to_value(data)?),
@@ -1157,7 +1591,14 @@ pub trait API: Sync {
     /// date seems invalid, a submitted document is blurry, a scan shows
     /// evidence of tampering, etc. Supply some details in the error message
     /// to make sure the user knows how to correct the issues.
+    ///
+    /// Payloads without errors, or with errors for a section their source
+    /// doesn't apply to (see [`PassportElementError::is_valid`]), are rejected
+    /// without calling the API.
+    #[cfg(feature = "passport")]
     async fn set_passport_data_errors(&self, data: SetPassportDataErrors) -> Result<bool> {
+        super::Request::validate(&data)?;
+
         self.post(
             APIEndpoint::SetPassportDataErrors,
             Some(serde_json::to_value(data)?),
@@ -1166,3 +1607,83 @@ pub trait API: Sync {
         .into()
     }
 }
+
+/// Forwards to the shared api client, so it can be wrapped, for example in a
+/// [`DryRun`](super::DryRun)
+#[async_trait]
+impl API for Arc<Box<dyn API + Send>> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&***self)
+    }
+
+    async fn get(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        (***self).get(endpoint, data).await
+    }
+
+    async fn post(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        (***self).post(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        (***self).post_file(endpoint, data, files).await
+    }
+}
+
+/// Collects the files uploaded by an album, giving every file a distinct name
+/// to be attached by. The same file used twice is only uploaded once.
+fn attach_files<'a, I>(media: I) -> Vec<FormDataFile>
+where
+    I: Iterator<Item = &'a mut InputFile>,
+{
+    let mut files: Vec<FormDataFile> = Vec::new();
+    for input in media {
+        if let InputFile::File(f) = input {
+            let mut n = 1;
+            while files
+                .iter()
+                .any(|other| other.name == f.name && other != &*f)
+            {
+                f.name = format!("{n}_{}", f.file_name.as_deref().unwrap_or("file"));
+                n += 1;
+            }
+            if !files.contains(f) {
+                files.push(f.clone());
+            }
+        }
+    }
+    files
+}
+
+/// Checks the stickers and collects the files they upload, which are attached
+/// by their file name
+#[cfg(feature = "stickers")]
+fn sticker_files(stickers: &[InputSticker]) -> Result<Vec<FormDataFile>> {
+    let mut files: Vec<FormDataFile> = Vec::new();
+    for sticker in stickers {
+        check_sticker_lists(&sticker.emoji_list, sticker.keywords.as_deref())?;
+        if let InputFile::File(f) = &sticker.sticker {
+            if files.iter().any(|other| other.name == f.name) {
+                return Err(TelegramError::InvalidArgument(format!(
+                    "the uploaded stickers need different file names, {} is used twice",
+                    f.name
+                ))
+                .into());
+            }
+            files.push(f.clone());
+        }
+    }
+    Ok(files)
+}
@@ -1,16 +1,48 @@
-use super::{api::API, endpoints::APIEndpoint, response::Response};
-use crate::utils::{
-    encode_multipart_form_data,
-    result::Result,
-    AsFormData,
-    FormDataFile,
-    BOUNDARY,
+use super::{
+    api::API,
+    endpoints::APIEndpoint,
+    proxy::{Proxy, ProxyConnector},
+    response::Response,
+    tls::HttpsConnector,
+};
+use crate::{
+    model::User,
+    utils::{
+        encode_multipart_form_data,
+        result::{Error, Result, TelegramError},
+        stream_multipart_form_data,
+        AsFormData,
+        FormDataFile,
+        BOUNDARY,
+    },
 };
 use async_trait::async_trait;
-use hyper::{body::HttpBody, client::HttpConnector, Body, Client, Request};
-use std::io::Write;
+use futures::future::{select, Either};
+use hyper::{
+    body::HttpBody,
+    client::HttpConnector,
+    header::{HeaderName, HeaderValue},
+    http::request::Builder,
+    Body,
+    Client,
+    Request,
+    StatusCode,
+};
+use parking_lot::RwLock;
+use std::{
+    io::ErrorKind,
+    path::Path,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::OnceCell,
+};
 
-static TELEGRAM_API: &str = "https://api.telegram.org/bot";
+static TELEGRAM_API: &str = "https://api.telegram.org";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
 /// A default implementation of the [`API`] trait.
 ///
@@ -35,27 +67,53 @@ static TELEGRAM_API: &str = "https://api.telegram.org/bot";
 /// In most cases you would want to get updates though and the [`Client`] is
 /// best suited for that, as it allows for easier handling of those updates
 ///
+/// Requests which take too long fail with a timeout error instead of hanging:
+/// connecting gives up after 10 seconds and a request after 30 seconds, with
+/// file uploads and downloads getting 5 minutes. `getUpdates` additionally
+/// gets the long polling timeout it asks telegram to wait for.
+///
 /// [`Client`]: ../client/struct.Client.html
 pub struct APIClient {
-    hyper_client: Client<hyper_tls::HttpsConnector<HttpConnector>>,
+    hyper_client: Client<HttpsConnector<HttpConnector>>,
+    custom_hyper_client: bool,
+    // the default hyper client recreated by `reset_connection`, used instead
+    // of `hyper_client` once set
+    fresh_client: RwLock<Option<Client<HttpsConnector<HttpConnector>>>>,
+    proxy: Option<Proxy>,
+    proxied_client: RwLock<Option<Client<HttpsConnector<ProxyConnector>>>>,
     token: String,
+    base_url: String,
+    local_mode: bool,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    upload_timeout: Duration,
+    me: OnceCell<User>,
 }
 
 impl APIClient {
     /// Creates a new `APIClient` with the provided token and hyper client (if
     /// it is Some).
     pub fn new<T: ToString>(
-        hyper_client: Option<Client<hyper_tls::HttpsConnector<HttpConnector>>>,
+        hyper_client: Option<Client<HttpsConnector<HttpConnector>>>,
         token: T,
     ) -> Self {
         hyper_client.map_or_else(
-            || Self {
-                hyper_client: hyper::Client::builder().build(hyper_tls::HttpsConnector::new()),
-                token: token.to_string(),
-            },
+            || Self::new_default(token.to_string()),
             |c| Self {
                 hyper_client: c,
+                custom_hyper_client: true,
+                fresh_client: RwLock::new(None),
+                proxy: None,
+                proxied_client: RwLock::new(None),
                 token: token.to_string(),
+                base_url: TELEGRAM_API.to_owned(),
+                local_mode: false,
+                headers: Vec::new(),
+                connect_timeout: CONNECT_TIMEOUT,
+                request_timeout: REQUEST_TIMEOUT,
+                upload_timeout: UPLOAD_TIMEOUT,
+                me: OnceCell::new(),
             },
         )
     }
@@ -64,13 +122,178 @@ impl APIClient {
     /// client.
     pub fn new_default<T: ToString>(token: T) -> Self {
         Self {
-            hyper_client: hyper::Client::builder().build(hyper_tls::HttpsConnector::new()),
+            hyper_client: default_hyper_client(CONNECT_TIMEOUT),
+            custom_hyper_client: false,
+            fresh_client: RwLock::new(None),
+            proxy: None,
+            proxied_client: RwLock::new(None),
             token: token.to_string(),
+            base_url: TELEGRAM_API.to_owned(),
+            local_mode: false,
+            headers: Vec::new(),
+            connect_timeout: CONNECT_TIMEOUT,
+            request_timeout: REQUEST_TIMEOUT,
+            upload_timeout: UPLOAD_TIMEOUT,
+            me: OnceCell::new(),
+        }
+    }
+
+    /// Sets the url of the bot API server the requests are sent to, like
+    /// `http://localhost:8081` for a [local bot API server]. Defaults to
+    /// `https://api.telegram.org`.
+    ///
+    /// [local bot API server]: https://github.com/tdlib/telegram-bot-api
+    pub fn set_base_url(&mut self, url: &str) -> &mut Self {
+        url.trim_end_matches('/').clone_into(&mut self.base_url);
+        self
+    }
+
+    /// Sets whether the bot API server runs on the same machine with
+    /// `--local`, defaults to false. Such a server returns absolute paths for
+    /// the files it stores, which are then read from the disk by
+    /// [`API::download`] instead of being downloaded.
+    ///
+    /// Only enable it for a server you run yourself, as any absolute path it
+    /// returns is read.
+    pub fn set_local_mode(&mut self, local: bool) -> &mut Self {
+        self.local_mode = local;
+        self
+    }
+
+    /// Routes the requests through the proxy, instead of the hyper client
+    pub fn set_proxy(&mut self, proxy: Proxy) -> &mut Self {
+        *self.proxied_client.get_mut() =
+            Some(proxied_hyper_client(proxy.clone(), self.connect_timeout));
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets how long connecting to telegram, or the proxy, may take. Defaults
+    /// to 10 seconds. A custom hyper client keeps its own connect timeout.
+    pub fn set_connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = timeout;
+        if let Some(proxy) = &self.proxy {
+            *self.proxied_client.get_mut() = Some(proxied_hyper_client(proxy.clone(), timeout));
+        }
+        if !self.custom_hyper_client {
+            self.hyper_client = default_hyper_client(timeout);
+            *self.fresh_client.get_mut() = None;
+        }
+        self
+    }
+
+    /// Sets how long a request may take until its response is read, defaults
+    /// to 30 seconds. Long polling `getUpdates` requests get the polling
+    /// timeout on top of this.
+    pub fn set_request_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Sets how long uploading or downloading a file may take, defaults to 5
+    /// minutes. Files streamed from the disk or a reader may take longer, as
+    /// long as none of their chunks takes longer than this to send.
+    pub fn set_upload_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.upload_timeout = timeout;
+        self
+    }
+
+    /// how long the request to the endpoint may take
+    fn timeout_for(&self, endpoint: &APIEndpoint, data: Option<&serde_json::Value>) -> Duration {
+        // long polling keeps the request open for up to the polling timeout
+        let polling = match endpoint {
+            APIEndpoint::GetUpdates => data
+                .and_then(|d| d.get("timeout"))
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or(0),
+            _ => 0,
+        };
+        self.request_timeout + Duration::from_secs(polling)
+    }
+
+    /// sends the request and reads its response, failing with a timeout error
+    /// if that takes longer than the timeout
+    async fn fetch(
+        &self,
+        request: Request<Body>,
+        timeout: Duration,
+    ) -> Result<(StatusCode, Vec<u8>)> {
+        match tokio::time::timeout(timeout, self.send(request)).await {
+            Ok(res) => res,
+            Err(_) => Err(TelegramError::Timeout(timeout).into()),
+        }
+    }
+
+    /// sends the request and reads its response
+    async fn send(&self, request: Request<Body>) -> Result<(StatusCode, Vec<u8>)> {
+        let mut response = self.respond(request).await?;
+
+        let mut res: Vec<u8> = Vec::new();
+        while let Some(chunk) = response.body_mut().data().await {
+            res.extend_from_slice(&chunk?);
+        }
+        Ok((response.status(), res))
+    }
+
+    /// sends the request through the proxy if one is set, or the hyper client
+    async fn respond(&self, request: Request<Body>) -> Result<hyper::Response<Body>> {
+        let proxied = self.proxied_client.read().clone();
+        if let Some(client) = proxied {
+            return Ok(client.request(request).await?);
+        }
+        let fresh = self.fresh_client.read().clone();
+        Ok(match fresh {
+            Some(client) => client.request(request).await?,
+            None => self.hyper_client.request(request).await?,
+        })
+    }
+
+    /// sends the request for a file and writes the file into the writer as
+    /// it's received
+    async fn stream_file(
+        &self,
+        request: Request<Body>,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<u64> {
+        let mut response = self.respond(request).await?;
+        match response.status() {
+            StatusCode::OK => {},
+            StatusCode::NOT_FOUND => return Err(TelegramError::NotFound.into()),
+            status => {
+                return Err(TelegramError::APIResponseError(format!(
+                    "downloading the file failed with status {status}"
+                ))
+                .into())
+            },
         }
+
+        let mut written = 0;
+        while let Some(chunk) = response.body_mut().data().await {
+            let chunk = chunk?;
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+        Ok(written)
+    }
+
+    /// Adds a header sent with every request, for example to authenticate
+    /// with a proxy in front of the telegram API
+    pub fn add_header(&mut self, name: HeaderName, value: HeaderValue) -> &mut Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    /// adds the custom headers to the request
+    fn with_headers(&self, mut builder: Builder) -> Builder {
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder
     }
 
     fn parse_endpoint(&self, endpoint: &APIEndpoint) -> String {
-        format!("{}{}/{}", TELEGRAM_API, self.token, endpoint)
+        format!("{}/bot{}/{}", self.base_url, self.token, endpoint)
     }
 
     /// Sends a request to the provided `APIEndpoint` with the data provided
@@ -91,13 +314,30 @@ impl APIClient {
         }
     }
 
-    /// gets a reference to the underlying hyper client, for example so you can
-    /// make custom api requests
-    pub fn get_hyper(&self) -> &Client<hyper_tls::HttpsConnector<HttpConnector>> {
+    /// gets the underlying hyper client, for example so you can make custom
+    /// api requests
+    pub fn get_hyper(&self) -> &Client<HttpsConnector<HttpConnector>> {
         &self.hyper_client
     }
 }
 
+fn default_hyper_client(connect_timeout: Duration) -> Client<HttpsConnector<HttpConnector>> {
+    let mut http = HttpConnector::new();
+    http.set_connect_timeout(Some(connect_timeout));
+    http.enforce_http(false);
+    hyper::Client::builder().build(HttpsConnector::with_connector(http))
+}
+
+fn proxied_hyper_client(
+    proxy: Proxy,
+    connect_timeout: Duration,
+) -> Client<HttpsConnector<ProxyConnector>> {
+    hyper::Client::builder().build(HttpsConnector::with_connector(ProxyConnector::new(
+        proxy,
+        connect_timeout,
+    )))
+}
+
 #[async_trait]
 impl API for APIClient {
     async fn get(
@@ -105,10 +345,12 @@ impl API for APIClient {
         endpoint: APIEndpoint,
         data: Option<serde_json::Value>,
     ) -> Result<Response> {
-        let req_builder = Request::get(self.parse_endpoint(&endpoint))
+        let req_builder = self
+            .with_headers(Request::get(self.parse_endpoint(&endpoint)))
             .header("content-type", "application/json")
             .header("accept", "application/json");
 
+        let timeout = self.timeout_for(&endpoint, data.as_ref());
         let request = if let Some(d) = data {
             req_builder.body(Body::from(serde_json::to_string(&d)?))?
         } else {
@@ -116,12 +358,7 @@ impl API for APIClient {
         };
 
         log::debug!("GET request to {}", &endpoint);
-        let mut response = self.hyper_client.request(request).await?;
-
-        let mut res: Vec<u8> = Vec::new();
-        while let Some(chunk) = response.body_mut().data().await {
-            res.write_all(&chunk?)?
-        }
+        let (_, res) = self.fetch(request, timeout).await?;
 
         Ok(serde_json::from_slice(&res)?)
     }
@@ -131,10 +368,12 @@ impl API for APIClient {
         endpoint: APIEndpoint,
         data: Option<serde_json::Value>,
     ) -> Result<Response> {
-        let req_builder = Request::post(self.parse_endpoint(&endpoint))
+        let req_builder = self
+            .with_headers(Request::post(self.parse_endpoint(&endpoint)))
             .header("content-type", "application/json")
             .header("accept", "application/json");
 
+        let timeout = self.timeout_for(&endpoint, data.as_ref());
         let request = if let Some(d) = data {
             req_builder.body(Body::from(serde_json::to_string(&d)?))?
         } else {
@@ -142,12 +381,7 @@ impl API for APIClient {
         };
 
         log::debug!("POST request to {}", &endpoint);
-        let mut response = self.hyper_client.request(request).await?;
-
-        let mut res: Vec<u8> = Vec::new();
-        while let Some(chunk) = response.body_mut().data().await {
-            res.write_all(&chunk?)?
-        }
+        let (_, res) = self.fetch(request, timeout).await?;
 
         Ok(serde_json::from_slice(&res)?)
     }
@@ -167,7 +401,8 @@ impl API for APIClient {
             return self.post(endpoint, data).await;
         }
 
-        let req_builder = Request::post(self.parse_endpoint(&endpoint))
+        let req_builder = self
+            .with_headers(Request::post(self.parse_endpoint(&endpoint)))
             .header(
                 "content-type",
                 format!("multipart/form-data; boundary={}", BOUNDARY),
@@ -178,17 +413,98 @@ impl API for APIClient {
             files.append(&mut data.expect("no data").as_form_data()?)
         }
 
-        let bytes = encode_multipart_form_data(&files)?;
-        let request = req_builder.body(Body::from(bytes))?;
-
-        log::debug!("POST request with files to {}", &endpoint);
-        let mut response = self.hyper_client.request(request).await?;
+        if !files.iter().any(FormDataFile::is_streamed) {
+            let bytes = encode_multipart_form_data(&files)?;
+            let request = req_builder.body(Body::from(bytes))?;
 
-        let mut res: Vec<u8> = Vec::new();
-        while let Some(chunk) = response.body_mut().data().await {
-            res.write_all(&chunk?)?
+            log::debug!("POST request with files to {}", &endpoint);
+            let (_, res) = self.fetch(request, self.upload_timeout).await?;
+            return Ok(serde_json::from_slice(&res)?);
         }
 
+        // the files on disk and streams are sent in chunks as they're read,
+        // so the upload timeout applies to every chunk instead of the whole
+        // upload, and to waiting for the response once they're all sent
+        let (sender, body) = Body::channel();
+        let request = req_builder.body(body)?;
+
+        log::debug!("POST request streaming files to {}", &endpoint);
+        let timeout = self.upload_timeout;
+        let response = self.send(request);
+        let streaming = stream_multipart_form_data(&files, sender, timeout);
+        futures::pin_mut!(response, streaming);
+        let res = match select(response, streaming).await {
+            Either::Left((res, _)) => res,
+            Either::Right((streamed, response)) => {
+                // a file which couldn't be read aborted the request, while the
+                // body failing to send leaves the error to the response
+                match streamed {
+                    Err(Error::Hyper(_)) | Ok(()) => (),
+                    Err(e) => return Err(e),
+                }
+                match tokio::time::timeout(timeout, response).await {
+                    Ok(res) => res,
+                    Err(_) => Err(TelegramError::Timeout(timeout).into()),
+                }
+            },
+        };
+        let (_, res) = res?;
+
         Ok(serde_json::from_slice(&res)?)
     }
+
+    async fn download(&self, file_path: &str) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.download_to(file_path, &mut bytes).await?;
+        Ok(bytes)
+    }
+
+    async fn download_to(
+        &self,
+        file_path: &str,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<u64> {
+        // a local bot api server gives the path the file is stored at
+        if self.local_mode && Path::new(file_path).is_absolute() {
+            log::debug!("reading a file stored by the local bot api server");
+            let mut file = match tokio::fs::File::open(file_path).await {
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    return Err(TelegramError::NotFound.into())
+                },
+                res => res?,
+            };
+            return Ok(tokio::io::copy(&mut file, writer).await?);
+        }
+
+        let url = format!("{}/file/bot{}/{}", self.base_url, self.token, file_path);
+        let request = self.with_headers(Request::get(url)).body(Body::empty())?;
+
+        log::debug!("GET request to download a file");
+        let timeout = self.upload_timeout;
+        match tokio::time::timeout(timeout, self.stream_file(request, writer)).await {
+            Ok(res) => res,
+            Err(_) => Err(TelegramError::Timeout(timeout).into()),
+        }
+    }
+
+    /// Replaces the hyper client with a new one, so the following requests
+    /// don't reuse any of the pooled connections. A custom hyper client can't
+    /// be recreated, so it's kept and only the hung requests are dropped.
+    fn reset_connection(&self) {
+        if let Some(proxy) = &self.proxy {
+            *self.proxied_client.write() =
+                Some(proxied_hyper_client(proxy.clone(), self.connect_timeout));
+        } else if self.custom_hyper_client {
+            log::warn!("can't recreate a custom hyper client, keeping its connections");
+        } else {
+            *self.fresh_client.write() = Some(default_hyper_client(self.connect_timeout));
+        }
+    }
+
+    async fn get_me_cached(&self) -> Result<User> {
+        self.me
+            .get_or_try_init(|| self.get_me())
+            .await
+            .cloned()
+    }
 }
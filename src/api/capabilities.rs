@@ -0,0 +1,59 @@
+use crate::model::User;
+
+/// A capability of the bot which has to be enabled through
+/// [BotFather](https://t.me/botfather) before it can be used.
+///
+/// Use [`API::require_capability`] to check for a capability before using the
+/// features depending on it, to get an error explaining which setting to
+/// change instead of telegram failing in less obvious ways later on.
+///
+/// [`API::require_capability`]: trait.API.html#method.require_capability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BotCapability {
+    /// Receiving and answering inline queries
+    InlineQueries,
+    /// Being added to groups
+    JoinGroups,
+    /// Receiving all messages in groups, instead of only commands and replies
+    ReadAllGroupMessages,
+    /// Being connected to Telegram Business accounts
+    Business,
+}
+
+impl BotCapability {
+    /// Returns whether the bot described by the given [`User`], as returned by
+    /// [`get_me`], has this capability
+    ///
+    /// [`get_me`]: trait.API.html#method.get_me
+    pub fn is_supported_by(self, me: &User) -> bool {
+        let flag = match self {
+            Self::InlineQueries => me.supports_inline_queries,
+            Self::JoinGroups => me.can_join_groups,
+            Self::ReadAllGroupMessages => me.can_read_all_group_messages,
+            Self::Business => me.can_connect_to_business,
+        };
+        flag.unwrap_or(false)
+    }
+
+    /// Returns a description of the setting in
+    /// [BotFather](https://t.me/botfather) which enables this capability
+    pub fn botfather_hint(self) -> &'static str {
+        match self {
+            Self::InlineQueries => {
+                "inline mode is disabled, enable it by sending /setinline to @BotFather"
+            },
+            Self::JoinGroups => {
+                "the bot can't be added to groups, allow it by sending /setjoingroups to \
+                 @BotFather"
+            },
+            Self::ReadAllGroupMessages => {
+                "privacy mode is enabled, so the bot only receives commands and replies in \
+                 groups; disable it by sending /setprivacy to @BotFather"
+            },
+            Self::Business => {
+                "business mode is disabled, enable it in the bot settings of @BotFather \
+                 under \"Business Mode\""
+            },
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use super::{api::API, endpoints::APIEndpoint, response::Response};
+use crate::utils::{
+    result::{Error, Result, TelegramError},
+    FormDataFile,
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::AsyncWrite,
+    sync::{broadcast, watch},
+};
+
+/// The state of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Telegram is reachable and all requests are sent
+    Closed,
+    /// Telegram is deemed unreachable and requests are rejected without
+    /// being sent
+    Open,
+    /// A single probe request is allowed through to check whether telegram
+    /// is reachable again
+    HalfOpen,
+}
+
+/// What a [`CircuitBreaker`] does with requests while the circuit is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitMode {
+    /// Requests fail immediately with a `CircuitOpen` error
+    Reject,
+    /// Requests wait for the circuit to close for at most the given duration,
+    /// failing with a `CircuitOpen` error if it doesn't
+    Queue(Duration),
+}
+
+struct Inner {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+}
+
+/// the right to send the probe request of a half-open circuit, given back
+/// when dropped, even if the request was cancelled
+struct ProbeSlot<'a> {
+    probe: &'a AtomicBool,
+    changes: &'a watch::Sender<CircuitState>,
+}
+
+impl Drop for ProbeSlot<'_> {
+    fn drop(&mut self) {
+        self.probe.store(false, Ordering::Release);
+        // wakes up queued requests, which may now send the next probe
+        self.changes.send_modify(|_| {});
+    }
+}
+
+/// An [`API`] implementation wrapping another one, which stops sending requests
+/// after telegram was unreachable several times in a row.
+///
+/// After `failure_threshold` consecutive connection failures the circuit
+/// opens, and every request fails immediately with a `CircuitOpen` error
+/// instead of waiting on a connection that will likely time out. Once the
+/// `open_duration` has passed a single probe request is let through: if it
+/// succeeds the circuit closes again, otherwise it stays open for another
+/// `open_duration`.
+///
+/// Only transport errors (failing to connect or read the response) count as
+/// failures, errors returned by telegram itself mean it is reachable.
+///
+/// With [`CircuitMode::Queue`] requests made while the circuit is open wait for
+/// it to close instead of failing right away, for at most the given duration.
+///
+/// ```no_run
+/// use telexide::{api::{APIClient, CircuitBreaker}, Client};
+///
+/// # let token = "test token";
+/// let api = CircuitBreaker::new(APIClient::new_default(token))
+///     .set_failure_threshold(3);
+/// let client = Client::builder()
+///     .set_api_client(std::sync::Arc::new(Box::new(api)))
+///     .build();
+/// ```
+pub struct CircuitBreaker<A> {
+    api: A,
+    failure_threshold: u32,
+    open_duration: Duration,
+    mode: CircuitMode,
+    inner: Mutex<Inner>,
+    probe: AtomicBool,
+    changes: watch::Sender<CircuitState>,
+    events: broadcast::Sender<CircuitState>,
+}
+
+impl<A: API + Send> CircuitBreaker<A> {
+    /// Wraps the given api client, opening the circuit after 5 consecutive
+    /// failures for 30 seconds at a time and rejecting requests while it is
+    /// open
+    pub fn new(api: A) -> Self {
+        let (events, _) = broadcast::channel(16);
+        let (changes, _) = watch::channel(CircuitState::Closed);
+
+        Self {
+            api,
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+            mode: CircuitMode::Reject,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+            }),
+            probe: AtomicBool::new(false),
+            changes,
+            events,
+        }
+    }
+
+    /// Sets the amount of consecutive failures after which the circuit opens
+    #[must_use]
+    pub fn set_failure_threshold(mut self, threshold: u32) -> Self {
+        self.failure_threshold = threshold.max(1);
+        self
+    }
+
+    /// Sets how long the circuit stays open before probing telegram again
+    #[must_use]
+    pub fn set_open_duration(mut self, duration: Duration) -> Self {
+        self.open_duration = duration;
+        self
+    }
+
+    /// Sets what happens to requests while the circuit is open
+    #[must_use]
+    pub fn set_mode(mut self, mode: CircuitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The current state of the circuit
+    pub fn state(&self) -> CircuitState {
+        self.inner.lock().state
+    }
+
+    /// Returns a receiver which gets every new [`CircuitState`] the circuit
+    /// transitions to
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitState> {
+        self.events.subscribe()
+    }
+
+    /// Gets a reference to the wrapped api client
+    pub fn get_api(&self) -> &A {
+        &self.api
+    }
+
+    fn transition(&self, inner: &mut Inner, state: CircuitState) {
+        if inner.state == state {
+            return;
+        }
+
+        log::info!(
+            "circuit breaker changed from {:?} to {:?}",
+            inner.state,
+            state
+        );
+        inner.state = state;
+        self.changes.send_replace(state);
+        // an error only means nobody is listening
+        let _ = self.events.send(state);
+    }
+
+    /// waits until a request may be sent, returning the probe slot if the
+    /// request is the probe of a half-open circuit
+    async fn before_request(&self) -> Result<Option<ProbeSlot<'_>>> {
+        let mut changes = self.changes.subscribe();
+        let deadline = Instant::now()
+            + match self.mode {
+                CircuitMode::Reject => Duration::ZERO,
+                CircuitMode::Queue(max_wait) => max_wait,
+            };
+
+        loop {
+            let retry_in = match self.try_acquire() {
+                Ok(probe) => return Ok(probe),
+                Err(retry_in) => retry_in,
+            };
+
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Err(TelegramError::CircuitOpen(retry_in).into());
+            }
+
+            // while another request is probing only a change can let this one
+            // through, otherwise it may probe once the open duration is over
+            let wait = if retry_in.is_zero() {
+                left
+            } else {
+                left.min(retry_in)
+            };
+            let _ = tokio::time::timeout(wait, changes.changed()).await;
+        }
+    }
+
+    /// checks whether a request may be sent right away, letting a single
+    /// probe request through once the circuit has been open for long enough.
+    /// Fails with the time left until the next probe.
+    fn try_acquire(&self) -> std::result::Result<Option<ProbeSlot<'_>>, Duration> {
+        let mut inner = self.inner.lock();
+        if inner.state == CircuitState::Closed {
+            return Ok(None);
+        }
+
+        let elapsed = inner.opened_at.elapsed();
+        if elapsed < self.open_duration {
+            return Err(self.open_duration.saturating_sub(elapsed));
+        }
+        if self
+            .probe
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(Duration::ZERO);
+        }
+
+        self.transition(&mut inner, CircuitState::HalfOpen);
+        Ok(Some(ProbeSlot {
+            probe: &self.probe,
+            changes: &self.changes,
+        }))
+    }
+
+    /// records the result of a request. Once the circuit isn't closed only the
+    /// probe decides its state, requests sent before it opened are ignored.
+    fn after_request<T>(&self, res: &Result<T>, probe: bool) {
+        let mut inner = self.inner.lock();
+        if inner.state != CircuitState::Closed && !probe {
+            return;
+        }
+
+        if matches!(
+            res,
+            Err(Error::Hyper(_) | Error::IO(_) | Error::Telegram(TelegramError::Timeout(_)))
+        ) {
+            inner.failures += 1;
+            if probe || inner.failures >= self.failure_threshold {
+                inner.opened_at = Instant::now();
+                self.transition(&mut inner, CircuitState::Open);
+            }
+        } else {
+            inner.failures = 0;
+            self.transition(&mut inner, CircuitState::Closed);
+        }
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for CircuitBreaker<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let probe = self.before_request().await?;
+        let res = self.api.get(endpoint, data).await;
+        self.after_request(&res, probe.is_some());
+        res
+    }
+
+    async fn post(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let probe = self.before_request().await?;
+        let res = self.api.post(endpoint, data).await;
+        self.after_request(&res, probe.is_some());
+        res
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        let probe = self.before_request().await?;
+        let res = self.api.post_file(endpoint, data, files).await;
+        self.after_request(&res, probe.is_some());
+        res
+    }
+
+    async fn download(&self, file_path: &str) -> Result<Vec<u8>> {
+        let probe = self.before_request().await?;
+        let res = self.api.download(file_path).await;
+        self.after_request(&res, probe.is_some());
+        res
+    }
+
+    async fn download_to(
+        &self,
+        file_path: &str,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<u64> {
+        let probe = self.before_request().await?;
+        let res = self.api.download_to(file_path, writer).await;
+        self.after_request(&res, probe.is_some());
+        res
+    }
+}
@@ -0,0 +1,211 @@
+use super::{api::API, endpoints::APIEndpoint, response::Response, ApiSchema};
+use crate::{
+    redaction,
+    utils::{result::Result, FormDataFile},
+};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicI64, Ordering},
+};
+
+/// An [`API`] implementation wrapping another one, which doesn't send any calls
+/// changing something on telegram, but logs them and answers them with a
+/// synthesized response instead.
+///
+/// Methods starting with "get", like getMe and getUpdates, are still sent, so
+/// a bot in dry-run mode receives the real traffic while its replies never
+/// reach the chats. setWebhook and deleteWebhook are sent as well, so the bot
+/// can keep receiving updates with a webhook; use [`allow`] to let more methods
+/// through.
+///
+/// Before being answered every call is checked against the bundled
+/// [`ApiSchema`], logging a warning for fields it doesn't know about.
+///
+/// The responses echo the payload: sent and edited messages contain its chat
+/// and text, stopped polls its question and copied messages get new ids.
+/// Anything else is answered with `true`.
+///
+/// The client can be put in dry-run mode with [`ClientBuilder::set_dry_run`],
+/// or by wrapping an api client yourself:
+///
+/// ```no_run
+/// use telexide::{api::{APIClient, DryRun}, Client};
+///
+/// # let token = "test token";
+/// let api = DryRun::new(APIClient::new_default(token)).allow("answerCallbackQuery");
+/// let client = Client::builder()
+///     .set_api_client(std::sync::Arc::new(Box::new(api)))
+///     .build();
+/// ```
+///
+/// [`allow`]: DryRun::allow
+/// [`ClientBuilder::set_dry_run`]: crate::client::ClientBuilder::set_dry_run
+pub struct DryRun<A> {
+    api: A,
+    allowed: HashSet<String>,
+    next_message_id: AtomicI64,
+}
+
+impl<A: API + Send> DryRun<A> {
+    /// Wraps the given api client, only letting reads and the webhook methods
+    /// through
+    pub fn new(api: A) -> Self {
+        Self {
+            api,
+            allowed: ["setWebhook", "setWebHook", "deleteWebhook"]
+                .iter()
+                .map(|m| (*m).to_owned())
+                .collect(),
+            next_message_id: AtomicI64::new(1),
+        }
+    }
+
+    /// Lets calls to the given method, e.g. "answerCallbackQuery", through to
+    /// telegram
+    #[must_use]
+    pub fn allow(mut self, method: &str) -> Self {
+        self.allowed.insert(method.to_owned());
+        self
+    }
+
+    /// Gets a reference to the wrapped api client
+    pub fn get_api(&self) -> &A {
+        &self.api
+    }
+
+    /// Returns whether calls to the method are sent to telegram
+    pub fn is_sent(&self, method: &str) -> bool {
+        method.starts_with("get") || self.allowed.contains(method)
+    }
+
+    fn simulate(
+        &self,
+        endpoint: &APIEndpoint,
+        data: Option<Value>,
+        files: &[FormDataFile],
+    ) -> Response {
+        let method = endpoint.as_str();
+        let data = data.unwrap_or(Value::Null);
+
+        let schema = ApiSchema::bundled();
+        if schema.has_method(method) {
+            for mismatch in schema.check_value(method, &data) {
+                log::warn!("dry run: {mismatch}");
+            }
+        }
+        log::info!(
+            "dry run: not sending {method} with {} file(s): {}",
+            files.len(),
+            redaction::scrub(&data)
+        );
+
+        let message_id = self.next_message_id.fetch_add(1, Ordering::Relaxed);
+        Response {
+            ok: true,
+            description: None,
+            result: Some(synthesized_result(method, &data, message_id)),
+            error_code: None,
+            parameters: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for DryRun<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        if self.is_sent(endpoint.as_str()) {
+            return self.api.get(endpoint, data).await;
+        }
+        Ok(self.simulate(&endpoint, data, &[]))
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        if self.is_sent(endpoint.as_str()) {
+            return self.api.post(endpoint, data).await;
+        }
+        Ok(self.simulate(&endpoint, data, &[]))
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        if self.is_sent(endpoint.as_str()) {
+            return self.api.post_file(endpoint, data, files).await;
+        }
+        Ok(self.simulate(&endpoint, data, &files.unwrap_or_default()))
+    }
+}
+
+/// The result telegram would plausibly respond to the call with, echoing the
+/// chat and text of the payload
+pub(crate) fn synthesized_result(method: &str, data: &Value, message_id: i64) -> Value {
+    let chat_id = data["chat_id"].as_i64().unwrap_or_default();
+    let chat = if chat_id < 0 {
+        json!({"id": chat_id, "type": "supergroup", "title": "test chat"})
+    } else {
+        json!({"id": chat_id, "type": "private", "first_name": "test"})
+    };
+    let mut message = json!({
+        "message_id": data["message_id"].as_i64().unwrap_or(message_id),
+        "date": chrono::Utc::now().timestamp(),
+        "chat": chat,
+        "text": data["text"].as_str().or_else(|| data["caption"].as_str()).unwrap_or_default(),
+        "has_protected_content": data["protect_content"].as_bool().unwrap_or_default(),
+    });
+
+    match method {
+        "copyMessage" => json!({"message_id": message_id}),
+        "copyMessages" | "forwardMessages" => {
+            let count = data["message_ids"].as_array().map_or(0, Vec::len);
+            (message_id..)
+                .take(count)
+                .map(|id| json!({"message_id": id}))
+                .collect()
+        },
+        "sendMediaGroup" => json!([message]),
+        "sendPoll" | "stopPoll" => {
+            let poll = synthesized_poll(data, message_id, method == "stopPoll");
+            if method == "stopPoll" {
+                return poll;
+            }
+            message["poll"] = poll;
+            message
+        },
+        m if m.starts_with("edit") && !data["inline_message_id"].is_null() => json!(true),
+        m if m.starts_with("send") || m.starts_with("edit") || m == "forwardMessage" => {
+            if !m.starts_with("edit") {
+                message["message_id"] = json!(message_id);
+            }
+            message
+        },
+        _ => json!(true),
+    }
+}
+
+fn synthesized_poll(data: &Value, id: i64, is_closed: bool) -> Value {
+    let options: Vec<Value> = data["options"]
+        .as_array()
+        .map(|o| {
+            o.iter()
+                .map(|text| json!({"text": text, "voter_count": 0}))
+                .collect()
+        })
+        .unwrap_or_default();
+    json!({
+        "id": id.to_string(),
+        "question": data["question"].as_str().unwrap_or_default(),
+        "options": options,
+        "total_voter_count": 0,
+        "is_closed": is_closed,
+        "type": data["type"].as_str().unwrap_or("regular"),
+    })
+}
@@ -2,6 +2,7 @@
 ///
 /// It is mostly used for letting the get and post methods in the API trait know
 /// how to form the endpoint path
+#[derive(Clone)]
 pub enum APIEndpoint {
     GetUpdates,
     GetMe,
@@ -10,8 +11,18 @@ pub enum APIEndpoint {
     SendMessage,
     SetMyCommands,
     GetMyCommands,
+    DeleteMyCommands,
+    SetMyName,
+    GetMyName,
+    SetMyDescription,
+    GetMyDescription,
+    SetMyShortDescription,
+    GetMyShortDescription,
+    GetBusinessConnection,
     ForwardMessage,
+    ForwardMessages,
     CopyMessage,
+    CopyMessages,
     SendPhoto,
     SendAudio,
     SendDocument,
@@ -20,6 +31,7 @@ pub enum APIEndpoint {
     SendVoice,
     SendVideoNote,
     SendMediaGroup,
+    SendPaidMedia,
     SendLocation,
     EditMessageLiveLocation,
     StopMessageLiveLocation,
@@ -51,6 +63,7 @@ pub enum APIEndpoint {
     GetChat,
     GetChatAdministrators,
     GetChatMembersCount,
+    GetChatMemberCount,
     GetChatMember,
     SetChatStickerSet,
     DeleteChatStickerSet,
@@ -61,18 +74,28 @@ pub enum APIEndpoint {
     EditMessageReplyMarkup,
     StopPoll,
     DeleteMessage,
+    DeleteMessages,
     SendSticker,
     GetStickerSet,
+    GetCustomEmojiStickers,
     UploadStickerFile,
     CreateNewStickerSet,
     AddStickerToSet,
     SetStickerPositionInSet,
     DeleteStickerFromSet,
-    SetStickerSetThumb,
+    SetStickerEmojiList,
+    SetStickerKeywords,
+    SetStickerMaskPosition,
+    SetStickerSetTitle,
+    SetStickerSetThumbnail,
+    SetCustomEmojiStickerSetThumbnail,
+    DeleteStickerSet,
     AnswerInlineQuery,
     SendInvoice,
     AnswerShippingQuery,
     AnswerPreCheckoutQuery,
+    RefundStarPayment,
+    GetStarTransactions,
     SendGame,
     SetGameScore,
     GetGameHighScores,
@@ -84,6 +107,7 @@ pub enum APIEndpoint {
 }
 
 impl APIEndpoint {
+    #[allow(clippy::too_many_lines)]
     pub fn as_str(&self) -> &str {
         match *self {
             Self::GetUpdates => "getUpdates",
@@ -93,8 +117,18 @@ impl APIEndpoint {
             Self::SendMessage => "sendMessage",
             Self::SetMyCommands => "setMyCommands",
             Self::GetMyCommands => "getMyCommands",
+            Self::DeleteMyCommands => "deleteMyCommands",
+            Self::SetMyName => "setMyName",
+            Self::GetMyName => "getMyName",
+            Self::SetMyDescription => "setMyDescription",
+            Self::GetMyDescription => "getMyDescription",
+            Self::SetMyShortDescription => "setMyShortDescription",
+            Self::GetMyShortDescription => "getMyShortDescription",
+            Self::GetBusinessConnection => "getBusinessConnection",
             Self::CopyMessage => "copyMessage",
+            Self::CopyMessages => "copyMessages",
             Self::ForwardMessage => "forwardMessage",
+            Self::ForwardMessages => "forwardMessages",
             Self::SendPhoto => "sendPhoto",
             Self::SendAudio => "sendAudio",
             Self::SendDocument => "sendDocument",
@@ -103,6 +137,7 @@ impl APIEndpoint {
             Self::SendVoice => "sendVoice",
             Self::SendVideoNote => "sendVideoNote",
             Self::SendMediaGroup => "sendMediaGroup",
+            Self::SendPaidMedia => "sendPaidMedia",
             Self::SendLocation => "sendLocation",
             Self::EditMessageLiveLocation => "editMessageLiveLocation",
             Self::StopMessageLiveLocation => "stopMessageLiveLocation",
@@ -134,6 +169,7 @@ impl APIEndpoint {
             Self::GetChat => "getChat",
             Self::GetChatAdministrators => "getChatAdministrators",
             Self::GetChatMembersCount => "getChatMembersCount",
+            Self::GetChatMemberCount => "getChatMemberCount",
             Self::GetChatMember => "getChatMember",
             Self::SetChatStickerSet => "setChatStickerSet",
             Self::DeleteChatStickerSet => "deleteChatStickerSet",
@@ -144,14 +180,22 @@ impl APIEndpoint {
             Self::EditMessageReplyMarkup => "editMessageReplyMarkup",
             Self::StopPoll => "stopPoll",
             Self::DeleteMessage => "deleteMessage",
+            Self::DeleteMessages => "deleteMessages",
             Self::SendSticker => "sendSticker",
             Self::GetStickerSet => "getStickerSet",
+            Self::GetCustomEmojiStickers => "getCustomEmojiStickers",
             Self::UploadStickerFile => "uploadStickerFile",
             Self::CreateNewStickerSet => "createNewStickerSet",
             Self::AddStickerToSet => "addStickerToSet",
             Self::SetStickerPositionInSet => "setStickerPositionInSet",
             Self::DeleteStickerFromSet => "deleteStickerFromSet",
-            Self::SetStickerSetThumb => "setStickerSetThumb",
+            Self::SetStickerEmojiList => "setStickerEmojiList",
+            Self::SetStickerKeywords => "setStickerKeywords",
+            Self::SetStickerMaskPosition => "setStickerMaskPosition",
+            Self::SetStickerSetTitle => "setStickerSetTitle",
+            Self::SetStickerSetThumbnail => "setStickerSetThumbnail",
+            Self::SetCustomEmojiStickerSetThumbnail => "setCustomEmojiStickerSetThumbnail",
+            Self::DeleteStickerSet => "deleteStickerSet",
             Self::AnswerInlineQuery => "answerInlineQuery",
             Self::SendGame => "sendGame",
             Self::SetGameScore => "setGameScore",
@@ -159,6 +203,8 @@ impl APIEndpoint {
             Self::SendInvoice => "sendInvoice",
             Self::AnswerShippingQuery => "answerShippingQuery",
             Self::AnswerPreCheckoutQuery => "answerPreCheckoutQuery",
+            Self::RefundStarPayment => "refundStarPayment",
+            Self::GetStarTransactions => "getStarTransactions",
             Self::SetWebhook => "setWebHook",
             Self::SetPassportDataErrors => "setPassportDataErrors",
             Self::DeleteWebhook => "deleteWebhook",
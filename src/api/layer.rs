@@ -0,0 +1,93 @@
+use super::api::API;
+use std::sync::Arc;
+
+/// Wraps the [`API`] client of a [`Client`] with another [`API`]
+/// implementation, seeing every outgoing request before it's sent. Add it with
+/// [`ClientBuilder::add_api_layer`] to log, retry or alter requests, or to
+/// answer them from a test double.
+///
+/// The layers wrap the api client the bot was built with, before the client
+/// adds its own bookkeeping, so they see the requests the way they're sent to
+/// telegram. Like tower's layers, the first one added is the outermost, seeing
+/// the requests first and the responses last.
+///
+/// Any function taking the inner api client and returning an [`API`] wrapping
+/// it is a layer. Returning the inner client from [`API::inner`] forwards
+/// [`API::download`], [`API::download_to`], [`API::get_me_cached`] and
+/// [`API::reset_connection`] to it, so the wrapper only has to implement the
+/// requests:
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use telexide::{
+///     api::{APIEndpoint, FormDataFile, Response, API},
+///     prelude::*,
+///     Result,
+/// };
+///
+/// struct RequestLogger(Arc<Box<dyn API + Send>>);
+///
+/// impl RequestLogger {
+///     fn new(inner: Arc<Box<dyn API + Send>>) -> Self {
+///         Self(inner)
+///     }
+/// }
+///
+/// #[async_trait::async_trait]
+/// impl API for RequestLogger {
+///     fn inner(&self) -> Option<&dyn API> {
+///         Some(&**self.0)
+///     }
+///
+///     async fn get(
+///         &self,
+///         endpoint: APIEndpoint,
+///         data: Option<serde_json::Value>,
+///     ) -> Result<Response> {
+///         log::info!("calling {endpoint}");
+///         self.0.get(endpoint, data).await
+///     }
+///
+///     async fn post(
+///         &self,
+///         endpoint: APIEndpoint,
+///         data: Option<serde_json::Value>,
+///     ) -> Result<Response> {
+///         log::info!("calling {endpoint}");
+///         self.0.post(endpoint, data).await
+///     }
+///
+///     async fn post_file(
+///         &self,
+///         endpoint: APIEndpoint,
+///         data: Option<serde_json::Value>,
+///         files: Option<Vec<FormDataFile>>,
+///     ) -> Result<Response> {
+///         log::info!("calling {endpoint} with files");
+///         self.0.post_file(endpoint, data, files).await
+///     }
+/// }
+///
+/// # let token = "test token";
+/// let client = ClientBuilder::new()
+///     .set_token(token)
+///     .add_api_layer(RequestLogger::new)
+///     .build();
+/// ```
+///
+/// [`Client`]: ../client/struct.Client.html
+/// [`ClientBuilder::add_api_layer`]: ../client/struct.ClientBuilder.html#method.add_api_layer
+pub trait ApiLayer: Send + Sync + 'static {
+    /// Returns the api client wrapping the inner one
+    fn layer(&self, inner: Arc<Box<dyn API + Send>>) -> Box<dyn API + Send>;
+}
+
+impl<F, A> ApiLayer for F
+where
+    F: Fn(Arc<Box<dyn API + Send>>) -> A + Send + Sync + 'static,
+    A: API + Send + 'static,
+{
+    fn layer(&self, inner: Arc<Box<dyn API + Send>>) -> Box<dyn API + Send> {
+        Box::new(self(inner))
+    }
+}
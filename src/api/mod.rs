@@ -6,11 +6,35 @@
 
 mod api;
 mod api_client;
+mod capabilities;
+mod circuit_breaker;
+mod dry_run;
 mod endpoints;
+mod layer;
+mod proxy;
+mod request;
 mod response;
+mod retry;
+mod schema;
+mod throttle;
+mod tls;
 pub mod types;
 
 pub use api::API;
 pub use api_client::APIClient;
+pub use capabilities::BotCapability;
+pub use circuit_breaker::{CircuitBreaker, CircuitMode, CircuitState};
+pub use dry_run::DryRun;
+pub(crate) use dry_run::synthesized_result;
 pub use endpoints::APIEndpoint;
-pub use response::Response;
+pub use layer::ApiLayer;
+pub use proxy::Proxy;
+pub use request::{methods, APIExt, MethodInfo, Request};
+pub use response::{Response, TelegramResponse};
+pub use retry::FloodRetry;
+pub use schema::{ApiSchema, SchemaMismatch};
+pub(crate) use schema::log_self_check;
+pub use throttle::Throttled;
+pub use tls::{HttpsConnector, HttpsStream};
+
+pub use crate::utils::{FileStream, FormDataContents, FormDataFile};
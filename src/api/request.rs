@@ -0,0 +1,668 @@
+use super::{api::API, endpoints::APIEndpoint, response::TelegramResponse, types::*};
+use crate::{
+    model::*,
+    utils::result::{Result, TelegramError},
+};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::convert::TryFrom;
+
+/// A payload that can be sent to the telegram API, pairing it with the method
+/// it is meant for and the type telegram responds with.
+///
+/// All payloads in [`api::types`] which don't upload files implement this
+/// trait, so they can be sent with [`APIExt::send`]. Implement it for your own
+/// structs to call methods this library doesn't have a payload for yet:
+///
+/// ```no_run
+/// use serde::Serialize;
+/// use telexide::api::{APIClient, APIExt, Request};
+///
+/// #[derive(Serialize)]
+/// struct GetChatMenuButton {
+///     chat_id: i64,
+/// }
+///
+/// impl Request for GetChatMenuButton {
+///     const METHOD: &'static str = "getChatMenuButton";
+///     type Response = serde_json::Value;
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> telexide::Result<()> {
+/// let client = APIClient::new_default("test token");
+/// let button = client.send(GetChatMenuButton { chat_id: 3 }).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`api::types`]: types/index.html
+pub trait Request: Serialize + Send {
+    /// The name of the telegram API method, for example "sendMessage"
+    const METHOD: &'static str;
+    /// The type telegram returns on success
+    type Response: DeserializeOwned;
+
+    /// Checks the payload before it's sent, rejecting payloads telegram is
+    /// known to refuse without calling it. Accepts everything by default.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Extension methods for every [`API`] implementation
+#[async_trait]
+pub trait APIExt: API {
+    /// Sends the given [`Request`] to its method, returning the response of
+    /// the type the request is paired with. Methods starting with "get" are
+    /// sent as a get request, all others as a post request. Payloads failing
+    /// [`Request::validate`] are rejected without calling the API.
+    async fn send<R: Request + 'static>(&self, request: R) -> Result<R::Response> {
+        request.validate()?;
+        let endpoint = APIEndpoint::from(R::METHOD.to_owned());
+        let data = Some(serde_json::to_value(request)?);
+
+        if R::METHOD.starts_with("get") {
+            self.get(endpoint, data).await?.into()
+        } else {
+            self.post(endpoint, data).await?.into()
+        }
+    }
+
+    /// Sends the given [`Request`] like [`send`], but returns the full
+    /// [`TelegramResponse`] envelope instead of only the result. An
+    /// unsuccessful response is returned as an `Ok` as well.
+    ///
+    /// [`send`]: APIExt::send
+    async fn send_raw<R: Request + 'static>(
+        &self,
+        request: R,
+    ) -> Result<TelegramResponse<R::Response>> {
+        request.validate()?;
+        let endpoint = APIEndpoint::from(R::METHOD.to_owned());
+        let data = Some(serde_json::to_value(request)?);
+
+        let res = if R::METHOD.starts_with("get") {
+            self.get(endpoint, data).await?
+        } else {
+            self.post(endpoint, data).await?
+        };
+        TelegramResponse::try_from(res)
+    }
+
+    /// Calls the telegram API method with the given name and parameters,
+    /// returning its result as json. Use it to call methods this library
+    /// doesn't support yet, without defining a [`Request`] for them. The
+    /// method is always called with a post request, and the parameters have to
+    /// be a json object, or `Value::Null` to send none.
+    ///
+    /// ```no_run
+    /// use serde_json::json;
+    /// use telexide::api::{APIClient, APIExt};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> telexide::Result<()> {
+    /// let client = APIClient::new_default("test token");
+    /// let button = client
+    ///     .call_raw("getChatMenuButton", json!({"chat_id": 3}))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn call_raw(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let data = match params {
+            serde_json::Value::Null => None,
+            serde_json::Value::Object(_) => Some(params),
+            _ => {
+                return Err(TelegramError::InvalidArgument(format!(
+                    "the parameters of {method} have to be a json object"
+                ))
+                .into())
+            },
+        };
+
+        self.post(APIEndpoint::from(method.to_owned()), data)
+            .await?
+            .into()
+    }
+}
+
+impl<T: API + ?Sized> APIExt for T {}
+
+/// A method of the telegram API implemented by telexide, as listed by
+/// [`methods`]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethodInfo {
+    /// The name of the telegram API method, for example "sendMessage"
+    pub method: &'static str,
+    /// The name of the payload struct in [`api::types`], `None` for methods
+    /// without parameters
+    ///
+    /// [`api::types`]: types/index.html
+    pub payload: Option<&'static str>,
+    /// The rust type telegram returns on success, for example "Message" or
+    /// "Vec<Update>"
+    pub response: &'static str,
+    /// Whether the payload can upload files. These payloads don't implement
+    /// [`Request`] and are sent with the method of the [`API`] trait instead.
+    pub uploads_files: bool,
+}
+
+impl MethodInfo {
+    /// The name of the method of the [`API`] trait calling this method, for
+    /// example `send_message`
+    pub fn function(&self) -> String {
+        let mut function = String::with_capacity(self.method.len() + 4);
+        for c in self.method.chars() {
+            if c.is_ascii_uppercase() {
+                function.push('_');
+            }
+            function.push(c.to_ascii_lowercase());
+        }
+        function
+    }
+}
+
+/// Every telegram API method telexide implements with the enabled features,
+/// together with its payload and response types, for generating code against
+/// telexide.
+///
+/// ```
+/// let send_message = telexide::api::methods()
+///     .into_iter()
+///     .find(|m| m.method == "sendMessage")
+///     .unwrap();
+/// assert_eq!(send_message.payload, Some("SendMessage"));
+/// assert_eq!(send_message.response, "Message");
+/// assert_eq!(send_message.function(), "send_message");
+/// ```
+pub fn methods() -> Vec<MethodInfo> {
+    let tables: &[&[MethodInfo]] = &[
+        WITHOUT_PAYLOAD,
+        REQUESTS,
+        UPLOADS,
+        #[cfg(feature = "stickers")]
+        STICKER_REQUESTS,
+        #[cfg(feature = "stickers")]
+        STICKER_UPLOADS,
+        #[cfg(feature = "payments")]
+        PAYMENT_REQUESTS,
+        #[cfg(feature = "games")]
+        GAME_REQUESTS,
+        #[cfg(feature = "passport")]
+        PASSPORT_REQUESTS,
+    ];
+    let mut methods: Vec<MethodInfo> = tables.concat();
+    methods.sort_by_key(|m| m.method);
+    methods
+}
+
+const WITHOUT_PAYLOAD: &[MethodInfo] = &[
+    MethodInfo {
+        method: "getMe",
+        payload: None,
+        response: "User",
+        uploads_files: false,
+    },
+    MethodInfo {
+        method: "logOut",
+        payload: None,
+        response: "bool",
+        uploads_files: false,
+    },
+    MethodInfo {
+        method: "close",
+        payload: None,
+        response: "bool",
+        uploads_files: false,
+    },
+];
+
+/// implements [`Request`] for the payloads, listing them in a table of
+/// [`MethodInfo`]s with the given name. A payload can be followed by the
+/// function validating it.
+macro_rules! impl_request {
+    ($table:ident; $($payload:ty => $method:literal, $response:ty $(, $validate:path)?;)*) => {
+        $(
+            impl Request for $payload {
+                const METHOD: &'static str = $method;
+                type Response = $response;
+
+                $(
+                    fn validate(&self) -> Result<()> {
+                        $validate(self)
+                    }
+                )?
+            }
+        )*
+
+        const $table: &[MethodInfo] = &[$(
+            MethodInfo {
+                method: $method,
+                payload: Some(stringify!($payload)),
+                response: stringify!($response),
+                uploads_files: false,
+            },
+        )*];
+    };
+}
+
+/// lists payloads which can upload files in a table of [`MethodInfo`]s with
+/// the given name
+macro_rules! upload_methods {
+    ($table:ident; $($payload:ty => $method:literal, $response:ty;)*) => {
+        const $table: &[MethodInfo] = &[$(
+            MethodInfo {
+                method: $method,
+                payload: Some(stringify!($payload)),
+                response: stringify!($response),
+                uploads_files: true,
+            },
+        )*];
+    };
+}
+
+upload_methods! {
+    UPLOADS;
+    SendPhoto => "sendPhoto", Message;
+    SendAudio => "sendAudio", Message;
+    SendDocument => "sendDocument", Message;
+    SendVideo => "sendVideo", Message;
+    SendAnimation => "sendAnimation", Message;
+    SendVoice => "sendVoice", Message;
+    SendVideoNote => "sendVideoNote", Message;
+    SendMediaGroup => "sendMediaGroup", Vec<Message>;
+    SendPaidMedia => "sendPaidMedia", Message;
+    EditMessageMedia => "editMessageMedia", TrueOrObject<Message>;
+    SetChatPhoto => "setChatPhoto", bool;
+}
+
+#[cfg(feature = "stickers")]
+upload_methods! {
+    STICKER_UPLOADS;
+    SendSticker => "sendSticker", Message;
+    UploadStickerFile => "uploadStickerFile", File;
+    CreateNewStickerSet => "createNewStickerSet", bool;
+    AddStickerToSet => "addStickerToSet", bool;
+    SetStickerSetThumbnail => "setStickerSetThumbnail", bool;
+}
+
+impl_request! {
+    REQUESTS;
+    GetUpdates => "getUpdates", Vec<Update>;
+    SetWebhook => "setWebhook", bool;
+    DeleteWebhook => "deleteWebhook", bool;
+    GetWebhookInfo => "getWebhookInfo", WebhookInfo;
+    SendMessage => "sendMessage", Message;
+    SetMyCommands => "setMyCommands", bool;
+    GetMyCommands => "getMyCommands", Vec<BotCommand>;
+    DeleteMyCommands => "deleteMyCommands", bool;
+    SetMyName => "setMyName", bool;
+    GetMyName => "getMyName", BotName;
+    SetMyDescription => "setMyDescription", bool;
+    GetMyDescription => "getMyDescription", BotDescription;
+    SetMyShortDescription => "setMyShortDescription", bool;
+    GetMyShortDescription => "getMyShortDescription", BotShortDescription;
+    GetBusinessConnection => "getBusinessConnection", BusinessConnection;
+    ForwardMessage => "forwardMessage", Message;
+    ForwardMessages => "forwardMessages", Vec<MessageId>, validate_forward_messages;
+    CopyMessage => "copyMessage", MessageId;
+    CopyMessages => "copyMessages", Vec<MessageId>, validate_copy_messages;
+    SendLocation => "sendLocation", Message, validate_send_location;
+    SendVenue => "sendVenue", Message;
+    SendContact => "sendContact", Message;
+    SendPoll => "sendPoll", Message, validate_send_poll;
+    SendDice => "sendDice", Message;
+    SendChatAction => "sendChatAction", bool;
+    EditMessageText => "editMessageText", TrueOrObject<Message>;
+    EditMessageCaption => "editMessageCaption", TrueOrObject<Message>;
+    EditMessageReplyMarkup => "editMessageReplyMarkup", TrueOrObject<Message>;
+    StopPoll => "stopPoll", Poll;
+    DeleteMessage => "deleteMessage", bool;
+    DeleteMessages => "deleteMessages", bool, validate_delete_messages;
+    EditMessageLiveLocation => "editMessageLiveLocation", TrueOrObject<Message>, validate_live_location_edit;
+    StopMessageLiveLocation => "stopMessageLiveLocation", TrueOrObject<Message>, validate_live_location_stop;
+    GetUserProfilePhotos => "getUserProfilePhotos", UserProfilePhotos;
+    GetFile => "getFile", File;
+    UnbanChatMember => "unbanChatMember", bool;
+    KickChatMember => "kickChatMember", bool;
+    RestrictChatMember => "restrictChatMember", bool;
+    PromoteChatMember => "promoteChatMember", bool;
+    SetChatAdministratorCustomTitle => "setChatAdministratorCustomTitle", bool, validate_custom_title;
+    SetChatPermissions => "setChatPermissions", bool;
+    ExportChatInviteLink => "exportChatInviteLink", String;
+    CreateChatInviteLink => "createChatInviteLink", ChatInviteLink;
+    EditChatInviteLink => "editChatInviteLink", ChatInviteLink;
+    RevokeChatInviteLink => "revokeChatInviteLink", ChatInviteLink;
+    DeleteChatPhoto => "deleteChatPhoto", bool;
+    SetChatTitle => "setChatTitle", bool;
+    SetChatDescription => "setChatDescription", bool;
+    PinChatMessage => "pinChatMessage", bool;
+    UnpinChatMessage => "unpinChatMessage", bool;
+    UnpinAllChatMessages => "unpinAllChatMessages", bool;
+    LeaveChat => "leaveChat", bool;
+    GetChat => "getChat", Chat;
+    GetChatAdministrators => "getChatAdministrators", Vec<ChatMember>;
+    GetChatMembersCount => "getChatMembersCount", i64;
+    GetChatMemberCount => "getChatMemberCount", i64;
+    GetChatMember => "getChatMember", ChatMember;
+    SetChatStickerSet => "setChatStickerSet", bool;
+    DeleteChatStickerSet => "deleteChatStickerSet", bool;
+    AnswerCallbackQuery => "answerCallbackQuery", bool;
+    AnswerInlineQuery => "answerInlineQuery", bool;
+}
+
+fn validate_forward_messages(data: &ForwardMessages) -> Result<()> {
+    check_bulk_message_ids(&data.message_ids)
+}
+
+fn validate_copy_messages(data: &CopyMessages) -> Result<()> {
+    check_bulk_message_ids(&data.message_ids)
+}
+
+fn validate_delete_messages(data: &DeleteMessages) -> Result<()> {
+    check_bulk_message_ids(&data.message_ids)
+}
+
+/// The bulk message endpoints accept between 1 and 100 message ids
+fn check_bulk_message_ids(ids: &[i64]) -> Result<()> {
+    if ids.is_empty() || ids.len() > 100 {
+        return Err(TelegramError::InvalidArgument(format!(
+            "expected 1-100 message ids, got {}",
+            ids.len()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn validate_send_location(data: &SendLocation) -> Result<()> {
+    check_live_location(
+        data.horizontal_accuracy,
+        data.heading,
+        data.proximity_alert_radius,
+    )?;
+    if data
+        .live_period
+        .is_some_and(|p| !(60..=86400).contains(&p) && p != 0x7FFF_FFFF)
+    {
+        return Err(TelegramError::InvalidArgument(
+            "live_period has to be between 60 and 86400 seconds, or 0x7FFFFFFF".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// checks the optional parameters of a live location are in range
+fn check_live_location(
+    horizontal_accuracy: Option<f64>,
+    heading: Option<i64>,
+    proximity_alert_radius: Option<i64>,
+) -> Result<()> {
+    if horizontal_accuracy.is_some_and(|a| !(0.0..=1500.0).contains(&a)) {
+        return Err(TelegramError::InvalidArgument(
+            "horizontal_accuracy has to be between 0 and 1500 meters".to_owned(),
+        )
+        .into());
+    }
+    if heading.is_some_and(|h| !(1..=360).contains(&h)) {
+        return Err(TelegramError::InvalidArgument(
+            "heading has to be between 1 and 360 degrees".to_owned(),
+        )
+        .into());
+    }
+    if proximity_alert_radius.is_some_and(|r| !(1..=100_000).contains(&r)) {
+        return Err(TelegramError::InvalidArgument(
+            "proximity_alert_radius has to be between 1 and 100000 meters".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn validate_live_location_edit(data: &EditMessageLiveLocation) -> Result<()> {
+    check_edit_target(
+        data.chat_id,
+        data.message_id,
+        data.inline_message_id.as_ref(),
+    )?;
+    check_live_location(
+        data.horizontal_accuracy,
+        data.heading,
+        data.proximity_alert_radius,
+    )
+}
+
+fn validate_live_location_stop(data: &StopMessageLiveLocation) -> Result<()> {
+    check_edit_target(
+        data.chat_id,
+        data.message_id,
+        data.inline_message_id.as_ref(),
+    )
+}
+
+/// checks the payload targets either a message in a chat or an inline message
+fn check_edit_target(
+    chat_id: Option<i64>,
+    message_id: Option<i64>,
+    inline_message_id: Option<&String>,
+) -> Result<()> {
+    match (chat_id, message_id, inline_message_id) {
+        (Some(_), Some(_), None) | (None, None, Some(_)) => Ok(()),
+        _ => Err(TelegramError::InvalidArgument(
+            "expected either chat_id and message_id or inline_message_id".to_owned(),
+        )
+        .into()),
+    }
+}
+
+fn validate_send_poll(data: &SendPoll) -> Result<()> {
+    if !(2..=10).contains(&data.options.len()) {
+        return Err(
+            TelegramError::InvalidArgument("a poll needs 2-10 answer options".to_owned()).into(),
+        );
+    }
+    let correct_option = data
+        .correct_option_id
+        .and_then(|id| usize::try_from(id).ok());
+    if data.poll_type == Some(PollType::Quiz)
+        && correct_option.is_none_or(|id| id >= data.options.len())
+    {
+        return Err(TelegramError::InvalidArgument(
+            "a quiz needs the id of one of its options as correct_option_id".to_owned(),
+        )
+        .into());
+    }
+    if data.open_period.is_some() && data.close_date.is_some() {
+        return Err(TelegramError::InvalidArgument(
+            "open_period and close_date can't be used together".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn validate_custom_title(data: &SetChatAdministratorCustomTitle) -> Result<()> {
+    let length = data.custom_title.chars().count();
+    if length > 16 {
+        return Err(TelegramError::InvalidArgument(format!(
+            "custom titles can be at most 16 characters long, got {length}"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "stickers")]
+impl_request! {
+    STICKER_REQUESTS;
+    GetStickerSet => "getStickerSet", StickerSet;
+    GetCustomEmojiStickers => "getCustomEmojiStickers", Vec<Sticker>;
+    SetStickerPositionInSet => "setStickerPositionInSet", bool;
+    DeleteStickerFromSet => "deleteStickerFromSet", bool;
+    SetStickerEmojiList => "setStickerEmojiList", bool, validate_sticker_emoji_list;
+    SetStickerKeywords => "setStickerKeywords", bool, validate_sticker_keywords;
+    SetStickerMaskPosition => "setStickerMaskPosition", bool;
+    SetStickerSetTitle => "setStickerSetTitle", bool;
+    SetCustomEmojiStickerSetThumbnail => "setCustomEmojiStickerSetThumbnail", bool;
+    DeleteStickerSet => "deleteStickerSet", bool;
+}
+
+#[cfg(feature = "stickers")]
+fn validate_sticker_emoji_list(data: &SetStickerEmojiList) -> Result<()> {
+    check_sticker_lists(&data.emoji_list, None)
+}
+
+#[cfg(feature = "stickers")]
+fn validate_sticker_keywords(data: &SetStickerKeywords) -> Result<()> {
+    check_keywords(data.keywords.as_deref())
+}
+
+/// Checks the emoji and keyword lists of a sticker, which telegram limits to
+/// 1-20 emoji and 0-20 keywords of up to 64 characters in total
+#[cfg(feature = "stickers")]
+pub(super) fn check_sticker_lists(
+    emoji_list: &[String],
+    keywords: Option<&[String]>,
+) -> Result<()> {
+    if emoji_list.is_empty() || emoji_list.len() > 20 {
+        return Err(TelegramError::InvalidArgument(format!(
+            "a sticker needs 1-20 emoji, got {}",
+            emoji_list.len()
+        ))
+        .into());
+    }
+    check_keywords(keywords)
+}
+
+#[cfg(feature = "stickers")]
+fn check_keywords(keywords: Option<&[String]>) -> Result<()> {
+    let keywords = keywords.unwrap_or_default();
+    let length: usize = keywords.iter().map(|k| k.chars().count()).sum();
+    if keywords.len() > 20 || length > 64 {
+        return Err(TelegramError::InvalidArgument(format!(
+            "a sticker can have up to 20 keywords of 64 characters in total, got {} of {length}",
+            keywords.len()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "payments")]
+impl_request! {
+    PAYMENT_REQUESTS;
+    SendInvoice => "sendInvoice", Message, validate_send_invoice;
+    AnswerShippingQuery => "answerShippingQuery", bool, validate_shipping_answer;
+    AnswerPreCheckoutQuery => "answerPreCheckoutQuery", bool, validate_pre_checkout_answer;
+    RefundStarPayment => "refundStarPayment", bool;
+    GetStarTransactions => "getStarTransactions", StarTransactions;
+}
+
+#[cfg(feature = "payments")]
+fn validate_send_invoice(data: &SendInvoice) -> Result<()> {
+    if let Some(tips) = &data.suggested_tip_amounts {
+        if tips.len() > 4 {
+            return Err(TelegramError::InvalidArgument(
+                "no more than 4 suggested tip amounts are allowed".to_owned(),
+            )
+            .into());
+        }
+        if tips.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(TelegramError::InvalidArgument(
+                "suggested tip amounts must be strictly increasing".to_owned(),
+            )
+            .into());
+        }
+        if tips
+            .iter()
+            .any(|t| *t <= 0 || *t > data.max_tip_amount.unwrap_or(0))
+        {
+            return Err(TelegramError::InvalidArgument(
+                "suggested tip amounts must be positive and not exceed max_tip_amount".to_owned(),
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "payments")]
+fn validate_shipping_answer(data: &AnswerShippingQuery) -> Result<()> {
+    if data.ok && data.shipping_options.as_ref().is_none_or(Vec::is_empty) {
+        return Err(TelegramError::InvalidArgument(
+            "shipping_options are required when the shipping query is ok".to_owned(),
+        )
+        .into());
+    }
+    if !data.ok && data.error_message.is_none() {
+        return Err(TelegramError::InvalidArgument(
+            "an error_message is required when the shipping query isn't ok".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "payments")]
+fn validate_pre_checkout_answer(data: &AnswerPreCheckoutQuery) -> Result<()> {
+    if !data.ok && data.error_message.is_none() {
+        return Err(TelegramError::InvalidArgument(
+            "an error_message is required when the pre-checkout query isn't ok".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "games")]
+impl_request! {
+    GAME_REQUESTS;
+    SendGame => "sendGame", Message;
+    SetGameScore => "setGameScore", TrueOrObject<Message>, validate_game_score;
+    GetGameHighScores => "getGameHighScores", Vec<GameHighScore>, validate_game_high_scores;
+}
+
+#[cfg(feature = "games")]
+fn validate_game_score(data: &SetGameScore) -> Result<()> {
+    check_edit_target(
+        data.chat_id,
+        data.message_id,
+        data.inline_message_id.as_ref(),
+    )
+}
+
+#[cfg(feature = "games")]
+fn validate_game_high_scores(data: &GetGameHighScores) -> Result<()> {
+    check_edit_target(
+        data.chat_id,
+        data.message_id,
+        data.inline_message_id.as_ref(),
+    )
+}
+
+#[cfg(feature = "passport")]
+impl_request! {
+    PASSPORT_REQUESTS;
+    SetPassportDataErrors => "setPassportDataErrors", bool, validate_passport_errors;
+}
+
+#[cfg(feature = "passport")]
+fn validate_passport_errors(data: &SetPassportDataErrors) -> Result<()> {
+    if data.errors.is_empty() {
+        return Err(TelegramError::InvalidArgument(
+            "expected at least one passport element error".to_owned(),
+        )
+        .into());
+    }
+    if let Some(error) = data.errors.iter().find(|e| !e.is_valid()) {
+        return Err(TelegramError::InvalidArgument(format!(
+            "a {} passport element error can't be reported for {:?}",
+            error.source(),
+            error.section_type()
+        ))
+        .into());
+    }
+    Ok(())
+}
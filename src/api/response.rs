@@ -1,5 +1,9 @@
-use crate::utils::result::{Result, TelegramError};
-use serde::{Deserialize, Serialize};
+use crate::{
+    model::ResponseParameters,
+    utils::result::{Result, TelegramError},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::convert::TryFrom;
 
 /// The response object that gets returned from the telegram API
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -7,6 +11,10 @@ pub struct Response {
     pub ok: bool,
     pub description: Option<String>,
     pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<ResponseParameters>,
 }
 
 impl<T> From<Response> for Result<T>
@@ -32,3 +40,56 @@ where
         }
     }
 }
+
+/// The full envelope telegram wraps every result in, with the result
+/// deserialized into `T`.
+///
+/// Converting a [`Response`] into a `Result<T>` only keeps the result, use
+/// this instead when the description telegram sometimes adds to a successful
+/// response, or the parameters of a failed one, are needed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TelegramResponse<T> {
+    /// Whether the request was successful
+    pub ok: bool,
+    /// A human-readable description of the result
+    pub description: Option<String>,
+    /// The result of the request, if it was successful
+    pub result: Option<T>,
+    /// The error code of an unsuccessful request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i64>,
+    /// Information on why an unsuccessful request failed and how it can be
+    /// retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<ResponseParameters>,
+}
+
+impl<T> TelegramResponse<T> {
+    /// Converts the envelope into the ergonomic `Result<T>`, dropping the
+    /// description of a successful response
+    pub fn into_result(self) -> Result<T> {
+        match (self.ok, self.result) {
+            (true, Some(res)) => Ok(res),
+            (true, None) => Err(TelegramError::Unknown("response had no result".to_owned()).into()),
+            (false, _) => Err(TelegramError::APIResponseError(
+                self.description
+                    .unwrap_or_else(|| "api error does not contain description".to_owned()),
+            )
+            .into()),
+        }
+    }
+}
+
+impl<T: DeserializeOwned> TryFrom<Response> for TelegramResponse<T> {
+    type Error = crate::Error;
+
+    fn try_from(resp: Response) -> Result<Self> {
+        Ok(Self {
+            ok: resp.ok,
+            description: resp.description,
+            result: resp.result.map(serde_json::from_value).transpose()?,
+            error_code: resp.error_code,
+            parameters: resp.parameters,
+        })
+    }
+}
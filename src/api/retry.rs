@@ -0,0 +1,171 @@
+use super::{api::API, endpoints::APIEndpoint, response::Response};
+use crate::{
+    runtime::{Runtime, TokioRuntime},
+    utils::{result::Result, FormDataFile},
+};
+use async_trait::async_trait;
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+
+/// An [`API`] implementation wrapping another one, which retries requests
+/// telegram rejected because of its flood control.
+///
+/// When telegram answers with error 429, it tells how many seconds to wait
+/// with its `retry_after` parameter. The request is then sent again after
+/// sleeping that long, up to `max_retries` times. A response telling to wait
+/// longer than `max_wait` is returned right away, as is the response of the
+/// last retry.
+///
+/// The [`Client`] retries requests this way by default, configured with
+/// [`ClientBuilder::set_flood_retries`]. Api clients can be wrapped
+/// themselves as well:
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use telexide::{api::{APIClient, FloodRetry}, Client};
+///
+/// # let token = "test token";
+/// let api = FloodRetry::new(APIClient::new_default(token))
+///     .set_max_retries(5)
+///     .set_max_wait(Duration::from_secs(120));
+/// let client = Client::builder()
+///     .set_api_client(std::sync::Arc::new(Box::new(api)))
+///     .build();
+/// ```
+///
+/// [`Client`]: ../client/struct.Client.html
+/// [`ClientBuilder::set_flood_retries`]: ../client/struct.ClientBuilder.html#method.set_flood_retries
+pub struct FloodRetry<A> {
+    api: A,
+    max_retries: u32,
+    max_wait: Duration,
+    runtime: Arc<dyn Runtime>,
+}
+
+impl<A: API + Send> FloodRetry<A> {
+    /// Wraps the given api client, retrying a request up to 3 times when
+    /// telegram asks to wait at most a minute
+    pub fn new(api: A) -> Self {
+        Self {
+            api,
+            max_retries: 3,
+            max_wait: Duration::from_secs(60),
+            runtime: Arc::new(TokioRuntime),
+        }
+    }
+
+    /// Sets how often a request is sent again at most
+    #[must_use]
+    pub fn set_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the longest `retry_after` to wait for, longer waits return the
+    /// error instead
+    #[must_use]
+    pub fn set_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Sets the runtime used for waiting before retrying
+    #[must_use]
+    pub fn set_runtime(mut self, runtime: Arc<dyn Runtime>) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Gets a reference to the wrapped api client
+    pub fn get_api(&self) -> &A {
+        &self.api
+    }
+
+    /// how long to wait before retrying after the response, if it should be
+    /// retried at all
+    fn retry_delay(
+        &self,
+        endpoint: &APIEndpoint,
+        res: &Result<Response>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        let res = res.as_ref().ok()?;
+        if res.ok || res.error_code != Some(429) || attempt >= self.max_retries {
+            return None;
+        }
+
+        let retry_after = res.parameters.as_ref()?.retry_after?;
+        let delay = Duration::from_secs(u64::try_from(retry_after).unwrap_or(0));
+        if delay > self.max_wait {
+            log::warn!("{endpoint} hit the flood control, not retrying after {retry_after}s");
+            return None;
+        }
+
+        log::info!("{endpoint} hit the flood control, retrying after {retry_after}s");
+        Some(delay)
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for FloodRetry<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let res = self.api.get(endpoint.clone(), data.clone()).await;
+            match self.retry_delay(&endpoint, &res, attempt) {
+                Some(delay) => self.runtime.sleep(delay).await,
+                None => return res,
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn post(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let res = self.api.post(endpoint.clone(), data.clone()).await;
+            match self.retry_delay(&endpoint, &res, attempt) {
+                Some(delay) => self.runtime.sleep(delay).await,
+                None => return res,
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        // a reader can only be uploaded once, so the flood control error is
+        // returned as is instead of failing the retry on the used up reader
+        if !files.iter().flatten().all(FormDataFile::can_resend) {
+            return self.api.post_file(endpoint, data, files).await;
+        }
+
+        let mut attempt = 0;
+        loop {
+            let res = self
+                .api
+                .post_file(endpoint.clone(), data.clone(), files.clone())
+                .await;
+            match self.retry_delay(&endpoint, &res, attempt) {
+                Some(delay) => self.runtime.sleep(delay).await,
+                None => return res,
+            }
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use super::{types::*, Request};
+#[cfg(feature = "payments")]
+use crate::model::LabeledPrice;
+use crate::{
+    model::{ChatPermissions, ForceReply, ParseMode, ReplyMarkup},
+    redaction,
+    utils::result::Result,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::OnceLock,
+};
+
+/// The snapshot of the Bot API bundled with telexide
+const BUNDLED: &str = include_str!("schema/bot_api.json");
+
+/// A machine-readable description of the parameters every method of a
+/// version of the Bot API accepts.
+///
+/// telexide bundles a snapshot of the version it targets, see
+/// [`ApiSchema::bundled`]. When running against an older [self-hosted bot API
+/// server], load a snapshot of the version it runs with [`ApiSchema::from_json`]
+/// and set it on the [`ClientBuilder`] to find out which fields telexide sends
+/// that the server doesn't know about.
+///
+/// A snapshot is a json object like
+/// `{"version": "7.0", "methods": {"sendMessage": ["chat_id", "text", ...]}}`.
+///
+/// [self-hosted bot API server]: https://github.com/tdlib/telegram-bot-api
+/// [`ClientBuilder`]: ../client/struct.ClientBuilder.html
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiSchema {
+    version: String,
+    methods: HashMap<String, HashSet<String>>,
+}
+
+impl ApiSchema {
+    /// Parses a schema snapshot
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The schema snapshot bundled with telexide
+    ///
+    /// # Panics
+    /// If the bundled snapshot is invalid, which the tests of telexide make
+    /// sure it isn't
+    pub fn bundled() -> &'static ApiSchema {
+        static SCHEMA: OnceLock<ApiSchema> = OnceLock::new();
+        SCHEMA.get_or_init(|| Self::from_json(BUNDLED).expect("the bundled schema is valid"))
+    }
+
+    /// The version of the Bot API the schema describes, e.g. "7.0"
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns whether the schema knows about the method
+    pub fn has_method(&self, method: &str) -> bool {
+        self.methods.contains_key(method)
+    }
+
+    /// Compares the serialized payload of a call to the method against the
+    /// schema, returning everything the schema doesn't know about
+    pub fn check_value(&self, method: &str, payload: &Value) -> Vec<SchemaMismatch> {
+        let Some(params) = self.methods.get(method) else {
+            return vec![SchemaMismatch {
+                version: self.version.clone(),
+                method: method.to_owned(),
+                field: None,
+            }];
+        };
+
+        let mut mismatches: Vec<_> = payload
+            .as_object()
+            .into_iter()
+            .flat_map(|o| o.keys())
+            .filter(|k| !params.contains(*k))
+            .map(|k| SchemaMismatch {
+                version: self.version.clone(),
+                method: method.to_owned(),
+                field: Some(k.clone()),
+            })
+            .collect();
+        mismatches.sort_by(|a, b| a.field.cmp(&b.field));
+        mismatches
+    }
+
+    /// Compares the payload against the schema of its method, returning
+    /// everything the schema doesn't know about
+    pub fn check<R: Request>(&self, request: &R) -> Result<Vec<SchemaMismatch>> {
+        Ok(self.check_value(R::METHOD, &serde_json::to_value(request)?))
+    }
+
+    /// Checks payloads of the most commonly used methods, with all their
+    /// fields filled in, against the schema, returning everything the schema
+    /// doesn't know about
+    pub fn self_check(&self) -> Result<Vec<SchemaMismatch>> {
+        let mut mismatches = Vec::new();
+        for (method, payload) in samples()? {
+            mismatches.append(&mut self.check_value(method, &payload));
+        }
+        Ok(mismatches)
+    }
+}
+
+/// A method or field telexide sends which a version of the Bot API doesn't
+/// know about, found by an [`ApiSchema`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// The version of the Bot API that was checked against
+    pub version: String,
+    /// The name of the method
+    pub method: String,
+    /// The unknown field, or `None` if the whole method is unknown
+    pub field: Option<String>,
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(
+                f,
+                "{} sends the field {field}, which Bot API {} doesn't know",
+                self.method, self.version
+            ),
+            None => write!(
+                f,
+                "the method {} doesn't exist in Bot API {}",
+                self.method, self.version
+            ),
+        }
+    }
+}
+
+fn sample<R: Request>(request: R) -> Result<(&'static str, Value)> {
+    Ok((R::METHOD, serde_json::to_value(request)?))
+}
+
+/// Payloads of the most commonly used methods, with every field telexide can
+/// send filled in
+#[allow(clippy::too_many_lines)]
+fn samples() -> Result<Vec<(&'static str, Value)>> {
+    let markup = ReplyMarkup::ForceReply(ForceReply {
+        force_reply: true,
+        selective: false,
+    });
+
+    let mut get_updates = GetUpdates::new();
+    get_updates
+        .set_offset(1)
+        .set_limit(100)
+        .set_timeout(10)
+        .set_allowed_updates(vec![UpdateType::Message]);
+
+    let mut send_message = SendMessage::new(1, "text");
+    send_message
+        .set_parse_mode(&ParseMode::HTML)
+        .set_reply_to_message_id(1)
+        .set_reply_markup(&markup)
+        .set_business_connection_id("connection");
+    send_message.enitites = Some(Vec::new());
+
+    let mut copy_message = CopyMessage::new(1, 2, 3);
+    copy_message
+        .set_message_thread_id(1)
+        .set_reply_to_message_id(1)
+        .toggle_allow_sending_without_reply();
+    copy_message.caption = Some("caption".to_owned());
+    copy_message.caption_entities = Some(Vec::new());
+    copy_message.parse_mode = Some(ParseMode::HTML);
+    copy_message.reply_markup = Some(markup.clone());
+
+    let mut send_photo = SendPhoto::new(1, "photo".to_owned());
+    send_photo.business_connection_id = Some("connection".to_owned());
+    send_photo.caption = Some("caption".to_owned());
+    send_photo.caption_entities = Some(Vec::new());
+    send_photo.parse_mode = Some(ParseMode::HTML);
+    send_photo.reply_to_message_id = Some(1);
+    send_photo.reply_markup = Some(markup.clone());
+
+    let mut restrict = RestrictChatMember::new(1, 2, ChatPermissions::all());
+    restrict
+        .set_until_date(Utc::now())
+        .toggle_use_independent_chat_permissions();
+
+    let mut permissions = SetChatPermissions::new(1, ChatPermissions::all());
+    permissions.toggle_use_independent_chat_permissions();
+
+    let mut poll = SendPoll::quiz(1, "question", vec!["a".to_owned(), "b".to_owned()], 0);
+    poll.set_anonymous(false)
+        .set_explanation("explanation")
+        .set_explanation_parse_mode(&ParseMode::HTML)
+        .set_close_date(Utc::now())
+        .set_reply_to_message_id(1)
+        .set_reply_markup(&markup)
+        .toggle_allow_sending_without_reply();
+    poll.business_connection_id = Some("connection".to_owned());
+    poll.explanation_enitites = Some(Vec::new());
+    poll.open_period = Some(5);
+
+    #[cfg_attr(not(feature = "payments"), allow(unused_mut))]
+    let mut samples = vec![
+        sample(get_updates)?,
+        sample(SetWebhook {
+            url: "https://example.com".to_owned(),
+            certificate: Some(InputFile::FileId("certificate".to_owned())),
+            max_connections: Some(40),
+            allowed_updates: Some(vec![UpdateType::Message]),
+            ip_address: Some("127.0.0.1".to_owned()),
+            drop_pending_updates: Some(true),
+            secret_token: Some("secret".to_owned()),
+        })?,
+        sample(DeleteWebhook::dropping_pending_updates())?,
+        sample(send_message)?,
+        sample(ForwardMessage::new(1, 2, 3))?,
+        sample(copy_message)?,
+        // payloads which can upload files don't implement `Request`
+        ("sendPhoto", serde_json::to_value(send_photo)?),
+        sample(AnswerCallbackQuery {
+            callback_query_id: "query".to_owned(),
+            text: Some("text".to_owned()),
+            show_alert: true,
+            url: Some("https://example.com".to_owned()),
+            cache_time: Some(1),
+        })?,
+        sample(restrict)?,
+        sample(PromoteChatMember::demote(1, 2))?,
+        sample(permissions)?,
+        sample(poll)?,
+        sample(StopPoll::new(1, 2))?,
+    ];
+    #[cfg(feature = "payments")]
+    samples.extend(payment_samples(&markup)?);
+    Ok(samples)
+}
+
+#[cfg(feature = "payments")]
+fn payment_samples(markup: &ReplyMarkup) -> Result<Vec<(&'static str, Value)>> {
+    let mut invoice = SendInvoice::new(
+        1,
+        "title",
+        "description",
+        "payload",
+        "token",
+        "EUR",
+        vec![LabeledPrice::new("price", 1)],
+    );
+    invoice
+        .set_tips(2, vec![1])
+        .set_start_parameter("start")
+        .set_provider_data("{}")
+        .set_photo("https://example.com", 1, 1)
+        .set_photo_size(1)
+        .set_reply_to_message_id(1)
+        .set_reply_markup(markup);
+
+    Ok(vec![
+        sample(AnswerShippingQuery {
+            shipping_query_id: "query".to_owned(),
+            ok: true,
+            shipping_options: Some(Vec::new()),
+            error_message: Some("error".to_owned()),
+        })?,
+        sample(AnswerPreCheckoutQuery {
+            pre_checkout_query_id: "query".to_owned(),
+            ok: false,
+            error_message: Some("error".to_owned()),
+        })?,
+        sample(invoice)?,
+    ])
+}
+
+/// Runs [`ApiSchema::self_check`], logging everything the schema doesn't know
+/// about as a warning
+pub(crate) fn log_self_check(schema: &ApiSchema) {
+    match schema.self_check() {
+        Ok(mismatches) => {
+            for m in mismatches {
+                log::warn!("{m}");
+            }
+        },
+        Err(e) => log::warn!(
+            "failed to run the Bot API schema self-check: {}",
+            redaction::scrub(e)
+        ),
+    }
+}
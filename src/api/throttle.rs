@@ -0,0 +1,193 @@
+use super::{api::API, endpoints::APIEndpoint, response::Response};
+use crate::{
+    runtime::{Runtime, TokioRuntime},
+    utils::{result::Result, FormDataFile},
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// the sends within the last window, oldest first
+#[derive(Default)]
+struct Window {
+    sent: VecDeque<Instant>,
+}
+
+impl Window {
+    /// how long to wait until another send fits in the window, dropping the
+    /// sends which left it
+    fn wait(&mut self, now: Instant, (limit, per): (usize, Duration)) -> Duration {
+        while self.sent.front().is_some_and(|t| now - *t >= per) {
+            self.sent.pop_front();
+        }
+        if self.sent.len() < limit {
+            Duration::ZERO
+        } else {
+            per.saturating_sub(now - self.sent[self.sent.len() - limit])
+        }
+    }
+}
+
+#[derive(Default)]
+struct Windows {
+    global: Window,
+    groups: HashMap<i64, Window>,
+}
+
+/// An [`API`] implementation wrapping another one, which keeps the messages
+/// sent below telegram's limits by holding back the sends exceeding them.
+///
+/// By default at most 30 messages are sent per second overall, and at most 20
+/// per minute into the same group or channel, like telegram documents. Sends
+/// over those limits wait until they fit, so broadcasting to many chats
+/// doesn't trip the flood control. Only methods sending messages, like
+/// sendMessage, sendPhoto or copyMessage, are held back.
+///
+/// The [`Client`] can be throttled with [`ClientBuilder::set_throttling`],
+/// or an api client can be wrapped directly:
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use telexide::{api::{APIClient, Throttled}, Client};
+///
+/// # let token = "test token";
+/// let api = Throttled::new(APIClient::new_default(token))
+///     .set_global_limit(20, Duration::from_secs(1));
+/// let client = Client::builder()
+///     .set_api_client(std::sync::Arc::new(Box::new(api)))
+///     .build();
+/// ```
+///
+/// [`Client`]: ../client/struct.Client.html
+/// [`ClientBuilder::set_throttling`]: ../client/struct.ClientBuilder.html#method.set_throttling
+pub struct Throttled<A> {
+    api: A,
+    global_limit: (usize, Duration),
+    group_limit: (usize, Duration),
+    windows: Mutex<Windows>,
+    runtime: Arc<dyn Runtime>,
+}
+
+impl<A: API + Send> Throttled<A> {
+    /// Wraps the given api client, sending at most 30 messages per second and
+    /// 20 per minute into the same group
+    pub fn new(api: A) -> Self {
+        Self {
+            api,
+            global_limit: (30, Duration::from_secs(1)),
+            group_limit: (20, Duration::from_secs(60)),
+            windows: Mutex::new(Windows::default()),
+            runtime: Arc::new(TokioRuntime),
+        }
+    }
+
+    /// Sets how many messages are sent at most within the given time overall
+    #[must_use]
+    pub fn set_global_limit(mut self, limit: usize, per: Duration) -> Self {
+        self.global_limit = (limit.max(1), per);
+        self
+    }
+
+    /// Sets how many messages are sent at most within the given time into the
+    /// same group or channel
+    #[must_use]
+    pub fn set_group_limit(mut self, limit: usize, per: Duration) -> Self {
+        self.group_limit = (limit.max(1), per);
+        self
+    }
+
+    /// Sets the runtime used for waiting until a send fits
+    #[must_use]
+    pub fn set_runtime(mut self, runtime: Arc<dyn Runtime>) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Gets a reference to the wrapped api client
+    pub fn get_api(&self) -> &A {
+        &self.api
+    }
+
+    /// waits until the call fits within the limits, if it sends a message
+    async fn throttle(&self, endpoint: &APIEndpoint, data: Option<&Value>) {
+        if !sends_message(endpoint.as_str()) {
+            return;
+        }
+        // groups and channels have negative ids
+        let group = data
+            .and_then(|d| d.get("chat_id"))
+            .and_then(Value::as_i64)
+            .filter(|id| *id < 0);
+
+        loop {
+            let wait = {
+                let mut windows = self.windows.lock();
+                let now = Instant::now();
+                let mut wait = windows.global.wait(now, self.global_limit);
+                if let Some(group) = group {
+                    let group_wait = windows
+                        .groups
+                        .entry(group)
+                        .or_default()
+                        .wait(now, self.group_limit);
+                    wait = wait.max(group_wait);
+                }
+
+                if wait.is_zero() {
+                    windows.global.sent.push_back(now);
+                    if let Some(group) = group {
+                        windows.groups.entry(group).or_default().sent.push_back(now);
+                    }
+                    windows.groups.retain(|_, w| !w.sent.is_empty());
+                }
+                wait
+            };
+
+            if wait.is_zero() {
+                return;
+            }
+            log::debug!("holding back {endpoint} for {wait:?} to stay within the limits");
+            self.runtime.sleep(wait).await;
+        }
+    }
+}
+
+fn sends_message(method: &str) -> bool {
+    match method {
+        "sendChatAction" => false,
+        "forwardMessage" | "forwardMessages" | "copyMessage" | "copyMessages" => true,
+        m => m.starts_with("send"),
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for Throttled<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.throttle(&endpoint, data.as_ref()).await;
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.throttle(&endpoint, data.as_ref()).await;
+        self.api.post(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.throttle(&endpoint, data.as_ref()).await;
+        self.api.post_file(endpoint, data, files).await
+    }
+}
@@ -0,0 +1,246 @@
+use hyper::{
+    client::{
+        connect::{Connected, Connection},
+        HttpConnector,
+    },
+    service::Service,
+    Uri,
+};
+use std::{
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The connector making the connections of the [`APIClient`], wrapping the
+/// connector for the underlying connections.
+///
+/// [`HttpsConnector::new`] and [`HttpsConnector::with_connector`] use the
+/// platform's tls library when the `native-tls` feature is enabled, and
+/// rustls when only the `rustls` feature is. Without either feature only plain
+/// http connections can be made, for example to a local bot API server.
+///
+/// [`APIClient`]: struct.APIClient.html
+#[derive(Clone)]
+pub struct HttpsConnector<C> {
+    backend: Backend<C>,
+}
+
+#[derive(Clone)]
+enum Backend<C> {
+    #[cfg(feature = "native-tls")]
+    NativeTls(hyper_tls::HttpsConnector<C>),
+    #[cfg(feature = "rustls")]
+    Rustls(hyper_rustls::HttpsConnector<C>),
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    Plain(C),
+}
+
+impl HttpsConnector<HttpConnector> {
+    /// Creates a connector making both https and plain http connections
+    pub fn new() -> Self {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        Self::with_connector(http)
+    }
+}
+
+impl Default for HttpsConnector<HttpConnector> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> HttpsConnector<C> {
+    /// Wraps the connector so it makes https connections, while still
+    /// allowing plain http ones
+    #[cfg(feature = "native-tls")]
+    pub fn with_connector(connector: C) -> Self {
+        Self::native_tls(connector)
+    }
+
+    /// Wraps the connector so it makes https connections, while still
+    /// allowing plain http ones
+    #[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+    pub fn with_connector(connector: C) -> Self {
+        Self::rustls(connector)
+    }
+
+    /// Wraps the connector, which only makes plain http connections without
+    /// the `native-tls` or `rustls` feature
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    pub fn with_connector(connector: C) -> Self {
+        Self {
+            backend: Backend::Plain(connector),
+        }
+    }
+
+    /// Wraps the connector so it makes https connections using the
+    /// platform's tls library
+    #[cfg(feature = "native-tls")]
+    pub fn native_tls(connector: C) -> Self {
+        Self {
+            backend: Backend::NativeTls(hyper_tls::HttpsConnector::new_with_connector(connector)),
+        }
+    }
+
+    /// Wraps the connector so it makes https connections using rustls, with
+    /// the webpki root certificates
+    #[cfg(feature = "rustls")]
+    pub fn rustls(connector: C) -> Self {
+        Self {
+            backend: Backend::Rustls(
+                hyper_rustls::HttpsConnectorBuilder::new()
+                    .with_webpki_roots()
+                    .https_or_http()
+                    .enable_http1()
+                    .enable_http2()
+                    .wrap_connector(connector),
+            ),
+        }
+    }
+}
+
+impl<C> fmt::Debug for HttpsConnector<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let backend = match &self.backend {
+            #[cfg(feature = "native-tls")]
+            Backend::NativeTls(_) => "native-tls",
+            #[cfg(feature = "rustls")]
+            Backend::Rustls(_) => "rustls",
+            #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+            Backend::Plain(_) => "plain",
+        };
+        f.debug_struct("HttpsConnector")
+            .field("backend", &backend)
+            .finish()
+    }
+}
+
+impl<C> Service<Uri> for HttpsConnector<C>
+where
+    C: Service<Uri> + Send + 'static,
+    C::Response: Connection + AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+{
+    type Response = HttpsStream<C::Response>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        match &mut self.backend {
+            #[cfg(feature = "native-tls")]
+            Backend::NativeTls(c) => c.poll_ready(cx),
+            #[cfg(feature = "rustls")]
+            Backend::Rustls(c) => c.poll_ready(cx),
+            #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+            Backend::Plain(c) => c.poll_ready(cx).map_err(Into::into),
+        }
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        match &mut self.backend {
+            #[cfg(feature = "native-tls")]
+            Backend::NativeTls(c) => {
+                let connecting = c.call(uri);
+                Box::pin(async move { Ok(HttpsStream(Stream::NativeTls(connecting.await?))) })
+            },
+            #[cfg(feature = "rustls")]
+            Backend::Rustls(c) => {
+                let connecting = c.call(uri);
+                Box::pin(async move { Ok(HttpsStream(Stream::Rustls(connecting.await?))) })
+            },
+            #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+            Backend::Plain(c) => {
+                if uri.scheme() == Some(&hyper::http::uri::Scheme::HTTPS) {
+                    return Box::pin(async {
+                        Err("https needs the native-tls or rustls feature".into())
+                    });
+                }
+                let connecting = c.call(uri);
+                Box::pin(async move {
+                    let stream = connecting.await.map_err(Into::into)?;
+                    Ok(HttpsStream(Stream::Plain(stream)))
+                })
+            },
+        }
+    }
+}
+
+/// A connection made by a [`HttpsConnector`], either over tls or plain http
+pub struct HttpsStream<T>(Stream<T>);
+
+enum Stream<T> {
+    #[cfg(feature = "native-tls")]
+    NativeTls(hyper_tls::MaybeHttpsStream<T>),
+    #[cfg(feature = "rustls")]
+    Rustls(hyper_rustls::MaybeHttpsStream<T>),
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    Plain(T),
+}
+
+/// calls the method on whichever stream the connection is
+macro_rules! on_stream {
+    ($stream:expr, $s:ident => $call:expr) => {
+        match $stream {
+            #[cfg(feature = "native-tls")]
+            Stream::NativeTls($s) => $call,
+            #[cfg(feature = "rustls")]
+            Stream::Rustls($s) => $call,
+            #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+            Stream::Plain($s) => $call,
+        }
+    };
+}
+
+impl<T: Connection + AsyncRead + AsyncWrite + Unpin> Connection for HttpsStream<T> {
+    fn connected(&self) -> Connected {
+        on_stream!(&self.0, s => s.connected())
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> AsyncRead for HttpsStream<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        on_stream!(&mut self.get_mut().0, s => Pin::new(s).poll_read(cx, buf))
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> AsyncWrite for HttpsStream<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        on_stream!(&mut self.get_mut().0, s => Pin::new(s).poll_write(cx, buf))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        on_stream!(&mut self.get_mut().0, s => Pin::new(s).poll_write_vectored(cx, bufs))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        on_stream!(&self.0, s => s.is_write_vectored())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        on_stream!(&mut self.get_mut().0, s => Pin::new(s).poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        on_stream!(&mut self.get_mut().0, s => Pin::new(s).poll_shutdown(cx))
+    }
+}
@@ -1,5 +1,11 @@
 use super::InputFile;
-use crate::model::{utils::unix_date_formatting, Chat, ChatPermissions};
+use crate::model::{
+    utils::unix_date_formatting,
+    AdministratorMemberStatus,
+    Chat,
+    ChatPermissions,
+    Message,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -56,6 +62,13 @@ pub struct RestrictChatMember {
     pub user_id: i64,
     /// New user permissions
     pub permissions: ChatPermissions,
+    /// Pass True if chat permissions are set independently. Otherwise, the
+    /// `can_send_other_messages` and `can_add_web_page_previews` permissions
+    /// will imply all the permissions to send messages and media; the
+    /// `can_send_polls` permission will imply the `can_send_messages`
+    /// permission.
+    #[serde(default)]
+    pub use_independent_chat_permissions: bool,
     /// Date when the user will be unbanned, unix time.
     /// If user is banned for more than 366 days or less than 30 seconds from
     /// the current time they are considered to be banned forever
@@ -64,6 +77,28 @@ pub struct RestrictChatMember {
     pub until_date: Option<DateTime<Utc>>,
 }
 
+impl RestrictChatMember {
+    pub fn new(chat_id: i64, user_id: i64, permissions: ChatPermissions) -> Self {
+        Self {
+            chat_id,
+            user_id,
+            permissions,
+            use_independent_chat_permissions: false,
+            until_date: None,
+        }
+    }
+
+    pub fn set_until_date(&mut self, until_date: DateTime<Utc>) -> &mut Self {
+        self.until_date = Some(until_date);
+        self
+    }
+
+    pub fn toggle_use_independent_chat_permissions(&mut self) -> &mut Self {
+        self.use_independent_chat_permissions = !self.use_independent_chat_permissions;
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`promote_chat_member`]
 ///
@@ -78,6 +113,12 @@ pub struct PromoteChatMember {
     /// If the administrator's presence in the chat is hidden
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_anonymous: Option<bool>,
+    /// If the administrator can access the chat event log, chat statistics,
+    /// message statistics in channels, see channel members, see anonymous
+    /// administrators in supergroups and ignore slow mode. Implied by any
+    /// other administrator privilege.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_manage_chat: Option<bool>,
     /// If the administrator can create channel posts, channels only
     #[serde(skip_serializing_if = "Option::is_none")]
     pub can_post_messages: Option<bool>,
@@ -88,13 +129,16 @@ pub struct PromoteChatMember {
     /// If the administrator can delete messages of other users
     #[serde(skip_serializing_if = "Option::is_none")]
     pub can_delete_messages: Option<bool>,
+    /// If the administrator can manage video chats
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_manage_video_chats: Option<bool>,
     /// If the administrator can restrict, ban or unban chat members
     #[serde(skip_serializing_if = "Option::is_none")]
     pub can_restrict_members: Option<bool>,
-    /// If the administrator can add new administrators with a subset of his own
-    /// privileges or demote administrators that he has promoted, directly
-    /// or indirectly (promoted by administrators that were appointed by
-    /// him)
+    /// If the administrator can add new administrators with a subset of their
+    /// own privileges or demote administrators that they have promoted,
+    /// directly or indirectly (promoted by administrators that were appointed
+    /// by them)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub can_promote_members: Option<bool>,
     /// If the administrator can change chat title, photo and other settings
@@ -106,15 +150,19 @@ pub struct PromoteChatMember {
     /// If the administrator can pin messages, supergroups only
     #[serde(skip_serializing_if = "Option::is_none")]
     pub can_pin_messages: Option<bool>,
-    /// If the administrator can manage voice chats, supergroups only
+    /// If the user is allowed to create, rename, close, and reopen forum
+    /// topics, supergroups only
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub can_manage_voice_chats: Option<bool>,
-    /// If the administrator can access the chat event log, chat statistics,
-    /// message statistics in channels, see channel members, see anonymous
-    /// administrators in supergroups and ignore slow mode. Implied by any
-    /// other administrator privilege.
+    pub can_manage_topics: Option<bool>,
+    /// If the administrator can post stories to the chat
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub can_manage_chat: Option<bool>,
+    pub can_post_stories: Option<bool>,
+    /// If the administrator can edit stories posted by other users
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_edit_stories: Option<bool>,
+    /// If the administrator can delete stories posted by other users
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_delete_stories: Option<bool>,
 }
 
 impl PromoteChatMember {
@@ -129,12 +177,69 @@ impl PromoteChatMember {
             can_post_messages: None,
             can_edit_messages: None,
             can_delete_messages: None,
+            can_manage_video_chats: None,
             can_restrict_members: None,
             can_promote_members: None,
             can_change_info: None,
             can_invite_users: None,
             can_pin_messages: None,
-            can_manage_voice_chats: None,
+            can_manage_topics: None,
+            can_post_stories: None,
+            can_edit_stories: None,
+            can_delete_stories: None,
+        }
+    }
+
+    /// function to create a `PromoteChatMember` object which removes all
+    /// admin rights of the user, demoting them to a regular member
+    pub fn demote(chat_id: i64, user_id: i64) -> Self {
+        Self {
+            chat_id,
+            user_id,
+            is_anonymous: Some(false),
+            can_manage_chat: Some(false),
+            can_post_messages: Some(false),
+            can_edit_messages: Some(false),
+            can_delete_messages: Some(false),
+            can_manage_video_chats: Some(false),
+            can_restrict_members: Some(false),
+            can_promote_members: Some(false),
+            can_change_info: Some(false),
+            can_invite_users: Some(false),
+            can_pin_messages: Some(false),
+            can_manage_topics: Some(false),
+            can_post_stories: Some(false),
+            can_edit_stories: Some(false),
+            can_delete_stories: Some(false),
+        }
+    }
+
+    /// function to create a `PromoteChatMember` object granting the same
+    /// rights an existing administrator has, e.g. to copy them to another
+    /// user or chat
+    pub fn from_administrator(
+        chat_id: i64,
+        user_id: i64,
+        admin: &AdministratorMemberStatus,
+    ) -> Self {
+        Self {
+            chat_id,
+            user_id,
+            is_anonymous: Some(admin.is_anonymous),
+            can_manage_chat: Some(admin.can_manage_chat),
+            can_post_messages: Some(admin.can_post_messages),
+            can_edit_messages: Some(admin.can_edit_messages),
+            can_delete_messages: Some(admin.can_delete_messages),
+            can_manage_video_chats: Some(admin.can_manage_video_chats),
+            can_restrict_members: Some(admin.can_restrict_members),
+            can_promote_members: Some(admin.can_promote_members),
+            can_change_info: Some(admin.can_change_info),
+            can_invite_users: Some(admin.can_invite_users),
+            can_pin_messages: Some(admin.can_pin_messages),
+            can_manage_topics: Some(admin.can_manage_topics),
+            can_post_stories: Some(admin.can_post_stories),
+            can_edit_stories: Some(admin.can_edit_stories),
+            can_delete_stories: Some(admin.can_delete_stories),
         }
     }
 }
@@ -155,6 +260,21 @@ pub struct SetChatAdministratorCustomTitle {
     pub custom_title: String,
 }
 
+impl SetChatAdministratorCustomTitle {
+    pub fn new(chat_id: i64, user_id: i64, custom_title: &str) -> Self {
+        Self {
+            chat_id,
+            user_id,
+            custom_title: custom_title.to_owned(),
+        }
+    }
+
+    /// Creates the payload for removing the custom title of the administrator
+    pub fn clear(chat_id: i64, user_id: i64) -> Self {
+        Self::new(chat_id, user_id, "")
+    }
+}
+
 /// struct for holding data needed to call [`set_chat_permissions`]
 ///
 /// [`set_chat_permissions`]:
@@ -165,6 +285,28 @@ pub struct SetChatPermissions {
     pub chat_id: i64,
     /// New default chat permissions
     pub permissions: ChatPermissions,
+    /// Pass True if chat permissions are set independently. Otherwise, the
+    /// `can_send_other_messages` and `can_add_web_page_previews` permissions
+    /// will imply all the permissions to send messages and media; the
+    /// `can_send_polls` permission will imply the `can_send_messages`
+    /// permission.
+    #[serde(default)]
+    pub use_independent_chat_permissions: bool,
+}
+
+impl SetChatPermissions {
+    pub fn new(chat_id: i64, permissions: ChatPermissions) -> Self {
+        Self {
+            chat_id,
+            permissions,
+            use_independent_chat_permissions: false,
+        }
+    }
+
+    pub fn toggle_use_independent_chat_permissions(&mut self) -> &mut Self {
+        self.use_independent_chat_permissions = !self.use_independent_chat_permissions;
+        self
+    }
 }
 
 /// struct for holding data needed to call [`export_chat_invite_link`]
@@ -244,6 +386,25 @@ pub struct PinChatMessage {
     pub disable_notification: bool,
 }
 
+impl PinChatMessage {
+    pub fn new(chat_id: i64, message_id: i64) -> Self {
+        Self {
+            chat_id,
+            message_id,
+            disable_notification: false,
+        }
+    }
+
+    pub fn from_message(message: &Message) -> Self {
+        Self::new(message.chat.get_id(), message.message_id)
+    }
+
+    pub fn toggle_disable_notification(&mut self) -> &mut Self {
+        self.disable_notification = !self.disable_notification;
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`unpin_chat_message`]
 ///
@@ -259,11 +420,29 @@ pub struct UnpinChatMessage {
     pub message_id: Option<i64>,
 }
 
+impl UnpinChatMessage {
+    /// Unpins the most recent pinned message in the chat
+    pub fn new(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            message_id: None,
+        }
+    }
+
+    /// Unpins the given message
+    pub fn from_message(message: &Message) -> Self {
+        Self {
+            chat_id: message.chat.get_id(),
+            message_id: Some(message.message_id),
+        }
+    }
+}
+
 /// struct for holding data needed to call
 /// [`unpin_all_chat_messages`]
 ///
 /// [`unpin_all_chat_messages`]:
-/// ../../api/trait.API.html#method.unpin_all_chat_message
+/// ../../api/trait.API.html#method.unpin_all_chat_messages
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UnpinAllChatMessages {
     /// Unique identifier for the target chat
@@ -304,16 +483,27 @@ pub struct GetChatAdministrators {
 }
 
 /// struct for holding data needed to call
-/// [`get_chat_members_count`]
+/// [`get_members_count`]
 ///
-/// [`get_chat_members_count`]:
-/// ../../api/trait.API.html#method.get_chat_members_count
+/// [`get_members_count`]:
+/// ../../api/trait.API.html#method.get_members_count
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GetChatMembersCount {
     /// Unique identifier for the target chat
     pub chat_id: i64,
 }
 
+/// struct for holding data needed to call
+/// [`get_chat_member_count`]
+///
+/// [`get_chat_member_count`]:
+/// ../../api/trait.API.html#method.get_chat_member_count
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetChatMemberCount {
+    /// Unique identifier for the target chat
+    pub chat_id: i64,
+}
+
 /// struct for holding data needed to call
 /// [`get_chat_member`]
 ///
@@ -414,4 +604,5 @@ impl_from_chat!(LeaveChat);
 impl_from_chat!(GetChat);
 impl_from_chat!(GetChatAdministrators);
 impl_from_chat!(GetChatMembersCount);
+impl_from_chat!(GetChatMemberCount);
 impl_from_chat!(DeleteChatStickerSet);
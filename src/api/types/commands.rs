@@ -8,13 +8,26 @@ use serde::{Deserialize, Serialize};
 /// ../../api/trait.API.html#method.set_my_commands
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SetMyCommands {
+    /// A list of bot commands to be set as the list of the bot's commands. At
+    /// most 100 commands can be specified.
     pub commands: Vec<BotCommand>,
+    /// The scope of users for which the commands are relevant. Defaults to
+    /// [`BotCommandScope::Default`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<BotCommandScope>,
+    /// A two-letter ISO 639-1 language code. If empty, commands will be applied
+    /// to all users from the given scope, for whose language there are no
+    /// dedicated commands
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
 }
 
 impl From<Vec<BotCommand>> for SetMyCommands {
     fn from(commands: Vec<BotCommand>) -> Self {
         Self {
             commands,
+            scope: None,
+            language_code: None,
         }
     }
 }
@@ -26,6 +39,89 @@ impl From<&Vec<TelegramCommand>> for SetMyCommands {
                 .iter()
                 .map(TelegramCommand::get_bot_command)
                 .collect(),
+            scope: None,
+            language_code: None,
         }
     }
 }
+
+/// struct for holding data needed to call
+/// [`get_my_commands`]
+///
+/// [`get_my_commands`]:
+/// ../../api/trait.API.html#method.get_my_commands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GetMyCommands {
+    /// The scope of users to get the commands for. Defaults to
+    /// [`BotCommandScope::Default`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<BotCommandScope>,
+    /// A two-letter ISO 639-1 language code or an empty string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+/// struct for holding data needed to call
+/// [`delete_my_commands`]
+///
+/// [`delete_my_commands`]:
+/// ../../api/trait.API.html#method.delete_my_commands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DeleteMyCommands {
+    /// The scope of users for which the commands should be deleted. Defaults
+    /// to [`BotCommandScope::Default`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<BotCommandScope>,
+    /// A two-letter ISO 639-1 language code. If empty, commands will be
+    /// deleted for all users from the given scope, for whose language there
+    /// are no dedicated commands
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+/// This object represents the scope to which bot commands are applied.
+///
+/// When looking up the commands for a user, telegram picks the first list of
+/// commands set for the most narrow scope that applies to them, going from
+/// [`ChatMember`] down to [`Default`].
+///
+/// [`ChatMember`]: BotCommandScope::ChatMember
+/// [`Default`]: BotCommandScope::Default
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(tag = "type")]
+pub enum BotCommandScope {
+    /// The default scope, used if no commands with a narrower scope are
+    /// specified for the user
+    #[default]
+    #[serde(rename = "default")]
+    Default,
+    /// Covers all private chats
+    #[serde(rename = "all_private_chats")]
+    AllPrivateChats,
+    /// Covers all group and supergroup chats
+    #[serde(rename = "all_group_chats")]
+    AllGroupChats,
+    /// Covers all group and supergroup chat administrators
+    #[serde(rename = "all_chat_administrators")]
+    AllChatAdministrators,
+    /// Covers a specific chat
+    #[serde(rename = "chat")]
+    Chat {
+        /// Unique identifier for the target chat
+        chat_id: i64,
+    },
+    /// Covers all administrators of a specific group or supergroup chat
+    #[serde(rename = "chat_administrators")]
+    ChatAdministrators {
+        /// Unique identifier for the target chat
+        chat_id: i64,
+    },
+    /// Covers a specific member of a group or supergroup chat
+    #[serde(rename = "chat_member")]
+    ChatMember {
+        /// Unique identifier for the target chat
+        chat_id: i64,
+        /// Unique identifier of the target user
+        user_id: i64,
+    },
+}
@@ -1,5 +1,8 @@
 use super::InputMedia;
-use crate::model::{InlineKeyboardMarkup, Message, MessageEntity, ParseMode};
+use crate::{
+    helpers::formatting::FormattedText,
+    model::{InlineKeyboardMarkup, Message, MessageEntity, ParseMode},
+};
 use serde::{Deserialize, Serialize};
 
 /// struct for holding data needed to call
@@ -9,6 +12,10 @@ use serde::{Deserialize, Serialize};
 /// ../../api/trait.API.html#method.edit_message_text
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EditMessageText {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message to be edited was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_id: Option<i64>,
@@ -38,6 +45,7 @@ pub struct EditMessageText {
 impl EditMessageText {
     fn new(chat_id: i64, message_id: i64, new_text: &str) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: Some(chat_id),
             message_id: Some(message_id),
             text: new_text.to_owned(),
@@ -51,6 +59,7 @@ impl EditMessageText {
 
     fn from_message(message: &Message, new_text: &str) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: Some(message.chat.get_id()),
             message_id: Some(message.message_id),
             text: new_text.to_owned(),
@@ -61,6 +70,22 @@ impl EditMessageText {
             reply_markup: None,
         }
     }
+
+    /// Creates the payload for replacing the text of the message with the
+    /// formatted text
+    pub fn formatted(chat_id: i64, message_id: i64, text: &FormattedText) -> Self {
+        let mut data = Self::new(chat_id, message_id, "");
+        data.set_formatted_text(text);
+        data
+    }
+
+    /// Sets the text and its entities, removing any parse mode
+    pub fn set_formatted_text(&mut self, text: &FormattedText) -> &mut Self {
+        self.text.clone_from(&text.text);
+        self.entities = text.entities();
+        self.parse_mode = None;
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -70,6 +95,10 @@ impl EditMessageText {
 /// ../../api/trait.API.html#method.edit_message_caption
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EditMessageCaption {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message to be edited was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_id: Option<i64>,
@@ -98,6 +127,7 @@ pub struct EditMessageCaption {
 impl EditMessageCaption {
     fn new(chat_id: i64, message_id: i64, new_text: Option<&str>) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: Some(chat_id),
             message_id: Some(message_id),
             caption: new_text.map(ToString::to_string),
@@ -110,6 +140,7 @@ impl EditMessageCaption {
 
     fn from_message(message: &Message, new_text: Option<&str>) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: Some(message.chat.get_id()),
             message_id: Some(message.message_id),
             caption: new_text.map(ToString::to_string),
@@ -128,6 +159,10 @@ impl EditMessageCaption {
 /// ../../api/trait.API.html#method.edit_message_media
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EditMessageMedia {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message to be edited was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_id: Option<i64>,
@@ -145,22 +180,28 @@ pub struct EditMessageMedia {
 }
 
 impl EditMessageMedia {
-    fn new(chat_id: i64, message_id: i64, new_media: &InputMedia) -> Self {
+    pub fn new(chat_id: i64, message_id: i64, new_media: InputMedia) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: Some(chat_id),
             message_id: Some(message_id),
-            media: new_media.to_owned(),
+            media: new_media,
             inline_message_id: None,
             reply_markup: None,
         }
     }
 
-    fn from_message(message: &Message, new_media: &InputMedia) -> Self {
+    pub fn from_message(message: &Message, new_media: InputMedia) -> Self {
+        Self::new(message.chat.get_id(), message.message_id, new_media)
+    }
+
+    pub fn inline(inline_message_id: &str, new_media: InputMedia) -> Self {
         Self {
-            chat_id: Some(message.chat.get_id()),
-            message_id: Some(message.message_id),
-            media: new_media.to_owned(),
-            inline_message_id: None,
+            business_connection_id: None,
+            chat_id: None,
+            message_id: None,
+            media: new_media,
+            inline_message_id: Some(inline_message_id.to_owned()),
             reply_markup: None,
         }
     }
@@ -173,6 +214,10 @@ impl EditMessageMedia {
 /// ../../api/trait.API.html#method.edit_message_reply_markup
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EditMessageReplyMarkup {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message to be edited was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_id: Option<i64>,
@@ -190,6 +235,7 @@ pub struct EditMessageReplyMarkup {
 impl EditMessageReplyMarkup {
     fn new(chat_id: i64, message_id: i64, new_markup: Option<&InlineKeyboardMarkup>) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: Some(chat_id),
             message_id: Some(message_id),
             inline_message_id: None,
@@ -199,6 +245,7 @@ impl EditMessageReplyMarkup {
 
     fn from_message(message: &Message, new_markup: Option<&InlineKeyboardMarkup>) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: Some(message.chat.get_id()),
             message_id: Some(message.message_id),
             inline_message_id: None,
@@ -214,6 +261,10 @@ impl EditMessageReplyMarkup {
 /// ../../api/trait.API.html#method.stop_poll
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct StopPoll {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message to be edited was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Identifier of the message to edit
@@ -224,21 +275,28 @@ pub struct StopPoll {
 }
 
 impl StopPoll {
-    fn new(chat_id: i64, message_id: i64) -> Self {
+    pub fn new(chat_id: i64, message_id: i64) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
             message_id,
             reply_markup: None,
         }
     }
 
-    fn from_message(message: &Message) -> Self {
+    pub fn from_message(message: &Message) -> Self {
         Self {
+            business_connection_id: None,
             chat_id: message.chat.get_id(),
             message_id: message.message_id,
             reply_markup: None,
         }
     }
+
+    pub fn set_reply_markup(&mut self, markup: &InlineKeyboardMarkup) -> &mut Self {
+        self.reply_markup = Some(markup.to_owned());
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -270,6 +328,28 @@ impl DeleteMessage {
     }
 }
 
+/// struct for holding data needed to call
+/// [`delete_messages`]
+///
+/// [`delete_messages`]:
+/// ../../api/trait.API.html#method.delete_messages
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeleteMessages {
+    /// Unique identifier for the target chat
+    pub chat_id: i64,
+    /// Identifiers of 1-100 messages to delete
+    pub message_ids: Vec<i64>,
+}
+
+impl DeleteMessages {
+    pub fn new(chat_id: i64, message_ids: Vec<i64>) -> Self {
+        Self {
+            chat_id,
+            message_ids,
+        }
+    }
+}
+
 /// struct for holding data needed to call
 /// [`edit_message_live_location`]
 ///
@@ -277,6 +357,10 @@ impl DeleteMessage {
 /// ../../api/trait.API.html#method.edit_message_live_location
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EditMessageLiveLocation {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message to be edited was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_id: Option<i64>,
@@ -306,13 +390,73 @@ pub struct EditMessageLiveLocation {
     pub proximity_alert_radius: Option<i64>,
 }
 
+impl EditMessageLiveLocation {
+    pub fn new(chat_id: i64, message_id: i64, latitude: f64, longitude: f64) -> Self {
+        Self {
+            business_connection_id: None,
+            chat_id: Some(chat_id),
+            message_id: Some(message_id),
+            inline_message_id: None,
+            reply_markup: None,
+            latitude,
+            longitude,
+            horizontal_accuracy: None,
+            heading: None,
+            proximity_alert_radius: None,
+        }
+    }
+
+    pub fn from_message(message: &Message, latitude: f64, longitude: f64) -> Self {
+        Self::new(message.chat.get_id(), message.message_id, latitude, longitude)
+    }
+
+    pub fn inline(inline_message_id: &str, latitude: f64, longitude: f64) -> Self {
+        Self {
+            business_connection_id: None,
+            chat_id: None,
+            message_id: None,
+            inline_message_id: Some(inline_message_id.to_owned()),
+            reply_markup: None,
+            latitude,
+            longitude,
+            horizontal_accuracy: None,
+            heading: None,
+            proximity_alert_radius: None,
+        }
+    }
+
+    pub fn set_horizontal_accuracy(&mut self, meters: f64) -> &mut Self {
+        self.horizontal_accuracy = Some(meters);
+        self
+    }
+
+    pub fn set_heading(&mut self, degrees: i64) -> &mut Self {
+        self.heading = Some(degrees);
+        self
+    }
+
+    pub fn set_proximity_alert_radius(&mut self, meters: i64) -> &mut Self {
+        self.proximity_alert_radius = Some(meters);
+        self
+    }
+
+    pub fn set_reply_markup(&mut self, markup: &InlineKeyboardMarkup) -> &mut Self {
+        self.reply_markup = Some(markup.clone());
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`stop_message_live_location`]
 ///
 /// [`stop_message_live_location`]:
-/// ../../api/trait.API.html#method.edit_message_live_location
+/// ../../api/trait.API.html#method.stop_message_live_location
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct StopMessageLiveLocation {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message to be edited was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_id: Option<i64>,
@@ -326,3 +470,36 @@ pub struct StopMessageLiveLocation {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<InlineKeyboardMarkup>,
 }
+
+impl StopMessageLiveLocation {
+    pub fn new(chat_id: i64, message_id: i64) -> Self {
+        Self {
+            business_connection_id: None,
+            chat_id: Some(chat_id),
+            message_id: Some(message_id),
+            inline_message_id: None,
+            reply_markup: None,
+        }
+    }
+
+    pub fn from_message(message: &Message) -> Self {
+        Self::new(message.chat.get_id(), message.message_id)
+    }
+
+    pub fn inline(inline_message_id: &str) -> Self {
+        Self {
+            business_connection_id: None,
+            chat_id: None,
+            message_id: None,
+            inline_message_id: Some(inline_message_id.to_owned()),
+            reply_markup: None,
+        }
+    }
+
+    pub fn set_reply_markup(&mut self, markup: &InlineKeyboardMarkup) -> &mut Self {
+        self.reply_markup = Some(markup.clone());
+        self
+    }
+}
+
+impl_formatted_caption!(EditMessageCaption);
@@ -1,4 +1,4 @@
-use crate::model::ReplyMarkup;
+use crate::model::{CallbackQuery, ReplyMarkup};
 use serde::{Deserialize, Serialize};
 
 /// struct for holding data needed to call
@@ -8,6 +8,10 @@ use serde::{Deserialize, Serialize};
 /// ../../api/trait.API.html#method.send_game
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendGame {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Short name of the game, serves as the unique identifier for the game.
@@ -16,9 +20,14 @@ pub struct SendGame {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
+    /// Pass True, if the message should be sent even if the specified
+    /// replied-to message is not found
+    pub allow_sending_without_reply: bool,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -55,6 +64,61 @@ pub struct SetGameScore {
     pub inline_message_id: Option<String>,
 }
 
+impl SetGameScore {
+    /// Sets the score for the game in the message sent by the bot
+    pub fn new(chat_id: i64, message_id: i64, user_id: i64, score: i64) -> Self {
+        Self {
+            user_id,
+            score,
+            force: false,
+            disable_edit_message: false,
+            chat_id: Some(chat_id),
+            message_id: Some(message_id),
+            inline_message_id: None,
+        }
+    }
+
+    /// Sets the score for the game in the message sent via the bot in inline
+    /// mode
+    pub fn inline(inline_message_id: &str, user_id: i64, score: i64) -> Self {
+        Self {
+            user_id,
+            score,
+            force: false,
+            disable_edit_message: false,
+            chat_id: None,
+            message_id: None,
+            inline_message_id: Some(inline_message_id.to_owned()),
+        }
+    }
+
+    /// Sets the score of the user who pressed the play button of the game,
+    /// returning `None` if the query doesn't identify a message
+    pub fn for_callback_query(query: &CallbackQuery, score: i64) -> Option<Self> {
+        if let Some(id) = &query.inline_message_id {
+            return Some(Self::inline(id, query.from.id, score));
+        }
+
+        let message = query.message.as_ref()?;
+        Some(Self::new(
+            message.chat.get_id(),
+            message.message_id,
+            query.from.id,
+            score,
+        ))
+    }
+
+    pub fn toggle_force(&mut self) -> &mut Self {
+        self.force = !self.force;
+        self
+    }
+
+    pub fn toggle_disable_edit_message(&mut self) -> &mut Self {
+        self.disable_edit_message = !self.disable_edit_message;
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`get_game_high_scores`]
 ///
@@ -77,3 +141,27 @@ pub struct GetGameHighScores {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub inline_message_id: Option<String>,
 }
+
+impl GetGameHighScores {
+    /// Gets the high scores around the user for the game in the message sent by
+    /// the bot
+    pub fn new(chat_id: i64, message_id: i64, user_id: i64) -> Self {
+        Self {
+            user_id,
+            chat_id: Some(chat_id),
+            message_id: Some(message_id),
+            inline_message_id: None,
+        }
+    }
+
+    /// Gets the high scores around the user for the game in the message sent
+    /// via the bot in inline mode
+    pub fn inline(inline_message_id: &str, user_id: i64) -> Self {
+        Self {
+            user_id,
+            chat_id: None,
+            message_id: None,
+            inline_message_id: Some(inline_message_id.to_owned()),
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use crate::model::{InlineKeyboardMarkup, MessageEntity, ParseMode, LabeledPrice};
+#[cfg(feature = "payments")]
+use crate::model::LabeledPrice;
+use crate::{
+    helpers::formatting::FormattedText,
+    model::{InlineKeyboardMarkup, MessageEntity, ParseMode},
+};
 use serde::{Deserialize, Serialize};
 
 /// struct for holding data needed to call
@@ -589,10 +594,31 @@ pub struct InputTextMessageContent {
     /// fixed-width text or inline URLs in your bot's message.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in message text, which can be
+    /// specified instead of `parse_mode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<MessageEntity>>,
     /// Disables link previews for links in the sent message
     pub disable_web_page_preview: bool,
 }
 
+impl From<&FormattedText> for InputTextMessageContent {
+    fn from(text: &FormattedText) -> Self {
+        Self {
+            message_text: text.text.clone(),
+            parse_mode: None,
+            entities: text.entities(),
+            disable_web_page_preview: false,
+        }
+    }
+}
+
+impl From<FormattedText> for InputMessageContent {
+    fn from(text: FormattedText) -> Self {
+        Self::Text(InputTextMessageContent::from(&text))
+    }
+}
+
 /// Represents the content of a location message to be sent as the result of an
 /// inline query.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -664,6 +690,7 @@ pub struct InputContactMessageContent {
 }
 
 /// Represents the content of an invoice message to be sent as the result of an inline query.
+#[cfg(feature = "payments")]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InputInvoiceMessageContent {
     /// Product name, 1-32 characters
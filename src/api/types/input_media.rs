@@ -1,5 +1,8 @@
 use super::InputFile;
-use crate::model::{MessageEntity, ParseMode};
+use crate::{
+    helpers::formatting::FormattedText,
+    model::{MessageEntity, ParseMode},
+};
 use serde::{Deserialize, Serialize};
 
 /// This object represents the content of a media message to be sent
@@ -39,6 +42,17 @@ pub struct InputMediaPhoto {
     pub caption_entities: Option<Vec<MessageEntity>>,
 }
 
+impl InputMediaPhoto {
+    pub fn new(media: InputFile) -> Self {
+        Self {
+            media,
+            caption: None,
+            parse_mode: None,
+            caption_entities: None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InputMediaVideo {
     /// File to send. Pass a file_id to send a file that exists on the Telegram
@@ -70,6 +84,21 @@ pub struct InputMediaVideo {
     pub supports_streaming: bool,
 }
 
+impl InputMediaVideo {
+    pub fn new(media: InputFile) -> Self {
+        Self {
+            media,
+            caption: None,
+            parse_mode: None,
+            caption_entities: None,
+            duration: None,
+            width: None,
+            height: None,
+            supports_streaming: false,
+        }
+    }
+}
+
 /// Represents an animation file (GIF or H.264/MPEG-4 AVC video without sound)
 /// to be sent.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -101,6 +130,20 @@ pub struct InputMediaAnimation {
     pub height: Option<i64>,
 }
 
+impl InputMediaAnimation {
+    pub fn new(media: InputFile) -> Self {
+        Self {
+            media,
+            caption: None,
+            parse_mode: None,
+            caption_entities: None,
+            duration: None,
+            width: None,
+            height: None,
+        }
+    }
+}
+
 /// Represents an audio file to be treated as music to be sent.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InputMediaAudio {
@@ -131,6 +174,20 @@ pub struct InputMediaAudio {
     pub title: Option<String>,
 }
 
+impl InputMediaAudio {
+    pub fn new(media: InputFile) -> Self {
+        Self {
+            media,
+            caption: None,
+            parse_mode: None,
+            caption_entities: None,
+            duration: None,
+            performer: None,
+            title: None,
+        }
+    }
+}
+
 /// Represents a general file to be sent.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InputMediaDocument {
@@ -156,6 +213,18 @@ pub struct InputMediaDocument {
     pub disable_content_type_detection: bool,
 }
 
+impl InputMediaDocument {
+    pub fn new(media: InputFile) -> Self {
+        Self {
+            media,
+            caption: None,
+            parse_mode: None,
+            caption_entities: None,
+            disable_content_type_detection: false,
+        }
+    }
+}
+
 impl InputMedia {
     pub fn get_media(&self) -> &InputFile {
         match self {
@@ -166,4 +235,76 @@ impl InputMedia {
             InputMedia::Document(m) => &m.media,
         }
     }
+
+    pub(crate) fn get_media_mut(&mut self) -> &mut InputFile {
+        match self {
+            InputMedia::Photo(m) => &mut m.media,
+            InputMedia::Video(m) => &mut m.media,
+            InputMedia::Audio(m) => &mut m.media,
+            InputMedia::Animation(m) => &mut m.media,
+            InputMedia::Document(m) => &mut m.media,
+        }
+    }
+}
+
+/// This object describes the paid media to be sent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum InputPaidMedia {
+    #[serde(rename = "photo")]
+    Photo(InputPaidMediaPhoto),
+    #[serde(rename = "video")]
+    Video(InputPaidMediaVideo),
+}
+
+/// The paid media to send is a photo.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputPaidMediaPhoto {
+    /// File to send. Pass a `file_id` to send a file that exists on the Telegram
+    /// servers (recommended), pass an HTTP URL for Telegram to get a file
+    /// from the Internet
+    pub media: InputFile,
+}
+
+/// The paid media to send is a video.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputPaidMediaVideo {
+    /// File to send. Pass a `file_id` to send a file that exists on the Telegram
+    /// servers (recommended), pass an HTTP URL for Telegram to get a file
+    /// from the Internet
+    pub media: InputFile,
+    /// Video width
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i64>,
+    /// Video height
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i64>,
+    /// Duration of the video in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+    /// If the uploaded video is suitable for streaming
+    pub supports_streaming: bool,
+}
+
+impl InputPaidMedia {
+    /// Gets the file to send, whether it's a photo or a video
+    pub fn get_media(&self) -> &InputFile {
+        match self {
+            InputPaidMedia::Photo(m) => &m.media,
+            InputPaidMedia::Video(m) => &m.media,
+        }
+    }
+
+    pub(crate) fn get_media_mut(&mut self) -> &mut InputFile {
+        match self {
+            InputPaidMedia::Photo(m) => &mut m.media,
+            InputPaidMedia::Video(m) => &mut m.media,
+        }
+    }
 }
+
+impl_formatted_caption!(InputMediaPhoto);
+impl_formatted_caption!(InputMediaVideo);
+impl_formatted_caption!(InputMediaAnimation);
+impl_formatted_caption!(InputMediaAudio);
+impl_formatted_caption!(InputMediaDocument);
@@ -1,30 +1,54 @@
 //! This modules provides all the objects describing the payloads to be send to
 //! the different telegram API endpoints
 
+macro_rules! impl_formatted_caption {
+    ($name:ident) => {
+        impl $name {
+            /// Sets the caption and its entities, removing any parse mode
+            pub fn set_formatted_caption(&mut self, caption: &FormattedText) -> &mut Self {
+                self.caption = Some(caption.text.clone());
+                self.caption_entities = caption.entities();
+                self.parse_mode = None;
+                self
+            }
+        }
+    };
+}
+
+mod bot;
 mod chat;
 mod commands;
 mod edit_messages;
+#[cfg(feature = "games")]
 mod games;
 mod inline;
 mod input_media;
 mod other;
+#[cfg(feature = "passport")]
 mod passport;
+#[cfg(feature = "payments")]
 mod payments;
 mod send_messages;
+#[cfg(feature = "stickers")]
 mod stickers;
 mod updates;
 mod webhooks;
 
+pub use bot::*;
 pub use chat::*;
 pub use commands::*;
 pub use edit_messages::*;
+#[cfg(feature = "games")]
 pub use games::*;
 pub use inline::*;
 pub use input_media::*;
 pub use other::*;
+#[cfg(feature = "passport")]
 pub use passport::*;
+#[cfg(feature = "payments")]
 pub use payments::*;
 pub use send_messages::*;
+#[cfg(feature = "stickers")]
 pub use stickers::*;
 pub use updates::{GetUpdates, UpdateType};
 pub use webhooks::*;
@@ -4,6 +4,7 @@ use crate::utils::{
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{fs::File, path::Path};
+use tokio::io::AsyncRead;
 
 /// struct for holding data needed to call
 /// [`get_user_profile_photos`]
@@ -76,11 +77,16 @@ pub enum TrueOrObject<T> {
     Object(T),
 }
 
-/// This object represents either the `file_id`, http url or the contents of a
-/// file to be uploaded.
+/// This object represents a file sent to telegram, which is either referenced
+/// by its `file_id` or url, or uploaded along with the request. Payloads with a
+/// file to upload are sent as a multipart form, others as JSON.
 #[derive(Debug, Clone, PartialEq)]
 pub enum InputFile {
-    String(String),
+    /// The `file_id` of a file already stored on the telegram servers
+    FileId(String),
+    /// An HTTP url telegram gets the file from
+    Url(String),
+    /// A file to upload, read from bytes, a path or a stream
     File(FormDataFile),
 }
 
@@ -91,34 +97,71 @@ impl InputFile {
         )?))
     }
 
+    /// References a file by its `file_id`, or by its url if it's an HTTP url
     pub fn new(string: &str) -> Self {
-        Self::String(string.to_owned())
+        if string.starts_with("http://") || string.starts_with("https://") {
+            Self::Url(string.to_owned())
+        } else {
+            Self::FileId(string.to_owned())
+        }
     }
 
+    /// Uploads the file at the path. It's streamed from the disk while
+    /// uploading instead of being read into memory, so large videos and
+    /// documents don't have to fit in memory.
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let mut file = File::open(&path)?;
+        // fails right away for missing files, instead of while uploading
+        std::fs::metadata(&path)?;
         let file_name = path.as_ref().file_name().ok_or_else(|| {
             TelegramError::InvalidArgument("file doesn't have a valid file name".to_owned())
         })?;
 
-        Self::new_file(
-            &mut file,
+        Ok(Self::File(FormDataFile::new_from_path(
+            path.as_ref(),
             file_name.to_str().ok_or_else(|| {
                 TelegramError::InvalidArgument("file doesn't have a valid file name".to_owned())
             })?,
-        )
+        )?))
+    }
+
+    /// Uploads the bytes as a file with the given name, e.g. an image rendered
+    /// in memory
+    pub fn from_bytes<B: Into<Vec<u8>>>(bytes: B, file_name: &str) -> Result<Self> {
+        Ok(Self::File(FormDataFile::new_from_bytes(
+            bytes.into(),
+            file_name,
+        )?))
+    }
+
+    /// Uploads the contents of the reader as a file with the given name. The
+    /// reader is streamed while uploading, so it can only be sent once.
+    pub fn from_reader<R>(reader: R, file_name: &str) -> Result<Self>
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        Ok(Self::File(FormDataFile::new_from_reader(
+            reader, file_name,
+        )?))
+    }
+
+    /// Gets the file to upload, if it isn't referenced by its `file_id` or url
+    pub fn upload(&self) -> Option<&FormDataFile> {
+        match self {
+            Self::File(f) => Some(f),
+            Self::FileId(_) | Self::Url(_) => None,
+        }
     }
 }
 
 impl From<String> for InputFile {
     fn from(string: String) -> Self {
-        Self::String(string)
+        Self::new(&string)
     }
 }
 
 impl From<&str> for InputFile {
     fn from(string: &str) -> Self {
-        Self::String(string.to_owned())
+        Self::new(string)
     }
 }
 
@@ -134,15 +177,8 @@ impl Serialize for InputFile {
         S: Serializer,
     {
         match self {
-            Self::String(ref c) => serializer.serialize_str(c),
-            Self::File(ref c) => serializer.serialize_str(&format!(
-                "attach://{}",
-                &c.file_name
-                    .as_ref()
-                    .ok_or_else(|| serde::ser::Error::custom(
-                        "file name doesn't exist for the InputFile file"
-                    ))?
-            )),
+            Self::FileId(ref c) | Self::Url(ref c) => serializer.serialize_str(c),
+            Self::File(ref c) => serializer.serialize_str(&format!("attach://{}", c.name)),
         }
     }
 }
@@ -152,6 +188,7 @@ impl<'de> Deserialize<'de> for InputFile {
     where
         D: Deserializer<'de>,
     {
-        Ok(Self::String(Deserialize::deserialize(deserializer)?))
+        let string: String = Deserialize::deserialize(deserializer)?;
+        Ok(Self::new(&string))
     }
 }
@@ -1,4 +1,4 @@
-use crate::model::TelegramPassportElement;
+use crate::model::{EncryptedPassportElement, TelegramPassportElement};
 use serde::{Deserialize, Serialize};
 
 /// struct for holding data needed to call
@@ -14,6 +14,21 @@ pub struct SetPassportDataErrors {
     pub errors: Vec<PassportElementError>,
 }
 
+impl SetPassportDataErrors {
+    pub fn new(user_id: i64, errors: Vec<PassportElementError>) -> Self {
+        Self {
+            user_id,
+            errors,
+        }
+    }
+
+    /// Adds an error to the errors reported to the user
+    pub fn add_error(&mut self, error: PassportElementError) -> &mut Self {
+        self.errors.push(error);
+        self
+    }
+}
+
 /// This object represents an error in the Telegram Passport element which was
 /// submitted that should be resolved by the user
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -39,6 +54,188 @@ pub enum PassportElementError {
     Unspecified(PassportElementErrorUnspecified),
 }
 
+/// The sections of documents with a front side and a selfie
+const DOCUMENTS: &[TelegramPassportElement] = &[
+    TelegramPassportElement::Passport,
+    TelegramPassportElement::DriverLicense,
+    TelegramPassportElement::IdentityCard,
+    TelegramPassportElement::InternalPassport,
+];
+
+/// The sections consisting of a list of scans
+const SCANS: &[TelegramPassportElement] = &[
+    TelegramPassportElement::UtilityBill,
+    TelegramPassportElement::BankStatement,
+    TelegramPassportElement::RentalAgreement,
+    TelegramPassportElement::PassportRegistration,
+    TelegramPassportElement::TemporaryRegistration,
+];
+
+impl PassportElementError {
+    /// An issue in the data field with the given name
+    pub fn data_field(
+        section_type: TelegramPassportElement,
+        field_name: &str,
+        data_hash: &str,
+        message: &str,
+    ) -> Self {
+        Self::DataField(PassportElementErrorDataField {
+            section_type,
+            field_name: field_name.to_owned(),
+            data_hash: data_hash.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue with the front side of a document
+    pub fn front_side(
+        section_type: TelegramPassportElement,
+        file_hash: &str,
+        message: &str,
+    ) -> Self {
+        Self::FrontSide(PassportElementErrorFrontSide {
+            section_type,
+            file_hash: file_hash.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue with the reverse side of a document
+    pub fn reverse_side(
+        section_type: TelegramPassportElement,
+        file_hash: &str,
+        message: &str,
+    ) -> Self {
+        Self::ReverseSide(PassportElementErrorReverseSide {
+            section_type,
+            file_hash: file_hash.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue with the selfie with a document
+    pub fn selfie(section_type: TelegramPassportElement, file_hash: &str, message: &str) -> Self {
+        Self::Selfie(PassportElementErrorSelfie {
+            section_type,
+            file_hash: file_hash.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue with a document scan
+    pub fn file(section_type: TelegramPassportElement, file_hash: &str, message: &str) -> Self {
+        Self::File(PassportElementErrorFile {
+            section_type,
+            file_hash: file_hash.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue with the list of scans
+    pub fn files(
+        section_type: TelegramPassportElement,
+        file_hashes: Vec<String>,
+        message: &str,
+    ) -> Self {
+        Self::Files(PassportElementErrorFiles {
+            section_type,
+            file_hashes,
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue with one of the files of the translation of a document
+    pub fn translation_file(
+        section_type: TelegramPassportElement,
+        file_hash: &str,
+        message: &str,
+    ) -> Self {
+        Self::TranslationFile(PassportElementErrorTranslationFile {
+            section_type,
+            file_hash: file_hash.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue with the translation of a document
+    pub fn translation_files(
+        section_type: TelegramPassportElement,
+        file_hashes: Vec<String>,
+        message: &str,
+    ) -> Self {
+        Self::TranslationFiles(PassportElementErrorTranslationFiles {
+            section_type,
+            file_hashes,
+            message: message.to_owned(),
+        })
+    }
+
+    /// An issue in an unspecified place of the element, resolved when the
+    /// user submits it again
+    pub fn unspecified(element: &EncryptedPassportElement, message: &str) -> Self {
+        Self::Unspecified(PassportElementErrorUnspecified {
+            section_type: element.element_type,
+            element_hash: element.hash.clone(),
+            message: message.to_owned(),
+        })
+    }
+
+    /// The section of the Telegram Passport which has the issue
+    pub fn section_type(&self) -> TelegramPassportElement {
+        match self {
+            Self::DataField(e) => e.section_type,
+            Self::FrontSide(e) => e.section_type,
+            Self::ReverseSide(e) => e.section_type,
+            Self::Selfie(e) => e.section_type,
+            Self::File(e) => e.section_type,
+            Self::Files(e) => e.section_type,
+            Self::TranslationFile(e) => e.section_type,
+            Self::TranslationFiles(e) => e.section_type,
+            Self::Unspecified(e) => e.section_type,
+        }
+    }
+
+    /// The source of the error, e.g. `front_side`
+    pub fn source(&self) -> &'static str {
+        match self {
+            Self::DataField(_) => "data",
+            Self::FrontSide(_) => "front_side",
+            Self::ReverseSide(_) => "reverse_side",
+            Self::Selfie(_) => "selfie",
+            Self::File(_) => "file",
+            Self::Files(_) => "files",
+            Self::TranslationFile(_) => "translation_file",
+            Self::TranslationFiles(_) => "translation_files",
+            Self::Unspecified(_) => "unspecified",
+        }
+    }
+
+    /// Returns whether telegram accepts errors from this source for the
+    /// section, e.g. only driver licenses and identity cards have a reverse
+    /// side
+    pub fn is_valid(&self) -> bool {
+        let section = self.section_type();
+        match self {
+            Self::DataField(_) => {
+                matches!(
+                    section,
+                    TelegramPassportElement::PersonalDetails | TelegramPassportElement::Address
+                ) || DOCUMENTS.contains(&section)
+            },
+            Self::FrontSide(_) | Self::Selfie(_) => DOCUMENTS.contains(&section),
+            Self::ReverseSide(_) => matches!(
+                section,
+                TelegramPassportElement::DriverLicense | TelegramPassportElement::IdentityCard
+            ),
+            Self::File(_) | Self::Files(_) => SCANS.contains(&section),
+            Self::TranslationFile(_) | Self::TranslationFiles(_) => {
+                DOCUMENTS.contains(&section) || SCANS.contains(&section)
+            },
+            Self::Unspecified(_) => true,
+        }
+    }
+}
+
 /// Represents an issue in one of the data fields that was provided by the user.
 /// The error is considered resolved when the field's value changes.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -1,4 +1,11 @@
-use crate::model::{LabeledPrice, ReplyMarkup, ShippingOption};
+use crate::model::{
+    LabeledPrice,
+    PreCheckoutQuery,
+    ReplyMarkup,
+    ShippingOption,
+    ShippingQuery,
+    STARS_CURRENCY,
+};
 use serde::{Deserialize, Serialize};
 
 /// struct for holding data needed to call
@@ -17,7 +24,8 @@ pub struct SendInvoice {
     /// Bot-defined invoice payload, 1-128 bytes.
     /// This will not be displayed to the user, use for your internal processes.
     pub payload: String,
-    /// Payments provider token, obtained via [Botfather](https://t.me/botfather)
+    /// Payments provider token, obtained via [Botfather](https://t.me/botfather).
+    /// Pass an empty string for payments in [Telegram Stars][STARS_CURRENCY].
     pub provider_token: String,
     /// The maximum accepted amount for tips in the smallest units of the currency (integer, not
     /// float/double). For example, for a maximum tip of `US$ 1.45` pass `max_tip_amount = 145`. See
@@ -38,7 +46,8 @@ pub struct SendInvoice {
     /// start parameter.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub start_parameter: Option<String>,
-    /// Three-letter ISO 4217 currency code
+    /// Three-letter ISO 4217 currency code, or [`STARS_CURRENCY`] for payments
+    /// in Telegram Stars
     pub currency: String,
     /// Price breakdown, a list of components (e.g. product price, tax,
     /// discount, delivery cost, delivery tax, bonus, etc.)
@@ -80,14 +89,172 @@ pub struct SendInvoice {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
+    /// Pass True, if the message should be sent even if the specified
+    /// replied-to message is not found
+    pub allow_sending_without_reply: bool,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+impl SendInvoice {
+    pub fn new(
+        chat_id: i64,
+        title: &str,
+        description: &str,
+        payload: &str,
+        provider_token: &str,
+        currency: &str,
+        prices: Vec<LabeledPrice>,
+    ) -> Self {
+        Self {
+            provider_token: provider_token.to_owned(),
+            currency: currency.to_owned(),
+            prices,
+            ..Self::new_stars(chat_id, title, description, payload, 0)
+        }
+    }
+
+    /// Creates an invoice for digital goods or services, to be paid with the
+    /// given amount of Telegram Stars
+    pub fn new_stars(
+        chat_id: i64,
+        title: &str,
+        description: &str,
+        payload: &str,
+        amount: i64,
+    ) -> Self {
+        Self {
+            chat_id,
+            title: title.to_owned(),
+            description: description.to_owned(),
+            payload: payload.to_owned(),
+            provider_token: String::new(),
+            max_tip_amount: None,
+            suggested_tip_amounts: None,
+            start_parameter: None,
+            currency: STARS_CURRENCY.to_owned(),
+            prices: vec![LabeledPrice {
+                label: title.to_owned(),
+                amount,
+            }],
+            provider_data: None,
+            photo_url: None,
+            photo_size: None,
+            photo_width: None,
+            photo_height: None,
+            need_name: false,
+            need_phone_number: false,
+            need_email: false,
+            need_shipping_address: false,
+            send_phone_number_to_provider: false,
+            send_email_to_provider: false,
+            is_flexible: false,
+            disable_notification: false,
+            protect_content: false,
+            reply_to_message_id: None,
+            allow_sending_without_reply: false,
+            reply_markup: None,
+        }
+    }
+
+    /// Lets the user add a tip of at most `max_tip_amount`, suggesting the
+    /// given amounts. At most 4 amounts can be suggested, in strictly
+    /// increasing order. Tips aren't supported for payments in Telegram Stars.
+    pub fn set_tips(&mut self, max_tip_amount: i64, suggested_tip_amounts: Vec<i64>) -> &mut Self {
+        self.max_tip_amount = Some(max_tip_amount);
+        self.suggested_tip_amounts = Some(suggested_tip_amounts);
+        self
+    }
+
+    pub fn set_start_parameter(&mut self, start_parameter: &str) -> &mut Self {
+        self.start_parameter = Some(start_parameter.to_owned());
+        self
+    }
+
+    pub fn set_provider_data(&mut self, provider_data: &str) -> &mut Self {
+        self.provider_data = Some(provider_data.to_owned());
+        self
+    }
+
+    pub fn set_photo(&mut self, url: &str, width: i64, height: i64) -> &mut Self {
+        self.photo_url = Some(url.to_owned());
+        self.photo_width = Some(width);
+        self.photo_height = Some(height);
+        self
+    }
+
+    pub fn set_photo_size(&mut self, size: i64) -> &mut Self {
+        self.photo_size = Some(size);
+        self
+    }
+
+    pub fn set_reply_to_message_id(&mut self, id: i64) -> &mut Self {
+        self.reply_to_message_id = Some(id);
+        self
+    }
+
+    pub fn set_reply_markup(&mut self, markup: &ReplyMarkup) -> &mut Self {
+        self.reply_markup = Some(markup.to_owned());
+        self
+    }
+
+    pub fn toggle_need_name(&mut self) -> &mut Self {
+        self.need_name = !self.need_name;
+        self
+    }
+
+    pub fn toggle_need_phone_number(&mut self) -> &mut Self {
+        self.need_phone_number = !self.need_phone_number;
+        self
+    }
+
+    pub fn toggle_need_email(&mut self) -> &mut Self {
+        self.need_email = !self.need_email;
+        self
+    }
+
+    pub fn toggle_need_shipping_address(&mut self) -> &mut Self {
+        self.need_shipping_address = !self.need_shipping_address;
+        self
+    }
+
+    pub fn toggle_send_phone_number_to_provider(&mut self) -> &mut Self {
+        self.send_phone_number_to_provider = !self.send_phone_number_to_provider;
+        self
+    }
+
+    pub fn toggle_send_email_to_provider(&mut self) -> &mut Self {
+        self.send_email_to_provider = !self.send_email_to_provider;
+        self
+    }
+
+    pub fn toggle_is_flexible(&mut self) -> &mut Self {
+        self.is_flexible = !self.is_flexible;
+        self
+    }
+
+    pub fn toggle_disable_notification(&mut self) -> &mut Self {
+        self.disable_notification = !self.disable_notification;
+        self
+    }
+
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+
+    pub fn toggle_allow_sending_without_reply(&mut self) -> &mut Self {
+        self.allow_sending_without_reply = !self.allow_sending_without_reply;
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`answer_shipping_query`]
 ///
@@ -113,6 +280,30 @@ pub struct AnswerShippingQuery {
     pub error_message: Option<String>,
 }
 
+impl AnswerShippingQuery {
+    /// Accepts the shipping address of the query, letting the user choose
+    /// from the given shipping options
+    pub fn ok(query: &ShippingQuery, shipping_options: Vec<ShippingOption>) -> Self {
+        Self {
+            shipping_query_id: query.id.clone(),
+            ok: true,
+            shipping_options: Some(shipping_options),
+            error_message: None,
+        }
+    }
+
+    /// Rejects the shipping address of the query, showing the error message
+    /// to the user
+    pub fn error(query: &ShippingQuery, error_message: &str) -> Self {
+        Self {
+            shipping_query_id: query.id.clone(),
+            ok: false,
+            shipping_options: None,
+            error_message: Some(error_message.to_owned()),
+        }
+    }
+}
+
 /// struct for holding data needed to call
 /// [`answer_pre_checkout_query`]
 ///
@@ -135,3 +326,52 @@ pub struct AnswerPreCheckoutQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
 }
+
+impl AnswerPreCheckoutQuery {
+    /// Confirms the order, letting telegram complete the payment
+    pub fn ok(query: &PreCheckoutQuery) -> Self {
+        Self {
+            pre_checkout_query_id: query.id.clone(),
+            ok: true,
+            error_message: None,
+        }
+    }
+
+    /// Cancels the order, showing the error message to the user
+    pub fn error(query: &PreCheckoutQuery, error_message: &str) -> Self {
+        Self {
+            pre_checkout_query_id: query.id.clone(),
+            ok: false,
+            error_message: Some(error_message.to_owned()),
+        }
+    }
+}
+
+/// struct for holding data needed to call
+/// [`refund_star_payment`]
+///
+/// [`refund_star_payment`]:
+/// ../../api/trait.API.html#method.refund_star_payment
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RefundStarPayment {
+    /// Identifier of the user whose payment will be refunded
+    pub user_id: i64,
+    /// Telegram payment identifier
+    pub telegram_payment_charge_id: String,
+}
+
+/// struct for holding data needed to call
+/// [`get_star_transactions`]
+///
+/// [`get_star_transactions`]:
+/// ../../api/trait.API.html#method.get_star_transactions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GetStarTransactions {
+    /// Number of transactions to skip in the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// The maximum number of transactions to be retrieved. Values between
+    /// 1-100 are accepted. Defaults to 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
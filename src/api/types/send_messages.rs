@@ -1,8 +1,10 @@
-use super::{InputFile, InputMedia};
+use super::{InputFile, InputMedia, InputPaidMedia};
 use crate::{
+    helpers::formatting::FormattedText,
     model::{
         utils::unix_date_formatting,
         ChatAction,
+        DiceEmoji,
         MessageEntity,
         ParseMode,
         PhotoSize,
@@ -23,6 +25,10 @@ use std::path::Path;
 /// ../../api/trait.API.html#method.send_message
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendMessage {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Text of the message to be sen, 1-4096 characters after entities parsing
@@ -33,13 +39,15 @@ pub struct SendMessage {
     pub parse_mode: Option<ParseMode>,
     /// List of special entities that appear in message text, which can be
     /// specified instead of parse_mode
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "entities", skip_serializing_if = "Option::is_none")]
     pub enitites: Option<Vec<MessageEntity>>,
     /// Disables link previews for links in this message
     pub disable_web_page_preview: bool,
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -54,11 +62,13 @@ pub struct SendMessage {
 impl SendMessage {
     pub fn new(chat_id: i64, text: &str) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
             text: text.to_owned(),
             parse_mode: None,
             enitites: None,
             disable_notification: false,
+            protect_content: false,
             disable_web_page_preview: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
@@ -86,15 +96,38 @@ impl SendMessage {
         self
     }
 
+    pub fn set_business_connection_id(&mut self, id: &str) -> &mut Self {
+        self.business_connection_id = Some(id.to_owned());
+        self
+    }
+
     pub fn toggle_disable_notification(&mut self) -> &mut Self {
         self.disable_notification = !self.disable_notification;
         self
     }
 
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+
+    pub fn toggle_allow_sending_without_reply(&mut self) -> &mut Self {
+        self.allow_sending_without_reply = !self.allow_sending_without_reply;
+        self
+    }
+
     pub fn toggle_disable_web_page_preview(&mut self) -> &mut Self {
         self.disable_web_page_preview = !self.disable_web_page_preview;
         self
     }
+
+    /// Sets the text and its entities, removing any parse mode
+    pub fn set_formatted_text(&mut self, text: &FormattedText) -> &mut Self {
+        self.text.clone_from(&text.text);
+        self.enitites = text.entities();
+        self.parse_mode = None;
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -113,6 +146,8 @@ pub struct ForwardMessage {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
 }
 
 impl ForwardMessage {
@@ -122,6 +157,7 @@ impl ForwardMessage {
             from_chat_id,
             message_id,
             disable_notification: false,
+            protect_content: false,
         }
     }
 
@@ -130,16 +166,65 @@ impl ForwardMessage {
         self
     }
 
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+
     pub fn from_message(chat_id: i64, message: &Message) -> Self {
         Self {
             chat_id,
             from_chat_id: message.chat.get_id(),
             message_id: message.message_id,
             disable_notification: false,
+            protect_content: false,
         }
     }
 }
 
+/// struct for holding data needed to call
+/// [`forward_messages`]
+///
+/// [`forward_messages`]:
+/// ../../api/trait.API.html#method.forward_messages
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ForwardMessages {
+    /// Unique identifier for the target chat
+    pub chat_id: i64,
+    /// Unique identifier for the chat where the original messages were sent
+    pub from_chat_id: i64,
+    /// Identifiers of 1-100 messages in the chat `from_chat_id` to forward. The
+    /// identifiers must be specified in a strictly increasing order.
+    pub message_ids: Vec<i64>,
+    /// Sends the messages silently. Users will receive a notification with no
+    /// sound.
+    pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
+}
+
+impl ForwardMessages {
+    pub fn new(chat_id: i64, from_chat_id: i64, message_ids: Vec<i64>) -> Self {
+        Self {
+            chat_id,
+            from_chat_id,
+            message_ids,
+            disable_notification: false,
+            protect_content: false,
+        }
+    }
+
+    pub fn toggle_disable_notification(&mut self) -> &mut Self {
+        self.disable_notification = !self.disable_notification;
+        self
+    }
+
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+}
+
 /// struct for holding data needed to call [`copy_message`]
 ///
 /// [`copy_message`]: ../../api/trait.API.html#method.copy_message
@@ -147,6 +232,10 @@ impl ForwardMessage {
 pub struct CopyMessage {
     /// Unique identifier for the target chat
     pub chat_id: i64,
+    /// Unique identifier for the target message thread (topic) of the forum;
+    /// for forum supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
     /// Unique identifier for the chat where the original message was sent.
     pub from_chat_id: i64,
     /// Message identifier in the chat specified in from_chat_id
@@ -165,6 +254,8 @@ pub struct CopyMessage {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -180,12 +271,14 @@ impl CopyMessage {
     pub fn new(chat_id: i64, from_chat_id: i64, message_id: i64) -> Self {
         Self {
             chat_id,
+            message_thread_id: None,
             from_chat_id,
             message_id,
             caption: None,
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -195,17 +288,85 @@ impl CopyMessage {
     pub fn from_message(chat_id: i64, from: &Message) -> Self {
         Self {
             chat_id,
+            message_thread_id: None,
             from_chat_id: from.chat.get_id(),
             message_id: from.message_id,
             caption: None,
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
         }
     }
+
+    pub fn set_message_thread_id(&mut self, thread_id: i64) -> &mut Self {
+        self.message_thread_id = Some(thread_id);
+        self
+    }
+
+    pub fn set_reply_to_message_id(&mut self, message_id: i64) -> &mut Self {
+        self.reply_to_message_id = Some(message_id);
+        self
+    }
+
+    pub fn toggle_allow_sending_without_reply(&mut self) -> &mut Self {
+        self.allow_sending_without_reply = !self.allow_sending_without_reply;
+        self
+    }
+}
+
+/// struct for holding data needed to call
+/// [`copy_messages`]
+///
+/// [`copy_messages`]:
+/// ../../api/trait.API.html#method.copy_messages
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CopyMessages {
+    /// Unique identifier for the target chat
+    pub chat_id: i64,
+    /// Unique identifier for the chat where the original messages were sent
+    pub from_chat_id: i64,
+    /// Identifiers of 1-100 messages in the chat `from_chat_id` to copy. The
+    /// identifiers must be specified in a strictly increasing order.
+    pub message_ids: Vec<i64>,
+    /// Sends the messages silently. Users will receive a notification with no
+    /// sound.
+    pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
+    /// Pass True to copy the messages without their captions
+    pub remove_caption: bool,
+}
+
+impl CopyMessages {
+    pub fn new(chat_id: i64, from_chat_id: i64, message_ids: Vec<i64>) -> Self {
+        Self {
+            chat_id,
+            from_chat_id,
+            message_ids,
+            disable_notification: false,
+            protect_content: false,
+            remove_caption: false,
+        }
+    }
+
+    pub fn toggle_disable_notification(&mut self) -> &mut Self {
+        self.disable_notification = !self.disable_notification;
+        self
+    }
+
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+
+    pub fn toggle_remove_caption(&mut self) -> &mut Self {
+        self.remove_caption = !self.remove_caption;
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -215,6 +376,10 @@ impl CopyMessage {
 /// ../../api/trait.API.html#method.send_photo
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendPhoto {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Photo to send. Pass a file_id as String to send a photo that exists on
@@ -236,6 +401,8 @@ pub struct SendPhoto {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -250,12 +417,14 @@ pub struct SendPhoto {
 impl SendPhoto {
     pub fn new(chat_id: i64, photo: String) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            photo: InputFile::String(photo),
+            photo: InputFile::from(photo),
             caption: None,
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -264,12 +433,14 @@ impl SendPhoto {
 
     pub fn from_photo_size(chat_id: i64, photo: &PhotoSize) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            photo: InputFile::String(photo.file_id.clone()),
+            photo: InputFile::FileId(photo.file_id.clone()),
             caption: None,
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -278,12 +449,14 @@ impl SendPhoto {
 
     pub fn from_file<P: AsRef<Path>>(chat_id: i64, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             photo: InputFile::from_path(path)?,
             caption: None,
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -298,6 +471,10 @@ impl SendPhoto {
 /// ../../api/trait.API.html#method.send_audio
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendAudio {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Audio to send. Pass a file_id as String to send an audio file that
@@ -335,6 +512,8 @@ pub struct SendAudio {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -349,8 +528,9 @@ pub struct SendAudio {
 impl SendAudio {
     pub fn new(chat_id: i64, audio: String) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            audio: InputFile::String(audio),
+            audio: InputFile::from(audio),
             thumb: None,
             caption: None,
             caption_entities: None,
@@ -359,6 +539,7 @@ impl SendAudio {
             duration: None,
             title: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -367,6 +548,7 @@ impl SendAudio {
 
     pub fn from_file<P: AsRef<Path>>(chat_id: i64, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             audio: InputFile::from_path(path)?,
             thumb: None,
@@ -377,11 +559,35 @@ impl SendAudio {
             title: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
         })
     }
+
+    /// Sets the thumbnail of the file, a jpeg of at most 200kb and 320px wide
+    /// and high. Thumbnails can't be reused, so they have to be uploaded as a
+    /// new file.
+    pub fn set_thumb(&mut self, thumb: InputFile) -> &mut Self {
+        self.thumb = Some(thumb);
+        self
+    }
+
+    pub fn set_duration(&mut self, seconds: i64) -> &mut Self {
+        self.duration = Some(seconds);
+        self
+    }
+
+    pub fn set_performer(&mut self, performer: &str) -> &mut Self {
+        self.performer = Some(performer.to_owned());
+        self
+    }
+
+    pub fn set_title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(title.to_owned());
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -391,6 +597,10 @@ impl SendAudio {
 /// ../../api/trait.API.html#method.send_document
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendDocument {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Document to send. Pass a file_id as String to send a photo that exists
@@ -422,6 +632,8 @@ pub struct SendDocument {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -436,13 +648,15 @@ pub struct SendDocument {
 impl SendDocument {
     pub fn new(chat_id: i64, document: String) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            document: InputFile::String(document),
+            document: InputFile::from(document),
             thumb: None,
             caption: None,
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             disable_content_type_detection: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
@@ -452,6 +666,7 @@ impl SendDocument {
 
     pub fn from_file<P: AsRef<Path>>(chat_id: i64, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             document: InputFile::from_path(path)?,
             thumb: None,
@@ -459,12 +674,26 @@ impl SendDocument {
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             disable_content_type_detection: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
         })
     }
+
+    /// Sets the thumbnail of the file, a jpeg of at most 200kb and 320px wide
+    /// and high. Thumbnails can't be reused, so they have to be uploaded as a
+    /// new file.
+    pub fn set_thumb(&mut self, thumb: InputFile) -> &mut Self {
+        self.thumb = Some(thumb);
+        self
+    }
+
+    pub fn toggle_disable_content_type_detection(&mut self) -> &mut Self {
+        self.disable_content_type_detection = !self.disable_content_type_detection;
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -474,6 +703,10 @@ impl SendDocument {
 /// ../../api/trait.API.html#method.send_video
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendVideo {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Video to send. Pass a file_id as String to send an video file that
@@ -517,6 +750,8 @@ pub struct SendVideo {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the uploaded video is suitable for streaming
     pub supports_streaming: bool,
     /// If the message is a reply, ID of the original message
@@ -533,8 +768,9 @@ pub struct SendVideo {
 impl SendVideo {
     pub fn new(chat_id: i64, video: String) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            video: InputFile::String(video),
+            video: InputFile::from(video),
             thumb: None,
             caption: None,
             caption_entities: None,
@@ -546,6 +782,7 @@ impl SendVideo {
             supports_streaming: false,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -554,6 +791,7 @@ impl SendVideo {
 
     pub fn from_file<P: AsRef<Path>>(chat_id: i64, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             video: InputFile::from_path(path)?,
             thumb: None,
@@ -567,11 +805,36 @@ impl SendVideo {
             supports_streaming: false,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
         })
     }
+
+    /// Sets the thumbnail of the file, a jpeg of at most 200kb and 320px wide
+    /// and high. Thumbnails can't be reused, so they have to be uploaded as a
+    /// new file.
+    pub fn set_thumb(&mut self, thumb: InputFile) -> &mut Self {
+        self.thumb = Some(thumb);
+        self
+    }
+
+    pub fn set_duration(&mut self, seconds: i64) -> &mut Self {
+        self.duration = Some(seconds);
+        self
+    }
+
+    pub fn set_dimensions(&mut self, width: i64, height: i64) -> &mut Self {
+        self.width = Some(width);
+        self.height = Some(height);
+        self
+    }
+
+    pub fn toggle_supports_streaming(&mut self) -> &mut Self {
+        self.supports_streaming = !self.supports_streaming;
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -581,6 +844,10 @@ impl SendVideo {
 /// ../../api/trait.API.html#method.send_animation
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendAnimation {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Animation to send. Pass a file_id as String to send an animation file
@@ -624,6 +891,8 @@ pub struct SendAnimation {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -638,8 +907,9 @@ pub struct SendAnimation {
 impl SendAnimation {
     pub fn new(chat_id: i64, animation: String) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            animation: InputFile::String(animation),
+            animation: InputFile::from(animation),
             thumb: None,
             caption: None,
             caption_entities: None,
@@ -650,6 +920,7 @@ impl SendAnimation {
             title: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -658,6 +929,7 @@ impl SendAnimation {
 
     pub fn from_file<P: AsRef<Path>>(chat_id: i64, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             animation: InputFile::from_path(path)?,
             thumb: None,
@@ -670,11 +942,31 @@ impl SendAnimation {
             title: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
         })
     }
+
+    /// Sets the thumbnail of the file, a jpeg of at most 200kb and 320px wide
+    /// and high. Thumbnails can't be reused, so they have to be uploaded as a
+    /// new file.
+    pub fn set_thumb(&mut self, thumb: InputFile) -> &mut Self {
+        self.thumb = Some(thumb);
+        self
+    }
+
+    pub fn set_duration(&mut self, seconds: i64) -> &mut Self {
+        self.duration = Some(seconds);
+        self
+    }
+
+    pub fn set_dimensions(&mut self, width: i64, height: i64) -> &mut Self {
+        self.width = Some(width);
+        self.height = Some(height);
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -684,6 +976,10 @@ impl SendAnimation {
 /// ../../api/trait.API.html#method.send_voice
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendVoice {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Voice to send. Pass a file_id as String to send an voice file that
@@ -708,6 +1004,8 @@ pub struct SendVoice {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -722,13 +1020,15 @@ pub struct SendVoice {
 impl SendVoice {
     pub fn new(chat_id: i64, voice: String) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            voice: InputFile::String(voice),
+            voice: InputFile::from(voice),
             caption: None,
             caption_entities: None,
             duration: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -737,6 +1037,7 @@ impl SendVoice {
 
     pub fn from_file<P: AsRef<Path>>(chat_id: i64, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             voice: InputFile::from_path(path)?,
             duration: None,
@@ -744,11 +1045,17 @@ impl SendVoice {
             caption_entities: None,
             parse_mode: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
         })
     }
+
+    pub fn set_duration(&mut self, seconds: i64) -> &mut Self {
+        self.duration = Some(seconds);
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -758,6 +1065,10 @@ impl SendVoice {
 /// ../../api/trait.API.html#method.send_video_note
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendVideoNote {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// VideoNote to send. Pass a file_id as String to send an video_note file
@@ -780,6 +1091,8 @@ pub struct SendVideoNote {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -794,12 +1107,14 @@ pub struct SendVideoNote {
 impl SendVideoNote {
     pub fn new(chat_id: i64, note: String) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
-            video_note: InputFile::String(note),
+            video_note: InputFile::from(note),
             thumb: None,
             duration: None,
             length: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
@@ -808,17 +1123,38 @@ impl SendVideoNote {
 
     pub fn from_file<P: AsRef<Path>>(chat_id: i64, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             video_note: InputFile::from_path(path)?,
             thumb: None,
             duration: None,
             length: None,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
             reply_markup: None,
         })
     }
+
+    /// Sets the thumbnail of the file, a jpeg of at most 200kb and 320px wide
+    /// and high. Thumbnails can't be reused, so they have to be uploaded as a
+    /// new file.
+    pub fn set_thumb(&mut self, thumb: InputFile) -> &mut Self {
+        self.thumb = Some(thumb);
+        self
+    }
+
+    pub fn set_duration(&mut self, seconds: i64) -> &mut Self {
+        self.duration = Some(seconds);
+        self
+    }
+
+    /// Sets the diameter of the video note
+    pub fn set_length(&mut self, length: i64) -> &mut Self {
+        self.length = Some(length);
+        self
+    }
 }
 
 /// struct for sending photos, videos, documents or audios as an album
@@ -828,6 +1164,10 @@ impl SendVideoNote {
 /// ../../api/trait.API.html#method.send_media_group
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendMediaGroup {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Photos, videos, documents or audios as an album to be send, amount must
@@ -836,6 +1176,8 @@ pub struct SendMediaGroup {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -847,11 +1189,82 @@ pub struct SendMediaGroup {
 impl SendMediaGroup {
     pub fn new(chat_id: i64, media: Vec<InputMedia>) -> Self {
         Self {
+            business_connection_id: None,
+            chat_id,
+            media,
+            disable_notification: false,
+            protect_content: false,
+            reply_to_message_id: None,
+            allow_sending_without_reply: false,
+        }
+    }
+}
+
+/// struct for holding data needed to call
+/// [`send_paid_media`]
+///
+/// [`send_paid_media`]:
+/// ../../api/trait.API.html#method.send_paid_media
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SendPaidMedia {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
+    /// Unique identifier for the target chat
+    pub chat_id: i64,
+    /// The number of Telegram Stars that must be paid to buy access to the
+    /// media, 1-2500
+    pub star_count: i64,
+    /// The paid media to be sent, amount must be 1-10
+    pub media: Vec<InputPaidMedia>,
+    /// Media caption, 0-1024 characters after entities parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in the media caption
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True, if the caption must be shown above the message media
+    pub show_caption_above_media: bool,
+    /// Sends the message silently. Users will receive a notification with no
+    /// sound.
+    pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
+    /// If the message is a reply, ID of the original message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_to_message_id: Option<i64>,
+    /// Pass True, if the message should be sent even if the specified
+    /// replied-to message is not found
+    pub allow_sending_without_reply: bool,
+    /// Additional interface options.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<ReplyMarkup>,
+}
+
+impl SendPaidMedia {
+    /// Creates a payload sending the media to the chat, which users can view
+    /// after paying the given amount of Telegram Stars
+    pub fn new(chat_id: i64, star_count: i64, media: Vec<InputPaidMedia>) -> Self {
+        Self {
+            business_connection_id: None,
             chat_id,
+            star_count,
             media,
+            caption: None,
+            parse_mode: None,
+            caption_entities: None,
+            show_caption_above_media: false,
             disable_notification: false,
+            protect_content: false,
             reply_to_message_id: None,
             allow_sending_without_reply: false,
+            reply_markup: None,
         }
     }
 }
@@ -863,13 +1276,22 @@ impl SendMediaGroup {
 /// ../../api/trait.API.html#method.send_location
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendLocation {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Latitude of the location
     pub latitude: f64,
     /// Longitude of the location
     pub longitude: f64,
-    /// Period in seconds for which the location will be updated
+    /// The radius of uncertainty for the location, measured in meters; 0-1500
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub horizontal_accuracy: Option<f64>,
+    /// Period in seconds for which the location will be updated, should be
+    /// between 60 and 86400, or 0x7FFFFFFF for live locations that can be
+    /// edited indefinitely
     #[serde(skip_serializing_if = "Option::is_none")]
     pub live_period: Option<i64>,
     /// For live locations, a direction in which the user is moving, in degrees.
@@ -884,6 +1306,8 @@ pub struct SendLocation {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -895,6 +1319,68 @@ pub struct SendLocation {
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+impl SendLocation {
+    pub fn new(chat_id: i64, latitude: f64, longitude: f64) -> Self {
+        Self {
+            business_connection_id: None,
+            chat_id,
+            latitude,
+            longitude,
+            horizontal_accuracy: None,
+            live_period: None,
+            heading: None,
+            proximity_alert_radius: None,
+            disable_notification: false,
+            protect_content: false,
+            reply_to_message_id: None,
+            allow_sending_without_reply: false,
+            reply_markup: None,
+        }
+    }
+
+    /// Makes the location a live location, which can be edited for the
+    /// given amount of seconds
+    pub fn set_live_period(&mut self, seconds: i64) -> &mut Self {
+        self.live_period = Some(seconds);
+        self
+    }
+
+    pub fn set_horizontal_accuracy(&mut self, meters: f64) -> &mut Self {
+        self.horizontal_accuracy = Some(meters);
+        self
+    }
+
+    pub fn set_heading(&mut self, degrees: i64) -> &mut Self {
+        self.heading = Some(degrees);
+        self
+    }
+
+    pub fn set_proximity_alert_radius(&mut self, meters: i64) -> &mut Self {
+        self.proximity_alert_radius = Some(meters);
+        self
+    }
+
+    pub fn set_reply_to_message_id(&mut self, id: i64) -> &mut Self {
+        self.reply_to_message_id = Some(id);
+        self
+    }
+
+    pub fn set_reply_markup(&mut self, markup: &ReplyMarkup) -> &mut Self {
+        self.reply_markup = Some(markup.clone());
+        self
+    }
+
+    pub fn toggle_disable_notification(&mut self) -> &mut Self {
+        self.disable_notification = !self.disable_notification;
+        self
+    }
+
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`send_venue`]
 ///
@@ -902,6 +1388,10 @@ pub struct SendLocation {
 /// ../../api/trait.API.html#method.send_venue
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendVenue {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Latitude of the venue
@@ -923,6 +1413,8 @@ pub struct SendVenue {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -941,6 +1433,10 @@ pub struct SendVenue {
 /// ../../api/trait.API.html#method.send_contact
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendContact {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Contact's phone number
@@ -956,6 +1452,8 @@ pub struct SendContact {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -974,6 +1472,10 @@ pub struct SendContact {
 /// ../../api/trait.API.html#method.send_poll
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendPoll {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Poll question, 1-255 characters
@@ -998,12 +1500,14 @@ pub struct SendPoll {
     /// Text that is shown when a user chooses an incorrect answer or taps on
     /// the lamp icon in a quiz-style poll, 0-200 characters with at most 2 line
     /// feeds after entities parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub explanation: Option<String>,
     /// Mode for parsing entities in the explanation.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub explanation_parse_mode: Option<ParseMode>,
     /// List of special entities that appear in the poll explanation, which can
     /// be specified instead of parse_mode
+    #[serde(rename = "explanation_entities")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub explanation_enitites: Option<Vec<MessageEntity>>,
     /// Amount of time in seconds the poll will be active after creation, 5-600.
@@ -1013,6 +1517,8 @@ pub struct SendPoll {
     /// Point in time (Unix timestamp) when the poll will be automatically
     /// closed. Must be at least 5 and no more than 600 seconds in the future.
     /// Can't be used together with open_period.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(with = "unix_date_formatting::optional")]
     pub close_date: Option<DateTime<Utc>>,
     /// Pass True, if the poll needs to be immediately closed.
@@ -1020,6 +1526,8 @@ pub struct SendPoll {
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -1031,6 +1539,120 @@ pub struct SendPoll {
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+impl SendPoll {
+    pub fn new(chat_id: i64, question: &str, options: Vec<String>) -> Self {
+        Self {
+            business_connection_id: None,
+            chat_id,
+            question: question.to_owned(),
+            options,
+            is_anonymous: None,
+            poll_type: None,
+            allows_multiple_answers: false,
+            correct_option_id: None,
+            explanation: None,
+            explanation_parse_mode: None,
+            explanation_enitites: None,
+            open_period: None,
+            close_date: None,
+            is_closed: false,
+            disable_notification: false,
+            protect_content: false,
+            reply_to_message_id: None,
+            allow_sending_without_reply: false,
+            reply_markup: None,
+        }
+    }
+
+    /// Creates a poll in quiz mode, with the option at the given 0-based
+    /// index being the correct answer
+    pub fn quiz(
+        chat_id: i64,
+        question: &str,
+        options: Vec<String>,
+        correct_option_id: i64,
+    ) -> Self {
+        Self {
+            poll_type: Some(PollType::Quiz),
+            correct_option_id: Some(correct_option_id),
+            ..Self::new(chat_id, question, options)
+        }
+    }
+
+    pub fn set_anonymous(&mut self, is_anonymous: bool) -> &mut Self {
+        self.is_anonymous = Some(is_anonymous);
+        self
+    }
+
+    pub fn set_explanation(&mut self, explanation: &str) -> &mut Self {
+        self.explanation = Some(explanation.to_owned());
+        self
+    }
+
+    pub fn set_explanation_parse_mode(&mut self, mode: &ParseMode) -> &mut Self {
+        self.explanation_parse_mode = Some(mode.to_owned());
+        self
+    }
+
+    /// Sets the explanation to the formatted text, clearing the parse mode
+    pub fn set_formatted_explanation(&mut self, explanation: &FormattedText) -> &mut Self {
+        self.explanation = Some(explanation.text.clone());
+        self.explanation_enitites = explanation.entities();
+        self.explanation_parse_mode = None;
+        self
+    }
+
+    /// Closes the poll the given amount of seconds after it's sent, clearing
+    /// the close date
+    pub fn set_open_period(&mut self, seconds: i64) -> &mut Self {
+        self.open_period = Some(seconds);
+        self.close_date = None;
+        self
+    }
+
+    /// Closes the poll at the given point in time, clearing the open period
+    pub fn set_close_date(&mut self, date: DateTime<Utc>) -> &mut Self {
+        self.close_date = Some(date);
+        self.open_period = None;
+        self
+    }
+
+    pub fn set_reply_to_message_id(&mut self, id: i64) -> &mut Self {
+        self.reply_to_message_id = Some(id);
+        self
+    }
+
+    pub fn set_reply_markup(&mut self, markup: &ReplyMarkup) -> &mut Self {
+        self.reply_markup = Some(markup.to_owned());
+        self
+    }
+
+    pub fn toggle_allows_multiple_answers(&mut self) -> &mut Self {
+        self.allows_multiple_answers = !self.allows_multiple_answers;
+        self
+    }
+
+    pub fn toggle_closed(&mut self) -> &mut Self {
+        self.is_closed = !self.is_closed;
+        self
+    }
+
+    pub fn toggle_disable_notification(&mut self) -> &mut Self {
+        self.disable_notification = !self.disable_notification;
+        self
+    }
+
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+
+    pub fn toggle_allow_sending_without_reply(&mut self) -> &mut Self {
+        self.allow_sending_without_reply = !self.allow_sending_without_reply;
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`send_dice`]
 ///
@@ -1038,17 +1660,23 @@ pub struct SendPoll {
 /// ../../api/trait.API.html#method.send_dice
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendDice {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Emoji on which the dice throw animation is based.
-    /// Currently, must be one of “🎲”, “🎯”, “🏀”, “⚽”, “🎳”, or “🎰”.
     /// Dice can have values 1-6 for “🎲”, “🎯” and “🎳”, values 1-5 for “🏀”
     /// and “⚽”, and values 1-64 for “🎰”.
     /// Defauts to “🎲”
-    pub emoji: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<DiceEmoji>,
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -1060,6 +1688,46 @@ pub struct SendDice {
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+impl SendDice {
+    pub fn new(chat_id: i64, emoji: DiceEmoji) -> Self {
+        Self {
+            business_connection_id: None,
+            chat_id,
+            emoji: Some(emoji),
+            disable_notification: false,
+            protect_content: false,
+            reply_to_message_id: None,
+            allow_sending_without_reply: false,
+            reply_markup: None,
+        }
+    }
+
+    pub fn set_reply_to_message_id(&mut self, id: i64) -> &mut Self {
+        self.reply_to_message_id = Some(id);
+        self
+    }
+
+    pub fn set_reply_markup(&mut self, markup: &ReplyMarkup) -> &mut Self {
+        self.reply_markup = Some(markup.to_owned());
+        self
+    }
+
+    pub fn toggle_disable_notification(&mut self) -> &mut Self {
+        self.disable_notification = !self.disable_notification;
+        self
+    }
+
+    pub fn toggle_protect_content(&mut self) -> &mut Self {
+        self.protect_content = !self.protect_content;
+        self
+    }
+
+    pub fn toggle_allow_sending_without_reply(&mut self) -> &mut Self {
+        self.allow_sending_without_reply = !self.allow_sending_without_reply;
+        self
+    }
+}
+
 /// struct for holding data needed to call
 /// [`send_chat_action`]
 ///
@@ -1067,8 +1735,21 @@ pub struct SendDice {
 /// ../../api/trait.API.html#method.send_chat_action
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendChatAction {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
     /// Type of action to broadcast.
     pub action: ChatAction,
 }
+
+impl_formatted_caption!(CopyMessage);
+impl_formatted_caption!(SendPhoto);
+impl_formatted_caption!(SendAudio);
+impl_formatted_caption!(SendDocument);
+impl_formatted_caption!(SendVideo);
+impl_formatted_caption!(SendAnimation);
+impl_formatted_caption!(SendVoice);
+impl_formatted_caption!(SendPaidMedia);
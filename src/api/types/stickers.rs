@@ -1,6 +1,10 @@
 use super::InputFile;
-use crate::model::{MaskPosition, ReplyMarkup};
+use crate::{
+    model::{MaskPosition, MessageEntity, ReplyMarkup, StickerFormat, StickerType},
+    utils::result::{Result, TelegramError},
+};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 /// struct for holding data needed to call
 /// [`send_sticker`]
@@ -9,18 +13,27 @@ use serde::{Deserialize, Serialize};
 /// ../../api/trait.API.html#method.send_sticker
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendSticker {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: i64,
-    /// Sticker to send. Pass a file_id as String to send a file that exists on
+    /// Sticker to send. Pass a `file_id` as String to send a file that exists on
     /// the Telegram servers (recommended), pass an HTTP URL as a String for
     /// Telegram to get a .WEBP file from the Internet, or upload a new one
     pub sticker: InputFile,
     /// Sends the message silently. Users will receive a notification with no
     /// sound.
     pub disable_notification: bool,
+    /// Protects the contents of the sent message from forwarding and saving
+    pub protect_content: bool,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
+    /// Pass True, if the message should be sent even if the specified
+    /// replied-to message is not found
+    pub allow_sending_without_reply: bool,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -37,6 +50,39 @@ pub struct GetStickerSet {
     pub name: String,
 }
 
+/// struct for holding data needed to call
+/// [`get_custom_emoji_stickers`]
+///
+/// [`get_custom_emoji_stickers`]:
+/// ../../api/trait.API.html#method.get_custom_emoji_stickers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetCustomEmojiStickers {
+    /// List of custom emoji identifiers, at most 200
+    pub custom_emoji_ids: Vec<String>,
+}
+
+impl GetCustomEmojiStickers {
+    pub fn new(custom_emoji_ids: Vec<String>) -> Self {
+        Self {
+            custom_emoji_ids,
+        }
+    }
+
+    /// Gets the ids of the custom emoji used in the entities of a message,
+    /// without duplicates
+    pub fn from_entities(entities: &[MessageEntity]) -> Self {
+        let mut custom_emoji_ids: Vec<String> = Vec::new();
+        for entity in entities {
+            if let MessageEntity::CustomEmoji(e) = entity {
+                if !custom_emoji_ids.contains(&e.custom_emoji_id) {
+                    custom_emoji_ids.push(e.custom_emoji_id.clone());
+                }
+            }
+        }
+        Self::new(custom_emoji_ids)
+    }
+}
+
 /// struct for holding data needed to call
 /// [`upload_sticker_file`]
 ///
@@ -46,10 +92,79 @@ pub struct GetStickerSet {
 pub struct UploadStickerFile {
     /// User identifier of sticker file owner
     pub user_id: i64,
-    /// Png image with the sticker, must be up to 512 kilobytes in size,
-    /// dimensions must not exceed 512px, and either width or height must be
-    /// exactly 512px.
-    pub png_sticker: InputFile,
+    /// A file with the sticker in .WEBP, .PNG, .TGS, or .WEBM format, which
+    /// has to be uploaded
+    pub sticker: InputFile,
+    /// Format of the sticker
+    pub sticker_format: StickerFormat,
+}
+
+impl UploadStickerFile {
+    pub fn new(user_id: i64, sticker: InputFile, sticker_format: StickerFormat) -> Self {
+        Self {
+            user_id,
+            sticker,
+            sticker_format,
+        }
+    }
+
+    /// Reads the sticker to upload from the file at the path, taking its
+    /// format from the extension of the file
+    pub fn from_path<P: AsRef<Path>>(user_id: i64, path: P) -> Result<Self> {
+        let format = format_of(path.as_ref())?;
+        Ok(Self::new(user_id, InputFile::from_path(path)?, format))
+    }
+}
+
+/// This object describes a sticker to be added to a sticker set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputSticker {
+    /// The added sticker. Pass a `file_id` as a String to send a file that
+    /// already exists on the Telegram servers, pass an HTTP URL as a String
+    /// for Telegram to get a file from the Internet, or upload a new one.
+    /// Animated and video stickers can't be uploaded via HTTP URL.
+    pub sticker: InputFile,
+    /// Format of the added sticker
+    pub format: StickerFormat,
+    /// List of 1-20 emoji associated with the sticker
+    pub emoji_list: Vec<String>,
+    /// Position where the mask should be placed on faces, for mask stickers
+    /// only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask_position: Option<MaskPosition>,
+    /// List of 0-20 search keywords for the sticker with total length of up to
+    /// 64 characters, for regular and custom emoji stickers only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+}
+
+impl InputSticker {
+    pub fn new<S: ToString>(sticker: InputFile, format: StickerFormat, emoji_list: &[S]) -> Self {
+        Self {
+            sticker,
+            format,
+            emoji_list: emoji_list.iter().map(ToString::to_string).collect(),
+            mask_position: None,
+            keywords: None,
+        }
+    }
+
+    /// Reads the sticker to upload from the file at the path, taking its
+    /// format from the extension of the file
+    pub fn from_path<P: AsRef<Path>, S: ToString>(path: P, emoji_list: &[S]) -> Result<Self> {
+        let format = format_of(path.as_ref())?;
+        Ok(Self::new(InputFile::from_path(path)?, format, emoji_list))
+    }
+
+    pub fn set_mask_position(&mut self, position: MaskPosition) -> &mut Self {
+        self.mask_position = Some(position);
+        self
+    }
+
+    pub fn set_keywords<S: ToString>(&mut self, keywords: &[S]) -> &mut Self {
+        self.keywords = Some(keywords.iter().map(ToString::to_string).collect());
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -64,29 +179,43 @@ pub struct CreateNewStickerSet {
     /// Short name of sticker set, to be used in t.me/addstickers/ URLs (e.g.,
     /// animals). Can contain only english letters, digits and underscores.
     /// Must begin with a letter, can't contain consecutive underscores and
-    /// must end in “_by_<bot username>”. <bot_username> is case
+    /// must end in `_by_<bot username>`. The bot username is case
     /// insensitive. 1-64 characters.
     pub name: String,
     /// Sticker set title, 1-64 characters
     pub title: String,
-    /// PNG image with the sticker, must be up to 512 kilobytes in size,
-    /// dimensions must not exceed 512px, and either width or height must be
-    /// exactly 512px. Pass a file_id as a String to send a file that
-    /// already exists on the Telegram servers, ass an HTTP URL as a String
-    /// for Telegram to get a file from the Internet, or upload a new one
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub png_sticker: Option<InputFile>,
-    /// TGS animation with the sticker, uploaded using multipart/form-data.
-    /// See <https://core.telegram.org/animated_stickers#technical-requirements> for technical requirements
+    /// A list of 1-50 initial stickers to be added to the sticker set
+    pub stickers: Vec<InputSticker>,
+    /// Type of stickers in the set, defaults to regular stickers
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tgs_sticker: Option<InputFile>,
-    /// One or more emoji corresponding to the sticker
-    pub emojis: String,
-    /// Pass True, if a set of mask stickers should be created
-    pub contains_masks: bool,
-    /// position where the mask should be placed on faces
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub mask_position: Option<MaskPosition>,
+    pub sticker_type: Option<StickerType>,
+    /// Pass True if stickers in the sticker set must be repainted to the
+    /// color of text when used in messages, for custom emoji sticker sets
+    /// only
+    pub needs_repainting: bool,
+}
+
+impl CreateNewStickerSet {
+    pub fn new(user_id: i64, name: &str, title: &str, stickers: Vec<InputSticker>) -> Self {
+        Self {
+            user_id,
+            name: name.to_owned(),
+            title: title.to_owned(),
+            stickers,
+            sticker_type: None,
+            needs_repainting: false,
+        }
+    }
+
+    pub fn set_sticker_type(&mut self, sticker_type: StickerType) -> &mut Self {
+        self.sticker_type = Some(sticker_type);
+        self
+    }
+
+    pub fn toggle_needs_repainting(&mut self) -> &mut Self {
+        self.needs_repainting = !self.needs_repainting;
+        self
+    }
 }
 
 /// struct for holding data needed to call
@@ -100,22 +229,19 @@ pub struct AddStickerToSet {
     pub user_id: i64,
     /// Name of the sticker set
     pub name: String,
-    /// PNG image with the sticker, must be up to 512 kilobytes in size,
-    /// dimensions must not exceed 512px, and either width or height must be
-    /// exactly 512px. Pass a file_id as a String to send a file that
-    /// already exists on the Telegram servers, ass an HTTP URL as a String
-    /// for Telegram to get a file from the Internet, or upload a new one
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub png_sticker: Option<InputFile>,
-    /// TGS animation with the sticker, uploaded using multipart/form-data.
-    /// See <https://core.telegram.org/animated_stickers#technical-requirements> for technical requirements
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tgs_sticker: Option<InputFile>,
-    /// One or more emoji corresponding to the sticker
-    pub emojis: String,
-    /// position where the mask should be placed on faces
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub mask_position: Option<MaskPosition>,
+    /// The sticker to add to the set. If exactly the same sticker had already
+    /// been added to the set, then the set isn't changed.
+    pub sticker: InputSticker,
+}
+
+impl AddStickerToSet {
+    pub fn new(user_id: i64, name: &str, sticker: InputSticker) -> Self {
+        Self {
+            user_id,
+            name: name.to_owned(),
+            sticker,
+        }
+    }
 }
 
 /// struct for holding data needed to call
@@ -143,23 +269,132 @@ pub struct DeleteStickerFromSet {
 }
 
 /// struct for holding data needed to call
-/// [`set_sticker_set_thumb`]
+/// [`set_sticker_emoji_list`]
+///
+/// [`set_sticker_emoji_list`]:
+/// ../../api/trait.API.html#method.set_sticker_emoji_list
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetStickerEmojiList {
+    /// File identifier of the sticker
+    pub sticker: String,
+    /// A list of 1-20 emoji associated with the sticker
+    pub emoji_list: Vec<String>,
+}
+
+/// struct for holding data needed to call
+/// [`set_sticker_keywords`]
 ///
-/// [`set_sticker_set_thumb`]:
-/// ../../api/trait.API.html#method.set_sticker_set_thumb
+/// [`set_sticker_keywords`]:
+/// ../../api/trait.API.html#method.set_sticker_keywords
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct SetStickerSetThumb {
+pub struct SetStickerKeywords {
+    /// File identifier of the sticker
+    pub sticker: String,
+    /// A list of 0-20 search keywords for the sticker with total length of up
+    /// to 64 characters, `None` removes the keywords
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+}
+
+/// struct for holding data needed to call
+/// [`set_sticker_mask_position`]
+///
+/// [`set_sticker_mask_position`]:
+/// ../../api/trait.API.html#method.set_sticker_mask_position
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetStickerMaskPosition {
+    /// File identifier of the sticker
+    pub sticker: String,
+    /// The position where the mask should be placed on faces, `None` removes
+    /// the mask position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask_position: Option<MaskPosition>,
+}
+
+/// struct for holding data needed to call
+/// [`set_sticker_set_title`]
+///
+/// [`set_sticker_set_title`]:
+/// ../../api/trait.API.html#method.set_sticker_set_title
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetStickerSetTitle {
+    /// Sticker set name
+    pub name: String,
+    /// Sticker set title, 1-64 characters
+    pub title: String,
+}
+
+/// struct for holding data needed to call
+/// [`set_sticker_set_thumbnail`]
+///
+/// [`set_sticker_set_thumbnail`]:
+/// ../../api/trait.API.html#method.set_sticker_set_thumbnail
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetStickerSetThumbnail {
     /// Sticker set name
     pub name: String,
     /// User identifier of the sticker set owner
     pub user_id: i64,
-    /// A PNG image with the thumbnail, must be up to 128 kilobytes in size and
-    /// have width and height exactly 100px, or a TGS animation with the
-    /// thumbnail up to 32 kilobytes in size; see <https://core.telegram.org/animated_stickers#technical-requirements> for animated sticker technical requirements.
-    /// Pass a file_id as a String to send a file that already exists on the
-    /// Telegram servers, pass an HTTP URL as a String for Telegram to get a
-    /// file from the Internet, or upload a new one. Animated sticker set
-    /// thumbnail can't be uploaded via HTTP URL.
+    /// A .WEBP or .PNG image with the thumbnail, must be up to 128 kilobytes
+    /// in size and have a width and height of exactly 100px, or a .TGS
+    /// animation up to 32 kilobytes, or a .WEBM video up to 32 kilobytes;
+    /// see <https://core.telegram.org/stickers> for the technical
+    /// requirements. Pass a `file_id` as a String to send a file that already
+    /// exists on the Telegram servers, pass an HTTP URL as a String for
+    /// Telegram to get a file from the Internet, or upload a new one.
+    /// Animated and video thumbnails can't be uploaded via HTTP URL. `None`
+    /// drops the thumbnail, making the first sticker the thumbnail.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<InputFile>,
+    /// Format of the thumbnail
+    pub format: StickerFormat,
+}
+
+impl SetStickerSetThumbnail {
+    pub fn new(name: &str, user_id: i64, thumbnail: InputFile, format: StickerFormat) -> Self {
+        Self {
+            name: name.to_owned(),
+            user_id,
+            thumbnail: Some(thumbnail),
+            format,
+        }
+    }
+}
+
+/// struct for holding data needed to call
+/// [`set_custom_emoji_sticker_set_thumbnail`]
+///
+/// [`set_custom_emoji_sticker_set_thumbnail`]:
+/// ../../api/trait.API.html#method.set_custom_emoji_sticker_set_thumbnail
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetCustomEmojiStickerSetThumbnail {
+    /// Sticker set name
+    pub name: String,
+    /// Custom emoji identifier of a sticker from the sticker set, `None` drops
+    /// the thumbnail, making the first sticker the thumbnail
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub thumb: Option<InputFile>,
+    pub custom_emoji_id: Option<String>,
+}
+
+/// struct for holding data needed to call
+/// [`delete_sticker_set`]
+///
+/// [`delete_sticker_set`]:
+/// ../../api/trait.API.html#method.delete_sticker_set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeleteStickerSet {
+    /// Sticker set name
+    pub name: String,
+}
+
+/// The format of the sticker file at the path, based on its extension
+fn format_of(path: &Path) -> Result<StickerFormat> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    StickerFormat::from_file_name(file_name).ok_or_else(|| {
+        TelegramError::InvalidArgument(format!("{file_name} isn't a png, webp, tgs or webm file"))
+            .into()
+    })
 }
@@ -67,7 +67,11 @@ impl std::default::Default for GetUpdates {
 }
 
 /// The type of an update, can be used for specifying which update types you
-/// want to receive
+/// want to receive.
+///
+/// Without any allowed updates telegram sends all types except `ChatMember`,
+/// `MessageReaction` and `MessageReactionCount`, which have to be asked for
+/// explicitly, for example by allowing [`UpdateType::all`].
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum UpdateType {
     #[serde(rename = "message")]
@@ -92,4 +96,60 @@ pub enum UpdateType {
     Poll,
     #[serde(rename = "poll_answer")]
     PollAnswer,
+    #[serde(rename = "business_connection")]
+    BusinessConnection,
+    #[serde(rename = "message_reaction")]
+    MessageReaction,
+    #[serde(rename = "message_reaction_count")]
+    MessageReactionCount,
+    #[serde(rename = "business_message")]
+    BusinessMessage,
+    #[serde(rename = "edited_business_message")]
+    EditedBusinessMessage,
+    #[serde(rename = "deleted_business_messages")]
+    DeletedBusinessMessages,
+    #[serde(rename = "purchased_paid_media")]
+    PurchasedPaidMedia,
+    #[serde(rename = "my_chat_member")]
+    MyChatMember,
+    #[serde(rename = "chat_member")]
+    ChatMember,
+    #[serde(rename = "chat_join_request")]
+    ChatJoinRequest,
+    #[serde(rename = "chat_boost")]
+    ChatBoost,
+    #[serde(rename = "removed_chat_boost")]
+    RemovedChatBoost,
+}
+
+impl UpdateType {
+    /// Gets every type of update, to receive all of them including the ones
+    /// telegram doesn't send by default
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Message,
+            Self::EditedMessage,
+            Self::ChannelPost,
+            Self::EditedChannelPost,
+            Self::InlineQuery,
+            Self::ChosenInlineResult,
+            Self::CallbackQuery,
+            Self::ShippingQuery,
+            Self::PreCheckoutQuery,
+            Self::Poll,
+            Self::PollAnswer,
+            Self::BusinessConnection,
+            Self::MessageReaction,
+            Self::MessageReactionCount,
+            Self::BusinessMessage,
+            Self::EditedBusinessMessage,
+            Self::DeletedBusinessMessages,
+            Self::PurchasedPaidMedia,
+            Self::MyChatMember,
+            Self::ChatMember,
+            Self::ChatJoinRequest,
+            Self::ChatBoost,
+            Self::RemovedChatBoost,
+        ]
+    }
 }
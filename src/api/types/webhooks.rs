@@ -31,6 +31,10 @@ pub struct SetWebhook {
     pub ip_address: Option<String>,
     /// Pass True to drop all pending updates
     pub drop_pending_updates: Option<bool>,
+    /// A secret token to be sent in a header “X-Telegram-Bot-Api-Secret-Token”
+    /// in every webhook request, 1-256 characters. Only characters `A-Z`,
+    /// `a-z`, `0-9`, `_` and `-` are allowed.
+    pub secret_token: Option<String>,
 }
 
 /// Struct for holding data needed to call [`delete_webhook`]
@@ -41,3 +45,24 @@ pub struct DeleteWebhook {
     /// Pass True to drop all pending updates
     pub drop_pending_updates: Option<bool>,
 }
+
+impl DeleteWebhook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes the webhook and drops all updates telegram hasn't delivered
+    /// yet
+    pub fn dropping_pending_updates() -> Self {
+        Self {
+            drop_pending_updates: Some(true),
+        }
+    }
+}
+
+/// Struct for calling [`get_webhook_info`] through [`APIExt::send`]
+///
+/// [`get_webhook_info`]: ../../api/trait.API.html#method.get_webhook_info
+/// [`APIExt::send`]: ../../api/trait.APIExt.html#method.send
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GetWebhookInfo {}
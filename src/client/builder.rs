@@ -1,23 +1,94 @@
-use super::{APIConnector, Client, EventHandlerFunc, RawEventHandlerFunc, WebhookOptions};
+use super::{
+    event_handlers,
+    shutdown::DEFAULT_SHUTDOWN_TIMEOUT,
+    APIConnector,
+    BoundAddr,
+    BusinessConnectionHandlerFunc,
+    CallbackTracked,
+    ChannelChecked,
+    ChatTypes,
+    Client,
+    Configured,
+    Context,
+    ErrorHandlerFunc,
+    EventHandlerFunc,
+    HandlerError,
+    HandlerResult,
+    HealthTracker,
+    LiveConfig,
+    Monitored,
+    PendingCallbacks,
+    PollStall,
+    PollStallHandlerFunc,
+    RawEventHandlerFunc,
+    ReconfigureHandlerFunc,
+    RuntimeConfig,
+    Sanitized,
+    ShutdownHandle,
+    TextSanitizer,
+    WebhookOptions,
+};
 use crate::{
-    api::{types::UpdateType, APIClient},
+    api::{
+        types::UpdateType,
+        APIClient,
+        ApiLayer,
+        ApiSchema,
+        DryRun,
+        FloodRetry,
+        HttpsConnector,
+        Proxy,
+        Throttled,
+    },
     framework::Framework,
+    metrics::HandlerMetrics,
+    middleware::Middleware,
+    model::{raw::RawUpdate, BusinessConnection, Update},
+    runtime::{Runtime, TokioRuntime},
+    storage::{OffsetStorage, Storage},
 };
 
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
 use typemap::ShareMap;
 
 /// A builder for the [`Client`] object to make customisation easier
 pub struct ClientBuilder {
-    hyper_client: Option<hyper::Client<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>>,
+    hyper_client: Option<hyper::Client<HttpsConnector<hyper::client::HttpConnector>>>,
+    api_url: Option<String>,
+    local_mode: bool,
+    proxy: Option<Proxy>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    upload_timeout: Option<Duration>,
     api_client: Option<Arc<Box<APIConnector>>>,
+    api_layers: Vec<Arc<dyn ApiLayer>>,
+    flood_retries: u32,
+    throttling: bool,
     webhook: Option<WebhookOptions>,
     framework: Option<Arc<Framework>>,
     token: Option<String>,
     allowed_updates: Vec<UpdateType>,
     event_handler_funcs: Vec<EventHandlerFunc>,
     raw_event_handler_funcs: Vec<RawEventHandlerFunc>,
+    business_connection_handler_funcs: Vec<BusinessConnectionHandlerFunc>,
+    poll_stall_handler_funcs: Vec<PollStallHandlerFunc>,
+    reconfigure_handler_funcs: Vec<ReconfigureHandlerFunc>,
+    error_handler: Option<ErrorHandlerFunc>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    handler_metrics: Option<Arc<HandlerMetrics>>,
+    api_schema: Option<ApiSchema>,
+    dry_run: bool,
+    live_config: Option<LiveConfig>,
+    text_sanitizers: Vec<Arc<dyn TextSanitizer>>,
+    health_storage: Vec<(String, Arc<dyn Storage>)>,
+    offset_storage: Option<Arc<dyn OffsetStorage>>,
+    stale_callback_answer: Option<String>,
+    runtime: Option<Arc<dyn Runtime>>,
+    shutdown_timeout: Duration,
+    max_concurrent_updates: Option<usize>,
+    sequential_chats: bool,
 }
 
 impl ClientBuilder {
@@ -26,13 +97,39 @@ impl ClientBuilder {
     pub fn new() -> Self {
         Self {
             api_client: None,
+            api_layers: Vec::new(),
+            flood_retries: 3,
+            throttling: false,
             hyper_client: None,
+            api_url: None,
+            local_mode: false,
+            proxy: None,
+            connect_timeout: None,
+            request_timeout: None,
+            upload_timeout: None,
             webhook: None,
             framework: None,
             token: None,
             allowed_updates: Vec::new(),
             event_handler_funcs: Vec::new(),
             raw_event_handler_funcs: Vec::new(),
+            business_connection_handler_funcs: Vec::new(),
+            poll_stall_handler_funcs: Vec::new(),
+            reconfigure_handler_funcs: Vec::new(),
+            error_handler: None,
+            middlewares: Vec::new(),
+            handler_metrics: None,
+            api_schema: None,
+            dry_run: false,
+            live_config: None,
+            text_sanitizers: Vec::new(),
+            health_storage: Vec::new(),
+            offset_storage: None,
+            stale_callback_answer: None,
+            runtime: None,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            max_concurrent_updates: None,
+            sequential_chats: false,
         }
     }
 
@@ -60,19 +157,99 @@ impl ClientBuilder {
     /// Sets the custom hyper client for the `APIClient` to use
     pub fn set_hyper_client(
         &mut self,
-        client: hyper::Client<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>,
+        client: hyper::Client<HttpsConnector<hyper::client::HttpConnector>>,
     ) -> &mut Self {
         self.hyper_client = Some(client);
         self
     }
 
+    /// Sets the url of the bot API server for the `APIClient` to use, for
+    /// example a local bot API server. See [`APIClient::set_base_url`].
+    ///
+    /// [`APIClient::set_base_url`]: ../api/struct.APIClient.html#method.set_base_url
+    pub fn set_api_url(&mut self, url: &str) -> &mut Self {
+        self.api_url = Some(url.to_owned());
+        self
+    }
+
+    /// Sets whether the bot API server runs locally with `--local`, so the
+    /// files it stores are read from the disk. See
+    /// [`APIClient::set_local_mode`].
+    ///
+    /// [`APIClient::set_local_mode`]: ../api/struct.APIClient.html#method.set_local_mode
+    pub fn set_local_mode(&mut self, local: bool) -> &mut Self {
+        self.local_mode = local;
+        self
+    }
+
+    /// Sets the [`Proxy`] the `APIClient` routes its requests through
+    pub fn set_proxy(&mut self, proxy: Proxy) -> &mut Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets how long the `APIClient` may take to connect to telegram. See
+    /// [`APIClient::set_connect_timeout`].
+    ///
+    /// [`APIClient::set_connect_timeout`]: ../api/struct.APIClient.html#method.set_connect_timeout
+    pub fn set_connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long a request of the `APIClient` may take, on top of the
+    /// long polling timeout for getting updates. See
+    /// [`APIClient::set_request_timeout`].
+    ///
+    /// [`APIClient::set_request_timeout`]: ../api/struct.APIClient.html#method.set_request_timeout
+    pub fn set_request_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long uploading or downloading a file with the `APIClient` may
+    /// take. See [`APIClient::set_upload_timeout`].
+    ///
+    /// [`APIClient::set_upload_timeout`]: ../api/struct.APIClient.html#method.set_upload_timeout
+    pub fn set_upload_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.upload_timeout = Some(timeout);
+        self
+    }
+
     /// Sets the custom API client
     pub fn set_api_client(&mut self, client: Arc<Box<APIConnector>>) -> &mut Self {
         self.api_client = Some(client);
         self
     }
 
-    /// Set the list of update types you want your update handlers to handle
+    /// Adds an [`ApiLayer`] wrapping the API client, which sees every outgoing
+    /// request before it's sent. The first layer added is the outermost one.
+    pub fn add_api_layer<L: ApiLayer>(&mut self, layer: L) -> &mut Self {
+        self.api_layers.push(Arc::new(layer));
+        self
+    }
+
+    /// Sets how often a request telegram's flood control rejected is sent
+    /// again at most, after waiting as long as telegram asks for. Defaults to
+    /// 3, set it to 0 to get the errors right away. See [`FloodRetry`] for the
+    /// details.
+    pub fn set_flood_retries(&mut self, max_retries: u32) -> &mut Self {
+        self.flood_retries = max_retries;
+        self
+    }
+
+    /// Sets whether the messages sent are kept below telegram's limits of 30
+    /// messages per second and 20 per minute into a group, holding back the
+    /// ones exceeding them. Off by default, see [`Throttled`] for the details.
+    pub fn set_throttling(&mut self, throttling: bool) -> &mut Self {
+        self.throttling = throttling;
+        self
+    }
+
+    /// Set the list of update types you want your update handlers to handle.
+    /// They're sent to telegram when polling for updates as well as when
+    /// setting the webhook, see [`UpdateType`] for the types telegram leaves
+    /// out when none are set.
     pub fn set_allowed_updates(&mut self, allowed: Vec<UpdateType>) -> &mut Self {
         self.allowed_updates = allowed;
         self
@@ -93,17 +270,236 @@ impl ClientBuilder {
     }
 
     /// Adds an [`EventHandlerFunc`] function for handling incoming updates
-    pub fn add_handler_func(&mut self, handler: EventHandlerFunc) -> &mut Self {
-        self.event_handler_funcs.push(handler);
+    pub fn add_handler_func<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Context, Update) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        self.event_handler_funcs
+            .push(event_handlers::boxed(handler));
         self
     }
 
     /// Adds an [`RawEventHandlerFunc`] function for handling incoming updates
-    pub fn add_raw_handler_func(&mut self, handler: RawEventHandlerFunc) -> &mut Self {
-        self.raw_event_handler_funcs.push(handler);
+    pub fn add_raw_handler_func<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Context, RawUpdate) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        self.raw_event_handler_funcs
+            .push(event_handlers::boxed(handler));
+        self
+    }
+
+    /// Adds a [`BusinessConnectionHandlerFunc`] function for handling business
+    /// connection updates
+    pub fn add_business_connection_handler_func<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Context, BusinessConnection) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        self.business_connection_handler_funcs
+            .push(event_handlers::boxed(handler));
+        self
+    }
+
+    /// Adds a [`PollStallHandlerFunc`] function for handling the watchdog of
+    /// the long poll recovering from a stall
+    pub fn add_poll_stall_handler_func<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Context, PollStall) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        self.poll_stall_handler_funcs
+            .push(event_handlers::boxed(handler));
+        self
+    }
+
+    /// Sets the function called with the errors returned by the handlers and
+    /// commands, after they're logged
+    pub fn on_handler_error<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Context, HandlerError) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.error_handler = Some(event_handlers::boxed_error_handler(handler));
+        self
+    }
+
+    /// Adds a [`Middleware`] to the end of the middleware chain, which every
+    /// incoming update passes through before being dispatched
+    pub fn add_middleware<M: Middleware>(&mut self, middleware: M) -> &mut Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets the [`HandlerMetrics`] to record the invocations of all handlers
+    /// and commands in
+    pub fn set_handler_metrics(&mut self, metrics: Arc<HandlerMetrics>) -> &mut Self {
+        self.handler_metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the [`ApiSchema`] of the Bot API version the bot runs against, for
+    /// example when using an older self-hosted bot API server. When starting,
+    /// the client logs a warning for every field it sends which that version
+    /// doesn't know about.
+    ///
+    /// Without a schema set, the bundled one is checked against in debug
+    /// builds, or when the `schema-check` feature is enabled.
+    pub fn set_api_schema(&mut self, schema: ApiSchema) -> &mut Self {
+        self.api_schema = Some(schema);
+        self
+    }
+
+    /// Puts the client in dry-run mode: calls sending, editing or deleting
+    /// anything are logged and answered with a synthesized response instead
+    /// of reaching telegram, while reads like getMe and getUpdates still go
+    /// through. See [`DryRun`] for the details.
+    ///
+    /// [`DryRun`]: ../api/struct.DryRun.html
+    pub fn set_dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Sets the [`LiveConfig`] holding the settings which can be changed while
+    /// the client is running, like the default parse mode of outgoing messages
+    /// and the allowed updates. It's inserted into the data of the client, for
+    /// handlers to read with [`Context::config`].
+    ///
+    /// [`Context::config`]: super::Context::config
+    pub fn set_live_config(&mut self, config: LiveConfig) -> &mut Self {
+        self.live_config = Some(config);
+        self
+    }
+
+    /// Adds a [`ReconfigureHandlerFunc`] function for handling changes of the
+    /// [`LiveConfig`]
+    pub fn add_reconfigure_handler_func<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Context, Arc<RuntimeConfig>) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        self.reconfigure_handler_funcs
+            .push(event_handlers::boxed(handler));
+        self
+    }
+
+    /// Adds a [`TextSanitizer`] to the end of the ones the texts and captions
+    /// of outgoing messages pass through, right before being sent. The
+    /// default parse mode of the [`LiveConfig`] is already applied to them.
+    pub fn add_text_sanitizer<S: TextSanitizer>(&mut self, sanitizer: S) -> &mut Self {
+        self.text_sanitizers.push(Arc::new(sanitizer));
+        self
+    }
+
+    /// Adds a [`Storage`] backend to check in the [`Client::health`]
+    /// snapshot, reported under the given name
+    ///
+    /// [`Client::health`]: Client::health
+    pub fn add_health_storage(&mut self, name: &str, storage: Arc<dyn Storage>) -> &mut Self {
+        self.health_storage.push((name.to_owned(), storage));
+        self
+    }
+
+    /// Sets the [`OffsetStorage`] keeping the last update confirmed by
+    /// polling, so the client resumes after it when restarted instead of
+    /// handling updates again or losing them
+    pub fn set_offset_storage(&mut self, storage: Arc<dyn OffsetStorage>) -> &mut Self {
+        self.offset_storage = Some(storage);
+        self
+    }
+
+    /// Makes the client answer the callback queries still being handled when
+    /// it stops, with a notification showing the given text, like "the bot is
+    /// restarting, please try again". Otherwise their users keep looking at a
+    /// loading button until telegram gives up on them.
+    ///
+    /// See [`Client::answer_stale_callbacks`] for stopping the client in
+    /// another way.
+    pub fn set_stale_callback_answer(&mut self, text: &str) -> &mut Self {
+        self.stale_callback_answer = Some(text.to_owned());
         self
     }
 
+    /// Sets the [`Runtime`] the client spawns its handlers and sets its timers
+    /// on, for running it on another runtime than tokio. See the
+    /// [`runtime`] module for what still needs tokio.
+    ///
+    /// [`runtime`]: crate::runtime
+    pub fn set_runtime<R: Runtime + 'static>(&mut self, runtime: R) -> &mut Self {
+        self.runtime = Some(Arc::new(runtime));
+        self
+    }
+
+    /// Sets how long the client waits for the updates it's handling to finish
+    /// when it's shut down, 30 seconds by default. See
+    /// [`Client::shutdown`].
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Limits how many updates are handled at the same time. Once the limit is
+    /// reached the client waits for one of them to finish before taking the
+    /// next update, not fetching new ones from telegram meanwhile, so a busy
+    /// bot doesn't pile up an unbounded amount of tasks. Unlimited by default.
+    ///
+    /// With [`set_sequential_chats`] enabled, updates waiting for an earlier
+    /// update of their chat to be handled don't count toward the limit.
+    ///
+    /// Use a [`UserConcurrencyLimit`] to limit the updates per user instead.
+    ///
+    /// [`set_sequential_chats`]: ClientBuilder::set_sequential_chats
+    /// [`UserConcurrencyLimit`]: crate::middleware::UserConcurrencyLimit
+    pub fn set_max_concurrent_updates(&mut self, limit: usize) -> &mut Self {
+        self.max_concurrent_updates = Some(limit.max(1));
+        self
+    }
+
+    /// Sets whether the updates of the same chat are handled one after
+    /// another, in the order they were received, so a dialogue doesn't get
+    /// mixed up when a user sends two messages in quick succession. The
+    /// updates of different chats are still handled in parallel, and updates
+    /// without a chat, like inline queries, aren't held up. Off by default.
+    pub fn set_sequential_chats(&mut self, sequential: bool) -> &mut Self {
+        self.sequential_chats = sequential;
+        self
+    }
+
+    /// creates the default `APIClient` from the settings
+    fn build_api_client(&self) -> APIClient {
+        let mut api = APIClient::new(
+            self.hyper_client.clone(),
+            self.token
+                .as_ref()
+                .expect("A token must be provided for the telegram bot to work"),
+        );
+        if let Some(url) = &self.api_url {
+            api.set_base_url(url);
+        }
+        api.set_local_mode(self.local_mode);
+        if let Some(timeout) = self.connect_timeout {
+            api.set_connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.request_timeout {
+            api.set_request_timeout(timeout);
+        }
+        if let Some(timeout) = self.upload_timeout {
+            api.set_upload_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            api.set_proxy(proxy.clone());
+        }
+        api
+    }
+
     /// Creates the [`Client`] object from the settings set in the
     /// [`ClientBuilder`] object
     pub fn build(&mut self) -> Client {
@@ -111,30 +507,89 @@ impl ClientBuilder {
             self.allowed_updates.push(UpdateType::Message)
         }
 
-        self.api_client.clone().map_or_else(
-            || Client {
-                api_client: Arc::new(Box::new(APIClient::new(
-                    self.hyper_client.clone(),
-                    self.token
-                        .as_ref()
-                        .expect("A token must be provided for the telegram bot to work"),
-                ))),
-                event_handlers: self.event_handler_funcs.clone(),
-                raw_event_handlers: self.raw_event_handler_funcs.clone(),
-                data: Arc::new(RwLock::new(ShareMap::custom())),
-                framework: self.framework.clone(),
-                webhook_opts: self.webhook.clone(),
-                allowed_updates: self.allowed_updates.clone(),
-            },
-            |c| Client {
-                api_client: c,
-                event_handlers: self.event_handler_funcs.clone(),
-                webhook_opts: self.webhook.clone(),
-                raw_event_handlers: self.raw_event_handler_funcs.clone(),
-                data: Arc::new(RwLock::new(ShareMap::custom())),
-                framework: self.framework.clone(),
-                allowed_updates: self.allowed_updates.clone(),
-            },
-        )
+        let mut api_client = self
+            .api_client
+            .clone()
+            .unwrap_or_else(|| Arc::new(Box::new(self.build_api_client())));
+        for layer in self.api_layers.iter().rev() {
+            api_client = Arc::new(layer.layer(api_client));
+        }
+        let runtime = self
+            .runtime
+            .clone()
+            .unwrap_or_else(|| Arc::new(TokioRuntime));
+        if self.flood_retries > 0 {
+            api_client = Arc::new(Box::new(
+                FloodRetry::new(api_client)
+                    .set_max_retries(self.flood_retries)
+                    .set_runtime(runtime.clone()),
+            ));
+        }
+        if self.throttling {
+            api_client = Arc::new(Box::new(
+                Throttled::new(api_client).set_runtime(runtime.clone()),
+            ));
+        }
+        let health = Arc::new(HealthTracker::new(self.health_storage.clone()));
+        let chat_types = ChatTypes::new();
+        let mut api_client: Arc<Box<APIConnector>> = Arc::new(Box::new(ChannelChecked::new(
+            Monitored::new(api_client, health.clone()),
+            chat_types.clone(),
+        )));
+        let pending_callbacks = self
+            .stale_callback_answer
+            .clone()
+            .map(|answer| Arc::new(PendingCallbacks::new(answer)));
+        if let Some(pending) = &pending_callbacks {
+            api_client = Arc::new(Box::new(CallbackTracked::new(api_client, pending.clone())));
+        }
+        if self.dry_run {
+            api_client = Arc::new(Box::new(DryRun::new(api_client)));
+        }
+        if !self.text_sanitizers.is_empty() {
+            let sanitizers = self.text_sanitizers.clone();
+            api_client = Arc::new(Box::new(Sanitized::new(api_client, sanitizers)));
+        }
+
+        let mut data = ShareMap::custom();
+        data.insert::<ChatTypes>(chat_types.clone());
+        if let Some(config) = &self.live_config {
+            api_client = Arc::new(Box::new(Configured::new(
+                api_client,
+                config.clone(),
+                chat_types.clone(),
+            )));
+            data.insert::<LiveConfig>(config.clone());
+        }
+
+        Client {
+            api_client,
+            event_handlers: Arc::new(self.event_handler_funcs.clone()),
+            raw_event_handlers: Arc::new(self.raw_event_handler_funcs.clone()),
+            business_connection_handlers: Arc::new(self.business_connection_handler_funcs.clone()),
+            poll_stall_handlers: Arc::new(self.poll_stall_handler_funcs.clone()),
+            reconfigure_handlers: Arc::new(self.reconfigure_handler_funcs.clone()),
+            error_handler: self.error_handler.clone(),
+            data: Arc::new(RwLock::new(data)),
+            framework: self.framework.clone(),
+            middlewares: Arc::new(self.middlewares.clone()),
+            handler_metrics: self.handler_metrics.clone(),
+            api_schema: self.api_schema.clone().map(Arc::new),
+            webhook_opts: self.webhook.clone().map(Arc::new),
+            allowed_updates: self.allowed_updates.clone(),
+            live_config: self.live_config.clone(),
+            health,
+            chat_types,
+            pending_callbacks,
+            runtime,
+            shutdown: ShutdownHandle::default(),
+            webhook_addr: BoundAddr::default(),
+            shutdown_timeout: self.shutdown_timeout,
+            dispatch_permits: self
+                .max_concurrent_updates
+                .map(|limit| Arc::new(Semaphore::new(limit))),
+            chat_queues: self.sequential_chats.then(Arc::default),
+            offset_storage: self.offset_storage.clone(),
+        }
     }
 }
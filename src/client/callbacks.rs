@@ -0,0 +1,115 @@
+use crate::{
+    api::{types::AnswerCallbackQuery, APIEndpoint, Response, API},
+    redaction,
+    utils::{result::Result, FormDataFile},
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::{collections::HashSet, sync::Arc};
+
+/// The callback queries the client received, but which weren't answered yet
+/// and are still being handled, answered with a notice when the client stops
+pub(super) struct PendingCallbacks {
+    answer: String,
+    ids: Mutex<HashSet<String>>,
+}
+
+impl PendingCallbacks {
+    pub(super) fn new(answer: String) -> Self {
+        Self {
+            answer,
+            ids: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub(super) fn received(&self, id: &str) {
+        self.ids.lock().insert(id.to_owned());
+    }
+
+    /// forgets the query once it's answered or its handling finished, after
+    /// which answering it on shutdown can't help anymore
+    pub(super) fn done(&self, id: &str) {
+        self.ids.lock().remove(id);
+    }
+
+    /// answers all pending queries, returning how many were answered
+    pub(super) async fn answer_all(&self, api: &(dyn API + Send)) -> usize {
+        let ids: Vec<String> = self.ids.lock().drain().collect();
+        let mut answered = 0;
+        for id in ids {
+            let res = api
+                .answer_callback_query(AnswerCallbackQuery {
+                    callback_query_id: id,
+                    text: Some(self.answer.clone()),
+                    show_alert: false,
+                    url: None,
+                    cache_time: None,
+                })
+                .await;
+            match res {
+                Ok(_) => answered += 1,
+                Err(e) => log::debug!(
+                    "couldn't answer a stale callback query: {}",
+                    redaction::scrub(e)
+                ),
+            }
+        }
+        answered
+    }
+}
+
+/// An [`API`] wrapper noting which callback queries got answered, so they
+/// aren't answered again when the client stops
+pub(super) struct CallbackTracked<A> {
+    api: A,
+    pending: Arc<PendingCallbacks>,
+}
+
+impl<A> CallbackTracked<A> {
+    pub(super) fn new(api: A, pending: Arc<PendingCallbacks>) -> Self {
+        Self {
+            api,
+            pending,
+        }
+    }
+
+    fn note(&self, endpoint: &APIEndpoint, data: Option<&Value>) {
+        if !matches!(endpoint, APIEndpoint::AnswerCallbackQuery) {
+            return;
+        }
+        if let Some(id) = data
+            .and_then(|d| d.get("callback_query_id"))
+            .and_then(Value::as_str)
+        {
+            self.pending.done(id);
+        }
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for CallbackTracked<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.note(&endpoint, data.as_ref());
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.note(&endpoint, data.as_ref());
+        self.api.post(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.note(&endpoint, data.as_ref());
+        self.api.post_file(endpoint, data, files).await
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    api::{types::GetChat, APIEndpoint, Response, API},
+    model::{Chat, ChatType},
+    utils::{
+        result::{Result, TelegramError},
+        FormDataFile,
+    },
+};
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::{collections::HashMap, sync::Arc};
+
+/// Tells the type of a chat from its id, for the chats the client sends
+/// messages to.
+///
+/// Private chats and groups can be told apart by their id, but channels have
+/// the same kind of ids as supergroups. The client remembers the type of the
+/// chats it received updates from, and [`resolve`] asks telegram about the
+/// other ones, remembering the answer as well.
+///
+/// Clients built by a [`ClientBuilder`] insert their chat types into
+/// [`Context::data`]:
+///
+/// ```rust,no_run
+/// use telexide::{client::{ChatTypes, Context}, model::ChatType};
+///
+/// # fn handle(ctx: Context, chat_id: i64) {
+/// let chat_types = ctx.data.read().get::<ChatTypes>().cloned();
+/// if chat_types.is_some_and(|t| t.chat_type(chat_id) == ChatType::Channel) {
+///     // only inline keyboards can be sent
+/// }
+/// # }
+/// ```
+///
+/// [`resolve`]: ChatTypes::resolve
+/// [`ClientBuilder`]: super::ClientBuilder
+/// [`Context::data`]: super::Context::data
+#[derive(Clone, Default)]
+pub struct ChatTypes {
+    /// the supergroups and channels whose type is known
+    known: Arc<RwLock<HashMap<i64, ChatType>>>,
+}
+
+impl typemap::Key for ChatTypes {
+    type Value = ChatTypes;
+}
+
+impl ChatTypes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the type of the chat with the given id. Channels which weren't
+    /// seen or resolved yet are reported as supergroups.
+    pub fn chat_type(&self, chat_id: i64) -> ChatType {
+        if chat_id > 0 {
+            ChatType::Private
+        } else if let Some(chat_type) = self.known.read().get(&chat_id) {
+            *chat_type
+        } else if is_supergroup_id(chat_id) {
+            ChatType::SuperGroup
+        } else {
+            ChatType::Group
+        }
+    }
+
+    /// Gets the type of the chat with the given id, asking telegram with
+    /// [`get_chat`] for the supergroups and channels whose type isn't known
+    /// yet
+    ///
+    /// [`get_chat`]: API::get_chat
+    pub async fn resolve<A: API + ?Sized>(&self, api: &A, chat_id: i64) -> Result<ChatType> {
+        if !is_supergroup_id(chat_id) || self.known.read().contains_key(&chat_id) {
+            return Ok(self.chat_type(chat_id));
+        }
+
+        let chat = api
+            .get_chat(GetChat {
+                chat_id,
+            })
+            .await?;
+        self.note(&chat);
+        Ok(chat.get_type())
+    }
+
+    /// Returns whether the chat with the given id is known to be a channel
+    pub fn is_channel(&self, chat_id: i64) -> bool {
+        self.chat_type(chat_id) == ChatType::Channel
+    }
+
+    /// Remembers the type of the chat if it can't be told from its id. The
+    /// client does so for the chat of every update it receives.
+    pub fn note(&self, chat: &Chat) {
+        let id = chat.get_id();
+        if is_supergroup_id(id) && !self.known.read().contains_key(&id) {
+            self.known.write().insert(id, chat.get_type());
+        }
+    }
+}
+
+/// supergroups and channels have ids of the form -100xxxxxxxxxx
+fn is_supergroup_id(chat_id: i64) -> bool {
+    chat_id <= -1_000_000_000_000
+}
+
+/// An [`API`] wrapper rejecting the messages sent to channels with a markup
+/// channels don't support, before they reach telegram
+pub(super) struct ChannelChecked<A> {
+    api: A,
+    chat_types: ChatTypes,
+}
+
+impl<A> ChannelChecked<A> {
+    pub(super) fn new(api: A, chat_types: ChatTypes) -> Self {
+        Self {
+            api,
+            chat_types,
+        }
+    }
+
+    /// channels only support inline keyboards, not reply keyboards, removing
+    /// them or forcing a reply
+    async fn check(&self, endpoint: &APIEndpoint, data: Option<&Value>) -> Result<()>
+    where
+        A: API + Send,
+    {
+        let (chat_id, markup) = match data {
+            Some(data) => (
+                data.get("chat_id").and_then(Value::as_i64),
+                data.get("reply_markup"),
+            ),
+            None => return Ok(()),
+        };
+        let (Some(chat_id), Some(markup)) = (chat_id, markup) else {
+            return Ok(());
+        };
+        if markup.is_null() || markup.get("inline_keyboard").is_some() {
+            return Ok(());
+        }
+
+        // a chat which can't be looked up is left for telegram to reject
+        let is_channel = self
+            .chat_types
+            .resolve(&self.api, chat_id)
+            .await
+            .is_ok_and(|t| t == ChatType::Channel);
+        if is_channel {
+            return Err(TelegramError::InvalidArgument(format!(
+                "{} can only send inline keyboards to the channel {chat_id}",
+                endpoint.as_str()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for ChannelChecked<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.check(&endpoint, data.as_ref()).await?;
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.check(&endpoint, data.as_ref()).await?;
+        self.api.post(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.check(&endpoint, data.as_ref()).await?;
+        self.api.post_file(endpoint, data, files).await
+    }
+}
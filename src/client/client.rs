@@ -1,27 +1,71 @@
 use super::{
+    event_handlers::{self, HandlerOutcome},
+    shutdown::DEFAULT_SHUTDOWN_TIMEOUT,
     APIConnector,
+    BoundAddr,
+    BusinessConnectionHandlerFunc,
+    ChannelChecked,
+    ChatQueues,
+    ChatTypes,
     ClientBuilder,
+    ClientMode,
+    report_error,
     Context,
+    ErrorHandlerFunc,
     EventHandlerFunc,
+    HandlerError,
+    HandlerResult,
+    HealthSnapshot,
+    HealthTracker,
+    LiveConfig,
+    Monitored,
+    PendingCallbacks,
+    PollStall,
+    PollStallHandlerFunc,
     RawEventHandlerFunc,
+    ReconfigureHandlerFunc,
+    RuntimeConfig,
+    ShutdownHandle,
     UpdatesStream,
     Webhook,
     WebhookOptions,
 };
 use crate::{
     api::{
-        types::{SetWebhook, UpdateType},
+        types::{InputFile, SetWebhook, UpdateType},
+        log_self_check,
         APIClient,
+        ApiSchema,
+        BotCapability,
+        FloodRetry,
+        API,
     },
     framework::Framework,
-    model::Update,
+    metrics::{self, HandlerMetrics},
+    middleware::{Middleware, Next},
+    model::{raw::RawUpdate, BusinessConnection, Chat, Update, UpdateContent},
+    redaction,
+    runtime::{spawn_task, Runtime, TaskHandle, TokioRuntime},
+    storage::OffsetStorage,
+    utils::result::TelegramError,
     Result,
 };
-use futures::StreamExt;
+use futures::{
+    future::{join_all, select, Either},
+    Future,
+    FutureExt,
+    Stream,
+    StreamExt,
+};
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use typemap::ShareMap;
 
+/// How long [`Client::updates_stream`] waits before polling again after
+/// getUpdates failed
+const POLL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
 /// The Client is the main object to manage your interaction with telegram.
 ///
 /// It handles the incoming update objects from telegram and dispatches them to
@@ -81,10 +125,32 @@ pub struct Client {
     ///
     /// [repeat_image_bot]: https://github.com/callieve/telexide/tree/master/examples/repeat_image_bot.rs
     pub data: Arc<RwLock<ShareMap>>,
-    pub(super) event_handlers: Vec<EventHandlerFunc>,
-    pub(super) raw_event_handlers: Vec<RawEventHandlerFunc>,
+    // the client gets cloned for every update it dispatches, so the fields
+    // below are `Copy`, an `Arc` or a handle wrapping `Arc`s, and cloning them
+    // only bumps reference counts. The public `allowed_updates` are the
+    // exception, being a `Vec` that is copied with every clone.
+    pub(super) event_handlers: Arc<Vec<EventHandlerFunc>>,
+    pub(super) raw_event_handlers: Arc<Vec<RawEventHandlerFunc>>,
+    pub(super) business_connection_handlers: Arc<Vec<BusinessConnectionHandlerFunc>>,
+    pub(super) poll_stall_handlers: Arc<Vec<PollStallHandlerFunc>>,
+    pub(super) reconfigure_handlers: Arc<Vec<ReconfigureHandlerFunc>>,
+    pub(super) error_handler: Option<ErrorHandlerFunc>,
     pub(super) framework: Option<Arc<Framework>>,
-    pub(super) webhook_opts: Option<WebhookOptions>,
+    pub(super) middlewares: Arc<Vec<Arc<dyn Middleware>>>,
+    pub(super) handler_metrics: Option<Arc<HandlerMetrics>>,
+    pub(super) api_schema: Option<Arc<ApiSchema>>,
+    pub(super) webhook_opts: Option<Arc<WebhookOptions>>,
+    pub(super) live_config: Option<LiveConfig>,
+    pub(super) health: Arc<HealthTracker>,
+    pub(super) chat_types: ChatTypes,
+    pub(super) pending_callbacks: Option<Arc<PendingCallbacks>>,
+    pub(super) runtime: Arc<dyn Runtime>,
+    pub(super) shutdown: ShutdownHandle,
+    pub(super) webhook_addr: BoundAddr,
+    pub(super) shutdown_timeout: Duration,
+    pub(super) dispatch_permits: Option<Arc<Semaphore>>,
+    pub(super) chat_queues: Option<Arc<ChatQueues>>,
+    pub(super) offset_storage: Option<Arc<dyn OffsetStorage>>,
     /// The update types that you want to receive, see the documentation of
     /// [`UpdateType`] for more information
     pub allowed_updates: Vec<UpdateType>,
@@ -93,27 +159,50 @@ pub struct Client {
 impl Client {
     /// Creates a Client object with default values and no framework
     pub fn new<T: ToString>(token: T) -> Self {
-        Self {
-            api_client: Arc::new(Box::new(APIClient::new(None, token))),
-            event_handlers: Vec::new(),
-            raw_event_handlers: Vec::new(),
-            data: Arc::new(RwLock::new(ShareMap::custom())),
-            framework: None,
-            webhook_opts: None,
-            allowed_updates: Vec::new(),
-        }
+        Self::with_api(APIClient::new(None, token), None)
     }
 
     /// Creates a Client object with default values, but with a [`Framework`]
     pub fn with_framework<T: ToString>(fr: Arc<Framework>, token: T) -> Self {
+        Self::with_api(APIClient::new(None, token), Some(fr))
+    }
+
+    /// creates a client with default values around the given api client,
+    /// adding the bookkeeping the client needs to it
+    fn with_api<A: API + Send + 'static>(api: A, framework: Option<Arc<Framework>>) -> Self {
+        let health = Arc::new(HealthTracker::default());
+        let chat_types = ChatTypes::new();
+        let mut data = ShareMap::custom();
+        data.insert::<ChatTypes>(chat_types.clone());
         Self {
-            api_client: Arc::new(Box::new(APIClient::new(None, token))),
-            event_handlers: Vec::new(),
-            raw_event_handlers: Vec::new(),
-            data: Arc::new(RwLock::new(ShareMap::custom())),
+            api_client: Arc::new(Box::new(ChannelChecked::new(
+                Monitored::new(FloodRetry::new(api), health.clone()),
+                chat_types.clone(),
+            ))),
+            event_handlers: Arc::default(),
+            raw_event_handlers: Arc::default(),
+            business_connection_handlers: Arc::default(),
+            poll_stall_handlers: Arc::default(),
+            reconfigure_handlers: Arc::default(),
+            error_handler: None,
+            data: Arc::new(RwLock::new(data)),
+            framework,
+            middlewares: Arc::default(),
+            handler_metrics: None,
+            api_schema: None,
             webhook_opts: None,
-            framework: Some(fr),
             allowed_updates: Vec::new(),
+            live_config: None,
+            health,
+            chat_types,
+            pending_callbacks: None,
+            runtime: Arc::new(TokioRuntime),
+            shutdown: ShutdownHandle::default(),
+            webhook_addr: BoundAddr::default(),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            dispatch_permits: None,
+            chat_queues: None,
+            offset_storage: None,
         }
     }
 
@@ -123,7 +212,8 @@ impl Client {
     }
 
     /// Starts the client and blocks until an error happens in the updates
-    /// stream or the program exits (for example due to a panic).
+    /// stream, it's stopped with [`Client::shutdown`] or the program exits
+    /// (for example due to a panic).
     /// If using the framework, it will update your commands in telegram.
     /// If using a webhook, it will handle it, else it will use polling using a
     /// default [`UpdatesStream`] object
@@ -132,7 +222,12 @@ impl Client {
             self.start_with_webhook(opts).await
         } else {
             let mut stream = UpdatesStream::new(self.api_client.clone());
-            stream.set_allowed_updates(self.allowed_updates.clone());
+            stream
+                .set_allowed_updates(self.current_allowed_updates())
+                .set_runtime(self.runtime.clone());
+            if let Some(storage) = &self.offset_storage {
+                stream.set_offset_storage(storage.clone());
+            }
 
             self.start_with_stream(&mut stream).await
         }
@@ -142,24 +237,56 @@ impl Client {
     /// stream or the program exits (for example due to a panic).
     /// If using the framework, it will update your commands in telegram
     /// You have to provide your own [`UpdatesStream`] object
+    ///
+    /// If any [`PollStallHandlerFunc`]s are subscribed, they replace the
+    /// [`UpdatesStream::on_stall`] handler of the stream.
     pub async fn start_with_stream(&self, stream: &mut UpdatesStream) -> Result<()> {
-        if let Some(fr) = self.framework.clone() {
-            self.api_client
-                .set_my_commands(fr.get_commands().into())
-                .await?;
+        self.prepare().await?;
+
+        if !self.poll_stall_handlers.is_empty() {
+            let client = self.clone();
+            stream.on_stall(move |stall| client.fire_poll_stall_handlers(stall));
         }
 
+        let mut changes = self.live_config.as_ref().map(LiveConfig::subscribe);
+        let _running = self.health.running(ClientMode::Polling);
+
         log::info!("starting long polling to listen for updates from telegram api");
-        while let Some(poll) = stream.next().await {
+        // the poll in flight is dropped when stopping, its updates aren't
+        // confirmed so telegram sends them again on the next start
+        let stopped = self.shutdown.stopped();
+        tokio::pin!(stopped);
+        let res = loop {
+            let poll = if let Some(changes) = &mut changes {
+                tokio::select! {
+                    () = &mut stopped => break Ok(()),
+                    poll = stream.next() => poll,
+                    change = changes.recv() => {
+                        if let Ok(config) = change {
+                            stream.replace_allowed_updates(self.allowed_updates_in(&config));
+                            self.fire_reconfigure_handlers(&config);
+                        }
+                        continue;
+                    },
+                }
+            } else {
+                tokio::select! {
+                    () = &mut stopped => break Ok(()),
+                    poll = stream.next() => poll,
+                }
+            };
+
             match poll {
-                Ok(update) => {
-                    self.fire_handlers(update);
+                Some(Ok(update)) => {
+                    self.queue_update(update).await;
                 },
-                Err(err) => return Err(err),
+                Some(Err(err)) => break Err(err),
+                None => break Ok(()),
             }
-        }
+        };
 
-        Ok(())
+        self.finish_running().await;
+        res
     }
 
     /// Starts the client and blocks until an error happens in the webhook
@@ -167,34 +294,259 @@ impl Client {
     /// If using the framework, it will update your commands in telegram
     /// You have to provide your own [`WebhookOptions`] object
     pub async fn start_with_webhook(&self, opts: &WebhookOptions) -> Result<()> {
-        if let Some(fr) = self.framework.clone() {
-            self.api_client
-                .set_my_commands(fr.get_commands().into())
-                .await?;
+        self.prepare().await?;
+
+        if opts.url.is_some() {
+            self.register_webhook(
+                opts,
+                self.current_allowed_updates(),
+                opts.drop_pending_updates,
+            )
+            .await?;
+        }
+        let mut changes = self.live_config.as_ref().map(LiveConfig::subscribe);
+        let _running = self.health.running(ClientMode::Webhook);
+
+        log::info!("starting to listen on the webhook");
+        let client = self.clone();
+        // the server stops by itself when shutting down, the updates it
+        // already confirmed to telegram are still received afterwards
+        let mut receiver = Webhook::new(opts).start_for_client(
+            Arc::new(move || {
+                let client = client.clone();
+                Box::pin(async move { client.health().await })
+            }),
+            self.webhook_addr.clone(),
+            self.shutdown.clone(),
+        );
+        let res = loop {
+            let incoming = match &mut changes {
+                Some(changes) => tokio::select! {
+                    incoming = receiver.recv() => incoming,
+                    change = changes.recv() => {
+                        if let Ok(config) = change {
+                            self.reconfigure_webhook(opts, &config).await;
+                            self.fire_reconfigure_handlers(&config);
+                        }
+                        continue;
+                    },
+                },
+                None => receiver.recv().await,
+            };
+
+            match incoming {
+                Some(Ok(update)) => {
+                    self.queue_update(update).await;
+                },
+                Some(Err(err)) => break Err(err),
+                None => break Ok(()),
+            }
+        };
+
+        self.finish_running().await;
+        res
+    }
+
+    /// Starts listening for updates on `addr`, with telegram sending them to
+    /// `path`. The [`WebhookOptions`] the client was built with are used for
+    /// everything else, so the webhook is set on startup if they have an url.
+    /// The path of that url has to be `path` then, otherwise an error is
+    /// returned.
+    ///
+    /// ```rust,no_run
+    /// # use telexide::client::Client;
+    /// # async fn run(client: Client) -> telexide::Result<()> {
+    /// client.start_webhook(([0, 0, 0, 0], 8443), "/telegram").await
+    /// # }
+    /// ```
+    pub async fn start_webhook<A: Into<SocketAddr>>(&self, addr: A, path: &str) -> Result<()> {
+        let mut opts = self
+            .webhook_opts
+            .as_deref()
+            .cloned()
+            .unwrap_or_else(WebhookOptions::new);
+        if let Some(url) = opts.url.as_ref().filter(|u| u.path() != path) {
+            return Err(TelegramError::InvalidArgument(format!(
+                "the webhook path {path} doesn't match the path of the webhook url {}",
+                url.path()
+            ))
+            .into());
+        }
+        opts.set_addr(addr).set_path(path);
+        self.start_with_webhook(&opts).await
+    }
+
+    /// Resolves with the address the webhook is listening on once the client
+    /// started it, which is useful when it's set to listen on port 0 to let
+    /// the os pick a free port
+    pub async fn webhook_addr(&self) -> SocketAddr {
+        self.webhook_addr.get().await
+    }
+
+    async fn register_webhook(
+        &self,
+        opts: &WebhookOptions,
+        allowed_updates: Vec<UpdateType>,
+        drop_pending_updates: bool,
+    ) -> Result<()> {
+        let Some(url) = &opts.url else {
+            return Ok(());
+        };
+        let certificate = match &opts.certificate {
+            Some(path) if opts.upload_certificate => Some(InputFile::from_path(path)?),
+            _ => None,
+        };
+
+        self.api_client
+            .set_webhook(SetWebhook {
+                url: url.to_string(),
+                certificate,
+                max_connections: opts.max_connections,
+                allowed_updates: Some(allowed_updates),
+                drop_pending_updates: drop_pending_updates.then_some(true),
+                ip_address: None,
+                secret_token: opts.secret_token.clone(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the updates of the client as a stream, for handling them with
+    /// your own dispatch logic instead of registering handlers. It long polls
+    /// telegram for the [`Client::current_allowed_updates`], ending once the
+    /// client is stopped with [`Client::shutdown`].
+    ///
+    /// The updates don't pass through the middlewares, handlers or framework
+    /// of the client, call [`Client::fire_handlers`] with the ones they should
+    /// handle. Errors of getUpdates are logged and polling is retried after a
+    /// second, so the stream only ends when stopped.
+    ///
+    /// ```rust,no_run
+    /// use futures::StreamExt;
+    /// use telexide::{model::UpdateContent, prelude::*};
+    ///
+    /// # async fn run(client: Client) {
+    /// let mut updates = client.updates_stream();
+    /// while let Some(update) = updates.next().await {
+    ///     match update.content {
+    ///         UpdateContent::Message(m) => println!("new message in {}", m.chat.get_id()),
+    ///         _ => client.fire_handlers(update),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn updates_stream(&self) -> impl Stream<Item = Update> + Send + Unpin {
+        let mut stream = UpdatesStream::new(self.api_client.clone());
+        stream
+            .set_allowed_updates(self.current_allowed_updates())
+            .set_runtime(self.runtime.clone());
+        if let Some(storage) = &self.offset_storage {
+            stream.set_offset_storage(storage.clone());
+        }
+        if !self.poll_stall_handlers.is_empty() {
+            let client = self.clone();
+            stream.on_stall(move |stall| client.fire_poll_stall_handlers(stall));
         }
 
-        if let Some(webhook_url) = &opts.url {
+        let runtime = self.runtime.clone();
+        let shutdown = self.shutdown.clone();
+        stream
+            .filter_map(move |poll| {
+                let runtime = runtime.clone();
+                async move {
+                    match poll {
+                        Ok(update) => Some(update),
+                        Err(err) => {
+                            log::warn!("couldn't get updates: {}", redaction::scrub(&err));
+                            runtime.sleep(POLL_RETRY_DELAY).await;
+                            None
+                        },
+                    }
+                }
+            })
+            .take_until(async move {
+                shutdown.stopped().await;
+                shutdown.reset();
+            })
+            .boxed()
+    }
+
+    /// sets the webhook again if the new config changes the allowed updates
+    async fn reconfigure_webhook(&self, opts: &WebhookOptions, config: &RuntimeConfig) {
+        if opts.url.is_none() || config.allowed_updates.is_none() {
+            return;
+        }
+        if let Err(e) = self
+            .register_webhook(opts, self.allowed_updates_in(config), false)
+            .await
+        {
+            log::warn!(
+                "couldn't apply the new allowed updates to the webhook: {}",
+                redaction::scrub(e)
+            );
+        }
+    }
+
+    /// The update types the client receives: the ones set in its
+    /// [`LiveConfig`], if set there, otherwise [`Client::allowed_updates`]
+    pub fn current_allowed_updates(&self) -> Vec<UpdateType> {
+        match &self.live_config {
+            Some(c) => self.allowed_updates_in(&c.get()),
+            None => self.allowed_updates.clone(),
+        }
+    }
+
+    fn allowed_updates_in(&self, config: &RuntimeConfig) -> Vec<UpdateType> {
+        config
+            .allowed_updates
+            .clone()
+            .unwrap_or_else(|| self.allowed_updates.clone())
+    }
+
+    /// Takes a [`HealthSnapshot`] of the client, for exposing it on a health
+    /// endpoint. When running with a webhook, the webhook info is requested
+    /// from telegram, and every storage added with
+    /// [`ClientBuilder::add_health_storage`] is checked for being reachable.
+    pub async fn health(&self) -> HealthSnapshot {
+        self.health.snapshot(&**self.api_client).await
+    }
+
+    /// The types of the chats the client sends messages to, see [`ChatTypes`]
+    pub fn chat_types(&self) -> &ChatTypes {
+        &self.chat_types
+    }
+
+    /// The [`LiveConfig`] set on the [`ClientBuilder`], if any
+    pub fn live_config(&self) -> Option<&LiveConfig> {
+        self.live_config.as_ref()
+    }
+
+    /// checks the capabilities needed for the allowed updates, checks the
+    /// payloads against the schema and, if using the framework, gives it the
+    /// username of the bot and updates the commands in telegram
+    async fn prepare(&self) -> Result<()> {
+        if let Some(schema) = &self.api_schema {
+            log_self_check(schema);
+        } else if cfg!(any(debug_assertions, feature = "schema-check")) {
+            log_self_check(ApiSchema::bundled());
+        }
+
+        if self
+            .current_allowed_updates()
+            .contains(&UpdateType::InlineQuery)
+        {
             self.api_client
-                .set_webhook(SetWebhook {
-                    url: webhook_url.to_string(),
-                    certificate: None,
-                    max_connections: None,
-                    allowed_updates: Some(self.allowed_updates.clone()),
-                    drop_pending_updates: None,
-                    ip_address: None, // TODO: add opts for these
-                })
+                .require_capability(BotCapability::InlineQueries)
                 .await?;
         }
 
-        log::info!("starting to listen on the webhook");
-        let mut receiver = Webhook::new(opts).start();
-        while let Some(u) = receiver.recv().await {
-            match u {
-                Ok(update) => {
-                    self.fire_handlers(update);
-                },
-                Err(err) => return Err(err),
+        if let Some(fr) = self.framework.clone() {
+            if let Some(username) = self.api_client.get_me_cached().await?.username {
+                fr.set_bot_name(&username);
             }
+            self.api_client
+                .set_my_commands(fr.get_commands().into())
+                .await?;
         }
 
         Ok(())
@@ -202,51 +554,317 @@ impl Client {
 
     /// Subscribes an update event handler function ([`EventHandlerFunc`]) to
     /// the client and will be ran whenever a new update is received
-    pub fn subscribe_handler_func(&mut self, handler: EventHandlerFunc) {
-        self.event_handlers.push(handler);
+    pub fn subscribe_handler_func<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(Context, Update) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        Arc::make_mut(&mut self.event_handlers).push(event_handlers::boxed(handler));
     }
 
     /// Subscribes a raw update event handler function ([`RawEventHandlerFunc`])
     /// to the client and will be ran whenever a new update is received
-    pub fn subscribe_raw_handler(&mut self, handler: RawEventHandlerFunc) {
-        self.raw_event_handlers.push(handler);
+    pub fn subscribe_raw_handler<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(Context, RawUpdate) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        Arc::make_mut(&mut self.raw_event_handlers).push(event_handlers::boxed(handler));
+    }
+
+    /// Subscribes a business connection handler function
+    /// ([`BusinessConnectionHandlerFunc`]) to the client and will be ran
+    /// whenever a business connection update is received
+    pub fn subscribe_business_connection_handler<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(Context, BusinessConnection) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        Arc::make_mut(&mut self.business_connection_handlers).push(event_handlers::boxed(handler));
+    }
+
+    /// Subscribes a poll stall handler function ([`PollStallHandlerFunc`]) to
+    /// the client and will be ran whenever the watchdog of the long poll
+    /// recovers from a stall
+    pub fn subscribe_poll_stall_handler<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(Context, PollStall) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        Arc::make_mut(&mut self.poll_stall_handlers).push(event_handlers::boxed(handler));
+    }
+
+    /// Subscribes a reconfigure handler function ([`ReconfigureHandlerFunc`])
+    /// to the client and will be ran whenever its [`LiveConfig`] changes while
+    /// it's running
+    pub fn subscribe_reconfigure_handler<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(Context, Arc<RuntimeConfig>) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: HandlerResult,
+    {
+        Arc::make_mut(&mut self.reconfigure_handlers).push(event_handlers::boxed(handler));
+    }
+
+    /// Sets the function called with the errors returned by the handlers and
+    /// commands of the client, after they're logged. Use it to report the
+    /// errors somewhere, or to tell the user something went wrong:
+    ///
+    /// ```rust,no_run
+    /// use telexide::{api::types::SendMessage, prelude::*};
+    ///
+    /// # fn setup(client: &mut Client) {
+    /// client.on_handler_error(|ctx, err| async move {
+    ///     if let Some(chat_id) = err.chat_id {
+    ///         let msg = SendMessage::new(chat_id, "something went wrong, please try again");
+    ///         ctx.api.send_message(msg).await.ok();
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn on_handler_error<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(Context, HandlerError) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.error_handler = Some(event_handlers::boxed_error_handler(handler));
+    }
+
+    /// Adds a [`Middleware`] to the end of the middleware chain, which every
+    /// incoming update passes through before being dispatched
+    pub fn add_middleware<M: Middleware>(&mut self, middleware: M) {
+        Arc::make_mut(&mut self.middlewares).push(Arc::new(middleware));
     }
 
     // public only for testing purposes
     #[doc(hidden)]
     pub fn fire_handlers(&self, update: Update) {
-        for h in self.raw_event_handlers.clone() {
-            let ctx = Context::new(self.api_client.clone(), self.data.clone());
-            let u = update.clone();
-            tokio::spawn(async move { h(ctx, u.into()).await });
+        self.spawn_dispatch(update, None);
+    }
+
+    /// dispatches the update once fewer than the maximum amount of updates
+    /// set with [`ClientBuilder::set_max_concurrent_updates`] are being
+    /// handled, so the client doesn't fetch new updates while it's busy
+    async fn queue_update(&self, update: Update) {
+        let permit = self.acquire_dispatch_permit().await;
+        self.spawn_dispatch(update, permit);
+    }
+
+    async fn acquire_dispatch_permit(&self) -> Option<OwnedSemaphorePermit> {
+        match &self.dispatch_permits {
+            Some(permits) => permits.clone().acquire_owned().await.ok(),
+            None => None,
         }
+    }
+
+    fn spawn_dispatch(&self, update: Update, mut permit: Option<OwnedSemaphorePermit>) {
+        let in_flight = self.health.update_received();
+        let callback_id = match (&self.pending_callbacks, &update.content) {
+            (Some(pending), UpdateContent::CallbackQuery(q)) => {
+                pending.received(&q.id);
+                Some(q.id.clone())
+            },
+            _ => None,
+        };
+        let mut turn = match (&self.chat_queues, update.get_chat()) {
+            (Some(queues), Some(chat)) => Some(queues.enqueue(chat.get_id())),
+            _ => None,
+        };
+
+        let client = self.clone();
+        self.runtime.spawn(Box::pin(async move {
+            if let Some(turn) = turn.as_mut().filter(|t| t.is_queued()) {
+                // an update waiting behind another one of its chat gives up its
+                // permit meanwhile, so a busy chat doesn't hold up the others
+                let had_permit = permit.take().is_some();
+                turn.wait().await;
+                if had_permit {
+                    permit = client.acquire_dispatch_permit().await;
+                }
+            }
+            client.dispatch(update).await;
+            drop(in_flight);
+            if let (Some(pending), Some(id)) = (&client.pending_callbacks, callback_id) {
+                pending.done(&id);
+            }
+            drop(turn);
+            drop(permit);
+        }));
+    }
+
+    /// Gracefully stops the running client: it stops fetching updates, waits
+    /// for the ones it's handling to finish and then resolves the future
+    /// returned by [`Client::start`]. See [`ShutdownHandle`] for the details.
+    pub fn shutdown(&self) {
+        self.shutdown.stop();
+    }
+
+    /// Returns a handle for stopping the client once it's started, see
+    /// [`Client::shutdown`]
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// waits for the handlers still running when shutting down, then answers
+    /// the callback queries they left unanswered
+    async fn finish_running(&self) {
+        if self.shutdown.is_stopping() {
+            let timeout = self.runtime.sleep(self.shutdown_timeout);
+            if let Either::Right(_) = select(Box::pin(self.health.drained()), timeout).await {
+                log::warn!(
+                    "stopped waiting for {} updates still being handled when shutting down",
+                    self.health.in_flight()
+                );
+            }
+            self.shutdown.reset();
+        }
+        self.answer_stale_callbacks().await;
+    }
 
-        for h in self.event_handlers.clone() {
+    /// Answers the callback queries which are still being handled and weren't
+    /// answered yet with the notice set by
+    /// [`ClientBuilder::set_stale_callback_answer`], so their users aren't
+    /// left looking at a loading button. Returns how many were answered.
+    ///
+    /// The client does so when it stops running, call this when shutting it
+    /// down in another way.
+    pub async fn answer_stale_callbacks(&self) -> usize {
+        match &self.pending_callbacks {
+            Some(pending) => pending.answer_all(self.api_client.as_ref().as_ref()).await,
+            None => 0,
+        }
+    }
+
+    fn fire_poll_stall_handlers(&self, stall: PollStall) {
+        for (i, h) in self.poll_stall_handlers.iter().enumerate() {
             let ctx = Context::new(self.api_client.clone(), self.data.clone());
-            let u = update.clone();
-            tokio::spawn(async move { h(ctx, u).await });
+            let fut = h(ctx.clone(), stall);
+            let name = format!("poll_stall_handler#{i}");
+            self.runtime
+                .spawn(Box::pin(self.catch_error(ctx, name, None, fut)));
         }
+    }
 
-        if self.framework.is_some() {
+    fn fire_reconfigure_handlers(&self, config: &Arc<RuntimeConfig>) {
+        for (i, h) in self.reconfigure_handlers.iter().enumerate() {
             let ctx = Context::new(self.api_client.clone(), self.data.clone());
-            let fr = self.framework.clone();
-            fr.as_ref()
-                .expect("Framework needs to be set before trying to fire commands")
-                .fire_commands(ctx, update);
+            let fut = h(ctx.clone(), config.clone());
+            let name = format!("reconfigure_handler#{i}");
+            self.runtime
+                .spawn(Box::pin(self.catch_error(ctx, name, None, fut)));
+        }
+    }
+
+    /// runs the update through the middleware chain and then all handlers,
+    /// resolving once all of them have finished
+    pub(crate) async fn dispatch(&self, update: Update) {
+        let ctx = Context::new(self.api_client.clone(), self.data.clone());
+        let endpoint = |ctx, update| self.run_handlers(ctx, update).boxed();
+
+        Next::new(&self.middlewares, &endpoint)
+            .run(ctx, update)
+            .await;
+    }
+
+    async fn run_handlers(&self, ctx: Context, update: Update) {
+        let mut handles = Vec::new();
+        if let Some(chat) = update.get_chat() {
+            self.chat_types.note(chat);
+        }
+        let chat_id = update.get_chat().map(Chat::get_id);
+        let update_type = update.content.kind();
+        if let Some(metrics) = &self.handler_metrics {
+            metrics.record_update(update_type);
+        }
+
+        for (i, h) in self.raw_event_handlers.iter().enumerate() {
+            let fut = h(ctx.clone(), update.clone().into());
+            let name = format!("raw_handler#{i}");
+            handles.push(self.spawn_measured(ctx.clone(), name, chat_id, update_type, fut));
+        }
+
+        for (i, h) in self.event_handlers.iter().enumerate() {
+            let fut = h(ctx.clone(), update.clone());
+            let name = format!("handler#{i}");
+            handles.push(self.spawn_measured(ctx.clone(), name, chat_id, update_type, fut));
+        }
+
+        if let UpdateContent::BusinessConnection(c) = &update.content {
+            for (i, h) in self.business_connection_handlers.iter().enumerate() {
+                let fut = h(ctx.clone(), c.clone());
+                let name = format!("business_connection_handler#{i}");
+                handles.push(self.spawn_measured(ctx.clone(), name, chat_id, update_type, fut));
+            }
+        }
+
+        if let Some(fr) = &self.framework {
+            handles.append(&mut fr.spawn_commands(
+                ctx,
+                update,
+                self.handler_metrics.clone(),
+                self.error_handler.clone(),
+                self.runtime.as_ref(),
+            ));
+        }
+
+        let panicked = join_all(handles)
+            .await
+            .iter()
+            .filter(|r| r.is_err())
+            .count();
+        if let Some(metrics) = &self.handler_metrics {
+            for _ in 0..panicked {
+                metrics.record_error();
+            }
+        }
+    }
+
+    fn spawn_measured(
+        &self,
+        ctx: Context,
+        name: String,
+        chat_id: Option<i64>,
+        update_type: &'static str,
+        fut: HandlerOutcome,
+    ) -> TaskHandle {
+        let fut = self.catch_error(ctx, name.clone(), chat_id, fut);
+        let metrics = self.handler_metrics.clone();
+        let fut = metrics::measure(metrics, name, chat_id, update_type, fut);
+        spawn_task(self.runtime.as_ref(), fut)
+    }
+
+    /// reports the error the handler returns, if any
+    fn catch_error(
+        &self,
+        ctx: Context,
+        handler: String,
+        chat_id: Option<i64>,
+        fut: HandlerOutcome,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let metrics = self.handler_metrics.clone();
+        let on_error = self.error_handler.clone();
+        async move {
+            if let Err(error) = fut.await {
+                if let Some(metrics) = metrics {
+                    metrics.record_error();
+                }
+                let error = HandlerError {
+                    handler,
+                    chat_id,
+                    error,
+                };
+                report_error(ctx, error, on_error).await;
+            }
         }
     }
 }
 
 impl From<Box<APIConnector>> for Client {
     fn from(api: Box<APIConnector>) -> Self {
-        Self {
-            api_client: Arc::new(api),
-            event_handlers: Vec::new(),
-            raw_event_handlers: Vec::new(),
-            data: Arc::new(RwLock::new(ShareMap::custom())),
-            framework: None,
-            webhook_opts: None,
-            allowed_updates: Vec::new(),
-        }
+        Self::with_api(Arc::new(api), None)
     }
 }
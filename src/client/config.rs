@@ -0,0 +1,345 @@
+use super::ChatTypes;
+use crate::{
+    api::{types::UpdateType, APIEndpoint, Response, API},
+    model::{ChatType, ParseMode},
+    redaction,
+    utils::{result::Result, FormDataFile},
+};
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::{sync::broadcast, task::JoinHandle};
+
+/// The settings of a [`Client`] which can be changed while it's running, see
+/// [`LiveConfig`]
+///
+/// The config can be (de)serialized, fields missing from the json get their
+/// default value:
+///
+/// ```json
+/// {
+///     "default_parse_mode": "HTML",
+///     "allowed_updates": ["message", "callback_query"],
+///     "chat_defaults": {"channel": {"disable_notification": true}},
+///     "toggles": {"welcome_message": true}
+/// }
+/// ```
+///
+/// [`Client`]: struct.Client.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// The parse mode used for the texts and captions sent and edited through
+    /// the client which don't set a parse mode or entities themselves
+    pub default_parse_mode: Option<ParseMode>,
+    /// The update types to receive, replacing the ones set on the [`Client`]
+    /// when set
+    ///
+    /// [`Client`]: struct.Client.html
+    pub allowed_updates: Option<Vec<UpdateType>>,
+    /// The send options applied to the messages sent through the client,
+    /// depending on the type of the chat they're sent to
+    pub chat_defaults: HashMap<ChatType, SendDefaults>,
+    /// Switches for features of your bot, to turn them on and off without a
+    /// restart
+    pub toggles: HashMap<String, bool>,
+}
+
+impl RuntimeConfig {
+    /// Returns whether the toggle is on, toggles which aren't set are off
+    pub fn is_enabled(&self, toggle: &str) -> bool {
+        self.toggles.get(toggle).copied().unwrap_or(false)
+    }
+
+    /// Turns the toggle on or off
+    pub fn set_toggle(&mut self, toggle: &str, enabled: bool) -> &mut Self {
+        self.toggles.insert(toggle.to_owned(), enabled);
+        self
+    }
+
+    /// Sets the send options applied to the messages sent to chats of the
+    /// given type, as told by the [`ChatTypes`] of the client
+    pub fn set_chat_defaults(&mut self, chat_type: ChatType, defaults: SendDefaults) -> &mut Self {
+        self.chat_defaults.insert(chat_type, defaults);
+        self
+    }
+
+    /// Gets the send options applied to the messages sent to chats of the
+    /// given type
+    pub fn chat_defaults(&self, chat_type: ChatType) -> SendDefaults {
+        self.chat_defaults
+            .get(&chat_type)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// The send options a [`RuntimeConfig`] applies to the messages sent to chats
+/// of a certain type, e.g. sending silently in channels or protecting the
+/// content in private chats.
+///
+/// The defaults only turn options on: a message which already turns an option
+/// on keeps it, but there's no way for a single message to turn an option off
+/// which its chat type has on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct SendDefaults {
+    /// Sends the messages silently. Users will receive a notification with no
+    /// sound.
+    pub disable_notification: bool,
+    /// Protects the contents of the sent messages from forwarding and saving
+    pub protect_content: bool,
+}
+
+impl SendDefaults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets `disable_notification` to true
+    pub fn silent(&mut self) -> &mut Self {
+        self.disable_notification = true;
+        self
+    }
+
+    /// sets `protect_content` to true
+    pub fn protected(&mut self) -> &mut Self {
+        self.protect_content = true;
+        self
+    }
+
+    fn apply(&self, payload: &mut serde_json::Map<String, Value>) {
+        for (key, on) in [
+            ("disable_notification", self.disable_notification),
+            ("protect_content", self.protect_content),
+        ] {
+            if on {
+                payload.insert(key.to_owned(), json!(true));
+            }
+        }
+    }
+}
+
+/// A [`RuntimeConfig`] which can be swapped atomically while the client is
+/// running, for example by a watcher of a config file or a remote config
+/// service, so long-running bots can be tuned without restarting them.
+///
+/// Readers get a snapshot of the config with [`get`], which a swap doesn't
+/// change halfway through. Every swap changing the config emits it to the
+/// [`subscribe`]rs, and the client started with the live config passes it to
+/// its [`ReconfigureHandlerFunc`]s after applying the new allowed updates.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use telexide::{
+///     client::{LiveConfig, RuntimeConfig},
+///     prelude::*,
+/// };
+///
+/// # async fn run(token: &str) -> telexide::Result<()> {
+/// let config = LiveConfig::new(RuntimeConfig::default());
+/// config.watch_file("bot-config.json", Duration::from_secs(10));
+///
+/// let client = ClientBuilder::new()
+///     .set_token(token)
+///     .set_live_config(config)
+///     .build();
+/// client.start().await
+/// # }
+/// ```
+///
+/// Handlers can read the current config with [`Context::config`].
+///
+/// [`get`]: LiveConfig::get
+/// [`subscribe`]: LiveConfig::subscribe
+/// [`ReconfigureHandlerFunc`]: super::ReconfigureHandlerFunc
+/// [`Context::config`]: super::Context::config
+#[derive(Clone)]
+pub struct LiveConfig {
+    current: Arc<RwLock<Arc<RuntimeConfig>>>,
+    changes: broadcast::Sender<Arc<RuntimeConfig>>,
+}
+
+impl typemap::Key for LiveConfig {
+    type Value = LiveConfig;
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        Self::new(RuntimeConfig::default())
+    }
+}
+
+impl LiveConfig {
+    /// Creates a live config starting out with the given config
+    pub fn new(config: RuntimeConfig) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(config))),
+            changes: broadcast::channel(16).0,
+        }
+    }
+
+    /// Gets a snapshot of the current config
+    pub fn get(&self) -> Arc<RuntimeConfig> {
+        self.current.read().clone()
+    }
+
+    /// Swaps in the new config, emitting it to the subscribers if it differs
+    /// from the current one
+    pub fn store(&self, config: RuntimeConfig) {
+        let config = Arc::new(config);
+        let previous = std::mem::replace(&mut *self.current.write(), config.clone());
+        if previous != config {
+            log::info!("the runtime configuration changed");
+            // there being no subscribers is fine
+            let _ = self.changes.send(config);
+        }
+    }
+
+    /// Changes a copy of the current config and swaps it in, see
+    /// [`store`](LiveConfig::store)
+    pub fn update<F: FnOnce(&mut RuntimeConfig)>(&self, change: F) {
+        let mut config = RuntimeConfig::clone(&self.get());
+        change(&mut config);
+        self.store(config);
+    }
+
+    /// Receives every config swapped in from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<RuntimeConfig>> {
+        self.changes.subscribe()
+    }
+
+    /// Spawns a task reloading the config from the json file at the path
+    /// whenever it was modified, checking it at the given interval. Files
+    /// which can't be read or parsed are logged and otherwise ignored, keeping
+    /// the current config.
+    pub fn watch_file<P: Into<PathBuf>>(&self, path: P, interval: Duration) -> JoinHandle<()> {
+        let config = self.clone();
+        let path = path.into();
+        tokio::spawn(async move {
+            let mut last_modified: Option<SystemTime> = None;
+            loop {
+                match tokio::fs::metadata(&path).await.and_then(|m| m.modified()) {
+                    Ok(modified) if last_modified != Some(modified) => {
+                        last_modified = Some(modified);
+                        match read_config(&path).await {
+                            Ok(c) => config.store(c),
+                            Err(e) => log::warn!(
+                                "couldn't reload the runtime configuration from {}: {}",
+                                redaction::scrub(path.display()),
+                                redaction::scrub(e)
+                            ),
+                        }
+                    },
+                    Ok(_) => {},
+                    Err(e) => log::warn!(
+                        "couldn't check {} for changes: {}",
+                        redaction::scrub(path.display()),
+                        redaction::scrub(e)
+                    ),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+}
+
+async fn read_config(path: &std::path::Path) -> Result<RuntimeConfig> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// An [`API`] wrapper applying the [`RuntimeConfig::default_parse_mode`] and
+/// [`RuntimeConfig::chat_defaults`] to the outgoing calls
+pub(super) struct Configured<A> {
+    api: A,
+    config: LiveConfig,
+    chat_types: ChatTypes,
+}
+
+impl<A> Configured<A> {
+    pub(super) fn new(api: A, config: LiveConfig, chat_types: ChatTypes) -> Self {
+        Self {
+            api,
+            config,
+            chat_types,
+        }
+    }
+
+    async fn apply(&self, endpoint: &APIEndpoint, data: Option<Value>) -> Option<Value>
+    where
+        A: API + Send,
+    {
+        let mut data = data?;
+        let method = endpoint.as_str();
+        let sends = (method.starts_with("send") && method != "sendChatAction")
+            || method.starts_with("copyMessage")
+            || method.starts_with("forwardMessage");
+        if !(sends || method.starts_with("edit")) {
+            return Some(data);
+        }
+
+        let config = self.config.get();
+        let chat_id = data.get("chat_id").and_then(Value::as_i64);
+        if let (true, Some(chat_id)) = (sends, chat_id) {
+            // a chat which can't be looked up gets the defaults of the type its
+            // id suggests, the call will most likely fail anyway
+            let chat_type = match self.chat_types.resolve(&self.api, chat_id).await {
+                Ok(chat_type) => chat_type,
+                Err(_) => self.chat_types.chat_type(chat_id),
+            };
+            if let Some(payload) = data.as_object_mut() {
+                config.chat_defaults(chat_type).apply(payload);
+            }
+        }
+
+        if let (Some(mode), Some(payload)) =
+            (config.default_parse_mode.clone(), data.as_object_mut())
+        {
+            let is_set = |key: &str| payload.get(key).is_some_and(|v| !v.is_null());
+            let has_text = is_set("text") || is_set("caption");
+            if has_text
+                && !is_set("parse_mode")
+                && !is_set("entities")
+                && !is_set("caption_entities")
+            {
+                payload.insert("parse_mode".to_owned(), json!(mode));
+            }
+        }
+        Some(data)
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for Configured<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        let data = self.apply(&endpoint, data).await;
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        let data = self.apply(&endpoint, data).await;
+        self.api.post(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        let data = self.apply(&endpoint, data).await;
+        self.api.post_file(endpoint, data, files).await
+    }
+}
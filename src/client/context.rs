@@ -1,4 +1,9 @@
-use super::APIConnector;
+use super::{APIConnector, LiveConfig, RuntimeConfig};
+use crate::{
+    helpers::{Experiments, FileVault, I18n, LocaleFormat},
+    model::Message,
+    utils::result::{Result, TelegramError},
+};
 use parking_lot::RwLock;
 use std::sync::Arc;
 use typemap::ShareMap;
@@ -6,6 +11,10 @@ use typemap::ShareMap;
 /// The context object is an utility object that gets passed to all event
 /// handlers, it provides access to the API client and to any custom data you
 /// have set in the data object.
+///
+/// Every handler gets its own clone of the context, which only bumps the
+/// reference counts of the [`Arc`]s it consists of. Shared state belongs in
+/// [`data`](Context::data), behind an `Arc` as well, rather than in new fields.
 #[derive(Clone)]
 pub struct Context {
     /// The API client, implementing the [`API`] trait
@@ -18,6 +27,12 @@ pub struct Context {
     pub data: Arc<RwLock<ShareMap>>,
 }
 
+// handlers move their context into a future which may run on any thread
+const _: fn() = || {
+    fn assert_bounds<T: Clone + Send + Sync + 'static>() {}
+    assert_bounds::<Context>();
+};
+
 impl Context {
     pub fn new(api: Arc<Box<APIConnector>>, data: Arc<RwLock<ShareMap>>) -> Self {
         Self {
@@ -25,4 +40,82 @@ impl Context {
             data,
         }
     }
+
+    /// Gets a snapshot of the current [`RuntimeConfig`] of the client, from the
+    /// [`LiveConfig`] set on the builder. Without a live config the default
+    /// config is returned.
+    pub fn config(&self) -> Arc<RuntimeConfig> {
+        self.data
+            .read()
+            .get::<LiveConfig>()
+            .map(LiveConfig::get)
+            .unwrap_or_default()
+    }
+
+    /// Sends the file stored under the given name for the chat in the
+    /// [`FileVault`] inserted into the [`data`], using the send method
+    /// matching the kind of the stored media
+    ///
+    /// [`data`]: Context::data
+    pub async fn send_stored(&self, chat_id: i64, name: &str) -> Result<Message> {
+        let vault = self
+            .data
+            .read()
+            .get::<FileVault>()
+            .cloned()
+            .ok_or_else(|| {
+                TelegramError::Unknown("no FileVault was inserted into the data".to_owned())
+            })?;
+
+        vault.send(&**self.api, chat_id, name).await
+    }
+
+    /// Translates the message with the given key into the language of the
+    /// sender of the message, using the [`I18n`] inserted into the [`data`].
+    /// See [`I18n`] for how the language is chosen.
+    ///
+    /// [`data`]: Context::data
+    pub async fn t(&self, message: &Message, key: &str) -> Result<String> {
+        let i18n = self.data.read().get::<I18n>().cloned().ok_or_else(|| {
+            TelegramError::Unknown("no I18n was inserted into the data".to_owned())
+        })?;
+
+        i18n.translate_for(message, key).await
+    }
+
+    /// Gets the conventions for rendering numbers and dates in the language of
+    /// the sender of the message, using the [`I18n`] inserted into the
+    /// [`data`].
+    ///
+    /// [`data`]: Context::data
+    pub async fn locale_format(&self, message: &Message) -> Result<LocaleFormat> {
+        let i18n = self.data.read().get::<I18n>().cloned().ok_or_else(|| {
+            TelegramError::Unknown("no I18n was inserted into the data".to_owned())
+        })?;
+
+        i18n.locale_format_for(message).await
+    }
+
+    /// Replies to the message with the variant of the experiment picked for
+    /// its sender, using the [`Experiments`] inserted into the [`data`].
+    /// Messages without a sender, like channel posts, use the id of the chat
+    /// instead.
+    ///
+    /// [`data`]: Context::data
+    pub async fn send_variant(&self, message: &Message, experiment: &str) -> Result<Message> {
+        let experiments = self
+            .data
+            .read()
+            .get::<Experiments>()
+            .cloned()
+            .ok_or_else(|| {
+                TelegramError::Unknown("no Experiments were inserted into the data".to_owned())
+            })?;
+
+        let chat_id = message.chat.get_id();
+        let user_id = message.from.as_ref().map_or(chat_id, |u| u.id);
+        experiments
+            .send_variant(&**self.api, chat_id, user_id, experiment)
+            .await
+    }
 }
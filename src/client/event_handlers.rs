@@ -1,12 +1,138 @@
-use super::{Context, FutureOutcome};
-use crate::model::{raw::RawUpdate, Update};
+use super::{Context, FutureOutcome, PollStall, RuntimeConfig};
+use crate::{
+    model::{raw::RawUpdate, BusinessConnection, Update},
+    redaction,
+    Error,
+    Result,
+};
+use std::{future::Future, pin::Pin, sync::Arc};
+
+/// The pinned future the handlers of the client are stored returning
+pub(crate) type HandlerOutcome = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+/// What the future of a handler resolves to: nothing, or a [`Result`] whose
+/// error is logged and passed to the [`ErrorHandlerFunc`] of the client
+pub trait HandlerResult {
+    fn into_result(self) -> Result<()>;
+}
+
+impl HandlerResult for () {
+    fn into_result(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl HandlerResult for Result<()> {
+    fn into_result(self) -> Result<()> {
+        self
+    }
+}
+
+/// stores a handler, whatever its future resolves to
+pub(super) fn boxed<T, F, Fut>(
+    handler: F,
+) -> Arc<dyn Fn(Context, T) -> HandlerOutcome + Send + Sync>
+where
+    F: Fn(Context, T) -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: HandlerResult,
+{
+    Arc::new(move |ctx, arg| {
+        let fut = handler(ctx, arg);
+        Box::pin(async move { fut.await.into_result() })
+    })
+}
+
+/// boxes the error hook of the client
+pub(super) fn boxed_error_handler<F, Fut>(handler: F) -> ErrorHandlerFunc
+where
+    F: Fn(Context, HandlerError) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    Arc::new(move |ctx, error| Box::pin(handler(ctx, error)))
+}
+
+/// A handler or command of the client returning an error, passed to its
+/// [`ErrorHandlerFunc`]
+#[derive(Debug)]
+pub struct HandlerError {
+    /// The name of the handler, like "handler#0" or "command /start"
+    pub handler: String,
+    /// The chat of the update being handled, for telling the user something
+    /// went wrong
+    pub chat_id: Option<i64>,
+    /// The error the handler returned
+    pub error: Error,
+}
+
+/// A function that handles the errors returned by the handlers and commands
+/// of the client, it receives a [`Context`] and the [`HandlerError`] and
+/// returns a pinned future. Set it with [`Client::on_handler_error`].
+///
+/// [`Client::on_handler_error`]: super::Client::on_handler_error
+pub type ErrorHandlerFunc = Arc<dyn Fn(Context, HandlerError) -> FutureOutcome + Send + Sync>;
+
+/// logs the error and passes it on to the error handler
+pub(crate) async fn report_error(
+    ctx: Context,
+    error: HandlerError,
+    on_error: Option<ErrorHandlerFunc>,
+) {
+    log::warn!(
+        "{} returned error: {}",
+        error.handler,
+        redaction::scrub(&error.error)
+    );
+    if let Some(on_error) = on_error {
+        on_error(ctx, error).await;
+    }
+}
 
 /// A function that handles a new update, it receives a [`Context`] and
 /// [`Update`] and returns a pinned future. Wrap an async function with
 /// `#[prepare_listener]` for easier development.
-pub type EventHandlerFunc = fn(Context, Update) -> FutureOutcome;
+///
+/// The future may resolve to a [`Result`], its error is then passed to the
+/// [`ErrorHandlerFunc`] of the client. Closures capturing state, like a
+/// database pool, are handlers as well:
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use telexide::prelude::*;
+///
+/// # struct Pool;
+/// # impl Pool { async fn log(&self, _: i64) {} }
+/// # fn setup(client: &mut Client, pool: Arc<Pool>) {
+/// client.subscribe_handler_func(move |_ctx, update| {
+///     let pool = pool.clone();
+///     Box::pin(async move { pool.log(update.update_id).await })
+/// });
+/// # }
+/// ```
+pub type EventHandlerFunc = Arc<dyn Fn(Context, Update) -> HandlerOutcome + Send + Sync>;
 
 /// A function that handles a new raw update, it receives a [`Context`] and
 /// [`RawUpdate`] and returns a pinned future. Wrap an async function with
 /// `#[prepare_listener]` for easier development.
-pub type RawEventHandlerFunc = fn(Context, RawUpdate) -> FutureOutcome;
+pub type RawEventHandlerFunc = Arc<dyn Fn(Context, RawUpdate) -> HandlerOutcome + Send + Sync>;
+
+/// A function that handles a new or changed [`BusinessConnection`], it receives
+/// a [`Context`] and the [`BusinessConnection`] and returns a pinned future.
+/// Wrap an async function with `#[prepare_listener]` for easier development.
+pub type BusinessConnectionHandlerFunc =
+    Arc<dyn Fn(Context, BusinessConnection) -> HandlerOutcome + Send + Sync>;
+
+/// A function that handles the watchdog of the long poll recovering from a
+/// [`PollStall`], it receives a [`Context`] and the [`PollStall`] and returns a
+/// pinned future. Wrap an async function with `#[prepare_listener]` for easier
+/// development.
+pub type PollStallHandlerFunc = Arc<dyn Fn(Context, PollStall) -> HandlerOutcome + Send + Sync>;
+
+/// A function that handles the [`LiveConfig`] of the client changing, it
+/// receives a [`Context`] and the new [`RuntimeConfig`] and returns a pinned
+/// future. Wrap an async function with `#[prepare_listener]` for easier
+/// development.
+///
+/// [`LiveConfig`]: super::LiveConfig
+pub type ReconfigureHandlerFunc =
+    Arc<dyn Fn(Context, Arc<RuntimeConfig>) -> HandlerOutcome + Send + Sync>;
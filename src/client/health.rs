@@ -0,0 +1,381 @@
+use crate::{
+    api::{APIEndpoint, Response, API},
+    model::{utils::unix_date_formatting, WebhookInfo},
+    storage::Storage,
+    utils::{result::Result, FormDataFile},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{io::AsyncWrite, sync::watch};
+
+/// The amount of most recent api calls the error rate is calculated over
+const API_WINDOW: usize = 100;
+
+/// How long the webhook status is reused for, so frequent health probes don't
+/// each make a call to telegram
+const WEBHOOK_STATUS_TTL: Duration = Duration::from_secs(30);
+
+tokio::task_local! {
+    /// set while the health snapshot gets the webhook info, so the call isn't
+    /// counted in the api error rate
+    static PROBING: ();
+}
+
+/// How the [`Client`] receives its updates
+///
+/// [`Client`]: struct.Client.html
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientMode {
+    /// The client isn't receiving any updates, it wasn't started yet or
+    /// stopped
+    Stopped,
+    /// The client is long polling getUpdates
+    Polling,
+    /// The client is listening on a webhook
+    Webhook,
+}
+
+/// The state of a [`Storage`] registered with
+/// [`ClientBuilder::add_health_storage`]
+///
+/// [`ClientBuilder::add_health_storage`]: struct.ClientBuilder.html#method.add_health_storage
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StorageStatus {
+    /// The name the storage was registered under
+    pub name: String,
+    /// The error reading from the storage failed with, `None` if it's
+    /// reachable
+    pub error: Option<String>,
+}
+
+/// The delivery state of the webhook reported by telegram, as part of a
+/// [`HealthSnapshot`]. It leaves out the url of the [`WebhookInfo`], as its
+/// path is meant to be secret.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WebhookStatus {
+    /// Number of updates awaiting delivery
+    pub pending_update_count: i64,
+    /// When the most recent error happened when trying to deliver an update
+    /// to the webhook
+    #[serde(with = "unix_date_formatting::optional")]
+    pub last_error_date: Option<DateTime<Utc>>,
+    /// Error message in human-readable format for the most recent error that
+    /// happened when trying to deliver an update to the webhook
+    pub last_error_message: Option<String>,
+    /// When the most recent error happened when trying to synchronize
+    /// available updates with telegram datacenters
+    #[serde(with = "unix_date_formatting::optional")]
+    pub last_synchronization_error_date: Option<DateTime<Utc>>,
+}
+
+impl From<WebhookInfo> for WebhookStatus {
+    fn from(info: WebhookInfo) -> Self {
+        Self {
+            pending_update_count: info.pending_update_count,
+            last_error_date: info.last_error_date,
+            last_error_message: info.last_error_message,
+            last_synchronization_error_date: info.last_synchronization_error_date,
+        }
+    }
+}
+
+/// A snapshot of the health of a [`Client`], returned by [`Client::health`].
+///
+/// It serializes to json, so deployments can expose it on their own health
+/// endpoint, or let the webhook server serve it by setting
+/// [`WebhookOptions::set_health_path`].
+///
+/// [`Client`]: struct.Client.html
+/// [`Client::health`]: struct.Client.html#method.health
+/// [`WebhookOptions::set_health_path`]: struct.WebhookOptions.html#method.set_health_path
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HealthSnapshot {
+    /// How the client receives its updates
+    pub mode: ClientMode,
+    /// When the client last received an update
+    #[serde(with = "unix_date_formatting::optional")]
+    pub last_update: Option<DateTime<Utc>>,
+    /// How many updates the client received since it was created
+    pub updates_received: u64,
+    /// How many received updates are still being handled
+    pub queue_depth: usize,
+    /// How many api calls the client made since it was created
+    pub api_calls: u64,
+    /// How many of those api calls failed, either not reaching telegram or
+    /// being answered with an error
+    pub api_errors: u64,
+    /// The share of the most recent 100 api calls which failed, between 0 and
+    /// 1
+    pub api_error_rate: f64,
+    /// The webhook status reported by telegram, when running with a webhook.
+    /// It's fetched at most once every 30 seconds.
+    pub webhook: Option<WebhookStatus>,
+    /// The error getting the webhook info failed with
+    pub webhook_error: Option<String>,
+    /// The state of the registered storage backends
+    pub storage: Vec<StorageStatus>,
+}
+
+impl HealthSnapshot {
+    /// Returns whether the client is receiving updates, at most half of the
+    /// recent api calls failed, telegram could deliver the latest update to
+    /// the webhook and all storage backends are reachable
+    pub fn is_healthy(&self) -> bool {
+        let webhook_failing = self.webhook_error.is_some()
+            || self
+                .webhook
+                .as_ref()
+                .is_some_and(|w| w.last_error_message.is_some() && w.pending_update_count > 0);
+
+        self.mode != ClientMode::Stopped
+            && self.api_error_rate <= 0.5
+            && !webhook_failing
+            && self.storage.iter().all(|s| s.error.is_none())
+    }
+}
+
+/// keeps track of what the health snapshot reports, shared by all clones of
+/// the client
+pub(super) struct HealthTracker {
+    mode: Mutex<ClientMode>,
+    last_update: Mutex<Option<DateTime<Utc>>>,
+    updates_received: AtomicU64,
+    in_flight: watch::Sender<usize>,
+    api_calls: AtomicU64,
+    api_errors: AtomicU64,
+    recent_calls: Mutex<VecDeque<bool>>,
+    webhook_status: tokio::sync::Mutex<Option<(Instant, WebhookResult)>>,
+    storage: Vec<(String, Arc<dyn Storage>)>,
+}
+
+/// the webhook status, or the error getting it failed with
+type WebhookResult = std::result::Result<WebhookStatus, String>;
+
+impl Default for HealthTracker {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl HealthTracker {
+    pub(super) fn new(storage: Vec<(String, Arc<dyn Storage>)>) -> Self {
+        Self {
+            mode: Mutex::new(ClientMode::Stopped),
+            last_update: Mutex::new(None),
+            updates_received: AtomicU64::new(0),
+            in_flight: watch::channel(0).0,
+            api_calls: AtomicU64::new(0),
+            api_errors: AtomicU64::new(0),
+            recent_calls: Mutex::new(VecDeque::with_capacity(API_WINDOW)),
+            webhook_status: tokio::sync::Mutex::new(None),
+            storage,
+        }
+    }
+
+    /// sets the mode until the returned guard is dropped
+    pub(super) fn running(self: &Arc<Self>, mode: ClientMode) -> RunningGuard {
+        *self.mode.lock() = mode;
+        RunningGuard(self.clone())
+    }
+
+    pub(super) fn mode(&self) -> ClientMode {
+        *self.mode.lock()
+    }
+
+    /// counts the update as being handled until the returned guard is
+    /// dropped, even if its handler panics
+    pub(super) fn update_received(self: &Arc<Self>) -> InFlightGuard {
+        *self.last_update.lock() = Some(Utc::now());
+        self.updates_received.fetch_add(1, Ordering::Relaxed);
+        self.in_flight.send_modify(|n| *n += 1);
+        InFlightGuard(self.clone())
+    }
+
+    pub(super) fn in_flight(&self) -> usize {
+        *self.in_flight.borrow()
+    }
+
+    /// waits until no updates are being handled anymore
+    pub(super) async fn drained(&self) {
+        let mut in_flight = self.in_flight.subscribe();
+        // the sender lives as long as `self`, so this can't fail
+        in_flight.wait_for(|n| *n == 0).await.ok();
+    }
+
+    fn record_call(&self, failed: bool) {
+        self.api_calls.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.api_errors.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut recent = self.recent_calls.lock();
+        if recent.len() == API_WINDOW {
+            recent.pop_front();
+        }
+        recent.push_back(failed);
+    }
+
+    fn error_rate(&self) -> f64 {
+        let recent = self.recent_calls.lock();
+        if recent.is_empty() {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = recent.iter().filter(|f| **f).count() as f64 / recent.len() as f64;
+        rate
+    }
+
+    /// takes the snapshot, checking the storage backends and getting the
+    /// webhook info from telegram when running with a webhook
+    pub(super) async fn snapshot(&self, api: &(dyn API + Send)) -> HealthSnapshot {
+        let mode = self.mode();
+        let (webhook, webhook_error) = if mode == ClientMode::Webhook {
+            match self.webhook_status(api).await {
+                Ok(status) => (Some(status), None),
+                Err(e) => (None, Some(e)),
+            }
+        } else {
+            (None, None)
+        };
+
+        let mut storage = Vec::with_capacity(self.storage.len());
+        for (name, backend) in &self.storage {
+            storage.push(StorageStatus {
+                name: name.clone(),
+                error: backend
+                    .get("health:ping")
+                    .await
+                    .err()
+                    .map(|e| e.to_string()),
+            });
+        }
+
+        HealthSnapshot {
+            mode,
+            last_update: *self.last_update.lock(),
+            updates_received: self.updates_received.load(Ordering::Relaxed),
+            queue_depth: self.in_flight(),
+            api_calls: self.api_calls.load(Ordering::Relaxed),
+            api_errors: self.api_errors.load(Ordering::Relaxed),
+            api_error_rate: self.error_rate(),
+            webhook,
+            webhook_error,
+            storage,
+        }
+    }
+
+    /// the cached webhook status, getting it again once it's too old. The
+    /// lock is held while getting it, so concurrent probes share one call.
+    async fn webhook_status(&self, api: &(dyn API + Send)) -> WebhookResult {
+        let mut cached = self.webhook_status.lock().await;
+        if let Some((fetched, status)) = &*cached {
+            if fetched.elapsed() < WEBHOOK_STATUS_TTL {
+                return status.clone();
+            }
+        }
+
+        let status = PROBING
+            .scope((), api.get_webhook_info())
+            .await
+            .map(WebhookStatus::from)
+            .map_err(|e| e.to_string());
+        *cached = Some((Instant::now(), status.clone()));
+        status
+    }
+}
+
+/// stops counting an update as being handled once it's dropped
+pub(super) struct InFlightGuard(Arc<HealthTracker>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.in_flight.send_modify(|n| *n -= 1);
+    }
+}
+
+/// sets the mode back to stopped when the client stops running
+pub(super) struct RunningGuard(Arc<HealthTracker>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        *self.0.mode.lock() = ClientMode::Stopped;
+    }
+}
+
+/// An [`API`] wrapper recording the outcome of every call in the
+/// [`HealthTracker`]
+pub(super) struct Monitored<A> {
+    api: A,
+    health: Arc<HealthTracker>,
+}
+
+impl<A> Monitored<A> {
+    pub(super) fn new(api: A, health: Arc<HealthTracker>) -> Self {
+        Self {
+            api,
+            health,
+        }
+    }
+
+    /// failed calls either didn't reach telegram or were answered with an
+    /// error
+    fn record(&self, res: Result<Response>) -> Result<Response> {
+        if PROBING.try_with(|()| ()).is_err() {
+            self.health
+                .record_call(res.as_ref().map_or(true, |r| !r.ok));
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for Monitored<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.record(self.api.get(endpoint, data).await)
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.record(self.api.post(endpoint, data).await)
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.record(self.api.post_file(endpoint, data, files).await)
+    }
+
+    async fn download(&self, file_path: &str) -> Result<Vec<u8>> {
+        let res = self.api.download(file_path).await;
+        self.health.record_call(res.is_err());
+        res
+    }
+
+    async fn download_to(
+        &self,
+        file_path: &str,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<u64> {
+        let res = self.api.download_to(file_path, writer).await;
+        self.health.record_call(res.is_err());
+        res
+    }
+}
@@ -17,9 +17,16 @@
 //! [`Client`]: struct.Client.html
 
 mod builder;
+mod callbacks;
+mod chats;
 mod client;
+mod config;
 mod context;
 mod event_handlers;
+mod health;
+mod ordering;
+mod sanitizer;
+mod shutdown;
 mod stream;
 mod webhook_handling;
 
@@ -28,11 +35,33 @@ use core::future::Future;
 use std::pin::Pin;
 
 pub use builder::ClientBuilder;
+use callbacks::{CallbackTracked, PendingCallbacks};
+use chats::ChannelChecked;
+pub use chats::ChatTypes;
 pub use client::Client;
+use config::Configured;
+use health::{HealthTracker, Monitored};
+use ordering::ChatQueues;
+use sanitizer::Sanitized;
+use webhook_handling::BoundAddr;
+pub use config::{LiveConfig, RuntimeConfig, SendDefaults};
 pub use context::Context;
-pub use event_handlers::{EventHandlerFunc, RawEventHandlerFunc};
-pub use stream::UpdatesStream;
-pub use webhook_handling::{Webhook, WebhookOptions};
+pub(crate) use event_handlers::report_error;
+pub use event_handlers::{
+    BusinessConnectionHandlerFunc,
+    ErrorHandlerFunc,
+    EventHandlerFunc,
+    HandlerError,
+    HandlerResult,
+    PollStallHandlerFunc,
+    RawEventHandlerFunc,
+    ReconfigureHandlerFunc,
+};
+pub use health::{ClientMode, HealthSnapshot, StorageStatus, WebhookStatus};
+pub use sanitizer::{OutgoingText, TextSanitizer};
+pub use shutdown::ShutdownHandle;
+pub use stream::{PollStall, UpdatesStream};
+pub use webhook_handling::{PreCommitHookFunc, Webhook, WebhookOptions};
 
 type APIConnector = dyn API + Send;
 pub(crate) type FutureOutcome = Pin<Box<dyn Future<Output = ()> + Send>>;
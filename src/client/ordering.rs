@@ -0,0 +1,80 @@
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Makes the updates of the same chat get handled one after another, in the
+/// order they were received, while the updates of different chats are still
+/// handled in parallel
+#[derive(Default)]
+pub(super) struct ChatQueues {
+    /// the ticket of the last update queued per chat, with the receiver
+    /// resolving once it's handled
+    tails: Mutex<HashMap<i64, (u64, oneshot::Receiver<()>)>>,
+    next_ticket: AtomicU64,
+}
+
+impl ChatQueues {
+    /// queues an update of the chat behind the ones already queued, which has
+    /// to happen in the order the updates were received
+    pub(super) fn enqueue(self: &Arc<Self>, chat_id: i64) -> Turn {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        let (done, handled) = oneshot::channel();
+        let previous = self
+            .tails
+            .lock()
+            .insert(chat_id, (ticket, handled))
+            .map(|(_, previous)| previous);
+
+        Turn {
+            queues: self.clone(),
+            chat_id,
+            ticket,
+            previous,
+            _done: done,
+        }
+    }
+}
+
+/// The place of an update in the queue of its chat, letting the next update
+/// of the chat go once dropped
+pub(super) struct Turn {
+    queues: Arc<ChatQueues>,
+    chat_id: i64,
+    ticket: u64,
+    previous: Option<oneshot::Receiver<()>>,
+    // never sent on, dropping it wakes up the next update
+    _done: oneshot::Sender<()>,
+}
+
+impl Turn {
+    /// whether the update was queued behind another update of its chat
+    pub(super) fn is_queued(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    /// waits until the previous update of the chat is handled
+    pub(super) async fn wait(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            previous.await.ok();
+        }
+    }
+}
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        let mut tails = self.queues.tails.lock();
+        // nothing was queued after this update, so the chat is idle
+        if tails
+            .get(&self.chat_id)
+            .is_some_and(|(t, _)| *t == self.ticket)
+        {
+            tails.remove(&self.chat_id);
+        }
+    }
+}
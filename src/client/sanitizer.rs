@@ -0,0 +1,149 @@
+use crate::{
+    api::{APIEndpoint, Response, API},
+    model::ParseMode,
+    utils::{result::Result, FormDataFile},
+};
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// A text or caption about to be sent, passed to the [`TextSanitizer`]s
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutgoingText<'a> {
+    /// The API method sending the text, for example "sendMessage"
+    pub method: &'a str,
+    /// The field of the payload holding the text, "text" or "caption"
+    pub field: &'a str,
+    /// The text itself, which the sanitizer may change
+    pub text: String,
+    /// The parse mode the text is sent with
+    pub parse_mode: Option<ParseMode>,
+    /// Whether the text is formatted with entities. Their offsets aren't
+    /// adjusted, so only change the text after the last entity when this is
+    /// set.
+    pub has_entities: bool,
+}
+
+/// A hook inspecting and transforming the texts and captions sent and edited
+/// through the client, right before they're sent. Use it to enforce an
+/// escaping policy, strip disallowed tags or append a signature for all
+/// messages at once, instead of in every handler.
+///
+/// Closures taking an [`OutgoingText`] are sanitizers as well:
+///
+/// ```rust,no_run
+/// use telexide::{client::{ClientBuilder, OutgoingText}, model::ParseMode};
+///
+/// # let token = "test token";
+/// let client = ClientBuilder::new()
+///     .set_token(token)
+///     .add_text_sanitizer(|text: &mut OutgoingText| {
+///         if text.parse_mode == Some(ParseMode::HTML) {
+///             text.text = text.text.replace("<script>", "&lt;script&gt;");
+///         }
+///         text.text.push_str("\n— sent by a bot");
+///     })
+///     .build();
+/// ```
+pub trait TextSanitizer: Send + Sync + 'static {
+    /// Changes the text in place
+    fn sanitize(&self, text: &mut OutgoingText<'_>);
+}
+
+impl<F> TextSanitizer for F
+where
+    F: Fn(&mut OutgoingText<'_>) + Send + Sync + 'static,
+{
+    fn sanitize(&self, text: &mut OutgoingText<'_>) {
+        self(text);
+    }
+}
+
+/// An [`API`] wrapper running the texts and captions of the outgoing send and
+/// edit calls through the sanitizers
+pub(super) struct Sanitized<A> {
+    api: A,
+    sanitizers: Vec<Arc<dyn TextSanitizer>>,
+}
+
+impl<A> Sanitized<A> {
+    pub(super) fn new(api: A, sanitizers: Vec<Arc<dyn TextSanitizer>>) -> Self {
+        Self {
+            api,
+            sanitizers,
+        }
+    }
+
+    fn apply(&self, endpoint: &APIEndpoint, data: Option<Value>) -> Option<Value> {
+        let mut data = data?;
+        let method = endpoint.as_str();
+        if !(method.starts_with("send") || method.starts_with("edit") || method == "copyMessage") {
+            return Some(data);
+        }
+
+        if let Some(payload) = data.as_object_mut() {
+            self.sanitize_fields(method, payload);
+            // the captions of albums and edited media
+            match payload.get_mut("media") {
+                Some(Value::Array(media)) => {
+                    for item in media.iter_mut().filter_map(Value::as_object_mut) {
+                        self.sanitize_fields(method, item);
+                    }
+                },
+                Some(Value::Object(item)) => self.sanitize_fields(method, item),
+                _ => {},
+            }
+        }
+        Some(data)
+    }
+
+    fn sanitize_fields(&self, method: &str, payload: &mut Map<String, Value>) {
+        let parse_mode: Option<ParseMode> = payload
+            .get("parse_mode")
+            .and_then(|p| serde_json::from_value(p.clone()).ok());
+
+        for (field, entities) in &[("text", "entities"), ("caption", "caption_entities")] {
+            let Some(text) = payload.get(*field).and_then(Value::as_str) else {
+                continue;
+            };
+            let mut outgoing = OutgoingText {
+                method,
+                field,
+                text: text.to_owned(),
+                parse_mode: parse_mode.clone(),
+                has_entities: payload.get(*entities).is_some_and(|e| !e.is_null()),
+            };
+            for sanitizer in &self.sanitizers {
+                sanitizer.sanitize(&mut outgoing);
+            }
+            payload.insert((*field).to_owned(), Value::String(outgoing.text));
+        }
+    }
+}
+
+#[async_trait]
+impl<A: API + Send> API for Sanitized<A> {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&self.api)
+    }
+
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        let data = self.apply(&endpoint, data);
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        let data = self.apply(&endpoint, data);
+        self.api.post(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        let data = self.apply(&endpoint, data);
+        self.api.post_file(endpoint, data, files).await
+    }
+}
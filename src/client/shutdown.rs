@@ -0,0 +1,68 @@
+use std::{sync::Arc, time::Duration};
+use tokio::sync::watch;
+
+/// How long a client waits for its handlers to finish when shutting down,
+/// unless set otherwise
+pub(super) const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Stops a running [`Client`] gracefully, returned by
+/// [`Client::shutdown_handle`].
+///
+/// Stopping makes the client stop fetching updates, wait for the updates it's
+/// still handling to finish, up to the timeout set with
+/// [`ClientBuilder::set_shutdown_timeout`], and then resolves its `start`
+/// future with `Ok(())`. Updates which weren't fetched yet are delivered again
+/// by telegram when the client is started the next time.
+///
+/// ```rust,no_run
+/// # use telexide::client::Client;
+/// # async fn run(client: Client) -> telexide::Result<()> {
+/// let handle = client.shutdown_handle();
+/// tokio::spawn(async move {
+///     tokio::signal::ctrl_c().await.ok();
+///     handle.stop();
+/// });
+///
+/// client.start().await
+/// # }
+/// ```
+///
+/// [`Client`]: struct.Client.html
+/// [`Client::shutdown_handle`]: struct.Client.html#method.shutdown_handle
+/// [`ClientBuilder::set_shutdown_timeout`]: struct.ClientBuilder.html#method.set_shutdown_timeout
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    stopping: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ShutdownHandle {
+    fn default() -> Self {
+        Self {
+            stopping: Arc::new(watch::Sender::new(false)),
+        }
+    }
+}
+
+impl ShutdownHandle {
+    /// Tells the client to stop, without waiting for it to do so
+    pub fn stop(&self) {
+        self.stopping.send_replace(true);
+    }
+
+    /// Returns whether the client was told to stop and didn't finish doing so
+    /// yet
+    pub fn is_stopping(&self) -> bool {
+        *self.stopping.borrow()
+    }
+
+    /// resolves once the client is told to stop
+    pub(super) async fn stopped(&self) {
+        let mut stopping = self.stopping.subscribe();
+        stopping.wait_for(|s| *s).await.ok();
+    }
+
+    /// lets the client be started again after it stopped
+    pub(super) fn reset(&self) {
+        self.stopping.send_replace(false);
+    }
+}
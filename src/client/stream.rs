@@ -1,20 +1,38 @@
-use futures::{Future, Stream};
+use futures::{ready, Future, Stream};
 use std::{
     cmp::max,
     collections::VecDeque,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 
 use super::APIConnector;
 use crate::{
     api::types::{GetUpdates, UpdateType},
     model::Update,
+    redaction,
+    runtime::{BoxedTask, Runtime, TokioRuntime},
+    storage::OffsetStorage,
     Result,
 };
 
-type FutureUpdate = Pin<Box<dyn Future<Output = Result<Vec<Update>>>>>;
+type FutureUpdate = Pin<Box<dyn Future<Output = Result<Vec<Update>>> + Send>>;
+type FutureOffset = Pin<Box<dyn Future<Output = Result<Option<i64>>> + Send>>;
+type StallHandler = Box<dyn Fn(PollStall) + Send + Sync>;
+
+/// Emitted by the watchdog of an [`UpdatesStream`] when getUpdates hasn't
+/// completed successfully for too long, after it dropped the hung request and
+/// reset the connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollStall {
+    /// How long ago getUpdates last completed successfully, or the stream
+    /// started polling
+    pub stalled_for: Duration,
+    /// The timeout used for long polling
+    pub poll_timeout: Duration,
+}
 
 /// The stream of incoming updates, created by long polling the telegram API
 /// using their getUpdates endpoint.
@@ -61,9 +79,17 @@ pub struct UpdatesStream {
     buffer: VecDeque<Update>,
     allowed_updates: Vec<UpdateType>,
     offset: i64,
+    offset_storage: Option<Arc<dyn OffsetStorage>>,
+    loading_offset: Option<FutureOffset>,
+    saved_offset: i64,
     limit: usize,
     timeout: usize,
     current_request: Option<FutureUpdate>,
+    watchdog: Option<u32>,
+    deadline: Option<BoxedTask>,
+    last_success: Instant,
+    on_stall: Option<StallHandler>,
+    runtime: Arc<dyn Runtime>,
 }
 
 impl Stream for UpdatesStream {
@@ -72,18 +98,38 @@ impl Stream for UpdatesStream {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         let ref_mut = self.get_mut();
 
+        if let Some(loading) = &mut ref_mut.loading_offset {
+            let loaded = ready!(loading.as_mut().poll(cx));
+            ref_mut.loading_offset = None;
+            match loaded {
+                Ok(update_id) => {
+                    ref_mut.offset = max(update_id.unwrap_or(0), ref_mut.offset);
+                    ref_mut.saved_offset = ref_mut.offset;
+                },
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+
         if let Some(u) = ref_mut.buffer.pop_front() {
             return Poll::Ready(Some(Ok(u)));
         }
 
         if let Some(ref mut request) = ref_mut.current_request {
             match request.as_mut().poll(cx) {
-                Poll::Pending => return Poll::Pending,
+                Poll::Pending => {
+                    if ref_mut.deadline_passed(cx) {
+                        ref_mut.recover_from_stall();
+                        return Pin::new(ref_mut).poll_next(cx);
+                    }
+                    return Poll::Pending;
+                },
                 Poll::Ready(Ok(ref res)) if res.is_empty() => {
+                    ref_mut.mark_success();
                     ref_mut.poll_telegram();
                     return Pin::new(ref_mut).poll_next(cx);
                 },
                 Poll::Ready(Ok(res)) => {
+                    ref_mut.mark_success();
                     for u in res {
                         ref_mut.offset = max(u.update_id, ref_mut.offset);
                         ref_mut.buffer.push_back(u);
@@ -112,8 +158,73 @@ impl UpdatesStream {
             .set_offset(self.offset + 1)
             .set_timeout(self.timeout);
 
+        // polling with the offset confirms the updates before it, so they're
+        // stored as confirmed along with it
+        let confirmed = self
+            .offset_storage
+            .clone()
+            .filter(|_| self.offset != self.saved_offset);
+        self.saved_offset = self.offset;
+        let offset = self.offset;
+
         let api = self.api.clone();
-        self.current_request = Some(Box::pin(async move { api.get_updates(data).await }));
+        self.current_request = Some(Box::pin(async move {
+            if let Some(storage) = confirmed {
+                if let Err(err) = storage.save(offset).await {
+                    log::warn!(
+                        "couldn't store the offset of the updates: {}",
+                        redaction::scrub(err)
+                    );
+                }
+            }
+            api.get_updates(data).await
+        }));
+
+        if self.deadline.is_none() {
+            if let Some(limit) = self.stall_limit() {
+                self.deadline = Some(self.runtime.sleep(limit));
+            }
+        }
+    }
+
+    /// how long getUpdates may go without completing successfully before the
+    /// watchdog steps in, at least a second so short polling isn't affected
+    fn stall_limit(&self) -> Option<Duration> {
+        let timeout = Duration::from_secs(self.timeout as u64);
+        self.watchdog
+            .map(|factor| max(timeout * factor, Duration::from_secs(1)))
+    }
+
+    fn deadline_passed(&mut self, cx: &mut Context) -> bool {
+        self.deadline
+            .as_mut()
+            .is_some_and(|d| d.as_mut().poll(cx).is_ready())
+    }
+
+    fn mark_success(&mut self) {
+        self.last_success = Instant::now();
+        self.deadline = None;
+    }
+
+    /// drops the hung request, makes the api open a new connection and
+    /// emits the stall
+    fn recover_from_stall(&mut self) {
+        let stall = PollStall {
+            stalled_for: self.last_success.elapsed(),
+            poll_timeout: Duration::from_secs(self.timeout as u64),
+        };
+        log::warn!(
+            "getUpdates hasn't completed successfully for {:?}, resetting the connection",
+            stall.stalled_for
+        );
+
+        self.current_request = None;
+        self.deadline = None;
+        self.api.reset_connection();
+        if let Some(on_stall) = &self.on_stall {
+            on_stall(stall);
+        }
+        self.poll_telegram();
     }
 
     /// creates a new update stream using the provided [`API`]
@@ -125,12 +236,37 @@ impl UpdatesStream {
             buffer: VecDeque::new(),
             allowed_updates: Vec::new(),
             offset: 0,
+            offset_storage: None,
+            loading_offset: None,
+            saved_offset: 0,
             limit: 100,
             timeout: 5,
             current_request: None,
+            watchdog: Some(3),
+            deadline: None,
+            last_success: Instant::now(),
+            on_stall: None,
+            runtime: Arc::new(TokioRuntime),
         }
     }
 
+    /// Sets the [`Runtime`] the timer of the watchdog runs on, tokio by
+    /// default
+    pub fn set_runtime(&mut self, runtime: Arc<dyn Runtime>) -> &mut Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Sets the [`OffsetStorage`] keeping the `update_id` of the last update
+    /// confirmed to telegram. The stream starts after the stored update, and
+    /// stores the updates it confirms by polling again.
+    pub fn set_offset_storage(&mut self, storage: Arc<dyn OffsetStorage>) -> &mut Self {
+        let loading = storage.clone();
+        self.loading_offset = Some(Box::pin(async move { loading.load().await }));
+        self.offset_storage = Some(storage);
+        self
+    }
+
     /// Sets the maximum amount of updates retrieved in one API call
     pub fn set_limit(&mut self, limit: usize) -> &mut Self {
         self.limit = limit;
@@ -145,6 +281,37 @@ impl UpdatesStream {
         self
     }
 
+    /// Sets the watchdog of the long poll: when getUpdates hasn't completed
+    /// successfully within `factor` times the timeout, for example because of
+    /// a hung connection or a silent network partition, the request is
+    /// dropped, the connection to telegram is reset and polling starts over.
+    /// Defaults to 3, `None` disables the watchdog.
+    pub fn set_watchdog(&mut self, factor: Option<u32>) -> &mut Self {
+        self.watchdog = factor;
+        self
+    }
+
+    /// Sets the function called with every [`PollStall`] the watchdog
+    /// recovers from
+    pub fn on_stall<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(PollStall) + Send + Sync + 'static,
+    {
+        self.on_stall = Some(Box::new(handler));
+        self
+    }
+
+    /// sets the allowed updates, restarting the current poll if they changed
+    /// so they apply right away. Updates are only confirmed by the offset of
+    /// the next poll, so none get lost.
+    pub(super) fn replace_allowed_updates(&mut self, allowed: Vec<UpdateType>) {
+        if self.allowed_updates != allowed {
+            self.allowed_updates = allowed;
+            self.current_request = None;
+            self.deadline = None;
+        }
+    }
+
     /// Set which update types you want to receive
     pub fn set_allowed_updates(&mut self, allowed: Vec<UpdateType>) -> &mut Self {
         self.allowed_updates = allowed;
@@ -1,11 +1,19 @@
 use std::{
     convert::Infallible,
+    fmt,
+    future::Future,
     io::Write,
     net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
 };
 
+use super::{HealthSnapshot, ShutdownHandle};
 use crate::{
     model::Update,
+    redaction,
     utils::result::{Result as TelegramResult, TelegramError},
 };
 use hyper::{
@@ -19,7 +27,56 @@ use hyper::{
     StatusCode,
     Uri,
 };
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::{
+    mpsc::{channel, Receiver, Sender},
+    watch,
+};
+#[cfg(feature = "native-tls")]
+use tokio_native_tls::{native_tls, TlsAcceptor};
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+use tokio_rustls::{rustls, TlsAcceptor};
+
+/// The header telegram sends the secret token of the webhook in
+const SECRET_TOKEN_HEADER: &str = "x-telegram-bot-api-secret-token";
+
+/// A function that gets called with every incoming update before telegram is
+/// told it has been received, for example to durably store the update. It
+/// returns a pinned future resolving to a result, if it is an error telegram
+/// will be asked to send the update again later.
+pub type PreCommitHookFunc =
+    Arc<dyn Fn(Update) -> Pin<Box<dyn Future<Output = TelegramResult<()>> + Send>> + Send + Sync>;
+
+/// takes the snapshot served on the health path
+pub(super) type HealthSource =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = HealthSnapshot> + Send>> + Send + Sync>;
+
+/// the address the webhook of a client is listening on, `None` while it isn't
+#[derive(Debug, Clone)]
+pub(super) struct BoundAddr(Arc<watch::Sender<Option<SocketAddr>>>);
+
+impl Default for BoundAddr {
+    fn default() -> Self {
+        Self(Arc::new(watch::Sender::new(None)))
+    }
+}
+
+impl BoundAddr {
+    fn set(&self, addr: Option<SocketAddr>) {
+        self.0.send_replace(addr);
+    }
+
+    /// resolves once the webhook is listening
+    pub(super) async fn get(&self) -> SocketAddr {
+        let mut bound = self.0.subscribe();
+        loop {
+            if let Some(addr) = *bound.borrow_and_update() {
+                return addr;
+            }
+            // the sender is kept alive by self, so this can't fail
+            bound.changed().await.ok();
+        }
+    }
+}
 
 /// Handles listening to the telegram webhook and will provide you with the
 /// incoming updates
@@ -38,10 +95,35 @@ impl Webhook {
 
     /// starts the webhandling and returns a [`Receiver`], which will allow you
     /// to receive the incoming updates
+    ///
+    /// The health path, if set, is only served by a webhook started by the
+    /// [`Client`].
+    ///
+    /// [`Client`]: struct.Client.html
     pub fn start(self) -> Receiver<TelegramResult<Update>> {
         let (tx, rx) = channel(1000);
 
-        tokio::spawn(start_ws(self.opts, tx));
+        tokio::spawn(start_ws(self.opts, tx, None));
+        rx
+    }
+
+    /// starts the webhook handling, serving the snapshots of the health
+    /// source on the health path, reporting the address it listens on and
+    /// stopping once the client shuts down
+    pub(super) fn start_for_client(
+        self,
+        health: HealthSource,
+        bound: BoundAddr,
+        shutdown: ShutdownHandle,
+    ) -> Receiver<TelegramResult<Update>> {
+        let (tx, rx) = channel(1000);
+
+        let client = ClientServing {
+            health,
+            bound,
+            shutdown,
+        };
+        tokio::spawn(start_ws(self.opts, tx, Some(client)));
         rx
     }
 }
@@ -59,6 +141,25 @@ async fn handle_update(
     }
 
     let update: Update = serde_json::from_slice(&body)?;
+
+    if let Some(hook) = &payload.pre_commit_hook {
+        match tokio::time::timeout(payload.pre_commit_timeout, hook(update.clone())).await {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => {
+                log::warn!(
+                    "pre-commit hook failed for update {}: {}",
+                    update.update_id,
+                    redaction::scrub(&e)
+                );
+                return Err(e);
+            },
+            Err(_) => {
+                log::warn!("pre-commit hook timed out for update {}", update.update_id);
+                return Err(TelegramError::WebhookError.into());
+            },
+        }
+    }
+
     let send_res = payload.chan.send(Ok(update)).await;
     if send_res.is_err() {
         return Err(TelegramError::WebhookError.into());
@@ -75,6 +176,10 @@ async fn handle_req(
     let mut response = Response::new(Body::empty());
 
     match (req.method(), req.uri().path()) {
+        (&Method::POST, path) if path == payload.path && !payload.is_authorized(&req) => {
+            log::warn!("rejected a webhook request without the secret token");
+            *response.status_mut() = StatusCode::UNAUTHORIZED;
+        },
         (&Method::POST, path) if path == payload.path => {
             let result = handle_update(payload, req).await;
 
@@ -84,6 +189,12 @@ async fn handle_req(
                 response = result.unwrap()
             }
         },
+        (&Method::GET, path) if Some(path) == payload.health_path.as_deref() => {
+            match &payload.health {
+                Some(health) => response = health_response(&health().await),
+                None => *response.status_mut() = StatusCode::NOT_FOUND,
+            }
+        },
         _ => {
             *response.status_mut() = StatusCode::NOT_FOUND;
         },
@@ -92,13 +203,65 @@ async fn handle_req(
     Ok(response)
 }
 
+/// the snapshot as json, with a 503 status if it isn't healthy
+fn health_response(snapshot: &HealthSnapshot) -> Response<Body> {
+    let body = serde_json::to_vec(snapshot).unwrap_or_default();
+    let mut response = Response::new(Body::from(body));
+    if !snapshot.is_healthy() {
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    }
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// what a webhook started by a client serves and reports to it
+struct ClientServing {
+    health: HealthSource,
+    bound: BoundAddr,
+    shutdown: ShutdownHandle,
+}
+
 async fn start_ws(
     opts: WebhookOptions,
     chan: Sender<TelegramResult<Update>>,
+    client: Option<ClientServing>,
 ) -> TelegramResult<()> {
     let addr = SocketAddr::from((opts.ip, opts.port));
+    let (health, bound, shutdown) = match client {
+        Some(c) => (Some(c.health), Some(c.bound), Some(c.shutdown)),
+        None => (None, None, None),
+    };
+    let payload = HandlingPayload::new(&opts, chan.clone(), health);
+
+    let res = match opts.tls_acceptor().await {
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        Ok(Some(acceptor)) => serve_tls(addr, acceptor, payload, bound.as_ref(), shutdown).await,
+        #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+        Ok(Some(acceptor)) => match acceptor {},
+        Ok(None) => serve(addr, payload, bound.as_ref(), shutdown).await,
+        Err(e) => Err(e),
+    };
+    if let Some(bound) = bound {
+        bound.set(None);
+    }
+
+    if let Err(e) = res {
+        if chan.send(Err(e)).await.is_err() {
+            return Err(TelegramError::WebhookError.into());
+        }
+    }
+    Ok(())
+}
 
-    let payload = HandlingPayload::new(&opts, chan.clone());
+async fn serve(
+    addr: SocketAddr,
+    payload: HandlingPayload,
+    bound: Option<&BoundAddr>,
+    shutdown: Option<ShutdownHandle>,
+) -> TelegramResult<()> {
     let make_svc = make_service_fn(move |_conn| {
         let inner_payload = payload.clone();
         async move {
@@ -108,34 +271,121 @@ async fn start_ws(
         }
     });
 
-    let server = Server::bind(&addr).serve(make_svc);
-    let graceful = server.with_graceful_shutdown(shutdown_signal());
+    let server = Server::try_bind(&addr)
+        .map_err(|e| TelegramError::Unknown(e.to_string()))?
+        .serve(make_svc);
+    if let Some(bound) = bound {
+        bound.set(Some(server.local_addr()));
+    }
+    server
+        .with_graceful_shutdown(shutdown_signal(shutdown))
+        .await
+        .map_err(|e| TelegramError::Unknown(e.to_string()).into())
+}
 
-    if let Err(e) = graceful.await {
-        let send_res = chan
-            .send(Err(TelegramError::Unknown(e.to_string()).into()))
-            .await;
-        if send_res.is_err() {
-            return Err(TelegramError::WebhookError.into());
-        }
+/// serves every connection on its own task after the tls handshake, so a
+/// slow handshake doesn't hold up the other connections
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+async fn serve_tls(
+    addr: SocketAddr,
+    acceptor: TlsAcceptor,
+    payload: HandlingPayload,
+    bound: Option<&BoundAddr>,
+    shutdown: Option<ShutdownHandle>,
+) -> TelegramResult<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    if let Some(bound) = bound {
+        bound.set(Some(listener.local_addr()?));
+    }
+    let stopping = shutdown;
+    let shutdown = shutdown_signal(stopping.clone());
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            () = &mut shutdown => return Ok(()),
+        };
+        let acceptor = acceptor.clone();
+        let payload = payload.clone();
+        let connection_shutdown = shutdown_signal(stopping.clone());
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::debug!("tls handshake on the webhook failed: {e}");
+                    return;
+                },
+            };
+            let service = service_fn(move |req| handle_req(payload.clone(), req));
+            let connection = hyper::server::conn::Http::new().serve_connection(stream, service);
+            tokio::pin!(connection);
+            // lets the requests being served finish, like the server without
+            // tls does
+            let res = tokio::select! {
+                res = connection.as_mut() => res,
+                () = connection_shutdown => {
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                },
+            };
+            if let Err(e) = res {
+                log::debug!("webhook connection failed: {e}");
+            }
+        });
     }
-    Ok(())
 }
 
-async fn shutdown_signal() {
-    // Wait for the CTRL+C signal
-    tokio::signal::ctrl_c()
-        .await
-        .expect("failed to install CTRL+C signal handler");
+/// resolves on CTRL+C, or once the client is shut down
+async fn shutdown_signal(shutdown: Option<ShutdownHandle>) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install CTRL+C signal handler");
+    };
+    if let Some(shutdown) = shutdown {
+        tokio::select! {
+            () = ctrl_c => (),
+            () = shutdown.stopped() => (),
+        }
+    } else {
+        ctrl_c.await;
+    }
 }
 
 /// Represents the options to set for the webhook handling
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct WebhookOptions {
     pub url: Option<Uri>,
     pub path: String,
     pub port: u16,
     pub ip: IpAddr,
+    /// The hook to run before acknowledging an update, see
+    /// [`PreCommitHookFunc`]
+    pub pre_commit_hook: Option<PreCommitHookFunc>,
+    /// The maximum time the pre-commit hook may take, after which the update
+    /// is not acknowledged. Defaults to 10 seconds.
+    pub pre_commit_timeout: Duration,
+    /// The path a GET request to returns the [`HealthSnapshot`] of the client
+    /// on, with a 503 status if it isn't healthy. Not served by default.
+    pub health_path: Option<String>,
+    /// The secret telegram sends along with every update, requests without
+    /// it are rejected. 1-256 characters, only `A-Z`, `a-z`, `0-9`, `_` and
+    /// `-` are allowed.
+    pub secret_token: Option<String>,
+    /// The pem encoded certificate chain to serve the webhook over https with
+    pub certificate: Option<PathBuf>,
+    /// The pem encoded PKCS #8 private key of the certificate
+    pub private_key: Option<PathBuf>,
+    /// Whether the certificate is uploaded to telegram when setting the
+    /// webhook, which is needed for self-signed certificates
+    pub upload_certificate: bool,
+    /// The maximum amount of simultaneous connections telegram makes to the
+    /// webhook, 1-100. Defaults to 40.
+    pub max_connections: Option<i64>,
+    /// Whether the updates telegram has pending are dropped when the client
+    /// sets the webhook
+    pub drop_pending_updates: bool,
 }
 
 impl WebhookOptions {
@@ -148,9 +398,26 @@ impl WebhookOptions {
             path: "/".to_owned(),
             port: 8006,
             ip: [127, 0, 0, 1].into(),
+            pre_commit_hook: None,
+            pre_commit_timeout: Duration::from_secs(10),
+            health_path: None,
+            secret_token: None,
+            certificate: None,
+            private_key: None,
+            upload_certificate: false,
+            max_connections: None,
+            drop_pending_updates: false,
         }
     }
 
+    /// Sets the ip and port the webhook will be listening on
+    pub fn set_addr<T: Into<SocketAddr>>(&mut self, addr: T) -> &mut Self {
+        let addr = addr.into();
+        self.ip = addr.ip();
+        self.port = addr.port();
+        self
+    }
+
     /// Sets the path of the webhook
     pub fn set_path(&mut self, path: &str) -> &mut Self {
         self.path = path.to_owned();
@@ -169,12 +436,81 @@ impl WebhookOptions {
         self
     }
 
+    /// Sets the hook to run before acknowledging an update. Telegram only
+    /// considers an update delivered once the hook succeeded, if it fails or
+    /// times out telegram will send the update again later.
+    pub fn set_pre_commit_hook<F, Fut>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(Update) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = TelegramResult<()>> + Send + 'static,
+    {
+        self.pre_commit_hook = Some(Arc::new(move |update| Box::pin(hook(update))));
+        self
+    }
+
+    /// Sets the maximum time the pre-commit hook may take
+    pub fn set_pre_commit_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.pre_commit_timeout = timeout;
+        self
+    }
+
+    /// Sets the path to serve the [`HealthSnapshot`] of the client on, for
+    /// example "/health"
+    pub fn set_health_path(&mut self, path: &str) -> &mut Self {
+        self.health_path = Some(path.to_owned());
+        self
+    }
+
     /// Sets the url of the webhook
     pub fn set_url(&mut self, url: &str) -> TelegramResult<&mut Self> {
         self.url = Some(url.parse()?);
         Ok(self)
     }
 
+    /// Sets the secret telegram has to send along with every update
+    pub fn set_secret_token(&mut self, token: &str) -> &mut Self {
+        self.secret_token = Some(token.to_owned());
+        self
+    }
+
+    /// Serves the webhook over https, with the pem encoded certificate chain
+    /// and PKCS #8 private key at the given paths. Without them the webhook is
+    /// served over plain http, for running behind a reverse proxy
+    /// terminating tls.
+    pub fn set_tls<P: Into<PathBuf>>(&mut self, certificate: P, private_key: P) -> &mut Self {
+        self.certificate = Some(certificate.into());
+        self.private_key = Some(private_key.into());
+        self
+    }
+
+    pub fn toggle_upload_certificate(&mut self) -> &mut Self {
+        self.upload_certificate = !self.upload_certificate;
+        self
+    }
+
+    pub fn set_max_connections(&mut self, max_connections: i64) -> &mut Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    pub fn toggle_drop_pending_updates(&mut self) -> &mut Self {
+        self.drop_pending_updates = !self.drop_pending_updates;
+        self
+    }
+
+    /// reads the certificate and key, if tls is set up
+    async fn tls_acceptor(&self) -> TelegramResult<Option<TlsAcceptor>> {
+        let (Some(certificate), Some(private_key)) = (&self.certificate, &self.private_key) else {
+            return Ok(None);
+        };
+
+        let certificate = tokio::fs::read(certificate).await?;
+        let private_key = tokio::fs::read(private_key).await?;
+        let acceptor = acceptor_from_pem(&certificate, &private_key)
+            .map_err(|e| TelegramError::InvalidArgument(format!("invalid webhook tls: {e}")))?;
+        Ok(Some(acceptor))
+    }
+
     fn get_path(&self) -> &str {
         self.url
             .as_ref()
@@ -182,23 +518,111 @@ impl WebhookOptions {
     }
 }
 
+#[cfg(feature = "native-tls")]
+fn acceptor_from_pem(certificate: &[u8], private_key: &[u8]) -> Result<TlsAcceptor, String> {
+    native_tls::Identity::from_pkcs8(certificate, private_key)
+        .and_then(native_tls::TlsAcceptor::new)
+        .map(TlsAcceptor::from)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+fn acceptor_from_pem(
+    mut certificate: &[u8],
+    mut private_key: &[u8],
+) -> Result<TlsAcceptor, String> {
+    let chain = rustls_pemfile::certs(&mut certificate)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+    let key = rustls_pemfile::pkcs8_private_keys(&mut private_key)
+        .map_err(|e| e.to_string())?
+        .pop()
+        .ok_or("the private key isn't a pem encoded PKCS #8 key")?;
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(chain, rustls::PrivateKey(key))
+        .map_err(|e| e.to_string())?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// without a tls feature the webhook can only be served over plain http, so
+/// no acceptor can ever be made
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+type TlsAcceptor = Infallible;
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+fn acceptor_from_pem(_certificate: &[u8], _private_key: &[u8]) -> Result<TlsAcceptor, String> {
+    Err("serving the webhook over https needs the native-tls or rustls feature".to_owned())
+}
+
+impl fmt::Debug for WebhookOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookOptions")
+            .field("url", &self.url)
+            .field("path", &self.path)
+            .field("port", &self.port)
+            .field("ip", &self.ip)
+            .field("pre_commit_hook", &self.pre_commit_hook.is_some())
+            .field("pre_commit_timeout", &self.pre_commit_timeout)
+            .field("health_path", &self.health_path)
+            .field("secret_token", &self.secret_token)
+            .field("certificate", &self.certificate)
+            .field("private_key", &self.private_key)
+            .field("upload_certificate", &self.upload_certificate)
+            .field("max_connections", &self.max_connections)
+            .field("drop_pending_updates", &self.drop_pending_updates)
+            .finish()
+    }
+}
+
 impl Default for WebhookOptions {
     fn default() -> Self {
         Self::new()
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 struct HandlingPayload {
     path: String,
     chan: Sender<TelegramResult<Update>>,
+    pre_commit_hook: Option<PreCommitHookFunc>,
+    pre_commit_timeout: Duration,
+    health_path: Option<String>,
+    health: Option<HealthSource>,
+    secret_token: Option<String>,
 }
 
 impl HandlingPayload {
-    fn new(opts: &WebhookOptions, sender: Sender<TelegramResult<Update>>) -> Self {
+    fn new(
+        opts: &WebhookOptions,
+        sender: Sender<TelegramResult<Update>>,
+        health: Option<HealthSource>,
+    ) -> Self {
         Self {
             path: opts.get_path().to_owned(),
             chan: sender,
+            pre_commit_hook: opts.pre_commit_hook.clone(),
+            pre_commit_timeout: opts.pre_commit_timeout,
+            health_path: opts.health_path.clone(),
+            health,
+            secret_token: opts.secret_token.clone(),
         }
     }
+
+    fn is_authorized(&self, req: &Request<Body>) -> bool {
+        self.secret_token.as_ref().is_none_or(|token| {
+            req.headers()
+                .get(SECRET_TOKEN_HEADER)
+                .is_some_and(|h| constant_time_eq(h.as_bytes(), token.as_bytes()))
+        })
+    }
+}
+
+/// compares the secrets without returning early at the first difference, so
+/// the time taken doesn't tell how much of a guess was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
 }
@@ -0,0 +1,155 @@
+use super::types::CommandError;
+use crate::model::{Message, MessageContent, MessageEntity};
+use std::{fmt::Display, str::FromStr};
+
+/// The arguments given after a command, split on whitespace. Starting an
+/// argument with a `"` or `'` quote keeps the whitespace in it until the quote
+/// is closed, and a `\` escapes the character after it.
+///
+/// ```rust,no_run
+/// use telexide::{api::types::SendMessage, framework::Args, prelude::*};
+///
+/// #[command(description = "bans a user for the given amount of days")]
+/// async fn ban(context: Context, message: Message) -> CommandResult {
+///     // `/ban "John Doe" 3`
+///     let args = Args::from_message(&message).unwrap_or_default();
+///     let name = args.get(0).ok_or("who should be banned?")?;
+///     let days: u32 = args.parse(1)?;
+///
+///     let text = format!("banned {name} for {days} days");
+///     context
+///         .api
+///         .send_message(SendMessage::new(message.chat.get_id(), &text))
+///         .await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Args {
+    raw: String,
+    args: Vec<String>,
+}
+
+impl Args {
+    /// Splits the text into arguments
+    pub fn new(raw: &str) -> Self {
+        let raw = raw.trim();
+        Self {
+            raw: raw.to_owned(),
+            args: split(raw),
+        }
+    }
+
+    /// Gets the arguments given after the command the message starts with,
+    /// or `None` if the message isn't a command
+    pub fn from_message(message: &Message) -> Option<Self> {
+        if let MessageContent::Text {
+            entities,
+            content,
+        } = &message.content
+        {
+            let command = entities.iter().find_map(|e| match e {
+                MessageEntity::BotCommand(t) => Some(t),
+                _ => None,
+            })?;
+            let rest: Vec<u16> = content
+                .encode_utf16()
+                .skip(command.offset + command.length)
+                .collect();
+            return Some(Self::new(&String::from_utf16_lossy(&rest)));
+        }
+        None
+    }
+
+    /// The text after the command, without the surrounding whitespace
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// The amount of arguments
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Whether no arguments were given
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Gets the argument at the index
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(String::as_str)
+    }
+
+    /// Iterates over the arguments
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.args.iter().map(String::as_str)
+    }
+
+    /// Parses the argument at the index, failing with a [`CommandError`]
+    /// saying which argument is missing or invalid
+    pub fn parse<T>(&self, index: usize) -> Result<T, CommandError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let arg = self
+            .get(index)
+            .ok_or_else(|| CommandError(format!("argument {} is missing", index + 1)))?;
+        arg.parse()
+            .map_err(|e| CommandError(format!("argument {} is invalid: {e}", index + 1)))
+    }
+
+    /// Parses all of the arguments as the same type
+    pub fn parse_all<T>(&self) -> Result<Vec<T>, CommandError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        (0..self.len()).map(|i| self.parse(i)).collect()
+    }
+
+    /// Takes the arguments out
+    pub fn into_vec(self) -> Vec<String> {
+        self.args
+    }
+}
+
+/// splits on whitespace outside of quotes, with an unterminated quote running
+/// until the end of the text
+fn split(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.extend(chars.next());
+                in_arg = true;
+            },
+            '"' | '\'' if quote.is_none() && !in_arg => {
+                quote = Some(c);
+                in_arg = true;
+            },
+            c if quote == Some(c) => quote = None,
+            c if c.is_whitespace() && quote.is_none() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            },
+            c => {
+                current.push(c);
+                in_arg = true;
+            },
+        }
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
@@ -0,0 +1,74 @@
+use super::types::{Cooldown, CooldownBucket};
+use crate::{
+    client::{Context, FutureOutcome},
+    model::Message,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// A function handling a command being used during its [`Cooldown`], it
+/// receives a [`Context`], the [`Message`] with the command and the time left
+/// until it can be used again. Set it with [`Framework::on_cooldown`] to tell
+/// users to slow down.
+///
+/// [`Framework::on_cooldown`]: super::Framework::on_cooldown
+pub type CooldownHandlerFunc =
+    Arc<dyn Fn(Context, Message, Duration) -> FutureOutcome + Send + Sync>;
+
+/// How often the finished cooldowns are removed
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keeps when the commands can be used again
+pub(crate) struct Cooldowns {
+    until: Mutex<HashMap<(&'static str, i64), Instant>>,
+    pruned: Mutex<Instant>,
+}
+
+impl Default for Cooldowns {
+    fn default() -> Self {
+        Self {
+            until: Mutex::new(HashMap::new()),
+            pruned: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl Cooldowns {
+    /// Starts the cooldown of the command for the bucket the message is in,
+    /// or returns the time left if it's already running
+    pub(crate) fn start(
+        &self,
+        command: &'static str,
+        cooldown: Cooldown,
+        message: &Message,
+    ) -> Option<Duration> {
+        let chat_id = message.chat.get_id();
+        let bucket = match cooldown.bucket {
+            CooldownBucket::User => message.from.as_ref().map_or(chat_id, |u| u.id),
+            CooldownBucket::Chat => chat_id,
+            CooldownBucket::Global => 0,
+        };
+
+        let now = Instant::now();
+        let mut until = self.until.lock();
+        if let Some(&ends) = until.get(&(command, bucket)) {
+            if ends > now {
+                return Some(ends - now);
+            }
+        }
+        let mut pruned = self.pruned.lock();
+        if now.duration_since(*pruned) >= PRUNE_INTERVAL {
+            until.retain(|_, ends| *ends > now);
+            *pruned = now;
+        }
+        until.insert(
+            (command, bucket),
+            now + Duration::from_secs(cooldown.seconds),
+        );
+        None
+    }
+}
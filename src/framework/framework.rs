@@ -1,27 +1,151 @@
-use super::types::{CommandTypes, TelegramCommand};
+use super::{
+    cooldown::{CooldownHandlerFunc, Cooldowns},
+    handlers::CheckFunc,
+    hooks::{
+        run_after_hooks,
+        run_before_hooks,
+        AfterCommandFunc,
+        BeforeCommandFunc,
+        CommandRun,
+    },
+    types::{CommandTypes, TelegramCommand},
+};
 use crate::{
-    client::Context,
+    api::types::SendMessage,
+    client::{report_error, Context, ErrorHandlerFunc, HandlerError},
+    metrics::{self, HandlerMetrics},
     model::{Message, MessageContent, MessageEntity, Update, UpdateContent},
+    runtime::{spawn_task, Runtime, TaskHandle, TokioRuntime},
+    Error,
+};
+use log::debug;
+use parking_lot::RwLock;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
-use log::{debug, warn};
 
 /// A utility for easily managing commands.
 ///
 /// Refer to the [module-level documentation](index.html) for more detail
 pub struct Framework {
     commands: Vec<TelegramCommand>,
-    bot_name: String,
+    bot_name: RwLock<String>,
+    handle_edits: AtomicBool,
+    case_insensitive: AtomicBool,
+    cooldowns: Arc<Cooldowns>,
+    on_cooldown: RwLock<Option<CooldownHandlerFunc>>,
+    before_hooks: RwLock<Vec<BeforeCommandFunc>>,
+    after_hooks: RwLock<Vec<AfterCommandFunc>>,
 }
 
 impl Framework {
-    /// Creates a new framework instance given the bot name
+    /// Creates a new framework instance given the bot name. The client
+    /// replaces it with the username of the bot when it starts.
     pub fn new(bot_name: &str) -> Self {
         Self {
             commands: Vec::new(),
-            bot_name: bot_name.to_owned(),
+            bot_name: RwLock::new(bot_name.trim_start_matches('@').to_owned()),
+            handle_edits: AtomicBool::new(false),
+            case_insensitive: AtomicBool::new(false),
+            cooldowns: Arc::new(Cooldowns::default()),
+            on_cooldown: RwLock::new(None),
+            before_hooks: RwLock::new(Vec::new()),
+            after_hooks: RwLock::new(Vec::new()),
         }
     }
 
+    /// Sets the username of the bot, which commands addressed at a bot (like
+    /// `/ping@my_bot`) have to mention to be run. Commands addressed at other
+    /// bots in a group are ignored.
+    pub fn set_bot_name(&self, bot_name: &str) {
+        bot_name
+            .trim_start_matches('@')
+            .clone_into(&mut self.bot_name.write());
+    }
+
+    /// Gets the username of the bot commands can be addressed at
+    pub fn bot_name(&self) -> String {
+        self.bot_name.read().clone()
+    }
+
+    /// Sets whether commands are also run when a message containing one is
+    /// edited, so a user fixing a typo in a command gets an answer. Off by
+    /// default; commands can opt out with [`CommandOptions::ignore_edits`] and
+    /// tell edits apart with [`Message::is_edit`].
+    ///
+    /// [`CommandOptions::ignore_edits`]: struct.CommandOptions.html#structfield.ignore_edits
+    /// [`Message::is_edit`]: ../model/struct.Message.html#method.is_edit
+    pub fn set_handle_edits(&self, handle_edits: bool) -> &Self {
+        self.handle_edits.store(handle_edits, Ordering::Relaxed);
+        self
+    }
+
+    /// Sets whether commands are matched regardless of their case, so `/Start`
+    /// and `/START` run the `start` command, as keyboards on phones often
+    /// capitalize the first letter. Off by default. Like the bot name, it can
+    /// be changed on the framework made by [`create_framework!`].
+    ///
+    /// [`create_framework!`]: ../macro.create_framework.html
+    pub fn set_case_insensitive(&self, case_insensitive: bool) -> &Self {
+        self.case_insensitive
+            .store(case_insensitive, Ordering::Relaxed);
+        self
+    }
+
+    /// Sets the handler called when a command is used during its
+    /// [`Cooldown`], e.g. to tell the user to slow down. Without one, those
+    /// uses are dropped silently. It can be set on the framework made by
+    /// [`create_framework!`] as well.
+    ///
+    /// [`Cooldown`]: struct.Cooldown.html
+    /// [`create_framework!`]: ../macro.create_framework.html
+    pub fn on_cooldown<F, Fut>(&self, handler: F) -> &Self
+    where
+        F: Fn(Context, Message, Duration) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        *self.on_cooldown.write() = Some(Arc::new(move |ctx, message, remaining| {
+            Box::pin(handler(ctx, message, remaining))
+        }));
+        self
+    }
+
+    /// Adds a hook running before every command, before its checks. It gets
+    /// the name of the command and returns whether the command should run, so
+    /// commands can be disabled or logged in one place.
+    pub fn add_before_hook<F, Fut>(&self, hook: F) -> &Self
+    where
+        F: Fn(Context, Message, &'static str) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        self.before_hooks
+            .write()
+            .push(Arc::new(move |ctx, message, command| {
+                Box::pin(hook(ctx, message, command))
+            }));
+        self
+    }
+
+    /// Adds a hook running after every command which ran, getting its name,
+    /// result and how long it took
+    pub fn add_after_hook<F, Fut>(&self, hook: F) -> &Self
+    where
+        F: Fn(Context, Message, CommandRun) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.after_hooks
+            .write()
+            .push(Arc::new(move |ctx, message, run| {
+                Box::pin(hook(ctx, message, run))
+            }));
+        self
+    }
+
     fn match_command(&self, message: &Message, name: &str) -> bool {
         if let MessageContent::Text {
             entities,
@@ -31,8 +155,19 @@ impl Framework {
             for entity in entities {
                 if let MessageEntity::BotCommand(ref t) = entity {
                     let t = t.get_text(content);
-                    return t == format!("/{}", name)
-                        || t == format!("/{}@{}", name, &self.bot_name);
+                    let (command, bot) = t
+                        .split_once('@')
+                        .map_or((t.as_str(), None), |(c, b)| (c, Some(b)));
+                    let name_matches = command.strip_prefix('/').is_some_and(|c| {
+                        if self.case_insensitive.load(Ordering::Relaxed) {
+                            c.eq_ignore_ascii_case(name)
+                        } else {
+                            c == name
+                        }
+                    });
+                    // usernames aren't case sensitive
+                    return name_matches
+                        && bot.is_none_or(|b| b.eq_ignore_ascii_case(&self.bot_name.read()));
                 }
             }
         }
@@ -40,8 +175,24 @@ impl Framework {
     }
 
     #[allow(clippy::needless_pass_by_value)]
-    fn fire_message_commands(&self, context: Context, message: Message) {
+    fn fire_message_commands(
+        &self,
+        context: Context,
+        message: Message,
+        metrics: Option<Arc<HandlerMetrics>>,
+        on_error: Option<ErrorHandlerFunc>,
+        runtime: &dyn Runtime,
+    ) -> Vec<TaskHandle> {
+        let mut handles = Vec::new();
+        let update_type = if message.is_edit() {
+            "edited_message"
+        } else {
+            "message"
+        };
         for command in &self.commands {
+            if message.is_edit() && command.options.ignore_edits {
+                continue;
+            }
             match command.command.clone() {
                 CommandTypes::Default(c) if self.match_command(&message, &command.options.name) => {
                     let ctx = context.clone();
@@ -49,20 +200,72 @@ impl Framework {
                     let command_name = command.options.name;
                     debug!("calling command {}", &command_name);
 
-                    tokio::spawn(async move {
-                        let res = c(ctx, msg).await;
-                        if res.is_err() {
-                            warn!(
-                                "command {} returned error: {}",
-                                &command_name,
-                                res.unwrap_err().0
-                            )
+                    let chat_id = Some(msg.chat.get_id());
+                    let command_metrics = metrics.clone();
+                    if let Some(m) = &command_metrics {
+                        m.record_command(command_name);
+                    }
+                    let on_error = on_error.clone();
+                    let options = command.options;
+                    let cooldowns = self.cooldowns.clone();
+                    let on_cooldown = self.on_cooldown.read().clone();
+                    let before_hooks = self.before_hooks.read().clone();
+                    let after_hooks = self.after_hooks.read().clone();
+                    let fut = async move {
+                        if !run_before_hooks(&before_hooks, &ctx, &msg, command_name).await {
+                            debug!("command {} was skipped by a hook", &command_name);
+                            return;
                         }
-                    });
+                        if !run_checks(options.checks, &ctx, &msg).await {
+                            debug!("command {} was rejected by a check", &command_name);
+                            return;
+                        }
+                        let remaining = options
+                            .cooldown
+                            .and_then(|c| cooldowns.start(command_name, c, &msg));
+                        if let Some(remaining) = remaining {
+                            debug!("command {} is on cooldown", &command_name);
+                            if let Some(on_cooldown) = on_cooldown {
+                                on_cooldown(ctx, msg, remaining).await;
+                            }
+                            return;
+                        }
+                        let started = Instant::now();
+                        let result = c(ctx.clone(), msg.clone()).await;
+                        let run = CommandRun {
+                            name: command_name,
+                            result,
+                            elapsed: started.elapsed(),
+                        };
+                        run_after_hooks(&after_hooks, &ctx, &msg, &run).await;
+                        if let Err(e) = run.result {
+                            if let Some(m) = command_metrics {
+                                m.record_error();
+                            }
+                            let error = HandlerError {
+                                handler: format!("command /{command_name}"),
+                                chat_id,
+                                error: Error::Command(e),
+                            };
+                            report_error(ctx, error, on_error).await;
+                        }
+                    };
+
+                    handles.push(spawn_task(
+                        runtime,
+                        metrics::measure(
+                            metrics.clone(),
+                            format!("command /{command_name}"),
+                            chat_id,
+                            update_type,
+                            fut,
+                        ),
+                    ));
                 },
                 _ => (),
             }
         }
+        handles
     }
 
     /// add a command to the registered commands
@@ -77,8 +280,45 @@ impl Framework {
 
     /// fires off all commands matching the content in the update
     pub fn fire_commands(&self, context: Context, update: Update) {
-        if let UpdateContent::Message(c) = update.content {
-            self.fire_message_commands(context, c);
+        self.spawn_commands(context, update, None, None, &TokioRuntime);
+    }
+
+    /// fires off all commands matching the content in the update, returning
+    /// the handles to the spawned tasks
+    pub(crate) fn spawn_commands(
+        &self,
+        context: Context,
+        update: Update,
+        metrics: Option<Arc<HandlerMetrics>>,
+        on_error: Option<ErrorHandlerFunc>,
+        runtime: &dyn Runtime,
+    ) -> Vec<TaskHandle> {
+        match update.content {
+            UpdateContent::Message(c) => {
+                self.fire_message_commands(context, c, metrics, on_error, runtime)
+            },
+            UpdateContent::EditedMessage(c) if self.handle_edits.load(Ordering::Relaxed) => {
+                self.fire_message_commands(context, c, metrics, on_error, runtime)
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// runs the checks of a command in order, until one rejects it. The message
+/// of the rejection is sent to the chat, unless it's empty.
+async fn run_checks(checks: &[CheckFunc], ctx: &Context, message: &Message) -> bool {
+    for check in checks {
+        if let Err(e) = check(ctx.clone(), message.clone()).await {
+            if !e.0.is_empty() {
+                let mut reply = SendMessage::new(message.chat.get_id(), &e.0);
+                reply.reply_to_message(message);
+                if let Err(e) = ctx.api.send_message(reply).await {
+                    debug!("couldn't send the rejection of a command: {e}");
+                }
+            }
+            return false;
         }
     }
+    true
 }
@@ -1,6 +1,8 @@
-use super::types::CommandResult;
+use super::types::{CheckResult, CommandResult};
 use crate::{client::Context, model::Message};
 use std::{future::Future, pin::Pin};
 
 pub(crate) type CommandOutcome = Pin<Box<dyn Future<Output = CommandResult> + Send>>;
 pub(crate) type CommandHandlerFunc = fn(Context, Message) -> CommandOutcome;
+pub(crate) type CheckOutcome = Pin<Box<dyn Future<Output = CheckResult> + Send>>;
+pub(crate) type CheckFunc = fn(Context, Message) -> CheckOutcome;
@@ -0,0 +1,63 @@
+use super::types::CommandResult;
+use crate::{
+    client::{Context, FutureOutcome},
+    model::Message,
+};
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+
+/// A function running before every command, it receives a [`Context`], the
+/// [`Message`] with the command and the name of the command. Returning false
+/// skips the command, e.g. when it's disabled by a feature flag. Add it with
+/// [`Framework::add_before_hook`].
+///
+/// [`Framework::add_before_hook`]: super::Framework::add_before_hook
+pub type BeforeCommandFunc = Arc<
+    dyn Fn(Context, Message, &'static str) -> Pin<Box<dyn Future<Output = bool> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// A function running after every command, it receives a [`Context`], the
+/// [`Message`] with the command and the [`CommandRun`] describing how it
+/// went. Add it with [`Framework::add_after_hook`].
+///
+/// [`Framework::add_after_hook`]: super::Framework::add_after_hook
+pub type AfterCommandFunc =
+    Arc<dyn Fn(Context, Message, CommandRun) -> FutureOutcome + Send + Sync>;
+
+/// A command which ran, passed to the [`AfterCommandFunc`]s
+#[derive(Debug, Clone)]
+pub struct CommandRun {
+    /// The name of the command
+    pub name: &'static str,
+    /// What the command returned
+    pub result: CommandResult,
+    /// How long the command took to run
+    pub elapsed: Duration,
+}
+
+/// runs the hooks in order, until one of them skips the command
+pub(super) async fn run_before_hooks(
+    hooks: &[BeforeCommandFunc],
+    ctx: &Context,
+    message: &Message,
+    command: &'static str,
+) -> bool {
+    for hook in hooks {
+        if !hook(ctx.clone(), message.clone(), command).await {
+            return false;
+        }
+    }
+    true
+}
+
+pub(super) async fn run_after_hooks(
+    hooks: &[AfterCommandFunc],
+    ctx: &Context,
+    message: &Message,
+    run: &CommandRun,
+) {
+    for hook in hooks {
+        hook(ctx.clone(), message.clone(), run.clone()).await;
+    }
+}
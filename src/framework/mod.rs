@@ -1,6 +1,9 @@
 //! The framework provides a customizable way to manage your bots commands
 
+mod args;
+mod cooldown;
 pub(crate) mod framework;
+mod hooks;
 
 // made public for the procedural macros to use
 #[doc(hidden)]
@@ -8,5 +11,8 @@ pub mod handlers;
 #[doc(hidden)]
 pub mod types;
 
+pub use args::Args;
+pub use cooldown::CooldownHandlerFunc;
 pub use framework::Framework;
-pub use types::{CommandError, CommandResult};
+pub use hooks::{AfterCommandFunc, BeforeCommandFunc, CommandRun};
+pub use types::{CheckResult, CommandError, CommandResult, Cooldown, CooldownBucket};
@@ -1,4 +1,4 @@
-use super::handlers::CommandHandlerFunc;
+use super::handlers::{CheckFunc, CommandHandlerFunc};
 use crate::{model::BotCommand, utils::result::Error};
 
 #[derive(Clone)]
@@ -6,10 +6,80 @@ pub enum CommandTypes {
     Default(CommandHandlerFunc),
 }
 
+/// The options of a command, made by `#[command]`. Build them with
+/// [`CommandOptions::new`] when writing a [`TelegramCommand`] by hand, as more
+/// options may be added.
 #[derive(Clone)]
+#[non_exhaustive]
 pub struct CommandOptions {
     pub name: &'static str,
     pub description: &'static str,
+    /// Whether the command is skipped for edited messages, when the
+    /// framework handles edited commands
+    pub ignore_edits: bool,
+    /// The checks which have to pass before the command is run, in order
+    pub checks: &'static [CheckFunc],
+    /// How often the command can be used, set with `#[cooldown(...)]`
+    pub cooldown: Option<Cooldown>,
+}
+
+impl CommandOptions {
+    /// Creates the options of a command without checks or a cooldown, which
+    /// also runs for edited messages when the framework handles those
+    pub const fn new(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            ignore_edits: false,
+            checks: &[],
+            cooldown: None,
+        }
+    }
+
+    /// Sets whether the command is skipped for edited messages
+    #[must_use]
+    pub const fn set_ignore_edits(mut self, ignore_edits: bool) -> Self {
+        self.ignore_edits = ignore_edits;
+        self
+    }
+
+    /// Sets the checks which have to pass before the command is run
+    #[must_use]
+    pub const fn set_checks(mut self, checks: &'static [CheckFunc]) -> Self {
+        self.checks = checks;
+        self
+    }
+
+    /// Sets how often the command can be used
+    #[must_use]
+    pub const fn set_cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+}
+
+/// How often a command can be used, added to it with `#[cooldown(seconds)]`
+/// or `#[cooldown(seconds, per = "chat")]`. Uses of the command during the
+/// cooldown are dropped, or passed to the [`Framework::on_cooldown`] handler.
+///
+/// [`Framework::on_cooldown`]: struct.Framework.html#method.on_cooldown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cooldown {
+    /// The seconds until the command can be used again
+    pub seconds: u64,
+    /// Which uses of the command share the cooldown
+    pub bucket: CooldownBucket,
+}
+
+/// Which uses of a command share a [`Cooldown`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CooldownBucket {
+    /// Every user has their own cooldown, `per = "user"`. This is the default.
+    User,
+    /// Every chat has its own cooldown, `per = "chat"`
+    Chat,
+    /// All users share the cooldown, `per = "global"`
+    Global,
 }
 
 #[derive(Clone)]
@@ -45,6 +115,11 @@ impl<T: std::fmt::Display> From<T> for CommandError {
 /// as the Ok
 pub type CommandResult = ::std::result::Result<(), CommandError>;
 
+/// The result of a check of a command, added to it with `#[check(...)]`.
+/// Returning an error rejects the command, its message is then sent to the
+/// chat unless it's empty.
+pub type CheckResult = ::std::result::Result<(), CommandError>;
+
 impl From<CommandError> for Error {
     #[inline]
     fn from(d: CommandError) -> Self {
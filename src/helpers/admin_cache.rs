@@ -0,0 +1,71 @@
+use crate::{
+    api::{types::GetChatAdministrators, API},
+    utils::result::Result,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+/// Caches the administrators of chats, so checking whether a user is an admin
+/// doesn't need a call to [`get_chat_administrators`] every time.
+///
+/// The administrators of a chat are fetched again once the cached list is
+/// older than the configured time to live, or after it was invalidated.
+///
+/// [`get_chat_administrators`]: ../api/trait.API.html#method.get_chat_administrators
+pub struct AdminCache {
+    ttl: Duration,
+    chats: Mutex<HashMap<i64, (Instant, HashSet<i64>)>>,
+}
+
+impl AdminCache {
+    /// Creates a new cache, keeping the administrators of a chat for the given
+    /// duration
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            chats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns whether the given user is the creator or an administrator of
+    /// the chat, fetching the administrators if they aren't cached
+    pub async fn is_admin(&self, api: &dyn API, chat_id: i64, user_id: i64) -> Result<bool> {
+        if let Some((fetched, admins)) = self.chats.lock().get(&chat_id) {
+            if fetched.elapsed() < self.ttl {
+                return Ok(admins.contains(&user_id));
+            }
+        }
+
+        let admins: HashSet<i64> = api
+            .get_chat_administrators(GetChatAdministrators {
+                chat_id,
+            })
+            .await?
+            .iter()
+            .filter(|m| m.is_admin())
+            .map(|m| m.get_user().id)
+            .collect();
+
+        let is_admin = admins.contains(&user_id);
+        self.chats.lock().insert(chat_id, (Instant::now(), admins));
+        Ok(is_admin)
+    }
+
+    /// Removes the cached administrators of the given chat, for example after
+    /// receiving a [`ChatMember`] update for it
+    ///
+    /// [`ChatMember`]: ../model/enum.UpdateContent.html#variant.ChatMember
+    pub fn invalidate(&self, chat_id: i64) {
+        self.chats.lock().remove(&chat_id);
+    }
+}
+
+impl Default for AdminCache {
+    /// Creates a cache keeping the administrators for 10 minutes
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10 * 60))
+    }
+}
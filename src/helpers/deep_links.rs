@@ -0,0 +1,141 @@
+//! Builders for [deep links](https://core.telegram.org/api/links#bot-links)
+//! to the bot, its Mini Apps and its attachment menu, and helpers for encoding
+//! the payloads passed through them.
+
+use crate::utils::result::{Result, TelegramError};
+use percent_encoding::percent_decode_str;
+
+/// The maximum length of a start parameter accepted by telegram
+const MAX_PAYLOAD_LENGTH: usize = 64;
+
+/// The types of chats an attachment menu link can let the user choose from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachMenuChatType {
+    Users,
+    Bots,
+    Groups,
+    Channels,
+}
+
+impl AttachMenuChatType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Users => "users",
+            Self::Bots => "bots",
+            Self::Groups => "groups",
+            Self::Channels => "channels",
+        }
+    }
+}
+
+/// Encodes arbitrary data into a start parameter, using url-safe base64 as
+/// start parameters may only contain `A-Z`, `a-z`, `0-9`, `_` and `-`.
+/// Returns an error if the encoded data is longer than the 64 characters
+/// telegram allows.
+pub fn encode_payload(data: &[u8]) -> Result<String> {
+    let payload = base64::encode_config(data, base64::URL_SAFE_NO_PAD);
+    validate_payload(&payload)?;
+    Ok(payload)
+}
+
+/// Decodes a start parameter created using [`encode_payload`]
+pub fn decode_payload(payload: &str) -> Result<Vec<u8>> {
+    base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+        .map_err(|e| TelegramError::InvalidArgument(format!("invalid payload: {e}")).into())
+}
+
+/// Checks whether the payload can be used as a start parameter, it may be at
+/// most 64 characters long and only contain `A-Z`, `a-z`, `0-9`, `_` and `-`
+pub fn validate_payload(payload: &str) -> Result<()> {
+    if payload.len() > MAX_PAYLOAD_LENGTH {
+        return Err(TelegramError::InvalidArgument(format!(
+            "payload is {} characters long, while at most {MAX_PAYLOAD_LENGTH} are allowed",
+            payload.len()
+        ))
+        .into());
+    }
+
+    if let Some(c) = payload
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-'))
+    {
+        return Err(TelegramError::InvalidArgument(format!(
+            "payload contains the character '{c}', only A-Z, a-z, 0-9, _ and - are allowed"
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+fn bot_link(bot_username: &str) -> String {
+    format!("https://t.me/{}", bot_username.trim_start_matches('@'))
+}
+
+/// Builds a link starting a chat with the bot, sending it `/start <payload>`
+pub fn start_link(bot_username: &str, payload: &str) -> Result<String> {
+    validate_payload(payload)?;
+    Ok(format!("{}?start={payload}", bot_link(bot_username)))
+}
+
+/// Builds a link opening a Mini App of the bot, passing the payload as
+/// `start_param` in its init data.
+///
+/// If `app_name` is `None`, the link opens the main Mini App of the bot,
+/// otherwise the Mini App with the given short name.
+pub fn mini_app_link(
+    bot_username: &str,
+    app_name: Option<&str>,
+    payload: Option<&str>,
+) -> Result<String> {
+    let mut link = bot_link(bot_username);
+    if let Some(app) = app_name {
+        link.push('/');
+        link.push_str(app);
+    }
+
+    if let Some(p) = payload {
+        validate_payload(p)?;
+        link.push_str("?startapp=");
+        link.push_str(p);
+    }
+    Ok(link)
+}
+
+/// Builds a link opening the attachment menu of the bot, letting the user pick
+/// a chat of one of the given types to open it in. If no chat types are given,
+/// it opens in the current chat. The payload is passed as `start_param` in the
+/// init data of the Mini App.
+pub fn attachment_menu_link(
+    bot_username: &str,
+    choose: &[AttachMenuChatType],
+    payload: Option<&str>,
+) -> Result<String> {
+    let mut link = format!("{}?startattach", bot_link(bot_username));
+    if let Some(p) = payload {
+        validate_payload(p)?;
+        link.push('=');
+        link.push_str(p);
+    }
+
+    if !choose.is_empty() {
+        let types: Vec<&str> = choose.iter().map(|c| c.as_str()).collect();
+        link.push_str("&choose=");
+        link.push_str(&types.join("+"));
+    }
+    Ok(link)
+}
+
+/// Gets the `start_param` from the init data a Mini App received, which holds
+/// the payload of the link the Mini App was opened with
+pub fn start_param_from_init_data(init_data: &str) -> Option<String> {
+    init_data
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "start_param")
+        .map(|(_, value)| {
+            percent_decode_str(&value.replace('+', " "))
+                .decode_utf8_lossy()
+                .into_owned()
+        })
+}
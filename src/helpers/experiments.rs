@@ -0,0 +1,270 @@
+use crate::{
+    api::{types::SendMessage, API},
+    helpers::formatting::FormattedText,
+    model::{utils::unix_date_formatting, Message},
+    storage::Storage,
+    utils::result::{Error, Result, TelegramError},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Mutex;
+
+/// A message variant of an experiment
+#[derive(Debug, Clone, PartialEq)]
+struct Variant {
+    name: String,
+    weight: u32,
+    text: FormattedText,
+}
+
+/// Which variant of an experiment was sent to a user, as recorded by
+/// [`Experiments`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SentVariant {
+    /// The name of the variant
+    pub variant: String,
+    /// The id of the user the variant was picked for
+    pub user_id: i64,
+    /// The chat the variant was last sent to
+    pub chat_id: i64,
+    /// The id of the message the variant was last sent as
+    pub message_id: i64,
+    /// When the variant was first sent to the user
+    #[serde(with = "unix_date_formatting")]
+    pub first_sent: DateTime<Utc>,
+    /// How often the variant was sent to the user
+    pub times_sent: u64,
+}
+
+/// Message variants for A/B tests, picking a variant per user by weight and
+/// recording which variant every user got for correlating it with their
+/// behaviour later on.
+///
+/// A user is assigned a variant by hashing their id together with the name of
+/// the experiment, so they keep getting the same variant without anything
+/// being stored first. Once a variant was sent to a user they keep getting
+/// that one when the weights are changed afterwards, unless its weight is set
+/// to 0.
+///
+/// The assignments are persisted in the provided [`Storage`]. To use
+/// [`Context::send_variant`], insert the experiments into the data of the
+/// client:
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use telexide::{helpers::Experiments, prelude::*, storage::MemoryStorage};
+///
+/// # fn register(client: &Client) {
+/// let mut experiments = Experiments::new(Arc::new(MemoryStorage::new()));
+/// experiments
+///     .add_variant("onboarding", "short", 3, "Welcome!")
+///     .add_variant("onboarding", "guided", 1, "Welcome! Try /help to get started.");
+/// client.data.write().insert::<Experiments>(Arc::new(experiments));
+/// # }
+///
+/// # async fn welcome(ctx: Context, message: Message) -> telexide::Result<()> {
+/// ctx.send_variant(&message, "onboarding").await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Context::send_variant`]: ../client/struct.Context.html#method.send_variant
+pub struct Experiments {
+    storage: Arc<dyn Storage>,
+    variants: HashMap<String, Vec<Variant>>,
+    lock: Mutex<()>,
+}
+
+impl typemap::Key for Experiments {
+    type Value = Arc<Experiments>;
+}
+
+impl Experiments {
+    /// Creates experiments without any variants, persisting the assignments in
+    /// the given storage
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            variants: HashMap::new(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Adds a variant with the given weight and text to the experiment,
+    /// creating the experiment if it doesn't exist yet. A variant with a weight
+    /// of 3 is sent three times as often as one with a weight of 1.
+    pub fn add_variant(
+        &mut self,
+        experiment: &str,
+        variant: &str,
+        weight: u32,
+        text: &str,
+    ) -> &mut Self {
+        let text = FormattedText {
+            text: text.to_owned(),
+            entities: Vec::new(),
+        };
+        self.add_formatted_variant(experiment, variant, weight, &text)
+    }
+
+    /// Adds a variant with a formatted text to the experiment, see
+    /// [`add_variant`](Experiments::add_variant)
+    pub fn add_formatted_variant(
+        &mut self,
+        experiment: &str,
+        variant: &str,
+        weight: u32,
+        text: &FormattedText,
+    ) -> &mut Self {
+        let variants = self.variants.entry(experiment.to_owned()).or_default();
+        variants.retain(|v| v.name != variant);
+        variants.push(Variant {
+            name: variant.to_owned(),
+            weight,
+            text: text.clone(),
+        });
+        self
+    }
+
+    /// picks the variant for the user by hashing their id, `None` if the
+    /// experiment doesn't have any variants with a weight
+    fn pick_variant(&self, experiment: &str, user_id: i64) -> Option<&Variant> {
+        let variants = self.variants.get(experiment)?;
+        let total: u64 = variants.iter().map(|v| u64::from(v.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut point = stable_hash(experiment, user_id) % total;
+        variants.iter().find(|v| {
+            if point < u64::from(v.weight) {
+                return true;
+            }
+            point -= u64::from(v.weight);
+            false
+        })
+    }
+
+    /// Picks the variant of the experiment for the user by hashing their id,
+    /// without looking at which variant they were sent before. Returns the name
+    /// of the variant.
+    pub fn pick(&self, experiment: &str, user_id: i64) -> Result<&str> {
+        self.pick_variant(experiment, user_id)
+            .map(|v| v.name.as_str())
+            .ok_or_else(|| no_variants(experiment))
+    }
+
+    fn key(experiment: &str) -> String {
+        format!("experiments:{experiment}")
+    }
+
+    async fn load(&self, experiment: &str) -> Result<HashMap<i64, SentVariant>> {
+        Ok(self
+            .storage
+            .get_as(&Self::key(experiment))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// The variant of the experiment sent to the user, if any was sent yet
+    pub async fn sent_variant(
+        &self,
+        experiment: &str,
+        user_id: i64,
+    ) -> Result<Option<SentVariant>> {
+        Ok(self.load(experiment).await?.remove(&user_id))
+    }
+
+    /// Every user a variant of the experiment was sent to, ordered by when they
+    /// first got it
+    pub async fn export(&self, experiment: &str) -> Result<Vec<SentVariant>> {
+        let mut sent: Vec<_> = self.load(experiment).await?.into_values().collect();
+        sent.sort_by_key(|s| (s.first_sent, s.user_id));
+        Ok(sent)
+    }
+
+    /// How many users were sent each variant of the experiment
+    pub async fn counts(&self, experiment: &str) -> Result<HashMap<String, u64>> {
+        let mut counts = HashMap::new();
+        for sent in self.load(experiment).await?.into_values() {
+            *counts.entry(sent.variant).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+
+    /// Forgets which variants of the experiment were sent
+    pub async fn clear(&self, experiment: &str) -> Result<()> {
+        self.storage.remove(&Self::key(experiment)).await
+    }
+
+    /// Sends the variant of the experiment for the user to the chat and
+    /// records it. Users who were sent a variant before get the same one
+    /// again, as long as it still exists and has a weight.
+    pub async fn send_variant(
+        &self,
+        api: &dyn API,
+        chat_id: i64,
+        user_id: i64,
+        experiment: &str,
+    ) -> Result<Message> {
+        let previous = self.sent_variant(experiment, user_id).await?;
+        let variant = previous
+            .and_then(|p| {
+                self.variants
+                    .get(experiment)?
+                    .iter()
+                    .find(|v| v.name == p.variant && v.weight > 0)
+            })
+            .or_else(|| self.pick_variant(experiment, user_id))
+            .ok_or_else(|| no_variants(experiment))?;
+
+        let mut data = SendMessage::new(chat_id, "");
+        data.set_formatted_text(&variant.text);
+        let message = api.send_message(data).await?;
+
+        let _guard = self.lock.lock().await;
+        let mut sent = self.load(experiment).await?;
+        let record = sent.entry(user_id).or_insert_with(|| SentVariant {
+            variant: variant.name.clone(),
+            user_id,
+            chat_id,
+            message_id: message.message_id,
+            first_sent: message.date,
+            times_sent: 0,
+        });
+        if record.variant != variant.name {
+            record.variant.clone_from(&variant.name);
+            record.first_sent = message.date;
+            record.times_sent = 0;
+        }
+        record.chat_id = chat_id;
+        record.message_id = message.message_id;
+        record.times_sent += 1;
+        self.storage.set_as(&Self::key(experiment), &sent).await?;
+
+        Ok(message)
+    }
+}
+
+fn no_variants(experiment: &str) -> Error {
+    TelegramError::InvalidArgument(format!(
+        "the experiment {experiment} doesn't have any variants with a weight"
+    ))
+    .into()
+}
+
+/// FNV-1a over the experiment and user, which unlike the hasher of the
+/// standard library is guaranteed to stay the same across releases
+fn stable_hash(experiment: &str, user_id: i64) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in experiment
+        .bytes()
+        .chain(std::iter::once(0))
+        .chain(user_id.to_le_bytes().iter().copied())
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,211 @@
+#[cfg(feature = "stickers")]
+use crate::api::types::{InputFile, SendSticker};
+use crate::{
+    api::{
+        types::{
+            SendAnimation,
+            SendAudio,
+            SendDocument,
+            SendPhoto,
+            SendVideo,
+            SendVideoNote,
+            SendVoice,
+        },
+        API,
+    },
+    model::{Message, MessageContent},
+    storage::Storage,
+    utils::result::{Result, TelegramError},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// The kind of media a [`StoredFile`] is, deciding which method is used to
+/// send it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StoredFileKind {
+    Photo,
+    Audio,
+    Document,
+    Video,
+    Animation,
+    Voice,
+    VideoNote,
+    Sticker,
+}
+
+/// A file stored in a [`FileVault`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoredFile {
+    /// The id of the file on the telegram servers
+    pub file_id: String,
+    /// What kind of media the file is
+    pub kind: StoredFileKind,
+}
+
+impl StoredFile {
+    pub fn new(file_id: &str, kind: StoredFileKind) -> Self {
+        Self {
+            file_id: file_id.to_owned(),
+            kind,
+        }
+    }
+
+    /// Gets the file sent in the message, taking the largest size of photos.
+    /// Returns `None` if the message doesn't contain any sendable media.
+    pub fn from_message(message: &Message) -> Option<Self> {
+        let (file_id, kind) = match &message.content {
+            MessageContent::Photo {
+                content, ..
+            } => (
+                &content.iter().max_by_key(|p| p.width * p.height)?.file_id,
+                StoredFileKind::Photo,
+            ),
+            MessageContent::Audio {
+                content, ..
+            } => (&content.file_id, StoredFileKind::Audio),
+            MessageContent::Document {
+                content, ..
+            } => (&content.file_id, StoredFileKind::Document),
+            MessageContent::Video {
+                content, ..
+            } => (&content.file_id, StoredFileKind::Video),
+            MessageContent::Animation {
+                content, ..
+            } => (&content.file_id, StoredFileKind::Animation),
+            MessageContent::Voice {
+                content, ..
+            } => (&content.file_id, StoredFileKind::Voice),
+            MessageContent::VideoNote {
+                content,
+            } => (&content.file_id, StoredFileKind::VideoNote),
+            MessageContent::Sticker {
+                content,
+            } => (&content.file_id, StoredFileKind::Sticker),
+            _ => return None,
+        };
+
+        Some(Self::new(file_id, kind))
+    }
+}
+
+/// Keeps files which were sent to telegram before under a logical name per
+/// chat, like "rules-image" or "price-list.pdf", so they can be sent again
+/// by their `file_id` without uploading them every time.
+///
+/// The files are persisted in the provided [`Storage`]. To use
+/// [`Context::send_stored`], insert the vault into the data of the client:
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use telexide::{helpers::FileVault, prelude::*, storage::MemoryStorage};
+///
+/// # fn register(client: &Client) {
+/// let vault = FileVault::new(Arc::new(MemoryStorage::new()));
+/// client.data.write().insert::<FileVault>(Arc::new(vault));
+/// # }
+///
+/// # async fn send_rules(ctx: Context, chat_id: i64) -> telexide::Result<()> {
+/// ctx.send_stored(chat_id, "rules-image").await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Context::send_stored`]: ../client/struct.Context.html#method.send_stored
+pub struct FileVault {
+    storage: Arc<dyn Storage>,
+}
+
+impl typemap::Key for FileVault {
+    type Value = Arc<FileVault>;
+}
+
+impl FileVault {
+    /// Creates a new vault, persisting the files in the given storage
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+        }
+    }
+
+    fn key(chat_id: i64, name: &str) -> String {
+        format!("file_vault:{chat_id}:{name}")
+    }
+
+    /// Stores the file under the given name for the chat, replacing any file
+    /// previously stored under that name
+    pub async fn store(&self, chat_id: i64, name: &str, file: &StoredFile) -> Result<()> {
+        self.storage.set_as(&Self::key(chat_id, name), file).await
+    }
+
+    /// Stores the media sent in the message under the given name for the chat,
+    /// returning the stored file
+    pub async fn store_from_message(
+        &self,
+        chat_id: i64,
+        name: &str,
+        message: &Message,
+    ) -> Result<StoredFile> {
+        let file = StoredFile::from_message(message).ok_or_else(|| {
+            TelegramError::InvalidArgument("the message doesn't contain any media".to_owned())
+        })?;
+
+        self.store(chat_id, name, &file).await?;
+        Ok(file)
+    }
+
+    /// Gets the file stored under the given name for the chat
+    pub async fn get(&self, chat_id: i64, name: &str) -> Result<Option<StoredFile>> {
+        self.storage.get_as(&Self::key(chat_id, name)).await
+    }
+
+    /// Removes the file stored under the given name for the chat
+    pub async fn remove(&self, chat_id: i64, name: &str) -> Result<()> {
+        self.storage.remove(&Self::key(chat_id, name)).await
+    }
+
+    /// Sends the file stored under the given name to the chat, using the send
+    /// method matching the kind of the file. Returns
+    /// [`TelegramError::NotFound`] if no such file is stored.
+    pub async fn send(&self, api: &dyn API, chat_id: i64, name: &str) -> Result<Message> {
+        let file = self
+            .get(chat_id, name)
+            .await?
+            .ok_or(TelegramError::NotFound)?;
+
+        send_file(api, chat_id, file).await
+    }
+}
+
+async fn send_file(api: &dyn API, chat_id: i64, file: StoredFile) -> Result<Message> {
+    let id = file.file_id;
+    match file.kind {
+        StoredFileKind::Photo => api.send_photo(SendPhoto::new(chat_id, id)).await,
+        StoredFileKind::Audio => api.send_audio(SendAudio::new(chat_id, id)).await,
+        StoredFileKind::Document => api.send_document(SendDocument::new(chat_id, id)).await,
+        StoredFileKind::Video => api.send_video(SendVideo::new(chat_id, id)).await,
+        StoredFileKind::Animation => api.send_animation(SendAnimation::new(chat_id, id)).await,
+        StoredFileKind::Voice => api.send_voice(SendVoice::new(chat_id, id)).await,
+        StoredFileKind::VideoNote => api.send_video_note(SendVideoNote::new(chat_id, id)).await,
+        #[cfg(feature = "stickers")]
+        StoredFileKind::Sticker => {
+            api.send_sticker(SendSticker {
+                business_connection_id: None,
+                chat_id,
+                sticker: InputFile::FileId(id),
+                disable_notification: false,
+                protect_content: false,
+                reply_to_message_id: None,
+                allow_sending_without_reply: false,
+                reply_markup: None,
+            })
+            .await
+        },
+        #[cfg(not(feature = "stickers"))]
+        StoredFileKind::Sticker => Err(TelegramError::InvalidArgument(
+            "sending stickers needs the stickers feature".to_owned(),
+        )
+        .into()),
+    }
+}
@@ -0,0 +1,243 @@
+//! Composing formatted text from typed fragments, producing the text together
+//! with its [`MessageEntity`]s, so nothing needs to be escaped for a parse
+//! mode and the UTF-16 offsets telegram expects are always right.
+//!
+//! ```rust
+//! use telexide::{
+//!     api::types::SendMessage,
+//!     helpers::formatting::{bold, compose, italic, link, text},
+//! };
+//!
+//! let name = "Ådne 👋";
+//! let formatted = compose([
+//!     text("Hi "),
+//!     bold(name),
+//!     text(", read "),
+//!     link("the rules", "https://example.com/rules"),
+//!     italic(" first!"),
+//! ]);
+//! assert_eq!(formatted.text, "Hi Ådne 👋, read the rules first!");
+//!
+//! let mut message = SendMessage::new(-100, "");
+//! message.set_formatted_text(&formatted);
+//! ```
+//!
+//! [`MessageEntity`]: ../../model/enum.MessageEntity.html
+
+use crate::model::{Message, MessageEntity, Pre, TextBlock, TextLink, TextMention, User};
+
+/// How a [`Fragment`] is styled
+#[derive(Debug, Clone, PartialEq)]
+enum Style {
+    Bold,
+    Italic,
+    Underline,
+    StrikeThrough,
+    Code,
+    Pre(Option<String>),
+    Link(String),
+    Mention(User),
+}
+
+impl Style {
+    fn entity(&self, text_block: TextBlock) -> MessageEntity {
+        match self {
+            Self::Bold => MessageEntity::Bold(text_block),
+            Self::Italic => MessageEntity::Italic(text_block),
+            Self::Underline => MessageEntity::Underline(text_block),
+            Self::StrikeThrough => MessageEntity::StrikeThrough(text_block),
+            Self::Code => MessageEntity::Code(text_block),
+            Self::Pre(language) => MessageEntity::Pre(Pre {
+                text_block,
+                language: language.clone(),
+            }),
+            Self::Link(url) => MessageEntity::TextLink(TextLink {
+                text_block,
+                url: url.clone(),
+            }),
+            Self::Mention(user) => MessageEntity::TextMention(TextMention {
+                text_block,
+                user: user.clone(),
+            }),
+        }
+    }
+}
+
+/// A part of a formatted text, created by the functions of this module. Styled
+/// fragments can contain other fragments, so `bold(italic("text"))` is both
+/// bold and italic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    style: Option<Style>,
+    content: Content,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    Text(String),
+    Fragments(Vec<Fragment>),
+}
+
+impl From<&str> for Fragment {
+    fn from(t: &str) -> Self {
+        text(t)
+    }
+}
+
+impl From<String> for Fragment {
+    fn from(t: String) -> Self {
+        text(t)
+    }
+}
+
+impl From<&String> for Fragment {
+    fn from(t: &String) -> Self {
+        text(t)
+    }
+}
+
+impl From<Vec<Fragment>> for Fragment {
+    fn from(fragments: Vec<Fragment>) -> Self {
+        Self {
+            style: None,
+            content: Content::Fragments(fragments),
+        }
+    }
+}
+
+impl Fragment {
+    fn styled(style: Style, content: impl Into<Fragment>) -> Self {
+        Self {
+            style: Some(style),
+            content: Content::Fragments(vec![content.into()]),
+        }
+    }
+
+    fn write_to(&self, formatted: &mut FormattedText, offset: &mut usize) {
+        let start = *offset;
+        let first_entity = formatted.entities.len();
+
+        match &self.content {
+            Content::Text(t) => {
+                formatted.text.push_str(t);
+                *offset += t.encode_utf16().count();
+            },
+            Content::Fragments(fragments) => {
+                for f in fragments {
+                    f.write_to(formatted, offset);
+                }
+            },
+        }
+
+        if let Some(style) = &self.style {
+            if *offset > start {
+                // outer entities go before the ones nested in them
+                formatted.entities.insert(
+                    first_entity,
+                    style.entity(TextBlock {
+                        offset: start,
+                        length: *offset - start,
+                    }),
+                );
+            }
+        }
+    }
+}
+
+/// A text together with the entities formatting it
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FormattedText {
+    /// The text, without any markup
+    pub text: String,
+    /// The entities formatting the text, with offsets in UTF-16 code units
+    pub entities: Vec<MessageEntity>,
+}
+
+impl FormattedText {
+    /// Appends the fragment to the end of the text
+    pub fn push(&mut self, fragment: impl Into<Fragment>) -> &mut Self {
+        let mut offset = self.text.encode_utf16().count();
+        fragment.into().write_to(self, &mut offset);
+        self
+    }
+
+    /// Gets the text or caption of a received message together with its
+    /// entities, for sending it on with the exact same formatting
+    pub fn from_message(message: &Message) -> Option<Self> {
+        Some(Self {
+            text: message.get_text()?,
+            entities: message.get_entities().unwrap_or_default(),
+        })
+    }
+
+    /// Returns the entities, or `None` if there are none, as expected by the
+    /// payloads
+    pub fn entities(&self) -> Option<Vec<MessageEntity>> {
+        if self.entities.is_empty() {
+            None
+        } else {
+            Some(self.entities.clone())
+        }
+    }
+}
+
+/// Composes the fragments into one formatted text
+pub fn compose<I, F>(fragments: I) -> FormattedText
+where
+    I: IntoIterator<Item = F>,
+    F: Into<Fragment>,
+{
+    let mut formatted = FormattedText::default();
+    for f in fragments {
+        formatted.push(f);
+    }
+    formatted
+}
+
+/// Plain text without formatting
+pub fn text(text: impl Into<String>) -> Fragment {
+    Fragment {
+        style: None,
+        content: Content::Text(text.into()),
+    }
+}
+
+/// Bold text
+pub fn bold(content: impl Into<Fragment>) -> Fragment {
+    Fragment::styled(Style::Bold, content)
+}
+
+/// Italic text
+pub fn italic(content: impl Into<Fragment>) -> Fragment {
+    Fragment::styled(Style::Italic, content)
+}
+
+/// Underlined text
+pub fn underline(content: impl Into<Fragment>) -> Fragment {
+    Fragment::styled(Style::Underline, content)
+}
+
+/// Strikethrough text
+pub fn strikethrough(content: impl Into<Fragment>) -> Fragment {
+    Fragment::styled(Style::StrikeThrough, content)
+}
+
+/// A monowidth code string
+pub fn code(content: impl Into<Fragment>) -> Fragment {
+    Fragment::styled(Style::Code, content)
+}
+
+/// A monowidth code block, optionally highlighted as the given language
+pub fn pre(content: impl Into<Fragment>, language: Option<&str>) -> Fragment {
+    Fragment::styled(Style::Pre(language.map(ToOwned::to_owned)), content)
+}
+
+/// Text opening the url when clicked
+pub fn link(content: impl Into<Fragment>, url: &str) -> Fragment {
+    Fragment::styled(Style::Link(url.to_owned()), content)
+}
+
+/// Text mentioning the user, which also works for users without a username
+pub fn mention(content: impl Into<Fragment>, user: &User) -> Fragment {
+    Fragment::styled(Style::Mention(user.clone()), content)
+}
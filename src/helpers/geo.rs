@@ -0,0 +1,166 @@
+use super::LocaleFormat;
+use crate::{
+    api::types::{AnswerInlineQuery, InlineQueryResult},
+    model::{InlineQuery, Location},
+    utils::result::Result,
+};
+use async_trait::async_trait;
+
+/// The mean radius of the earth in meters
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// The maximum amount of results telegram accepts for an inline query
+const MAX_RESULTS: usize = 50;
+
+/// A point on earth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    /// Latitude in degrees
+    pub latitude: f64,
+    /// Longitude in degrees
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// The great-circle distance to the other point in meters
+    pub fn distance_to(&self, other: &GeoPoint) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().asin()
+    }
+}
+
+impl From<&Location> for GeoPoint {
+    fn from(location: &Location) -> Self {
+        Self::new(location.latitude, location.longitude)
+    }
+}
+
+/// Renders a distance in meters for use in result descriptions, e.g. "350 m",
+/// "2.4 km" or "18 km", using the number format of the given locale
+pub fn format_distance(meters: f64, locale: &LocaleFormat) -> String {
+    let meters = meters.max(0.0);
+    if meters < 1000.0 {
+        format!("{} m", locale.format_decimal(meters, 0))
+    } else if meters < 10_000.0 {
+        format!("{} km", locale.format_decimal(meters / 1000.0, 1))
+    } else {
+        format!("{} km", locale.format_decimal(meters / 1000.0, 0))
+    }
+}
+
+/// A result of a [`GeoResultProvider`], together with the place it is about
+#[derive(Debug, Clone)]
+pub struct GeoResult {
+    /// Where the result is located
+    pub point: GeoPoint,
+    /// The result to show to the user
+    pub result: InlineQueryResult,
+}
+
+/// Provides results for inline queries which depend on where the user is, like
+/// nearby places.
+///
+/// Telegram only sends the location of the user if the bot requested it using
+/// the `/setinlinegeo` command of `BotFather`, and the user agreed to share it.
+///
+/// ## Example
+/// ```rust,no_run
+/// use telexide::{
+///     api::types::{InlineQueryResult, InlineQueryResultVenue},
+///     helpers::{
+///         answer_geo_query,
+///         format_distance,
+///         GeoPoint,
+///         GeoResult,
+///         GeoResultProvider,
+///         LocaleFormat,
+///     },
+///     model::InlineQuery,
+/// };
+///
+/// struct Cafes(Vec<(GeoPoint, InlineQueryResultVenue)>);
+///
+/// #[async_trait::async_trait]
+/// impl GeoResultProvider for Cafes {
+///     async fn results(
+///         &self,
+///         query: &InlineQuery,
+///         origin: Option<GeoPoint>,
+///     ) -> telexide::Result<Vec<GeoResult>> {
+///         let locale = LocaleFormat::for_user(&query.from);
+///         Ok(self
+///             .0
+///             .iter()
+///             .map(|(point, venue)| {
+///                 let mut venue = venue.clone();
+///                 if let Some(origin) = origin {
+///                     venue.address = format_distance(origin.distance_to(point), &locale);
+///                 }
+///                 GeoResult {
+///                     point: *point,
+///                     result: InlineQueryResult::Venue(venue),
+///                 }
+///             })
+///             .collect())
+///     }
+/// }
+///
+/// # async fn run(cafes: Cafes, query: InlineQuery) -> telexide::Result<()> {
+/// let answer = answer_geo_query(&cafes, &query).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait GeoResultProvider: Send + Sync {
+    /// Returns the results for the query. `origin` is the location of the
+    /// user, if they shared it.
+    async fn results(
+        &self,
+        query: &InlineQuery,
+        origin: Option<GeoPoint>,
+    ) -> Result<Vec<GeoResult>>;
+}
+
+/// Builds the answer to an inline query from the results of the provider.
+///
+/// If the user shared their location the results are sorted by their distance
+/// to the user, nearest first, and the answer is marked as personal. At most
+/// 50 results are included.
+pub async fn answer_geo_query<P: GeoResultProvider + ?Sized>(
+    provider: &P,
+    query: &InlineQuery,
+) -> Result<AnswerInlineQuery> {
+    let origin = query.location.as_ref().map(GeoPoint::from);
+    let mut results = provider.results(query, origin).await?;
+
+    if let Some(origin) = origin {
+        results.sort_by(|a, b| {
+            origin
+                .distance_to(&a.point)
+                .total_cmp(&origin.distance_to(&b.point))
+        });
+    }
+    results.truncate(MAX_RESULTS);
+
+    Ok(AnswerInlineQuery {
+        inline_query_id: query.id.clone(),
+        results: results.into_iter().map(|r| r.result).collect(),
+        cache_time: None,
+        is_personal: origin.is_some(),
+        next_offset: None,
+        switch_pm_text: None,
+        switch_pm_parameter: None,
+    })
+}
@@ -0,0 +1,365 @@
+use super::LocaleFormat;
+use crate::{
+    api::{
+        types::{AnswerCallbackQuery, SendMessage},
+        API,
+    },
+    client::Context,
+    framework::types::{CommandOptions, CommandResult, CommandTypes, TelegramCommand},
+    model::{
+        CallbackQuery,
+        Chat,
+        InlineKeyboardButton,
+        InlineKeyboardMarkup,
+        Message,
+        ReplyMarkup,
+        User,
+    },
+    storage::Storage,
+    utils::result::{Result, TelegramError},
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// The prefix of the callback data of the language buttons
+const CALLBACK_PREFIX: &str = "language:";
+
+/// The key of the message sent by the [`LANGUAGE_COMMAND`]
+const CHOOSE_KEY: &str = "language.choose";
+
+/// The key of the answer to pressing a language button
+const CHANGED_KEY: &str = "language.changed";
+
+/// A language the bot has translations for
+#[derive(Debug, Clone, PartialEq)]
+struct Locale {
+    code: String,
+    label: String,
+    messages: HashMap<String, String>,
+    format: LocaleFormat,
+}
+
+/// Translations of the messages of the bot, together with the languages chats
+/// and users chose with the [`LANGUAGE_COMMAND`].
+///
+/// The language used for a message is, in order of precedence:
+/// 1. the language the user chose in a private chat with the bot
+/// 2. the language chosen for the chat the message is in
+/// 3. the language of the telegram client of the user, if translated
+/// 4. the default language
+///
+/// Messages missing in a language fall back to the default language, and to
+/// their key if they're missing there too. The chosen languages are persisted
+/// in the provided [`Storage`]. Numbers and dates can be rendered in the
+/// chosen language with the [`LocaleFormat`] of [`I18n::locale_format_for`].
+///
+/// To use [`Context::t`] and the [`LANGUAGE_COMMAND`], insert the translations
+/// into the data of the client. The presses of the language buttons have to be
+/// passed to [`I18n::handle_callback_query`]:
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use telexide::{
+///     api::types::SendMessage,
+///     framework::Framework,
+///     helpers::{I18n, LANGUAGE_COMMAND},
+///     model::CallbackQuery,
+///     prelude::*,
+///     storage::MemoryStorage,
+/// };
+///
+/// # fn register(client: &mut Client, framework: &mut Framework) {
+/// let mut i18n = I18n::new(Arc::new(MemoryStorage::new()));
+/// i18n.add_locale("en", "English", [("greeting", "Hello!")])
+///     .add_locale("nl", "Nederlands", [("greeting", "Hallo!")]);
+/// client.data.write().insert::<I18n>(Arc::new(i18n));
+/// framework.add_command(&LANGUAGE_COMMAND);
+/// # }
+///
+/// # async fn on_callback(ctx: Context, query: CallbackQuery) -> telexide::Result<()> {
+/// let i18n = ctx.data.read().get::<I18n>().cloned();
+/// if let Some(i18n) = i18n {
+///     i18n.handle_callback_query(&**ctx.api, &query).await?;
+/// }
+/// # Ok(())
+/// # }
+///
+/// # async fn greet(ctx: Context, message: Message) -> telexide::Result<()> {
+/// let greeting = ctx.t(&message, "greeting").await?;
+/// ctx.api.send_message(SendMessage::new(message.chat.get_id(), &greeting)).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Context::t`]: ../client/struct.Context.html#method.t
+pub struct I18n {
+    storage: Arc<dyn Storage>,
+    locales: Vec<Locale>,
+}
+
+impl typemap::Key for I18n {
+    type Value = Arc<I18n>;
+}
+
+impl I18n {
+    /// Creates translations without any languages, persisting the chosen
+    /// languages in the given storage
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            locales: Vec::new(),
+        }
+    }
+
+    /// Adds a language with the given code (e.g. "en" or "pt-br"), the label
+    /// shown on its button and its messages by key. The first added language
+    /// is the default one.
+    pub fn add_locale<'a, I>(&mut self, code: &str, label: &str, messages: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        self.locales.push(Locale {
+            code: code.to_lowercase(),
+            label: label.to_owned(),
+            messages: messages
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                .collect(),
+            format: LocaleFormat::for_language(code),
+        });
+        self
+    }
+
+    /// Sets the conventions for rendering numbers and dates in the language,
+    /// instead of the ones [`LocaleFormat::for_language`] returns for its code.
+    /// Languages which weren't added are ignored.
+    pub fn set_locale_format(&mut self, code: &str, format: LocaleFormat) -> &mut Self {
+        let code = code.to_lowercase();
+        if let Some(locale) = self.locales.iter_mut().find(|l| l.code == code) {
+            locale.format = format;
+        }
+        self
+    }
+
+    /// The codes of the languages, the default one first
+    pub fn locales(&self) -> Vec<&str> {
+        self.locales.iter().map(|l| l.code.as_str()).collect()
+    }
+
+    fn locale(&self, code: &str) -> Option<&Locale> {
+        let code = code.to_lowercase();
+        self.locales.iter().find(|l| l.code == code).or_else(|| {
+            // "de-at" is still german
+            let language = code.split(['-', '_']).next()?;
+            self.locales.iter().find(|l| l.code == language)
+        })
+    }
+
+    fn lookup(&self, code: &str, key: &str) -> Option<&str> {
+        self.locale(code)
+            .and_then(|l| l.messages.get(key))
+            .or_else(|| self.locales.first()?.messages.get(key))
+            .map(String::as_str)
+    }
+
+    /// Translates the message with the given key into the language, falling
+    /// back to the default language and then to the key itself
+    pub fn translate(&self, code: &str, key: &str) -> String {
+        self.lookup(code, key).unwrap_or(key).to_owned()
+    }
+
+    /// The conventions for rendering numbers and dates in the language,
+    /// falling back to the default language like [`I18n::translate`]
+    pub fn locale_format(&self, code: &str) -> LocaleFormat {
+        self.locale(code)
+            .or_else(|| self.locales.first())
+            .map_or_else(|| LocaleFormat::for_language(code), |l| l.format.clone())
+    }
+
+    fn chat_key(chat_id: i64) -> String {
+        format!("i18n:chat:{chat_id}")
+    }
+
+    fn user_key(user_id: i64) -> String {
+        format!("i18n:user:{user_id}")
+    }
+
+    fn ensure_known(&self, code: &str) -> Result<String> {
+        self.locale(code).map(|l| l.code.clone()).ok_or_else(|| {
+            TelegramError::InvalidArgument(format!("there are no translations for {code}")).into()
+        })
+    }
+
+    /// Sets the language used for everyone in the chat
+    pub async fn set_chat_language(&self, chat_id: i64, code: &str) -> Result<()> {
+        let code = self.ensure_known(code)?;
+        self.storage.set_as(&Self::chat_key(chat_id), &code).await
+    }
+
+    /// Sets the language used for the user, which takes precedence over the
+    /// language of the chat
+    pub async fn set_user_language(&self, user_id: i64, code: &str) -> Result<()> {
+        let code = self.ensure_known(code)?;
+        self.storage.set_as(&Self::user_key(user_id), &code).await
+    }
+
+    /// Removes the language chosen for the chat
+    pub async fn clear_chat_language(&self, chat_id: i64) -> Result<()> {
+        self.storage.remove(&Self::chat_key(chat_id)).await
+    }
+
+    /// Removes the language chosen by the user
+    pub async fn clear_user_language(&self, user_id: i64) -> Result<()> {
+        self.storage.remove(&Self::user_key(user_id)).await
+    }
+
+    /// Resolves the language to use for the user in the chat, see the
+    /// [struct-level documentation](I18n) for the order of precedence
+    pub async fn resolve(&self, chat_id: Option<i64>, user: Option<&User>) -> Result<String> {
+        if let Some(user) = user {
+            if let Some(code) = self.stored(&Self::user_key(user.id)).await? {
+                return Ok(code);
+            }
+        }
+        if let Some(chat_id) = chat_id {
+            if let Some(code) = self.stored(&Self::chat_key(chat_id)).await? {
+                return Ok(code);
+            }
+        }
+
+        let client_language = user
+            .and_then(|u| u.language_code.as_deref())
+            .and_then(|c| self.locale(c));
+        Ok(client_language
+            .or_else(|| self.locales.first())
+            .map(|l| l.code.clone())
+            .unwrap_or_default())
+    }
+
+    /// gets a stored language, ignoring ones which are no longer translated
+    async fn stored(&self, key: &str) -> Result<Option<String>> {
+        let code: Option<String> = self.storage.get_as(key).await?;
+        Ok(code.filter(|c| self.locale(c).is_some()))
+    }
+
+    /// Translates the message with the given key into the language of the
+    /// sender of the message
+    pub async fn translate_for(&self, message: &Message, key: &str) -> Result<String> {
+        let code = self
+            .resolve(Some(message.chat.get_id()), message.from.as_ref())
+            .await?;
+        Ok(self.translate(&code, key))
+    }
+
+    /// The conventions for rendering numbers and dates in the language of the
+    /// sender of the message
+    pub async fn locale_format_for(&self, message: &Message) -> Result<LocaleFormat> {
+        let code = self
+            .resolve(Some(message.chat.get_id()), message.from.as_ref())
+            .await?;
+        Ok(self.locale_format(&code))
+    }
+
+    /// An inline keyboard with a button for every language, marking the
+    /// current one
+    pub fn keyboard(&self, current: &str) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup {
+            inline_keyboard: self
+                .locales
+                .chunks(2)
+                .map(|row| {
+                    row.iter()
+                        .map(|l| InlineKeyboardButton {
+                            text: if l.code == current {
+                                format!("✓ {}", l.label)
+                            } else {
+                                l.label.clone()
+                            },
+                            url: None,
+                            login_url: None,
+                            callback_data: Some(format!("{CALLBACK_PREFIX}{}", l.code)),
+                            switch_inline_query: None,
+                            switch_inline_query_current_chat: None,
+                            callback_game: None,
+                            pay: false,
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Stores the chosen language if the query comes from a language button
+    /// and answers the query, returns whether it was a language button.
+    ///
+    /// In private chats the language is chosen for the user, in other chats
+    /// for the whole chat.
+    pub async fn handle_callback_query(
+        &self,
+        api: &dyn API,
+        query: &CallbackQuery,
+    ) -> Result<bool> {
+        let Some(code) = query
+            .data
+            .as_deref()
+            .and_then(|d| d.strip_prefix(CALLBACK_PREFIX))
+        else {
+            return Ok(false);
+        };
+
+        match query.message.as_ref().map(|m| &m.chat) {
+            Some(Chat::Private(_)) | None => self.set_user_language(query.from.id, code).await?,
+            Some(chat) => self.set_chat_language(chat.get_id(), code).await?,
+        }
+
+        let label = self.locale(code).map_or(code, |l| l.label.as_str());
+        let text = self
+            .lookup(code, CHANGED_KEY)
+            .map_or_else(|| label.to_owned(), |t| t.replace("{language}", label));
+        api.answer_callback_query(AnswerCallbackQuery {
+            callback_query_id: query.id.clone(),
+            text: Some(text),
+            show_alert: false,
+            url: None,
+            cache_time: None,
+        })
+        .await?;
+        Ok(true)
+    }
+}
+
+static LANGUAGE_COMMAND_OPTIONS: CommandOptions =
+    CommandOptions::new("language", "Choose the language of the bot");
+
+/// A ready-made `/language` command, replying with the keyboard of the
+/// languages of the [`I18n`] inserted into the data of the client. Its message
+/// is translated from the "language.choose" key.
+pub static LANGUAGE_COMMAND: TelegramCommand = TelegramCommand {
+    options: &LANGUAGE_COMMAND_OPTIONS,
+    command: CommandTypes::Default(language_command),
+};
+
+fn language_command(
+    ctx: Context,
+    message: Message,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = CommandResult> + Send>> {
+    Box::pin(async move {
+        let i18n = ctx
+            .data
+            .read()
+            .get::<I18n>()
+            .cloned()
+            .ok_or("no I18n was inserted into the data")?;
+
+        let code = i18n
+            .resolve(Some(message.chat.get_id()), message.from.as_ref())
+            .await?;
+        let text = i18n
+            .lookup(&code, CHOOSE_KEY)
+            .unwrap_or("Choose a language");
+
+        let mut reply = SendMessage::new(message.chat.get_id(), text);
+        reply.set_reply_markup(&ReplyMarkup::InlineKeyboardMarkup(i18n.keyboard(&code)));
+        ctx.api.send_message(reply).await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,148 @@
+use crate::{api::types::AnswerInlineQuery, model::InlineQuery, utils::result::Result};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// The default time telegram caches inline results for, when no `cache_time`
+/// is given
+const DEFAULT_CACHE_TIME: i64 = 300;
+
+/// The key results are cached under in an [`InlineCache`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InlineCacheKey {
+    /// The normalized text of the query
+    pub query: String,
+    /// The offset of the results
+    pub offset: String,
+    /// The user the results are cached for, if they are personal
+    pub user_id: Option<i64>,
+}
+
+impl InlineCacheKey {
+    /// Creates a key for the given query, `personal` decides whether the key
+    /// is bound to the user who sent the query
+    pub fn new(query: &InlineQuery, personal: bool) -> Self {
+        Self {
+            query: Self::normalize(&query.query),
+            offset: query.offset.clone(),
+            user_id: if personal { Some(query.from.id) } else { None },
+        }
+    }
+
+    /// Normalizes query text by lowercasing it and collapsing all whitespace,
+    /// so that "Cat  pics" and "cat pics " share their results
+    pub fn normalize(query: &str) -> String {
+        query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+struct CacheEntry {
+    answer: AnswerInlineQuery,
+    expires_at: Instant,
+}
+
+/// A cache for built inline query answers, so that expensive backends don't
+/// get hit for every keystroke as users type their queries.
+///
+/// Answers are cached for as long as their `cache_time` says telegram may
+/// cache them, and answers marked as `is_personal` are only returned to the
+/// user they were built for.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use telexide::api::{types::AnswerInlineQuery, API};
+/// # async fn search(query: &str) -> telexide::Result<AnswerInlineQuery> { unimplemented!() }
+/// use telexide::{client::Context, helpers::InlineCache, model::InlineQuery};
+///
+/// async fn on_inline_query(
+///     ctx: &Context,
+///     cache: &InlineCache,
+///     query: &InlineQuery,
+/// ) -> telexide::Result<()> {
+///     let answer = cache.get_or_insert_with(query, || search(&query.query)).await?;
+///     ctx.api.answer_inline_query(answer).await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct InlineCache {
+    entries: Mutex<HashMap<InlineCacheKey, CacheEntry>>,
+}
+
+impl InlineCache {
+    /// Creates a new, empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the cached answer for the query, if there is one that hasn't
+    /// expired yet. Personal answers for the sender take precedence over
+    /// shared answers. The `inline_query_id` of the returned answer is set to
+    /// the id of the given query.
+    pub fn get(&self, query: &InlineQuery) -> Option<AnswerInlineQuery> {
+        let now = Instant::now();
+        let entries = self.entries.lock();
+
+        [true, false]
+            .iter()
+            .filter_map(|personal| entries.get(&InlineCacheKey::new(query, *personal)))
+            .find(|entry| entry.expires_at > now)
+            .map(|entry| AnswerInlineQuery {
+                inline_query_id: query.id.clone(),
+                ..entry.answer.clone()
+            })
+    }
+
+    /// Caches the answer for the given query. It expires after its
+    /// `cache_time`, or 300 seconds if none is set, and is bound to the
+    /// sender if it is marked as `is_personal`.
+    pub fn insert(&self, query: &InlineQuery, answer: &AnswerInlineQuery) {
+        let cache_time =
+            u64::try_from(answer.cache_time.unwrap_or(DEFAULT_CACHE_TIME)).unwrap_or(0);
+        let now = Instant::now();
+        let entry = CacheEntry {
+            answer: answer.clone(),
+            expires_at: now + Duration::from_secs(cache_time),
+        };
+
+        let mut entries = self.entries.lock();
+        entries.retain(|_, e| e.expires_at > now);
+        entries.insert(InlineCacheKey::new(query, answer.is_personal), entry);
+    }
+
+    /// Gets the cached answer for the query, or builds it using the given
+    /// function and caches it if there is none
+    pub async fn get_or_insert_with<F, Fut>(
+        &self,
+        query: &InlineQuery,
+        build: F,
+    ) -> Result<AnswerInlineQuery>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<AnswerInlineQuery>>,
+    {
+        if let Some(answer) = self.get(query) {
+            return Ok(answer);
+        }
+
+        let answer = build().await?;
+        self.insert(query, &answer);
+        Ok(AnswerInlineQuery {
+            inline_query_id: query.id.clone(),
+            ..answer
+        })
+    }
+
+    /// Removes all cached answers
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
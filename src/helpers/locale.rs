@@ -0,0 +1,225 @@
+use crate::model::User;
+use chrono::{DateTime, Datelike, TimeZone};
+use std::fmt::Display;
+
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const DE_MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+const FR_MONTHS: [&str; 12] = [
+    "janvier",
+    "février",
+    "mars",
+    "avril",
+    "mai",
+    "juin",
+    "juillet",
+    "août",
+    "septembre",
+    "octobre",
+    "novembre",
+    "décembre",
+];
+
+const ES_MONTHS: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+
+const RU_MONTHS: [&str; 12] = [
+    "января",
+    "февраля",
+    "марта",
+    "апреля",
+    "мая",
+    "июня",
+    "июля",
+    "августа",
+    "сентября",
+    "октября",
+    "ноября",
+    "декабря",
+];
+
+/// The conventions used for rendering numbers and dates for a language, so
+/// messages with statistics or schedules read naturally to the user receiving
+/// them.
+///
+/// Date and time formats use the [`chrono` format syntax][fmt], with `%B`
+/// being replaced by the localized month name.
+///
+/// The languages of an [`I18n`] get the conventions of their code, which
+/// [`I18n::set_locale_format`] overrides. [`Context::locale_format`] returns
+/// the ones for the language of the sender of a message.
+///
+/// [fmt]: https://docs.rs/chrono/0.4/chrono/format/strftime/index.html
+/// [`I18n`]: struct.I18n.html
+/// [`I18n::set_locale_format`]: struct.I18n.html#method.set_locale_format
+/// [`Context::locale_format`]: ../client/struct.Context.html#method.locale_format
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocaleFormat {
+    /// The language code these conventions are for
+    pub language_code: String,
+    /// The separator between the integer and fractional part of a number
+    pub decimal_separator: char,
+    /// The separator between groups of thousands
+    pub group_separator: char,
+    /// The format used for rendering dates
+    pub date_format: String,
+    /// The format used for rendering times
+    pub time_format: String,
+    /// The month names, starting with January
+    pub month_names: [&'static str; 12],
+}
+
+impl LocaleFormat {
+    /// Returns the conventions for the given IETF language tag (for example
+    /// "de" or "en-US"), falling back to english for unknown languages
+    pub fn for_language(code: &str) -> Self {
+        let language = code
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let (code, decimal_separator, group_separator, date_format, time_format, month_names) =
+            match language.as_str() {
+                "de" => ("de", ',', '.', "%-d. %B %Y", "%H:%M", DE_MONTHS),
+                "fr" => ("fr", ',', '\u{202f}', "%-d %B %Y", "%H:%M", FR_MONTHS),
+                "es" => ("es", ',', '.', "%-d de %B de %Y", "%H:%M", ES_MONTHS),
+                "ru" => ("ru", ',', '\u{a0}', "%-d %B %Y", "%H:%M", RU_MONTHS),
+                _ => ("en", '.', ',', "%B %-d, %Y", "%-I:%M %p", EN_MONTHS),
+            };
+
+        Self {
+            language_code: code.to_owned(),
+            decimal_separator,
+            group_separator,
+            date_format: date_format.to_owned(),
+            time_format: time_format.to_owned(),
+            month_names,
+        }
+    }
+
+    /// Returns the conventions for the language of the given [`User`], or
+    /// english if their language is unknown
+    pub fn for_user(user: &User) -> Self {
+        Self::for_language(user.language_code.as_deref().unwrap_or("en"))
+    }
+
+    /// Renders an integer with the group separators of this locale, e.g.
+    /// `1234567` as "1,234,567"
+    pub fn format_integer(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let mut res = self.group_digits(&digits);
+        if value < 0 {
+            res.insert(0, '-');
+        }
+        res
+    }
+
+    /// Renders a number rounded to the given amount of decimals, using the
+    /// separators of this locale
+    pub fn format_decimal(&self, value: f64, decimals: usize) -> String {
+        let rendered = format!("{:.*}", decimals, value.abs());
+        let (int_part, fraction) = match rendered.split_once('.') {
+            Some((i, f)) => (i, Some(f)),
+            None => (rendered.as_str(), None),
+        };
+
+        let mut res = self.group_digits(int_part);
+        if let Some(fraction) = fraction {
+            res.push(self.decimal_separator);
+            res.push_str(fraction);
+        }
+        if value.is_sign_negative() && rendered.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            res.insert(0, '-');
+        }
+        res
+    }
+
+    /// Renders the date part of the given datetime
+    pub fn format_date<Tz: TimeZone>(&self, date: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        self.render(date, &self.date_format)
+    }
+
+    /// Renders the time part of the given datetime
+    pub fn format_time<Tz: TimeZone>(&self, date: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        self.render(date, &self.time_format)
+    }
+
+    /// Renders both the date and time of the given datetime
+    pub fn format_datetime<Tz: TimeZone>(&self, date: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        format!("{} {}", self.format_date(date), self.format_time(date))
+    }
+
+    fn render<Tz: TimeZone>(&self, date: &DateTime<Tz>, pattern: &str) -> String
+    where
+        Tz::Offset: Display,
+    {
+        let month = self.month_names[date.month0() as usize];
+        date.format(&pattern.replace("%B", month)).to_string()
+    }
+
+    fn group_digits(&self, digits: &str) -> String {
+        let mut res = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                res.push(self.group_separator);
+            }
+            res.push(c);
+        }
+        res
+    }
+}
+
+impl Default for LocaleFormat {
+    fn default() -> Self {
+        Self::for_language("en")
+    }
+}
@@ -0,0 +1,46 @@
+//! Standalone helpers for common bot tasks, built on top of the [`api`] and
+//! [`model`] modules.
+//!
+//! Nothing in here is used by the [`Client`] automatically, use whichever
+//! helpers are useful for your bot from within your handlers.
+//!
+//! [`api`]: ../api/index.html
+//! [`model`]: ../model/index.html
+//! [`Client`]: ../client/struct.Client.html
+
+mod admin_cache;
+pub mod deep_links;
+mod experiments;
+mod file_vault;
+pub mod formatting;
+mod geo;
+mod i18n;
+mod inline_cache;
+mod locale;
+mod raffle;
+#[cfg(feature = "stickers")]
+mod sticker_export;
+mod voting;
+
+pub use admin_cache::AdminCache;
+pub use experiments::{Experiments, SentVariant};
+pub use file_vault::{FileVault, StoredFile, StoredFileKind};
+pub use geo::{answer_geo_query, format_distance, GeoPoint, GeoResult, GeoResultProvider};
+pub use i18n::{I18n, LANGUAGE_COMMAND};
+pub use inline_cache::{InlineCache, InlineCacheKey};
+pub use locale::LocaleFormat;
+pub use raffle::{draw_winners, Raffle, RaffleEntry, RaffleHandle, RaffleResult, Raffles};
+#[cfg(feature = "stickers")]
+pub use sticker_export::{export_sticker_set, sticker_set_files, StickerFile};
+pub use voting::{
+    Majority,
+    Quorum,
+    Vote,
+    VoteHandle,
+    VoteOutcome,
+    VoteRules,
+    VoteStyle,
+    VoteTally,
+    VoterEligibility,
+    Voting,
+};
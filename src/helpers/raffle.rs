@@ -0,0 +1,727 @@
+use crate::{
+    api::{
+        types::{AnswerCallbackQuery, EditMessageReplyMarkup, SendMessage},
+        API,
+    },
+    helpers::formatting::{compose, mention, text, FormattedText, Fragment},
+    model::{
+        CallbackQuery,
+        InlineKeyboardButton,
+        InlineKeyboardMarkup,
+        MessageReactionUpdated,
+        ReactionType,
+        ReplyMarkup,
+        User,
+    },
+    redaction,
+    runtime::{Runtime, TokioRuntime},
+    storage::Storage,
+    utils::result::{Error, Result, TelegramError},
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::oneshot;
+
+/// The callback data of the join button
+const JOIN_DATA: &str = "raffle:join";
+
+/// The storage key listing the ids of the raffles that are still running
+const PENDING_KEY: &str = "raffle:pending";
+
+/// How long to wait before trying to close a raffle again after it failed
+const CLOSE_RETRY: Duration = Duration::from_secs(60);
+
+/// How often closing a raffle is tried before giving up on it
+const CLOSE_ATTEMPTS: u32 = 5;
+
+/// How users enter a [`Raffle`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RaffleEntry {
+    /// Pressing an inline keyboard button with the given label under the
+    /// announcement
+    Button(String),
+    /// Reacting to the announcement with the given emoji, removing the
+    /// reaction withdraws the entry
+    Reaction(String),
+}
+
+/// The settings of a raffle to start with [`Raffles::start`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raffle {
+    chat_id: i64,
+    announcement: FormattedText,
+    winners: usize,
+    entry: RaffleEntry,
+    window: Duration,
+    seed: Option<u64>,
+    excluded: HashSet<i64>,
+    announce_results: bool,
+}
+
+impl Raffle {
+    /// Creates a raffle in the given chat drawing the given amount of winners,
+    /// entered with a "Join" button for 10 minutes
+    pub fn new(chat_id: i64, announcement: &str, winners: usize) -> Self {
+        Self {
+            chat_id,
+            announcement: compose([text(announcement)]),
+            winners,
+            entry: RaffleEntry::Button("Join".to_owned()),
+            window: Duration::from_secs(10 * 60),
+            seed: None,
+            excluded: HashSet::new(),
+            announce_results: true,
+        }
+    }
+
+    /// Sets the announcement to a formatted text
+    #[must_use]
+    pub fn set_formatted_announcement(mut self, announcement: &FormattedText) -> Self {
+        self.announcement = announcement.clone();
+        self
+    }
+
+    /// Sets how users enter the raffle
+    #[must_use]
+    pub fn set_entry(mut self, entry: RaffleEntry) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    /// Sets how long users can enter the raffle
+    #[must_use]
+    pub fn set_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets the seed of the draw, by default it's derived from the current
+    /// time. The seed is part of the [`RaffleResult`] either way.
+    #[must_use]
+    pub fn set_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Doesn't let the given users enter, e.g. the organisers
+    #[must_use]
+    pub fn exclude_users<I: IntoIterator<Item = i64>>(mut self, users: I) -> Self {
+        self.excluded.extend(users);
+        self
+    }
+
+    /// Sets whether the winners are announced in the chat when the raffle
+    /// closes, defaults to true
+    #[must_use]
+    pub fn set_announce_results(mut self, announce: bool) -> Self {
+        self.announce_results = announce;
+        self
+    }
+}
+
+/// The outcome of a closed [`Raffle`], stored by [`Raffles`] so draws can be
+/// audited later
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RaffleResult {
+    /// The id of the raffle
+    pub id: String,
+    /// The chat the raffle ran in
+    pub chat_id: i64,
+    /// The id of the announcement
+    pub message_id: i64,
+    /// The seed the winners were drawn with
+    pub seed: u64,
+    /// Every user who entered, ordered by their id
+    pub participants: Vec<User>,
+    /// The winners, in the order they were drawn
+    pub winners: Vec<User>,
+}
+
+impl RaffleResult {
+    /// Draws the winners from the participants again using the seed, returning
+    /// whether the same winners come out
+    pub fn verify(&self) -> bool {
+        let winners = draw_winners(&self.participants, self.winners.len(), self.seed);
+        winners
+            .iter()
+            .map(|u| u.id)
+            .eq(self.winners.iter().map(|u| u.id))
+    }
+}
+
+/// Draws `count` distinct winners from the entrants, or all of them if there
+/// are fewer. The same entrants in the same order and the same seed always
+/// result in the same winners, so draws can be reproduced.
+pub fn draw_winners<T: Clone>(entrants: &[T], count: usize, seed: u64) -> Vec<T> {
+    let mut rng = SplitMix64(seed);
+    let mut pool = entrants.to_vec();
+    let count = count.min(pool.len());
+    // a partial Fisher-Yates shuffle, the first `count` entrants are the
+    // winners
+    for i in 0..count {
+        let j = i + rng.below(pool.len() - i);
+        pool.swap(i, j);
+    }
+    pool.truncate(count);
+    pool
+}
+
+/// A small, fixed pseudo random generator, so draws stay reproducible across
+/// versions of telexide and its dependencies
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// a number below `n`, rejecting the values that would make lower
+    /// numbers more likely
+    #[allow(clippy::cast_possible_truncation)]
+    fn below(&mut self, n: usize) -> usize {
+        let n = n as u64;
+        let zone = u64::MAX - (u64::MAX % n);
+        loop {
+            let value = self.next();
+            if value < zone {
+                return (value % n) as usize;
+            }
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// The state of a running raffle, persisted so it survives restarts
+#[derive(Serialize, Deserialize, Clone)]
+struct PendingRaffle {
+    chat_id: i64,
+    message_id: i64,
+    winners: usize,
+    entry: RaffleEntry,
+    seed: u64,
+    excluded: HashSet<i64>,
+    announce_results: bool,
+    /// when the window closes, in seconds since the unix epoch
+    closes_at: u64,
+    entrants: BTreeMap<i64, User>,
+}
+
+impl PendingRaffle {
+    fn may_enter(&self, user: &User) -> bool {
+        !user.is_bot && !self.excluded.contains(&user.id)
+    }
+
+    fn closes_in(&self) -> Duration {
+        let closes_at = UNIX_EPOCH + Duration::from_secs(self.closes_at);
+        closes_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+}
+
+struct ActiveRaffle {
+    pending: PendingRaffle,
+    /// whether the raffle is being closed, no one can enter in the meantime
+    closing: bool,
+    result: oneshot::Sender<Result<RaffleResult>>,
+}
+
+/// A handle to a running raffle, used to wait for its result
+pub struct RaffleHandle {
+    id: String,
+    message_id: i64,
+    result: oneshot::Receiver<Result<RaffleResult>>,
+}
+
+impl RaffleHandle {
+    /// The id of the raffle, which can be passed to [`Raffles::close`]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The id of the announcement of the raffle
+    pub fn message_id(&self) -> i64 {
+        self.message_id
+    }
+
+    /// Waits until the raffle is closed and returns its result
+    pub async fn result(self) -> Result<RaffleResult> {
+        self.result.await.unwrap_or_else(|_| {
+            Err(TelegramError::Unknown("the raffle was dropped before closing".to_owned()).into())
+        })
+    }
+}
+
+/// Runs raffles in chats: announces them, collects the users entering until
+/// the window closes, draws the winners and announces them.
+///
+/// Callback queries and reaction updates have to be passed on to
+/// [`handle_callback_query`] and [`handle_reaction`] from your handlers for
+/// users to enter. Every user can only enter once, bots and excluded users
+/// can't enter at all. The results are persisted in the provided [`Storage`]
+/// and can be looked up with [`result`], and checked with
+/// [`RaffleResult::verify`]. Running raffles are persisted as well, call
+/// [`restore`] at startup to pick them up again after a restart.
+///
+/// Reaction updates are only sent to bots that are an administrator in the
+/// chat and explicitly list `message_reaction` in their allowed updates.
+///
+/// Raffles are closed on the tokio runtime by default, use [`set_runtime`] to
+/// close them on another one. When closing a raffle fails it's tried again
+/// every minute and given up on after five attempts, resolving its
+/// [`RaffleHandle`] with the error.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// use telexide::helpers::{Raffle, RaffleEntry, Raffles};
+///
+/// async fn giveaway(raffles: &Raffles, chat_id: i64) -> telexide::Result<()> {
+///     let raffle = Raffle::new(chat_id, "React with 🎉 to win a sticker pack!", 3)
+///         .set_entry(RaffleEntry::Reaction("🎉".to_owned()))
+///         .set_window(Duration::from_secs(60 * 60));
+///     let handle = raffles.start(raffle).await?;
+///
+///     let result = handle.result().await?;
+///     log::info!("drew {} winners with seed {}", result.winners.len(), result.seed);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`handle_callback_query`]: Raffles::handle_callback_query
+/// [`handle_reaction`]: Raffles::handle_reaction
+/// [`result`]: Raffles::result
+/// [`restore`]: Raffles::restore
+/// [`set_runtime`]: Raffles::set_runtime
+#[derive(Clone)]
+pub struct Raffles {
+    api: Arc<Box<dyn API + Send>>,
+    storage: Arc<dyn Storage>,
+    active: Arc<Mutex<HashMap<String, ActiveRaffle>>>,
+    runtime: Arc<dyn Runtime>,
+}
+
+impl typemap::Key for Raffles {
+    type Value = Raffles;
+}
+
+impl Raffles {
+    /// Creates a new raffle helper sending its messages with the given api
+    /// client and storing the results in the given storage
+    pub fn new(api: Arc<Box<dyn API + Send>>, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            api,
+            storage,
+            active: Arc::new(Mutex::new(HashMap::new())),
+            runtime: Arc::new(TokioRuntime),
+        }
+    }
+
+    /// Sets the runtime the raffles are closed on once their window passed
+    #[must_use]
+    pub fn set_runtime(mut self, runtime: Arc<dyn Runtime>) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    fn key(id: &str) -> String {
+        format!("raffle:{id}")
+    }
+
+    fn pending_key(id: &str) -> String {
+        format!("raffle:pending:{id}")
+    }
+
+    /// Announces the raffle and closes it once its window has passed
+    pub async fn start(&self, raffle: Raffle) -> Result<RaffleHandle> {
+        if raffle.winners == 0 {
+            return Err(TelegramError::InvalidArgument(
+                "a raffle needs at least one winner".to_owned(),
+            )
+            .into());
+        }
+
+        let mut announcement = SendMessage::new(raffle.chat_id, "");
+        announcement.set_formatted_text(&raffle.announcement);
+        if let RaffleEntry::Button(label) = &raffle.entry {
+            let keyboard = ReplyMarkup::InlineKeyboardMarkup(join_keyboard(label));
+            announcement.set_reply_markup(&keyboard);
+        }
+        let message = self.api.send_message(announcement).await?;
+
+        let closes_at = SystemTime::now() + raffle.window;
+        let pending = PendingRaffle {
+            chat_id: raffle.chat_id,
+            message_id: message.message_id,
+            winners: raffle.winners,
+            entry: raffle.entry,
+            seed: raffle.seed.unwrap_or_else(time_seed),
+            excluded: raffle.excluded,
+            announce_results: raffle.announce_results,
+            closes_at: closes_at
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            entrants: BTreeMap::new(),
+        };
+        let id = format!("{}:{}", pending.chat_id, pending.message_id);
+        self.storage
+            .set_as(&Self::pending_key(&id), &pending)
+            .await?;
+        self.activate(id, pending).await
+    }
+
+    /// Picks up the raffles that were still running when the bot stopped,
+    /// closing them once their window has passed or right away if it already
+    /// did. Call this once at startup, before handling updates.
+    pub async fn restore(&self) -> Result<Vec<RaffleHandle>> {
+        let ids: Vec<String> = self.storage.get_as(PENDING_KEY).await?.unwrap_or_default();
+        let mut handles = Vec::new();
+        for id in ids {
+            if self.active.lock().contains_key(&id) {
+                continue;
+            }
+            if let Some(pending) = self.storage.get_as(&Self::pending_key(&id)).await? {
+                handles.push(self.activate(id, pending).await?);
+            }
+        }
+        Ok(handles)
+    }
+
+    /// tracks the raffle as running and closes it once its window has passed,
+    /// trying again later if that fails until it's given up on
+    async fn activate(&self, id: String, pending: PendingRaffle) -> Result<RaffleHandle> {
+        let (tx, rx) = oneshot::channel();
+        let message_id = pending.message_id;
+        let mut wait = pending.closes_in();
+        self.store_pending_ids(Some(&id)).await?;
+        self.active.lock().insert(
+            id.clone(),
+            ActiveRaffle {
+                pending,
+                closing: false,
+                result: tx,
+            },
+        );
+
+        let raffles = self.clone();
+        let raffle_id = id.clone();
+        self.runtime.spawn(Box::pin(async move {
+            for attempt in 1..=CLOSE_ATTEMPTS {
+                raffles.runtime.sleep(wait).await;
+                match raffles.close(&raffle_id).await {
+                    Ok(()) => return,
+                    Err(e) if attempt == CLOSE_ATTEMPTS => {
+                        log::warn!(
+                            "closing raffle {raffle_id} failed, giving up: {}",
+                            redaction::scrub(&e)
+                        );
+                        raffles.abandon(&raffle_id, e).await;
+                    },
+                    Err(e) => log::warn!(
+                        "closing raffle {raffle_id} failed, trying again later: {}",
+                        redaction::scrub(e)
+                    ),
+                }
+                wait = CLOSE_RETRY;
+            }
+        }));
+
+        Ok(RaffleHandle {
+            id,
+            message_id,
+            result: rx,
+        })
+    }
+
+    /// stores the ids of the running raffles, including the one about to be
+    /// added
+    async fn store_pending_ids(&self, adding: Option<&str>) -> Result<()> {
+        let mut ids: Vec<String> = self.active.lock().keys().cloned().collect();
+        ids.extend(adding.map(str::to_owned));
+        ids.sort();
+        ids.dedup();
+        self.storage.set_as(PENDING_KEY, &ids).await
+    }
+
+    async fn store_pending(&self, id: &str) -> Result<()> {
+        let pending = self.active.lock().get(id).map(|a| a.pending.clone());
+        match pending {
+            Some(pending) => self.storage.set_as(&Self::pending_key(id), &pending).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Enters the user if the query comes from the join button of a running
+    /// raffle and answers the query, returns whether it belonged to a raffle
+    pub async fn handle_callback_query(&self, query: &CallbackQuery) -> Result<bool> {
+        if query.data.as_deref() != Some(JOIN_DATA) {
+            return Ok(false);
+        }
+        let Some(message) = &query.message else {
+            return Ok(false);
+        };
+
+        let id = format!("{}:{}", message.chat.get_id(), message.message_id);
+        let entered = self.enter(&id, &query.from, |e| matches!(e, RaffleEntry::Button(_)));
+        let text = match entered {
+            None => return Ok(false),
+            Some(true) => "You entered the raffle",
+            Some(false) => "You can't enter this raffle (again)",
+        };
+        if entered == Some(true) {
+            self.store_pending(&id).await?;
+        }
+        self.api
+            .answer_callback_query(AnswerCallbackQuery {
+                callback_query_id: query.id.clone(),
+                text: Some(text.to_owned()),
+                show_alert: false,
+                url: None,
+                cache_time: None,
+            })
+            .await?;
+        Ok(true)
+    }
+
+    /// Enters or withdraws the user if they changed their reaction to the
+    /// announcement of a running raffle, returns whether the reaction belonged
+    /// to a raffle
+    pub async fn handle_reaction(&self, reaction: &MessageReactionUpdated) -> Result<bool> {
+        let Some(user) = &reaction.user else {
+            return Ok(false);
+        };
+        let id = format!("{}:{}", reaction.chat.get_id(), reaction.message_id);
+
+        {
+            let mut active_raffles = self.active.lock();
+            let Some(active) = active_raffles.get_mut(&id) else {
+                return Ok(false);
+            };
+            let RaffleEntry::Reaction(emoji) = &active.pending.entry else {
+                return Ok(false);
+            };
+            if active.closing {
+                return Ok(true);
+            }
+
+            let reacted = reaction.new_reaction.iter().any(|r| match r {
+                ReactionType::Emoji {
+                    emoji: e,
+                } => e == emoji,
+                _ => false,
+            });
+            if reacted {
+                if active.pending.may_enter(user) {
+                    active.pending.entrants.insert(user.id, user.clone());
+                }
+            } else {
+                active.pending.entrants.remove(&user.id);
+            }
+        }
+        self.store_pending(&id).await?;
+        Ok(true)
+    }
+
+    /// enters the user into the raffle if it's entered the given way, returns
+    /// `None` if there's no such raffle and whether the user was entered
+    /// otherwise
+    fn enter<F>(&self, id: &str, user: &User, entered_by: F) -> Option<bool>
+    where
+        F: FnOnce(&RaffleEntry) -> bool,
+    {
+        let mut active_raffles = self.active.lock();
+        let active = active_raffles.get_mut(id)?;
+        if !entered_by(&active.pending.entry) {
+            return None;
+        }
+        if active.closing
+            || !active.pending.may_enter(user)
+            || active.pending.entrants.contains_key(&user.id)
+        {
+            return Some(false);
+        }
+        active.pending.entrants.insert(user.id, user.clone());
+        Some(true)
+    }
+
+    /// The users who entered the running raffle so far, ordered by their id
+    pub fn participants(&self, id: &str) -> Vec<User> {
+        self.active
+            .lock()
+            .get(id)
+            .map(|a| a.pending.entrants.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The result of the closed raffle with the given id
+    pub async fn result(&self, id: &str) -> Result<Option<RaffleResult>> {
+        self.storage.get_as(&Self::key(id)).await
+    }
+
+    /// Closes the raffle with the given id before its window has passed,
+    /// sending its result to its [`RaffleHandle`]. Does nothing if the raffle
+    /// was already closed or is being closed.
+    ///
+    /// The winners are drawn and stored before anything is sent, so if
+    /// announcing them fails the raffle stays running with the same result,
+    /// and closing it can be tried again.
+    pub async fn close(&self, id: &str) -> Result<()> {
+        let pending = {
+            let mut active_raffles = self.active.lock();
+            let Some(active) = active_raffles.get_mut(id) else {
+                return Ok(());
+            };
+            if active.closing {
+                return Ok(());
+            }
+            active.closing = true;
+            active.pending.clone()
+        };
+
+        let result = match self.finish(id, &pending).await {
+            Ok(result) => result,
+            Err(e) => {
+                if let Some(active) = self.active.lock().get_mut(id) {
+                    active.closing = false;
+                }
+                return Err(e);
+            },
+        };
+
+        let Some(active) = self.active.lock().remove(id) else {
+            return Ok(());
+        };
+        // the result is stored, so the raffle won't be picked up again even
+        // if forgetting it fails
+        if let Err(e) = self.forget_pending(id).await {
+            log::warn!(
+                "forgetting the closed raffle {id} failed: {}",
+                redaction::scrub(e)
+            );
+        }
+        // an error only means the handle was dropped
+        let _ = active.result.send(Ok(result));
+        Ok(())
+    }
+
+    /// stops tracking the raffle after closing it failed too often, sending
+    /// the error to its handle
+    async fn abandon(&self, id: &str, error: Error) {
+        let Some(active) = self.active.lock().remove(id) else {
+            return;
+        };
+        if let Err(e) = self.forget_pending(id).await {
+            log::warn!(
+                "forgetting the abandoned raffle {id} failed: {}",
+                redaction::scrub(e)
+            );
+        }
+        // an error only means the handle was dropped
+        let _ = active.result.send(Err(error));
+    }
+
+    async fn forget_pending(&self, id: &str) -> Result<()> {
+        self.store_pending_ids(None).await?;
+        self.storage.remove(&Self::pending_key(id)).await
+    }
+
+    async fn finish(&self, id: &str, pending: &PendingRaffle) -> Result<RaffleResult> {
+        // a previous attempt may have drawn the winners already
+        let result = if let Some(result) = self.result(id).await? {
+            result
+        } else {
+            let participants: Vec<User> = pending.entrants.values().cloned().collect();
+            let result = RaffleResult {
+                id: id.to_owned(),
+                chat_id: pending.chat_id,
+                message_id: pending.message_id,
+                seed: pending.seed,
+                winners: draw_winners(&participants, pending.winners, pending.seed),
+                participants,
+            };
+            self.storage.set_as(&Self::key(id), &result).await?;
+            result
+        };
+
+        if let RaffleEntry::Button(_) = pending.entry {
+            // only cosmetic, late presses are ignored either way
+            if let Err(e) = self
+                .api
+                .edit_message_reply_markup(EditMessageReplyMarkup {
+                    business_connection_id: None,
+                    chat_id: Some(pending.chat_id),
+                    message_id: Some(pending.message_id),
+                    inline_message_id: None,
+                    reply_markup: None,
+                })
+                .await
+            {
+                log::warn!(
+                    "removing the join button of raffle {id} failed: {}",
+                    redaction::scrub(e)
+                );
+            }
+        }
+
+        if pending.announce_results {
+            let mut announcement = SendMessage::new(pending.chat_id, "");
+            announcement
+                .set_formatted_text(&results_text(&result))
+                .set_reply_to_message_id(pending.message_id);
+            self.api.send_message(announcement).await?;
+        }
+        Ok(result)
+    }
+}
+
+fn join_keyboard(label: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup {
+        inline_keyboard: vec![vec![InlineKeyboardButton {
+            text: label.to_owned(),
+            url: None,
+            login_url: None,
+            callback_data: Some(JOIN_DATA.to_owned()),
+            switch_inline_query: None,
+            switch_inline_query_current_chat: None,
+            callback_game: None,
+            pay: false,
+        }]],
+    }
+}
+
+/// the announcement of the winners, including what's needed to verify the
+/// draw
+fn results_text(result: &RaffleResult) -> FormattedText {
+    if result.winners.is_empty() {
+        return compose([text("Nobody entered the raffle")]);
+    }
+
+    let mut fragments: Vec<Fragment> = vec![text("Winners: ")];
+    for (i, user) in result.winners.iter().enumerate() {
+        if i > 0 {
+            fragments.push(text(", "));
+        }
+        fragments.push(mention(user.first_name.clone(), user));
+    }
+    fragments.push(text(format!(
+        "\n{} participants, seed {}",
+        result.participants.len(),
+        result.seed
+    )));
+    compose(fragments)
+}
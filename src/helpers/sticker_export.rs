@@ -0,0 +1,141 @@
+use crate::{
+    api::{
+        types::{GetFile, GetStickerSet},
+        API,
+    },
+    model::{Sticker, StickerFormat, StickerSet},
+    utils::result::{Result, TelegramError},
+};
+use futures::{Stream, StreamExt, TryStreamExt};
+use std::path::{Path, PathBuf};
+
+/// The file of a sticker in a set, downloaded by [`sticker_set_files`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StickerFile {
+    /// The position of the sticker in the set
+    pub index: usize,
+    /// The sticker the file belongs to
+    pub sticker: Sticker,
+    /// The format of the file
+    pub format: StickerFormat,
+    /// The extension of the file: "webp" or "png" for static stickers, "tgs"
+    /// for animated ones and "webm" for video stickers
+    pub extension: String,
+    /// The contents of the file
+    pub bytes: Vec<u8>,
+}
+
+impl StickerFile {
+    /// A file name for the sticker which keeps the files in the order of the
+    /// set, for example `003_AgADBQADIs4xGw.webp`
+    pub fn file_name(&self) -> String {
+        format!(
+            "{:03}_{}.{}",
+            self.index, self.sticker.file_unique_id, self.extension
+        )
+    }
+}
+
+/// The format of the file of the sticker
+fn sticker_format(sticker: &Sticker) -> StickerFormat {
+    if sticker.is_animated {
+        StickerFormat::Animated
+    } else if sticker.is_video {
+        StickerFormat::Video
+    } else {
+        StickerFormat::Static
+    }
+}
+
+async fn download_sticker(api: &dyn API, index: usize, sticker: Sticker) -> Result<StickerFile> {
+    let file = api
+        .get_file(GetFile {
+            file_id: sticker.file_id.clone(),
+        })
+        .await?;
+    let path = file.file_path.ok_or_else(|| {
+        TelegramError::Unknown(format!(
+            "telegram didn't return a path for the sticker {}",
+            sticker.file_unique_id
+        ))
+    })?;
+
+    let format = sticker_format(&sticker);
+    let extension = match path.rsplit_once('.') {
+        Some((_, e)) if StickerFormat::from_file_name(&path) == Some(format) => e.to_lowercase(),
+        _ => match format {
+            StickerFormat::Static => "webp",
+            StickerFormat::Animated => "tgs",
+            StickerFormat::Video => "webm",
+        }
+        .to_owned(),
+    };
+
+    Ok(StickerFile {
+        index,
+        bytes: api.download(&path).await?,
+        sticker,
+        format,
+        extension,
+    })
+}
+
+/// Downloads the files of all stickers in the set, at most `concurrency` at
+/// a time so large sets don't run into the rate limits of telegram. The files
+/// are yielded as soon as they're downloaded, which isn't necessarily in the
+/// order of the set.
+pub fn sticker_set_files<'a>(
+    api: &'a dyn API,
+    set: &StickerSet,
+    concurrency: usize,
+) -> impl Stream<Item = Result<StickerFile>> + Send + 'a {
+    futures::stream::iter(set.stickers.clone().into_iter().enumerate())
+        .map(move |(index, sticker)| download_sticker(api, index, sticker))
+        .buffer_unordered(concurrency.max(1))
+}
+
+/// Fetches the sticker set with the given name and downloads all of its
+/// stickers into the directory, for backups or moving the stickers to another
+/// set. The set itself is written to "set.json" in the directory, next to the
+/// sticker files named by [`StickerFile::file_name`].
+///
+/// At most `concurrency` files are downloaded at a time. Returns the paths of
+/// the sticker files in the order of the set.
+///
+/// ```rust,no_run
+/// use telexide::{api::APIClient, helpers::export_sticker_set};
+///
+/// # async fn backup(token: &str) -> telexide::Result<()> {
+/// let api = APIClient::new_default(token);
+/// let files = export_sticker_set(&api, "my_stickers_by_bot", "backups/my_stickers", 4).await?;
+/// println!("exported {} stickers", files.len());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn export_sticker_set<P: AsRef<Path>>(
+    api: &dyn API,
+    name: &str,
+    dir: P,
+    concurrency: usize,
+) -> Result<Vec<PathBuf>> {
+    let set = api
+        .get_sticker_set(GetStickerSet {
+            name: name.to_owned(),
+        })
+        .await?;
+
+    let dir = dir.as_ref();
+    tokio::fs::create_dir_all(dir).await?;
+    tokio::fs::write(dir.join("set.json"), serde_json::to_vec_pretty(&set)?).await?;
+
+    let mut paths: Vec<(usize, PathBuf)> = sticker_set_files(api, &set, concurrency)
+        .and_then(|file| async move {
+            let path = dir.join(file.file_name());
+            tokio::fs::write(&path, &file.bytes).await?;
+            Ok((file.index, path))
+        })
+        .try_collect()
+        .await?;
+    paths.sort();
+    Ok(paths.into_iter().map(|(_, path)| path).collect())
+}
@@ -0,0 +1,676 @@
+use crate::{
+    api::{
+        types::{
+            AnswerCallbackQuery,
+            EditMessageReplyMarkup,
+            GetChatMember,
+            GetChatMemberCount,
+            SendMessage,
+            SendPoll,
+            StopPoll,
+        },
+        API,
+    },
+    model::{
+        CallbackQuery,
+        InlineKeyboardButton,
+        InlineKeyboardMarkup,
+        MessageContent,
+        PollAnswer,
+        PollType,
+    },
+    redaction,
+    runtime::{Runtime, TokioRuntime},
+    utils::result::{Error, Result, TelegramError},
+};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+/// The prefix of the callback data of vote buttons
+const CALLBACK_PREFIX: &str = "vote:";
+
+/// How long to wait before trying to close a vote again after it failed
+const CLOSE_RETRY: Duration = Duration::from_secs(60);
+
+/// How often closing a vote is tried before giving up on it
+const CLOSE_ATTEMPTS: u32 = 5;
+
+/// How the options of a [`Vote`] are presented to the voters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteStyle {
+    /// A native, non-anonymous telegram poll
+    Poll,
+    /// A message with an inline keyboard button for every option
+    Buttons,
+}
+
+/// Who is allowed to take part in a [`Vote`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoterEligibility {
+    /// Everyone who is currently a member of the chat
+    ChatMembers,
+    /// Only the users with the given ids
+    Only(HashSet<i64>),
+}
+
+/// The minimum participation for a [`Vote`] to be valid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quorum {
+    /// Any amount of voters is enough
+    None,
+    /// At least the given amount of voters
+    Voters(usize),
+    /// At least the given fraction (between 0 and 1) of the eligible voters
+    Fraction(f64),
+}
+
+/// How many votes an option needs to win a [`Vote`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Majority {
+    /// The option with the most votes wins, as long as it isn't tied
+    Plurality,
+    /// The option needs more than half of the cast votes
+    Simple,
+    /// The option needs at least the given fraction (between 0 and 1) of the
+    /// cast votes, e.g. `2.0 / 3.0` for a two-thirds majority
+    Fraction(f64),
+}
+
+/// The votes cast on every option of a [`Vote`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteTally {
+    /// The options that were voted on
+    pub options: Vec<String>,
+    /// The amount of votes for each option, in the same order as `options`
+    pub counts: Vec<usize>,
+    /// The amount of users who voted
+    pub voters: usize,
+    /// The amount of users who were allowed to vote
+    pub eligible: usize,
+}
+
+/// The outcome of a closed [`Vote`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoteOutcome {
+    /// The option with the given index won
+    Passed {
+        /// The index of the winning option
+        option: usize,
+        /// The final tally
+        tally: VoteTally,
+    },
+    /// Enough users voted, but no option reached the required majority
+    NoMajority {
+        /// The final tally
+        tally: VoteTally,
+    },
+    /// Not enough users voted for the outcome to be valid
+    QuorumNotMet {
+        /// The amount of voters that were required
+        required: usize,
+        /// The final tally
+        tally: VoteTally,
+    },
+}
+
+impl VoteOutcome {
+    /// The final tally of the vote
+    pub fn tally(&self) -> &VoteTally {
+        match self {
+            VoteOutcome::Passed {
+                tally, ..
+            }
+            | VoteOutcome::NoMajority {
+                tally,
+            }
+            | VoteOutcome::QuorumNotMet {
+                tally, ..
+            } => tally,
+        }
+    }
+
+    /// The text of the winning option, if there is one
+    pub fn winner(&self) -> Option<&str> {
+        match self {
+            VoteOutcome::Passed {
+                option,
+                tally,
+            } => tally.options.get(*option).map(String::as_str),
+            _ => None,
+        }
+    }
+}
+
+/// The rules deciding the [`VoteOutcome`] of a [`Vote`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoteRules {
+    /// The minimum participation
+    pub quorum: Quorum,
+    /// The majority the winning option needs
+    pub majority: Majority,
+}
+
+impl VoteRules {
+    /// Decides the outcome of a vote with the given tally
+    pub fn decide(&self, tally: VoteTally) -> VoteOutcome {
+        let required = match self.quorum {
+            Quorum::None => 0,
+            Quorum::Voters(n) => n,
+            Quorum::Fraction(f) => fraction_of(tally.eligible, f),
+        };
+        if tally.voters < required {
+            return VoteOutcome::QuorumNotMet {
+                required,
+                tally,
+            };
+        }
+
+        let cast: usize = tally.counts.iter().sum();
+        let best = tally.counts.iter().copied().max().unwrap_or(0);
+        let leaders: Vec<usize> = (0..tally.counts.len())
+            .filter(|i| tally.counts[*i] == best)
+            .collect();
+
+        let reached = match self.majority {
+            Majority::Plurality => true,
+            Majority::Simple => best * 2 > cast,
+            Majority::Fraction(f) => best >= fraction_of(cast, f),
+        };
+
+        match leaders.as_slice() {
+            [option] if best > 0 && reached => VoteOutcome::Passed {
+                option: *option,
+                tally,
+            },
+            _ => VoteOutcome::NoMajority {
+                tally,
+            },
+        }
+    }
+}
+
+impl Default for VoteRules {
+    /// No quorum and a simple majority
+    fn default() -> Self {
+        Self {
+            quorum: Quorum::None,
+            majority: Majority::Simple,
+        }
+    }
+}
+
+/// rounds up, so that e.g. a two-thirds quorum of 10 voters requires 7 of them
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn fraction_of(total: usize, fraction: f64) -> usize {
+    (total as f64 * fraction.clamp(0.0, 1.0)).ceil() as usize
+}
+
+/// The settings of a vote to start with [`Voting::start`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vote {
+    chat_id: i64,
+    question: String,
+    options: Vec<String>,
+    style: VoteStyle,
+    eligibility: VoterEligibility,
+    rules: VoteRules,
+    deadline: Duration,
+}
+
+impl Vote {
+    /// Creates a poll vote in the given chat, open to all chat members for
+    /// 10 minutes and decided by a simple majority
+    pub fn new<S: ToString>(chat_id: i64, question: &str, options: &[S]) -> Self {
+        Self {
+            chat_id,
+            question: question.to_owned(),
+            options: options.iter().map(ToString::to_string).collect(),
+            style: VoteStyle::Poll,
+            eligibility: VoterEligibility::ChatMembers,
+            rules: VoteRules::default(),
+            deadline: Duration::from_secs(10 * 60),
+        }
+    }
+
+    /// Sets how the options are presented to the voters
+    #[must_use]
+    pub fn set_style(mut self, style: VoteStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Only allows the given users to vote, instead of all chat members
+    #[must_use]
+    pub fn set_eligible_voters<I: IntoIterator<Item = i64>>(mut self, voters: I) -> Self {
+        self.eligibility = VoterEligibility::Only(voters.into_iter().collect());
+        self
+    }
+
+    /// Sets the minimum participation for the vote to be valid
+    #[must_use]
+    pub fn set_quorum(mut self, quorum: Quorum) -> Self {
+        self.rules.quorum = quorum;
+        self
+    }
+
+    /// Sets the majority the winning option needs
+    #[must_use]
+    pub fn set_majority(mut self, majority: Majority) -> Self {
+        self.rules.majority = majority;
+        self
+    }
+
+    /// Sets how long the vote stays open
+    #[must_use]
+    pub fn set_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+}
+
+struct ActiveVote {
+    vote: Vote,
+    message_id: i64,
+    ballots: HashMap<i64, usize>,
+    outcome: oneshot::Sender<Result<VoteOutcome>>,
+}
+
+/// A handle to a running vote, used to wait for its outcome
+pub struct VoteHandle {
+    id: String,
+    outcome: oneshot::Receiver<Result<VoteOutcome>>,
+}
+
+impl VoteHandle {
+    /// The id of the vote, which can be passed to [`Voting::close`]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Waits until the vote is closed and returns its outcome
+    pub async fn outcome(self) -> Result<VoteOutcome> {
+        self.outcome.await.unwrap_or_else(|_| {
+            Err(TelegramError::Unknown("the vote was dropped before closing".to_owned()).into())
+        })
+    }
+}
+
+/// Runs votes in chats, keeping track of who voted for what and deciding the
+/// outcome once the deadline has passed.
+///
+/// Poll answers and callback queries have to be passed on to
+/// [`handle_poll_answer`] and [`handle_callback_query`] from your handlers,
+/// for the votes to be counted. Votes from users who aren't eligible are
+/// ignored, and every user can only vote for a single option, changing their
+/// vote replaces their earlier one.
+///
+/// Votes are closed on the tokio runtime by default, use [`set_runtime`] to
+/// close them on another one. Closing a vote fails while the amount of
+/// eligible voters can't be looked up, it's tried again every minute and
+/// given up on after five attempts, resolving its [`VoteHandle`] with the
+/// error.
+///
+/// ## Example
+/// ```rust,no_run
+/// use telexide::{
+///     client::Context,
+///     helpers::{Majority, Vote, Voting},
+/// };
+///
+/// async fn start_vote(ctx: &Context, voting: &Voting, chat_id: i64) -> telexide::Result<()> {
+///     let vote = Vote::new(chat_id, "Pin the rules?", &["yes", "no"])
+///         .set_majority(Majority::Fraction(2.0 / 3.0));
+///     let handle = voting.start(vote).await?;
+///
+///     let outcome = handle.outcome().await?;
+///     log::info!("the vote was won by {:?}", outcome.winner());
+///     Ok(())
+/// }
+/// ```
+///
+/// [`handle_poll_answer`]: Voting::handle_poll_answer
+/// [`handle_callback_query`]: Voting::handle_callback_query
+/// [`set_runtime`]: Voting::set_runtime
+#[derive(Clone)]
+pub struct Voting {
+    api: Arc<Box<dyn API + Send>>,
+    votes: Arc<Mutex<HashMap<String, ActiveVote>>>,
+    runtime: Arc<dyn Runtime>,
+}
+
+impl Voting {
+    /// Creates a new voting helper sending its messages with the given api
+    /// client
+    pub fn new(api: Arc<Box<dyn API + Send>>) -> Self {
+        Self {
+            api,
+            votes: Arc::new(Mutex::new(HashMap::new())),
+            runtime: Arc::new(TokioRuntime),
+        }
+    }
+
+    /// Sets the runtime the votes are closed on once their deadline passed
+    #[must_use]
+    pub fn set_runtime(mut self, runtime: Arc<dyn Runtime>) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Sends the vote to its chat and closes it once its deadline has passed
+    pub async fn start(&self, vote: Vote) -> Result<VoteHandle> {
+        if vote.options.len() < 2 {
+            return Err(TelegramError::InvalidArgument(
+                "a vote needs at least two options".to_owned(),
+            )
+            .into());
+        }
+
+        let (id, message_id) = match vote.style {
+            VoteStyle::Poll => self.send_poll(&vote).await?,
+            VoteStyle::Buttons => self.send_buttons(&vote).await?,
+        };
+
+        let (tx, rx) = oneshot::channel();
+        let deadline = vote.deadline;
+        self.votes.lock().insert(
+            id.clone(),
+            ActiveVote {
+                vote,
+                message_id,
+                ballots: HashMap::new(),
+                outcome: tx,
+            },
+        );
+
+        let voting = self.clone();
+        let vote_id = id.clone();
+        self.runtime.spawn(Box::pin(async move {
+            let mut wait = deadline;
+            for attempt in 1..=CLOSE_ATTEMPTS {
+                voting.runtime.sleep(wait).await;
+                match voting.close(&vote_id).await {
+                    Ok(()) => return,
+                    Err(e) if attempt == CLOSE_ATTEMPTS => {
+                        log::warn!(
+                            "closing vote {vote_id} failed, giving up: {}",
+                            redaction::scrub(&e)
+                        );
+                        voting.abandon(&vote_id, e);
+                    },
+                    Err(e) => log::warn!(
+                        "closing vote {vote_id} failed, trying again later: {}",
+                        redaction::scrub(e)
+                    ),
+                }
+                wait = CLOSE_RETRY;
+            }
+        }));
+
+        Ok(VoteHandle {
+            id,
+            outcome: rx,
+        })
+    }
+
+    async fn send_poll(&self, vote: &Vote) -> Result<(String, i64)> {
+        let message = self
+            .api
+            .send_poll(SendPoll {
+                business_connection_id: None,
+                chat_id: vote.chat_id,
+                question: vote.question.clone(),
+                options: vote.options.clone(),
+                is_anonymous: Some(false),
+                poll_type: Some(PollType::Regular),
+                allows_multiple_answers: false,
+                correct_option_id: None,
+                explanation: None,
+                explanation_parse_mode: None,
+                explanation_enitites: None,
+                open_period: None,
+                close_date: None,
+                is_closed: false,
+                disable_notification: false,
+                protect_content: false,
+                reply_to_message_id: None,
+                allow_sending_without_reply: false,
+                reply_markup: None,
+            })
+            .await?;
+
+        if let MessageContent::Poll {
+            content,
+        } = message.content
+        {
+            Ok((content.id, message.message_id))
+        } else {
+            let err = "sendPoll didn't return a poll".to_owned();
+            Err(TelegramError::APIResponseError(err).into())
+        }
+    }
+
+    async fn send_buttons(&self, vote: &Vote) -> Result<(String, i64)> {
+        let message = self
+            .api
+            .send_message(SendMessage::new(vote.chat_id, &vote.question))
+            .await?;
+        // the buttons can only be added once the message id is known, as it is
+        // part of their callback data
+        let id = format!("{}:{}", vote.chat_id, message.message_id);
+        self.api
+            .edit_message_reply_markup(EditMessageReplyMarkup {
+                business_connection_id: None,
+                chat_id: Some(vote.chat_id),
+                message_id: Some(message.message_id),
+                inline_message_id: None,
+                reply_markup: Some(vote_keyboard(&id, &vote.options)),
+            })
+            .await?;
+
+        Ok((id, message.message_id))
+    }
+
+    /// Counts the answer if it belongs to a running poll vote, returns
+    /// whether it did
+    pub async fn handle_poll_answer(&self, answer: &PollAnswer) -> Result<bool> {
+        let option = answer.option_ids.first().copied();
+        self.record(&answer.poll_id, answer.user.id, option).await
+    }
+
+    /// Counts the vote if the query comes from the button of a running vote
+    /// and answers the query, returns whether it belonged to a vote
+    pub async fn handle_callback_query(&self, query: &CallbackQuery) -> Result<bool> {
+        let (id, option) = match query
+            .data
+            .as_deref()
+            .and_then(|d| d.strip_prefix(CALLBACK_PREFIX))
+            .and_then(|d| d.rsplit_once(':'))
+        {
+            Some((id, option)) => match option.parse::<usize>() {
+                Ok(option) => (id.to_owned(), option),
+                Err(_) => return Ok(false),
+            },
+            None => return Ok(false),
+        };
+
+        let counted = self.record(&id, query.from.id, Some(option)).await?;
+        let text = if counted {
+            "Your vote was counted"
+        } else {
+            "You can't vote on this"
+        };
+        self.api
+            .answer_callback_query(AnswerCallbackQuery {
+                callback_query_id: query.id.clone(),
+                text: Some(text.to_owned()),
+                show_alert: false,
+                url: None,
+                cache_time: None,
+            })
+            .await?;
+        Ok(counted)
+    }
+
+    async fn record(&self, id: &str, user_id: i64, option: Option<usize>) -> Result<bool> {
+        let (chat_id, eligibility) = match self.votes.lock().get(id) {
+            Some(active) => (active.vote.chat_id, active.vote.eligibility.clone()),
+            None => return Ok(false),
+        };
+
+        let eligible = match eligibility {
+            VoterEligibility::Only(voters) => voters.contains(&user_id),
+            VoterEligibility::ChatMembers => self
+                .api
+                .get_chat_member(GetChatMember {
+                    chat_id,
+                    user_id,
+                })
+                .await?
+                .is_member(),
+        };
+        if !eligible {
+            return Ok(false);
+        }
+
+        let mut votes = self.votes.lock();
+        let Some(active) = votes.get_mut(id) else {
+            return Ok(false);
+        };
+        match option {
+            Some(option) if option < active.vote.options.len() => {
+                active.ballots.insert(user_id, option);
+            },
+            Some(_) => return Ok(false),
+            None => {
+                active.ballots.remove(&user_id);
+            },
+        }
+        Ok(true)
+    }
+
+    /// Closes the vote with the given id before its deadline, sending its
+    /// outcome to its [`VoteHandle`]. Does nothing if the vote was already
+    /// closed.
+    ///
+    /// The vote stays open if the amount of eligible voters can't be looked
+    /// up, so closing it can be tried again. Stopping the poll or removing the
+    /// buttons happens after the outcome is decided, and failing to do so
+    /// doesn't change it.
+    pub async fn close(&self, id: &str) -> Result<()> {
+        let (chat_id, eligibility) = match self.votes.lock().get(id) {
+            Some(active) => (active.vote.chat_id, active.vote.eligibility.clone()),
+            None => return Ok(()),
+        };
+        let eligible = self.eligible_voters(chat_id, &eligibility).await?;
+
+        let Some(active) = self.votes.lock().remove(id) else {
+            return Ok(());
+        };
+        let outcome = tally(&active, eligible);
+        // an error only means the handle was dropped
+        let _ = active.outcome.send(Ok(outcome));
+
+        if let Err(e) = self.stop_voting(&active.vote, active.message_id).await {
+            log::warn!("stopping vote {id} failed: {}", redaction::scrub(e));
+        }
+        Ok(())
+    }
+
+    /// drops the vote without deciding it, sending the error to its handle
+    fn abandon(&self, id: &str, error: Error) {
+        if let Some(active) = self.votes.lock().remove(id) {
+            // an error only means the handle was dropped
+            let _ = active.outcome.send(Err(error));
+        }
+    }
+
+    async fn eligible_voters(&self, chat_id: i64, eligibility: &VoterEligibility) -> Result<usize> {
+        Ok(match eligibility {
+            VoterEligibility::Only(voters) => voters.len(),
+            VoterEligibility::ChatMembers => {
+                let count = self
+                    .api
+                    .get_chat_member_count(GetChatMemberCount {
+                        chat_id,
+                    })
+                    .await?;
+                usize::try_from(count).unwrap_or_default()
+            },
+        })
+    }
+
+    /// stops the poll or removes the buttons, so the message shows the vote
+    /// is over
+    async fn stop_voting(&self, vote: &Vote, message_id: i64) -> Result<()> {
+        match vote.style {
+            VoteStyle::Poll => {
+                self.api
+                    .stop_poll(StopPoll {
+                        business_connection_id: None,
+                        chat_id: vote.chat_id,
+                        message_id,
+                        reply_markup: None,
+                    })
+                    .await?;
+            },
+            VoteStyle::Buttons => {
+                self.api
+                    .edit_message_reply_markup(EditMessageReplyMarkup {
+                        business_connection_id: None,
+                        chat_id: Some(vote.chat_id),
+                        message_id: Some(message_id),
+                        inline_message_id: None,
+                        reply_markup: None,
+                    })
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+}
+
+fn tally(active: &ActiveVote, eligible: usize) -> VoteOutcome {
+    let vote = &active.vote;
+    let mut counts = vec![0; vote.options.len()];
+    for option in active.ballots.values() {
+        counts[*option] += 1;
+    }
+
+    vote.rules.decide(VoteTally {
+        options: vote.options.clone(),
+        counts,
+        voters: active.ballots.len(),
+        eligible,
+    })
+}
+
+fn vote_keyboard(id: &str, options: &[String]) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup {
+        inline_keyboard: options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| {
+                vec![InlineKeyboardButton {
+                    text: option.clone(),
+                    url: None,
+                    login_url: None,
+                    callback_data: Some(format!("{CALLBACK_PREFIX}{id}:{i}")),
+                    switch_inline_query: None,
+                    switch_inline_query_current_chat: None,
+                    callback_game: None,
+                    pay: false,
+                }]
+            })
+            .collect(),
+    }
+}
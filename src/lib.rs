@@ -66,7 +66,14 @@
 pub mod api;
 pub mod client;
 pub mod framework;
+pub mod helpers;
+pub mod metrics;
+pub mod middleware;
 pub mod model;
+pub mod redaction;
+pub mod runtime;
+pub mod storage;
+pub mod test;
 mod utils;
 
 /// Macros for using the framework and helping with adding listeners
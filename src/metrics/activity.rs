@@ -0,0 +1,88 @@
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+
+/// How many minutes of activity are kept, enough for the last day
+const RETENTION_MINUTES: i64 = 24 * 60;
+
+/// The activity of a bot within a time window, as returned by
+/// [`HandlerMetrics::activity`]
+///
+/// [`HandlerMetrics::activity`]: super::HandlerMetrics::activity
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityStats {
+    /// How many updates were handled
+    pub updates: u64,
+    /// How many of those updates were new messages
+    pub messages: u64,
+    /// How often every command was used, by the name of the command
+    pub commands: HashMap<String, u64>,
+    /// How many commands returned an error or handlers panicked
+    pub errors: u64,
+}
+
+impl ActivityStats {
+    /// The total amount of commands used
+    pub fn command_uses(&self) -> u64 {
+        self.commands.values().sum()
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.updates += other.updates;
+        self.messages += other.messages;
+        self.errors += other.errors;
+        for (command, uses) in &other.commands {
+            *self.commands.entry(command.clone()).or_default() += uses;
+        }
+    }
+}
+
+/// the activity per minute of the last day, oldest first
+#[derive(Default)]
+pub(super) struct Activity {
+    minutes: Mutex<VecDeque<(i64, ActivityStats)>>,
+}
+
+impl Activity {
+    /// changes the counts of the current minute, dropping the minutes which
+    /// are too old to be queried
+    pub(super) fn record<F: FnOnce(&mut ActivityStats)>(&self, change: F) {
+        let minute = Utc::now().timestamp().div_euclid(60);
+        let mut minutes = self.minutes.lock();
+        while minutes
+            .front()
+            .is_some_and(|(m, _)| *m <= minute - RETENTION_MINUTES)
+        {
+            minutes.pop_front();
+        }
+
+        match minutes.back_mut() {
+            Some((m, stats)) if *m == minute => change(stats),
+            _ => {
+                let mut stats = ActivityStats::default();
+                change(&mut stats);
+                minutes.push_back((minute, stats));
+            },
+        }
+    }
+
+    /// sums up the minutes starting at or after the minute of `since`
+    pub(super) fn since(&self, since: DateTime<Utc>) -> ActivityStats {
+        let since = since.timestamp().div_euclid(60);
+        let mut total = ActivityStats::default();
+        for (_, stats) in self.minutes.lock().iter().filter(|(m, _)| *m >= since) {
+            total.add(stats);
+        }
+        total
+    }
+
+    pub(super) fn within(&self, window: std::time::Duration) -> ActivityStats {
+        let window =
+            Duration::from_std(window).unwrap_or_else(|_| Duration::minutes(RETENTION_MINUTES));
+        self.since(Utc::now() - window)
+    }
+
+    pub(super) fn clear(&self) {
+        self.minutes.lock().clear();
+    }
+}
@@ -0,0 +1,235 @@
+use super::activity::{Activity, ActivityStats};
+use crate::redaction;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast;
+
+/// The amount of most recent durations kept per handler for calculating the
+/// percentiles
+const SAMPLE_SIZE: usize = 1024;
+
+/// Emitted when a handler ran for longer than the configured threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowHandler {
+    /// The name of the handler, for example "handler#0" or "command /start"
+    pub handler: String,
+    /// The chat the update originated from, if there is one
+    pub chat_id: Option<i64>,
+    /// The kind of update that was handled, for example "message"
+    pub update_type: &'static str,
+    /// How long the handler ran for
+    pub elapsed: Duration,
+}
+
+/// A summary of the recorded invocations of a handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandlerStats {
+    /// How often the handler has been invoked
+    pub invocations: u64,
+    /// The median duration of the recent invocations
+    pub p50: Duration,
+    /// The 90th percentile duration of the recent invocations
+    pub p90: Duration,
+    /// The 99th percentile duration of the recent invocations
+    pub p99: Duration,
+    /// The longest duration of the recent invocations
+    pub max: Duration,
+}
+
+#[derive(Default)]
+struct Samples {
+    invocations: u64,
+    durations: VecDeque<Duration>,
+}
+
+/// Records the invocation count and latency of every handler, see the
+/// [module-level documentation](index.html) for more information.
+pub struct HandlerMetrics {
+    handlers: Mutex<HashMap<String, Samples>>,
+    slow_threshold: Option<Duration>,
+    events: broadcast::Sender<SlowHandler>,
+    activity: Activity,
+}
+
+impl HandlerMetrics {
+    /// Creates a new `HandlerMetrics` without a slow handler threshold
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(64);
+
+        Self {
+            handlers: Mutex::new(HashMap::new()),
+            slow_threshold: None,
+            events,
+            activity: Activity::default(),
+        }
+    }
+
+    /// Sets the duration after which a handler is considered slow, a warning
+    /// gets logged and a [`SlowHandler`] event emitted for every invocation
+    /// running longer than it
+    #[must_use]
+    pub fn set_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = Some(threshold);
+        self
+    }
+
+    /// Returns a receiver which gets a [`SlowHandler`] event for every handler
+    /// invocation exceeding the slow threshold
+    pub fn subscribe(&self) -> broadcast::Receiver<SlowHandler> {
+        self.events.subscribe()
+    }
+
+    /// Records a single invocation of the given handler
+    pub fn record(
+        &self,
+        handler: &str,
+        chat_id: Option<i64>,
+        update_type: &'static str,
+        elapsed: Duration,
+    ) {
+        {
+            let mut handlers = self.handlers.lock();
+            let samples = handlers.entry(handler.to_owned()).or_default();
+            samples.invocations += 1;
+            if samples.durations.len() == SAMPLE_SIZE {
+                samples.durations.pop_front();
+            }
+            samples.durations.push_back(elapsed);
+        }
+
+        if self.slow_threshold.is_some_and(|t| elapsed > t) {
+            log::warn!(
+                "handler {handler} took {elapsed:?} handling a {update_type} update in chat {}",
+                chat_id.map_or_else(|| "-".to_owned(), redaction::chat)
+            );
+            // an error only means nobody is listening
+            let _ = self.events.send(SlowHandler {
+                handler: handler.to_owned(),
+                chat_id,
+                update_type,
+                elapsed,
+            });
+        }
+    }
+
+    /// Gets the stats of the given handler, if it has been invoked before
+    pub fn stats(&self, handler: &str) -> Option<HandlerStats> {
+        self.handlers.lock().get(handler).map(Samples::stats)
+    }
+
+    /// Gets the stats of all handlers that have been invoked
+    pub fn all_stats(&self) -> HashMap<String, HandlerStats> {
+        self.handlers
+            .lock()
+            .iter()
+            .map(|(name, samples)| (name.clone(), samples.stats()))
+            .collect()
+    }
+
+    /// Counts an update handled by the client
+    pub fn record_update(&self, update_type: &str) {
+        self.activity.record(|a| {
+            a.updates += 1;
+            if update_type == "message" {
+                a.messages += 1;
+            }
+        });
+    }
+
+    /// Counts a use of the given command
+    pub fn record_command(&self, command: &str) {
+        self.activity
+            .record(|a| *a.commands.entry(command.to_owned()).or_default() += 1);
+    }
+
+    /// Counts a command returning an error or a handler panicking
+    pub fn record_error(&self) {
+        self.activity.record(|a| a.errors += 1);
+    }
+
+    /// Gets the activity within the given window up to now, e.g. the last
+    /// hour. Activity is counted per minute and kept for a day, so the window
+    /// is rounded to whole minutes and longer windows are cut off at a day.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use telexide::metrics::HandlerMetrics;
+    ///
+    /// # fn stats(metrics: &HandlerMetrics) -> String {
+    /// let hour = metrics.activity(Duration::from_secs(60 * 60));
+    /// let day = metrics.activity(Duration::from_secs(24 * 60 * 60));
+    /// format!(
+    ///     "{} messages and {} commands in the last hour, {} errors today",
+    ///     hour.messages,
+    ///     hour.command_uses(),
+    ///     day.errors
+    /// )
+    /// # }
+    /// ```
+    pub fn activity(&self, window: Duration) -> ActivityStats {
+        self.activity.within(window)
+    }
+
+    /// Gets the activity since the given moment, at most a day ago
+    pub fn activity_since(&self, since: DateTime<Utc>) -> ActivityStats {
+        self.activity.since(since)
+    }
+
+    /// Clears all recorded invocations and activity
+    pub fn reset(&self) {
+        self.handlers.lock().clear();
+        self.activity.clear();
+    }
+}
+
+impl Default for HandlerMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Samples {
+    fn stats(&self) -> HandlerStats {
+        let mut sorted: Vec<Duration> = self.durations.iter().copied().collect();
+        sorted.sort_unstable();
+
+        let percentile = |p: usize| {
+            if sorted.is_empty() {
+                Duration::default()
+            } else {
+                sorted[((sorted.len() * p).div_ceil(100)).saturating_sub(1)]
+            }
+        };
+
+        HandlerStats {
+            invocations: self.invocations,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: sorted.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// runs the given handler future, recording its duration if metrics are
+/// enabled
+pub(crate) async fn measure<F: Future<Output = ()>>(
+    metrics: Option<Arc<HandlerMetrics>>,
+    handler: String,
+    chat_id: Option<i64>,
+    update_type: &'static str,
+    fut: F,
+) {
+    let started = Instant::now();
+    fut.await;
+
+    if let Some(metrics) = metrics {
+        metrics.record(&handler, chat_id, update_type, started.elapsed());
+    }
+}
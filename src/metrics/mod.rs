@@ -0,0 +1,36 @@
+//! The metrics module provides [`HandlerMetrics`], which keeps track of how
+//! often and how long your event handlers and commands run.
+//!
+//! Register it on the [`ClientBuilder`] to have every handler invocation
+//! recorded, and optionally set a threshold above which a [`SlowHandler`]
+//! event is emitted, naming the handler, chat and update type involved.
+//!
+//! The handled updates, used commands and errors of the last day are counted
+//! per minute as well, so a bot can answer a `/stats` command with the
+//! [`ActivityStats`] of the last hour or day.
+//!
+//! ```rust,no_run
+//! use std::{sync::Arc, time::Duration};
+//! use telexide::{metrics::HandlerMetrics, prelude::*};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> telexide::Result<()> {
+//! # let token = "test token";
+//! let metrics = Arc::new(HandlerMetrics::new().set_slow_threshold(Duration::from_secs(2)));
+//! let client = ClientBuilder::new()
+//!     .set_token(token)
+//!     .set_handler_metrics(metrics.clone())
+//!     .build();
+//!
+//! client.start().await
+//! # }
+//! ```
+//!
+//! [`ClientBuilder`]: ../client/struct.ClientBuilder.html
+
+mod activity;
+mod handlers;
+
+pub use activity::ActivityStats;
+pub use handlers::{HandlerMetrics, HandlerStats, SlowHandler};
+pub(crate) use handlers::measure;
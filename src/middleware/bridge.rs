@@ -0,0 +1,280 @@
+use super::{Middleware, Next};
+use crate::{
+    api::{
+        types::{CopyMessage, DeleteMessage, EditMessageCaption, EditMessageText},
+        API,
+    },
+    client::Context,
+    model::{Message, MessageContent, Update, UpdateContent},
+    redaction,
+    storage::Storage,
+    utils::result::Result,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// One side of a [`ForwardingBridge`] link: a chat, or a single forum topic
+/// of a chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BridgeEndpoint {
+    /// The id of the chat
+    pub chat_id: i64,
+    /// The forum topic within the chat, or `None` for the whole chat
+    pub thread_id: Option<i64>,
+}
+
+impl BridgeEndpoint {
+    /// An endpoint covering the whole chat
+    pub fn chat(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            thread_id: None,
+        }
+    }
+
+    /// An endpoint covering a single forum topic of the chat
+    pub fn topic(chat_id: i64, thread_id: i64) -> Self {
+        Self {
+            chat_id,
+            thread_id: Some(thread_id),
+        }
+    }
+
+    /// Returns whether the message was sent in this endpoint
+    pub fn contains(&self, message: &Message) -> bool {
+        message.chat.get_id() == self.chat_id
+            && self
+                .thread_id
+                .is_none_or(|t| message.message_thread_id == Some(t))
+    }
+}
+
+/// A [`Middleware`] mirroring messages between chats, or between forum topics
+/// of chats.
+///
+/// Messages are mirrored using [`copy_message`], so formatting is kept but the
+/// copies don't show the "forwarded from" header. Which message got copied to
+/// which is stored in the provided [`Storage`], which is used to:
+/// - let replies to a mirrored message reply to its copies in the other chats
+/// - propagate edits of the text or caption of a message to its copies
+/// - propagate deletions with [`propagate_delete`]
+///
+/// Telegram doesn't tell bots when messages get deleted, so deletions only get
+/// propagated when you call [`propagate_delete`], for example after deleting a
+/// message in a moderation handler.
+///
+/// Updates are always passed on to the handlers.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use telexide::{
+///     middleware::{BridgeEndpoint, ForwardingBridge},
+///     storage::MemoryStorage,
+/// };
+///
+/// let bridge = ForwardingBridge::new(Arc::new(MemoryStorage::new()))
+///     .add_pair(BridgeEndpoint::chat(-1001), BridgeEndpoint::topic(-1002, 7));
+/// ```
+///
+/// [`copy_message`]: ../api/trait.API.html#method.copy_message
+/// [`propagate_delete`]: ForwardingBridge::propagate_delete
+pub struct ForwardingBridge {
+    links: Vec<(BridgeEndpoint, BridgeEndpoint)>,
+    storage: Arc<dyn Storage>,
+}
+
+impl ForwardingBridge {
+    /// Creates a new bridge without any links, storing the message
+    /// correspondence in the given storage
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            links: Vec::new(),
+            storage,
+        }
+    }
+
+    /// Mirrors messages sent in `from` to `to`, but not the other way around
+    #[must_use]
+    pub fn add_link(mut self, from: BridgeEndpoint, to: BridgeEndpoint) -> Self {
+        self.links.push((from, to));
+        self
+    }
+
+    /// Mirrors messages sent in either endpoint to the other one
+    #[must_use]
+    pub fn add_pair(self, a: BridgeEndpoint, b: BridgeEndpoint) -> Self {
+        self.add_link(a, b).add_link(b, a)
+    }
+
+    /// Returns the endpoints the message should be mirrored to
+    pub fn targets(&self, message: &Message) -> Vec<BridgeEndpoint> {
+        self.links
+            .iter()
+            .filter(|(from, _)| from.contains(message))
+            .map(|(_, to)| *to)
+            .collect()
+    }
+
+    fn copies_key(chat_id: i64, message_id: i64) -> String {
+        format!("bridge:{chat_id}:{message_id}")
+    }
+
+    /// Returns the chat and message ids of all messages the given message is
+    /// linked to: the original it was copied from and all of its copies
+    pub async fn linked_messages(&self, chat_id: i64, message_id: i64) -> Result<Vec<(i64, i64)>> {
+        Ok(self
+            .storage
+            .get_as(&Self::copies_key(chat_id, message_id))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// adds the copy to the messages linked to the original, storing for every
+    /// message of the group all the other ones, so the original and every copy
+    /// can be found from any of them
+    async fn link_messages(&self, original: (i64, i64), copy: (i64, i64)) -> Result<()> {
+        let mut group = self.linked_messages(original.0, original.1).await?;
+        group.push(original);
+        if !group.contains(&copy) {
+            group.push(copy);
+        }
+
+        for member in &group {
+            let others: Vec<(i64, i64)> = group.iter().filter(|m| *m != member).copied().collect();
+            self.storage
+                .set_as(&Self::copies_key(member.0, member.1), &others)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Finds the message in the target chat to reply to, when the message is
+    /// a reply to a message which was mirrored there
+    async fn reply_target(&self, message: &Message, target: i64) -> Result<Option<i64>> {
+        let Some(reply) = &message.reply_to_message else {
+            return Ok(None);
+        };
+        // messages in forum topics without an explicit reply are replies to
+        // the message that created the topic
+        if message.message_thread_id == Some(reply.message_id) {
+            return Ok(None);
+        }
+
+        Ok(self
+            .linked_messages(reply.chat.get_id(), reply.message_id)
+            .await?
+            .into_iter()
+            .find(|(chat_id, _)| *chat_id == target)
+            .map(|(_, message_id)| message_id))
+    }
+
+    async fn mirror(&self, api: &dyn API, message: &Message) -> Result<()> {
+        for target in self.targets(message) {
+            let mut data = CopyMessage::from_message(target.chat_id, message);
+            if let Some(thread_id) = target.thread_id {
+                data.set_message_thread_id(thread_id);
+            }
+            if let Some(reply_to) = self.reply_target(message, target.chat_id).await? {
+                data.set_reply_to_message_id(reply_to)
+                    .toggle_allow_sending_without_reply();
+            }
+
+            let copy = api.copy_message(data).await?;
+            self.link_messages(
+                (message.chat.get_id(), message.message_id),
+                (target.chat_id, copy.message_id),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn propagate_edit(&self, api: &dyn API, message: &Message) -> Result<()> {
+        let linked = self
+            .linked_messages(message.chat.get_id(), message.message_id)
+            .await?;
+
+        for (chat_id, message_id) in linked {
+            if let MessageContent::Text {
+                content,
+                entities,
+            } = &message.content
+            {
+                api.edit_message_text(EditMessageText {
+                    business_connection_id: None,
+                    chat_id: Some(chat_id),
+                    message_id: Some(message_id),
+                    inline_message_id: None,
+                    text: content.clone(),
+                    parse_mode: None,
+                    entities: Some(entities.clone()),
+                    disable_web_page_preview: false,
+                    reply_markup: None,
+                })
+                .await?;
+            } else if let Some(caption) = message.get_text() {
+                // only media with a caption can have it edited, other media
+                // can't be edited through the bot api at all
+                api.edit_message_caption(EditMessageCaption {
+                    business_connection_id: None,
+                    chat_id: Some(chat_id),
+                    message_id: Some(message_id),
+                    inline_message_id: None,
+                    caption: Some(caption),
+                    parse_mode: None,
+                    caption_entities: message.get_entities(),
+                    reply_markup: None,
+                })
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes all messages linked to the given message, and forgets about
+    /// the links. Call this after deleting a bridged message to also delete
+    /// the original it was copied from and all of its other copies.
+    pub async fn propagate_delete(
+        &self,
+        api: &dyn API,
+        chat_id: i64,
+        message_id: i64,
+    ) -> Result<()> {
+        let key = Self::copies_key(chat_id, message_id);
+        for (linked_chat, linked_message) in self.linked_messages(chat_id, message_id).await? {
+            api.delete_message(DeleteMessage {
+                chat_id: linked_chat,
+                message_id: linked_message,
+            })
+            .await?;
+            self.storage
+                .remove(&Self::copies_key(linked_chat, linked_message))
+                .await?;
+        }
+        self.storage.remove(&key).await
+    }
+}
+
+#[async_trait]
+impl Middleware for ForwardingBridge {
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>) {
+        let res = match &update.content {
+            UpdateContent::Message(m) | UpdateContent::ChannelPost(m) => {
+                self.mirror(&**ctx.api, m).await
+            },
+            UpdateContent::EditedMessage(m) | UpdateContent::EditedChannelPost(m) => {
+                self.propagate_edit(&**ctx.api, m).await
+            },
+            _ => Ok(()),
+        };
+        if let Err(e) = res {
+            log::warn!(
+                "forwarding bridge failed to mirror a message: {}",
+                redaction::scrub(e)
+            );
+        }
+
+        next.run(ctx, update).await;
+    }
+}
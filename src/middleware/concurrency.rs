@@ -0,0 +1,106 @@
+use super::{Middleware, Next};
+use crate::{client::Context, model::Update, redaction};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Semaphore;
+
+/// What a [`UserConcurrencyLimit`] does with updates from a user that is
+/// already at their limit of concurrently running handlers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait until one of the running handlers for the user has finished
+    Queue,
+    /// Drop the update without dispatching it
+    Drop,
+}
+
+/// A [`Middleware`] limiting how many updates from the same user get handled
+/// at the same time.
+///
+/// When a user sends a burst of messages, every message would otherwise get
+/// handled in parallel, which can cause races in stateful per-user flows.
+/// Updates that don't belong to a user (like channel posts without a sender)
+/// are not limited.
+pub struct UserConcurrencyLimit {
+    limit: usize,
+    policy: OverflowPolicy,
+    users: Mutex<HashMap<i64, Arc<Semaphore>>>,
+}
+
+impl UserConcurrencyLimit {
+    /// Creates a new limiter allowing `limit` updates per user to be handled
+    /// at the same time, queueing any excess updates
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            policy: OverflowPolicy::Queue,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets what to do with updates exceeding the limit, defaults to
+    /// [`OverflowPolicy::Queue`]
+    #[must_use]
+    pub fn set_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn get_semaphore(&self, user_id: i64) -> Arc<Semaphore> {
+        self.users
+            .lock()
+            .entry(user_id)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone()
+    }
+
+    fn release(&self, user_id: i64, semaphore: Arc<Semaphore>) {
+        let mut users = self.users.lock();
+        drop(semaphore);
+
+        // only the map itself still holds on to it, so nobody is waiting on or
+        // holding a permit for this user anymore
+        if users
+            .get(&user_id)
+            .is_some_and(|s| Arc::strong_count(s) == 1)
+        {
+            users.remove(&user_id);
+        }
+    }
+}
+
+impl Default for UserConcurrencyLimit {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+#[async_trait]
+impl Middleware for UserConcurrencyLimit {
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>) {
+        let user_id = match update.get_user() {
+            Some(user) => user.id,
+            None => return next.run(ctx, update).await,
+        };
+
+        let semaphore = self.get_semaphore(user_id);
+        let permit = match self.policy {
+            OverflowPolicy::Queue => semaphore.clone().acquire_owned().await.ok(),
+            OverflowPolicy::Drop => semaphore.clone().try_acquire_owned().ok(),
+        };
+
+        if let Some(permit) = permit {
+            next.run(ctx, update).await;
+            drop(permit);
+        } else {
+            log::debug!(
+                "dropping update {} from user {}: too many updates being handled",
+                update.update_id,
+                redaction::user(user_id)
+            );
+        }
+
+        self.release(user_id, semaphore);
+    }
+}
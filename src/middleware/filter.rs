@@ -0,0 +1,307 @@
+use super::{Middleware, Next};
+use crate::{
+    api::types::{DeleteMessage, RestrictChatMember, SendMessage},
+    client::{Context, FutureOutcome},
+    model::{Chat, ChatPermissions, Message, Update, UpdateContent, User},
+    redaction,
+    storage::Storage,
+    utils::result::{Result, TelegramError},
+};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use futures::Future;
+use regex::{Regex, RegexBuilder};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A function that gets called whenever a [`KeywordFilter`] catches a
+/// message, after the configured actions have been taken. It receives a
+/// [`Context`] and the [`Violation`] and returns a pinned future.
+pub type ViolationHandlerFunc = Arc<dyn Fn(Context, Violation) -> FutureOutcome + Send + Sync>;
+
+/// Describes a message caught by a [`KeywordFilter`] and the actions taken
+/// because of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The offending message
+    pub message: Message,
+    /// The user who sent the message
+    pub user: User,
+    /// The part of the message that matched one of the filters
+    pub matched: String,
+    /// The amount of strikes the user has in the chat, including this one
+    pub strikes: u32,
+    /// If the message got deleted
+    pub deleted: bool,
+    /// If the user got muted because of reaching the strike limit
+    pub muted: bool,
+}
+
+/// A [`Middleware`] filtering messages on keywords and regular expressions.
+///
+/// Every offending message gives its sender a strike in the chat, stored using
+/// the provided [`Storage`]. Depending on the configuration the message gets
+/// deleted, the user gets warned and, after reaching a set amount of strikes,
+/// muted for some time. Offending messages are not passed on to the handlers.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use telexide::{
+///     middleware::KeywordFilter,
+///     storage::MemoryStorage,
+/// };
+///
+/// # fn main() -> telexide::Result<()> {
+/// let filter = KeywordFilter::new(Arc::new(MemoryStorage::new()))
+///     .add_keyword("spam")
+///     .add_pattern(r"https?://\S+\.example")?
+///     .set_warning("{name}, please mind your language ({strikes}/3)")
+///     .mute_after(3, chrono::Duration::hours(1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct KeywordFilter {
+    patterns: Vec<Regex>,
+    storage: Arc<dyn Storage>,
+    delete_messages: bool,
+    warning: Option<String>,
+    mute: Option<(u32, Duration)>,
+    handlers: Vec<ViolationHandlerFunc>,
+    strikes_lock: Mutex<()>,
+}
+
+impl KeywordFilter {
+    /// Creates a new filter without any keywords, storing the strikes in the
+    /// provided storage. By default offending messages get deleted.
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            patterns: Vec::new(),
+            storage,
+            delete_messages: true,
+            warning: None,
+            mute: None,
+            handlers: Vec::new(),
+            strikes_lock: Mutex::new(()),
+        }
+    }
+
+    /// Adds a keyword to filter on, it is matched case-insensitively as a
+    /// whole word. Ends of the keyword which aren't a letter, digit or
+    /// underscore, like in `c++` or `@spam`, match regardless of what is next
+    /// to them.
+    #[must_use]
+    pub fn add_keyword(mut self, keyword: &str) -> Self {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let boundary = |c: Option<char>| if c.is_some_and(is_word) { r"\b" } else { "" };
+        let pattern = format!(
+            "{}{}{}",
+            boundary(keyword.chars().next()),
+            regex::escape(keyword),
+            boundary(keyword.chars().last())
+        );
+        // escaped keywords always form a valid pattern
+        if let Ok(regex) = RegexBuilder::new(&pattern).case_insensitive(true).build() {
+            self.patterns.push(regex);
+        }
+        self
+    }
+
+    /// Adds a regular expression to filter on
+    pub fn add_pattern(mut self, pattern: &str) -> Result<Self> {
+        self.patterns.push(
+            Regex::new(pattern)
+                .map_err(|e| TelegramError::InvalidArgument(format!("invalid pattern: {e}")))?,
+        );
+        Ok(self)
+    }
+
+    /// Sets whether offending messages get deleted, defaults to true
+    #[must_use]
+    pub fn set_delete_messages(mut self, delete: bool) -> Self {
+        self.delete_messages = delete;
+        self
+    }
+
+    /// Sets the warning to send to the chat when a message got caught.
+    /// `{name}` and `{strikes}` get replaced with the first name of the user and
+    /// their current amount of strikes.
+    #[must_use]
+    pub fn set_warning(mut self, warning: &str) -> Self {
+        self.warning = Some(warning.to_owned());
+        self
+    }
+
+    /// Mutes users for the given duration once they reach the given amount of
+    /// strikes, after which their strikes are reset. Muting only works in
+    /// supergroups.
+    #[must_use]
+    pub fn mute_after(mut self, strikes: u32, duration: Duration) -> Self {
+        self.mute = Some((strikes, duration));
+        self
+    }
+
+    /// Adds a handler to be called for every caught message, see
+    /// [`ViolationHandlerFunc`]
+    #[must_use]
+    pub fn add_violation_handler<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(Context, Violation) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.push(Arc::new(move |ctx, violation| {
+            Box::pin(handler(ctx, violation))
+        }));
+        self
+    }
+
+    /// Returns the first part of the text matching any of the filters
+    pub fn find_match(&self, text: &str) -> Option<String> {
+        self.patterns
+            .iter()
+            .find_map(|p| p.find(text))
+            .map(|m| m.as_str().to_owned())
+    }
+
+    /// adds a strike under the lock, so strikes given at the same time aren't
+    /// lost
+    async fn add_strike(&self, key: &str) -> u32 {
+        let _guard = self.strikes_lock.lock().await;
+        let strikes = match self.storage.get_as::<u32>(key).await {
+            Ok(s) => s.unwrap_or(0) + 1,
+            Err(e) => {
+                log::warn!(
+                    "keyword filter failed to get strikes: {}",
+                    redaction::scrub(e)
+                );
+                1
+            },
+        };
+
+        if let Err(e) = self.storage.set_as(key, &strikes).await {
+            log::warn!(
+                "keyword filter failed to store strikes: {}",
+                redaction::scrub(e)
+            );
+        }
+        strikes
+    }
+
+    async fn mute(&self, ctx: &Context, message: &Message, user: &User, key: &str) -> bool {
+        let duration = match self.mute {
+            Some((limit, duration)) if matches!(message.chat, Chat::SuperGroup(_)) => {
+                match self.storage.get_as::<u32>(key).await {
+                    Ok(Some(strikes)) if strikes >= limit => duration,
+                    _ => return false,
+                }
+            },
+            _ => return false,
+        };
+
+        let mut data =
+            RestrictChatMember::new(message.chat.get_id(), user.id, ChatPermissions::default());
+        data.set_until_date(Utc::now() + duration);
+        let res = ctx.api.restrict_chat_member(data).await;
+
+        match res {
+            Ok(_) => {
+                if let Err(e) = self.storage.remove(key).await {
+                    log::warn!(
+                        "keyword filter failed to reset strikes: {}",
+                        redaction::scrub(e)
+                    );
+                }
+                true
+            },
+            Err(e) => {
+                log::warn!(
+                    "keyword filter failed to mute user {}: {}",
+                    redaction::user(user.id),
+                    redaction::scrub(e)
+                );
+                false
+            },
+        }
+    }
+
+    async fn punish(
+        &self,
+        ctx: &Context,
+        message: Message,
+        user: User,
+        matched: String,
+    ) -> Violation {
+        let key = format!("keyword_filter:{}:{}", message.chat.get_id(), user.id);
+        let strikes = self.add_strike(&key).await;
+
+        let deleted = self.delete_messages
+            && ctx
+                .api
+                .delete_message(DeleteMessage {
+                    chat_id: message.chat.get_id(),
+                    message_id: message.message_id,
+                })
+                .await
+                .map_err(|e| {
+                    log::warn!(
+                        "keyword filter failed to delete message: {}",
+                        redaction::scrub(e)
+                    );
+                })
+                .is_ok();
+
+        let muted = self.mute(ctx, &message, &user, &key).await;
+
+        if let Some(warning) = &self.warning {
+            let text = warning
+                .replace("{name}", &user.first_name)
+                .replace("{strikes}", &strikes.to_string());
+            let mut data = SendMessage::new(message.chat.get_id(), &text);
+            if !deleted {
+                data.reply_to_message(&message);
+            }
+
+            if let Err(e) = ctx.api.send_message(data).await {
+                log::warn!(
+                    "keyword filter failed to send warning: {}",
+                    redaction::scrub(e)
+                );
+            }
+        }
+
+        Violation {
+            message,
+            user,
+            matched,
+            strikes,
+            deleted,
+            muted,
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for KeywordFilter {
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>) {
+        let (message, user, matched) = match &update.content {
+            UpdateContent::Message(m) | UpdateContent::EditedMessage(m) => {
+                match (m.get_text().and_then(|t| self.find_match(&t)), &m.from) {
+                    (Some(matched), Some(user)) => (m.clone(), user.clone(), matched),
+                    _ => return next.run(ctx, update).await,
+                }
+            },
+            _ => return next.run(ctx, update).await,
+        };
+
+        log::debug!(
+            "keyword filter caught message {} from user {}",
+            message.message_id,
+            redaction::user(user.id)
+        );
+
+        let violation = self.punish(&ctx, message, user, matched).await;
+        for h in &self.handlers {
+            h(ctx.clone(), violation.clone()).await;
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use super::{Middleware, Next};
+use crate::{
+    client::Context,
+    model::{utils::unix_date_formatting, Message, MessageContent, Update, UpdateContent},
+    redaction,
+    storage::Storage,
+    utils::result::Result,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, sync::Arc};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+
+/// A message as archived by the [`HistoryCollector`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchivedMessage {
+    /// Identifier of the chat the message belongs to
+    pub chat_id: i64,
+    /// Unique message identifier inside the chat
+    pub message_id: i64,
+    /// Identifier of the sender, empty for messages sent to channels
+    pub user_id: Option<i64>,
+    /// Date the message was sent
+    #[serde(with = "unix_date_formatting")]
+    pub date: DateTime<Utc>,
+    /// Date the message was last edited, only set for edits
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(with = "unix_date_formatting::optional")]
+    pub edit_date: Option<DateTime<Utc>>,
+    /// The text or caption of the message
+    pub text: Option<String>,
+    /// The media attached to the message
+    pub media: Option<ArchivedMedia>,
+}
+
+/// Metadata of a file attached to an [`ArchivedMessage`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchivedMedia {
+    /// The kind of media, for example "photo" or "voice"
+    pub kind: String,
+    /// Identifier for the file, which can be used to download or reuse it
+    pub file_id: String,
+    /// Unique identifier for the file, which is supposed to be the same over
+    /// time and for different bots. Can't be used to download or reuse the
+    /// file.
+    pub file_unique_id: String,
+    /// File size, if known
+    pub file_size: Option<usize>,
+    /// MIME type of the file, if known
+    pub mime_type: Option<String>,
+}
+
+impl From<&Message> for ArchivedMessage {
+    fn from(message: &Message) -> Self {
+        Self {
+            chat_id: message.chat.get_id(),
+            message_id: message.message_id,
+            user_id: message.from.as_ref().map(|u| u.id),
+            date: message.date,
+            edit_date: message.edit_date,
+            text: message.get_text(),
+            media: ArchivedMedia::from_content(&message.content),
+        }
+    }
+}
+
+impl ArchivedMedia {
+    fn new(
+        kind: &str,
+        file_id: &str,
+        file_unique_id: &str,
+        file_size: Option<usize>,
+        mime_type: Option<&String>,
+    ) -> Self {
+        Self {
+            kind: kind.to_owned(),
+            file_id: file_id.to_owned(),
+            file_unique_id: file_unique_id.to_owned(),
+            file_size,
+            mime_type: mime_type.cloned(),
+        }
+    }
+
+    /// Gets the metadata of the media in the message content, if it has any.
+    /// For photos the largest size is used.
+    pub fn from_content(content: &MessageContent) -> Option<Self> {
+        Some(match content {
+            MessageContent::Audio {
+                content: c, ..
+            } => Self::new(
+                "audio",
+                &c.file_id,
+                &c.file_unique_id,
+                c.file_size,
+                c.mime_type.as_ref(),
+            ),
+            MessageContent::Document {
+                content: c, ..
+            } => Self::new(
+                "document",
+                &c.file_id,
+                &c.file_unique_id,
+                c.file_size,
+                c.mime_type.as_ref(),
+            ),
+            MessageContent::Animation {
+                content: c, ..
+            } => Self::new(
+                "animation",
+                &c.file_id,
+                &c.file_unique_id,
+                c.file_size,
+                c.mime_type.as_ref(),
+            ),
+            MessageContent::Video {
+                content: c, ..
+            } => Self::new(
+                "video",
+                &c.file_id,
+                &c.file_unique_id,
+                c.file_size,
+                c.mime_type.as_ref(),
+            ),
+            MessageContent::Voice {
+                content: c, ..
+            } => Self::new(
+                "voice",
+                &c.file_id,
+                &c.file_unique_id,
+                c.file_size,
+                c.mime_type.as_ref(),
+            ),
+            MessageContent::Photo {
+                content, ..
+            } => {
+                let c = content.last()?;
+                Self::new("photo", &c.file_id, &c.file_unique_id, c.file_size, None)
+            },
+            MessageContent::Sticker {
+                content: c,
+            } => Self::new("sticker", &c.file_id, &c.file_unique_id, c.file_size, None),
+            MessageContent::VideoNote {
+                content: c,
+            } => Self::new("video_note", &c.file_id, &c.file_unique_id, c.file_size, None),
+            _ => return None,
+        })
+    }
+}
+
+/// A destination for messages archived by the [`HistoryCollector`], for
+/// example a file or a database.
+#[async_trait]
+pub trait HistorySink: Send + Sync {
+    /// Stores the archived message
+    async fn archive(&self, message: &ArchivedMessage) -> Result<()>;
+}
+
+/// A [`HistorySink`] appending each message as a json object on its own line
+/// to a file
+pub struct JsonLinesSink {
+    file: Mutex<File>,
+}
+
+impl JsonLinesSink {
+    /// Opens the file at the given path for appending, creating it if it
+    /// doesn't exist yet
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+#[async_trait]
+impl HistorySink for JsonLinesSink {
+    async fn archive(&self, message: &ArchivedMessage) -> Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// A [`Middleware`] archiving all messages the bot can see into a
+/// [`HistorySink`], including the metadata and `file_id`s of attached media.
+///
+/// Archiving can be toggled per chat, the toggles are persisted in the
+/// provided [`Storage`]. Chats without a toggle are archived depending on
+/// [`set_enabled_by_default`], which defaults to false, making the collector
+/// opt-in per chat.
+///
+/// [`set_enabled_by_default`]: HistoryCollector::set_enabled_by_default
+pub struct HistoryCollector {
+    sink: Arc<dyn HistorySink>,
+    storage: Arc<dyn Storage>,
+    enabled_by_default: bool,
+}
+
+impl HistoryCollector {
+    /// Creates a new collector archiving into the given sink
+    pub fn new(sink: Arc<dyn HistorySink>, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            sink,
+            storage,
+            enabled_by_default: false,
+        }
+    }
+
+    /// Sets whether chats without a toggle get archived
+    #[must_use]
+    pub fn set_enabled_by_default(mut self, enabled: bool) -> Self {
+        self.enabled_by_default = enabled;
+        self
+    }
+
+    fn toggle_key(chat_id: i64) -> String {
+        format!("history_collector:{chat_id}")
+    }
+
+    /// Enables or disables archiving messages of the given chat
+    pub async fn set_chat_enabled(&self, chat_id: i64, enabled: bool) -> Result<()> {
+        self.storage
+            .set_as(&Self::toggle_key(chat_id), &enabled)
+            .await
+    }
+
+    /// Returns whether messages of the given chat get archived
+    pub async fn is_chat_enabled(&self, chat_id: i64) -> Result<bool> {
+        Ok(self
+            .storage
+            .get_as(&Self::toggle_key(chat_id))
+            .await?
+            .unwrap_or(self.enabled_by_default))
+    }
+
+    async fn collect(&self, message: &Message) -> Result<()> {
+        if self.is_chat_enabled(message.chat.get_id()).await? {
+            self.sink.archive(&message.into()).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Middleware for HistoryCollector {
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>) {
+        match &update.content {
+            UpdateContent::Message(m)
+            | UpdateContent::EditedMessage(m)
+            | UpdateContent::ChannelPost(m)
+            | UpdateContent::EditedChannelPost(m) => {
+                if let Err(e) = self.collect(m).await {
+                    log::warn!(
+                        "history collector failed to archive message: {}",
+                        redaction::scrub(e)
+                    );
+                }
+            },
+            _ => (),
+        }
+
+        next.run(ctx, update).await;
+    }
+}
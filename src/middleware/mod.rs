@@ -0,0 +1,130 @@
+//! Middleware allows you to intercept incoming updates before they get
+//! dispatched to your event handlers and commands.
+//!
+//! Every registered [`Middleware`] receives the [`Context`] and [`Update`]
+//! together with a [`Next`] object. Calling [`Next::run`] passes the update
+//! on to the next middleware in the chain, or to your handlers if it was the
+//! last one. A middleware can thus inspect or alter the update, decide to not
+//! pass it on at all, or do work after the handlers have finished.
+//!
+//! Middleware gets run in the order it was added to the [`Client`]. Besides
+//! implementing [`Middleware`] on a struct, a closure can be turned into one
+//! using [`from_fn`].
+//!
+//! # Examples
+//! ```rust,no_run
+//! use telexide::{
+//!     middleware::UserConcurrencyLimit,
+//!     prelude::*,
+//! };
+//!
+//! # #[tokio::main]
+//! # async fn main() -> telexide::Result<()> {
+//! # let token = "test token";
+//! let client = ClientBuilder::new()
+//!     .set_token(token)
+//!     .add_middleware(UserConcurrencyLimit::new(1))
+//!     .build();
+//!
+//! client.start().await
+//! # }
+//! ```
+//!
+//! [`Client`]: ../client/struct.Client.html
+//! [`Context`]: ../client/struct.Context.html
+//! [`Update`]: ../model/struct.Update.html
+
+mod bridge;
+mod concurrency;
+mod filter;
+mod history;
+mod reactions;
+mod roster;
+
+pub use bridge::{BridgeEndpoint, ForwardingBridge};
+pub use concurrency::{OverflowPolicy, UserConcurrencyLimit};
+pub use filter::{KeywordFilter, Violation, ViolationHandlerFunc};
+pub use history::{ArchivedMedia, ArchivedMessage, HistoryCollector, HistorySink, JsonLinesSink};
+pub use reactions::{ModerationAction, ReactionModeration};
+pub use roster::{MemberRoster, RosterMember};
+
+use crate::{client::Context, model::Update};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::sync::Arc;
+
+/// A layer that gets to handle every incoming update before it gets
+/// dispatched, see the [module-level documentation](index.html) for more
+/// detail.
+#[async_trait]
+pub trait Middleware: Send + Sync + 'static {
+    /// Handles the incoming update, call [`Next::run`] to pass it on to the
+    /// rest of the chain
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>);
+}
+
+pub(crate) type Endpoint<'a> = dyn Fn(Context, Update) -> BoxFuture<'a, ()> + Send + Sync + 'a;
+
+/// Creates a [`Middleware`] from a closure, for layers that don't need a
+/// struct of their own:
+///
+/// ```rust,no_run
+/// use telexide::{middleware, prelude::*};
+///
+/// # fn build(token: &str) -> Client {
+/// ClientBuilder::new()
+///     .set_token(token)
+///     .add_middleware(middleware::from_fn(|ctx, update, next| {
+///         Box::pin(async move {
+///             log::debug!("received update {}", update.update_id);
+///             next.run(ctx, update).await;
+///         })
+///     }))
+///     .build()
+/// # }
+/// ```
+pub fn from_fn<F>(f: F) -> FnMiddleware<F>
+where
+    F: for<'a> Fn(Context, Update, Next<'a>) -> BoxFuture<'a, ()> + Send + Sync + 'static,
+{
+    FnMiddleware(f)
+}
+
+/// A [`Middleware`] created from a closure by [`from_fn`]
+pub struct FnMiddleware<F>(F);
+
+#[async_trait]
+impl<F> Middleware for FnMiddleware<F>
+where
+    F: for<'a> Fn(Context, Update, Next<'a>) -> BoxFuture<'a, ()> + Send + Sync + 'static,
+{
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>) {
+        (self.0)(ctx, update, next).await;
+    }
+}
+
+/// The remainder of the middleware chain, ending with the dispatching of the
+/// update to the event handlers and commands
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn Middleware>],
+    endpoint: &'a Endpoint<'a>,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(middlewares: &'a [Arc<dyn Middleware>], endpoint: &'a Endpoint<'a>) -> Self {
+        Self {
+            middlewares,
+            endpoint,
+        }
+    }
+
+    /// Passes the update on to the next middleware in the chain, resolving
+    /// once it and all of the handlers after it have finished
+    pub async fn run(self, ctx: Context, update: Update) {
+        if let Some((current, rest)) = self.middlewares.split_first() {
+            current.handle(ctx, update, Next::new(rest, self.endpoint)).await;
+        } else {
+            (self.endpoint)(ctx, update).await;
+        }
+    }
+}
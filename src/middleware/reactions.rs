@@ -0,0 +1,172 @@
+use super::{Middleware, Next};
+use crate::{
+    api::types::{DeleteMessage, SendMessage},
+    client::Context,
+    helpers::AdminCache,
+    model::{MessageReactionUpdated, ReactionType, Update, UpdateContent},
+    redaction,
+    storage::Storage,
+    utils::result::Result,
+};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc};
+
+/// An action taken on a message when an admin reacts to it with the emoji the
+/// action is configured for
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModerationAction {
+    /// Deletes the message
+    Delete,
+    /// Replies to the message with the given warning
+    Warn(String),
+    /// Replies to the message with the given warning and then deletes it
+    DeleteAndWarn(String),
+}
+
+/// A [`Middleware`] letting chat administrators moderate by reacting to
+/// messages, for example deleting a message and warning its author when an
+/// admin reacts to it with 🚫.
+///
+/// Reactions of users who aren't an administrator of the chat, according to
+/// the provided [`AdminCache`], are ignored. Moderation can be toggled per
+/// chat, the toggles are persisted in the provided [`Storage`]. Chats without a
+/// toggle are moderated depending on [`set_enabled_by_default`], which
+/// defaults to true.
+///
+/// Telegram only sends reaction updates to bots that are an administrator in
+/// the chat and explicitly list `message_reaction` in their allowed updates.
+/// The updates are always passed on to the handlers.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use telexide::{
+///     helpers::AdminCache,
+///     middleware::{ModerationAction, ReactionModeration},
+///     storage::MemoryStorage,
+/// };
+///
+/// let moderation = ReactionModeration::new(
+///     Arc::new(MemoryStorage::new()),
+///     Arc::new(AdminCache::default()),
+/// )
+/// .add_action(
+///     "🚫",
+///     ModerationAction::DeleteAndWarn("Please follow the chat rules".to_owned()),
+/// );
+/// ```
+///
+/// [`set_enabled_by_default`]: ReactionModeration::set_enabled_by_default
+pub struct ReactionModeration {
+    actions: HashMap<String, ModerationAction>,
+    storage: Arc<dyn Storage>,
+    admins: Arc<AdminCache>,
+    enabled_by_default: bool,
+}
+
+impl ReactionModeration {
+    /// Creates a new moderation middleware without any actions
+    pub fn new(storage: Arc<dyn Storage>, admins: Arc<AdminCache>) -> Self {
+        Self {
+            actions: HashMap::new(),
+            storage,
+            admins,
+            enabled_by_default: true,
+        }
+    }
+
+    /// Sets the action to take when an admin reacts with the given emoji
+    #[must_use]
+    pub fn add_action(mut self, emoji: &str, action: ModerationAction) -> Self {
+        self.actions.insert(emoji.to_owned(), action);
+        self
+    }
+
+    /// Sets whether chats without a toggle get moderated
+    #[must_use]
+    pub fn set_enabled_by_default(mut self, enabled: bool) -> Self {
+        self.enabled_by_default = enabled;
+        self
+    }
+
+    fn toggle_key(chat_id: i64) -> String {
+        format!("reaction_moderation:{chat_id}")
+    }
+
+    /// Enables or disables moderation by reactions in the given chat
+    pub async fn set_chat_enabled(&self, chat_id: i64, enabled: bool) -> Result<()> {
+        self.storage
+            .set_as(&Self::toggle_key(chat_id), &enabled)
+            .await
+    }
+
+    /// Returns whether reactions in the given chat are used for moderation
+    pub async fn is_chat_enabled(&self, chat_id: i64) -> Result<bool> {
+        Ok(self
+            .storage
+            .get_as(&Self::toggle_key(chat_id))
+            .await?
+            .unwrap_or(self.enabled_by_default))
+    }
+
+    /// Returns the action configured for the first newly added reaction which
+    /// has one
+    pub fn find_action(&self, reaction: &MessageReactionUpdated) -> Option<&ModerationAction> {
+        reaction.added_reactions().find_map(|r| match r {
+            ReactionType::Emoji {
+                emoji,
+            } => self.actions.get(emoji),
+            _ => None,
+        })
+    }
+
+    async fn moderate(&self, ctx: &Context, reaction: &MessageReactionUpdated) -> Result<()> {
+        let (Some(action), Some(user)) = (self.find_action(reaction), &reaction.user) else {
+            return Ok(());
+        };
+
+        let chat_id = reaction.chat.get_id();
+        if !self.is_chat_enabled(chat_id).await?
+            || !self.admins.is_admin(&**ctx.api, chat_id, user.id).await?
+        {
+            return Ok(());
+        }
+
+        log::debug!(
+            "admin {} reacted to message {} in chat {}, taking action {action:?}",
+            redaction::user(user.id),
+            reaction.message_id,
+            redaction::chat(chat_id)
+        );
+
+        if let ModerationAction::Warn(warning) | ModerationAction::DeleteAndWarn(warning) = action {
+            let mut data = SendMessage::new(chat_id, warning);
+            data.set_reply_to_message_id(reaction.message_id);
+            ctx.api.send_message(data).await?;
+        }
+
+        if let ModerationAction::Delete | ModerationAction::DeleteAndWarn(_) = action {
+            ctx.api
+                .delete_message(DeleteMessage {
+                    chat_id,
+                    message_id: reaction.message_id,
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Middleware for ReactionModeration {
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>) {
+        if let UpdateContent::MessageReaction(r) = &update.content {
+            if let Err(e) = self.moderate(&ctx, r).await {
+                log::warn!("reaction moderation failed: {}", redaction::scrub(e));
+            }
+        }
+
+        next.run(ctx, update).await;
+    }
+}
@@ -0,0 +1,311 @@
+use super::{Middleware, Next};
+use crate::{
+    client::Context,
+    helpers::formatting::{mention, FormattedText},
+    model::{
+        utils::unix_date_formatting,
+        Chat,
+        Message,
+        MessageContent,
+        Update,
+        UpdateContent,
+        User,
+    },
+    redaction,
+    storage::Storage,
+    utils::result::Result,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, future::Future, sync::Arc};
+use tokio::sync::Mutex;
+
+/// the most users mentioned in one message of [`MemberRoster::mention_all`],
+/// keeping clear of the 100 entities telegram handles in a message
+const MENTIONS_PER_MESSAGE: usize = 50;
+
+/// the most characters telegram allows in the text of a message, in UTF-16
+/// code units
+const MESSAGE_LENGTH: usize = 4096;
+
+/// A user seen in a chat by the [`MemberRoster`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RosterMember {
+    /// The user as last seen
+    pub user: User,
+    /// Whether the user is still in the chat, as far as the roster knows
+    pub is_member: bool,
+    /// When the user was first seen in the chat
+    #[serde(with = "unix_date_formatting")]
+    pub first_seen: DateTime<Utc>,
+    /// When the user last sent a message or their membership changed
+    #[serde(with = "unix_date_formatting")]
+    pub last_seen: DateTime<Utc>,
+    /// The amount of messages the user sent while the roster was watching
+    pub message_count: u64,
+}
+
+impl RosterMember {
+    fn new(user: &User, date: DateTime<Utc>) -> Self {
+        Self {
+            user: user.clone(),
+            is_member: true,
+            first_seen: date,
+            last_seen: date,
+            message_count: 0,
+        }
+    }
+}
+
+/// A [`Middleware`] building the member list of every group the bot is in,
+/// as telegram has no method for bots to list the members of a chat.
+///
+/// Users get added when they send a message or join, and marked as no longer
+/// being a member when they leave or get banned. `chat_member` updates are
+/// only sent to bots which are an administrator and explicitly include
+/// [`UpdateType::ChatMember`] in their allowed updates; without them, members
+/// who never send a message stay unknown.
+///
+/// The rosters are persisted in the provided [`Storage`], with an entry for
+/// every member and a list of the members of every chat. Clones share their
+/// state, so keep a clone (or insert one into the data of the client) to query
+/// the rosters from your handlers.
+///
+/// [`UpdateType::ChatMember`]: ../api/types/enum.UpdateType.html#variant.ChatMember
+#[derive(Clone)]
+pub struct MemberRoster {
+    storage: Arc<dyn Storage>,
+    locks: Arc<parking_lot::Mutex<HashMap<i64, Arc<Mutex<()>>>>>,
+}
+
+impl typemap::Key for MemberRoster {
+    type Value = MemberRoster;
+}
+
+impl MemberRoster {
+    /// Creates a new roster, persisting the members in the given storage
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            locks: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn key(chat_id: i64) -> String {
+        format!("member_roster:{chat_id}")
+    }
+
+    fn member_key(chat_id: i64, user_id: i64) -> String {
+        format!("member_roster:{chat_id}:{user_id}")
+    }
+
+    /// runs the future holding the lock of the chat, so concurrent updates in
+    /// the same chat don't overwrite each other while other chats go on
+    async fn locked<T>(&self, chat_id: i64, fut: impl Future<Output = T>) -> T {
+        let lock = self
+            .locks
+            .lock()
+            .entry(chat_id)
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone();
+        let result = {
+            let _guard = lock.lock().await;
+            fut.await
+        };
+
+        let mut locks = self.locks.lock();
+        drop(lock);
+        // only the map itself still holds on to it, so nobody is waiting on
+        // or holding the lock of this chat anymore
+        if locks
+            .get(&chat_id)
+            .is_some_and(|l| Arc::strong_count(l) == 1)
+        {
+            locks.remove(&chat_id);
+        }
+        result
+    }
+
+    async fn ids(&self, chat_id: i64) -> Result<Vec<i64>> {
+        Ok(self
+            .storage
+            .get_as(&Self::key(chat_id))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// records the user as seen in the chat and applies the change to their
+    /// entry, holding the lock of the chat. Only the entry is stored again, the list of members of the chat
+    /// only when the user is new.
+    async fn seen<F>(&self, chat_id: i64, user: &User, date: DateTime<Utc>, change: F) -> Result<()>
+    where
+        F: FnOnce(&mut RosterMember) + Send,
+    {
+        self.locked(chat_id, async {
+            let key = Self::member_key(chat_id, user.id);
+            let stored: Option<RosterMember> = self.storage.get_as(&key).await?;
+            if stored.is_none() {
+                let mut ids = self.ids(chat_id).await?;
+                ids.push(user.id);
+                self.storage.set_as(&Self::key(chat_id), &ids).await?;
+            }
+
+            let mut member = stored.unwrap_or_else(|| RosterMember::new(user, date));
+            member.user = user.clone();
+            member.last_seen = member.last_seen.max(date);
+            change(&mut member);
+            self.storage.set_as(&key, &member).await
+        })
+        .await
+    }
+
+    /// Every user ever seen in the chat, including the ones who left, ordered
+    /// by when they were first seen
+    pub async fn export(&self, chat_id: i64) -> Result<Vec<RosterMember>> {
+        let mut members = Vec::new();
+        for id in self.ids(chat_id).await? {
+            members.extend(self.member(chat_id, id).await?);
+        }
+        members.sort_by_key(|m| (m.first_seen, m.user.id));
+        Ok(members)
+    }
+
+    /// The users currently in the chat, as far as the roster knows
+    pub async fn members(&self, chat_id: i64) -> Result<Vec<RosterMember>> {
+        let mut members = self.export(chat_id).await?;
+        members.retain(|m| m.is_member);
+        Ok(members)
+    }
+
+    /// The users currently in the chat who were seen since the given date
+    pub async fn active_since(
+        &self,
+        chat_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<RosterMember>> {
+        let mut members = self.members(chat_id).await?;
+        members.retain(|m| m.last_seen >= since);
+        Ok(members)
+    }
+
+    /// The user in the chat, if they were ever seen there
+    pub async fn member(&self, chat_id: i64, user_id: i64) -> Result<Option<RosterMember>> {
+        self.storage
+            .get_as(&Self::member_key(chat_id, user_id))
+            .await
+    }
+
+    /// Texts mentioning every user currently in the chat, except bots. Each
+    /// text fits in a single message, mentioning at most 50 users.
+    pub async fn mention_all(&self, chat_id: i64) -> Result<Vec<FormattedText>> {
+        let mut texts = Vec::new();
+        let mut current = FormattedText::default();
+        let mut length = 0;
+
+        for m in self.members(chat_id).await? {
+            if m.user.is_bot {
+                continue;
+            }
+
+            let name_length = m.user.first_name.encode_utf16().count();
+            if current.entities.len() == MENTIONS_PER_MESSAGE
+                || length + 2 + name_length > MESSAGE_LENGTH
+            {
+                texts.push(std::mem::take(&mut current));
+                length = 0;
+            }
+            if length > 0 {
+                current.push(", ");
+                length += 2;
+            }
+            current.push(mention(m.user.first_name.clone(), &m.user));
+            length += name_length;
+        }
+
+        if length > 0 {
+            texts.push(current);
+        }
+        Ok(texts)
+    }
+
+    /// Forgets the roster of the chat
+    pub async fn clear(&self, chat_id: i64) -> Result<()> {
+        self.locked(chat_id, async {
+            for id in self.ids(chat_id).await? {
+                self.storage.remove(&Self::member_key(chat_id, id)).await?;
+            }
+            self.storage.remove(&Self::key(chat_id)).await
+        })
+        .await
+    }
+
+    async fn observe_message(&self, message: &Message) -> Result<()> {
+        if matches!(message.chat, Chat::Private(_) | Chat::Channel(_)) {
+            return Ok(());
+        }
+
+        let chat_id = message.chat.get_id();
+        let date = message.date;
+        // joins and leaves are service messages, they don't count as messages
+        // of their sender
+        match &message.content {
+            MessageContent::NewChatMembers {
+                content,
+            } => {
+                for user in content {
+                    self.seen(chat_id, user, date, |m| m.is_member = true)
+                        .await?;
+                }
+                Ok(())
+            },
+            MessageContent::LeftChatMember {
+                content,
+            } => {
+                self.seen(chat_id, content, date, |m| m.is_member = false)
+                    .await
+            },
+            _ => {
+                let Some(user) = &message.from else {
+                    return Ok(());
+                };
+                self.seen(chat_id, user, date, |m| {
+                    m.is_member = true;
+                    m.message_count += 1;
+                })
+                .await
+            },
+        }
+    }
+
+    async fn observe(&self, update: &Update) -> Result<()> {
+        match &update.content {
+            UpdateContent::Message(m) => self.observe_message(m).await,
+            UpdateContent::ChatMember(c) => {
+                let is_member = c.new_chat_member.is_member();
+                self.seen(
+                    c.chat.get_id(),
+                    c.new_chat_member.get_user(),
+                    c.date,
+                    move |m| m.is_member = is_member,
+                )
+                .await
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for MemberRoster {
+    async fn handle(&self, ctx: Context, update: Update, next: Next<'_>) {
+        if let Err(e) = self.observe(&update).await {
+            log::warn!(
+                "member roster failed to record an update: {}",
+                redaction::scrub(e)
+            );
+        }
+
+        next.run(ctx, update).await;
+    }
+}
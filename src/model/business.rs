@@ -0,0 +1,23 @@
+use super::{utils::unix_date_formatting, User};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Describes the connection of the bot with a business account.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BusinessConnection {
+    /// Unique identifier of the business connection
+    pub id: String,
+    /// Business account user that created the business connection
+    pub user: User,
+    /// Identifier of a private chat with the user who created the business
+    /// connection
+    pub user_chat_id: i64,
+    /// Date the connection was established
+    #[serde(with = "unix_date_formatting")]
+    pub date: DateTime<Utc>,
+    /// True, if the bot can act on behalf of the business account in chats
+    /// that were active in the last 24 hours
+    pub can_reply: bool,
+    /// True, if the connection is active
+    pub is_enabled: bool,
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use super::{
     raw::RawChat,
-    utils::unix_date_formatting,
+    utils::{id_formatting, unix_date_formatting},
     User,
 };
 
@@ -11,6 +11,7 @@ use super::{
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PrivateChat {
     /// Unique identifier for this chat
+    #[serde(with = "id_formatting")]
     pub id: i64,
     /// Username if available
     pub username: Option<String>,
@@ -31,6 +32,7 @@ pub struct PrivateChat {
 /// A Group chat object
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GroupChat {
+    #[serde(with = "id_formatting")]
     pub id: i64,
     /// Title
     pub title: String,
@@ -52,11 +54,17 @@ pub struct GroupChat {
     ///
     /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
     pub permissions: Option<super::ChatPermissions>,
+    /// True, if messages from the chat can't be forwarded to other chats.
+    /// Returned only in [`get_chat`].
+    ///
+    /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
+    pub has_protected_content: Option<bool>,
 }
 
 /// A supergroup object (a group with more than 200 members)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SuperGroupChat {
+    #[serde(with = "id_formatting")]
     pub id: i64,
     /// Title
     pub title: String,
@@ -80,6 +88,11 @@ pub struct SuperGroupChat {
     ///
     /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
     pub permissions: Option<super::ChatPermissions>,
+    /// True, if messages from the chat can't be forwarded to other chats.
+    /// Returned only in [`get_chat`].
+    ///
+    /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
+    pub has_protected_content: Option<bool>,
     /// The minimum allowed delay between consecutive messages sent by each
     /// unprivileged user. Returned only in [`get_chat`].
     ///
@@ -99,6 +112,8 @@ pub struct SuperGroupChat {
     /// chats. Returned only in [`get_chat`].
     ///
     /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
+    #[serde(default)]
+    #[serde(with = "id_formatting::optional")]
     pub linked_chat_id: Option<i64>,
     /// For supergroups, the location to which the supergroup is connected.
     /// Returned only in [`get_chat`].
@@ -110,6 +125,7 @@ pub struct SuperGroupChat {
 /// A Channel object
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ChannelChat {
+    #[serde(with = "id_formatting")]
     pub id: i64,
     /// Title
     pub title: String,
@@ -129,11 +145,18 @@ pub struct ChannelChat {
     ///
     /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
     pub pinned_message: Option<Box<super::Message>>,
+    /// True, if messages from the chat can't be forwarded to other chats.
+    /// Returned only in [`get_chat`].
+    ///
+    /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
+    pub has_protected_content: Option<bool>,
     /// Unique identifier for the linked chat, i.e. the discussion group
     /// identifier for a channel and vice versa; for supergroups and channel
     /// chats. Returned only in [`get_chat`].
     ///
     /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
+    #[serde(default)]
+    #[serde(with = "id_formatting::optional")]
     pub linked_chat_id: Option<i64>,
 }
 
@@ -164,26 +187,38 @@ pub struct ChatLocation {
 
 /// Describes actions that a non-administrator user is allowed to take in a
 /// chat.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct ChatPermissions {
-    /// True, if the user is allowed to send text messages, contacts, locations
-    /// and venues.
+    /// True, if the user is allowed to send text messages, contacts, giveaways,
+    /// giveaway winners, invoices, locations and venues.
     #[serde(default)]
     pub can_send_messages: bool,
-    /// True, if the user is allowed to send audios, documents, photos, videos,
-    /// video notes and voice notes, implies can_send_messages to be true.
+    /// True, if the user is allowed to send audios.
+    #[serde(default)]
+    pub can_send_audios: bool,
+    /// True, if the user is allowed to send documents.
+    #[serde(default)]
+    pub can_send_documents: bool,
+    /// True, if the user is allowed to send photos.
     #[serde(default)]
-    pub can_send_media_messages: bool,
-    /// True, if the user is allowed to send polls, implies can_send_messages to
-    /// be true.
+    pub can_send_photos: bool,
+    /// True, if the user is allowed to send videos.
+    #[serde(default)]
+    pub can_send_videos: bool,
+    /// True, if the user is allowed to send video notes.
+    #[serde(default)]
+    pub can_send_video_notes: bool,
+    /// True, if the user is allowed to send voice notes.
+    #[serde(default)]
+    pub can_send_voice_notes: bool,
+    /// True, if the user is allowed to send polls.
     #[serde(default)]
     pub can_send_polls: bool,
     /// True, if the user is allowed to send animations, games, stickers and use
-    /// inline bots, implies can_send_media_messages to be true.
+    /// inline bots.
     #[serde(default)]
     pub can_send_other_messages: bool,
-    /// True, if the user is allowed to add web page previews to their messages,
-    /// implies can_send_media_messages to be true.
+    /// True, if the user is allowed to add web page previews to their messages.
     #[serde(default)]
     pub can_add_web_page_previews: bool,
     /// True, if the user is allowed to change the chat title, photo and other
@@ -197,6 +232,51 @@ pub struct ChatPermissions {
     /// supergroups.
     #[serde(default)]
     pub can_pin_messages: bool,
+    /// True, if the user is allowed to create forum topics. If omitted
+    /// defaults to the value of `can_pin_messages`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_manage_topics: Option<bool>,
+}
+
+impl ChatPermissions {
+    /// Permissions allowing everything a non-administrator can be allowed to
+    /// do
+    pub fn all() -> Self {
+        Self {
+            can_send_messages: true,
+            can_send_polls: true,
+            can_send_other_messages: true,
+            can_add_web_page_previews: true,
+            can_change_info: true,
+            can_invite_users: true,
+            can_pin_messages: true,
+            can_manage_topics: Some(true),
+            ..Self::default()
+        }
+        .with_media(true)
+    }
+
+    /// Returns true if the user is allowed to send every kind of media
+    pub fn can_send_media(&self) -> bool {
+        self.can_send_audios
+            && self.can_send_documents
+            && self.can_send_photos
+            && self.can_send_videos
+            && self.can_send_video_notes
+            && self.can_send_voice_notes
+    }
+
+    /// Allows or disallows sending all kinds of media at once
+    #[must_use]
+    pub fn with_media(mut self, allowed: bool) -> Self {
+        self.can_send_audios = allowed;
+        self.can_send_documents = allowed;
+        self.can_send_photos = allowed;
+        self.can_send_videos = allowed;
+        self.can_send_video_notes = allowed;
+        self.can_send_voice_notes = allowed;
+        self
+    }
 }
 
 /// This object represents a chat photo.
@@ -230,6 +310,16 @@ impl Chat {
             Chat::SuperGroup(c) => c.id,
         }
     }
+
+    /// Gets the type of the chat
+    pub fn get_type(&self) -> ChatType {
+        match self {
+            Chat::Private(_) => ChatType::Private,
+            Chat::Channel(_) => ChatType::Channel,
+            Chat::Group(_) => ChatType::Group,
+            Chat::SuperGroup(_) => ChatType::SuperGroup,
+        }
+    }
 }
 
 impl From<RawChat> for Chat {
@@ -243,6 +333,7 @@ impl From<RawChat> for Chat {
                 description: raw.description,
                 pinned_message: raw.pinned_message.map(|m| Box::new((*m).into())),
                 invite_link: raw.invite_link,
+                has_protected_content: raw.has_protected_content,
                 linked_chat_id: raw.linked_chat_id,
             }),
             ChatType::Private => Chat::Private(PrivateChat {
@@ -261,6 +352,7 @@ impl From<RawChat> for Chat {
                 pinned_message: raw.pinned_message.map(|m| Box::new((*m).into())),
                 invite_link: raw.invite_link,
                 permissions: raw.permissions,
+                has_protected_content: raw.has_protected_content,
             }),
             ChatType::SuperGroup => Chat::SuperGroup(SuperGroupChat {
                 id: raw.id,
@@ -271,6 +363,7 @@ impl From<RawChat> for Chat {
                 pinned_message: raw.pinned_message.map(|m| Box::new((*m).into())),
                 invite_link: raw.invite_link,
                 permissions: raw.permissions,
+                has_protected_content: raw.has_protected_content,
                 sticker_set_name: raw.sticker_set_name,
                 can_set_sticker_set: raw.can_set_sticker_set,
                 slow_mode_delay: raw.slow_mode_delay,
@@ -298,6 +391,7 @@ impl From<Chat> for RawChat {
                 pinned_message: None,
                 invite_link: None,
                 permissions: None,
+                has_protected_content: None,
                 sticker_set_name: None,
                 can_set_sticker_set: None,
                 slow_mode_delay: None,
@@ -313,6 +407,7 @@ impl From<Chat> for RawChat {
                 pinned_message: c.pinned_message.map(|m| Box::new((*m).into())),
                 invite_link: c.invite_link,
                 permissions: c.permissions,
+                has_protected_content: c.has_protected_content,
                 username: None,
                 sticker_set_name: None,
                 can_set_sticker_set: None,
@@ -333,6 +428,7 @@ impl From<Chat> for RawChat {
                 pinned_message: c.pinned_message.map(|m| Box::new((*m).into())),
                 invite_link: c.invite_link,
                 permissions: c.permissions,
+                has_protected_content: c.has_protected_content,
                 sticker_set_name: c.sticker_set_name,
                 can_set_sticker_set: c.can_set_sticker_set,
                 slow_mode_delay: c.slow_mode_delay,
@@ -352,6 +448,7 @@ impl From<Chat> for RawChat {
                 pinned_message: c.pinned_message.map(|m| Box::new((*m).into())),
                 invite_link: c.invite_link,
                 linked_chat_id: c.linked_chat_id,
+                has_protected_content: c.has_protected_content,
                 permissions: None,
                 sticker_set_name: None,
                 can_set_sticker_set: None,
@@ -369,16 +466,23 @@ impl From<Chat> for RawChat {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "status")]
 pub enum ChatMember {
+    /// The owner of the chat
     #[serde(rename = "creator")]
     Creator(CreatorMemberStatus),
+    /// An administrator of the chat
     #[serde(rename = "administrator")]
     Administrator(AdministratorMemberStatus),
+    /// A member without any additional privileges or restrictions
     #[serde(rename = "member")]
     Member(MemberMemberStatus),
+    /// A member with restrictions applied
     #[serde(rename = "restricted")]
     Restricted(RestrictedMemberStatus),
+    /// A user who isn't currently a member, but may join it themselves
     #[serde(rename = "left")]
     Left(LeftMemberStatus),
+    /// A user who was banned from the chat and can't return to it or view its
+    /// messages
     #[serde(rename = "kicked")]
     Kicked(KickedMemberStatus),
 }
@@ -417,22 +521,52 @@ pub struct AdministratorMemberStatus {
     /// Implied by any other administrator privilege
     #[serde(default)]
     pub can_manage_chat: bool,
+    /// True, if the administrator can delete messages of other users
+    #[serde(default)]
+    pub can_delete_messages: bool,
+    /// True, if the administrator can manage video chats
+    #[serde(default, alias = "can_manage_voice_chats")]
+    pub can_manage_video_chats: bool,
+    /// True, if the administrator can restrict, ban or unban chat members, or
+    /// access supergroup statistics
+    #[serde(default)]
+    pub can_restrict_members: bool,
+    /// True, if the administrator can add new administrators with a subset of
+    /// their own privileges or demote administrators that they have promoted,
+    /// directly or indirectly
+    #[serde(default)]
+    pub can_promote_members: bool,
+    /// True, if the user is allowed to change the chat title, photo and other
+    /// settings
+    #[serde(default)]
+    pub can_change_info: bool,
+    /// True, if the user is allowed to invite new users to the chat
+    #[serde(default)]
+    pub can_invite_users: bool,
     /// True, if the administrator can post in the channel; channels only
     #[serde(default)]
-    pub can_send_media_messages: bool,
-    /// True, if the user is allowed to send polls
+    pub can_post_messages: bool,
+    /// True, if the administrator can edit messages of other users and can pin
+    /// messages; channels only
     #[serde(default)]
-    pub can_send_polls: bool,
-    /// True, if the user is allowed to send animations, games, stickers and use
-    /// inline bots
+    pub can_edit_messages: bool,
+    /// True, if the user is allowed to pin messages; groups and supergroups
+    /// only
     #[serde(default)]
-    pub can_send_other_messages: bool,
-    /// True, if the user is allowed to add web page previews to their messages
+    pub can_pin_messages: bool,
+    /// True, if the user is allowed to create, rename, close, and reopen forum
+    /// topics; supergroups only
     #[serde(default)]
-    pub can_add_web_page_previews: bool,
-    /// True, if the administrator can manage voice chats
+    pub can_manage_topics: bool,
+    /// True, if the administrator can post stories to the chat
     #[serde(default)]
-    pub can_manage_voice_chats: bool,
+    pub can_post_stories: bool,
+    /// True, if the administrator can edit stories posted by other users
+    #[serde(default)]
+    pub can_edit_stories: bool,
+    /// True, if the administrator can delete stories posted by other users
+    #[serde(default)]
+    pub can_delete_stories: bool,
 }
 
 /// Represents a [`ChatMember`] who is a normal member of the [`Chat`] without
@@ -441,6 +575,13 @@ pub struct AdministratorMemberStatus {
 pub struct MemberMemberStatus {
     /// Information about the user
     pub user: User,
+    /// Date when the user's subscription will expire
+    #[serde(
+        default,
+        with = "unix_date_formatting::optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub until_date: Option<DateTime<Utc>>,
 }
 
 /// Represents a restricted [`ChatMember`] of a [`Chat`].
@@ -465,14 +606,28 @@ pub struct RestrictedMemberStatus {
     /// True, if the user is a member of the chat at the moment of the request
     #[serde(default)]
     pub is_member: bool,
-    /// True, if the user is allowed to send text messages, contacts, locations
-    /// and venues
+    /// True, if the user is allowed to send text messages, contacts,
+    /// giveaways, giveaway winners, invoices, locations and venues
     #[serde(default)]
     pub can_send_messages: bool,
-    /// True, if the user is allowed to send audios, documents, photos, videos,
-    /// video notes and voice notes
+    /// True, if the user is allowed to send audios
+    #[serde(default)]
+    pub can_send_audios: bool,
+    /// True, if the user is allowed to send documents
     #[serde(default)]
-    pub can_send_media_messages: bool,
+    pub can_send_documents: bool,
+    /// True, if the user is allowed to send photos
+    #[serde(default)]
+    pub can_send_photos: bool,
+    /// True, if the user is allowed to send videos
+    #[serde(default)]
+    pub can_send_videos: bool,
+    /// True, if the user is allowed to send video notes
+    #[serde(default)]
+    pub can_send_video_notes: bool,
+    /// True, if the user is allowed to send voice notes
+    #[serde(default)]
+    pub can_send_voice_notes: bool,
     /// True, if the user is allowed to send polls
     #[serde(default)]
     pub can_send_polls: bool,
@@ -483,6 +638,34 @@ pub struct RestrictedMemberStatus {
     /// True, if the user is allowed to add web page previews to their messages
     #[serde(default)]
     pub can_add_web_page_previews: bool,
+    /// True, if the user is allowed to create forum topics
+    #[serde(default)]
+    pub can_manage_topics: bool,
+}
+
+impl RestrictedMemberStatus {
+    /// The permissions of the restricted user, e.g. for restoring them later
+    /// using [`restrict_chat_member`]
+    ///
+    /// [`restrict_chat_member`]: ../api/trait.API.html#method.restrict_chat_member
+    pub fn permissions(&self) -> ChatPermissions {
+        ChatPermissions {
+            can_send_messages: self.can_send_messages,
+            can_send_audios: self.can_send_audios,
+            can_send_documents: self.can_send_documents,
+            can_send_photos: self.can_send_photos,
+            can_send_videos: self.can_send_videos,
+            can_send_video_notes: self.can_send_video_notes,
+            can_send_voice_notes: self.can_send_voice_notes,
+            can_send_polls: self.can_send_polls,
+            can_send_other_messages: self.can_send_other_messages,
+            can_add_web_page_previews: self.can_add_web_page_previews,
+            can_change_info: self.can_change_info,
+            can_invite_users: self.can_invite_users,
+            can_pin_messages: self.can_pin_messages,
+            can_manage_topics: Some(self.can_manage_topics),
+        }
+    }
 }
 
 /// Represents a [`ChatMember`] who left the [`Chat`].
@@ -503,6 +686,21 @@ pub struct KickedMemberStatus {
 }
 
 impl ChatMember {
+    /// Returns true if the member is the owner or an administrator of the chat
+    pub fn is_admin(&self) -> bool {
+        matches!(self, ChatMember::Creator(_) | ChatMember::Administrator(_))
+    }
+
+    /// Returns true if the user is currently in the chat, including when they
+    /// are restricted but still a member
+    pub fn is_member(&self) -> bool {
+        match self {
+            ChatMember::Creator(_) | ChatMember::Administrator(_) | ChatMember::Member(_) => true,
+            ChatMember::Restricted(m) => m.is_member,
+            ChatMember::Left(_) | ChatMember::Kicked(_) => false,
+        }
+    }
+
     /// Retrieves the underlying [`User`] of the [`ChatMember`].
     pub fn get_user(&self) -> &User {
         match self {
@@ -557,7 +755,7 @@ pub struct ChatMemberUpdated {
 }
 
 /// The type of chat
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ChatType {
     #[serde(rename = "private")]
     Private,
@@ -28,10 +28,6 @@ pub struct Game {
     pub animation: Option<Animation>,
 }
 
-/// A placeholder, currently holds no information.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct CallbackGame {}
-
 /// This object represents one row of the high scores table for a game.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GameHighScore {
@@ -1,4 +1,4 @@
-use super::{CallbackGame, LoginUrl};
+use super::LoginUrl;
 use serde::{Deserialize, Serialize};
 
 /// This object represents an [inline keyboard] that appears right next to the
@@ -193,3 +193,7 @@ pub struct KeyboardButtonPollType {
     #[serde(rename = "type")]
     pub poll_type: super::PollType,
 }
+
+/// A placeholder, currently holds no information.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CallbackGame {}
@@ -19,6 +19,9 @@ use super::{
 pub struct Message {
     /// Unique message identifier inside this chat
     pub message_id: i64,
+    /// Unique identifier of the message thread or forum topic the message
+    /// belongs to; for supergroups only
+    pub message_thread_id: Option<i64>,
     /// Sender, empty for messages sent to channels
     pub from: Option<super::User>,
     /// Sender of the message, sent on behalf of a chat. The channel itself for
@@ -35,12 +38,16 @@ pub struct Message {
     pub forward_data: Option<ForwardData>,
 
     pub reply_to_message: Option<Box<Message>>,
+    /// For replies to a story, the original story
+    pub reply_to_story: Option<Story>,
     /// Bot through which the message was sent
     pub via_bot: Option<User>,
     /// Date the message was last edited in Unix time
     pub edit_date: Option<DateTime<Utc>>,
     /// Signature of the post author for messages in channels
     pub author_signature: Option<String>,
+    /// True, if the message can't be forwarded
+    pub has_protected_content: bool,
 
     /// The content of the message
     pub content: MessageContent,
@@ -113,6 +120,15 @@ pub enum MessageContent {
         /// appear in the caption
         caption_entities: Option<Vec<MessageEntity>>,
     },
+    PaidMedia {
+        /// Information about the paid media
+        content: PaidMediaInfo,
+        /// The caption, 0-1024 characters
+        caption: Option<String>,
+        /// Special entities like usernames, URLs, bot commands, etc. that
+        /// appear in the caption
+        caption_entities: Option<Vec<MessageEntity>>,
+    },
     Photo {
         /// Available sizes of the photo
         content: Vec<PhotoSize>,
@@ -125,7 +141,6 @@ pub enum MessageContent {
         /// to
         media_group_id: Option<String>,
     },
-
     Game {
         /// Information about the game
         content: Game,
@@ -158,6 +173,10 @@ pub enum MessageContent {
         /// a dice with a random value from 1 to 6
         content: Dice,
     },
+    Story {
+        /// A forwarded story
+        content: Story,
+    },
     NewChatMembers {
         /// New members that were added to the group or supergroup and
         /// information about them (the bot itself may be one of these
@@ -273,6 +292,47 @@ pub struct ForwardData {
 }
 
 impl Message {
+    /// Returns whether the message was edited, which is the case for messages
+    /// of `edited_message` and `edited_channel_post` updates
+    pub fn is_edit(&self) -> bool {
+        self.edit_date.is_some()
+    }
+
+    /// Returns whether the message was posted in a channel
+    pub fn is_channel_post(&self) -> bool {
+        matches!(self.chat, super::Chat::Channel(_))
+    }
+
+    /// Gets the signature of the author of the message: the author signature
+    /// of channel posts and of messages by anonymous group administrators, or
+    /// the signature of the original post for forwarded channel posts
+    pub fn signature(&self) -> Option<&str> {
+        self.author_signature
+            .as_deref()
+            .or_else(|| self.forward_data.as_ref()?.signature.as_deref())
+    }
+
+    /// Gets the name to attribute the message to: its [`signature`], the
+    /// title of the chat it was sent on behalf of, or the name of the user who
+    /// sent it
+    ///
+    /// [`signature`]: Message::signature
+    pub fn author_name(&self) -> Option<String> {
+        if let Some(signature) = self.signature() {
+            return Some(signature.to_owned());
+        }
+        match &self.sender_chat {
+            Some(super::Chat::Channel(c)) => return Some(c.title.clone()),
+            Some(super::Chat::SuperGroup(c)) => return Some(c.title.clone()),
+            Some(super::Chat::Group(c)) => return Some(c.title.clone()),
+            Some(super::Chat::Private(_)) | None => {},
+        }
+        self.from.as_ref().map(|user| match &user.last_name {
+            Some(last_name) => format!("{} {last_name}", user.first_name),
+            None => user.first_name.clone(),
+        })
+    }
+
     pub fn get_text(&self) -> Option<String> {
         match self.content {
             MessageContent::Text {
@@ -295,24 +355,61 @@ impl Message {
             }
             | MessageContent::Photo {
                 ref caption, ..
+            }
+            | MessageContent::PaidMedia {
+                ref caption, ..
             } => caption.clone(),
             _ => None,
         }
     }
+
+    /// Gets the entities of the text or caption of the message, if it has any
+    pub fn get_entities(&self) -> Option<Vec<MessageEntity>> {
+        match self.content {
+            MessageContent::Text {
+                ref entities, ..
+            } => Some(entities.clone()),
+            MessageContent::Audio {
+                ref caption_entities, ..
+            }
+            | MessageContent::Document {
+                ref caption_entities, ..
+            }
+            | MessageContent::Animation {
+                ref caption_entities, ..
+            }
+            | MessageContent::Video {
+                ref caption_entities, ..
+            }
+            | MessageContent::Voice {
+                ref caption_entities, ..
+            }
+            | MessageContent::Photo {
+                ref caption_entities, ..
+            }
+            | MessageContent::PaidMedia {
+                ref caption_entities, ..
+            } => caption_entities.clone(),
+            _ => None,
+        }
+    }
 }
 
 impl From<RawMessage> for Message {
     #[allow(clippy::too_many_lines)]
     fn from(raw: RawMessage) -> Message {
         let message_id = raw.message_id;
+        let message_thread_id = raw.message_thread_id;
         let from = raw.from;
         let sender_chat = raw.sender_chat.map(|c| c.into());
         let date = raw.date;
         let chat = raw.chat.into();
         let reply_to_message = raw.reply_to_message.map(|r| Box::new((*r).into()));
+        let reply_to_story = raw.reply_to_story;
         let via_bot = raw.via_bot;
         let edit_date = raw.edit_date;
         let author_signature = raw.author_signature;
+        let has_protected_content = raw.has_protected_content;
         let connected_website = raw.connected_website;
         let passport_data = raw.passport_data;
         let reply_markup = raw.reply_markup;
@@ -332,15 +429,18 @@ impl From<RawMessage> for Message {
 
         let fill_in_content = |content: MessageContent| Self {
             message_id,
+            message_thread_id,
             from,
             sender_chat,
             date,
             chat,
             forward_data,
             reply_to_message,
+            reply_to_story,
             via_bot,
             edit_date,
             author_signature,
+            has_protected_content,
             content,
             connected_website,
             passport_data,
@@ -406,7 +506,7 @@ impl From<RawMessage> for Message {
         content_with_captions!(raw.animation, Animation);
         content_with_captions!(raw.document, Document);
         content_with_captions!(raw.voice, Voice);
-
+        content_with_captions!(raw.paid_media, PaidMedia);
         content!(raw.game, Game);
         content!(raw.sticker, Sticker);
         content!(raw.video_note, VideoNote);
@@ -415,6 +515,7 @@ impl From<RawMessage> for Message {
         content!(raw.venue, Venue);
         content!(raw.poll, Poll);
         content!(raw.dice, Dice);
+        content!(raw.story, Story);
         content!(raw.new_chat_members, NewChatMembers);
         content!(raw.left_chat_member, LeftChatMember);
         content!(raw.new_chat_title, NewChatTitle);
@@ -450,15 +551,18 @@ impl From<Message> for RawMessage {
     fn from(message: Message) -> RawMessage {
         let mut ret = Self {
             message_id: message.message_id,
+            message_thread_id: message.message_thread_id,
             from: message.from,
             sender_chat: message.sender_chat.map(|c| c.into()),
             date: message.date,
             chat: message.chat.into(),
             reply_to_message: message.reply_to_message.map(|r| Box::new((*r).into())),
+            reply_to_story: message.reply_to_story,
             via_bot: message.via_bot,
             edit_date: message.edit_date,
             media_group_id: None,
             author_signature: message.author_signature,
+            has_protected_content: message.has_protected_content,
 
             forward_date: None,
             forward_sender_name: None,
@@ -479,12 +583,14 @@ impl From<Message> for RawMessage {
             video: None,
             voice: None,
             video_note: None,
+            paid_media: None,
             caption: None,
             contact: None,
             location: None,
             venue: None,
             poll: None,
             dice: None,
+            story: None,
             new_chat_members: None,
             left_chat_member: None,
             new_chat_title: None,
@@ -548,6 +654,16 @@ impl From<Message> for RawMessage {
                 ret.caption_entities = caption_entities;
                 ret
             },
+            MessageContent::PaidMedia {
+                content,
+                caption,
+                caption_entities,
+            } => {
+                ret.paid_media = Some(content);
+                ret.caption = caption;
+                ret.caption_entities = caption_entities;
+                ret
+            },
             MessageContent::Animation {
                 content,
                 caption,
@@ -640,6 +756,12 @@ impl From<Message> for RawMessage {
                 ret.dice = Some(content);
                 ret
             },
+            MessageContent::Story {
+                content,
+            } => {
+                ret.story = Some(content);
+                ret
+            },
             MessageContent::NewChatMembers {
                 content,
             } => {
@@ -1,4 +1,7 @@
-use super::{utils::unix_date_formatting, User};
+use super::{
+    utils::{id_formatting, unix_date_formatting},
+    User,
+};
 use crate::model::MessageEntity;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -171,6 +174,8 @@ pub struct Contact {
     /// Contact's last name
     pub last_name: Option<String>,
     /// Contact's user identifier in Telegram
+    #[serde(default)]
+    #[serde(with = "id_formatting::optional")]
     pub user_id: Option<i64>,
     /// Additional data about the contact in the form of a [vCard]
     ///
@@ -274,6 +279,139 @@ pub struct Dice {
     pub value: u8,
 }
 
+impl Dice {
+    /// The emoji the dice is based on, or `None` for emoji telexide doesn't
+    /// know about yet
+    pub fn kind(&self) -> Option<DiceEmoji> {
+        DiceEmoji::from_emoji(&self.emoji)
+    }
+
+    /// Whether the throw is the best possible outcome for its emoji: a six, a
+    /// bullseye, a strike, or three of the same symbol on the slot machine
+    pub fn is_win(&self) -> bool {
+        match self.kind() {
+            Some(DiceEmoji::Basketball | DiceEmoji::Football) => self.is_score(),
+            Some(DiceEmoji::SlotMachine) => {
+                self.slot_reels().is_some_and(|[a, b, c]| a == b && b == c)
+            },
+            Some(kind) => self.value == kind.max_value(),
+            None => false,
+        }
+    }
+
+    /// Whether the ball went in, for basketball and football throws
+    pub fn is_score(&self) -> bool {
+        match self.kind() {
+            Some(DiceEmoji::Basketball) => self.value >= 4,
+            Some(DiceEmoji::Football) => self.value >= 3,
+            _ => false,
+        }
+    }
+
+    /// Whether the slot machine shows three sevens
+    pub fn is_jackpot(&self) -> bool {
+        self.kind() == Some(DiceEmoji::SlotMachine) && self.value == 64
+    }
+
+    /// The symbols on the reels of the slot machine, from left to right.
+    /// Returns `None` for other emoji.
+    pub fn slot_reels(&self) -> Option<[SlotSymbol; 3]> {
+        if self.kind() != Some(DiceEmoji::SlotMachine) || !(1..=64).contains(&self.value) {
+            return None;
+        }
+
+        // the value - 1 holds the reels as base 4 digits, leftmost reel first
+        let value = self.value - 1;
+        Some([value % 4, value / 4 % 4, value / 16].map(SlotSymbol::from_digit))
+    }
+}
+
+/// This object represents a story, bots only get to know the chat which
+/// posted it and its identifier
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Story {
+    /// Chat that posted the story
+    pub chat: super::Chat,
+    /// Unique identifier for the story in the chat
+    pub id: i64,
+}
+
+/// The emoji a [`Dice`] can be based on
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiceEmoji {
+    #[serde(rename = "🎲")]
+    Dice,
+    #[serde(rename = "🎯")]
+    Darts,
+    #[serde(rename = "🏀")]
+    Basketball,
+    #[serde(rename = "⚽")]
+    Football,
+    #[serde(rename = "🎰")]
+    SlotMachine,
+    #[serde(rename = "🎳")]
+    Bowling,
+}
+
+impl DiceEmoji {
+    /// The emoji itself
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dice => "🎲",
+            Self::Darts => "🎯",
+            Self::Basketball => "🏀",
+            Self::Football => "⚽",
+            Self::SlotMachine => "🎰",
+            Self::Bowling => "🎳",
+        }
+    }
+
+    /// Gets the variant of the emoji, if it's one a dice can be based on
+    pub fn from_emoji(emoji: &str) -> Option<Self> {
+        [
+            Self::Dice,
+            Self::Darts,
+            Self::Basketball,
+            Self::Football,
+            Self::SlotMachine,
+            Self::Bowling,
+        ]
+        .iter()
+        .copied()
+        .find(|e| e.as_str() == emoji)
+    }
+
+    /// The highest value a dice based on the emoji can have, the lowest is
+    /// always 1
+    pub fn max_value(self) -> u8 {
+        match self {
+            Self::Dice | Self::Darts | Self::Bowling => 6,
+            Self::Basketball | Self::Football => 5,
+            Self::SlotMachine => 64,
+        }
+    }
+}
+
+/// A symbol on a reel of the slot machine, see [`Dice::slot_reels`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlotSymbol {
+    Bar,
+    Grapes,
+    Lemon,
+    Seven,
+}
+
+impl SlotSymbol {
+    fn from_digit(digit: u8) -> Self {
+        match digit {
+            0 => Self::Bar,
+            1 => Self::Grapes,
+            2 => Self::Lemon,
+            _ => Self::Seven,
+        }
+    }
+}
+
 /// This object contains information about one answer option in a poll.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PollOption {
@@ -392,3 +530,41 @@ pub struct MessageAutoDeleteTimerChanged {
     /// New auto-delete time for messages in the chat
     pub message_auto_delete_time: i64,
 }
+
+/// Describes the paid media added to a message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PaidMediaInfo {
+    /// The number of Telegram Stars that must be paid to buy access to the
+    /// media
+    pub star_count: i64,
+    /// Information about the paid media
+    pub paid_media: Vec<PaidMedia>,
+}
+
+/// This object describes paid media.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum PaidMedia {
+    /// The paid media isn't available before the payment
+    #[serde(rename = "preview")]
+    Preview {
+        /// Media width as defined by the sender
+        width: Option<i64>,
+        /// Media height as defined by the sender
+        height: Option<i64>,
+        /// Duration of the media in seconds as defined by the sender
+        duration: Option<i64>,
+    },
+    /// The paid media is a photo
+    #[serde(rename = "photo")]
+    Photo {
+        /// The photo
+        photo: Vec<PhotoSize>,
+    },
+    /// The paid media is a video
+    #[serde(rename = "video")]
+    Video {
+        /// The video
+        video: Video,
+    },
+}
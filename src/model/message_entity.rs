@@ -52,6 +52,9 @@ pub enum MessageEntity {
     /// A mention of users without usernames
     #[serde(rename = "text_mention")]
     TextMention(TextMention),
+    /// An inline custom emoji sticker
+    #[serde(rename = "custom_emoji")]
+    CustomEmoji(CustomEmoji),
 }
 
 /// A monowidth code block
@@ -83,3 +86,16 @@ pub struct TextMention {
     /// The mentioned user
     pub user: User,
 }
+
+/// An inline custom emoji sticker, which can be resolved with
+/// [`get_custom_emoji_stickers`]
+///
+/// [`get_custom_emoji_stickers`]: ../api/trait.API.html#method.get_custom_emoji_stickers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomEmoji {
+    /// The part of the text that is replaced by the custom emoji
+    #[serde(flatten)]
+    pub text_block: TextBlock,
+    /// Unique identifier of the custom emoji
+    pub custom_emoji_id: String,
+}
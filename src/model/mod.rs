@@ -2,6 +2,7 @@
 
 pub(crate) mod utils;
 
+mod business;
 mod chat;
 mod games;
 mod inline;
@@ -12,11 +13,13 @@ mod message_entity;
 mod other;
 mod payments;
 pub mod raw;
+mod reactions;
 mod stickers;
 mod telegram_passport;
 mod update;
 mod user;
 
+pub use business::*;
 pub use chat::*;
 pub use games::*;
 pub use inline::*;
@@ -26,7 +29,9 @@ pub use message_contents::*;
 pub use message_entity::*;
 pub use other::*;
 pub use payments::*;
+pub use reactions::*;
 pub use stickers::*;
 pub use telegram_passport::*;
 pub use update::*;
 pub use user::*;
+pub use utils::StringIds;
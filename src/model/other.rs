@@ -32,7 +32,7 @@ pub struct CallbackQuery {
     pub message: Option<Message>,
     /// Identifier of the message sent via the bot in inline mode, that
     /// originated the query.
-    pub inline_message_id: Option<Message>,
+    pub inline_message_id: Option<String>,
     /// Global identifier, uniquely corresponding to the chat to which the
     /// message with the callback button was sent. Useful for high scores in [games](https://core.telegram.org/bots/api#games).
     pub chat_instance: String,
@@ -55,6 +55,27 @@ pub struct BotCommand {
     pub description: String,
 }
 
+/// Represents the bot's name
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BotName {
+    /// The bot's name
+    pub name: String,
+}
+
+/// Represents the bot's description
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BotDescription {
+    /// The bot's description
+    pub description: String,
+}
+
+/// Represents the bot's short description
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BotShortDescription {
+    /// The bot's short description
+    pub short_description: String,
+}
+
 /// The Bot API supports basic formatting for messages.
 /// You can use bold, italic, underlined and strikethrough text, as well as
 /// inline links and pre-formatted code in your bots' messages. Telegram clients
@@ -155,11 +176,17 @@ pub struct WebhookInfo {
     pub pending_update_count: i64,
     /// Unix time for the most recent error that happened when trying to deliver
     /// an update via webhook
+    #[serde(default)]
     #[serde(with = "unix_date_formatting::optional")]
     pub last_error_date: Option<DateTime<Utc>>,
     /// Error message in human-readable format for the most recent error that
     /// happened when trying to deliver an update via webhook
     pub last_error_message: Option<String>,
+    /// Unix time of the most recent error that happened when trying to
+    /// synchronize available updates with Telegram datacenters
+    #[serde(default)]
+    #[serde(with = "unix_date_formatting::optional")]
+    pub last_synchronization_error_date: Option<DateTime<Utc>>,
     /// Maximum allowed number of simultaneous HTTPS connections to the webhook
     /// for update delivery
     pub max_connections: Option<i64>,
@@ -169,3 +196,14 @@ pub struct WebhookInfo {
     /// Currently used webhook IP address
     pub ip_address: Option<String>,
 }
+
+/// Describes why a request was unsuccessful
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResponseParameters {
+    /// The group has been migrated to a supergroup with the specified
+    /// identifier
+    pub migrate_to_chat_id: Option<i64>,
+    /// In case of exceeding flood control, the number of seconds left to wait
+    /// before the request can be repeated
+    pub retry_after: Option<i64>,
+}
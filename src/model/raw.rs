@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 use super::{
     message_contents::*,
     message_entity::*,
-    utils::unix_date_formatting,
+    utils::{id_formatting, unix_date_formatting},
+    BusinessConnection,
     ChatType,
     CallbackQuery,
     ChatLocation,
@@ -15,6 +16,7 @@ use super::{
     InlineKeyboardMarkup,
     InlineQuery,
     Invoice,
+    MessageReactionUpdated,
     PassportData,
     PreCheckoutQuery,
     ShippingQuery,
@@ -29,6 +31,7 @@ use super::{
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RawMessage {
     pub message_id: i64,
+    pub message_thread_id: Option<i64>,
     pub from: Option<super::User>,
     pub sender_chat: Option<RawChat>,
     #[serde(with = "unix_date_formatting")]
@@ -45,6 +48,7 @@ pub struct RawMessage {
     pub forward_date: Option<DateTime<Utc>>,
 
     pub reply_to_message: Option<Box<RawMessage>>,
+    pub reply_to_story: Option<Story>,
     pub via_bot: Option<User>,
 
     #[serde(default)]
@@ -53,6 +57,8 @@ pub struct RawMessage {
 
     pub media_group_id: Option<String>,
     pub author_signature: Option<String>,
+    #[serde(default)]
+    pub has_protected_content: bool,
 
     pub text: Option<String>,
     pub entities: Option<Vec<MessageEntity>>,
@@ -66,12 +72,14 @@ pub struct RawMessage {
     pub video: Option<Video>,
     pub voice: Option<Voice>,
     pub video_note: Option<VideoNote>,
+    pub paid_media: Option<PaidMediaInfo>,
     pub caption: Option<String>,
     pub contact: Option<Contact>,
     pub location: Option<Location>,
     pub venue: Option<Venue>,
     pub poll: Option<Poll>,
     pub dice: Option<Dice>,
+    pub story: Option<Story>,
     pub new_chat_members: Option<Vec<User>>,
     pub left_chat_member: Option<User>,
     pub new_chat_title: Option<String>,
@@ -88,7 +96,11 @@ pub struct RawMessage {
 
     pub message_auto_delete_timer_changed: Option<MessageAutoDeleteTimerChanged>,
 
+    #[serde(default)]
+    #[serde(with = "id_formatting::optional")]
     pub migrate_to_chat_id: Option<i64>,
+    #[serde(default)]
+    #[serde(with = "id_formatting::optional")]
     pub migrate_from_chat_id: Option<i64>,
 
     pub pinned_message: Option<Box<RawMessage>>,
@@ -112,6 +124,7 @@ pub struct RawMessage {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RawChat {
     /// Unique identifier for this chat
+    #[serde(with = "id_formatting")]
     pub id: i64,
     #[serde(rename = "type")]
     pub chat_type: ChatType,
@@ -161,6 +174,11 @@ pub struct RawChat {
     ///
     /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
     pub can_set_sticker_set: Option<bool>,
+    /// True, if messages from the chat can't be forwarded to other chats.
+    /// Returned only in [`get_chat`].
+    ///
+    /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
+    pub has_protected_content: Option<bool>,
     /// Unique identifier for the linked chat, i.e. the discussion group
     /// identifier for a channel and vice versa; for supergroups and channel
     /// chats. This identifier may be greater than 32 bits and some
@@ -170,6 +188,8 @@ pub struct RawChat {
     /// Returned only in [`get_chat`].
     ///
     /// [`get_chat`]: ../../api/trait.API.html#method.get_chat
+    #[serde(default)]
+    #[serde(with = "id_formatting::optional")]
     pub linked_chat_id: Option<i64>,
     /// For supergroups, the location to which the supergroup is connected.
     /// Returned only in [`get_chat`].
@@ -222,4 +242,11 @@ pub struct RawUpdate {
     /// administrator in the chat and must explicitly specify “chat_member”
     /// in the list of allowed_updates to receive these updates.
     pub chat_member: Option<ChatMemberUpdated>,
+    /// The bot was connected to or disconnected from a business account, or a
+    /// user edited an existing connection with the bot.
+    pub business_connection: Option<BusinessConnection>,
+    /// A reaction to a message was changed by a user. The bot must be an
+    /// administrator in the chat and must explicitly specify `message_reaction`
+    /// in the list of `allowed_updates` to receive these updates.
+    pub message_reaction: Option<MessageReactionUpdated>,
 }
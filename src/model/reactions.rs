@@ -0,0 +1,54 @@
+use super::{utils::unix_date_formatting, Chat, User};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The type of a reaction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "type")]
+pub enum ReactionType {
+    /// The reaction is based on an emoji
+    #[serde(rename = "emoji")]
+    Emoji {
+        /// Reaction emoji
+        emoji: String,
+    },
+    /// The reaction is based on a custom emoji
+    #[serde(rename = "custom_emoji")]
+    CustomEmoji {
+        /// Custom emoji identifier
+        custom_emoji_id: String,
+    },
+    /// The reaction is paid
+    #[serde(rename = "paid")]
+    Paid,
+}
+
+/// Represents a change of a reaction on a message performed by a user
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MessageReactionUpdated {
+    /// The chat containing the message the user reacted to
+    pub chat: Chat,
+    /// Unique identifier of the message inside the chat
+    pub message_id: i64,
+    /// The user that changed the reaction, if the user isn't anonymous
+    pub user: Option<User>,
+    /// The chat on behalf of which the reaction was changed, if the user is
+    /// anonymous
+    pub actor_chat: Option<Chat>,
+    /// Date of the change
+    #[serde(with = "unix_date_formatting")]
+    pub date: DateTime<Utc>,
+    /// Previous list of reaction types that were set by the user
+    pub old_reaction: Vec<ReactionType>,
+    /// New list of reaction types that have been set by the user
+    pub new_reaction: Vec<ReactionType>,
+}
+
+impl MessageReactionUpdated {
+    /// Returns the reactions the user added with this change
+    pub fn added_reactions(&self) -> impl Iterator<Item = &ReactionType> {
+        self.new_reaction
+            .iter()
+            .filter(move |r| !self.old_reaction.contains(r))
+    }
+}
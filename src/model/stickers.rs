@@ -11,6 +11,10 @@ pub struct Sticker {
     /// time and for different bots. Can't be used to download or reuse the
     /// file.
     pub file_unique_id: String,
+    /// Type of the sticker, the type of a sticker is independent of its
+    /// format
+    #[serde(rename = "type", default)]
+    pub sticker_type: StickerType,
     /// Sticker width
     pub width: usize,
     /// Sticker height
@@ -18,14 +22,24 @@ pub struct Sticker {
     /// True, if the sticker is [animated](https://telegram.org/blog/animated-stickers)
     #[serde(default)]
     pub is_animated: bool,
+    /// True, if the sticker is a [video sticker](https://telegram.org/blog/video-stickers-better-reactions)
+    #[serde(default)]
+    pub is_video: bool,
     /// Sticker thumbnail in the .WEBP or .JPG format
-    pub thumb: Option<PhotoSize>,
+    #[serde(alias = "thumb")]
+    pub thumbnail: Option<PhotoSize>,
     /// Emoji associated with the sticker
     pub emoji: Option<String>,
     /// Name of the sticker set to which the sticker belongs
     pub set_name: Option<String>,
     /// For mask stickers, the position where the mask should be placed
     pub mask_position: Option<MaskPosition>,
+    /// For custom emoji stickers, unique identifier of the custom emoji
+    pub custom_emoji_id: Option<String>,
+    /// True, if the custom emoji sticker must be repainted to the color of the
+    /// text, e.g. in messages
+    #[serde(default)]
+    pub needs_repainting: bool,
     /// File size
     pub file_size: Option<usize>,
 }
@@ -37,19 +51,59 @@ pub struct StickerSet {
     pub name: String,
     /// Sticker set title
     pub title: String,
-    /// True, if the sticker set contains [animated stickers](https://telegram.org/blog/animated-stickers)
-    pub is_animated: bool,
-    /// True, if the sticker set contains masks
-    pub contains_masks: bool,
+    /// Type of the stickers in the set
+    #[serde(rename = "type", default)]
+    pub sticker_type: StickerType,
     /// List of all set stickers
     pub stickers: Vec<Sticker>,
-    /// Optional. Sticker set thumbnail in the .WEBP or .TGS format
-    pub thumb: Option<PhotoSize>,
+    /// Optional. Sticker set thumbnail in the .WEBP, .TGS or .WEBM format
+    #[serde(alias = "thumb")]
+    pub thumbnail: Option<PhotoSize>,
+}
+
+/// The type of a sticker, independent of its [`StickerFormat`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StickerType {
+    #[default]
+    #[serde(rename = "regular")]
+    Regular,
+    #[serde(rename = "mask")]
+    Mask,
+    #[serde(rename = "custom_emoji")]
+    CustomEmoji,
+}
+
+/// The format of the file of a sticker
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StickerFormat {
+    /// A .WEBP or .PNG image
+    #[serde(rename = "static")]
+    Static,
+    /// A .TGS animation
+    #[serde(rename = "animated")]
+    Animated,
+    /// A .WEBM video
+    #[serde(rename = "video")]
+    Video,
+}
+
+impl StickerFormat {
+    /// Guesses the format from the extension of the file name, returning
+    /// `None` for files which can't be stickers
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension.to_lowercase().as_str() {
+            "webp" | "png" => Some(Self::Static),
+            "tgs" => Some(Self::Animated),
+            "webm" => Some(Self::Video),
+            _ => None,
+        }
+    }
 }
 
 /// This object describes the position on faces where a mask should be placed by
 /// default.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct MaskPosition {
     /// The part of the face relative to which the mask should be placed
     pub point: MaskPoint,
@@ -66,7 +120,7 @@ pub struct MaskPosition {
 }
 
 /// The part of the face relative to which a mask should be placed
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MaskPoint {
     #[serde(rename = "forehead")]
     Forehead,
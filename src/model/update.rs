@@ -1,14 +1,18 @@
 use super::{
     raw::RawUpdate,
+    BusinessConnection,
     CallbackQuery,
+    Chat,
     ChatMemberUpdated,
     ChosenInlineResult,
     InlineQuery,
     Message,
+    MessageReactionUpdated,
     Poll,
     PollAnswer,
     PreCheckoutQuery,
     ShippingQuery,
+    User,
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -69,10 +73,78 @@ pub enum UpdateContent {
     /// administrator in the chat and must explicitly specify “chat_member”
     /// in the list of allowed_updates to receive these updates.
     ChatMember(ChatMemberUpdated),
+    /// The bot was connected to or disconnected from a business account, or a
+    /// user edited an existing connection with the bot
+    BusinessConnection(BusinessConnection),
+    /// A reaction to a message was changed by a user. The bot must be an
+    /// administrator in the chat and must explicitly specify `message_reaction`
+    /// in the list of `allowed_updates` to receive these updates.
+    MessageReaction(MessageReactionUpdated),
     /// An unknown update content
     Unknown,
 }
 
+impl UpdateContent {
+    /// The name telegram uses for this kind of update, for example `message`
+    /// or `callback_query`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Message(_) => "message",
+            Self::EditedMessage(_) => "edited_message",
+            Self::ChannelPost(_) => "channel_post",
+            Self::EditedChannelPost(_) => "edited_channel_post",
+            Self::InlineQuery(_) => "inline_query",
+            Self::ChosenInlineResult(_) => "chosen_inline_result",
+            Self::CallbackQuery(_) => "callback_query",
+            Self::ShippingQuery(_) => "shipping_query",
+            Self::PreCheckoutQuery(_) => "pre_checkout_query",
+            Self::Poll(_) => "poll",
+            Self::PollAnswer(_) => "poll_answer",
+            Self::MyChatMember(_) => "my_chat_member",
+            Self::ChatMember(_) => "chat_member",
+            Self::BusinessConnection(_) => "business_connection",
+            Self::MessageReaction(_) => "message_reaction",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl Update {
+    /// Gets the [`User`] that caused the update, if there is one
+    pub fn get_user(&self) -> Option<&User> {
+        match &self.content {
+            UpdateContent::Message(m)
+            | UpdateContent::EditedMessage(m)
+            | UpdateContent::ChannelPost(m)
+            | UpdateContent::EditedChannelPost(m) => m.from.as_ref(),
+            UpdateContent::InlineQuery(q) => Some(&q.from),
+            UpdateContent::ChosenInlineResult(r) => Some(&r.from),
+            UpdateContent::CallbackQuery(q) => Some(&q.from),
+            UpdateContent::ShippingQuery(q) => Some(&q.from),
+            UpdateContent::PreCheckoutQuery(q) => Some(&q.from),
+            UpdateContent::PollAnswer(a) => Some(&a.user),
+            UpdateContent::MyChatMember(m) | UpdateContent::ChatMember(m) => Some(&m.from),
+            UpdateContent::BusinessConnection(c) => Some(&c.user),
+            UpdateContent::MessageReaction(r) => r.user.as_ref(),
+            UpdateContent::Poll(_) | UpdateContent::Unknown => None,
+        }
+    }
+
+    /// Gets the [`Chat`] the update originated from, if there is one
+    pub fn get_chat(&self) -> Option<&Chat> {
+        match &self.content {
+            UpdateContent::Message(m)
+            | UpdateContent::EditedMessage(m)
+            | UpdateContent::ChannelPost(m)
+            | UpdateContent::EditedChannelPost(m) => Some(&m.chat),
+            UpdateContent::CallbackQuery(q) => q.message.as_ref().map(|m| &m.chat),
+            UpdateContent::MyChatMember(m) | UpdateContent::ChatMember(m) => Some(&m.chat),
+            UpdateContent::MessageReaction(r) => Some(&r.chat),
+            _ => None,
+        }
+    }
+}
+
 impl From<RawUpdate> for Update {
     fn from(raw: RawUpdate) -> Update {
         let update_id = raw.update_id;
@@ -102,6 +174,8 @@ impl From<RawUpdate> for Update {
         set_content!(raw.poll_answer, PollAnswer);
         set_content!(raw.my_chat_member, MyChatMember);
         set_content!(raw.chat_member, ChatMember);
+        set_content!(raw.business_connection, BusinessConnection);
+        set_content!(raw.message_reaction, MessageReaction);
 
         make_update(UpdateContent::Unknown)
     }
@@ -124,6 +198,8 @@ impl From<Update> for RawUpdate {
             poll_answer: None,
             my_chat_member: None,
             chat_member: None,
+            business_connection: None,
+            message_reaction: None,
         };
 
         match update.content {
@@ -179,6 +255,14 @@ impl From<Update> for RawUpdate {
                 ret.chat_member = Some(c);
                 ret
             },
+            UpdateContent::BusinessConnection(c) => {
+                ret.business_connection = Some(c);
+                ret
+            },
+            UpdateContent::MessageReaction(c) => {
+                ret.message_reaction = Some(c);
+                ret
+            },
             UpdateContent::Unknown => ret,
         }
     }
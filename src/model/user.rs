@@ -1,10 +1,11 @@
-use super::PhotoSize;
+use super::{utils::id_formatting, PhotoSize};
 use serde::{Deserialize, Serialize};
 
 /// This object represents a Telegram user or bot.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct User {
     /// Unique identifier for this user or bot
+    #[serde(with = "id_formatting")]
     pub id: i64,
     /// True, if this user is a bot
     pub is_bot: bool,
@@ -29,6 +30,11 @@ pub struct User {
     ///
     /// [`get_me`]: ../api/struct.API.html#method.get_me
     pub supports_inline_queries: Option<bool>,
+    /// True, if the bot can be connected to a Telegram Business account to
+    /// receive its messages. Returned only in [`get_me`].
+    ///
+    /// [`get_me`]: ../api/struct.API.html#method.get_me
+    pub can_connect_to_business: Option<bool>,
 }
 
 /// This object represent a user's profile pictures.
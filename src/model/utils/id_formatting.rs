@@ -0,0 +1,158 @@
+use serde::{self, de, Deserializer, Serialize, Serializer};
+use std::{cell::Cell, convert::TryFrom, fmt};
+
+thread_local! {
+    static IDS_AS_STRINGS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Wraps a serializable object so that all chat and user identifiers within it
+/// get serialized as strings instead of numbers.
+///
+/// Telegram identifiers can be up to 52 bits long, which is more than some
+/// consumers (like JavaScript, which uses doubles for its numbers) can
+/// safely represent. Identifiers serialized as strings will still be
+/// deserialized by telexide as usual.
+///
+/// ## Example
+/// ```rust
+/// # fn main() -> serde_json::Result<()> {
+/// use telexide::model::{StringIds, User};
+///
+/// let user = User {
+///     id: 4_503_599_627_370_495,
+///     is_bot: false,
+///     first_name: "test".to_owned(),
+///     last_name: None,
+///     username: None,
+///     language_code: None,
+///     can_join_groups: None,
+///     can_read_all_group_messages: None,
+///     supports_inline_queries: None,
+///     can_connect_to_business: None,
+/// };
+///
+/// let json = serde_json::to_value(StringIds(&user))?;
+/// assert_eq!(json["id"], "4503599627370495");
+///
+/// let decoded: User = serde_json::from_value(json)?;
+/// assert_eq!(decoded, user);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StringIds<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ?Sized + Serialize> Serialize for StringIds<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let _guard = ModeGuard::set(true);
+        self.0.serialize(serializer)
+    }
+}
+
+/// Restores the previous serialization mode when dropped, so that nested or
+/// panicking serializations don't leak the mode to other calls on the thread
+struct ModeGuard(bool);
+
+impl ModeGuard {
+    fn set(as_strings: bool) -> Self {
+        Self(IDS_AS_STRINGS.with(|m| m.replace(as_strings)))
+    }
+}
+
+impl Drop for ModeGuard {
+    fn drop(&mut self) {
+        IDS_AS_STRINGS.with(|m| m.set(self.0));
+    }
+}
+
+struct IdVisitor;
+
+impl de::Visitor<'_> for IdVisitor {
+    type Value = i64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an identifier as an integer or a string")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<i64, E> {
+        Ok(v)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<i64, E> {
+        i64::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<i64, E> {
+        v.parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+pub fn serialize<S>(id: &i64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if IDS_AS_STRINGS.with(Cell::get) {
+        serializer.collect_str(id)
+    } else {
+        serializer.serialize_i64(*id)
+    }
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(IdVisitor)
+}
+
+pub mod optional {
+    use super::*;
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S>(id: &Option<i64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match id {
+            Some(id) => super::serialize(id, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct OptionalIdVisitor;
+
+        impl<'de> de::Visitor<'de> for OptionalIdVisitor {
+            type Value = Option<i64>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an optional identifier as an integer or a string")
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Option<i64>, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Option<i64>, E> {
+                Ok(None)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Option<i64>, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                super::deserialize(deserializer).map(Some)
+            }
+        }
+
+        deserializer.deserialize_option(OptionalIdVisitor)
+    }
+}
@@ -1,4 +1,6 @@
+pub mod id_formatting;
 pub mod text;
 pub mod unix_date_formatting;
 
+pub use id_formatting::StringIds;
 pub use text::TextBlock;
@@ -0,0 +1,23 @@
+//! The redaction module scrubs personal data from everything telexide logs,
+//! so bots operating under privacy regulations like the GDPR can keep using
+//! its logging and metrics.
+//!
+//! Which data gets masked is decided by the active [`RedactionPolicy`], which
+//! can be swapped at any time using [`set_policy`]. By default phone numbers
+//! and file paths are masked, while user ids are kept.
+//!
+//! ```rust
+//! use telexide::redaction::{self, RedactionPolicy};
+//!
+//! redaction::set_policy(RedactionPolicy::default().redact_user_ids(true));
+//!
+//! assert_eq!(
+//!     redaction::policy().scrub("call +31 6 12345678 about /home/bot/data.json"),
+//!     "call [phone] about [path]"
+//! );
+//! ```
+
+mod policy;
+
+pub use policy::{policy, set_policy, RedactionPolicy};
+pub(crate) use policy::{chat, scrub, user};
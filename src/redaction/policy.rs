@@ -0,0 +1,148 @@
+use parking_lot::{const_rwlock, RwLock};
+use regex::{Captures, Regex};
+use std::{fmt::Display, sync::OnceLock};
+
+static POLICY: RwLock<RedactionPolicy> = const_rwlock(RedactionPolicy::DEFAULT);
+
+/// Decides which personal data gets masked in the logs of telexide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedactionPolicy {
+    phone_numbers: bool,
+    user_ids: bool,
+    file_paths: bool,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl RedactionPolicy {
+    const DEFAULT: Self = Self {
+        phone_numbers: true,
+        user_ids: false,
+        file_paths: true,
+    };
+
+    /// A policy which doesn't mask anything
+    pub fn none() -> Self {
+        Self {
+            phone_numbers: false,
+            user_ids: false,
+            file_paths: false,
+        }
+    }
+
+    /// A policy which masks everything it knows about
+    pub fn all() -> Self {
+        Self {
+            phone_numbers: true,
+            user_ids: true,
+            file_paths: true,
+        }
+    }
+
+    /// Sets whether phone numbers, like "+31 6 12345678", get masked
+    #[must_use]
+    pub fn redact_phone_numbers(mut self, redact: bool) -> Self {
+        self.phone_numbers = redact;
+        self
+    }
+
+    /// Sets whether the ids of users, and of the private chats with them, get
+    /// masked
+    #[must_use]
+    pub fn redact_user_ids(mut self, redact: bool) -> Self {
+        self.user_ids = redact;
+        self
+    }
+
+    /// Sets whether absolute file paths, like "/home/bot/data.json", get
+    /// masked
+    #[must_use]
+    pub fn redact_file_paths(mut self, redact: bool) -> Self {
+        self.file_paths = redact;
+        self
+    }
+
+    /// Masks the phone numbers and file paths in the text, as far as this
+    /// policy asks for it
+    pub fn scrub(&self, text: &str) -> String {
+        let mut text = text.to_owned();
+        if self.phone_numbers {
+            text = phone_regex().replace_all(&text, "[phone]").into_owned();
+        }
+        if self.file_paths {
+            text = path_regex()
+                .replace_all(&text, |c: &Captures| format!("{}[path]", &c[1]))
+                .into_owned();
+        }
+        text
+    }
+
+    /// Renders the id of a user for logging, masking it if this policy asks
+    /// for it
+    pub fn user_id(&self, user_id: i64) -> String {
+        if self.user_ids {
+            "[user]".to_owned()
+        } else {
+            user_id.to_string()
+        }
+    }
+
+    /// Renders the id of a chat for logging. Private chats have the same id
+    /// as the user they are with, so their id is masked like a user id.
+    pub fn chat_id(&self, chat_id: i64) -> String {
+        if chat_id > 0 {
+            self.user_id(chat_id)
+        } else {
+            chat_id.to_string()
+        }
+    }
+}
+
+/// Returns the currently active [`RedactionPolicy`]
+pub fn policy() -> RedactionPolicy {
+    *POLICY.read()
+}
+
+/// Replaces the active [`RedactionPolicy`], affecting everything logged from
+/// now on
+pub fn set_policy(policy: RedactionPolicy) {
+    *POLICY.write() = policy;
+}
+
+/// Renders the value for logging, scrubbed according to the active policy
+pub(crate) fn scrub(value: impl Display) -> String {
+    policy().scrub(&value.to_string())
+}
+
+/// Renders a user id for logging according to the active policy
+pub(crate) fn user(user_id: i64) -> String {
+    policy().user_id(user_id)
+}
+
+/// Renders a chat id for logging according to the active policy
+pub(crate) fn chat(chat_id: i64) -> String {
+    policy().chat_id(chat_id)
+}
+
+fn phone_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    // international numbers, or local numbers written with separators
+    REGEX.get_or_init(|| {
+        Regex::new(r"\+\d[\d ().-]{5,}\d|\(?\b\d{3}\)?[ .-]\d{3}[ .-]\d{4}\b")
+            .expect("phone number regex is valid")
+    })
+}
+
+fn path_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    // absolute paths with at least one directory, not preceded by anything
+    // which would make them part of a url
+    REGEX.get_or_init(|| {
+        Regex::new(r#"(^|[\s'"(=])(?:[A-Za-z]:\\|/)(?:[\w.-]+[/\\])+[\w.-]*"#)
+            .expect("file path regex is valid")
+    })
+}
@@ -0,0 +1,84 @@
+//! The async runtime the client spawns its tasks and sets its timers on.
+//!
+//! The [`Client`] runs on tokio by default. To embed a bot in an application
+//! built on another runtime, like async-std or smol, implement [`Runtime`] for
+//! it and set it with [`ClientBuilder::set_runtime`]:
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//! use telexide::runtime::{BoxedTask, Runtime};
+//!
+//! struct AsyncStd;
+//!
+//! impl Runtime for AsyncStd {
+//!     fn spawn(&self, task: BoxedTask) {
+//!         async_std::task::spawn(task);
+//!     }
+//!
+//!     fn sleep(&self, duration: Duration) -> BoxedTask {
+//!         Box::pin(async_std::task::sleep(duration))
+//!     }
+//! }
+//! ```
+//!
+//! The event handlers, commands and the long polling of the client then run
+//! on that runtime. The connections of the default [`APIClient`] and the
+//! webhook server are still driven by tokio's io, so either enable the tokio
+//! compatibility of your runtime, or set an [`API`] implementation using its
+//! own http client with [`ClientBuilder::set_api_client`] and poll for
+//! updates.
+//!
+//! [`Client`]: ../client/struct.Client.html
+//! [`ClientBuilder::set_runtime`]: ../client/struct.ClientBuilder.html#method.set_runtime
+//! [`ClientBuilder::set_api_client`]: ../client/struct.ClientBuilder.html#method.set_api_client
+//! [`APIClient`]: ../api/struct.APIClient.html
+//! [`API`]: ../api/trait.API.html
+
+use futures::{channel::oneshot, Future, FutureExt};
+use std::{panic::AssertUnwindSafe, pin::Pin, time::Duration};
+
+/// A task handed to a [`Runtime`]
+pub type BoxedTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Spawns tasks and creates timers, for the client to run on any async
+/// runtime
+pub trait Runtime: Send + Sync {
+    /// Runs the task in the background, without waiting for it to finish
+    fn spawn(&self, task: BoxedTask);
+
+    /// Returns a future which resolves once the duration has passed
+    fn sleep(&self, duration: Duration) -> BoxedTask;
+}
+
+/// The default [`Runtime`], spawning onto the tokio runtime the client is
+/// started in
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioRuntime;
+
+impl Runtime for TokioRuntime {
+    fn spawn(&self, task: BoxedTask) {
+        tokio::spawn(task);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxedTask {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Resolves with an error if the task panicked, and with `Ok` once it
+/// finished otherwise
+pub(crate) type TaskHandle = oneshot::Receiver<()>;
+
+/// spawns the task, catching its panic so the runtime doesn't have to
+pub(crate) fn spawn_task<F>(runtime: &dyn Runtime, task: F) -> TaskHandle
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (finished, handle) = oneshot::channel();
+    runtime.spawn(Box::pin(async move {
+        if AssertUnwindSafe(task).catch_unwind().await.is_ok() {
+            finished.send(()).ok();
+        }
+    }));
+    handle
+}
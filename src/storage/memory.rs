@@ -0,0 +1,37 @@
+use super::Storage;
+use crate::Result;
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A [`Storage`] keeping all data in memory, all data is lost when the
+/// program exits.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    data: RwLock<HashMap<String, Value>>,
+}
+
+impl MemoryStorage {
+    /// Creates a new, empty `MemoryStorage`
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn get(&self, key: &str) -> Result<Option<Value>> {
+        Ok(self.data.read().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: Value) -> Result<()> {
+        self.data.write().insert(key.to_owned(), value);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.data.write().remove(key);
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    middleware::{ArchivedMessage, HistorySink},
+    Result,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// Describes which stored messages of a chat to get from a [`MessageStore`].
+///
+/// All bounds are inclusive, messages are always returned ordered by their
+/// message id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryQuery {
+    /// The chat to get the messages of
+    pub chat_id: i64,
+    /// Only messages sent at or after this date
+    pub from_date: Option<DateTime<Utc>>,
+    /// Only messages sent at or before this date
+    pub to_date: Option<DateTime<Utc>>,
+    /// Only messages with an id of at least this
+    pub from_message_id: Option<i64>,
+    /// Only messages with an id of at most this
+    pub to_message_id: Option<i64>,
+    /// The maximum amount of messages to return, defaults to 50
+    pub limit: usize,
+}
+
+impl HistoryQuery {
+    /// Creates a query for all messages of the given chat
+    pub fn new(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            from_date: None,
+            to_date: None,
+            from_message_id: None,
+            to_message_id: None,
+            limit: 50,
+        }
+    }
+
+    /// Only gets messages sent in the given date range
+    #[must_use]
+    pub fn between_dates(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        self.from_date = Some(from);
+        self.to_date = Some(to);
+        self
+    }
+
+    /// Only gets messages in the given message id range
+    #[must_use]
+    pub fn between_ids(mut self, from: i64, to: i64) -> Self {
+        self.from_message_id = Some(from);
+        self.to_message_id = Some(to);
+        self
+    }
+
+    /// Sets the maximum amount of messages to return
+    #[must_use]
+    pub fn set_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Returns whether the message falls within the bounds of the query,
+    /// ignoring the limit
+    pub fn matches(&self, message: &ArchivedMessage) -> bool {
+        message.chat_id == self.chat_id
+            && self.from_date.is_none_or(|d| message.date >= d)
+            && self.to_date.is_none_or(|d| message.date <= d)
+            && self
+                .from_message_id
+                .is_none_or(|id| message.message_id >= id)
+            && self.to_message_id.is_none_or(|id| message.message_id <= id)
+    }
+}
+
+/// A store of archived messages which can be queried, for example to power
+/// `/history` commands or analytics.
+///
+/// Stores also implement [`HistorySink`], so they can be filled by a
+/// [`HistoryCollector`].
+///
+/// [`HistoryCollector`]: ../middleware/struct.HistoryCollector.html
+#[async_trait]
+pub trait MessageStore: HistorySink {
+    /// Gets the stored messages matching the query, ordered by message id and
+    /// at most `query.limit` of them
+    async fn messages(&self, query: &HistoryQuery) -> Result<Vec<ArchivedMessage>>;
+}
+
+/// Pages through the messages of a [`MessageStore`] matching a
+/// [`HistoryQuery`], each page holding at most `query.limit` messages.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use telexide::storage::{HistoryPages, HistoryQuery, MemoryMessageStore};
+///
+/// # async fn run() -> telexide::Result<()> {
+/// let store = Arc::new(MemoryMessageStore::new());
+/// let mut pages = HistoryPages::new(store, HistoryQuery::new(-100123).set_limit(20));
+///
+/// while let Some(page) = pages.next_page().await? {
+///     for message in page {
+///         println!("{}: {:?}", message.message_id, message.text);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct HistoryPages {
+    store: Arc<dyn MessageStore>,
+    query: HistoryQuery,
+    done: bool,
+}
+
+impl HistoryPages {
+    /// Creates a new paginator, starting at the beginning of the query
+    pub fn new(store: Arc<dyn MessageStore>, query: HistoryQuery) -> Self {
+        Self {
+            store,
+            query,
+            done: false,
+        }
+    }
+
+    /// Gets the next page of messages, returning `None` once all matching
+    /// messages have been returned
+    pub async fn next_page(&mut self) -> Result<Option<Vec<ArchivedMessage>>> {
+        if self.done || self.query.limit == 0 {
+            return Ok(None);
+        }
+
+        let page = self.store.messages(&self.query).await?;
+        match page.last() {
+            Some(last) if page.len() >= self.query.limit => {
+                self.query.from_message_id = Some(last.message_id + 1);
+            },
+            Some(_) => self.done = true,
+            None => return Ok(None),
+        }
+
+        Ok(Some(page))
+    }
+}
+
+/// A [`MessageStore`] keeping all messages in memory, all messages are lost
+/// when the program exits. Edits replace the stored version of a message.
+#[derive(Debug, Default)]
+pub struct MemoryMessageStore {
+    chats: RwLock<HashMap<i64, BTreeMap<i64, ArchivedMessage>>>,
+}
+
+impl MemoryMessageStore {
+    /// Creates a new, empty `MemoryMessageStore`
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl HistorySink for MemoryMessageStore {
+    async fn archive(&self, message: &ArchivedMessage) -> Result<()> {
+        self.chats
+            .write()
+            .entry(message.chat_id)
+            .or_default()
+            .insert(message.message_id, message.clone());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessageStore for MemoryMessageStore {
+    async fn messages(&self, query: &HistoryQuery) -> Result<Vec<ArchivedMessage>> {
+        let chats = self.chats.read();
+        let Some(messages) = chats.get(&query.chat_id) else {
+            return Ok(Vec::new());
+        };
+
+        let start = query.from_message_id.unwrap_or(i64::MIN);
+        let end = query.to_message_id.unwrap_or(i64::MAX);
+        if start > end {
+            return Ok(Vec::new());
+        }
+
+        Ok(messages
+            .range(start..=end)
+            .map(|(_, m)| m)
+            .filter(|m| query.matches(m))
+            .take(query.limit)
+            .cloned()
+            .collect())
+    }
+}
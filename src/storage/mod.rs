@@ -0,0 +1,55 @@
+//! The storage module provides the [`Storage`] trait, used by the built-in
+//! components of telexide which need to persist data, for example the strike
+//! counts of the [`KeywordFilter`].
+//!
+//! A [`MemoryStorage`] is provided, which keeps everything in memory and thus
+//! loses its data upon restarts. To persist the data across restarts, for
+//! example in a database, implement the [`Storage`] trait yourself.
+//!
+//! Archived messages are kept in a [`MessageStore`] instead, which can be
+//! paged through using [`HistoryPages`]. The polling loop keeps the offset of
+//! the updates it confirmed in an [`OffsetStorage`], like the
+//! [`FileOffsetStorage`].
+//!
+//! [`KeywordFilter`]: ../middleware/struct.KeywordFilter.html
+
+mod memory;
+mod messages;
+mod offset;
+
+pub use memory::MemoryStorage;
+pub use messages::{HistoryPages, HistoryQuery, MemoryMessageStore, MessageStore};
+pub use offset::{FileOffsetStorage, MemoryOffsetStorage, OffsetStorage};
+
+use crate::Result;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// A key-value store for persisting data as json values.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Gets the value stored under the given key, if there is one
+    async fn get(&self, key: &str) -> Result<Option<Value>>;
+
+    /// Stores the value under the given key, replacing any previous value
+    async fn set(&self, key: &str, value: Value) -> Result<()>;
+
+    /// Removes the value stored under the given key, if there is one
+    async fn remove(&self, key: &str) -> Result<()>;
+}
+
+impl dyn Storage {
+    /// Gets the value stored under the given key and deserializes it into `T`
+    pub async fn get_as<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.get(key).await? {
+            Some(v) => Ok(Some(serde_json::from_value(v)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Serializes the given value and stores it under the given key
+    pub async fn set_as<T: Serialize + ?Sized>(&self, key: &str, value: &T) -> Result<()> {
+        self.set(key, serde_json::to_value(value)?).await
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{utils::result::TelegramError, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::{io::ErrorKind, path::PathBuf};
+
+/// Stores the `update_id` of the last update the polling loop confirmed to
+/// telegram, so it resumes where it left off after a restart instead of
+/// handling updates again or skipping them.
+///
+/// Set it with [`ClientBuilder::set_offset_storage`] or
+/// [`UpdatesStream::set_offset_storage`].
+///
+/// [`ClientBuilder::set_offset_storage`]: ../client/struct.ClientBuilder.html#method.set_offset_storage
+/// [`UpdatesStream::set_offset_storage`]: ../client/struct.UpdatesStream.html#method.set_offset_storage
+#[async_trait]
+pub trait OffsetStorage: Send + Sync {
+    /// Gets the `update_id` of the last confirmed update, if one was stored
+    async fn load(&self) -> Result<Option<i64>>;
+
+    /// Stores the `update_id` of the last confirmed update
+    async fn save(&self, update_id: i64) -> Result<()>;
+}
+
+/// An [`OffsetStorage`] keeping the offset in memory, so it survives
+/// recreating the polling loop but not restarting the program.
+#[derive(Debug, Default)]
+pub struct MemoryOffsetStorage {
+    update_id: Mutex<Option<i64>>,
+}
+
+impl MemoryOffsetStorage {
+    /// Creates a new `MemoryOffsetStorage` without an offset
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl OffsetStorage for MemoryOffsetStorage {
+    async fn load(&self) -> Result<Option<i64>> {
+        Ok(*self.update_id.lock())
+    }
+
+    async fn save(&self, update_id: i64) -> Result<()> {
+        *self.update_id.lock() = Some(update_id);
+        Ok(())
+    }
+}
+
+/// An [`OffsetStorage`] keeping the offset in a file. The file is replaced
+/// as a whole on every save, so it's never left half written.
+#[derive(Debug, Clone)]
+pub struct FileOffsetStorage {
+    path: PathBuf,
+}
+
+impl FileOffsetStorage {
+    /// Creates a `FileOffsetStorage` keeping the offset in the file at the
+    /// given path, which is created on the first save
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl OffsetStorage for FileOffsetStorage {
+    async fn load(&self) -> Result<Option<i64>> {
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            res => res?,
+        };
+        let update_id = contents.trim().parse().map_err(|_| {
+            TelegramError::InvalidArgument(format!(
+                "{} doesn't contain an update id",
+                self.path.display()
+            ))
+        })?;
+        Ok(Some(update_id))
+    }
+
+    async fn save(&self, update_id: i64) -> Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        tokio::fs::write(&temporary, update_id.to_string()).await?;
+        tokio::fs::rename(&temporary, &self.path).await?;
+        Ok(())
+    }
+}
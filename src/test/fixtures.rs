@@ -0,0 +1,88 @@
+//! Functions building the [`Update`]s telegram would send, to feed into a
+//! [`Harness`].
+//!
+//! Chats with a negative id are supergroups, all other chats are private
+//! chats with the user.
+//!
+//! [`Harness`]: super::Harness
+
+use crate::model::Update;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+static NEXT_ID: AtomicI64 = AtomicI64::new(1);
+
+fn next_id() -> i64 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn user(user_id: i64) -> Value {
+    json!({"id": user_id, "is_bot": false, "first_name": "test", "username": format!("user{user_id}")})
+}
+
+fn chat(chat_id: i64) -> Value {
+    if chat_id < 0 {
+        json!({"id": chat_id, "type": "supergroup", "title": "test chat"})
+    } else {
+        json!({"id": chat_id, "type": "private", "first_name": "test"})
+    }
+}
+
+fn message(chat_id: i64, user_id: i64, text: &str) -> Value {
+    let mut message = json!({
+        "message_id": next_id(),
+        "date": chrono::Utc::now().timestamp(),
+        "chat": chat(chat_id),
+        "from": user(user_id),
+        "text": text,
+    });
+
+    if text.starts_with('/') {
+        let length = text
+            .split_whitespace()
+            .next()
+            .map_or(0, |c| c.encode_utf16().count());
+        message["entities"] = json!([{"type": "bot_command", "offset": 0, "length": length}]);
+    }
+    message
+}
+
+/// Builds an update from the json of its content, e.g. `("message", {...})`,
+/// giving it a new update id
+///
+/// # Panics
+/// If the json doesn't describe a valid update
+pub fn update(kind: &str, content: Value) -> Update {
+    let mut update = json!({"update_id": next_id()});
+    update[kind] = content;
+    serde_json::from_value(update).expect("fixture json isn't a valid update")
+}
+
+/// A text message sent by the user in the chat. Messages starting with a "/"
+/// are marked as a bot command, so they trigger the commands of the framework.
+pub fn text_message(chat_id: i64, user_id: i64, text: &str) -> Update {
+    update("message", message(chat_id, user_id, text))
+}
+
+/// An edit of a message sent by the user, now having the given text
+pub fn edited_message(chat_id: i64, user_id: i64, message_id: i64, text: &str) -> Update {
+    let mut message = message(chat_id, user_id, text);
+    message["message_id"] = json!(message_id);
+    message["edit_date"] = json!(chrono::Utc::now().timestamp());
+    update("edited_message", message)
+}
+
+/// The user pressing an inline keyboard button with the given callback data,
+/// attached to a message of the bot in the chat
+pub fn callback_query(chat_id: i64, user_id: i64, data: &str) -> Update {
+    update(
+        "callback_query",
+        json!({
+            "id": next_id().to_string(),
+            "from": user(user_id),
+            "message": message(chat_id, 1, ""),
+            "chat_instance": chat_id.to_string(),
+            "data": data,
+        }),
+    )
+}
@@ -0,0 +1,184 @@
+use super::{MockAPI, RecordedCall};
+use crate::{
+    client::{Client, ClientBuilder},
+    model::Update,
+};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Runs a [`Client`] against a [`MockAPI`], so the behaviour of handlers,
+/// commands and middleware can be verified without talking to telegram.
+///
+/// Updates fed into the harness go through the middleware and all handlers
+/// just like they would when received from telegram, and [`feed`] only returns
+/// once all of them have finished. Afterwards the calls they made can be
+/// asserted on.
+///
+/// ## Example
+/// ```rust,no_run
+/// use telexide::{
+///     api::types::SendMessage,
+///     prelude::*,
+///     test::{fixtures, Harness},
+/// };
+///
+/// #[prepare_listener]
+/// async fn greet(ctx: Context, update: Update) {
+///     if let Some(chat) = update.get_chat() {
+///         let _ = ctx.api.send_message(SendMessage::new(chat.get_id(), "hello there")).await;
+///     }
+/// }
+///
+/// # async fn test() {
+/// let harness = Harness::new(ClientBuilder::new().add_handler_func(greet));
+///
+/// harness.feed(fixtures::text_message(1, 5, "hi")).await;
+/// harness
+///     .expect_send_message()
+///     .to_chat(1)
+///     .containing("hello");
+/// # }
+/// ```
+///
+/// [`feed`]: Harness::feed
+pub struct Harness {
+    client: Client,
+    api: MockAPI,
+}
+
+impl Harness {
+    /// Builds the client from the given builder, with its api client replaced
+    /// by a new [`MockAPI`]
+    pub fn new(builder: &mut ClientBuilder) -> Self {
+        let api = MockAPI::new();
+        let client = builder
+            .set_api_client(Arc::new(Box::new(api.clone())))
+            .build();
+
+        Self {
+            client,
+            api,
+        }
+    }
+
+    /// The client running the handlers
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The mock the handlers make their calls to
+    pub fn api(&self) -> &MockAPI {
+        &self.api
+    }
+
+    /// Dispatches the update to the middleware and handlers of the client,
+    /// resolving once all of them have finished
+    pub async fn feed(&self, update: Update) {
+        self.client.dispatch(update).await;
+    }
+
+    /// Starts an expectation on the calls made to the given method
+    pub fn expect_call(&self, method: &str) -> CallExpectation {
+        CallExpectation {
+            description: method.to_owned(),
+            calls: self.api.calls_to(method),
+        }
+    }
+
+    /// Starts an expectation on the `sendMessage` calls made
+    pub fn expect_send_message(&self) -> CallExpectation {
+        self.expect_call("sendMessage")
+    }
+
+    /// Asserts that no calls were made to telegram at all
+    ///
+    /// # Panics
+    /// If any call was made
+    #[track_caller]
+    pub fn expect_no_calls(&self) {
+        let calls = self.api.calls();
+        assert!(calls.is_empty(), "expected no calls, but got {:#?}", calls);
+    }
+}
+
+/// An assertion on the calls made to a method of a [`MockAPI`], created by
+/// [`Harness::expect_call`].
+///
+/// Every matcher narrows down the matching calls and panics right away if none
+/// are left, listing the calls which were considered, so an expectation
+/// doesn't need to be used after its last matcher.
+pub struct CallExpectation {
+    description: String,
+    calls: Vec<RecordedCall>,
+}
+
+// the matchers assert as soon as they are called, so dropping the returned
+// expectation is fine
+#[allow(clippy::return_self_not_must_use)]
+impl CallExpectation {
+    #[track_caller]
+    fn filter<F: Fn(&RecordedCall) -> bool>(self, matcher: &str, f: F) -> Self {
+        let description = format!("{} {matcher}", self.description);
+        let (matching, rest): (Vec<_>, Vec<_>) = self.calls.into_iter().partition(|c| f(c));
+        assert!(
+            !matching.is_empty(),
+            "expected a call to {}, but the calls were {:#?}",
+            description,
+            rest
+        );
+
+        Self {
+            description,
+            calls: matching,
+        }
+    }
+
+    /// Only matches calls sent to the given chat
+    #[track_caller]
+    pub fn to_chat(self, chat_id: i64) -> Self {
+        self.filter(&format!("to chat {chat_id}"), |c| {
+            c.data["chat_id"].as_i64() == Some(chat_id)
+        })
+    }
+
+    /// Only matches calls whose text or caption contains the given text
+    #[track_caller]
+    pub fn containing(self, text: &str) -> Self {
+        self.filter(&format!("containing {text:?}"), |c| {
+            ["text", "caption"]
+                .iter()
+                .filter_map(|field| c.data[*field].as_str())
+                .any(|t| t.contains(text))
+        })
+    }
+
+    /// Only matches calls with the given value for the given field
+    #[track_caller]
+    pub fn with(self, field: &str, value: &Value) -> Self {
+        self.filter(&format!("with {field} = {value}"), |c| {
+            c.data[field] == *value
+        })
+    }
+
+    /// Asserts that exactly the given amount of calls match
+    ///
+    /// # Panics
+    /// If a different amount of calls match
+    #[track_caller]
+    pub fn times(self, count: usize) -> Self {
+        assert_eq!(
+            self.calls.len(),
+            count,
+            "expected {} calls to {}, but the calls were {:#?}",
+            count,
+            self.description,
+            self.calls
+        );
+        self
+    }
+
+    /// The calls which matched all matchers, oldest first
+    pub fn calls(&self) -> &[RecordedCall] {
+        &self.calls
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{
+    api::{synthesized_result, APIEndpoint, Response, API},
+    client::Context,
+    model::ResponseParameters,
+    utils::{
+        result::{Result, TelegramError},
+        FormDataFile,
+    },
+};
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc};
+use typemap::ShareMap;
+
+/// A call made to a [`MockAPI`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedCall {
+    /// The name of the called telegram method, e.g. "sendMessage"
+    pub method: String,
+    /// The data sent along with the call, or `Value::Null` if there was none
+    pub data: Value,
+    /// The names of the files uploaded with the call
+    pub files: Vec<String>,
+}
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<RecordedCall>,
+    responses: HashMap<String, Response>,
+    hanging: HashMap<String, usize>,
+    flooding: HashMap<String, (usize, i64)>,
+    next_message_id: i64,
+    connection_resets: usize,
+    files: HashMap<String, Vec<u8>>,
+}
+
+/// An [`API`] implementation which doesn't talk to telegram, but records every
+/// call made to it and answers with canned responses.
+///
+/// Unless a response was configured with [`respond_with`] or [`fail_with`],
+/// calls are answered like a [`DryRun`] answers them: methods sending or
+/// editing a message return a message echoing the chat and text of the call,
+/// and most other methods return `true`. `getMe` returns a bot user, and
+/// `getFile` a file whose path is its file id, which can be downloaded once
+/// it was added with [`add_file`].
+///
+/// Clones share their recorded calls and configured responses.
+///
+/// [`DryRun`]: crate::api::DryRun
+/// [`respond_with`]: MockAPI::respond_with
+/// [`fail_with`]: MockAPI::fail_with
+/// [`add_file`]: MockAPI::add_file
+#[derive(Clone, Default)]
+pub struct MockAPI {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockAPI {
+    /// Creates a new mock without any recorded calls
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a [`Context`] using this mock as its api client, with empty
+    /// data, to call handlers directly in unit tests without a [`Harness`]
+    ///
+    /// [`Harness`]: super::Harness
+    pub fn context(&self) -> Context {
+        Context::new(
+            Arc::new(Box::new(self.clone())),
+            Arc::new(RwLock::new(ShareMap::custom())),
+        )
+    }
+
+    /// Makes calls to the given method return the given result
+    pub fn respond_with(&self, method: &str, result: Value) {
+        self.state.lock().responses.insert(
+            method.to_owned(),
+            Response {
+                ok: true,
+                description: None,
+                result: Some(result),
+                error_code: None,
+                parameters: None,
+            },
+        );
+    }
+
+    /// Makes calls to the given method fail with the given error code and
+    /// description
+    pub fn fail_with(&self, method: &str, error_code: i64, description: &str) {
+        self.state.lock().responses.insert(
+            method.to_owned(),
+            Response {
+                ok: false,
+                description: Some(description.to_owned()),
+                result: None,
+                error_code: Some(error_code),
+                parameters: None,
+            },
+        );
+    }
+
+    /// Makes the next `times` calls to the given method never complete, like
+    /// requests on a hung connection. The calls are still recorded.
+    pub fn hang_next(&self, method: &str, times: usize) {
+        self.state.lock().hanging.insert(method.to_owned(), times);
+    }
+
+    /// Makes the next `times` calls to the method fail with error 429, telling
+    /// to retry after `retry_after` seconds like telegram's flood control
+    /// does. Calls after that get the configured response again.
+    pub fn flood_next(&self, method: &str, times: usize, retry_after: i64) {
+        self.state
+            .lock()
+            .flooding
+            .insert(method.to_owned(), (times, retry_after));
+    }
+
+    /// Makes [`API::download`] return the bytes for the given file path.
+    /// Downloads are recorded as calls to the "download" method, with the
+    /// path as their `file_path`.
+    pub fn add_file(&self, file_path: &str, bytes: impl Into<Vec<u8>>) {
+        self.state
+            .lock()
+            .files
+            .insert(file_path.to_owned(), bytes.into());
+    }
+
+    /// Returns how often [`API::reset_connection`] was called
+    pub fn connection_resets(&self) -> usize {
+        self.state.lock().connection_resets
+    }
+
+    /// Returns all calls made so far, oldest first
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.state.lock().calls.clone()
+    }
+
+    /// Returns all calls made so far to the given method, oldest first
+    pub fn calls_to(&self, method: &str) -> Vec<RecordedCall> {
+        self.state
+            .lock()
+            .calls
+            .iter()
+            .filter(|c| c.method == method)
+            .cloned()
+            .collect()
+    }
+
+    /// Forgets all recorded calls, keeping the configured responses
+    pub fn clear_calls(&self) {
+        self.state.lock().calls.clear();
+    }
+
+    /// records the call, returning `None` if it should hang
+    fn record(
+        &self,
+        endpoint: &APIEndpoint,
+        data: Option<Value>,
+        files: Vec<String>,
+    ) -> Option<Response> {
+        let method = endpoint.as_str().to_owned();
+        let data = data.unwrap_or(Value::Null);
+        let mut state = self.state.lock();
+
+        let hangs = match state.hanging.get_mut(&method) {
+            Some(left) if *left > 0 => {
+                *left -= 1;
+                true
+            },
+            _ => false,
+        };
+
+        let flood = match state.flooding.get_mut(&method) {
+            Some((left, retry_after)) if *left > 0 => {
+                *left -= 1;
+                Some(*retry_after)
+            },
+            _ => None,
+        };
+
+        let response = if let Some(retry_after) = flood {
+            Response {
+                ok: false,
+                description: Some(format!("Too Many Requests: retry after {retry_after}")),
+                result: None,
+                error_code: Some(429),
+                parameters: Some(ResponseParameters {
+                    migrate_to_chat_id: None,
+                    retry_after: Some(retry_after),
+                }),
+            }
+        } else if let Some(r) = state.responses.get(&method) {
+            r.clone()
+        } else {
+            state.next_message_id += 1;
+            Response {
+                ok: true,
+                description: None,
+                result: Some(default_result(&method, &data, state.next_message_id)),
+                error_code: None,
+                parameters: None,
+            }
+        };
+
+        state.calls.push(RecordedCall {
+            method,
+            data,
+            files,
+        });
+        if hangs {
+            None
+        } else {
+            Some(response)
+        }
+    }
+
+    async fn respond(
+        &self,
+        endpoint: &APIEndpoint,
+        data: Option<Value>,
+        files: Vec<String>,
+    ) -> Result<Response> {
+        match self.record(endpoint, data, files) {
+            Some(response) => Ok(response),
+            None => futures::future::pending().await,
+        }
+    }
+}
+
+fn default_result(method: &str, data: &Value, message_id: i64) -> Value {
+    match method {
+        "getMe" => json!({"id": 1, "is_bot": true, "first_name": "test", "username": "test_bot"}),
+        "getFile" => json!({
+            "file_id": data["file_id"],
+            "file_unique_id": data["file_id"],
+            "file_path": data["file_id"],
+        }),
+        _ => synthesized_result(method, data, message_id),
+    }
+}
+
+#[async_trait]
+impl API for MockAPI {
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.respond(&endpoint, data, Vec::new()).await
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.respond(&endpoint, data, Vec::new()).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        let names = files
+            .unwrap_or_default()
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        self.respond(&endpoint, data, names).await
+    }
+
+    async fn download(&self, file_path: &str) -> Result<Vec<u8>> {
+        let mut state = self.state.lock();
+        state.calls.push(RecordedCall {
+            method: "download".to_owned(),
+            data: json!({ "file_path": file_path }),
+            files: Vec::new(),
+        });
+        state
+            .files
+            .get(file_path)
+            .cloned()
+            .ok_or_else(|| TelegramError::NotFound.into())
+    }
+
+    fn reset_connection(&self) {
+        self.state.lock().connection_resets += 1;
+    }
+}
@@ -0,0 +1,15 @@
+//! Utilities for testing bots without talking to telegram.
+//!
+//! A [`Harness`] runs the handlers, commands and middleware of a [`Client`]
+//! against a [`MockAPI`], so fixture updates from the [`fixtures`] module can
+//! be fed into it and the calls the bot made in response asserted on, all
+//! inside a plain `#[tokio::test]`.
+//!
+//! [`Client`]: ../client/struct.Client.html
+
+pub mod fixtures;
+mod harness;
+mod mock_api;
+
+pub use harness::{CallExpectation, Harness};
+pub use mock_api::{MockAPI, RecordedCall};
@@ -1,30 +1,56 @@
 use super::result::{Result, TelegramError};
+use futures::Future;
+use hyper::body::Sender;
+use parking_lot::Mutex;
 use serde_json::{Map, Value};
 use std::{
+    fmt,
     fs::File,
     io::{Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
 };
+use tokio::io::{AsyncRead, AsyncReadExt};
 
+/// The size of the chunks files are streamed from the disk in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Reader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// A file, or another field, sent as part of a multipart form by
+/// [`API::post_file`]
+///
+/// [`API::post_file`]: crate::api::API::post_file
 #[derive(Debug, Clone, PartialEq)]
 pub struct FormDataFile {
-    pub bytes: Vec<u8>,
+    /// The contents of the field
+    pub contents: FormDataContents,
+    /// The name of the field
     pub name: String,
+    /// The name of the file, if the field is one
     pub file_name: Option<String>,
+    /// The media type of the file, if known
     pub media_type: Option<String>,
 }
 
+/// Where the contents of a [`FormDataFile`] come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormDataContents {
+    /// The contents, kept in memory
+    Bytes(Vec<u8>),
+    /// A file on disk, which is streamed from while uploading
+    Path(PathBuf),
+    /// A reader, which is streamed from while uploading
+    Stream(FileStream),
+}
+
 impl FormDataFile {
     pub fn new(bytes: &[u8], media_type: &str, file_name: &str) -> Self {
         Self {
-            bytes: bytes.to_vec(),
-            name: file_name
-                .splitn(1, '.')
-                .collect::<Vec<&str>>()
-                .first()
-                .unwrap_or(&"new_file")
-                .to_owned()
-                .to_owned(),
+            contents: FormDataContents::Bytes(bytes.to_vec()),
+            name: file_name.to_owned(),
             media_type: Some(media_type.to_owned()),
             file_name: Some(file_name.to_owned()),
         }
@@ -35,18 +61,96 @@ impl FormDataFile {
         file.read_to_end(&mut bytes)?;
 
         Ok(Self {
-            bytes,
-            name: file_name
-                .splitn(1, '.')
-                .collect::<Vec<&str>>()
-                .first()
-                .unwrap_or(&"new_file")
-                .to_owned()
-                .to_owned(),
+            contents: FormDataContents::Bytes(bytes),
+            name: file_name.to_owned(),
+            file_name: Some(file_name.to_owned()),
+            media_type: Some(get_media_type(file_name)?.to_owned()),
+        })
+    }
+
+    pub fn new_from_bytes(bytes: Vec<u8>, file_name: &str) -> Result<Self> {
+        Ok(Self {
+            contents: FormDataContents::Bytes(bytes),
+            name: file_name.to_owned(),
+            file_name: Some(file_name.to_owned()),
+            media_type: Some(get_media_type(file_name)?.to_owned()),
+        })
+    }
+
+    /// Creates a file which is streamed from the file at the path while
+    /// uploading, instead of being read into memory first, so even files of
+    /// several gigabytes can be sent to a local bot API server
+    pub fn new_from_path<P: Into<PathBuf>>(path: P, file_name: &str) -> Result<Self> {
+        Ok(Self {
+            contents: FormDataContents::Path(path.into()),
+            name: file_name.to_owned(),
             file_name: Some(file_name.to_owned()),
             media_type: Some(get_media_type(file_name)?.to_owned()),
         })
     }
+
+    /// Creates a file which is streamed from the reader while uploading, e.g.
+    /// the body of a download being passed on to telegram
+    pub fn new_from_reader<R>(reader: R, file_name: &str) -> Result<Self>
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        Ok(Self {
+            contents: FormDataContents::Stream(FileStream::new(reader)),
+            name: file_name.to_owned(),
+            file_name: Some(file_name.to_owned()),
+            media_type: Some(get_media_type(file_name)?.to_owned()),
+        })
+    }
+
+    /// Whether the contents are read while uploading, instead of being kept in
+    /// memory
+    pub fn is_streamed(&self) -> bool {
+        !matches!(self.contents, FormDataContents::Bytes(_))
+    }
+
+    /// Whether the file can be sent again after being uploaded, which isn't
+    /// the case for a reader
+    pub fn can_resend(&self) -> bool {
+        !matches!(self.contents, FormDataContents::Stream(_))
+    }
+}
+
+/// A reader a [`FormDataFile`] is streamed from while uploading. The reader
+/// can only be uploaded once, clones share it instead of copying the
+/// contents.
+#[derive(Clone)]
+pub struct FileStream(Arc<Mutex<Option<Reader>>>);
+
+impl FileStream {
+    /// Wraps the reader, so it can be streamed from once while uploading
+    pub fn new<R>(reader: R) -> Self
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(Some(Box::pin(reader)))))
+    }
+
+    /// Takes the reader out to be uploaded, failing if it already was
+    fn take(&self) -> Result<Reader> {
+        self.0.lock().take().ok_or_else(|| {
+            TelegramError::InvalidArgument("the stream was already uploaded".to_owned()).into()
+        })
+    }
+}
+
+impl fmt::Debug for FileStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileStream")
+            .field("uploaded", &self.0.lock().is_none())
+            .finish()
+    }
+}
+
+impl PartialEq for FileStream {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 pub static BOUNDARY: &str = "----------telexide-form-data-boundary";
@@ -55,45 +159,119 @@ pub fn encode_multipart_form_data(files: &[FormDataFile]) -> Result<Vec<u8>> {
     let mut data = Vec::new();
 
     for file in files {
-        write!(&mut data, "--{}\r\n", BOUNDARY)?;
-
-        if file.file_name.is_some() {
-            write!(
-                &mut data,
-                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
-                file.name,
-                file.file_name.as_ref().unwrap()
-            )?;
-        } else {
-            write!(
-                &mut data,
-                "Content-Disposition: form-data; name=\"{}\"\r\n",
-                file.name
-            )?;
-        }
+        write_part_header(&mut data, file)?;
 
-        if file.media_type.is_some() {
-            write!(
-                &mut data,
-                "Content-Type: {}\r\n",
-                file.media_type.as_ref().unwrap()
-            )?;
+        match &file.contents {
+            FormDataContents::Bytes(bytes) => data.extend_from_slice(bytes),
+            FormDataContents::Path(path) => {
+                File::open(path)?.read_to_end(&mut data)?;
+            },
+            FormDataContents::Stream(_) => {
+                return Err(TelegramError::InvalidArgument(
+                    "streams can only be uploaded with stream_multipart_form_data".to_owned(),
+                )
+                .into())
+            },
         }
 
         write!(&mut data, "\r\n")?;
+    }
 
-        file.bytes.as_slice().read_to_end(&mut data)?;
+    write!(&mut data, "--{BOUNDARY}--\r\n")?;
 
-        write!(&mut data, "\r\n")?;
+    Ok(data)
+}
+
+/// Writes the multipart form into the body as it's sent, streaming the files
+/// with a path from the disk in chunks. The body is aborted if a file can't
+/// be read, or if reading or sending a chunk takes longer than the
+/// `idle_timeout`, so the request fails instead of sending a truncated form.
+pub async fn stream_multipart_form_data(
+    files: &[FormDataFile],
+    mut body: Sender,
+    idle_timeout: Duration,
+) -> Result<()> {
+    let res = write_multipart_form_data(files, &mut body, idle_timeout).await;
+    if res.is_err() {
+        body.abort();
     }
+    res
+}
 
-    write!(&mut data, "--{}--\r\n", BOUNDARY)?;
+async fn write_multipart_form_data(
+    files: &[FormDataFile],
+    body: &mut Sender,
+    idle_timeout: Duration,
+) -> Result<()> {
+    for file in files {
+        let mut part = Vec::new();
+        write_part_header(&mut part, file)?;
 
-    Ok(data)
+        let mut reader: Reader = match &file.contents {
+            FormDataContents::Bytes(bytes) => {
+                part.extend_from_slice(bytes);
+                part.extend_from_slice(b"\r\n");
+                idle(idle_timeout, body.send_data(part.into())).await??;
+                continue;
+            },
+            FormDataContents::Path(path) => Box::pin(tokio::fs::File::open(path).await?),
+            FormDataContents::Stream(stream) => stream.take()?,
+        };
+
+        idle(idle_timeout, body.send_data(part.into())).await??;
+        loop {
+            let mut chunk = vec![0; CHUNK_SIZE];
+            let read = idle(idle_timeout, reader.read(&mut chunk)).await??;
+            if read == 0 {
+                break;
+            }
+            chunk.truncate(read);
+            idle(idle_timeout, body.send_data(chunk.into())).await??;
+        }
+        idle(idle_timeout, body.send_data("\r\n".into())).await??;
+    }
+
+    let end = format!("--{BOUNDARY}--\r\n");
+    idle(idle_timeout, body.send_data(end.into())).await??;
+    Ok(())
+}
+
+/// runs a step of sending the form, failing when it takes longer than the
+/// timeout
+async fn idle<F: Future>(timeout: Duration, step: F) -> Result<F::Output> {
+    tokio::time::timeout(timeout, step)
+        .await
+        .map_err(|_| TelegramError::Timeout(timeout).into())
+}
+
+/// writes the boundary and headers starting the part of the field
+fn write_part_header(data: &mut Vec<u8>, file: &FormDataFile) -> Result<()> {
+    write!(data, "--{BOUNDARY}\r\n")?;
+
+    if let Some(file_name) = &file.file_name {
+        write!(
+            data,
+            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+            file.name, file_name
+        )?;
+    } else {
+        write!(
+            data,
+            "Content-Disposition: form-data; name=\"{}\"\r\n",
+            file.name
+        )?;
+    }
+
+    if let Some(media_type) = &file.media_type {
+        write!(data, "Content-Type: {media_type}\r\n")?;
+    }
+
+    write!(data, "\r\n")?;
+    Ok(())
 }
 
-pub fn encode_file_as_multipart_form_data(mut file: &mut File, file_name: &str) -> Result<Vec<u8>> {
-    encode_multipart_form_data(&[FormDataFile::new_from_file(&mut file, file_name)?])
+pub fn encode_file_as_multipart_form_data(file: &mut File, file_name: &str) -> Result<Vec<u8>> {
+    encode_multipart_form_data(&[FormDataFile::new_from_file(file, file_name)?])
 }
 
 fn get_media_type(file_name: &str) -> Result<&str> {
@@ -149,11 +327,13 @@ impl AsFormData for Value {
                 name: key,
                 file_name: None,
                 media_type: None,
-                bytes: serde_json::to_string(&value)?
-                    .trim_matches('"')
-                    .as_bytes()
-                    .to_vec(),
-            })
+                contents: FormDataContents::Bytes(
+                    serde_json::to_string(&value)?
+                        .trim_matches('"')
+                        .as_bytes()
+                        .to_vec(),
+                ),
+            });
         }
 
         Ok(res)
@@ -2,4 +2,10 @@ mod form_data;
 pub mod macros;
 pub mod result;
 
-pub(crate) use form_data::{encode_multipart_form_data, AsFormData, FormDataFile, BOUNDARY};
+pub use form_data::{FileStream, FormDataContents, FormDataFile};
+pub(crate) use form_data::{
+    encode_multipart_form_data,
+    stream_multipart_form_data,
+    AsFormData,
+    BOUNDARY,
+};
@@ -1,4 +1,4 @@
-use crate::framework::types::CommandError;
+use crate::{api::BotCapability, framework::types::CommandError};
 
 /// The common result type between most library functions.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -30,6 +30,9 @@ pub enum TelegramError {
     InvalidCommandType,
     WebhookError,
     InvalidArgument(String),
+    MissingCapability(BotCapability),
+    CircuitOpen(std::time::Duration),
+    Timeout(std::time::Duration),
     APIResponseError(String),
     Unknown(String),
 }
@@ -54,6 +57,15 @@ impl TelegramError {
                 "This action cannot be done on this command type".to_owned()
             },
             TelegramError::InvalidArgument(ref e) => format!("Invalid argument provided: {}", e),
+            TelegramError::MissingCapability(c) => {
+                format!("The bot is missing a capability: {}", c.botfather_hint())
+            },
+            TelegramError::CircuitOpen(retry_in) => {
+                format!("telegram is unreachable, requests are rejected for another {retry_in:?}")
+            },
+            TelegramError::Timeout(timeout) => {
+                format!("the request to telegram didn't finish within {timeout:?}")
+            },
             TelegramError::APIResponseError(ref e) => {
                 format!("the telegram api returned an error: {}", e)
             },
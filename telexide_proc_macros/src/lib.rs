@@ -8,20 +8,23 @@ mod utils;
 #[allow(unused_extern_crates)]
 extern crate proc_macro;
 
-use quote::quote;
+use quote::{format_ident, quote};
 use proc_macro::TokenStream;
 use syn::{
-    parse_macro_input
+    parse_macro_input, punctuated::Punctuated, token::Comma, Path
 };
 use crate::structs::{
     ListenerFunc, CommandFunc
 };
-use utils::{add_suffix, PunctuatedNamedArgs};
+use utils::{add_suffix, CooldownArgs, PunctuatedNamedArgs};
 
 /// A function attribute macro for making event listeners easier.
 ///
 /// This macro transforms an async function into a function returning a pinned box containing a future,
 /// which is used internally by telexide to store the function.
+///
+/// The function may return a `telexide::Result<()>`, errors are then passed to the error handler of
+/// the client.
 #[proc_macro_attribute]
 pub fn prepare_listener(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let listener = parse_macro_input!(item as ListenerFunc);
@@ -47,6 +50,27 @@ pub fn prepare_listener(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// |-------------|----------------------------------|---------------------------------------------------------------------------------------------|
 /// | Description | description = "your description" | The description of the command as to be displayed in telegram, 3-256 characters             |
 /// | Name        | name = "the command name"        | The name to be used within telegram, 1-32 characters                                        |
+/// | Ignore edits| ignore_edits = true              | Don't run the command for edited messages, when the framework handles edited commands       |
+///
+/// # Cooldowns
+///
+/// `#[cooldown(seconds)]` below `#[command]` limits how often the command can be used by every
+/// user, `#[cooldown(seconds, per = "chat")]` by every chat and `per = "global"` by everyone.
+/// Uses during the cooldown are dropped, unless a handler is set with `Framework::on_cooldown`.
+///
+/// # Checks
+///
+/// Checks are async functions taking the same arguments as the command and returning a
+/// `CheckResult`, which run before the command with `#[check(...)]` below `#[command]`.
+/// The command only runs if all of them pass, the message of the error of the first failing
+/// check is sent to the chat.
+/// ```rust,ignore
+/// async fn is_admin(ctx: Context, message: Message) -> CheckResult { ... }
+///
+/// #[command(description = "bans a user")]
+/// #[check(is_admin)]
+/// async fn ban(ctx: Context, message: Message) -> CommandResult { ... }
+/// ```
 ///
 /// # Notes
 ///
@@ -59,15 +83,46 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let mut telegram_command_name = command_fun.name.to_string();
     let mut description = String::new();
+    let mut ignore_edits = false;
 
     for arg in args.0 {
         match arg.name.as_str() {
             "name" => telegram_command_name =  arg.value.clone(),
             "description" => description = arg.value.clone(),
+            "ignore_edits" => match arg.boolean {
+                Some(value) => ignore_edits = value,
+                None => {
+                    return syn::Error::new(arg.span, "expected `ignore_edits = true` or `ignore_edits = false`")
+                        .to_compile_error()
+                        .into()
+                },
+            },
             _ => ()
         }
     }
 
+    let mut checks = Vec::new();
+    for attr in command_fun.attributes.iter().filter(|a| a.path.is_ident("check")) {
+        match attr.parse_args_with(Punctuated::<Path, Comma>::parse_terminated) {
+            Ok(paths) => checks.extend(paths),
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
+
+    let mut cooldown = quote!();
+    if let Some(attr) = command_fun.attributes.iter().find(|a| a.path.is_ident("cooldown")) {
+        match attr.parse_args::<CooldownArgs>() {
+            Ok(CooldownArgs { seconds, bucket }) => {
+                let bucket = format_ident!("{}", bucket);
+                cooldown = quote!(.set_cooldown(telexide::framework::types::Cooldown {
+                    seconds: #seconds,
+                    bucket: telexide::framework::types::CooldownBucket::#bucket,
+                }));
+            },
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
+
     if description.len() < 3 {
         panic!(
             "No description longer than 3 characters has been provided for the {} command, while descriptions are required by telegram",
@@ -85,13 +140,23 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
     let command_struct_path = quote!(telexide::framework::types::TelegramCommand);
     let options_struct_path = quote!(telexide::framework::types::CommandOptions);
     let default_command_type_path = quote!(telexide::framework::types::CommandTypes::Default);
+    let check_outcome_path = quote!(
+        ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = telexide::framework::CheckResult> + ::std::marker::Send )>>
+    );
 
     (quote!{
         #(#options_cooked)*
-        pub static #options_name: #options_struct_path = #options_struct_path {
-            name: #telegram_command_name,
-            description: #description,
-        };
+        pub static #options_name: #options_struct_path = #options_struct_path::new(
+            #telegram_command_name,
+            #description,
+        )
+        .set_ignore_edits(#ignore_edits)
+        .set_checks(&[#(
+            |ctx: telexide::client::Context, message: telexide::model::Message| -> #check_outcome_path {
+                ::std::boxed::Box::pin(#checks(ctx, message))
+            }
+        ),*])
+        #cooldown;
 
         #(#command_cooked)*
         pub static #command_name: #command_struct_path = #command_struct_path {
@@ -12,6 +12,7 @@ pub struct ListenerFunc {
     pub cooked: Vec<Attribute>,
     pub visibility: Visibility,
     pub name: Ident,
+    pub ret: Type,
     pub args: Vec<FnArg>,
     pub body: Vec<Stmt>,
 }
@@ -31,12 +32,9 @@ impl Parse for ListenerFunc {
 
         let ParenthesisedItems(args) = input.parse::<ParenthesisedItems<FnArg>>()?;
 
-        match input.parse::<ReturnType>()? {
-            ReturnType::Type(_, _) => {
-                return Err(input
-                    .error("expected a default return value"))
-            },
-            ReturnType::Default => ()
+        let ret = match input.parse::<ReturnType>()? {
+            ReturnType::Type(_, t) => *t,
+            ReturnType::Default => syn::parse_quote!(()),
         };
 
         let body_content;
@@ -50,6 +48,7 @@ impl Parse for ListenerFunc {
             cooked,
             visibility,
             name,
+            ret,
             args,
             body,
         })
@@ -63,13 +62,14 @@ impl ToTokens for ListenerFunc {
             cooked,
             visibility,
             name,
+            ret,
             args,
             body,
         } = self;
 
         stream.extend(quote! {
             #(#cooked)*
-            #visibility fn #name (#(#args),*) -> ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = ()> + ::std::marker::Send )>> {
+            #visibility fn #name (#(#args),*) -> ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = #ret> + ::std::marker::Send )>> {
                 ::std::boxed::Box::pin(async move {
                     #(#body)*
             })
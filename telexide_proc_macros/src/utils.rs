@@ -1,10 +1,13 @@
-use proc_macro2::{Ident, Literal};
+use proc_macro2::{Ident, Literal, Span};
 use quote::format_ident;
 use syn::{
     parenthesized,
     parse::{Parse, ParseStream, Result},
     punctuated::Punctuated,
     token::Comma,
+    LitBool,
+    LitInt,
+    LitStr,
     Token,
 };
 
@@ -29,19 +32,72 @@ impl Parse for PunctuatedNamedArgs {
 pub struct NamedArgs {
     pub name: String,
     pub value: String,
+    /// The value if it's a `true` or `false` literal
+    pub boolean: Option<bool>,
+    pub span: Span,
 }
 
 impl Parse for NamedArgs {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let name = input.parse::<Ident>()?.to_string();
         input.parse::<Token![=]>()?;
-        let mut value = input.parse::<Literal>()?.to_string();
+        if input.peek(LitBool) {
+            let value = input.parse::<LitBool>()?;
+            return Ok(Self {
+                name,
+                value: value.value.to_string(),
+                boolean: Some(value.value),
+                span: value.span,
+            });
+        }
+        let literal = input.parse::<Literal>()?;
+        let mut value = literal.to_string();
         value = value.trim_start_matches('\"').to_owned();
         value = value.trim_end_matches('\"').to_owned();
 
         Ok(Self {
             name,
             value,
+            boolean: None,
+            span: literal.span(),
+        })
+    }
+}
+
+/// The arguments of `#[cooldown(seconds, per = "bucket")]`
+pub struct CooldownArgs {
+    pub seconds: u64,
+    /// The name of the `CooldownBucket` variant
+    pub bucket: &'static str,
+}
+
+impl Parse for CooldownArgs {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let seconds = input.parse::<LitInt>()?.base10_parse()?;
+        let mut bucket = "User";
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let name = input.parse::<Ident>()?;
+            if name != "per" {
+                return Err(syn::Error::new(name.span(), "expected `per = \"...\"`"));
+            }
+            input.parse::<Token![=]>()?;
+            let per = input.parse::<LitStr>()?;
+            bucket = match per.value().as_str() {
+                "user" => "User",
+                "chat" => "Chat",
+                "global" => "Global",
+                _ => {
+                    return Err(syn::Error::new(
+                        per.span(),
+                        "expected \"user\", \"chat\" or \"global\"",
+                    ))
+                },
+            };
+        }
+
+        Ok(Self {
+            seconds,
+            bucket,
         })
     }
 }
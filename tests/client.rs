@@ -1,7 +1,68 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use futures::Future;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use telexide::{
-    client::{ClientBuilder, Context},
-    model::{Update, UpdateContent},
+    api::{
+        types::{
+            AnswerCallbackQuery,
+            GetUpdates,
+            InputMedia,
+            InputMediaPhoto,
+            SendMediaGroup,
+            SendMessage,
+            UpdateType,
+        },
+        APIClient,
+        APIEndpoint,
+        APIExt,
+        CircuitBreaker,
+        CircuitMode,
+        CircuitState,
+        DryRun,
+        FormDataFile,
+        Proxy,
+        Response,
+        Throttled,
+        API,
+    },
+    client::{
+        ChatTypes,
+        Client,
+        ClientBuilder,
+        ClientMode,
+        Context,
+        HandlerError,
+        LiveConfig,
+        OutgoingText,
+        PollStall,
+        RuntimeConfig,
+        SendDefaults,
+        UpdatesStream,
+        WebhookOptions,
+    },
+    framework::Framework,
+    helpers::formatting::{bold, compose},
+    metrics::HandlerMetrics,
+    model::{
+        Chat,
+        ChatType,
+        File,
+        InlineKeyboardButton,
+        InlineKeyboardMarkup,
+        ParseMode,
+        ReplyKeyboardRemove,
+        ReplyMarkup,
+        Update,
+        UpdateContent,
+    },
+    runtime::{BoxedTask, Runtime},
+    storage::MemoryStorage,
+    test::{fixtures, Harness, MockAPI},
     Result,
 };
 
@@ -27,6 +88,19 @@ async fn update_handler_gets_called() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn context_stays_cheap_to_clone() {
+    fn assert_bounds<T: Clone + Send + Sync + 'static>() {}
+    assert_bounds::<Context>();
+    assert_bounds::<Client>();
+
+    // the api client and the data, cloned into every handler
+    assert_eq!(
+        std::mem::size_of::<Context>(),
+        2 * std::mem::size_of::<usize>()
+    );
+}
+
 static FUNC_B: AtomicUsize = AtomicUsize::new(0);
 
 fn testing_func(
@@ -54,3 +128,1703 @@ async fn test_using_func() -> Result<()> {
     assert_eq!(FUNC_B.load(Ordering::Relaxed), 10);
     Ok(())
 }
+
+#[tokio::test]
+async fn handler_metrics_detect_slow_handlers() -> Result<()> {
+    let metrics = Arc::new(HandlerMetrics::new().set_slow_threshold(Duration::from_millis(20)));
+    let mut slow = metrics.subscribe();
+
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_handler_metrics(metrics.clone())
+        .add_handler_func(|_c, _u| Box::pin(async {}))
+        .add_handler_func(|_c, _u| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_millis(40)).await;
+            })
+        })
+        .build();
+
+    c.fire_handlers(Update {
+        update_id: 1,
+        content: UpdateContent::Unknown,
+    });
+
+    let event = tokio::time::timeout(Duration::from_secs(1), slow.recv())
+        .await
+        .expect("no slow handler event")
+        .expect("event channel closed");
+    assert_eq!(event.handler, "handler#1");
+    assert_eq!(event.update_type, "unknown");
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let stats = metrics.all_stats();
+    assert_eq!(stats["handler#0"].invocations, 1);
+    assert!(stats["handler#1"].p50 >= Duration::from_millis(40));
+    Ok(())
+}
+
+#[tokio::test]
+async fn watchdog_recovers_from_hung_poll() -> Result<()> {
+    use futures::StreamExt;
+
+    let api = MockAPI::new();
+    api.hang_next("getUpdates", 1);
+    api.respond_with("getUpdates", serde_json::json!([{"update_id": 7}]));
+    let stalls = Arc::new(parking_lot::Mutex::new(Vec::<PollStall>::new()));
+
+    let mut stream = UpdatesStream::new(Arc::new(Box::new(api.clone())));
+    let recorded = stalls.clone();
+    stream
+        .set_timout(0)
+        .set_watchdog(Some(1))
+        .on_stall(move |s| recorded.lock().push(s));
+
+    let update = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("the watchdog didn't recover from the hung poll")
+        .expect("the stream ended")?;
+
+    assert_eq!(update.update_id, 7);
+    assert_eq!(api.calls_to("getUpdates").len(), 2);
+    assert_eq!(api.connection_resets(), 1);
+    let stalls = stalls.lock();
+    assert_eq!(stalls.len(), 1);
+    assert!(stalls[0].stalled_for >= Duration::from_secs(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn polling_resumes_after_the_stored_offset() -> Result<()> {
+    use futures::StreamExt;
+    use telexide::storage::{FileOffsetStorage, OffsetStorage};
+
+    let path = std::env::temp_dir().join("telexide-offset");
+    let storage = Arc::new(FileOffsetStorage::new(&path));
+    storage.save(41).await?;
+
+    let api = MockAPI::new();
+    api.respond_with("getUpdates", serde_json::json!([{"update_id": 42}]));
+    let mut stream = UpdatesStream::new(Arc::new(Box::new(api.clone())));
+    stream.set_timout(0).set_offset_storage(storage.clone());
+
+    // polling again confirms the update, storing its id
+    stream.next().await.expect("the stream ended")?;
+    stream.next().await.expect("the stream ended")?;
+    let offsets: Vec<_> = api
+        .calls_to("getUpdates")
+        .iter()
+        .map(|c| c.data["offset"].clone())
+        .collect();
+    assert_eq!(offsets, vec![42, 43]);
+    assert_eq!(storage.load().await?, Some(42));
+
+    std::fs::remove_file(&path)?;
+    assert_eq!(storage.load().await?, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn raw_calls_reach_methods_by_name() -> Result<()> {
+    let api = MockAPI::new();
+    api.respond_with("getChatMenuButton", serde_json::json!({"type": "commands"}));
+    api.fail_with("setFutureOption", 400, "Bad Request: unknown option");
+
+    let button = api
+        .call_raw("getChatMenuButton", serde_json::json!({"chat_id": 3}))
+        .await?;
+    assert_eq!(button, serde_json::json!({"type": "commands"}));
+    assert_eq!(api.calls_to("getChatMenuButton")[0].data["chat_id"], 3);
+
+    let err = api
+        .call_raw("setFutureOption", serde_json::Value::Null)
+        .await
+        .expect_err("telegram rejected the call");
+    assert!(err.to_string().contains("unknown option"));
+    assert!(api.calls_to("setFutureOption")[0].data.is_null());
+
+    let err = api
+        .call_raw("sendMessage", serde_json::json!([1, "text"]))
+        .await
+        .expect_err("the parameters aren't an object");
+    assert!(err.to_string().contains("json object"));
+    assert!(api.calls_to("sendMessage").is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn files_are_downloaded_again_after_their_link_expired() -> Result<()> {
+    let api = MockAPI::new();
+    api.add_file("documents/new.txt", "file contents");
+    api.respond_with(
+        "getFile",
+        serde_json::json!({
+            "file_id": "abc",
+            "file_unique_id": "a",
+            "file_path": "documents/new.txt",
+        }),
+    );
+    let expired = File {
+        file_id: "abc".to_owned(),
+        file_unique_id: "a".to_owned(),
+        file_size: None,
+        file_path: Some("documents/old.txt".to_owned()),
+    };
+
+    assert_eq!(api.download_file(&expired).await?, b"file contents");
+    assert_eq!(api.calls_to("getFile")[0].data["file_id"], "abc");
+
+    let mut written = Vec::new();
+    let unfetched = File {
+        file_path: None,
+        ..expired
+    };
+    assert_eq!(api.download_file_to(&unfetched, &mut written).await?, 13);
+    assert_eq!(written, b"file contents");
+    assert_eq!(api.calls_to("getFile").len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn dry_run_answers_writes_without_sending() -> Result<()> {
+    let mock = MockAPI::new();
+    let api = DryRun::new(mock.clone()).allow("answerCallbackQuery");
+
+    let message = api.send_message(SendMessage::new(-100, "hello")).await?;
+    assert_eq!(message.chat.get_id(), -100);
+    assert_eq!(message.get_text(), Some("hello".to_owned()));
+    assert!(mock.calls_to("sendMessage").is_empty());
+
+    api.get_me().await?;
+    assert_eq!(mock.calls_to("getMe").len(), 1);
+
+    api.answer_callback_query(AnswerCallbackQuery {
+        callback_query_id: "1".to_owned(),
+        text: None,
+        show_alert: false,
+        url: None,
+        cache_time: None,
+    })
+    .await?;
+    assert_eq!(mock.calls_to("answerCallbackQuery").len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn live_config_reconfigures_a_running_client() -> Result<()> {
+    static RECONFIGURED: AtomicUsize = AtomicUsize::new(0);
+
+    let api = MockAPI::new();
+    api.hang_next("getUpdates", 10);
+    let config = LiveConfig::new(RuntimeConfig::default());
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_live_config(config.clone())
+        .add_reconfigure_handler_func(|ctx, config| {
+            Box::pin(async move {
+                assert_eq!(ctx.config(), config);
+                RECONFIGURED.fetch_add(1, Ordering::Relaxed);
+            })
+        })
+        .build();
+
+    let polls = |n: usize| {
+        let api = api.clone();
+        async move {
+            while api.calls_to("getUpdates").len() < n {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+    };
+    let reconfigure = async {
+        tokio::time::timeout(Duration::from_secs(5), polls(1))
+            .await
+            .expect("the client didn't start polling");
+
+        config.update(|c| {
+            c.default_parse_mode = Some(ParseMode::HTML);
+            c.allowed_updates = Some(vec![UpdateType::CallbackQuery]);
+            c.set_toggle("welcome", true);
+        });
+        tokio::time::timeout(Duration::from_secs(5), polls(2))
+            .await
+            .expect("the poll wasn't restarted with the new allowed updates");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    tokio::select! {
+        res = client.start() => panic!("the client stopped: {:?}", res.err()),
+        () = reconfigure => {},
+    }
+
+    let calls = api.calls_to("getUpdates");
+    assert_eq!(calls[0].data["allowed_updates"], serde_json::json!([]));
+    assert_eq!(
+        calls[1].data["allowed_updates"],
+        serde_json::json!(["callback_query"])
+    );
+    assert_eq!(RECONFIGURED.load(Ordering::Relaxed), 1);
+    assert!(config.get().is_enabled("welcome"));
+
+    client
+        .api_client
+        .send_message(SendMessage::new(1, "<b>hi</b>"))
+        .await?;
+    let mut formatted = SendMessage::new(1, "hi");
+    formatted.set_formatted_text(&compose(vec![bold("hi")]));
+    client.api_client.send_message(formatted).await?;
+    let sent = api.calls_to("sendMessage");
+    assert_eq!(sent[0].data["parse_mode"], "HTML");
+    assert!(sent[1].data["parse_mode"].is_null());
+    Ok(())
+}
+
+#[test]
+fn every_client_shares_its_chat_types_in_the_data() -> Result<()> {
+    let channel: Chat = serde_json::from_value(serde_json::json!({
+        "id": -1_001_234_567_890_i64,
+        "type": "channel",
+        "title": "news",
+    }))?;
+    let clients = [
+        Client::new("token"),
+        Client::with_framework(Arc::new(Framework::new("bot")), "token"),
+        Client::from(Box::new(MockAPI::new()) as Box<dyn API + Send>),
+        ClientBuilder::new()
+            .set_api_client(Arc::new(Box::new(MockAPI::new())))
+            .build(),
+    ];
+    for client in clients {
+        let data = client.data.read();
+        let chat_types = data
+            .get::<ChatTypes>()
+            .expect("the chat types are in the data");
+        client.chat_types().note(&channel);
+        assert!(chat_types.is_channel(channel.get_id()));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn chat_defaults_depend_on_the_chat_type() -> Result<()> {
+    let mut config = RuntimeConfig::default();
+    config
+        .set_chat_defaults(ChatType::Channel, SendDefaults::new().silent().clone())
+        .set_chat_defaults(ChatType::Private, SendDefaults::new().protected().clone());
+    let config = LiveConfig::new(config);
+    let harness = Harness::new(ClientBuilder::new().set_live_config(config.clone()));
+    let channel = -1_001_234_567_890;
+    let chat_types = harness.client().chat_types();
+    assert_eq!(chat_types.chat_type(channel), ChatType::SuperGroup);
+
+    harness
+        .feed(fixtures::update(
+            "channel_post",
+            serde_json::json!({
+                "message_id": 1,
+                "date": 1_600_000_000,
+                "chat": {"id": channel, "type": "channel", "title": "news"},
+                "text": "hi",
+            }),
+        ))
+        .await;
+    assert_eq!(chat_types.chat_type(channel), ChatType::Channel);
+
+    let api = &harness.client().api_client;
+    api.send_message(SendMessage::new(channel, "to the channel"))
+        .await?;
+    api.send_message(SendMessage::new(5, "to a user")).await?;
+    api.send_message(SendMessage::new(-1_009_999_999_999, "to a supergroup"))
+        .await?;
+
+    let sent = harness.api().calls_to("sendMessage");
+    assert_eq!(sent[0].data["disable_notification"], true);
+    assert_eq!(sent[0].data["protect_content"], false);
+    assert_eq!(sent[1].data["disable_notification"], false);
+    assert_eq!(sent[1].data["protect_content"], true);
+    assert_eq!(sent[2].data["disable_notification"], false);
+    assert_eq!(sent[2].data["protect_content"], false);
+
+    // channels the client didn't get an update from yet are looked up once
+    let unseen = -1_005_555_555_555;
+    harness.api().respond_with(
+        "getChat",
+        serde_json::json!({"id": unseen, "type": "channel", "title": "other news"}),
+    );
+    harness.api().clear_calls();
+    for text in ["first", "second"] {
+        api.send_message(SendMessage::new(unseen, text)).await?;
+    }
+    assert_eq!(harness.api().calls_to("getChat").len(), 1);
+    assert_eq!(chat_types.chat_type(unseen), ChatType::Channel);
+    let sent = harness.api().calls_to("sendMessage");
+    assert!(sent.iter().all(|c| c.data["disable_notification"] == true));
+
+    let parsed: RuntimeConfig =
+        serde_json::from_str(r#"{"chat_defaults": {"channel": {"disable_notification": true}}}"#)?;
+    assert!(parsed.chat_defaults(ChatType::Channel).disable_notification);
+    assert_eq!(
+        parsed.chat_defaults(ChatType::Group),
+        SendDefaults::default()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn channels_only_get_inline_keyboards() -> Result<()> {
+    let harness = Harness::new(&mut ClientBuilder::new());
+    let channel = -1_001_234_567_890;
+    let post = fixtures::update(
+        "channel_post",
+        serde_json::json!({
+            "message_id": 1,
+            "date": 1_600_000_000,
+            "chat": {"id": channel, "type": "channel", "title": "news"},
+            "sender_chat": {"id": channel, "type": "channel", "title": "news"},
+            "author_signature": "Jane",
+            "text": "hi",
+        }),
+    );
+    let UpdateContent::ChannelPost(message) = &post.content else {
+        panic!("no channel post")
+    };
+    assert!(message.is_channel_post());
+    assert_eq!(message.signature(), Some("Jane"));
+    assert_eq!(message.author_name().as_deref(), Some("Jane"));
+    harness.feed(post).await;
+
+    let remove = ReplyMarkup::ReplyKeyboardRemove(ReplyKeyboardRemove {
+        remove_keyboard: true,
+        selective: false,
+    });
+    let api = &harness.client().api_client;
+    let mut to_channel = SendMessage::new(channel, "removed");
+    to_channel.set_reply_markup(&remove);
+    assert!(api.send_message(to_channel).await.is_err());
+    assert!(harness.api().calls().is_empty());
+
+    // the type of channels the client didn't get an update from is looked up
+    let unseen = -1_005_555_555_555;
+    harness.api().respond_with(
+        "getChat",
+        serde_json::json!({"id": unseen, "type": "channel", "title": "other news"}),
+    );
+    let mut to_unseen = SendMessage::new(unseen, "removed");
+    to_unseen.set_reply_markup(&remove);
+    assert!(api.send_message(to_unseen).await.is_err());
+    assert!(harness.api().calls_to("sendMessage").is_empty());
+
+    let group = -1_009_999_999_999;
+    harness.api().respond_with(
+        "getChat",
+        serde_json::json!({"id": group, "type": "supergroup", "title": "chat"}),
+    );
+    let mut to_group = SendMessage::new(group, "removed");
+    to_group.set_reply_markup(&remove);
+    api.send_message(to_group).await?;
+
+    let inline = ReplyMarkup::InlineKeyboardMarkup(InlineKeyboardMarkup {
+        inline_keyboard: vec![vec![InlineKeyboardButton {
+            text: "read more".to_owned(),
+            url: Some("https://example.com".to_owned()),
+            login_url: None,
+            callback_data: None,
+            switch_inline_query: None,
+            switch_inline_query_current_chat: None,
+            callback_game: None,
+            pay: false,
+        }]],
+    });
+    let mut inline_post = SendMessage::new(channel, "with a button");
+    inline_post.set_reply_markup(&inline);
+    api.send_message(inline_post).await?;
+    assert_eq!(harness.api().calls_to("sendMessage").len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn text_sanitizers_rewrite_outgoing_texts() -> Result<()> {
+    let api = MockAPI::new();
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .add_text_sanitizer(|text: &mut OutgoingText| {
+            if text.parse_mode == Some(ParseMode::HTML) {
+                text.text = text.text.replace("<script>", "");
+            }
+        })
+        .add_text_sanitizer(|text: &mut OutgoingText| {
+            if !text.has_entities {
+                text.text.push_str(" ~bot");
+            }
+        })
+        .build();
+
+    let mut html = SendMessage::new(1, "<script>hi");
+    html.set_parse_mode(&ParseMode::HTML);
+    client.api_client.send_message(html).await?;
+    let mut formatted = SendMessage::new(1, "hi");
+    formatted.set_formatted_text(&compose(vec![bold("hi")]));
+    client.api_client.send_message(formatted).await?;
+
+    let mut photo = InputMediaPhoto::new("file-id".into());
+    photo.caption = Some("album".to_owned());
+    client
+        .api_client
+        .send_media_group(SendMediaGroup::new(1, vec![InputMedia::Photo(photo)]))
+        .await?;
+
+    let calls = api.calls();
+    assert_eq!(calls[0].data["text"], "hi ~bot");
+    assert_eq!(calls[1].data["text"], "hi");
+    assert_eq!(calls[2].data["media"][0]["caption"], "album ~bot");
+    Ok(())
+}
+
+#[tokio::test]
+async fn health_snapshot_reports_the_running_client() -> Result<()> {
+    let api = MockAPI::new();
+    api.respond_with(
+        "getWebhookInfo",
+        serde_json::json!({
+            "url": "https://example.com/hook",
+            "has_custom_certificate": false,
+            "pending_update_count": 2,
+        }),
+    );
+    let mut opts = WebhookOptions::new();
+    opts.set_port(0).set_health_path("/health");
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_webhook(&opts)
+        .add_health_storage("memory", Arc::new(MemoryStorage::new()))
+        .build();
+    client.subscribe_handler_func(|_, _| Box::pin(tokio::time::sleep(Duration::from_secs(10))));
+
+    let idle = client.health().await;
+    assert_eq!(idle.mode, ClientMode::Stopped);
+    assert!(!idle.is_healthy());
+
+    api.fail_with("sendMessage", 400, "Bad Request: chat not found");
+    client
+        .api_client
+        .send_message(SendMessage::new(1, "hi"))
+        .await
+        .unwrap_err();
+    client.api_client.get_me().await?;
+    client.fire_handlers(Update {
+        update_id: 1,
+        content: UpdateContent::Unknown,
+    });
+
+    let probe = || async {
+        let addr = client.webhook_addr().await;
+        let uri = format!("http://{addr}/health").parse().expect("valid uri");
+        let res = hyper::Client::new().get(uri).await?;
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        Ok::<_, telexide::Error>((status, serde_json::from_slice::<serde_json::Value>(&body)?))
+    };
+    let check = async {
+        let first = probe().await?;
+        probe().await?;
+        Ok::<_, telexide::Error>(first)
+    };
+    let (status, served) = tokio::select! {
+        res = client.start() => panic!("the client stopped: {:?}", res.err()),
+        served = check => served?,
+    };
+
+    assert_eq!(status, hyper::StatusCode::OK);
+    assert_eq!(served["mode"], "webhook");
+    assert_eq!(served["updates_received"], 1);
+    assert_eq!(served["queue_depth"], 1);
+    assert_eq!(served["webhook"]["pending_update_count"], 2);
+    assert!(served["webhook"].get("url").is_none());
+    assert_eq!(served["storage"][0]["name"], "memory");
+    assert!(served["storage"][0]["error"].is_null());
+    // the webhook info calls of the probes aren't counted
+    assert_eq!(served["api_calls"], 2);
+    assert_eq!(served["api_errors"], 1);
+    assert_eq!(api.calls_to("getWebhookInfo").len(), 1);
+    assert!(served["last_update"].is_i64());
+    assert_eq!(client.health().await.mode, ClientMode::Stopped);
+    Ok(())
+}
+
+#[tokio::test]
+async fn start_webhook_only_accepts_updates_with_the_secret() -> Result<()> {
+    let api = MockAPI::new();
+    let mut opts = WebhookOptions::new();
+    opts.set_url("https://example.com/hook")?
+        .set_secret_token("s3cret")
+        .set_max_connections(10)
+        .toggle_drop_pending_updates();
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_webhook(&opts)
+        .build();
+    let (tx, mut received) = tokio::sync::mpsc::unbounded_channel();
+    client.subscribe_handler_func(move |_, u| {
+        tx.send(u.update_id).ok();
+        async {}
+    });
+
+    // the path has to match the one telegram is told to send the updates to
+    assert!(client
+        .start_webhook(([127, 0, 0, 1], 0), "/other")
+        .await
+        .is_err());
+
+    let post = |update_id: i64, token: &'static str| {
+        let client = &client;
+        async move {
+            let addr = client.webhook_addr().await;
+            let req = hyper::Request::post(format!("http://{addr}/hook"))
+                .header("content-type", "application/json")
+                .header("x-telegram-bot-api-secret-token", token)
+                .body(hyper::Body::from(serde_json::to_string(&Update {
+                    update_id,
+                    content: UpdateContent::Unknown,
+                })?))?;
+            Ok::<_, telexide::Error>(hyper::Client::new().request(req).await?.status())
+        }
+    };
+    let check = async {
+        let accepted = post(3, "s3cret").await?;
+        let handled = received.recv().await;
+        let rejected = post(4, "wrong").await?;
+        Ok::<_, telexide::Error>((accepted, handled, rejected))
+    };
+    let (accepted, handled, rejected) = tokio::select! {
+        res = client.start_webhook(([127, 0, 0, 1], 0), "/hook") => {
+            panic!("the client stopped: {:?}", res.err())
+        },
+        statuses = check => statuses?,
+    };
+
+    assert_eq!(accepted, hyper::StatusCode::OK);
+    assert_eq!(handled, Some(3));
+    assert_eq!(rejected, hyper::StatusCode::UNAUTHORIZED);
+    assert!(received.try_recv().is_err());
+
+    let set = api.calls_to("setWebHook");
+    assert_eq!(set.len(), 1);
+    assert_eq!(set[0].data["url"], "https://example.com/hook");
+    assert_eq!(set[0].data["secret_token"], "s3cret");
+    assert_eq!(set[0].data["max_connections"], 10);
+    assert_eq!(set[0].data["drop_pending_updates"], true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn stale_callbacks_are_answered_when_stopping() -> Result<()> {
+    let api = MockAPI::new();
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_stale_callback_answer("restarting, please retry")
+        .build();
+    client.subscribe_handler_func(|ctx, u| {
+        Box::pin(async move {
+            let UpdateContent::CallbackQuery(q) = u.content else {
+                return;
+            };
+            if q.data.as_deref() == Some("quick") {
+                ctx.api
+                    .answer_callback_query(AnswerCallbackQuery {
+                        callback_query_id: q.id,
+                        text: None,
+                        show_alert: false,
+                        url: None,
+                        cache_time: None,
+                    })
+                    .await
+                    .expect("answering works");
+            }
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        })
+    });
+
+    let quick = fixtures::callback_query(1, 2, "quick");
+    let slow = fixtures::callback_query(1, 2, "slow");
+    let UpdateContent::CallbackQuery(slow_query) = &slow.content else {
+        unreachable!()
+    };
+    let slow_id = slow_query.id.clone();
+    client.fire_handlers(quick);
+    client.fire_handlers(slow);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(client.answer_stale_callbacks().await, 1);
+    assert_eq!(client.answer_stale_callbacks().await, 0);
+
+    let answers = api.calls_to("answerCallbackQuery");
+    assert_eq!(answers.len(), 2);
+    assert_eq!(answers[1].data["callback_query_id"], slow_id.as_str());
+    assert_eq!(answers[1].data["text"], "restarting, please retry");
+    Ok(())
+}
+
+#[tokio::test]
+async fn handlers_run_on_the_set_runtime() -> Result<()> {
+    static SPAWNED: AtomicUsize = AtomicUsize::new(0);
+    static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counting;
+
+    impl Runtime for Counting {
+        fn spawn(&self, task: BoxedTask) {
+            SPAWNED.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(task);
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxedTask {
+            Box::pin(tokio::time::sleep(duration))
+        }
+    }
+
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(MockAPI::new())))
+        .set_runtime(Counting)
+        .build();
+    client.subscribe_handler_func(|_, u| {
+        Box::pin(async move {
+            HANDLED.fetch_add(u.update_id as usize, Ordering::SeqCst);
+        })
+    });
+    client.subscribe_handler_func(|_, _| -> BoxedTask {
+        Box::pin(async { panic!("broken handler") })
+    });
+
+    client.fire_handlers(Update {
+        update_id: 5,
+        content: UpdateContent::Unknown,
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(HANDLED.load(Ordering::SeqCst), 5);
+    // the dispatch of the update and both of its handlers
+    assert_eq!(SPAWNED.load(Ordering::SeqCst), 3);
+    assert_eq!(client.health().await.queue_depth, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_the_handlers_in_flight() -> Result<()> {
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    let api = MockAPI::new();
+    api.hang_next("getUpdates", 100);
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_shutdown_timeout(Duration::from_secs(5))
+        .build();
+    client.subscribe_handler_func(|_, u| {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            FINISHED.fetch_add(u.update_id as usize, Ordering::SeqCst);
+        })
+    });
+
+    let stop = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.fire_handlers(Update {
+            update_id: 6,
+            content: UpdateContent::Unknown,
+        });
+        client.shutdown();
+    };
+    let (res, ()) = tokio::time::timeout(Duration::from_secs(2), async {
+        tokio::join!(client.start(), stop)
+    })
+    .await
+    .expect("the client stopped");
+    res?;
+    assert_eq!(FINISHED.load(Ordering::SeqCst), 6);
+    assert_eq!(client.health().await.mode, ClientMode::Stopped);
+    assert!(!client.shutdown_handle().is_stopping());
+    Ok(())
+}
+
+#[tokio::test]
+async fn shutdown_gives_up_on_handlers_after_the_timeout() -> Result<()> {
+    let api = MockAPI::new();
+    let mut opts = WebhookOptions::new();
+    opts.set_port(0);
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_webhook(&opts)
+        .set_shutdown_timeout(Duration::from_millis(100))
+        .build();
+    client.subscribe_handler_func(|_, _| Box::pin(futures::future::pending::<()>()));
+
+    let handle = client.shutdown_handle();
+    let stop = async {
+        let addr = client.webhook_addr().await;
+        client.fire_handlers(Update {
+            update_id: 1,
+            content: UpdateContent::Unknown,
+        });
+        handle.stop();
+        addr
+    };
+    let (res, addr) = tokio::time::timeout(Duration::from_secs(2), async {
+        tokio::join!(client.start(), stop)
+    })
+    .await
+    .expect("the client stopped");
+    res?;
+
+    assert_eq!(client.health().await.queue_depth, 1);
+    // the webhook server let go of its port
+    std::net::TcpListener::bind(addr)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn dispatch_is_bounded_by_the_max_concurrent_updates() -> Result<()> {
+    static RUNNING: AtomicUsize = AtomicUsize::new(0);
+    static MOST_RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let (started, mut starts) = tokio::sync::mpsc::unbounded_channel();
+    let mut opts = WebhookOptions::new();
+    opts.set_port(0);
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(MockAPI::new())))
+        .set_webhook(&opts)
+        .set_max_concurrent_updates(2)
+        .build();
+    client.subscribe_handler_func({
+        let gate = gate.clone();
+        move |_, _| {
+            let gate = gate.clone();
+            let started = started.clone();
+            Box::pin(async move {
+                let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+                MOST_RUNNING.fetch_max(running, Ordering::SeqCst);
+                started.send(()).ok();
+                gate.acquire().await.expect("gate is open").forget();
+                RUNNING.fetch_sub(1, Ordering::SeqCst);
+            })
+        }
+    });
+
+    let post = async {
+        let addr = client.webhook_addr().await;
+        // the webhook only takes the next update once a permit is free, so
+        // the updates are posted at the same time
+        let posting = (1..=5).map(|update_id| {
+            let req = hyper::Request::post(format!("http://{addr}/"))
+                .header("content-type", "application/json")
+                .body(hyper::Body::from(
+                    serde_json::json!({ "update_id": update_id }).to_string(),
+                ))
+                .expect("valid request");
+            hyper::Client::new().request(req)
+        });
+        let posted = tokio::spawn(futures::future::join_all(posting));
+
+        starts.recv().await;
+        starts.recv().await;
+        assert_eq!(RUNNING.load(Ordering::SeqCst), 2);
+        gate.add_permits(5);
+        for res in posted.await.expect("posting didn't panic") {
+            assert!(res?.status().is_success());
+        }
+        for _ in 0..3 {
+            starts.recv().await;
+        }
+        Ok::<_, telexide::Error>(())
+    };
+    tokio::select! {
+        res = client.start() => panic!("the client stopped: {:?}", res.err()),
+        posted = post => posted?,
+    };
+
+    assert_eq!(MOST_RUNNING.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+/// a handler sending the text of the messages it handles, where handling
+/// "slow" waits until the gate is opened
+fn gated_text_handler(
+    gate: Arc<tokio::sync::Notify>,
+    handled: tokio::sync::mpsc::UnboundedSender<String>,
+) -> impl Fn(Context, Update) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send>> {
+    move |_, u| {
+        let gate = gate.clone();
+        let handled = handled.clone();
+        Box::pin(async move {
+            let UpdateContent::Message(m) = u.content else {
+                return;
+            };
+            let text = m.get_text().unwrap_or_default();
+            if text == "slow" {
+                gate.notified().await;
+            }
+            handled.send(text).ok();
+        })
+    }
+}
+
+#[tokio::test]
+async fn updates_of_a_chat_are_handled_in_order() -> Result<()> {
+    let gate = Arc::new(tokio::sync::Notify::new());
+    let (tx, mut handled) = tokio::sync::mpsc::unbounded_channel();
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(MockAPI::new())))
+        .set_sequential_chats(true)
+        .build();
+    client.subscribe_handler_func(gated_text_handler(gate.clone(), tx));
+
+    client.fire_handlers(fixtures::text_message(1, 1, "slow"));
+    client.fire_handlers(fixtures::text_message(1, 1, "fast"));
+    client.fire_handlers(fixtures::text_message(2, 2, "other chat"));
+
+    assert_eq!(handled.recv().await.as_deref(), Some("other chat"));
+    gate.notify_one();
+    assert_eq!(handled.recv().await.as_deref(), Some("slow"));
+    assert_eq!(handled.recv().await.as_deref(), Some("fast"));
+    tokio::task::yield_now().await;
+    assert_eq!(client.health().await.queue_depth, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn updates_waiting_for_their_chat_dont_take_a_dispatch_permit() -> Result<()> {
+    let gate = Arc::new(tokio::sync::Notify::new());
+    let (tx, mut handled) = tokio::sync::mpsc::unbounded_channel();
+    let mut opts = WebhookOptions::new();
+    opts.set_port(0);
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(MockAPI::new())))
+        .set_webhook(&opts)
+        .set_max_concurrent_updates(2)
+        .set_sequential_chats(true)
+        .build();
+    client.subscribe_handler_func(gated_text_handler(gate.clone(), tx));
+
+    let post = async {
+        let addr = client.webhook_addr().await;
+        for (update_id, chat_id, text) in [(1, 1, "slow"), (2, 1, "fast"), (3, 2, "other chat")] {
+            let update = serde_json::json!({
+                "update_id": update_id,
+                "message": {
+                    "message_id": update_id,
+                    "date": 0,
+                    "chat": {"id": chat_id, "type": "private", "first_name": "test"},
+                    "text": text
+                }
+            });
+            let req = hyper::Request::post(format!("http://{addr}/"))
+                .header("content-type", "application/json")
+                .body(hyper::Body::from(update.to_string()))?;
+            hyper::Client::new().request(req).await?;
+        }
+
+        // "fast" waits for "slow" without holding the second permit
+        let first = tokio::time::timeout(Duration::from_secs(5), handled.recv())
+            .await
+            .expect("the other chat was starved");
+        gate.notify_one();
+        let rest = [handled.recv().await, handled.recv().await];
+        Ok::<_, telexide::Error>((first, rest))
+    };
+    let (first, rest) = tokio::select! {
+        res = client.start() => panic!("the client stopped: {:?}", res.err()),
+        handled = post => handled?,
+    };
+
+    assert_eq!(first.as_deref(), Some("other chat"));
+    assert_eq!(rest, [Some("slow".to_owned()), Some("fast".to_owned())]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn handlers_can_capture_state() -> Result<()> {
+    let seen = Arc::new(AtomicUsize::new(0));
+    let stalls = Arc::new(AtomicUsize::new(0));
+
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(MockAPI::new())))
+        .add_handler_func({
+            let seen = seen.clone();
+            move |_, u| {
+                let seen = seen.clone();
+                Box::pin(async move {
+                    seen.fetch_add(u.update_id as usize, Ordering::SeqCst);
+                })
+            }
+        })
+        .build();
+    let offset = 100;
+    client.subscribe_handler_func({
+        let seen = seen.clone();
+        move |_, u| {
+            let seen = seen.clone();
+            Box::pin(async move {
+                seen.fetch_add(offset + u.update_id as usize, Ordering::SeqCst);
+            })
+        }
+    });
+    client.subscribe_poll_stall_handler({
+        let stalls = stalls.clone();
+        move |_, _| {
+            stalls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async {})
+        }
+    });
+
+    client.fire_handlers(Update {
+        update_id: 2,
+        content: UpdateContent::Unknown,
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(seen.load(Ordering::SeqCst), 104);
+    assert_eq!(stalls.load(Ordering::SeqCst), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn handler_errors_are_passed_to_the_error_handler() -> Result<()> {
+    let errors = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let handled = Arc::new(AtomicUsize::new(0));
+
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(MockAPI::new())))
+        .add_handler_func(|_, _| async { Err(std::io::Error::other("no such order").into()) })
+        .add_handler_func({
+            let handled = handled.clone();
+            move |_, _| {
+                let handled = handled.clone();
+                async move {
+                    handled.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        })
+        .build();
+    client.on_handler_error({
+        let errors = errors.clone();
+        move |_, err: HandlerError| {
+            let errors = errors.clone();
+            async move {
+                errors
+                    .lock()
+                    .push((err.handler, err.chat_id, err.error.to_string()));
+            }
+        }
+    });
+
+    client.fire_handlers(Update {
+        update_id: 1,
+        content: UpdateContent::Unknown,
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(handled.load(Ordering::SeqCst), 1);
+    let errors = errors.lock();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "handler#0");
+    assert_eq!(errors[0].1, None);
+    assert!(errors[0].2.contains("no such order"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn updates_stream_yields_updates_until_shutdown() -> Result<()> {
+    use futures::StreamExt;
+
+    let api = MockAPI::new();
+    api.respond_with(
+        "getUpdates",
+        serde_json::json!([{"update_id": 7}, {"update_id": 8}]),
+    );
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .build();
+
+    let mut updates = client.updates_stream();
+    let ids: Vec<i64> = (&mut updates).take(2).map(|u| u.update_id).collect().await;
+    assert_eq!(ids, vec![7, 8]);
+
+    client.shutdown();
+    let next = tokio::time::timeout(Duration::from_secs(1), updates.next())
+        .await
+        .expect("the stream didn't end when shutting down");
+    assert!(next.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_streams_can_be_spawned() -> Result<()> {
+    use futures::StreamExt;
+
+    let api = MockAPI::new();
+    api.respond_with("getUpdates", serde_json::json!([{"update_id": 7}]));
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .build();
+
+    let mut raw = UpdatesStream::new(Arc::new(Box::new(api.clone())));
+    raw.set_timout(0);
+    let raw = tokio::spawn(async move { raw.next().await });
+    let mut updates = client.updates_stream();
+    let updates = tokio::spawn(async move { updates.next().await });
+
+    let raw = raw.await.expect("the raw stream panicked");
+    assert_eq!(raw.expect("the raw stream ended")?.update_id, 7);
+    let update = updates.await.expect("the client stream panicked");
+    assert_eq!(update.expect("the client stream ended").update_id, 7);
+    Ok(())
+}
+
+/// appends its tag to the text of every message sent
+struct Tagging {
+    api: Arc<Box<dyn API + Send>>,
+    tag: &'static str,
+}
+
+impl Tagging {
+    fn tag(&self, data: Option<serde_json::Value>) -> Option<serde_json::Value> {
+        data.map(|mut d| {
+            if let Some(text) = d["text"].as_str() {
+                d["text"] = format!("{}{}", text, self.tag).into();
+            }
+            d
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl API for Tagging {
+    fn inner(&self) -> Option<&dyn API> {
+        Some(&**self.api)
+    }
+
+    async fn get(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        self.api.post(endpoint, self.tag(data)).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.api.post_file(endpoint, self.tag(data), files).await
+    }
+}
+
+#[tokio::test]
+async fn api_layers_wrap_outgoing_requests_in_order() -> Result<()> {
+    let api = MockAPI::new();
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .add_api_layer(|api| Tagging {
+            api,
+            tag: "-outer",
+        })
+        .add_api_layer(|api| Tagging {
+            api,
+            tag: "-inner",
+        })
+        .build();
+
+    client
+        .api_client
+        .send_message(SendMessage::new(1, "hi"))
+        .await?;
+
+    let sent = api.calls_to("sendMessage");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].data["text"], "hi-outer-inner");
+    Ok(())
+}
+
+#[tokio::test]
+async fn api_layers_forward_downloads_to_the_inner_client() -> Result<()> {
+    let api = MockAPI::new();
+    api.add_file("photos/file_1.jpg", b"jpeg".to_vec());
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .add_api_layer(|api| Tagging {
+            api,
+            tag: "-outer",
+        })
+        .build();
+
+    assert_eq!(
+        client.api_client.download("photos/file_1.jpg").await?,
+        b"jpeg"
+    );
+    let mut written = Vec::new();
+    client
+        .api_client
+        .download_to("photos/file_1.jpg", &mut written)
+        .await?;
+    assert_eq!(written, b"jpeg");
+    client.api_client.reset_connection();
+    assert_eq!(api.connection_resets(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn requests_are_retried_after_the_flood_control() -> Result<()> {
+    let api = MockAPI::new();
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .build();
+
+    api.flood_next("sendMessage", 2, 0);
+    client
+        .api_client
+        .send_message(SendMessage::new(1, "hi"))
+        .await?;
+    assert_eq!(api.calls_to("sendMessage").len(), 3);
+
+    api.clear_calls();
+    api.flood_next("sendMessage", 1, 3600);
+    let res = client
+        .api_client
+        .send_message(SendMessage::new(1, "hi"))
+        .await;
+    assert!(res.is_err(), "waiting an hour should be left to the caller");
+    assert_eq!(api.calls_to("sendMessage").len(), 1);
+
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_flood_retries(0)
+        .build();
+    api.clear_calls();
+    api.flood_next("sendMessage", 1, 0);
+    let res = client
+        .api_client
+        .send_message(SendMessage::new(1, "hi"))
+        .await;
+    assert!(res.is_err());
+    assert_eq!(api.calls_to("sendMessage").len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn uploads_from_readers_are_not_retried_after_the_flood_control() -> Result<()> {
+    let api = MockAPI::new();
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .build();
+
+    api.flood_next("sendDocument", 1, 0);
+    let file = FormDataFile::new_from_reader(&b"contents"[..], "notes.txt")?;
+    let res = client
+        .api_client
+        .post_file(APIEndpoint::SendDocument, None, Some(vec![file]))
+        .await?;
+    assert!(!res.ok);
+    assert_eq!(res.error_code, Some(429));
+    assert_eq!(api.calls_to("sendDocument").len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn throttled_sends_wait_for_the_group_limit() -> Result<()> {
+    let api = MockAPI::new();
+    let throttled = Throttled::new(api.clone())
+        .set_global_limit(10, Duration::from_millis(200))
+        .set_group_limit(2, Duration::from_millis(300));
+
+    let started = std::time::Instant::now();
+    for _ in 0..2 {
+        throttled.send_message(SendMessage::new(-100, "hi")).await?;
+    }
+    throttled.send_message(SendMessage::new(5, "hi")).await?;
+    assert!(started.elapsed() < Duration::from_millis(200));
+
+    throttled.send_message(SendMessage::new(-100, "hi")).await?;
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert_eq!(api.calls_to("sendMessage").len(), 4);
+    Ok(())
+}
+
+/// a mock api failing every request with a connection error while it's down
+struct Unreachable {
+    api: MockAPI,
+    down: AtomicBool,
+}
+
+impl Unreachable {
+    fn new(api: MockAPI) -> Self {
+        Self {
+            api,
+            down: AtomicBool::new(false),
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.down.load(Ordering::Acquire) {
+            return Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl API for Unreachable {
+    async fn get(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        self.check()?;
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        self.check()?;
+        self.api.post(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.check()?;
+        self.api.post_file(endpoint, data, files).await
+    }
+}
+
+fn ping() -> APIEndpoint {
+    APIEndpoint::from("ping".to_owned())
+}
+
+#[tokio::test]
+async fn circuit_breaker_opens_after_consecutive_failures() -> Result<()> {
+    let api = MockAPI::new();
+    let breaker = CircuitBreaker::new(Unreachable::new(api.clone()))
+        .set_failure_threshold(2)
+        .set_open_duration(Duration::from_secs(60));
+    let mut events = breaker.subscribe();
+
+    breaker.post(ping(), None).await?;
+    breaker.get_api().down.store(true, Ordering::Release);
+    assert!(breaker.post(ping(), None).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(breaker.post(ping(), None).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert_eq!(events.try_recv().ok(), Some(CircuitState::Open));
+
+    // the open circuit rejects requests without sending them
+    breaker.get_api().down.store(false, Ordering::Release);
+    let err = breaker
+        .post(ping(), None)
+        .await
+        .expect_err("the circuit is open");
+    assert!(err.to_string().contains("rejected"));
+    assert_eq!(api.calls_to("ping").len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn circuit_breaker_lets_a_single_probe_through_when_half_open() -> Result<()> {
+    let api = MockAPI::new();
+    let breaker = Arc::new(
+        CircuitBreaker::new(Unreachable::new(api.clone()))
+            .set_failure_threshold(1)
+            .set_open_duration(Duration::ZERO),
+    );
+    let mut events = breaker.subscribe();
+
+    breaker.get_api().down.store(true, Ordering::Release);
+    assert!(breaker.post(ping(), None).await.is_err());
+    assert_eq!(events.recv().await.ok(), Some(CircuitState::Open));
+
+    // a failing probe opens the circuit again
+    assert!(breaker.post(ping(), None).await.is_err());
+    assert_eq!(events.recv().await.ok(), Some(CircuitState::HalfOpen));
+    assert_eq!(events.recv().await.ok(), Some(CircuitState::Open));
+
+    breaker.get_api().down.store(false, Ordering::Release);
+    api.hang_next("ping", 1);
+    let probe = tokio::spawn({
+        let breaker = breaker.clone();
+        async move { breaker.post(ping(), None).await }
+    });
+    assert_eq!(events.recv().await.ok(), Some(CircuitState::HalfOpen));
+    assert!(breaker.post(ping(), None).await.is_err());
+    assert_eq!(api.calls_to("ping").len(), 1);
+
+    // a cancelled probe gives its slot to the next request
+    probe.abort();
+    let _ = probe.await;
+    breaker.post(ping(), None).await?;
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(events.recv().await.ok(), Some(CircuitState::Closed));
+    assert_eq!(api.calls_to("ping").len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn circuit_breaker_queues_requests_until_it_closes() -> Result<()> {
+    let api = MockAPI::new();
+    let breaker = Arc::new(
+        CircuitBreaker::new(Unreachable::new(api.clone()))
+            .set_failure_threshold(1)
+            .set_open_duration(Duration::ZERO)
+            .set_mode(CircuitMode::Queue(Duration::from_secs(10))),
+    );
+    let mut events = breaker.subscribe();
+
+    breaker.get_api().down.store(true, Ordering::Release);
+    assert!(breaker.post(ping(), None).await.is_err());
+    breaker.get_api().down.store(false, Ordering::Release);
+
+    let (cancel_probe, cancelled) = tokio::sync::oneshot::channel::<()>();
+    api.hang_next("ping", 1);
+    let probe = tokio::spawn({
+        let breaker = breaker.clone();
+        async move {
+            tokio::select! {
+                res = breaker.post(ping(), None) => res.map(|_| ()),
+                _ = cancelled => Ok(()),
+            }
+        }
+    });
+    assert_eq!(events.recv().await.ok(), Some(CircuitState::Open));
+    assert_eq!(events.recv().await.ok(), Some(CircuitState::HalfOpen));
+
+    // the queued request waits for the probe instead of failing
+    let queued = tokio::spawn({
+        let breaker = breaker.clone();
+        async move { breaker.post(ping(), None).await }
+    });
+    tokio::task::yield_now().await;
+    assert!(!queued.is_finished());
+
+    let _ = cancel_probe.send(());
+    probe.await.expect("the probe doesn't panic")?;
+    queued.await.expect("the request doesn't panic")?;
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(api.calls_to("ping").len(), 2);
+    Ok(())
+}
+
+/// serves a bot api answering every method like getMe and every download with
+/// "file contents", returning the paths requested
+fn serve_fake_bot_api() -> (
+    std::net::SocketAddr,
+    Arc<parking_lot::Mutex<Vec<String>>>,
+    tokio::sync::oneshot::Sender<()>,
+) {
+    use hyper::service::{make_service_fn, service_fn};
+
+    let paths = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let recorded = paths.clone();
+    let make_svc = make_service_fn(move |_| {
+        let recorded = recorded.clone();
+        async move {
+            Ok::<_, std::convert::Infallible>(service_fn(
+                move |req: hyper::Request<hyper::Body>| {
+                    let path = req.uri().path().to_owned();
+                    recorded.lock().push(path.clone());
+                    async move {
+                        let body = if path.starts_with("/file/") {
+                            hyper::Body::from("file contents")
+                        } else {
+                            hyper::Body::from(
+                                r#"{"ok": true, "result": {"id": 1, "is_bot": true, "first_name": "local"}}"#,
+                            )
+                        };
+                        Ok::<_, std::convert::Infallible>(hyper::Response::new(body))
+                    }
+                },
+            ))
+        }
+    });
+    let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(server.with_graceful_shutdown(async {
+        stopped.await.ok();
+    }));
+    (addr, paths, stop)
+}
+
+#[tokio::test]
+async fn api_client_uses_the_set_base_url() -> Result<()> {
+    let (addr, paths, stop) = serve_fake_bot_api();
+
+    let mut api = APIClient::new_default("test");
+    api.set_base_url(&format!("http://{addr}/"));
+    assert_eq!(api.get_me().await?.first_name, "local");
+    assert_eq!(api.download("photos/a.jpg").await?, b"file contents");
+
+    let stored = std::env::temp_dir().join("telexide-local-file");
+    std::fs::write(&stored, "stored locally")?;
+    let stored_path = stored.to_str().expect("utf-8 path");
+    // absolute paths are only read from the disk in local mode
+    let remote = api.download(stored_path).await?;
+    api.set_local_mode(true);
+    let local = api.download(stored_path).await?;
+    std::fs::remove_file(&stored)?;
+    assert_eq!(remote, b"file contents");
+    assert_eq!(local, b"stored locally");
+
+    let mut streamed = Vec::new();
+    assert_eq!(api.download_to("videos/b.mp4", &mut streamed).await?, 13);
+    assert_eq!(streamed, b"file contents");
+
+    stop.send(()).ok();
+    assert_eq!(
+        *paths.lock(),
+        vec![
+            "/bottest/getMe".to_owned(),
+            "/file/bottest/photos/a.jpg".to_owned(),
+            format!("/file/bottest/{stored_path}"),
+            "/file/bottest/videos/b.mp4".to_owned(),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn files_on_disk_are_streamed_while_uploading() -> Result<()> {
+    use hyper::service::{make_service_fn, service_fn};
+    use tokio::io::AsyncWriteExt;
+
+    let uploads = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let recorded = uploads.clone();
+    let make_svc = make_service_fn(move |_| {
+        let recorded = recorded.clone();
+        async move {
+            Ok::<_, std::convert::Infallible>(service_fn(
+                move |req: hyper::Request<hyper::Body>| {
+                    let recorded = recorded.clone();
+                    async move {
+                        let chunked = req.headers().get("transfer-encoding").is_some();
+                        let body = hyper::body::to_bytes(req.into_body()).await?;
+                        recorded.lock().push((chunked, body));
+                        Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::from(
+                            r#"{"ok": true, "result": true}"#,
+                        )))
+                    }
+                },
+            ))
+        }
+    });
+    let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(server.with_graceful_shutdown(async {
+        stopped.await.ok();
+    }));
+
+    let stored = std::env::temp_dir().join(format!("telexide-upload-{}.txt", std::process::id()));
+    let contents = "a line of the document\n".repeat(10_000);
+    std::fs::write(&stored, &contents)?;
+
+    let mut api = APIClient::new_default("test");
+    api.set_base_url(&format!("http://{addr}/"));
+    let upload = FormDataFile::new_from_path(&stored, "upload.txt")?;
+    let res = api
+        .post_file(APIEndpoint::SendDocument, None, Some(vec![upload]))
+        .await?;
+    std::fs::remove_file(&stored)?;
+    assert!(res.ok);
+
+    let missing = FormDataFile::new_from_path(&stored, "upload.txt")?;
+    assert!(api
+        .post_file(APIEndpoint::SendDocument, None, Some(vec![missing]))
+        .await
+        .is_err());
+
+    let stream = FormDataFile::new_from_reader(&b"from a stream"[..], "stream.txt")?;
+    api.post_file(APIEndpoint::SendDocument, None, Some(vec![stream.clone()]))
+        .await?;
+    // the reader was used up by the first upload
+    assert!(api
+        .post_file(APIEndpoint::SendDocument, None, Some(vec![stream]))
+        .await
+        .is_err());
+
+    // the upload timeout applies to every chunk of a stream, not all of it
+    api.set_upload_timeout(Duration::from_millis(300));
+    let (mut writer, reader) = tokio::io::duplex(64);
+    let writing = tokio::spawn(async move {
+        for _ in 0..4 {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            writer.write_all(b"slow ").await?;
+        }
+        Ok::<_, std::io::Error>(())
+    });
+    let slow = FormDataFile::new_from_reader(reader, "slow.txt")?;
+    api.post_file(APIEndpoint::SendDocument, None, Some(vec![slow]))
+        .await?;
+    writing.await.expect("the writer didn't panic")?;
+
+    let (_writer, reader) = tokio::io::duplex(64);
+    let stalled = FormDataFile::new_from_reader(reader, "stalled.txt")?;
+    let err = api
+        .post_file(APIEndpoint::SendDocument, None, Some(vec![stalled]))
+        .await
+        .expect_err("the stream stalls");
+    assert!(err.to_string().contains("didn't finish within 300ms"));
+    stop.send(()).ok();
+
+    let (chunked, body) = uploads.lock()[0].clone();
+    assert!(chunked);
+    let body = String::from_utf8_lossy(&body);
+    assert!(body.contains("filename=\"upload.txt\""));
+    assert!(body.contains(&contents));
+    let (_, streamed) = uploads.lock()[1].clone();
+    assert!(String::from_utf8_lossy(&streamed).contains("from a stream"));
+    let (_, slow) = uploads.lock()[2].clone();
+    assert!(String::from_utf8_lossy(&slow).contains("slow slow slow slow"));
+    assert_eq!(uploads.lock().len(), 3);
+    Ok(())
+}
+
+/// runs a proxy accepting connections on a free port, with `handshake`
+/// reading the request for a tunnel and returning the address to connect to
+fn serve_fake_proxy<H, Fut>(handshake: H) -> std::net::SocketAddr
+where
+    H: Fn(tokio::net::TcpStream) -> Fut + Send + 'static,
+    Fut: Future<Output = std::io::Result<(tokio::net::TcpStream, String)>> + Send + 'static,
+{
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).expect("port is free");
+    let addr = listener.local_addr().expect("bound address");
+    listener
+        .set_nonblocking(true)
+        .expect("nonblocking listener");
+    let listener = tokio::net::TcpListener::from_std(listener).expect("tokio listener");
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let tunnel = handshake(stream);
+            tokio::spawn(async move {
+                let (mut client, target) = tunnel.await?;
+                let mut target = tokio::net::TcpStream::connect(target).await?;
+                tokio::io::copy_bidirectional(&mut client, &mut target).await?;
+                Ok::<_, std::io::Error>(())
+            });
+        }
+    });
+    addr
+}
+
+#[tokio::test]
+async fn api_client_connects_through_proxies() -> Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (addr, paths, stop) = serve_fake_bot_api();
+
+    let connects = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let recorded = connects.clone();
+    let http_proxy = serve_fake_proxy(move |mut stream| {
+        let recorded = recorded.clone();
+        async move {
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                head.push(stream.read_u8().await?);
+            }
+            let head = String::from_utf8_lossy(&head).into_owned();
+            let target = head
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or_default()
+                .to_owned();
+            recorded.lock().push(head);
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await?;
+            Ok((stream, target))
+        }
+    });
+
+    let proxy = Proxy::new(&format!("http://bot:s%40cret@{http_proxy}"))?;
+    let printed = format!("{proxy:?}");
+    assert!(printed.contains("bot") && !printed.contains("s@cret"));
+    let mut api = APIClient::new_default("test");
+    api.set_base_url(&format!("http://{addr}")).set_proxy(proxy);
+    assert_eq!(api.get_me().await?.first_name, "local");
+    {
+        let connects = connects.lock();
+        assert_eq!(connects.len(), 1);
+        assert!(connects[0].starts_with(&format!("CONNECT {addr} HTTP/1.1\r\n")));
+        // base64 of "bot:s@cret"
+        assert!(connects[0].contains("Proxy-Authorization: Basic Ym90OnNAY3JldA==\r\n"));
+    }
+
+    let socks_proxy = serve_fake_proxy(|mut stream| async move {
+        let mut greeting = [0; 3];
+        stream.read_exact(&mut greeting).await?;
+        assert_eq!(greeting, [5, 1, 0]);
+        stream.write_all(&[5, 0]).await?;
+
+        let mut request = [0; 5];
+        stream.read_exact(&mut request).await?;
+        assert_eq!(request[..4], [5, 1, 0, 3]);
+        let mut host = vec![0; usize::from(request[4])];
+        stream.read_exact(&mut host).await?;
+        let port = stream.read_u16().await?;
+        stream.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0]).await?;
+        Ok((stream, format!("{}:{port}", String::from_utf8_lossy(&host))))
+    });
+
+    let mut api = APIClient::new_default("test");
+    api.set_base_url(&format!("http://localhost:{}", addr.port()))
+        .set_proxy(Proxy::new(&format!("socks5://{socks_proxy}"))?);
+    assert_eq!(api.download("photos/b.jpg").await?, b"file contents");
+
+    stop.send(()).ok();
+    assert_eq!(
+        *paths.lock(),
+        vec!["/bottest/getMe", "/file/bottest/photos/b.jpg"]
+    );
+    assert!(Proxy::new("ftp://127.0.0.1").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn hung_requests_time_out() -> Result<()> {
+    // accepts the connections but never answers
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        let mut open = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            open.push(stream);
+        }
+    });
+
+    let mut api = APIClient::new_default("test");
+    api.set_base_url(&format!("http://{addr}"))
+        .set_request_timeout(Duration::from_millis(100));
+
+    let started = std::time::Instant::now();
+    let err = api.get_me().await.expect_err("the request hangs");
+    assert!(err.to_string().contains("didn't finish within 100ms"));
+
+    // long polling gets the polling timeout on top
+    let mut poll = GetUpdates::new();
+    poll.timeout = Some(1);
+    let err = api.get_updates(poll).await.expect_err("the request hangs");
+    assert!(err.to_string().contains("didn't finish within 1.1s"));
+    assert!(started.elapsed() < Duration::from_secs(3));
+    Ok(())
+}
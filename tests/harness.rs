@@ -0,0 +1,444 @@
+use serde_json::json;
+use telexide::{
+    api::types::SendMessage,
+    client::{ClientBuilder, Context},
+    framework::{Args, CheckResult, CommandError, CommandResult, Framework},
+    macros::{command, create_framework, prepare_listener},
+    metrics::HandlerMetrics,
+    model::{Message, Update, UpdateContent},
+    test::{fixtures, Harness, MockAPI},
+};
+use std::{sync::Arc, time::Duration};
+
+#[prepare_listener]
+async fn echo(ctx: Context, update: Update) {
+    if let UpdateContent::Message(m) = update.content {
+        if let Some(text) = m.get_text() {
+            let _ = ctx
+                .api
+                .send_message(SendMessage::new(m.chat.get_id(), &format!("echo: {text}")))
+                .await;
+        }
+    }
+}
+
+#[command(description = "greets the user")]
+async fn hello(ctx: Context, m: Message) -> CommandResult {
+    ctx.api
+        .send_message(SendMessage::new(m.chat.get_id(), "hello there"))
+        .await?;
+    Ok(())
+}
+
+#[command(description = "repeats the message", ignore_edits = true)]
+async fn repeat(ctx: Context, m: Message) -> CommandResult {
+    ctx.api
+        .send_message(SendMessage::new(m.chat.get_id(), "repeated"))
+        .await?;
+    Ok(())
+}
+
+#[command(description = "always fails")]
+async fn broken(_ctx: Context, _m: Message) -> CommandResult {
+    Err(CommandError("out of order".to_owned()))
+}
+
+#[tokio::test]
+async fn harness_records_handler_calls() {
+    let harness = Harness::new(ClientBuilder::new().add_handler_func(echo));
+
+    harness.expect_no_calls();
+    harness.feed(fixtures::text_message(-100, 5, "hi")).await;
+    harness.feed(fixtures::text_message(3, 5, "bye")).await;
+
+    harness
+        .expect_send_message()
+        .times(2)
+        .to_chat(-100)
+        .containing("echo: hi")
+        .with("text", &json!("echo: hi"))
+        .times(1);
+    harness.expect_send_message().to_chat(3).containing("bye");
+    assert!(harness.api().calls_to("getMe").is_empty());
+}
+
+#[tokio::test]
+async fn harness_runs_commands() {
+    let harness =
+        Harness::new(ClientBuilder::new().set_framework(create_framework!("test_bot", hello)));
+
+    harness
+        .feed(fixtures::text_message(7, 5, "not a command"))
+        .await;
+    harness.expect_no_calls();
+
+    harness.feed(fixtures::text_message(7, 5, "/hello")).await;
+    harness
+        .expect_send_message()
+        .to_chat(7)
+        .containing("hello")
+        .times(1);
+}
+
+#[tokio::test]
+async fn edited_commands_run_when_enabled() {
+    let harness =
+        Harness::new(ClientBuilder::new().set_framework(create_framework!("test_bot", hello)));
+    harness
+        .feed(fixtures::edited_message(7, 5, 1, "/hello"))
+        .await;
+    harness.expect_no_calls();
+
+    let framework = create_framework!("test_bot", hello, repeat);
+    framework.set_handle_edits(true);
+    let harness = Harness::new(ClientBuilder::new().set_framework(framework));
+
+    harness
+        .feed(fixtures::edited_message(7, 5, 1, "/hello"))
+        .await;
+    harness
+        .feed(fixtures::edited_message(7, 5, 2, "/repeat"))
+        .await;
+    harness
+        .expect_send_message()
+        .to_chat(7)
+        .containing("hello")
+        .times(1);
+    assert!(harness
+        .api()
+        .calls_to("sendMessage")
+        .iter()
+        .all(|c| c.data["text"] != "repeated"));
+
+    harness.feed(fixtures::text_message(7, 5, "/repeat")).await;
+    harness
+        .expect_send_message()
+        .containing("repeated")
+        .times(1);
+}
+
+#[tokio::test]
+async fn metrics_count_the_activity_for_stats() {
+    let metrics = Arc::new(HandlerMetrics::new());
+    let harness = Harness::new(
+        ClientBuilder::new()
+            .set_handler_metrics(metrics.clone())
+            .set_framework(create_framework!("test_bot", hello, broken)),
+    );
+
+    harness.feed(fixtures::text_message(7, 5, "/hello")).await;
+    harness.feed(fixtures::text_message(7, 5, "/hello")).await;
+    harness.feed(fixtures::text_message(7, 5, "/broken")).await;
+    harness.feed(fixtures::text_message(7, 5, "hi")).await;
+    harness.feed(fixtures::callback_query(7, 5, "data")).await;
+
+    let hour = metrics.activity(Duration::from_secs(60 * 60));
+    assert_eq!(hour.updates, 5);
+    assert_eq!(hour.messages, 4);
+    assert_eq!(hour.commands["hello"], 2);
+    assert_eq!(hour.command_uses(), 3);
+    assert_eq!(hour.errors, 1);
+    assert_eq!(metrics.activity(Duration::from_secs(24 * 60 * 60)), hour);
+
+    let later = chrono::Utc::now() + chrono::Duration::minutes(1);
+    assert_eq!(metrics.activity_since(later).updates, 0);
+
+    metrics.reset();
+    assert_eq!(metrics.activity(Duration::from_secs(60)).updates, 0);
+}
+
+#[tokio::test]
+#[should_panic(expected = "expected a call to sendMessage to chat 2")]
+async fn harness_expectations_fail_without_match() {
+    let harness = Harness::new(ClientBuilder::new().add_handler_func(echo));
+
+    harness.feed(fixtures::text_message(1, 5, "hi")).await;
+    harness.expect_send_message().to_chat(2);
+}
+
+#[tokio::test]
+async fn mock_api_returns_configured_responses() {
+    let harness = Harness::new(ClientBuilder::new().add_handler_func(echo));
+    harness
+        .api()
+        .fail_with("sendMessage", 403, "Forbidden: bot was blocked by the user");
+
+    harness.feed(fixtures::text_message(1, 5, "hi")).await;
+    harness.expect_send_message().to_chat(1).times(1);
+}
+
+async fn greet(ctx: Context, m: Message) -> telexide::Result<()> {
+    let name = ctx.api.get_me().await?.first_name;
+    ctx.api
+        .send_message(SendMessage::new(m.chat.get_id(), &format!("I'm {name}")))
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn handlers_can_be_called_with_a_mock_context() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let update = fixtures::text_message(4, 5, "hi");
+    let UpdateContent::Message(message) = update.content else {
+        panic!("the fixture isn't a message");
+    };
+
+    greet(api.context(), message).await?;
+    let sent = api.calls_to("sendMessage");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].data["chat_id"], 4);
+    assert!(sent[0].data["text"]
+        .as_str()
+        .is_some_and(|t| t.starts_with("I'm ")));
+    Ok(())
+}
+
+#[test]
+fn command_args_are_split_and_parsed() {
+    let args = Args::new(r#"  ban "John Doe" 'for 3' days\ later don't  "#);
+    assert_eq!(args.raw(), r#"ban "John Doe" 'for 3' days\ later don't"#);
+    assert_eq!(
+        args.iter().collect::<Vec<_>>(),
+        vec!["ban", "John Doe", "for 3", "days later", "don't"]
+    );
+    assert!(Args::new("  ").is_empty());
+
+    let numbers = Args::new("3 14 15");
+    assert_eq!(numbers.parse::<u8>(1).unwrap(), 14);
+    assert_eq!(numbers.parse_all::<i32>().unwrap(), vec![3, 14, 15]);
+    assert_eq!(
+        numbers.parse::<u8>(3).unwrap_err().0,
+        "argument 4 is missing"
+    );
+    assert!(Args::new("three")
+        .parse::<u8>(0)
+        .unwrap_err()
+        .0
+        .starts_with("argument 1 is invalid"));
+}
+
+#[command(description = "adds up numbers")]
+async fn sum(ctx: Context, m: Message) -> CommandResult {
+    let args = Args::from_message(&m).unwrap_or_default();
+    let total: i64 = args.parse_all::<i64>()?.iter().sum();
+    ctx.api
+        .send_message(SendMessage::new(m.chat.get_id(), &format!("sum: {total}")))
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn commands_read_their_args() {
+    let harness =
+        Harness::new(ClientBuilder::new().set_framework(create_framework!("test_bot", sum)));
+
+    harness
+        .feed(fixtures::text_message(7, 5, "/sum@test_bot 1 2 \"39\""))
+        .await;
+    harness.expect_send_message().containing("sum: 42").times(1);
+
+    // the parse error goes to the error handler instead of the chat
+    harness
+        .feed(fixtures::text_message(7, 5, "/sum 1 two"))
+        .await;
+    assert_eq!(harness.api().calls_to("sendMessage").len(), 1);
+}
+
+async fn in_private_chat(_ctx: Context, m: Message) -> CheckResult {
+    if m.chat.get_id() > 0 {
+        Ok(())
+    } else {
+        Err(CommandError("only works in private chats".to_owned()))
+    }
+}
+
+async fn not_banned(_ctx: Context, m: Message) -> CheckResult {
+    match m.from {
+        Some(user) if user.id == 13 => Err(CommandError(String::new())),
+        _ => Ok(()),
+    }
+}
+
+#[command(description = "shows the secret")]
+#[check(in_private_chat, not_banned)]
+async fn secret(ctx: Context, m: Message) -> CommandResult {
+    ctx.api
+        .send_message(SendMessage::new(m.chat.get_id(), "the secret"))
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn checks_run_before_commands() {
+    let harness =
+        Harness::new(ClientBuilder::new().set_framework(create_framework!("test_bot", secret)));
+
+    harness.feed(fixtures::text_message(5, 5, "/secret")).await;
+    harness
+        .expect_send_message()
+        .containing("the secret")
+        .times(1);
+
+    harness
+        .feed(fixtures::text_message(-100, 5, "/secret"))
+        .await;
+    harness
+        .expect_send_message()
+        .to_chat(-100)
+        .containing("only works in private chats")
+        .times(1);
+
+    // empty rejections aren't answered
+    harness
+        .feed(fixtures::text_message(13, 13, "/secret"))
+        .await;
+    assert_eq!(harness.api().calls_to("sendMessage").len(), 2);
+}
+
+#[tokio::test]
+async fn commands_addressed_at_other_bots_are_ignored() {
+    let harness =
+        Harness::new(ClientBuilder::new().set_framework(create_framework!("@Test_Bot", hello)));
+
+    harness
+        .feed(fixtures::text_message(-100, 5, "/hello@other_bot"))
+        .await;
+    harness.expect_no_calls();
+
+    harness
+        .feed(fixtures::text_message(-100, 5, "/hello@test_bot"))
+        .await;
+    harness.expect_send_message().to_chat(-100).times(1);
+}
+
+#[tokio::test]
+async fn the_framework_learns_the_bot_name_when_starting() {
+    let api = MockAPI::new();
+    api.hang_next("getUpdates", 10);
+    let framework = create_framework!("placeholder", hello);
+    let client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_framework(framework.clone())
+        .build();
+
+    let polling = async {
+        while api.calls_to("getUpdates").is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    };
+    tokio::select! {
+        res = client.start() => panic!("the client stopped: {:?}", res.err()),
+        () = polling => {},
+    }
+    assert_eq!(framework.bot_name(), "test_bot");
+}
+
+#[tokio::test]
+async fn commands_can_be_matched_regardless_of_case() {
+    let harness =
+        Harness::new(ClientBuilder::new().set_framework(create_framework!("test_bot", hello)));
+    harness.feed(fixtures::text_message(7, 5, "/Hello")).await;
+    harness.expect_no_calls();
+
+    let framework = create_framework!("test_bot", hello);
+    framework.set_case_insensitive(true);
+    let harness = Harness::new(ClientBuilder::new().set_framework(framework));
+
+    harness.feed(fixtures::text_message(7, 5, "/Hello")).await;
+    harness
+        .feed(fixtures::text_message(-100, 5, "/HELLO@Test_Bot"))
+        .await;
+    harness
+        .feed(fixtures::text_message(7, 5, "/hellothere"))
+        .await;
+    harness
+        .expect_send_message()
+        .containing("hello there")
+        .times(2);
+}
+
+#[command(description = "rolls a dice")]
+#[cooldown(60)]
+async fn roll(ctx: Context, m: Message) -> CommandResult {
+    ctx.api
+        .send_message(SendMessage::new(m.chat.get_id(), "rolled"))
+        .await?;
+    Ok(())
+}
+
+#[command(description = "starts a vote")]
+#[cooldown(60, per = "chat")]
+async fn vote(ctx: Context, m: Message) -> CommandResult {
+    ctx.api
+        .send_message(SendMessage::new(m.chat.get_id(), "voting"))
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn commands_on_cooldown_are_dropped() {
+    let harness =
+        Harness::new(ClientBuilder::new().set_framework(create_framework!("test_bot", roll, vote)));
+
+    harness.feed(fixtures::text_message(-100, 5, "/roll")).await;
+    harness.feed(fixtures::text_message(-100, 5, "/roll")).await;
+    harness.feed(fixtures::text_message(-100, 6, "/roll")).await;
+    harness.expect_send_message().containing("rolled").times(2);
+
+    harness.feed(fixtures::text_message(-100, 5, "/vote")).await;
+    harness.feed(fixtures::text_message(-100, 6, "/vote")).await;
+    harness.feed(fixtures::text_message(-200, 6, "/vote")).await;
+    harness.expect_send_message().containing("voting").times(2);
+}
+
+#[tokio::test]
+async fn the_cooldown_handler_answers_commands_used_too_fast() {
+    let framework = create_framework!("test_bot", roll);
+    framework.on_cooldown(|ctx, m, remaining| async move {
+        assert!(remaining > Duration::from_secs(55));
+        let text = format!("wait {} more seconds", remaining.as_secs() + 1);
+        ctx.api
+            .send_message(SendMessage::new(m.chat.get_id(), &text))
+            .await
+            .ok();
+    });
+    let harness = Harness::new(ClientBuilder::new().set_framework(framework));
+
+    harness.feed(fixtures::text_message(7, 7, "/roll")).await;
+    harness.feed(fixtures::text_message(7, 7, "/roll")).await;
+    harness
+        .expect_send_message()
+        .containing("wait 60 more seconds")
+        .times(1);
+}
+
+#[tokio::test]
+async fn hooks_run_around_commands() {
+    let runs = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let recorded = runs.clone();
+
+    let mut framework = Framework::new("test_bot");
+    framework.add_command(&hello_COMMAND);
+    framework.add_command(&broken_COMMAND);
+    framework
+        .add_before_hook(|_ctx, m, command| async move {
+            // /hello is turned off in groups
+            command != "hello" || m.chat.get_id() > 0
+        })
+        .add_after_hook(move |_ctx, _m, run| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().push((run.name, run.result.is_ok()));
+            }
+        });
+    let harness = Harness::new(ClientBuilder::new().set_framework(Arc::new(framework)));
+
+    harness.feed(fixtures::text_message(7, 5, "/hello")).await;
+    harness
+        .feed(fixtures::text_message(-100, 5, "/hello"))
+        .await;
+    harness.feed(fixtures::text_message(7, 5, "/broken")).await;
+
+    harness.expect_send_message().to_chat(7).times(1);
+    assert_eq!(*runs.lock(), vec![("hello", true), ("broken", false)]);
+}
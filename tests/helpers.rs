@@ -0,0 +1,975 @@
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use std::sync::Arc;
+use telexide::{
+    api::types::{
+        AnswerInlineQuery,
+        EditMessageText,
+        InlineQueryResult,
+        InlineQueryResultLocation,
+        InputFile,
+        InputMediaPhoto,
+        InputMessageContent,
+        SendMessage,
+    },
+    client::{ClientBuilder, Context},
+    framework::Framework,
+    helpers::{
+        answer_geo_query,
+        deep_links::{self, AttachMenuChatType},
+        draw_winners,
+        export_sticker_set,
+        format_distance,
+        formatting::{bold, code, compose, italic, link, mention, text, FormattedText},
+        Experiments,
+        FileVault,
+        GeoPoint,
+        GeoResult,
+        GeoResultProvider,
+        I18n,
+        InlineCache,
+        InlineCacheKey,
+        LocaleFormat,
+        Majority,
+        Quorum,
+        Raffle,
+        RaffleEntry,
+        Raffles,
+        StoredFile,
+        StoredFileKind,
+        Vote,
+        VoteOutcome,
+        VoteRules,
+        VoteStyle,
+        VoteTally,
+        Voting,
+        LANGUAGE_COMMAND,
+    },
+    macros::prepare_listener,
+    model::{
+        CallbackQuery,
+        InlineQuery,
+        Location,
+        Message,
+        MessageEntity,
+        MessageReactionUpdated,
+        TextBlock,
+        Update,
+        UpdateContent,
+        User,
+    },
+    runtime::{BoxedTask, Runtime},
+    storage::MemoryStorage,
+    test::{fixtures, Harness, MockAPI},
+};
+
+fn inline_query(id: &str, user_id: i64, query: &str) -> InlineQuery {
+    InlineQuery {
+        id: id.to_owned(),
+        from: User {
+            id: user_id,
+            is_bot: false,
+            first_name: "test".to_owned(),
+            last_name: None,
+            username: None,
+            language_code: None,
+            can_join_groups: None,
+            can_read_all_group_messages: None,
+            supports_inline_queries: None,
+            can_connect_to_business: None,
+        },
+        location: None,
+        query: query.to_owned(),
+        offset: String::new(),
+        chat_type: None,
+    }
+}
+
+fn answer(is_personal: bool, cache_time: Option<i64>) -> AnswerInlineQuery {
+    AnswerInlineQuery {
+        inline_query_id: "original".to_owned(),
+        results: Vec::new(),
+        cache_time,
+        is_personal,
+        next_offset: Some("10".to_owned()),
+        switch_pm_text: None,
+        switch_pm_parameter: None,
+    }
+}
+
+#[test]
+fn inline_cache_shares_normalized_queries() {
+    let cache = InlineCache::new();
+    cache.insert(&inline_query("1", 5, "Cat  pics"), &answer(false, None));
+
+    let cached = cache
+        .get(&inline_query("2", 6, " cat pics"))
+        .expect("answer should be cached");
+    assert_eq!(cached.inline_query_id, "2");
+    assert_eq!(cached.next_offset, Some("10".to_owned()));
+    assert_eq!(InlineCacheKey::normalize(" Cat\tPICS "), "cat pics");
+}
+
+#[test]
+fn inline_cache_respects_personal_and_expiry() {
+    let cache = InlineCache::new();
+    cache.insert(&inline_query("1", 5, "mine"), &answer(true, None));
+    cache.insert(&inline_query("1", 5, "gone"), &answer(false, Some(0)));
+
+    assert!(cache.get(&inline_query("2", 5, "mine")).is_some());
+    assert!(cache.get(&inline_query("3", 6, "mine")).is_none());
+    assert!(cache.get(&inline_query("4", 5, "gone")).is_none());
+}
+
+#[tokio::test]
+async fn inline_cache_builds_once() -> telexide::Result<()> {
+    let cache = InlineCache::new();
+    let query = inline_query("1", 5, "build");
+
+    let first = cache
+        .get_or_insert_with(&query, || async { Ok(answer(false, None)) })
+        .await?;
+    let second = cache
+        .get_or_insert_with(&query, || async { panic!("answer should be cached") })
+        .await?;
+
+    assert_eq!(first, second);
+    assert_eq!(second.inline_query_id, "1");
+    Ok(())
+}
+
+#[test]
+fn deep_links_are_built_and_validated() -> telexide::Result<()> {
+    assert_eq!(
+        deep_links::start_link("@test_bot", "ref_42")?,
+        "https://t.me/test_bot?start=ref_42"
+    );
+    assert_eq!(
+        deep_links::mini_app_link("test_bot", Some("shop"), Some("item-7"))?,
+        "https://t.me/test_bot/shop?startapp=item-7"
+    );
+    assert_eq!(
+        deep_links::attachment_menu_link(
+            "test_bot",
+            &[AttachMenuChatType::Users, AttachMenuChatType::Groups],
+            Some("abc")
+        )?,
+        "https://t.me/test_bot?startattach=abc&choose=users+groups"
+    );
+
+    assert!(deep_links::start_link("test_bot", "not allowed").is_err());
+    assert!(deep_links::validate_payload(&"a".repeat(65)).is_err());
+
+    let payload = deep_links::encode_payload(b"{\"item\":7}")?;
+    assert_eq!(deep_links::decode_payload(&payload)?, b"{\"item\":7}");
+    Ok(())
+}
+
+#[test]
+fn start_param_is_parsed_from_init_data() {
+    let init_data = "query_id=AAH&user=%7B%22id%22%3A5%7D&start_param=item-7&auth_date=1&hash=ab";
+    assert_eq!(
+        deep_links::start_param_from_init_data(init_data),
+        Some("item-7".to_owned())
+    );
+    assert_eq!(deep_links::start_param_from_init_data("query_id=AAH"), None);
+}
+
+#[test]
+fn locale_format_numbers_and_dates() {
+    let en = LocaleFormat::for_language("en-US");
+    let de = LocaleFormat::for_language("de");
+    assert_eq!(en.format_integer(-1_234_567), "-1,234,567");
+    assert_eq!(de.format_integer(999), "999");
+    assert_eq!(de.format_decimal(12_345.678, 2), "12.345,68");
+    assert_eq!(en.format_decimal(-0.001, 2), "0.00");
+
+    let date = Utc.with_ymd_and_hms(2021, 3, 7, 14, 5, 0).unwrap();
+    assert_eq!(en.format_datetime(&date), "March 7, 2021 2:05 PM");
+    assert_eq!(de.format_date(&date), "7. März 2021");
+    assert_eq!(LocaleFormat::for_language("xx").language_code, "en");
+}
+
+#[tokio::test]
+async fn i18n_formats_in_the_chosen_language() -> telexide::Result<()> {
+    let mut i18n = I18n::new(Arc::new(MemoryStorage::new()));
+    i18n.add_locale("en", "English", [])
+        .add_locale("de", "Deutsch", [])
+        .add_locale("nl", "Nederlands", []);
+    let mut nl = LocaleFormat::for_language("de");
+    nl.language_code = "nl".to_owned();
+    i18n.set_locale_format("nl", nl.clone());
+
+    assert_eq!(i18n.locale_format("de-AT").format_integer(1234), "1.234");
+    assert_eq!(i18n.locale_format("nl"), nl);
+    assert_eq!(i18n.locale_format("fr").language_code, "en");
+
+    i18n.set_chat_language(-100, "de").await?;
+    let UpdateContent::Message(message) = fixtures::text_message(-100, 5, "hi").content else {
+        unreachable!()
+    };
+    let format = i18n.locale_format_for(&message).await?;
+    assert_eq!(format.format_decimal(1234.5, 1), "1.234,5");
+    Ok(())
+}
+
+fn tally(counts: &[usize], eligible: usize) -> VoteTally {
+    VoteTally {
+        options: (0..counts.len()).map(|i| format!("option {i}")).collect(),
+        counts: counts.to_vec(),
+        voters: counts.iter().sum(),
+        eligible,
+    }
+}
+
+#[test]
+fn vote_rules_decide_outcomes() {
+    let rules = VoteRules {
+        quorum: Quorum::Fraction(0.5),
+        majority: Majority::Fraction(2.0 / 3.0),
+    };
+
+    let outcome = rules.decide(tally(&[7, 3], 12));
+    assert_eq!(outcome.winner(), Some("option 0"));
+    assert!(matches!(
+        rules.decide(tally(&[6, 4], 12)),
+        VoteOutcome::NoMajority { .. }
+    ));
+    assert!(matches!(
+        rules.decide(tally(&[4, 1], 12)),
+        VoteOutcome::QuorumNotMet { required: 6, .. }
+    ));
+
+    let plurality = VoteRules {
+        quorum: Quorum::None,
+        majority: Majority::Plurality,
+    };
+    let outcome = plurality.decide(tally(&[2, 3, 1], 6));
+    assert_eq!(outcome.winner(), Some("option 1"));
+    assert_eq!(plurality.decide(tally(&[2, 2], 6)).winner(), None);
+
+    let outcome = VoteRules::default().decide(tally(&[0, 0], 6));
+    assert_eq!(outcome.winner(), None);
+}
+
+#[tokio::test]
+async fn vote_outcome_survives_failing_to_stop_the_vote() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let voting = Voting::new(Arc::new(Box::new(api.clone())));
+    let vote = Vote::new(-100, "Pin the rules?", &["yes", "no"])
+        .set_style(VoteStyle::Buttons)
+        .set_eligible_voters([1, 2, 3]);
+    let handle = voting.start(vote).await?;
+    for (user_id, option) in [(1, 0), (2, 0), (3, 1), (4, 1)] {
+        let data = format!("vote:{}:{option}", handle.id());
+        let UpdateContent::CallbackQuery(query) =
+            fixtures::callback_query(-100, user_id, &data).content
+        else {
+            unreachable!()
+        };
+        voting.handle_callback_query(&query).await?;
+    }
+
+    api.fail_with(
+        "editMessageReplyMarkup",
+        400,
+        "Bad Request: message to edit not found",
+    );
+    let id = handle.id().to_owned();
+    voting.close(&id).await?;
+    let outcome = handle.outcome().await?;
+    assert_eq!(outcome.winner(), Some("yes"));
+    assert_eq!(outcome.tally().voters, 3);
+    assert_eq!(outcome.tally().eligible, 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn vote_stays_open_until_the_voters_are_counted() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let voting = Voting::new(Arc::new(Box::new(api.clone())));
+    let vote = Vote::new(-100, "Pin the rules?", &["yes", "no"]).set_style(VoteStyle::Buttons);
+    let handle = voting.start(vote).await?;
+
+    let id = handle.id().to_owned();
+    api.clear_calls();
+    api.fail_with("getChatMemberCount", 400, "Bad Request: chat not found");
+    assert!(voting.close(&id).await.is_err());
+    assert!(api.calls_to("editMessageReplyMarkup").is_empty());
+
+    api.respond_with("getChatMemberCount", json!(10));
+    voting.close(&id).await?;
+    let outcome = handle.outcome().await?;
+    assert_eq!(outcome.tally().eligible, 10);
+    assert_eq!(api.calls_to("editMessageReplyMarkup").len(), 1);
+    Ok(())
+}
+
+/// a runtime whose timers resolve right away, for retries to happen without
+/// waiting
+struct Immediate;
+
+impl Runtime for Immediate {
+    fn spawn(&self, task: BoxedTask) {
+        tokio::spawn(task);
+    }
+
+    fn sleep(&self, _: std::time::Duration) -> BoxedTask {
+        Box::pin(async {})
+    }
+}
+
+#[tokio::test]
+async fn vote_is_given_up_on_when_it_cant_be_closed() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    api.fail_with("getChatMemberCount", 400, "Bad Request: chat not found");
+    let voting = Voting::new(Arc::new(Box::new(api.clone()))).set_runtime(Arc::new(Immediate));
+    let vote = Vote::new(-100, "Pin the rules?", &["yes", "no"]).set_style(VoteStyle::Buttons);
+    let handle = voting.start(vote).await?;
+
+    let outcome = tokio::time::timeout(std::time::Duration::from_secs(5), handle.outcome())
+        .await
+        .expect("the vote wasn't given up on");
+    assert!(outcome.is_err());
+    assert_eq!(api.calls_to("getChatMemberCount").len(), 5);
+    Ok(())
+}
+
+struct Places(Vec<(&'static str, GeoPoint)>);
+
+#[async_trait::async_trait]
+impl GeoResultProvider for Places {
+    async fn results(
+        &self,
+        _: &InlineQuery,
+        _: Option<GeoPoint>,
+    ) -> telexide::Result<Vec<GeoResult>> {
+        Ok(self
+            .0
+            .iter()
+            .map(|(name, point)| GeoResult {
+                point: *point,
+                result: InlineQueryResult::Location(InlineQueryResultLocation {
+                    id: (*name).to_owned(),
+                    latitude: point.latitude,
+                    longitude: point.longitude,
+                    title: (*name).to_owned(),
+                    live_period: None,
+                    heading: None,
+                    proximity_alert_radius: None,
+                    horizontal_accuracy: None,
+                    input_message_content: None,
+                    reply_markup: None,
+                    thumb_url: None,
+                    thumb_width: None,
+                    thumb_height: None,
+                }),
+            })
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn geo_results_are_sorted_by_distance() -> telexide::Result<()> {
+    let berlin = GeoPoint::new(52.52, 13.405);
+    let paris = GeoPoint::new(48.8566, 2.3522);
+    let distance = berlin.distance_to(&paris);
+    assert!((distance - 877_500.0).abs() < 5_000.0);
+
+    let en = LocaleFormat::for_language("en");
+    assert_eq!(format_distance(349.6, &en), "350 m");
+    assert_eq!(format_distance(2_440.0, &en), "2.4 km");
+    let de = LocaleFormat::for_language("de");
+    assert_eq!(format_distance(distance, &de), "877 km");
+
+    let places = Places(vec![("paris", paris), ("berlin", berlin)]);
+    let mut query = inline_query("1", 5, "places");
+
+    let answer = answer_geo_query(&places, &query).await?;
+    assert!(!answer.is_personal);
+    assert!(matches!(&answer.results[0], InlineQueryResult::Location(l) if l.id == "paris"));
+
+    query.location = Some(Location {
+        longitude: 13.4,
+        latitude: 52.5,
+        horizontal_accuracy: None,
+        live_period: None,
+        heading: None,
+        proximity_alert_radius: None,
+    });
+    let answer = answer_geo_query(&places, &query).await?;
+    assert!(answer.is_personal);
+    assert!(matches!(&answer.results[0], InlineQueryResult::Location(l) if l.id == "berlin"));
+    Ok(())
+}
+
+#[prepare_listener]
+async fn send_rules(ctx: Context, update: Update) {
+    if let UpdateContent::Message(m) = update.content {
+        let _ = ctx.send_stored(m.chat.get_id(), "rules-image").await;
+    }
+}
+
+#[tokio::test]
+async fn file_vault_sends_by_stored_kind() -> telexide::Result<()> {
+    let harness = Harness::new(ClientBuilder::new().add_handler_func(send_rules));
+    let vault = Arc::new(FileVault::new(Arc::new(MemoryStorage::new())));
+    harness
+        .client()
+        .data
+        .write()
+        .insert::<FileVault>(vault.clone());
+
+    let upload = fixtures::update(
+        "message",
+        json!({
+            "message_id": 4,
+            "date": 0,
+            "chat": {"id": -100, "type": "supergroup", "title": "test"},
+            "photo": [
+                {"file_id": "small", "file_unique_id": "s", "width": 90, "height": 90},
+                {"file_id": "large", "file_unique_id": "l", "width": 800, "height": 800}
+            ]
+        }),
+    );
+    let UpdateContent::Message(upload) = upload.content else {
+        unreachable!()
+    };
+    let stored = vault
+        .store_from_message(-100, "rules-image", &upload)
+        .await?;
+    assert_eq!(stored, StoredFile::new("large", StoredFileKind::Photo));
+    vault
+        .store(
+            5,
+            "rules-image",
+            &StoredFile::new("sticker", StoredFileKind::Sticker),
+        )
+        .await?;
+
+    harness
+        .feed(fixtures::text_message(-100, 5, "/rules"))
+        .await;
+    harness.feed(fixtures::text_message(5, 5, "/rules")).await;
+    harness.feed(fixtures::text_message(6, 6, "/rules")).await;
+
+    harness
+        .expect_call("sendPhoto")
+        .to_chat(-100)
+        .with("photo", &json!("large"))
+        .times(1);
+    harness
+        .expect_call("sendSticker")
+        .to_chat(5)
+        .with("sticker", &json!("sticker"))
+        .times(1);
+    assert_eq!(harness.api().calls().len(), 2);
+
+    vault.remove(5, "rules-image").await?;
+    assert!(vault.get(5, "rules-image").await?.is_none());
+    Ok(())
+}
+
+#[test]
+fn formatting_computes_utf16_offsets() -> serde_json::Result<()> {
+    let formatted = compose([
+        text("Hi "),
+        bold("Ådne 👋"),
+        text("! "),
+        italic(vec![
+            text("see "),
+            link(code("/rules"), "https://example.com"),
+        ]),
+    ]);
+    assert_eq!(formatted.text, "Hi Ådne 👋! see /rules");
+    assert_eq!(
+        serde_json::to_value(&formatted.entities)?,
+        json!([
+            {"type": "bold", "offset": 3, "length": 7},
+            {"type": "italic", "offset": 12, "length": 10},
+            {"type": "text_link", "offset": 16, "length": 6, "url": "https://example.com"},
+            {"type": "code", "offset": 16, "length": 6},
+        ])
+    );
+    if let MessageEntity::Bold(block) = &formatted.entities[0] {
+        assert_eq!(block.get_text(&formatted.text), "Ådne 👋");
+    }
+
+    let user = inline_query("1", 5, "").from;
+    let formatted = compose([mention("you", &user), text(""), bold("")]);
+    assert_eq!(formatted.entities.len(), 1);
+    assert!(matches!(
+        &formatted.entities[0],
+        MessageEntity::TextMention(m) if m.text_block == TextBlock { offset: 0, length: 3 }
+    ));
+
+    let mut message = SendMessage::new(1, "");
+    message.set_formatted_text(&formatted);
+    let value = serde_json::to_value(&message)?;
+    assert_eq!(value["text"], "you");
+    assert_eq!(value["entities"][0]["type"], "text_mention");
+
+    let edit = serde_json::to_value(EditMessageText::formatted(1, 2, &formatted))?;
+    assert_eq!(edit["entities"], value["entities"]);
+
+    let InputMessageContent::Text(content) = InputMessageContent::from(compose([text("plain")]))
+    else {
+        unreachable!()
+    };
+    assert_eq!(content.message_text, "plain");
+    assert!(content.entities.is_none());
+    Ok(())
+}
+
+#[test]
+fn formatted_text_echoes_received_entities() -> serde_json::Result<()> {
+    let received: Message = serde_json::from_value(json!({
+        "message_id": 1,
+        "date": 0,
+        "chat": {"id": 5, "type": "private", "first_name": "test"},
+        "photo": [{"file_id": "a", "file_unique_id": "b", "width": 1, "height": 1}],
+        "caption": "a *bold* move",
+        "caption_entities": [{"type": "bold", "offset": 2, "length": 6}],
+    }))?;
+    let formatted = FormattedText::from_message(&received).expect("has a caption");
+    assert_eq!(formatted.text, "a *bold* move");
+
+    let mut echo = SendMessage::new(5, "");
+    echo.set_formatted_text(&formatted);
+    let value = serde_json::to_value(&echo)?;
+    assert_eq!(value["text"], "a *bold* move");
+    assert_eq!(
+        value["entities"],
+        json!([{"type": "bold", "offset": 2, "length": 6}])
+    );
+    assert!(value["parse_mode"].is_null());
+
+    let mut photo = InputMediaPhoto::new(InputFile::FileId("a".to_owned()));
+    photo.set_formatted_caption(&formatted);
+    assert_eq!(
+        serde_json::to_value(&photo)?["caption_entities"],
+        value["entities"]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn language_command_and_overrides() -> telexide::Result<()> {
+    let mut framework = Framework::new("test_bot");
+    framework.add_command(&LANGUAGE_COMMAND);
+    let harness = Harness::new(ClientBuilder::new().set_framework(Arc::new(framework)));
+
+    let mut i18n = I18n::new(Arc::new(MemoryStorage::new()));
+    i18n.add_locale(
+        "en",
+        "English",
+        [
+            ("language.choose", "Choose a language"),
+            ("greeting", "Hello"),
+        ],
+    )
+    .add_locale(
+        "nl",
+        "Nederlands",
+        [
+            ("language.choose", "Kies een taal"),
+            ("language.changed", "Taal is nu {language}"),
+        ],
+    );
+    let i18n = Arc::new(i18n);
+    harness.client().data.write().insert::<I18n>(i18n.clone());
+
+    harness
+        .feed(fixtures::text_message(-100, 5, "/language"))
+        .await;
+    let sent = harness
+        .expect_send_message()
+        .to_chat(-100)
+        .containing("Choose a language")
+        .calls()[0]
+        .clone();
+    let button = &sent.data["reply_markup"]["inline_keyboard"][0][0];
+    assert_eq!(button["text"], "✓ English");
+    assert_eq!(button["callback_data"], "language:en");
+
+    // a button pressed in a group changes the language of the chat
+    let UpdateContent::CallbackQuery(query) =
+        fixtures::callback_query(-100, 5, "language:nl").content
+    else {
+        unreachable!()
+    };
+    assert!(i18n.handle_callback_query(harness.api(), &query).await?);
+    let answer = &harness.api().calls_to("answerCallbackQuery")[0];
+    assert_eq!(answer.data["text"], "Taal is nu Nederlands");
+
+    let mut user = inline_query("", 5, "").from;
+    assert_eq!(i18n.resolve(Some(-100), Some(&user)).await?, "nl");
+    assert_eq!(i18n.resolve(Some(-200), Some(&user)).await?, "en");
+    user.language_code = Some("nl-BE".to_owned());
+    assert_eq!(i18n.resolve(Some(-200), Some(&user)).await?, "nl");
+
+    // the choice of the user in a private chat wins over the one of the chat
+    let UpdateContent::CallbackQuery(query) = fixtures::callback_query(5, 5, "language:en").content
+    else {
+        unreachable!()
+    };
+    assert!(i18n.handle_callback_query(harness.api(), &query).await?);
+    assert_eq!(i18n.resolve(Some(-100), Some(&user)).await?, "en");
+
+    // missing messages fall back to the default language, then to the key
+    assert_eq!(i18n.translate("nl", "greeting"), "Hello");
+    assert_eq!(i18n.translate("nl", "unknown"), "unknown");
+    assert!(i18n.set_chat_language(-100, "xx").await.is_err());
+    Ok(())
+}
+
+fn participant(id: i64, is_bot: bool) -> serde_json::Value {
+    json!({"id": id, "is_bot": is_bot, "first_name": format!("user {id}")})
+}
+
+#[tokio::test]
+async fn raffle_draws_reproducible_winners() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let raffles = Raffles::new(
+        Arc::new(Box::new(api.clone())),
+        Arc::new(MemoryStorage::new()),
+    );
+    let raffle = Raffle::new(-100, "Win a prize!", 2)
+        .set_seed(42)
+        .exclude_users([4]);
+    let handle = raffles.start(raffle).await?;
+    let announcement = &api.calls_to("sendMessage")[0].data;
+    assert_eq!(
+        announcement["reply_markup"]["inline_keyboard"][0][0]["callback_data"],
+        "raffle:join"
+    );
+
+    let entries = [(1, false), (2, false), (2, false), (3, false), (4, false), (5, true)];
+    for (user_id, is_bot) in entries {
+        let query: CallbackQuery = serde_json::from_value(json!({
+            "id": format!("{user_id}"),
+            "from": participant(user_id, is_bot),
+            "message": {
+                "message_id": handle.message_id(),
+                "date": 0,
+                "chat": {"id": -100, "type": "supergroup", "title": "test chat"},
+                "text": "Win a prize!"
+            },
+            "chat_instance": "-100",
+            "data": "raffle:join"
+        }))?;
+        assert!(raffles.handle_callback_query(&query).await?);
+    }
+    let ids: Vec<i64> = raffles
+        .participants(handle.id())
+        .iter()
+        .map(|u| u.id)
+        .collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+
+    let id = handle.id().to_owned();
+    raffles.close(&id).await?;
+    let result = handle.result().await?;
+    assert_eq!(result.seed, 42);
+    assert_eq!(result.participants.len(), 3);
+    assert_eq!(result.winners.len(), 2);
+    assert_ne!(result.winners[0].id, result.winners[1].id);
+    assert!(result.verify());
+    assert_eq!(draw_winners(&result.participants, 2, 42), result.winners);
+    assert_eq!(raffles.result(&id).await?, Some(result));
+
+    let results = api.calls_to("sendMessage")[1].data["text"].clone();
+    let results = results.as_str().unwrap();
+    assert!(results.starts_with("Winners: "));
+    assert!(results.ends_with("3 participants, seed 42"));
+    assert_eq!(api.calls_to("editMessageReplyMarkup").len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn raffle_entered_with_reactions() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let raffles = Raffles::new(
+        Arc::new(Box::new(api.clone())),
+        Arc::new(MemoryStorage::new()),
+    );
+    let raffle = Raffle::new(-100, "React to win", 1)
+        .set_entry(RaffleEntry::Reaction("🎉".to_owned()))
+        .set_announce_results(false);
+    let handle = raffles.start(raffle).await?;
+
+    let react = |user_id: i64, emoji: &[&str]| -> MessageReactionUpdated {
+        let reactions: Vec<_> = emoji
+            .iter()
+            .map(|e| json!({"type": "emoji", "emoji": e}))
+            .collect();
+        serde_json::from_value(json!({
+            "chat": {"id": -100, "type": "supergroup", "title": "test chat"},
+            "message_id": handle.message_id(),
+            "user": participant(user_id, false),
+            "date": 0,
+            "old_reaction": [],
+            "new_reaction": reactions
+        }))
+        .unwrap()
+    };
+    assert!(raffles.handle_reaction(&react(1, &["🎉"])).await?);
+    assert!(raffles.handle_reaction(&react(2, &["👍"])).await?);
+    assert!(raffles.handle_reaction(&react(3, &["🎉", "👍"])).await?);
+    assert!(raffles.handle_reaction(&react(1, &[])).await?);
+
+    let id = handle.id().to_owned();
+    raffles.close(&id).await?;
+    let result = handle.result().await?;
+    assert_eq!(result.participants.len(), 1);
+    assert_eq!(result.winners[0].id, 3);
+    assert_eq!(api.calls().len(), 1);
+    Ok(())
+}
+
+fn join_raffle(user_id: i64, message_id: i64) -> CallbackQuery {
+    serde_json::from_value(json!({
+        "id": format!("{user_id}"),
+        "from": participant(user_id, false),
+        "message": {
+            "message_id": message_id,
+            "date": 0,
+            "chat": {"id": -100, "type": "supergroup", "title": "test chat"},
+            "text": "Win a prize!"
+        },
+        "chat_instance": "-100",
+        "data": "raffle:join"
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn raffle_stays_running_when_announcing_the_winners_fails() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let raffles = Raffles::new(
+        Arc::new(Box::new(api.clone())),
+        Arc::new(MemoryStorage::new()),
+    );
+    let handle = raffles.start(Raffle::new(-100, "Win a prize!", 1)).await?;
+    for user_id in 1..=3 {
+        raffles
+            .handle_callback_query(&join_raffle(user_id, handle.message_id()))
+            .await?;
+    }
+
+    let id = handle.id().to_owned();
+    api.fail_with(
+        "editMessageReplyMarkup",
+        400,
+        "Bad Request: message is not modified",
+    );
+    api.flood_next("sendMessage", 1, 5);
+    assert!(raffles.close(&id).await.is_err());
+    assert_eq!(raffles.participants(&id).len(), 3);
+    let drawn = raffles.result(&id).await?.unwrap();
+
+    raffles.close(&id).await?;
+    assert_eq!(handle.result().await?, drawn);
+    assert!(raffles.participants(&id).is_empty());
+    assert_eq!(api.calls_to("sendMessage").len(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn raffle_is_given_up_on_when_it_cant_be_closed() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let storage = Arc::new(MemoryStorage::new());
+    let raffles = Raffles::new(Arc::new(Box::new(api.clone())), storage.clone())
+        .set_runtime(Arc::new(Immediate));
+    let handle = raffles.start(Raffle::new(-100, "Win a prize!", 1)).await?;
+    api.fail_with("sendMessage", 400, "Bad Request: chat not found");
+
+    let result = tokio::time::timeout(std::time::Duration::from_secs(5), handle.result())
+        .await
+        .expect("the raffle wasn't given up on");
+    assert!(result.is_err());
+    assert_eq!(api.calls_to("sendMessage").len(), 6);
+    assert!(Raffles::new(Arc::new(Box::new(api)), storage)
+        .restore()
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn raffle_is_restored_after_a_restart() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let storage = Arc::new(MemoryStorage::new());
+    let raffles = Raffles::new(Arc::new(Box::new(api.clone())), storage.clone());
+    let handle = raffles
+        .start(Raffle::new(-100, "Win a prize!", 2).set_seed(7))
+        .await?;
+    for user_id in 1..=3 {
+        raffles
+            .handle_callback_query(&join_raffle(user_id, handle.message_id()))
+            .await?;
+    }
+
+    let restarted = Raffles::new(Arc::new(Box::new(api.clone())), storage.clone());
+    let handles = restarted.restore().await?;
+    assert_eq!(handles.len(), 1);
+    let id = handles[0].id().to_owned();
+    assert_eq!(id, handle.id());
+    assert_eq!(restarted.participants(&id).len(), 3);
+
+    restarted.close(&id).await?;
+    let result = handles.into_iter().next().unwrap().result().await?;
+    assert_eq!(result.seed, 7);
+    assert_eq!(result.participants.len(), 3);
+    assert!(restarted.restore().await?.is_empty());
+    assert!(Raffles::new(Arc::new(Box::new(api)), storage)
+        .restore()
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn experiments_pick_weighted_sticky_variants() -> telexide::Result<()> {
+    let mut experiments = Experiments::new(Arc::new(MemoryStorage::new()));
+    experiments
+        .add_variant("onboarding", "short", 1, "Welcome!")
+        .add_variant("onboarding", "guided", 1, "Welcome! Try /help.")
+        .add_variant("onboarding", "disabled", 0, "Hi");
+
+    let picks: Vec<&str> = (0..200)
+        .map(|user| experiments.pick("onboarding", user))
+        .collect::<telexide::Result<_>>()?;
+    assert!(picks.contains(&"short") && picks.contains(&"guided"));
+    assert!(!picks.contains(&"disabled"));
+    assert_eq!(experiments.pick("onboarding", 42)?, picks[42]);
+    assert!(experiments.pick("checkout", 42).is_err());
+
+    let api = MockAPI::new();
+    let first = picks[42].to_owned();
+    experiments.send_variant(&api, -100, 42, "onboarding").await?;
+    experiments.send_variant(&api, 42, 42, "onboarding").await?;
+    let texts: Vec<String> = api
+        .calls_to("sendMessage")
+        .iter()
+        .map(|c| c.data["text"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(texts[0], texts[1]);
+
+    let sent = experiments.sent_variant("onboarding", 42).await?.unwrap();
+    assert_eq!(sent.variant, first);
+    assert_eq!((sent.chat_id, sent.times_sent), (42, 2));
+
+    // a variant without weight is no longer sent, not even to its users
+    experiments.add_variant("onboarding", &first, 0, "gone");
+    experiments.send_variant(&api, 42, 42, "onboarding").await?;
+    let sent = experiments.sent_variant("onboarding", 42).await?.unwrap();
+    assert_ne!(sent.variant, first);
+    assert_eq!(sent.times_sent, 1);
+
+    let counts = experiments.counts("onboarding").await?;
+    assert_eq!(counts.get(&sent.variant), Some(&1));
+    assert_eq!(experiments.export("onboarding").await?, vec![sent]);
+    Ok(())
+}
+
+#[prepare_listener]
+async fn send_onboarding(ctx: Context, update: Update) {
+    if let UpdateContent::Message(m) = update.content {
+        let _ = ctx.send_variant(&m, "onboarding").await;
+    }
+}
+
+#[tokio::test]
+async fn experiments_pick_the_variant_for_the_sender() -> telexide::Result<()> {
+    let harness = Harness::new(ClientBuilder::new().add_handler_func(send_onboarding));
+    let mut experiments = Experiments::new(Arc::new(MemoryStorage::new()));
+    experiments.add_variant("onboarding", "short", 1, "Welcome!");
+    let experiments = Arc::new(experiments);
+    harness
+        .client()
+        .data
+        .write()
+        .insert::<Experiments>(experiments.clone());
+
+    harness.feed(fixtures::text_message(-100, 42, "hi")).await;
+    harness
+        .expect_send_message()
+        .to_chat(-100)
+        .containing("Welcome!")
+        .times(1);
+
+    let sent = experiments.sent_variant("onboarding", 42).await?.unwrap();
+    assert_eq!((sent.chat_id, sent.times_sent), (-100, 1));
+    assert!(experiments
+        .sent_variant("onboarding", -100)
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_sticker_set_downloads_every_sticker() -> telexide::Result<()> {
+    let sticker = |id: &str, is_animated: bool, is_video: bool| {
+        json!({
+            "file_id": id,
+            "file_unique_id": id.replace(['/', '.'], ""),
+            "type": "regular",
+            "width": 512,
+            "height": 512,
+            "is_animated": is_animated,
+            "is_video": is_video,
+        })
+    };
+    let api = MockAPI::new();
+    api.respond_with(
+        "getStickerSet",
+        json!({
+            "name": "pack_by_test_bot",
+            "title": "Pack",
+            "type": "regular",
+            "stickers": [
+                sticker("stickers/a.png", false, false),
+                sticker("animated/b.tgs", true, false),
+                sticker("c", false, true),
+            ],
+        }),
+    );
+    api.add_file("stickers/a.png", "png");
+    api.add_file("animated/b.tgs", "tgs");
+    api.add_file("c", "webm");
+
+    let dir = std::env::temp_dir().join(format!("telexide-stickers-{}", std::process::id()));
+    let paths = export_sticker_set(&api, "pack_by_test_bot", &dir, 2).await?;
+    let names: Vec<_> = paths
+        .iter()
+        .map(|p| {
+            p.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    assert_eq!(
+        names,
+        ["000_stickersapng.png", "001_animatedbtgs.tgs", "002_c.webm"]
+    );
+    assert_eq!(std::fs::read(&paths[1])?, b"tgs");
+    assert!(dir.join("set.json").exists());
+    assert_eq!(api.calls_to("download").len(), 3);
+
+    api.respond_with(
+        "getStickerSet",
+        json!({
+            "name": "pack_by_test_bot",
+            "title": "Pack",
+            "type": "regular",
+            "stickers": [sticker("missing", false, false)],
+        }),
+    );
+    assert!(export_sticker_set(&api, "pack_by_test_bot", &dir, 2)
+        .await
+        .is_err());
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
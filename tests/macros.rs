@@ -60,6 +60,7 @@ async fn test_using_command() -> Result<()> {
         update_id: 10,
         content: UpdateContent::Message(Message {
             message_id: 30,
+            message_thread_id: None,
             from: None,
             date: chrono::offset::Utc::now(),
             chat: Chat::Private(PrivateChat {
@@ -73,9 +74,11 @@ async fn test_using_command() -> Result<()> {
             sender_chat: None,
             forward_data: None,
             reply_to_message: None,
+            reply_to_story: None,
             via_bot: None,
             edit_date: None,
             author_signature: None,
+            has_protected_content: false,
             connected_website: None,
             passport_data: None,
             reply_markup: None,
@@ -91,6 +94,7 @@ async fn test_using_command() -> Result<()> {
         update_id: 10,
         content: UpdateContent::Message(Message {
             message_id: 30,
+            message_thread_id: None,
             from: None,
             date: chrono::offset::Utc::now(),
             chat: Chat::Private(PrivateChat {
@@ -104,9 +108,11 @@ async fn test_using_command() -> Result<()> {
             sender_chat: None,
             forward_data: None,
             reply_to_message: None,
+            reply_to_story: None,
             via_bot: None,
             edit_date: None,
             author_signature: None,
+            has_protected_content: false,
             connected_website: None,
             passport_data: None,
             reply_markup: None,
@@ -125,3 +131,34 @@ async fn test_using_command() -> Result<()> {
     assert_eq!(COMMAND_B.load(Ordering::Relaxed), 30);
     Ok(())
 }
+
+static FALLIBLE_B: AtomicUsize = AtomicUsize::new(0);
+
+#[prepare_listener]
+async fn fallible_listener(_c: Context, u: Update) -> Result<()> {
+    FALLIBLE_B.fetch_add(u.update_id as usize, Ordering::Acquire);
+    Err(std::io::Error::other("listener failed").into())
+}
+
+#[tokio::test]
+async fn test_prepared_listener_returning_result() -> Result<()> {
+    let mut c = ClientBuilder::new().set_token("test").build();
+
+    c.subscribe_handler_func(fallible_listener);
+    c.on_handler_error(|_, err| async move {
+        FALLIBLE_B.fetch_add(err.error.to_string().len(), Ordering::Acquire);
+    });
+
+    c.fire_handlers(Update {
+        update_id: 10,
+        content: UpdateContent::Unknown,
+    });
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    assert_eq!(
+        FALLIBLE_B.load(Ordering::Relaxed),
+        10 + "listener failed".len()
+    );
+    Ok(())
+}
@@ -0,0 +1,588 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use futures::future::{join, join_all};
+use serde_json::json;
+use telexide::{
+    client::ClientBuilder,
+    helpers::AdminCache,
+    middleware::{
+        self,
+        ArchivedMessage,
+        BridgeEndpoint,
+        ForwardingBridge,
+        HistoryCollector,
+        HistorySink,
+        JsonLinesSink,
+        KeywordFilter,
+        MemberRoster,
+        ModerationAction,
+        OverflowPolicy,
+        ReactionModeration,
+        UserConcurrencyLimit,
+    },
+    model::{Chat, Message, MessageContent, PrivateChat, Update, UpdateContent, User},
+    test::{fixtures, Harness},
+    storage::{
+        HistoryPages,
+        HistoryQuery,
+        MemoryMessageStore,
+        MemoryStorage,
+        MessageStore,
+        Storage,
+    },
+    Result,
+};
+use tokio::sync::{mpsc, Semaphore};
+
+fn message_from(update_id: i64, user_id: i64) -> Update {
+    Update {
+        update_id,
+        content: UpdateContent::Message(Message {
+            message_id: update_id,
+            message_thread_id: None,
+            from: Some(User {
+                id: user_id,
+                is_bot: false,
+                first_name: "test".to_owned(),
+                last_name: None,
+                username: None,
+                language_code: None,
+                can_join_groups: None,
+                can_read_all_group_messages: None,
+                supports_inline_queries: None,
+                can_connect_to_business: None,
+            }),
+            date: chrono::offset::Utc::now(),
+            chat: Chat::Private(PrivateChat {
+                id: user_id,
+                username: None,
+                first_name: None,
+                bio: None,
+                last_name: None,
+                photo: None,
+            }),
+            sender_chat: None,
+            forward_data: None,
+            reply_to_message: None,
+            reply_to_story: None,
+            via_bot: None,
+            edit_date: None,
+            author_signature: None,
+            has_protected_content: false,
+            connected_website: None,
+            passport_data: None,
+            reply_markup: None,
+            content: MessageContent::Unknown,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn user_concurrency_limit_queues() -> Result<()> {
+    let running = Arc::new(AtomicUsize::new(0));
+    let gate = Arc::new(Semaphore::new(0));
+    let (started, mut starts) = mpsc::unbounded_channel();
+    let harness = Harness::new(
+        ClientBuilder::new()
+            .add_middleware(UserConcurrencyLimit::new(1))
+            .add_handler_func({
+                let (running, gate) = (running.clone(), gate.clone());
+                move |_c, _u| {
+                    let (running, gate, started) = (running.clone(), gate.clone(), started.clone());
+                    async move {
+                        let _ = started.send(running.fetch_add(1, Ordering::SeqCst) + 1);
+                        gate.acquire()
+                            .await
+                            .expect("the gate is never closed")
+                            .forget();
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    }
+                }
+            }),
+    );
+
+    let feeds = join_all((0..3).map(|i| harness.feed(message_from(i, 5))));
+    let release = async {
+        for _ in 0..3 {
+            // the next handler only starts once the running one finished
+            assert_eq!(starts.recv().await, Some(1));
+            gate.add_permits(1);
+        }
+    };
+    join(feeds, release).await;
+    assert_eq!(running.load(Ordering::SeqCst), 0);
+    Ok(())
+}
+
+static FN_SEEN: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test]
+async fn closures_can_alter_and_stop_updates() -> Result<()> {
+    let harness = Harness::new(
+        ClientBuilder::new()
+            .add_middleware(middleware::from_fn(|ctx, mut update, next| {
+                Box::pin(async move {
+                    if update.update_id % 2 == 1 {
+                        return;
+                    }
+                    update.update_id *= 10;
+                    next.run(ctx, update).await;
+                })
+            }))
+            .add_handler_func(|_c, u| {
+                Box::pin(async move {
+                    FN_SEEN.fetch_add(u.update_id as usize, Ordering::SeqCst);
+                })
+            }),
+    );
+
+    for i in 1..=4 {
+        harness.feed(message_from(i, 5)).await;
+    }
+
+    assert_eq!(FN_SEEN.load(Ordering::SeqCst), 60);
+    Ok(())
+}
+
+#[tokio::test]
+async fn user_concurrency_limit_drops() -> Result<()> {
+    let gate = Arc::new(Semaphore::new(0));
+    let (started, mut starts) = mpsc::unbounded_channel();
+    let harness = Harness::new(
+        ClientBuilder::new()
+            .add_middleware(UserConcurrencyLimit::new(1).set_overflow_policy(OverflowPolicy::Drop))
+            .add_handler_func({
+                let gate = gate.clone();
+                move |_c, u| {
+                    let (gate, started) = (gate.clone(), started.clone());
+                    async move {
+                        let _ = started.send(u.update_id);
+                        gate.acquire()
+                            .await
+                            .expect("the gate is never closed")
+                            .forget();
+                    }
+                }
+            }),
+    );
+
+    let first = harness.feed(message_from(1, 5));
+    let rest = async {
+        assert_eq!(starts.recv().await, Some(1));
+        // the user is still busy, so this one is dropped right away
+        harness.feed(message_from(2, 5)).await;
+        gate.add_permits(2);
+        harness.feed(message_from(3, 6)).await;
+    };
+    join(first, rest).await;
+
+    assert_eq!(starts.try_recv().ok(), Some(3));
+    assert!(starts.try_recv().is_err());
+    Ok(())
+}
+
+#[test]
+fn keyword_filter_matches() -> Result<()> {
+    let filter = KeywordFilter::new(Arc::new(MemoryStorage::new()))
+        .add_keyword("spam")
+        .add_pattern(r"buy \d+ coins")?;
+
+    assert_eq!(filter.find_match("no SPAM please"), Some("SPAM".to_owned()));
+    assert_eq!(filter.find_match("spammer"), None);
+    assert_eq!(
+        filter.find_match("buy 100 coins now"),
+        Some("buy 100 coins".to_owned())
+    );
+    assert_eq!(filter.find_match("hello there"), None);
+    assert!(KeywordFilter::new(Arc::new(MemoryStorage::new()))
+        .add_pattern("(")
+        .is_err());
+
+    let filter = KeywordFilter::new(Arc::new(MemoryStorage::new()))
+        .add_keyword("c++")
+        .add_keyword("@spam");
+    assert_eq!(filter.find_match("I love C++!"), Some("C++".to_owned()));
+    assert_eq!(filter.find_match("ask @spam now"), Some("@spam".to_owned()));
+    assert_eq!(filter.find_match("ask @spammer now"), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn keyword_filter_counts_concurrent_strikes() {
+    let (violations, mut strikes) = mpsc::unbounded_channel();
+    let filter = KeywordFilter::new(Arc::new(MemoryStorage::new()))
+        .add_keyword("spam")
+        .set_delete_messages(false)
+        .add_violation_handler(move |_, violation| {
+            violations.send(violation.strikes).ok();
+            async {}
+        });
+    let harness = Harness::new(ClientBuilder::new().add_middleware(filter));
+
+    join(
+        harness.feed(fixtures::text_message(-100, 1, "spam")),
+        harness.feed(fixtures::text_message(-100, 1, "more spam")),
+    )
+    .await;
+
+    let mut counted = vec![
+        strikes.recv().await.expect("a violation"),
+        strikes.recv().await.expect("a violation"),
+    ];
+    counted.sort_unstable();
+    assert_eq!(counted, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn memory_storage_roundtrip() -> Result<()> {
+    let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+
+    assert_eq!(storage.get_as::<u32>("strikes").await?, None);
+    storage.set_as("strikes", &3_u32).await?;
+    assert_eq!(storage.get_as::<u32>("strikes").await?, Some(3));
+    storage.remove("strikes").await?;
+    assert_eq!(storage.get_as::<u32>("strikes").await?, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn history_collector_respects_chat_toggle() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("telexide_history_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let collector = HistoryCollector::new(
+        Arc::new(JsonLinesSink::open(&path).await?),
+        Arc::new(MemoryStorage::new()),
+    );
+    collector.set_chat_enabled(5, true).await?;
+    assert!(!collector.is_chat_enabled(6).await?);
+
+    let harness = Harness::new(ClientBuilder::new().add_middleware(collector));
+    harness.feed(message_from(1, 5)).await;
+    harness.feed(message_from(2, 6)).await;
+
+    let contents = std::fs::read_to_string(&path)?;
+    let _ = std::fs::remove_file(&path);
+    let archived = contents
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<serde_json::Result<Vec<ArchivedMessage>>>()?;
+
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].chat_id, 5);
+    assert_eq!(archived[0].message_id, 1);
+    assert_eq!(archived[0].user_id, Some(5));
+    Ok(())
+}
+
+fn archived(chat_id: i64, message_id: i64, date: i64) -> ArchivedMessage {
+    ArchivedMessage {
+        chat_id,
+        message_id,
+        user_id: Some(5),
+        date: chrono::TimeZone::timestamp_opt(&chrono::Utc, date, 0).unwrap(),
+        edit_date: None,
+        text: Some(format!("message {}", message_id)),
+        media: None,
+    }
+}
+
+#[tokio::test]
+async fn history_pages_through_store() -> Result<()> {
+    let store = Arc::new(MemoryMessageStore::new());
+    for id in 1..=5 {
+        store.archive(&archived(1, id, 1000 + id)).await?;
+    }
+    store.archive(&archived(2, 3, 1000)).await?;
+
+    let mut pages = HistoryPages::new(store.clone(), HistoryQuery::new(1).set_limit(2));
+    let mut ids = Vec::new();
+    while let Some(page) = pages.next_page().await? {
+        assert!(page.len() <= 2);
+        ids.extend(page.iter().map(|m| m.message_id));
+    }
+    assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+
+    let by_date = HistoryQuery::new(1).between_dates(
+        chrono::TimeZone::timestamp_opt(&chrono::Utc, 1002, 0).unwrap(),
+        chrono::TimeZone::timestamp_opt(&chrono::Utc, 1003, 0).unwrap(),
+    );
+    let ids: Vec<i64> = store
+        .messages(&by_date)
+        .await?
+        .iter()
+        .map(|m| m.message_id)
+        .collect();
+    assert_eq!(ids, vec![2, 3]);
+
+    let by_id = HistoryQuery::new(1).between_ids(4, 10);
+    assert_eq!(store.messages(&by_id).await?.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reaction_moderation_maps_added_reactions() -> Result<()> {
+    let update: Update = serde_json::from_str(
+        r#"{
+            "update_id": 3,
+            "message_reaction": {
+                "chat": {"id": -100, "type": "supergroup", "title": "test"},
+                "message_id": 12,
+                "user": {"id": 5, "is_bot": false, "first_name": "admin"},
+                "date": 1700000000,
+                "old_reaction": [{"type": "emoji", "emoji": "👍"}],
+                "new_reaction": [{"type": "emoji", "emoji": "👍"}, {"type": "emoji", "emoji": "🚫"}]
+            }
+        }"#,
+    )?;
+    let reaction = match update.content {
+        UpdateContent::MessageReaction(r) => r,
+        _ => panic!("no message reaction"),
+    };
+
+    let moderation = ReactionModeration::new(
+        Arc::new(MemoryStorage::new()),
+        Arc::new(AdminCache::default()),
+    )
+    .add_action("👍", ModerationAction::Delete)
+    .add_action("🚫", ModerationAction::Warn("mind the rules".to_owned()));
+
+    assert_eq!(
+        moderation.find_action(&reaction),
+        Some(&ModerationAction::Warn("mind the rules".to_owned()))
+    );
+
+    assert!(moderation.is_chat_enabled(-100).await?);
+    moderation.set_chat_enabled(-100, false).await?;
+    assert!(!moderation.is_chat_enabled(-100).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn forwarding_bridge_targets_topics() -> Result<()> {
+    let bridge = ForwardingBridge::new(Arc::new(MemoryStorage::new()))
+        .add_pair(BridgeEndpoint::chat(5), BridgeEndpoint::topic(-1002, 7))
+        .add_link(BridgeEndpoint::topic(-1002, 9), BridgeEndpoint::chat(6));
+
+    let mut update = message_from(1, 5);
+    let UpdateContent::Message(message) = &mut update.content else {
+        panic!("no message")
+    };
+    assert_eq!(
+        bridge.targets(message),
+        vec![BridgeEndpoint::topic(-1002, 7)]
+    );
+
+    let topic_message: Message = serde_json::from_str(
+        r#"{"message_id": 3, "message_thread_id": 7, "date": 1585772722,
+            "chat": {"id": -1002, "type": "supergroup", "title": "forum"}, "text": "hi"}"#,
+    )?;
+    assert_eq!(
+        bridge.targets(&topic_message),
+        vec![BridgeEndpoint::chat(5)]
+    );
+
+    message.chat = topic_message.chat.clone();
+    assert!(bridge.targets(message).is_empty());
+
+    assert!(bridge.linked_messages(5, 1).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn forwarding_bridge_mirrors_edits_and_deletes() -> Result<()> {
+    let storage = Arc::new(MemoryStorage::new());
+    let bridge = || {
+        ForwardingBridge::new(storage.clone())
+            .add_link(BridgeEndpoint::chat(-1001), BridgeEndpoint::chat(-1002))
+            .add_link(BridgeEndpoint::chat(-1001), BridgeEndpoint::topic(-1003, 7))
+    };
+    let harness = Harness::new(ClientBuilder::new().add_middleware(bridge()));
+
+    let update = fixtures::text_message(-1001, 1, "hello");
+    let UpdateContent::Message(original) = &update.content else {
+        panic!("no message")
+    };
+    let original = (-1001, original.message_id);
+    harness.feed(update).await;
+    harness
+        .expect_call("copyMessage")
+        .with("from_chat_id", &json!(-1001))
+        .times(2);
+    harness
+        .expect_call("copyMessage")
+        .to_chat(-1003)
+        .with("message_thread_id", &json!(7));
+
+    let copies = bridge().linked_messages(original.0, original.1).await?;
+    assert_eq!(copies.len(), 2);
+    // every copy knows about the original and the other copy
+    for copy in &copies {
+        let mut linked = bridge().linked_messages(copy.0, copy.1).await?;
+        linked.sort_unstable();
+        let mut expected: Vec<_> = copies.iter().filter(|c| *c != copy).copied().collect();
+        expected.push(original);
+        expected.sort_unstable();
+        assert_eq!(linked, expected);
+    }
+
+    let edit = fixtures::edited_message(-1001, 1, original.1, "hello again");
+    harness.feed(edit).await;
+    harness
+        .expect_call("editMessageText")
+        .containing("hello again")
+        .times(2);
+
+    let sticker = json!({
+        "message_id": original.1,
+        "date": 10,
+        "edit_date": 11,
+        "chat": {"id": -1001, "type": "supergroup", "title": "test chat"},
+        "sticker": {
+            "file_id": "sticker",
+            "file_unique_id": "sticker",
+            "type": "regular",
+            "width": 512,
+            "height": 512,
+            "is_animated": false,
+            "is_video": false
+        }
+    });
+    harness
+        .feed(fixtures::update("edited_message", sticker))
+        .await;
+    assert!(harness.api().calls_to("editMessageCaption").is_empty());
+
+    // deleting a copy deletes the original and the other copy
+    let (copy, sibling) = (copies[0], copies[1]);
+    bridge()
+        .propagate_delete(harness.api(), copy.0, copy.1)
+        .await?;
+    for (chat_id, message_id) in [original, sibling] {
+        harness
+            .expect_call("deleteMessage")
+            .to_chat(chat_id)
+            .with("message_id", &json!(message_id));
+    }
+    harness.expect_call("deleteMessage").times(2);
+    for (chat_id, message_id) in [original, copy, sibling] {
+        assert!(bridge()
+            .linked_messages(chat_id, message_id)
+            .await?
+            .is_empty());
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn member_roster_keeps_concurrent_members_per_chat() -> Result<()> {
+    let roster = MemberRoster::new(Arc::new(MemoryStorage::new()));
+    let harness = Harness::new(ClientBuilder::new().add_middleware(roster.clone()));
+
+    let updates = (1..=10).flat_map(|user_id| {
+        [
+            fixtures::text_message(-100, user_id, "hi"),
+            fixtures::text_message(-200, user_id, "hi"),
+        ]
+    });
+    join_all(updates.map(|update| harness.feed(update))).await;
+
+    for chat_id in [-100, -200] {
+        let mut ids: Vec<i64> = roster
+            .export(chat_id)
+            .await?
+            .iter()
+            .map(|m| m.user.id)
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, (1..=10).collect::<Vec<_>>());
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn member_roster_tracks_members() -> Result<()> {
+    let roster = MemberRoster::new(Arc::new(MemoryStorage::new()));
+    let harness = Harness::new(ClientBuilder::new().add_middleware(roster.clone()));
+    let user = |id: i64| json!({"id": id, "is_bot": false, "first_name": format!("user{id}")});
+    let service = |content: &str, value: serde_json::Value| {
+        let mut message = json!({
+            "message_id": 1,
+            "date": 10,
+            "chat": {"id": -100, "type": "supergroup", "title": "test"},
+            "from": user(1),
+        });
+        message[content] = value;
+        fixtures::update("message", message)
+    };
+
+    harness
+        .feed(fixtures::text_message(-100, 1, "hi"))
+        .await;
+    harness
+        .feed(fixtures::text_message(-100, 1, "again"))
+        .await;
+    harness
+        .feed(service("new_chat_members", json!([user(2), user(3)])))
+        .await;
+    harness.feed(service("left_chat_member", user(3))).await;
+    harness
+        .feed(fixtures::update(
+            "chat_member",
+            json!({
+                "chat": {"id": -100, "type": "supergroup", "title": "test"},
+                "from": user(4),
+                "date": 20,
+                "old_chat_member": {"status": "left", "user": user(4)},
+                "new_chat_member": {"status": "member", "user": user(4)}
+            }),
+        ))
+        .await;
+    // private chats don't have a roster
+    harness.feed(fixtures::text_message(5, 5, "hi")).await;
+
+    let ids = |members: Vec<telexide::middleware::RosterMember>| {
+        members.iter().map(|m| m.user.id).collect::<Vec<_>>()
+    };
+    assert_eq!(ids(roster.export(-100).await?), vec![2, 3, 4, 1]);
+    assert_eq!(ids(roster.members(-100).await?), vec![2, 4, 1]);
+    assert!(roster.members(5).await?.is_empty());
+
+    let sender = roster.member(-100, 1).await?.expect("the sender is known");
+    assert_eq!(sender.message_count, 2);
+    let since = chrono::Utc::now() - chrono::Duration::minutes(1);
+    assert_eq!(ids(roster.active_since(-100, since).await?), vec![1]);
+
+    let mentions = roster.mention_all(-100).await?;
+    assert_eq!(mentions.len(), 1);
+    assert_eq!(mentions[0].text, "user2, user4, test");
+    assert_eq!(mentions[0].entities.len(), 3);
+
+    // long lists are split over several messages
+    let joined: Vec<_> = (10..65).map(user).collect();
+    harness
+        .feed(service("new_chat_members", json!(joined)))
+        .await;
+    let mentions = roster.mention_all(-100).await?;
+    assert_eq!(mentions.len(), 2);
+    assert_eq!(mentions[0].entities.len(), 50);
+    assert_eq!(mentions[1].entities.len(), 8);
+    assert!(mentions[1].text.starts_with("user59, "));
+
+    let long_name = |id: i64| json!({"id": id, "is_bot": false, "first_name": "a".repeat(2000)});
+    let mut message = service(
+        "new_chat_members",
+        json!([long_name(1), long_name(2), long_name(3)]),
+    );
+    if let UpdateContent::Message(m) = &mut message.content {
+        m.chat =
+            serde_json::from_value(json!({"id": -200, "type": "supergroup", "title": "test"}))?;
+    }
+    harness.feed(message).await;
+    let mentions = roster.mention_all(-200).await?;
+    assert_eq!(mentions.len(), 2);
+    assert_eq!(mentions[0].text.len(), 4002);
+    Ok(())
+}
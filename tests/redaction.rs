@@ -0,0 +1,45 @@
+use telexide::redaction::{self, RedactionPolicy};
+
+#[test]
+fn scrubs_phone_numbers_and_paths() {
+    let policy = RedactionPolicy::default();
+
+    assert_eq!(
+        policy.scrub("user shared +1 (555) 010-9999 and 555.010.1234"),
+        "user shared [phone] and [phone]"
+    );
+    assert_eq!(
+        policy.scrub("failed to open \"/var/lib/bot/history.jsonl\": not found"),
+        "failed to open \"[path]\": not found"
+    );
+    assert_eq!(
+        policy.scrub(r"failed to read C:\bots\data\file.txt"),
+        "failed to read [path]"
+    );
+    // urls, chat ids and message ids are left alone
+    assert_eq!(
+        policy.scrub("POST https://api.telegram.org/bot/sendMessage in chat -1001234567890"),
+        "POST https://api.telegram.org/bot/sendMessage in chat -1001234567890"
+    );
+    assert_eq!(policy.user_id(12345), "12345");
+
+    let none = RedactionPolicy::none();
+    assert_eq!(
+        none.scrub("+31 6 12345678 /tmp/a/b"),
+        "+31 6 12345678 /tmp/a/b"
+    );
+}
+
+#[test]
+fn masks_user_ids_when_asked() {
+    let policy = RedactionPolicy::none().redact_user_ids(true);
+
+    assert_eq!(policy.user_id(12345), "[user]");
+    assert_eq!(policy.chat_id(12345), "[user]");
+    assert_eq!(policy.chat_id(-100123), "-100123");
+
+    redaction::set_policy(RedactionPolicy::all());
+    assert_eq!(redaction::policy(), RedactionPolicy::all());
+    redaction::set_policy(RedactionPolicy::default());
+    assert_eq!(redaction::policy(), RedactionPolicy::default());
+}
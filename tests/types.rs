@@ -1,4 +1,95 @@
-use telexide::model::{Chat, Message, MessageContent, User};
+use std::convert::TryFrom;
+use telexide::{
+    api::{
+        types::{
+            AddStickerToSet,
+            AnswerPreCheckoutQuery,
+            AnswerShippingQuery,
+            BotCommandScope,
+            CopyMessages,
+            CreateNewStickerSet,
+            DeleteMessages,
+            DeleteMyCommands,
+            DeleteWebhook,
+            EditMessageLiveLocation,
+            EditMessageMedia,
+            ForwardMessages,
+            GetCustomEmojiStickers,
+            GetUpdates,
+            GetGameHighScores,
+            GetWebhookInfo,
+            InputFile,
+            InputMedia,
+            InputMediaPhoto,
+            InputSticker,
+            PassportElementError,
+            PinChatMessage,
+            PromoteChatMember,
+            RestrictChatMember,
+            SendDice,
+            SendDocument,
+            SendGame,
+            SendLocation,
+            SendInvoice,
+            SendMediaGroup,
+            SendMessage,
+            SendPoll,
+            SendVideo,
+            SendVideoNote,
+            SendVoice,
+            SetChatAdministratorCustomTitle,
+            SetGameScore,
+            SetPassportDataErrors,
+            SetStickerEmojiList,
+            SetStickerKeywords,
+            StopMessageLiveLocation,
+            UploadStickerFile,
+            StopPoll,
+            UnpinChatMessage,
+            UpdateType,
+        },
+        APIExt,
+        ApiSchema,
+        BotCapability,
+        Request,
+        Response,
+        TelegramResponse,
+        API,
+    },
+    helpers::formatting::{bold, compose, text},
+    model::{
+        CallbackQuery,
+        Chat,
+        ChatMember,
+        ChatPermissions,
+        Dice,
+        DiceEmoji,
+        EncryptedPassportElement,
+        LabeledPrice,
+        Message,
+        MessageContent,
+        MessageId,
+        PaidMedia,
+        PreCheckoutQuery,
+        RevenueWithdrawalState,
+        ShippingOption,
+        ShippingQuery,
+        SlotSymbol,
+        StarTransactions,
+        StickerFormat,
+        StickerSet,
+        StickerType,
+        Story,
+        StringIds,
+        TelegramPassportElement,
+        TransactionPartner,
+        Update,
+        UpdateContent,
+        User,
+        WebhookInfo,
+    },
+    test::MockAPI,
+};
 
 #[test]
 fn decode_user() -> serde_json::Result<()> {
@@ -53,3 +144,1316 @@ fn decode_message() -> serde_json::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn string_ids_roundtrip() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 16373892,
+            "date": 1585772722,
+            "chat": {
+                "id": -1001234567890123,
+                "type": "supergroup",
+                "title": "test"
+            },
+            "from": {
+                "id": 4503599627370495,
+                "is_bot": false,
+                "first_name": "test"
+            },
+            "text": "just testing"
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+
+    let as_strings = serde_json::to_value(StringIds(&m))?;
+    assert_eq!(as_strings["chat"]["id"], "-1001234567890123");
+    assert_eq!(as_strings["from"]["id"], "4503599627370495");
+
+    let as_numbers = serde_json::to_value(&m)?;
+    assert_eq!(as_numbers["chat"]["id"], -1_001_234_567_890_123_i64);
+
+    let chat: Chat = serde_json::from_value(as_strings["chat"].clone())?;
+    assert_eq!(chat, m.chat);
+    let user: User = serde_json::from_value(as_strings["from"].clone())?;
+    assert_eq!(Some(user), m.from);
+    Ok(())
+}
+
+#[test]
+fn bot_command_scope_serialization() -> serde_json::Result<()> {
+    let data = DeleteMyCommands {
+        scope: Some(BotCommandScope::ChatMember {
+            chat_id: -1001,
+            user_id: 5,
+        }),
+        language_code: Some("en".to_owned()),
+    };
+
+    assert_eq!(
+        serde_json::to_value(data)?,
+        serde_json::json!({
+            "scope": {"type": "chat_member", "chat_id": -1001, "user_id": 5},
+            "language_code": "en",
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(BotCommandScope::AllPrivateChats)?,
+        serde_json::json!({"type": "all_private_chats"})
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_star_transactions() -> serde_json::Result<()> {
+    let t = r#"{"transactions":[
+        {"id":"a","amount":50,"date":1700000000,"source":{"type":"user","user":{"id":5,"is_bot":false,"first_name":"test"},"invoice_payload":"p"}},
+        {"id":"b","amount":20,"date":1700000100,"receiver":{"type":"fragment","withdrawal_state":{"type":"succeeded","date":1700000200,"url":"https://fragment.com"}}},
+        {"id":"c","amount":10,"date":1700000300,"source":{"type":"affiliate_program","commission_per_mille":100}}
+    ]}"#;
+    let res: StarTransactions = serde_json::from_str(t)?;
+
+    assert_eq!(res.transactions.len(), 3);
+    assert!(matches!(
+        res.transactions[0].source,
+        Some(TransactionPartner::User { ref invoice_payload, .. })
+            if invoice_payload.as_deref() == Some("p")
+    ));
+    assert!(matches!(
+        res.transactions[1].receiver,
+        Some(TransactionPartner::Fragment {
+            withdrawal_state: Some(RevenueWithdrawalState::Succeeded { .. }),
+        })
+    ));
+    assert_eq!(
+        res.transactions[2].source,
+        Some(TransactionPartner::Unknown)
+    );
+    Ok(())
+}
+
+#[test]
+fn bot_capabilities_from_get_me() -> serde_json::Result<()> {
+    let me: User = serde_json::from_str(
+        r#"{"id":1,"is_bot":true,"first_name":"bot","can_join_groups":true,"supports_inline_queries":false}"#,
+    )?;
+
+    assert!(BotCapability::JoinGroups.is_supported_by(&me));
+    assert!(!BotCapability::InlineQueries.is_supported_by(&me));
+    assert!(!BotCapability::ReadAllGroupMessages.is_supported_by(&me));
+    assert!(BotCapability::InlineQueries
+        .botfather_hint()
+        .contains("/setinline"));
+    Ok(())
+}
+
+#[test]
+fn decode_paid_media_message() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 17,
+            "date": 1585772722,
+            "chat": {
+                "id": -100123,
+                "type": "channel",
+                "title": "test"
+            },
+            "paid_media": {
+                "star_count": 25,
+                "paid_media": [{"type": "preview", "width": 640, "height": 480}]
+            },
+            "caption": "pay up"
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+
+    assert_eq!(m.get_text(), Some("pay up".to_owned()));
+    if let MessageContent::PaidMedia {
+        content, ..
+    } = m.content
+    {
+        assert_eq!(content.star_count, 25);
+        assert!(matches!(
+            content.paid_media[0],
+            PaidMedia::Preview {
+                width: Some(640),
+                ..
+            }
+        ));
+    } else {
+        panic!("no paid media")
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_business_connection_update() -> serde_json::Result<()> {
+    let t = r#"{
+            "update_id": 42,
+            "business_connection": {
+                "id": "conn-1",
+                "user": {"id": 5, "is_bot": false, "first_name": "owner"},
+                "user_chat_id": 5,
+                "date": 1700000000,
+                "can_reply": true,
+                "is_enabled": true
+            }
+        }"#;
+
+    let u: Update = serde_json::from_str(t)?;
+
+    assert_eq!(u.get_user().map(|u| u.id), Some(5));
+    if let UpdateContent::BusinessConnection(c) = u.content {
+        assert_eq!(c.id, "conn-1");
+        assert!(c.can_reply);
+    } else {
+        panic!("no business connection")
+    }
+
+    let mut msg = SendMessage::new(5, "hi");
+    assert!(serde_json::to_value(&msg)?
+        .get("business_connection_id")
+        .is_none());
+    msg.set_business_connection_id("conn-1");
+    assert_eq!(
+        serde_json::to_value(&msg)?["business_connection_id"],
+        "conn-1"
+    );
+    Ok(())
+}
+
+#[test]
+fn bulk_message_payloads() -> serde_json::Result<()> {
+    let mut data = CopyMessages::new(1, 2, vec![10, 11, 12]);
+    data.toggle_remove_caption();
+
+    assert_eq!(
+        serde_json::to_value(data)?,
+        serde_json::json!({
+            "chat_id": 1,
+            "from_chat_id": 2,
+            "message_ids": [10, 11, 12],
+            "disable_notification": false,
+            "protect_content": false,
+            "remove_caption": true,
+        })
+    );
+
+    let ids: Vec<MessageId> = serde_json::from_str(r#"[{"message_id": 20}, {"message_id": 21}]"#)?;
+    assert_eq!(ids[1].message_id, 21);
+    Ok(())
+}
+
+#[test]
+fn bulk_message_payloads_need_1_to_100_ids() {
+    let too_many: Vec<i64> = (0..101).collect();
+    assert!(CopyMessages::new(1, 2, vec![3]).validate().is_ok());
+    assert!(CopyMessages::new(1, 2, Vec::new()).validate().is_err());
+    assert!(ForwardMessages::new(1, 2, too_many.clone())
+        .validate()
+        .is_err());
+    assert!(DeleteMessages::new(1, too_many).validate().is_err());
+}
+
+fn method_of<R: Request>(_: &R) -> &'static str {
+    R::METHOD
+}
+
+#[test]
+fn requests_are_paired_with_methods() -> serde_json::Result<()> {
+    assert_eq!(method_of(&SendMessage::new(1, "hi")), "sendMessage");
+    assert_eq!(method_of(&CopyMessages::new(1, 2, vec![3])), "copyMessages");
+
+    let sent: <SendMessage as Request>::Response = serde_json::from_str(
+        r#"{"message_id": 1, "date": 1585772722, "chat": {"id": 1, "type": "private"}, "text": "hi"}"#,
+    )?;
+    assert_eq!(sent.get_text(), Some("hi".to_owned()));
+    Ok(())
+}
+
+#[test]
+fn pinning_payloads() -> serde_json::Result<()> {
+    let mut pin = PinChatMessage::new(-100, 7);
+    pin.toggle_disable_notification();
+    assert_eq!(
+        serde_json::to_value(pin)?,
+        serde_json::json!({"chat_id": -100, "message_id": 7, "disable_notification": true})
+    );
+
+    assert_eq!(
+        serde_json::to_value(UnpinChatMessage::new(-100))?,
+        serde_json::json!({"chat_id": -100})
+    );
+    Ok(())
+}
+
+#[test]
+fn custom_title_payloads() -> serde_json::Result<()> {
+    assert_eq!(
+        serde_json::to_value(SetChatAdministratorCustomTitle::new(-100, 5, "moderator"))?,
+        serde_json::json!({"chat_id": -100, "user_id": 5, "custom_title": "moderator"})
+    );
+    assert_eq!(
+        SetChatAdministratorCustomTitle::clear(-100, 5).custom_title,
+        ""
+    );
+    assert!(SetChatAdministratorCustomTitle::new(-100, 5, "ünïcödé moderator")
+        .validate()
+        .is_err());
+    assert!(SetChatAdministratorCustomTitle::new(-100, 5, "ünïcödé mod")
+        .validate()
+        .is_ok());
+    Ok(())
+}
+
+#[test]
+fn telegram_response_keeps_envelope() -> telexide::Result<()> {
+    let raw: Response = serde_json::from_str(
+        r#"{"ok": true, "result": true, "description": "Webhook is already deleted"}"#,
+    )?;
+    let res = TelegramResponse::<bool>::try_from(raw)?;
+    assert_eq!(
+        res.description.as_deref(),
+        Some("Webhook is already deleted")
+    );
+    assert!(res.into_result()?);
+
+    let raw: Response = serde_json::from_str(
+        r#"{"ok": false, "error_code": 429, "description": "Too Many Requests", "parameters": {"retry_after": 5}}"#,
+    )?;
+    let res = TelegramResponse::<bool>::try_from(raw)?;
+    assert_eq!(res.error_code, Some(429));
+    assert_eq!(res.parameters.and_then(|p| p.retry_after), Some(5));
+    Ok(())
+}
+
+#[test]
+fn decode_chat_members() -> serde_json::Result<()> {
+    let t = r#"[
+            {"status": "creator", "user": {"id": 1, "is_bot": false, "first_name": "owner"}, "is_anonymous": false},
+            {"status": "administrator", "user": {"id": 2, "is_bot": false, "first_name": "admin"},
+             "can_be_edited": true, "can_manage_chat": true, "can_delete_messages": true,
+             "can_manage_video_chats": true, "can_restrict_members": false, "can_promote_members": false,
+             "can_change_info": true, "can_invite_users": true, "can_pin_messages": true}
+        ]"#;
+
+    let admins: Vec<ChatMember> = serde_json::from_str(t)?;
+
+    assert!(admins.iter().all(ChatMember::is_admin));
+    if let ChatMember::Administrator(a) = &admins[1] {
+        assert!(a.can_delete_messages);
+        assert!(a.can_manage_video_chats);
+        assert!(!a.can_promote_members);
+        assert!(!a.can_post_messages);
+
+        let promote = serde_json::to_value(PromoteChatMember::from_administrator(-100, 9, a))?;
+        assert_eq!(promote["can_manage_video_chats"], true);
+        assert_eq!(promote["can_post_stories"], false);
+    } else {
+        panic!("no administrator")
+    }
+
+    let banned: ChatMember = serde_json::from_str(
+        r#"{"status": "kicked", "user": {"id": 3, "is_bot": false, "first_name": "x"}, "until_date": 0}"#,
+    )?;
+    assert!(!banned.is_member());
+    assert_eq!(banned.get_user().id, 3);
+    Ok(())
+}
+
+#[test]
+fn granular_chat_permissions_roundtrip() -> serde_json::Result<()> {
+    let t = r#"{
+            "status": "restricted",
+            "user": {"id": 3, "is_bot": false, "first_name": "x"},
+            "until_date": 0,
+            "is_member": true,
+            "can_send_messages": true,
+            "can_send_photos": true,
+            "can_send_voice_notes": false,
+            "can_send_polls": true
+        }"#;
+
+    let member: ChatMember = serde_json::from_str(t)?;
+    let permissions = if let ChatMember::Restricted(m) = member {
+        m.permissions()
+    } else {
+        panic!("no restricted member")
+    };
+    assert!(permissions.can_send_photos);
+    assert!(!permissions.can_send_voice_notes);
+    assert!(!permissions.can_send_media());
+
+    let mut data = RestrictChatMember::new(-100, 3, permissions);
+    data.toggle_use_independent_chat_permissions();
+    let value = serde_json::to_value(&data)?;
+    assert_eq!(value["use_independent_chat_permissions"], true);
+    assert_eq!(value["permissions"]["can_send_photos"], true);
+    assert_eq!(value["permissions"]["can_send_videos"], false);
+
+    assert!(ChatPermissions::all().can_send_media());
+    Ok(())
+}
+
+#[test]
+fn promote_chat_member_rights() -> serde_json::Result<()> {
+    let mut data = PromoteChatMember::new(-100, 5);
+    data.can_manage_topics = Some(true);
+    data.can_delete_stories = Some(true);
+    assert_eq!(
+        serde_json::to_value(&data)?,
+        serde_json::json!({
+            "chat_id": -100,
+            "user_id": 5,
+            "can_manage_topics": true,
+            "can_delete_stories": true,
+        })
+    );
+
+    let demote = serde_json::to_value(PromoteChatMember::demote(-100, 5))?;
+    let rights = demote.as_object().into_iter().flatten();
+    for (right, value) in rights.filter(|(k, _)| k.starts_with("can_")) {
+        assert_eq!(value, false, "{right} wasn't revoked");
+    }
+    assert_eq!(demote["can_post_stories"], false);
+    Ok(())
+}
+
+#[test]
+fn decode_webhook_info() -> serde_json::Result<()> {
+    let info: WebhookInfo = serde_json::from_str(
+        r#"{"url":"","has_custom_certificate":false,"pending_update_count":3}"#,
+    )?;
+    assert_eq!(info.pending_update_count, 3);
+    assert!(info.last_error_date.is_none());
+
+    let info: WebhookInfo = serde_json::from_str(
+        r#"{
+            "url": "https://example.com/hook",
+            "has_custom_certificate": false,
+            "pending_update_count": 0,
+            "ip_address": "1.2.3.4",
+            "last_error_date": 1700000000,
+            "last_error_message": "Connection refused",
+            "allowed_updates": ["message", "callback_query"]
+        }"#,
+    )?;
+    assert_eq!(
+        info.last_error_date.map(|d| d.timestamp()),
+        Some(1_700_000_000)
+    );
+    assert_eq!(
+        info.last_error_message.as_deref(),
+        Some("Connection refused")
+    );
+    assert_eq!(info.ip_address.as_deref(), Some("1.2.3.4"));
+    assert_eq!(info.allowed_updates.map(|u| u.len()), Some(2));
+
+    assert_eq!(GetWebhookInfo::METHOD, "getWebhookInfo");
+    assert_eq!(
+        serde_json::to_value(GetWebhookInfo::default())?,
+        serde_json::json!({})
+    );
+    assert_eq!(
+        serde_json::to_value(DeleteWebhook::dropping_pending_updates())?,
+        serde_json::json!({"drop_pending_updates": true})
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn answer_payment_queries() -> telexide::Result<()> {
+    let from = serde_json::json!({"id": 5, "is_bot": false, "first_name": "test"});
+    let shipping: ShippingQuery = serde_json::from_value(serde_json::json!({
+        "id": "ship",
+        "from": from,
+        "invoice_payload": "order-1",
+        "shipping_address": {
+            "country_code": "NL",
+            "state": "",
+            "city": "Utrecht",
+            "street_line1": "Street 1",
+            "street_line2": "",
+            "post_code": "1234AB"
+        }
+    }))?;
+    let checkout: PreCheckoutQuery = serde_json::from_value(serde_json::json!({
+        "id": "checkout",
+        "from": from,
+        "currency": "EUR",
+        "total_amount": 1250,
+        "invoice_payload": "order-1"
+    }))?;
+
+    let api = MockAPI::new();
+    let options = vec![ShippingOption::new(
+        "post",
+        "PostNL",
+        vec![LabeledPrice::new("Shipping", 495)],
+    )];
+    api.answer_shipping_query(AnswerShippingQuery::ok(&shipping, options))
+        .await?;
+    api.answer_shipping_query(AnswerShippingQuery::error(&shipping, "we don't ship there"))
+        .await?;
+    api.answer_pre_checkout_query(AnswerPreCheckoutQuery::ok(&checkout))
+        .await?;
+
+    assert!(api
+        .answer_shipping_query(AnswerShippingQuery::ok(&shipping, Vec::new()))
+        .await
+        .is_err());
+    let mut cancelled = AnswerPreCheckoutQuery::error(&checkout, "sold out");
+    cancelled.error_message = None;
+    assert!(api
+        .answer_pre_checkout_query(cancelled.clone())
+        .await
+        .is_err());
+    assert!(api
+        .send(AnswerShippingQuery::ok(&shipping, Vec::new()))
+        .await
+        .is_err());
+    assert!(api.send(cancelled).await.is_err());
+
+    let calls = api.calls();
+    assert_eq!(calls.len(), 3);
+    assert_eq!(
+        calls[0].data,
+        serde_json::json!({
+            "shipping_query_id": "ship",
+            "ok": true,
+            "shipping_options": [
+                {"id": "post", "title": "PostNL", "prices": [{"label": "Shipping", "amount": 495}]}
+            ]
+        })
+    );
+    assert_eq!(calls[1].data["error_message"], "we don't ship there");
+    assert_eq!(
+        calls[2].data,
+        serde_json::json!({"pre_checkout_query_id": "checkout", "ok": true})
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_invoice_with_tips() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let mut invoice = SendInvoice::new(
+        1,
+        "Coffee",
+        "A cup of coffee",
+        "order-2",
+        "provider",
+        "EUR",
+        vec![LabeledPrice::new("Coffee", 250)],
+    );
+    invoice
+        .set_tips(300, vec![50, 100, 200])
+        .set_photo("https://example.com/coffee.png", 512, 512)
+        .set_start_parameter("coffee")
+        .toggle_need_email()
+        .toggle_send_email_to_provider()
+        .toggle_is_flexible();
+    api.send_invoice(invoice.clone()).await?;
+
+    let data = &api.calls()[0].data;
+    assert_eq!(data["provider_token"], "provider");
+    assert_eq!(data["currency"], "EUR");
+    assert_eq!(data["max_tip_amount"], 300);
+    assert_eq!(data["suggested_tip_amounts"], serde_json::json!([50, 100, 200]));
+    assert_eq!(data["photo_width"], 512);
+    assert_eq!(data["need_email"], true);
+    assert_eq!(data["send_email_to_provider"], true);
+    assert_eq!(data["is_flexible"], true);
+    assert_eq!(data["need_name"], false);
+
+    for tips in [vec![100, 50], vec![50, 400], vec![10, 20, 30, 40, 50]] {
+        invoice.set_tips(300, tips);
+        assert!(api.send_invoice(invoice.clone()).await.is_err());
+        assert!(api.send(invoice.clone()).await.is_err());
+    }
+    assert_eq!(api.calls().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn bundled_schema_knows_all_sent_fields() -> telexide::Result<()> {
+    let schema = ApiSchema::bundled();
+    assert_eq!(schema.self_check()?, Vec::new());
+    assert!(schema.has_method("answerPreCheckoutQuery"));
+
+    let older = ApiSchema::from_json(
+        r#"{"version": "6.0", "methods": {"sendMessage": ["chat_id", "text", "parse_mode"]}}"#,
+    )?;
+    let mut data = SendMessage::new(1, "hi");
+    data.set_business_connection_id("connection");
+    let mismatches = older.check(&data)?;
+    let fields: Vec<_> = mismatches
+        .iter()
+        .filter_map(|m| m.field.as_deref())
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "allow_sending_without_reply",
+            "business_connection_id",
+            "disable_notification",
+            "disable_web_page_preview",
+            "protect_content"
+        ]
+    );
+    assert_eq!(
+        mismatches[1].to_string(),
+        "sendMessage sends the field business_connection_id, which Bot API 6.0 doesn't know"
+    );
+
+    let unknown = older.check(&DeleteWebhook::new())?;
+    assert_eq!(unknown.len(), 1);
+    assert_eq!(unknown[0].field, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_quiz_and_stop_poll() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let options = vec!["3".to_owned(), "4".to_owned(), "5".to_owned()];
+    let mut quiz = SendPoll::quiz(-100, "2 + 2?", options.clone(), 1);
+    quiz.set_anonymous(false)
+        .set_formatted_explanation(&compose([text("it's "), bold("4")]))
+        .set_close_date(chrono::Utc::now())
+        .set_open_period(30);
+    api.send_poll(quiz.clone()).await?;
+
+    let data = &api.calls()[0].data;
+    assert_eq!(data["type"], "quiz");
+    assert_eq!(data["correct_option_id"], 1);
+    assert_eq!(data["is_anonymous"], false);
+    assert_eq!(data["explanation"], "it's 4");
+    assert_eq!(
+        data["explanation_entities"],
+        serde_json::json!([{"type": "bold", "offset": 5, "length": 1}])
+    );
+    assert_eq!(data["open_period"], 30);
+    assert!(data.get("close_date").is_none());
+
+    quiz.correct_option_id = Some(3);
+    assert!(api.send_poll(quiz).await.is_err());
+    assert!(api
+        .send_poll(SendPoll::new(-100, "?", vec!["only".to_owned()]))
+        .await
+        .is_err());
+    let mut both = SendPoll::new(-100, "?", options);
+    both.set_open_period(30);
+    both.close_date = Some(chrono::Utc::now());
+    assert!(api.send_poll(both.clone()).await.is_err());
+    assert!(api.send(both).await.is_err());
+    assert_eq!(api.calls().len(), 1);
+
+    api.respond_with(
+        "stopPoll",
+        serde_json::json!({
+            "id": "poll",
+            "question": "2 + 2?",
+            "options": [{"text": "3", "voter_count": 0}, {"text": "4", "voter_count": 2}],
+            "total_voter_count": 2,
+            "is_closed": true,
+            "type": "quiz",
+            "correct_option_id": 1
+        }),
+    );
+    let poll = api.stop_poll(StopPoll::new(-100, 7)).await?;
+    assert!(poll.is_closed);
+    assert_eq!(poll.total_voter_count, 2);
+    assert_eq!(api.calls()[1].data, serde_json::json!({"chat_id": -100, "message_id": 7}));
+    Ok(())
+}
+
+#[test]
+fn dice_values_per_emoji() -> telexide::Result<()> {
+    let dice = |emoji: DiceEmoji, value: u8| Dice {
+        emoji: emoji.as_str().to_owned(),
+        value,
+    };
+
+    assert_eq!(
+        dice(DiceEmoji::SlotMachine, 1).slot_reels(),
+        Some([SlotSymbol::Bar; 3])
+    );
+    assert_eq!(
+        dice(DiceEmoji::SlotMachine, 2).slot_reels(),
+        Some([SlotSymbol::Grapes, SlotSymbol::Bar, SlotSymbol::Bar])
+    );
+    for value in [1, 22, 43, 64] {
+        assert!(dice(DiceEmoji::SlotMachine, value).is_win());
+    }
+    assert!(!dice(DiceEmoji::SlotMachine, 2).is_win());
+    assert!(dice(DiceEmoji::SlotMachine, 64).is_jackpot());
+    assert!(!dice(DiceEmoji::SlotMachine, 43).is_jackpot());
+
+    assert!(dice(DiceEmoji::Basketball, 4).is_score());
+    assert!(!dice(DiceEmoji::Basketball, 3).is_score());
+    assert!(dice(DiceEmoji::Football, 3).is_win());
+    assert!(dice(DiceEmoji::Darts, 6).is_win());
+    assert!(!dice(DiceEmoji::Bowling, 5).is_win());
+    assert_eq!(dice(DiceEmoji::Dice, 3).slot_reels(), None);
+
+    let unknown = Dice {
+        emoji: "🪀".to_owned(),
+        value: 1,
+    };
+    assert_eq!(unknown.kind(), None);
+    assert!(!unknown.is_win());
+
+    let send = serde_json::to_value(SendDice::new(1, DiceEmoji::Bowling))?;
+    assert_eq!(send["emoji"], "🎳");
+    Ok(())
+}
+
+#[tokio::test]
+async fn game_score_targets() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let query: CallbackQuery = serde_json::from_value(serde_json::json!({
+        "id": "1",
+        "from": {"id": 7, "is_bot": false, "first_name": "player"},
+        "inline_message_id": "inline",
+        "chat_instance": "instance",
+        "game_short_name": "game"
+    }))?;
+    let mut score = SetGameScore::for_callback_query(&query, 120).unwrap();
+    score.toggle_force();
+    api.set_game_score(score).await?;
+
+    let data = &api.calls()[0].data;
+    assert_eq!(data["user_id"], 7);
+    assert_eq!(data["score"], 120);
+    assert_eq!(data["force"], true);
+    assert_eq!(data["inline_message_id"], "inline");
+    assert!(data.get("chat_id").is_none());
+
+    let mut both = SetGameScore::new(-100, 5, 7, 1);
+    both.inline_message_id = Some("inline".to_owned());
+    assert!(api.set_game_score(both.clone()).await.is_err());
+    assert!(api.send(both).await.is_err());
+    let mut neither = GetGameHighScores::inline("inline", 7);
+    neither.inline_message_id = None;
+    assert!(api.get_game_high_scores(neither.clone()).await.is_err());
+    assert!(api.send(neither).await.is_err());
+    assert_eq!(api.calls().len(), 1);
+
+    api.respond_with(
+        "getGameHighScores",
+        serde_json::json!([
+            {"position": 1, "user": {"id": 8, "is_bot": false, "first_name": "best"}, "score": 300},
+            {"position": 2, "user": {"id": 7, "is_bot": false, "first_name": "player"}, "score": 120}
+        ]),
+    );
+    let scores = api
+        .get_game_high_scores(GetGameHighScores::new(-100, 5, 7))
+        .await?;
+    assert_eq!(scores.len(), 2);
+    assert_eq!(scores[1].user.id, 7);
+    assert_eq!(scores[1].score, 120);
+    assert_eq!(api.calls()[1].data["chat_id"], -100);
+    Ok(())
+}
+
+#[tokio::test]
+async fn set_passport_data_errors_checks_sections() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let element: EncryptedPassportElement = serde_json::from_value(serde_json::json!({
+        "type": "utility_bill",
+        "files": [{"file_id": "f", "file_unique_id": "u", "file_size": 1, "file_date": 0}],
+        "hash": "element-hash"
+    }))?;
+    assert!(element.front_side.is_none());
+
+    let mut data = SetPassportDataErrors::new(7, Vec::new());
+    data.add_error(PassportElementError::reverse_side(
+        TelegramPassportElement::DriverLicense,
+        "file-hash",
+        "the scan is blurry",
+    ))
+    .add_error(PassportElementError::unspecified(
+        &element,
+        "the bill is too old",
+    ));
+    api.set_passport_data_errors(data).await?;
+
+    let errors = &api.calls()[0].data["errors"];
+    assert_eq!(errors[0]["source"], "reverse_side");
+    assert_eq!(errors[0]["type"], "driver_license");
+    assert_eq!(errors[0]["file_hash"], "file-hash");
+    assert_eq!(errors[1]["source"], "unspecified");
+    assert_eq!(errors[1]["type"], "utility_bill");
+    assert_eq!(errors[1]["element_hash"], "element-hash");
+
+    let passport = PassportElementError::reverse_side(TelegramPassportElement::Passport, "h", "m");
+    assert!(!passport.is_valid());
+    assert!(api
+        .set_passport_data_errors(SetPassportDataErrors::new(7, vec![passport.clone()]))
+        .await
+        .is_err());
+    assert!(api
+        .send(SetPassportDataErrors::new(7, vec![passport]))
+        .await
+        .is_err());
+    assert!(api
+        .set_passport_data_errors(SetPassportDataErrors::new(7, Vec::new()))
+        .await
+        .is_err());
+    assert_eq!(api.calls().len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_sticker_set_with_uploads() -> telexide::Result<()> {
+    let dir = std::env::temp_dir().join(format!("telexide_stickers_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("cat.webp"), b"webp")?;
+    std::fs::write(dir.join("dog.tgs"), b"tgs")?;
+
+    let api = MockAPI::new();
+    let mut cat = InputSticker::from_path(dir.join("cat.webp"), &["🐱"])?;
+    cat.set_keywords(&["cat", "meow"]);
+    let dog = InputSticker::from_path(dir.join("dog.tgs"), &["🐶", "🦴"])?;
+    assert_eq!(dog.format, StickerFormat::Animated);
+    let reused = InputSticker::new("file-id".into(), StickerFormat::Static, &["🐭"]);
+
+    let stickers = vec![cat.clone(), dog, reused];
+    let mut set = CreateNewStickerSet::new(7, "pets_by_test_bot", "Pets", stickers);
+    set.set_sticker_type(StickerType::Regular);
+    api.create_new_sticker_set(set).await?;
+
+    let call = &api.calls()[0];
+    assert_eq!(call.method, "createNewStickerSet");
+    assert_eq!(call.files, vec!["cat.webp", "dog.tgs"]);
+    let stickers = &call.data["stickers"];
+    assert_eq!(stickers[0]["sticker"], "attach://cat.webp");
+    assert_eq!(stickers[0]["format"], "static");
+    assert_eq!(stickers[0]["keywords"], serde_json::json!(["cat", "meow"]));
+    assert_eq!(stickers[1]["format"], "animated");
+    assert_eq!(stickers[1]["emoji_list"], serde_json::json!(["🐶", "🦴"]));
+    assert_eq!(stickers[2]["sticker"], "file-id");
+    assert_eq!(call.data["sticker_type"], "regular");
+
+    let stickers = vec![cat.clone(), cat.clone()];
+    let twice = CreateNewStickerSet::new(7, "pets_by_test_bot", "Pets", stickers);
+    assert!(api.create_new_sticker_set(twice).await.is_err());
+    let mut no_emoji = cat.clone();
+    no_emoji.emoji_list.clear();
+    assert!(api
+        .add_sticker_to_set(AddStickerToSet::new(7, "pets_by_test_bot", no_emoji))
+        .await
+        .is_err());
+    assert!(api
+        .set_sticker_keywords(SetStickerKeywords {
+            sticker: "file-id".to_owned(),
+            keywords: Some(vec!["a".repeat(65)]),
+        })
+        .await
+        .is_err());
+    assert!(api
+        .send(SetStickerEmojiList {
+            sticker: "file-id".to_owned(),
+            emoji_list: Vec::new(),
+        })
+        .await
+        .is_err());
+    assert!(InputSticker::from_path(dir.join("notes.txt"), &["📝"]).is_err());
+    assert_eq!(api.calls().len(), 1);
+
+    api.add_sticker_to_set(AddStickerToSet::new(7, "pets_by_test_bot", cat))
+        .await?;
+    let added = &api.calls()[1].data["sticker"];
+    assert_eq!(added["emoji_list"], serde_json::json!(["🐱"]));
+    assert_eq!(api.calls()[1].files, vec!["cat.webp"]);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn sticker_set_decodes_current_fields() -> telexide::Result<()> {
+    let set: StickerSet = serde_json::from_value(serde_json::json!({
+        "name": "emoji_by_test_bot",
+        "title": "Emoji",
+        "type": "custom_emoji",
+        "stickers": [{
+            "file_id": "f",
+            "file_unique_id": "u",
+            "type": "custom_emoji",
+            "width": 100,
+            "height": 100,
+            "is_animated": false,
+            "is_video": true,
+            "thumbnail": {"file_id": "t", "file_unique_id": "tu", "width": 100, "height": 100},
+            "needs_repainting": true
+        }]
+    }))?;
+    assert_eq!(set.sticker_type, StickerType::CustomEmoji);
+    assert!(set.stickers[0].is_video);
+    assert!(set.stickers[0].needs_repainting);
+    assert!(set.stickers[0].thumbnail.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn resolve_custom_emoji_in_message() -> telexide::Result<()> {
+    let message: Message = serde_json::from_value(serde_json::json!({
+        "message_id": 1,
+        "date": 1700000000,
+        "chat": {"id": 7, "type": "private", "first_name": "test"},
+        "text": "👍 and 👍",
+        "entities": [
+            {"type": "custom_emoji", "offset": 0, "length": 2, "custom_emoji_id": "123"},
+            {"type": "custom_emoji", "offset": 7, "length": 2, "custom_emoji_id": "123"}
+        ]
+    }))?;
+    let data = GetCustomEmojiStickers::from_entities(&message.get_entities().unwrap());
+    assert_eq!(data.custom_emoji_ids, vec!["123".to_owned()]);
+
+    let api = MockAPI::new();
+    api.respond_with(
+        "getCustomEmojiStickers",
+        serde_json::json!([{
+            "file_id": "f",
+            "file_unique_id": "u",
+            "type": "custom_emoji",
+            "width": 100,
+            "height": 100,
+            "emoji": "👍",
+            "custom_emoji_id": "123"
+        }]),
+    );
+    let stickers = api.get_custom_emoji_stickers(data).await?;
+    assert_eq!(stickers[0].custom_emoji_id.as_deref(), Some("123"));
+
+    let too_many = GetCustomEmojiStickers::new((0..201).map(|i| i.to_string()).collect());
+    assert!(api.get_custom_emoji_stickers(too_many).await.is_err());
+    assert_eq!(api.calls_to("getCustomEmojiStickers").len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn upload_sticker_file_sends_the_file() -> telexide::Result<()> {
+    let dir = std::env::temp_dir().join(format!("telexide_upload_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("wave.webm"), b"webm")?;
+
+    let api = MockAPI::new();
+    api.respond_with(
+        "uploadStickerFile",
+        serde_json::json!({"file_id": "uploaded", "file_unique_id": "u", "file_size": 4}),
+    );
+    let data = UploadStickerFile::from_path(7, dir.join("wave.webm"))?;
+    assert_eq!(data.sticker_format, StickerFormat::Video);
+    let file = api.upload_sticker_file(data).await?;
+    assert_eq!(file.file_id, "uploaded");
+
+    let call = &api.calls()[0];
+    assert_eq!(call.files, vec!["wave.webm"]);
+    assert_eq!(call.data["sticker"], "attach://wave.webm");
+    assert_eq!(call.data["sticker_format"], "video");
+
+    let by_id = UploadStickerFile::new(7, "file-id".into(), StickerFormat::Static);
+    assert!(api.upload_sticker_file(by_id).await.is_err());
+    assert!(UploadStickerFile::from_path(7, dir.join("notes.txt")).is_err());
+    assert_eq!(api.calls().len(), 1);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn edit_message_media_uploads_new_files() -> telexide::Result<()> {
+    let dir = std::env::temp_dir().join(format!("telexide_edit_media_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("chart.png"), b"png")?;
+
+    let api = MockAPI::new();
+    let mut photo = InputMediaPhoto::new(InputFile::from_path(dir.join("chart.png"))?);
+    photo.caption = Some("today".to_owned());
+    let media = InputMedia::Photo(photo);
+    api.edit_message_media(EditMessageMedia::new(-100, 5, media.clone()))
+        .await?;
+
+    let call = &api.calls()[0];
+    assert_eq!(call.method, "editMessageMedia");
+    assert_eq!(call.files, vec!["chart.png"]);
+    assert_eq!(call.data["media"]["type"], "photo");
+    assert_eq!(call.data["media"]["media"], "attach://chart.png");
+
+    let by_id = InputMedia::Photo(InputMediaPhoto::new("file-id".into()));
+    api.edit_message_media(EditMessageMedia::new(-100, 5, by_id))
+        .await?;
+    assert!(api.calls()[1].files.is_empty());
+
+    assert!(api
+        .edit_message_media(EditMessageMedia::inline("inline", media))
+        .await
+        .is_err());
+    assert_eq!(api.calls().len(), 2);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn methods_list_every_implemented_method() {
+    let methods = telexide::api::methods();
+    let names: Vec<&str> = methods.iter().map(|m| m.method).collect();
+    let mut unique = names.clone();
+    unique.dedup();
+    assert_eq!(names, unique);
+
+    let send_message = methods.iter().find(|m| m.method == "sendMessage").unwrap();
+    assert_eq!(send_message.payload, Some("SendMessage"));
+    assert_eq!(send_message.response, "Message");
+    assert!(!send_message.uploads_files);
+
+    let updates = methods.iter().find(|m| m.method == "getUpdates").unwrap();
+    assert_eq!(updates.response, "Vec<Update>");
+    assert_eq!(updates.function(), "get_updates");
+
+    let photo = methods.iter().find(|m| m.method == "sendPhoto").unwrap();
+    assert!(photo.uploads_files);
+
+    let get_me = methods.iter().find(|m| m.method == "getMe").unwrap();
+    assert_eq!(get_me.payload, None);
+    assert_eq!(
+        serde_json::to_value(get_me).unwrap(),
+        serde_json::json!({
+            "method": "getMe",
+            "payload": null,
+            "response": "User",
+            "uploads_files": false,
+        })
+    );
+}
+
+#[tokio::test]
+async fn live_locations_send_heading_and_accuracy() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let mut location = SendLocation::new(5, 52.37, 4.89);
+    location
+        .set_live_period(3600)
+        .set_horizontal_accuracy(12.5)
+        .set_heading(90)
+        .set_proximity_alert_radius(200);
+    api.send_location(location.clone()).await?;
+
+    let call = &api.calls()[0];
+    assert_eq!(call.method, "sendLocation");
+    assert_eq!(call.data["horizontal_accuracy"], 12.5);
+    assert_eq!(call.data["heading"], 90);
+    assert_eq!(call.data["proximity_alert_radius"], 200);
+    assert_eq!(call.data["live_period"], 3600);
+
+    for invalid in [
+        location.clone().set_heading(0).clone(),
+        location.clone().set_horizontal_accuracy(2000.0).clone(),
+        location.clone().set_proximity_alert_radius(0).clone(),
+        location.clone().set_live_period(30).clone(),
+    ] {
+        assert!(api.send_location(invalid.clone()).await.is_err());
+        assert!(api.send(invalid).await.is_err());
+    }
+    assert_eq!(api.calls().len(), 1);
+
+    let message: Message = serde_json::from_value(serde_json::json!({
+        "message_id": 1,
+        "date": 1_600_000_000,
+        "chat": {"id": 5, "type": "private", "first_name": "courier"},
+        "location": {
+            "latitude": 52.37,
+            "longitude": 4.89,
+            "horizontal_accuracy": 12.5,
+            "live_period": 3600,
+            "heading": 90,
+        },
+    }))?;
+    match message.content {
+        MessageContent::Location {
+            content,
+        } => {
+            assert_eq!(content.heading, Some(90));
+            assert_eq!(content.horizontal_accuracy, Some(12.5));
+        },
+        other => panic!("expected a location, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn live_locations_can_be_updated_and_stopped() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let mut update = EditMessageLiveLocation::new(5, 9, 52.38, 4.9);
+    update.set_heading(180).set_horizontal_accuracy(8.0);
+    api.edit_message_live_location(update.clone()).await?;
+    api.edit_message_live_location(EditMessageLiveLocation::inline("inline", 52.38, 4.9))
+        .await?;
+    api.stop_message_live_location(StopMessageLiveLocation::new(5, 9))
+        .await?;
+    api.stop_message_live_location(StopMessageLiveLocation::inline("inline"))
+        .await?;
+
+    let calls = api.calls();
+    assert_eq!(calls[0].method, "editMessageLiveLocation");
+    assert_eq!(
+        calls[0].data,
+        serde_json::json!({
+            "chat_id": 5,
+            "message_id": 9,
+            "latitude": 52.38,
+            "longitude": 4.9,
+            "heading": 180,
+            "horizontal_accuracy": 8.0,
+        })
+    );
+    assert_eq!(calls[1].data["inline_message_id"], "inline");
+    assert!(calls[1].data.get("chat_id").is_none());
+    assert_eq!(calls[2].method, "stopMessageLiveLocation");
+    assert_eq!(calls[2].data, serde_json::json!({"chat_id": 5, "message_id": 9}));
+    assert_eq!(calls[3].data, serde_json::json!({"inline_message_id": "inline"}));
+
+    let mut untargeted = StopMessageLiveLocation::new(5, 9);
+    untargeted.inline_message_id = Some("inline".to_owned());
+    assert!(api.stop_message_live_location(untargeted.clone()).await.is_err());
+    assert!(api.send(untargeted).await.is_err());
+    assert!(api
+        .edit_message_live_location(update.set_heading(400).clone())
+        .await
+        .is_err());
+    assert!(api.send(update).await.is_err());
+    assert_eq!(api.calls().len(), 4);
+    Ok(())
+}
+
+#[tokio::test]
+async fn media_uploads_go_to_their_own_endpoints() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let mut note = SendVideoNote::new(5, String::new());
+    note.video_note = InputFile::from_bytes(b"note".to_vec(), "clip.mp4")?;
+    note.set_thumb(InputFile::from_bytes(b"thumb".to_vec(), "clip.mp4")?)
+        .set_duration(12)
+        .set_length(240);
+    api.send_video_note(note).await?;
+
+    let mut voice = SendVoice::new(5, String::new());
+    voice.voice = InputFile::from_bytes(b"ogg".to_vec(), "hello.ogg")?;
+    voice.set_duration(3);
+    api.send_voice(voice).await?;
+
+    let mut video = SendVideo::new(5, "file-id".to_owned());
+    video.set_dimensions(1280, 720).toggle_supports_streaming();
+    api.send_video(video).await?;
+
+    let calls = api.calls();
+    assert_eq!(calls[0].method, "sendVideoNote");
+    assert_eq!(calls[0].files, vec!["clip.mp4", "1_clip.mp4"]);
+    assert_eq!(calls[0].data["video_note"], "attach://clip.mp4");
+    assert_eq!(calls[0].data["thumb"], "attach://1_clip.mp4");
+    assert_eq!(calls[0].data["length"], 240);
+    assert_eq!(calls[1].method, "sendVoice");
+    assert_eq!(calls[1].files, vec!["hello.ogg"]);
+    assert_eq!(calls[1].data["duration"], 3);
+    assert_eq!(calls[2].method, "sendVideo");
+    assert!(calls[2].files.is_empty());
+    assert_eq!(calls[2].data["width"], 1280);
+    assert_eq!(calls[2].data["supports_streaming"], true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn input_files_are_sent_as_json_or_uploaded() -> telexide::Result<()> {
+    assert_eq!(
+        InputFile::from("https://example.com/cat.jpg"),
+        InputFile::Url("https://example.com/cat.jpg".to_owned())
+    );
+    assert_eq!(
+        InputFile::from("AgACAgIAAxk"),
+        InputFile::FileId("AgACAgIAAxk".to_owned())
+    );
+    let parsed: InputFile = serde_json::from_value(serde_json::json!("http://example.com/a.png"))?;
+    assert!(parsed.upload().is_none());
+    assert_eq!(serde_json::to_value(&parsed)?, "http://example.com/a.png");
+
+    let api = MockAPI::new();
+    api.send_document(SendDocument::new(5, "https://example.com/a.pdf".to_owned()))
+        .await?;
+    let mut streamed = SendDocument::new(5, String::new());
+    streamed.document = InputFile::from_reader(&b"%PDF"[..], "report.pdf")?;
+    api.send_document(streamed).await?;
+
+    let calls = api.calls();
+    assert!(calls[0].files.is_empty());
+    assert_eq!(calls[0].data["document"], "https://example.com/a.pdf");
+    assert_eq!(calls[1].files, vec!["report.pdf"]);
+    assert_eq!(calls[1].data["document"], "attach://report.pdf");
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_media_group_attaches_local_files() -> telexide::Result<()> {
+    let dir = std::env::temp_dir().join(format!("telexide-album-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("image.jpg"), b"first")?;
+
+    let api = MockAPI::new();
+    let from_disk = InputFile::from_path(dir.join("image.jpg"))?;
+    let media = vec![
+        InputMedia::Photo(InputMediaPhoto::new(from_disk.clone())),
+        InputMedia::Photo(InputMediaPhoto::new(InputFile::from_bytes(
+            b"second".to_vec(),
+            "image.jpg",
+        )?)),
+        InputMedia::Photo(InputMediaPhoto::new(from_disk)),
+        InputMedia::Photo(InputMediaPhoto::new("file-id".into())),
+    ];
+    api.send_media_group(SendMediaGroup::new(-100, media)).await?;
+
+    let call = &api.calls()[0];
+    assert_eq!(call.method, "sendMediaGroup");
+    assert_eq!(call.files, vec!["image.jpg", "1_image.jpg"]);
+    let attached: Vec<&str> = call.data["media"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["media"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        attached,
+        vec![
+            "attach://image.jpg",
+            "attach://1_image.jpg",
+            "attach://image.jpg",
+            "file-id"
+        ]
+    );
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn protect_content_is_sent_and_parsed() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let mut data = SendMessage::new(-100, "secret");
+    data.toggle_protect_content();
+    api.send_message(data).await?;
+    assert_eq!(api.calls()[0].data["protect_content"], true);
+
+    let message: Message = serde_json::from_value(serde_json::json!({
+        "message_id": 1,
+        "date": 0,
+        "chat": {"id": -100, "type": "supergroup", "title": "test"},
+        "text": "secret",
+        "has_protected_content": true,
+    }))?;
+    assert!(message.has_protected_content);
+
+    let chat: Chat = serde_json::from_value(serde_json::json!({
+        "id": -100,
+        "type": "channel",
+        "title": "test",
+        "has_protected_content": true,
+    }))?;
+    let Chat::Channel(channel) = chat else {
+        panic!("not a channel")
+    };
+    assert_eq!(channel.has_protected_content, Some(true));
+    Ok(())
+}
+
+#[test]
+fn story_messages_and_replies_round_trip() -> telexide::Result<()> {
+    let channel = serde_json::json!({"id": -100, "type": "channel", "title": "stories"});
+    let forwarded: Message = serde_json::from_value(serde_json::json!({
+        "message_id": 1,
+        "date": 0,
+        "chat": {"id": 5, "type": "private", "first_name": "test"},
+        "story": {"chat": channel, "id": 7},
+    }))?;
+    let MessageContent::Story {
+        content,
+    } = &forwarded.content
+    else {
+        panic!("not a story: {:?}", forwarded.content)
+    };
+    assert_eq!(content.id, 7);
+    assert_eq!(content.chat.get_id(), -100);
+
+    let reply: Message = serde_json::from_value(serde_json::json!({
+        "message_id": 2,
+        "date": 0,
+        "chat": {"id": 5, "type": "private", "first_name": "test"},
+        "text": "nice story",
+        "reply_to_story": {"chat": channel, "id": 7},
+    }))?;
+    assert_eq!(reply.reply_to_story.as_ref(), Some(content));
+    assert!(matches!(reply.content, MessageContent::Text { .. }));
+
+    let json = serde_json::to_value(content)?;
+    assert_eq!(serde_json::from_value::<Story>(json)?, *content);
+    Ok(())
+}
+
+#[tokio::test]
+async fn replies_can_be_sent_without_the_replied_message() -> telexide::Result<()> {
+    let api = MockAPI::new();
+    let mut dice = SendDice::new(-100, DiceEmoji::Dice);
+    dice.reply_to_message_id = Some(5);
+    dice.toggle_allow_sending_without_reply();
+    api.send_dice(dice).await?;
+
+    let game = SendGame {
+        business_connection_id: None,
+        chat_id: -100,
+        game_short_name: "snake".to_owned(),
+        disable_notification: false,
+        protect_content: false,
+        reply_to_message_id: Some(5),
+        allow_sending_without_reply: true,
+        reply_markup: None,
+    };
+    api.send_game(game).await?;
+
+    let mut invoice = SendInvoice::new_stars(-100, "title", "description", "payload", 10);
+    invoice.reply_to_message_id = Some(5);
+    invoice.toggle_allow_sending_without_reply();
+    api.send_invoice(invoice).await?;
+
+    for call in api.calls() {
+        assert_eq!(call.data["allow_sending_without_reply"], true, "{}", call.method);
+        assert!(ApiSchema::bundled()
+            .check_value(&call.method, &call.data)
+            .is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn all_update_types_are_sent_by_name() -> telexide::Result<()> {
+    let mut poll = GetUpdates::new();
+    poll.set_allowed_updates(UpdateType::all());
+    let data = serde_json::to_value(&poll)?;
+
+    let allowed = data["allowed_updates"].as_array().expect("a list of types");
+    assert_eq!(allowed.len(), 23);
+    for kind in [
+        "chat_member",
+        "message_reaction_count",
+        "business_message",
+        "chat_boost",
+    ] {
+        assert!(
+            allowed.contains(&serde_json::json!(kind)),
+            "{} is missing",
+            kind
+        );
+    }
+    assert!(ApiSchema::bundled()
+        .check_value("getUpdates", &data)
+        .is_empty());
+
+    let parsed: Vec<UpdateType> = serde_json::from_value(data["allowed_updates"].clone())?;
+    assert_eq!(parsed, UpdateType::all());
+    Ok(())
+}
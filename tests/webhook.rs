@@ -1,11 +1,20 @@
-use hyper;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use hyper::{self, client::HttpConnector, StatusCode};
+use parking_lot::Mutex;
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use telexide::{
-    client::{Webhook, WebhookOptions},
+    client::{ClientBuilder, Webhook, WebhookOptions},
     model::{Update, UpdateContent},
+    test::MockAPI,
     Result,
 };
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{self, Receiver};
 
 static ATOMIC: AtomicUsize = AtomicUsize::new(0);
 
@@ -43,3 +52,83 @@ async fn webhook_gets_called() -> Result<()> {
     assert_eq!(ATOMIC.load(Ordering::Relaxed), 10);
     Ok(())
 }
+
+async fn post_update(
+    client: &hyper::Client<HttpConnector>,
+    addr: SocketAddr,
+    update_id: i64,
+) -> Result<StatusCode> {
+    let req = hyper::Request::post(format!("http://{addr}/"))
+        .header("content-type", "application/json")
+        .body(hyper::Body::from(serde_json::to_string(&Update {
+            update_id,
+            content: UpdateContent::Unknown,
+        })?))?;
+    Ok(client.request(req).await?.status())
+}
+
+#[tokio::test]
+async fn webhook_waits_for_pre_commit_hook() -> Result<()> {
+    let committed = Arc::new(Mutex::new(Vec::new()));
+    let (delivered, mut deliveries) = mpsc::unbounded_channel();
+
+    let mut webhook_opts = WebhookOptions::new();
+    webhook_opts.set_port(0).set_pre_commit_hook({
+        let committed = committed.clone();
+        move |u: Update| {
+            let committed = committed.clone();
+            async move {
+                if u.update_id == 13 {
+                    return Err(
+                        std::io::Error::new(std::io::ErrorKind::Other, "storage down").into(),
+                    );
+                }
+                committed.lock().push(u.update_id);
+                Ok(())
+            }
+        }
+    });
+    let mut client = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(MockAPI::new())))
+        .set_webhook(&webhook_opts)
+        .build();
+    client.subscribe_handler_func(move |_, u| {
+        delivered.send(u.update_id).ok();
+        Box::pin(async {})
+    });
+
+    let handle = client.shutdown_handle();
+    let post = async {
+        let http = hyper::Client::new();
+        let addr = client.webhook_addr().await;
+
+        assert_eq!(post_update(&http, addr, 12).await?, StatusCode::OK);
+        assert_eq!(*committed.lock(), vec![12]);
+
+        assert_eq!(
+            post_update(&http, addr, 13).await?,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(*committed.lock(), vec![12]);
+
+        assert_eq!(post_update(&http, addr, 14).await?, StatusCode::OK);
+        let mut seen = vec![
+            deliveries.recv().await.expect("update 12 is delivered"),
+            deliveries.recv().await.expect("update 14 is delivered"),
+        ];
+        seen.sort_unstable();
+        assert_eq!(seen, vec![12, 14]);
+
+        handle.stop();
+        Result::Ok(())
+    };
+    let (res, posted) = tokio::time::timeout(Duration::from_secs(5), async {
+        tokio::join!(client.start(), post)
+    })
+    .await
+    .expect("the client stopped");
+    res?;
+    posted?;
+    assert_eq!(*committed.lock(), vec![12, 14]);
+    Ok(())
+}